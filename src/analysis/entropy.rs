@@ -0,0 +1,108 @@
+//! How the bytes at each offset of a set of packets vary, which sets apart constant headers,
+//! counters, and encrypted or compressed regions.
+
+/// Offsets looked at; the bytes of longer packets after them are left out
+pub const MAX_OFFSETS: usize = 4096;
+
+/// The bytes of the packets at one offset
+#[derive(Clone, PartialEq, Debug)]
+pub struct OffsetStats {
+    /// packets long enough to have a byte at the offset
+    pub packets: usize,
+    /// Shannon entropy of the bytes in bits, from 0 when they are all the same to 8
+    pub entropy: f64,
+    pub distinct: usize,
+    /// the most common byte, with the number of packets having it
+    pub most_common: (u8, usize),
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl OffsetStats {
+    /// Entropy as a share of the most the number of packets allows, from 0 to 1; near 1 for
+    /// counters and random bytes
+    pub fn spread(&self) -> f64 {
+        let max = (self.packets.min(256) as f64).log2();
+        if max > 0.0 { self.entropy / max } else { 0.0 }
+    }
+}
+
+/// Statistics of the bytes at each offset of the packets, up to the end of the longest packet
+/// or `MAX_OFFSETS`
+pub fn offset_stats(packets: &[&[u8]]) -> Vec<OffsetStats> {
+    let len = packets.iter().map(|p| p.len()).max().unwrap_or(0);
+    (0..len.min(MAX_OFFSETS))
+        .map(|offset| {
+            let mut counts = [0usize; 256];
+            for packet in packets {
+                if let Some(&b) = packet.get(offset) {
+                    counts[b as usize] += 1;
+                }
+            }
+            let n: usize = counts.iter().sum();
+            let total = n as f64;
+            let entropy = counts
+                .iter()
+                .filter(|&&c| c > 0)
+                .map(|&c| {
+                    let p = c as f64 / total;
+                    -p * p.log2()
+                })
+                .sum::<f64>()
+                .max(0.0);
+            let mean = (0..256).map(|b| (b * counts[b]) as f64).sum::<f64>() / total;
+            let variance = (0..256)
+                .map(|b| counts[b] as f64 * (b as f64 - mean).powi(2))
+                .sum::<f64>()
+                / total;
+            let most_common = (0..=255u8)
+                .map(|b| (b, counts[b as usize]))
+                .max_by_key(|(b, c)| (*c, std::cmp::Reverse(*b)))
+                .unwrap_or((0, 0));
+            OffsetStats {
+                packets: n,
+                entropy,
+                distinct: counts.iter().filter(|&&c| c > 0).count(),
+                most_common,
+                mean,
+                variance,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_stats() {
+        let packets: Vec<Vec<u8>> = (0..16u8)
+            .map(|i| {
+                let mut packet = vec![0xaa, i, i % 2 * 2];
+                if i < 4 {
+                    packet.push(7);
+                }
+                packet
+            })
+            .collect();
+        let packets: Vec<&[u8]> = packets.iter().map(Vec::as_slice).collect();
+        let stats = offset_stats(&packets);
+        assert_eq!(stats.len(), 4);
+
+        assert_eq!(stats[0].entropy, 0.0);
+        assert_eq!(stats[0].most_common, (0xaa, 16));
+        assert_eq!(stats[0].spread(), 0.0);
+
+        assert_eq!(stats[1].entropy, 4.0);
+        assert_eq!(stats[1].distinct, 16);
+        assert_eq!(stats[1].spread(), 1.0);
+
+        assert_eq!(stats[2].entropy, 1.0);
+        assert_eq!(stats[2].most_common, (0, 8));
+        assert_eq!((stats[2].mean, stats[2].variance), (1.0, 1.0));
+
+        assert_eq!(stats[3].packets, 4);
+        assert_eq!(stats[3].spread(), 0.0);
+    }
+}
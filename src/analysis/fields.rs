@@ -0,0 +1,341 @@
+//! Guessing the fields of an unknown protocol from sample packets of one type: constant
+//! regions, counters, lengths and a checksum at the end, as a skeleton protocol to refine.
+
+use crate::capture::read_uint;
+use crate::codec::checksum::CHECKSUMS;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::Endianness;
+
+/// Share of the steps from packet to packet a counter must go up by one in
+const COUNTER_STEPS: f64 = 0.8;
+/// Widest constant field; longer constant regions are split
+const MAX_CONSTANT_BYTES: usize = 8;
+/// Furthest from the start of the packet a checksum is looked for to start
+const MAX_CHECKSUM_START: usize = 4;
+/// Packets looked at; later ones are left out
+pub const MAX_PACKETS: usize = 10_000;
+
+/// What a field of the sample packets looks like
+#[derive(Clone, PartialEq, Debug)]
+pub enum FieldGuess {
+    /// the same value in every packet
+    Constant(i128),
+    /// goes up by one from packet to packet, mostly
+    Counter,
+    /// the length of the packet in bytes less `excluded`
+    Length { excluded: i64 },
+    /// a checksum of the bytes from `start` up to the field
+    Checksum {
+        algorithm: &'static str,
+        start: usize,
+    },
+    /// values with no pattern found
+    Varying,
+}
+
+/// A field found in the sample packets
+#[derive(Clone, PartialEq, Debug)]
+pub struct SuggestedField {
+    /// offset in bytes; a checksum ends every packet, and this is its offset in the shortest
+    pub offset: usize,
+    /// length in bytes, `None` for the rest of variable length packets
+    pub len: Option<usize>,
+    pub byte_order: Endianness,
+    pub guess: FieldGuess,
+}
+
+impl SuggestedField {
+    /// What the field is guessed to be, for display
+    pub fn label(&self) -> String {
+        match &self.guess {
+            FieldGuess::Constant(value) => format!("Constant 0x{:X}", value),
+            FieldGuess::Counter => "Counter".to_string(),
+            FieldGuess::Length { excluded: 0 } => "Length of the packet".to_string(),
+            FieldGuess::Length { excluded } => {
+                format!("Length of the packet less {} bytes", excluded)
+            }
+            FieldGuess::Checksum { algorithm, start } => {
+                format!("{} of the bytes from byte {}", algorithm, start)
+            }
+            FieldGuess::Varying if self.len.is_none() => "Payload".to_string(),
+            FieldGuess::Varying => "Varying".to_string(),
+        }
+    }
+}
+
+/// Guess the fields of packets of one type, in order: a checksum of the bytes before it at the
+/// end of every packet, and before it, from the start, lengths of the packet, counters and
+/// constant bytes, with the bytes between them as varying fields. When the packets differ in
+/// length, the varying bytes after the last field found are a variable length payload. Only the
+/// first packets are looked at when there are many.
+pub fn suggest_fields(packets: &[&[u8]]) -> Result<Vec<SuggestedField>, String> {
+    if packets.len() < 2 {
+        return Err("At least two packets are needed to tell what varies".to_string());
+    }
+    let packets = &packets[..packets.len().min(MAX_PACKETS)];
+    let shortest = packets.iter().map(|p| p.len()).min().unwrap_or(0);
+    let variable = packets.iter().any(|p| p.len() != shortest);
+    let checksum = tail_checksum(packets, shortest);
+    let head = shortest - checksum.as_ref().and_then(|c| c.len).unwrap_or(0);
+
+    let mut fields = Vec::new();
+    let mut offset = 0;
+    while offset < head {
+        if let Some(field) = window_field(packets, head, offset, variable) {
+            offset += field.len.unwrap_or(1);
+            fields.push(field);
+            continue;
+        }
+        let constant = |i: usize| {
+            i < head
+                && packets.iter().all(|p| p[i] == packets[0][i])
+                && window_field(packets, head, i, variable).is_none()
+        };
+        let len = if constant(offset) {
+            (offset..head)
+                .take_while(|&i| constant(i))
+                .take(MAX_CONSTANT_BYTES)
+                .count()
+        } else {
+            (offset..head)
+                .take_while(|&i| {
+                    !constant(i)
+                        && (i == offset || window_field(packets, head, i, variable).is_none())
+                })
+                .count()
+        };
+        let guess = if constant(offset) {
+            let bytes = &packets[0][offset..offset + len];
+            FieldGuess::Constant(read_uint(bytes, Endianness::Big) as i128)
+        } else {
+            FieldGuess::Varying
+        };
+        fields.push(SuggestedField {
+            offset,
+            len: Some(len),
+            byte_order: Endianness::Big,
+            guess,
+        });
+        offset += len;
+    }
+
+    if variable {
+        while fields
+            .last()
+            .is_some_and(|f| f.guess == FieldGuess::Varying)
+        {
+            fields.pop();
+        }
+        let offset = fields.last().map_or(0, |f| f.offset + f.len.unwrap_or(0));
+        fields.push(SuggestedField {
+            offset,
+            len: None,
+            byte_order: Endianness::Big,
+            guess: FieldGuess::Varying,
+        });
+    }
+    fields.extend(checksum);
+    Ok(fields)
+}
+
+/// A length or counter of 1, 2 or 4 bytes starting at `offset`, the widest found first
+fn window_field(
+    packets: &[&[u8]],
+    head: usize,
+    offset: usize,
+    variable: bool,
+) -> Option<SuggestedField> {
+    for width in [4, 2, 1] {
+        if offset + width > head {
+            continue;
+        }
+        for byte_order in [Endianness::Big, Endianness::Little] {
+            if width == 1 && byte_order == Endianness::Little {
+                continue;
+            }
+            let values: Vec<u64> = packets
+                .iter()
+                .map(|p| read_uint(&p[offset..offset + width], byte_order))
+                .collect();
+            let field = |guess| SuggestedField {
+                offset,
+                len: Some(width),
+                byte_order,
+                guess,
+            };
+            if variable && width <= 2 {
+                let excluded = packets[0].len() as i64 - values[0] as i64;
+                if packets
+                    .iter()
+                    .zip(&values)
+                    .all(|(p, v)| p.len() as i64 - *v as i64 == excluded)
+                {
+                    return Some(field(FieldGuess::Length { excluded }));
+                }
+            }
+            if is_counter(packets, offset, width, byte_order, &values) {
+                return Some(field(FieldGuess::Counter));
+            }
+        }
+    }
+    None
+}
+
+/// Whether the values go up by one from packet to packet often enough, and the bytes of the
+/// window other than the least significant one vary or are zero, so that the window is not a
+/// counter with a constant byte next to it
+fn is_counter(
+    packets: &[&[u8]],
+    offset: usize,
+    width: usize,
+    byte_order: Endianness,
+    values: &[u64],
+) -> bool {
+    if packets.len() < 3 {
+        return false;
+    }
+    let lsb = match byte_order {
+        Endianness::Big => offset + width - 1,
+        Endianness::Little => offset,
+    };
+    let varies = |i: usize| packets.iter().any(|p| p[i] != packets[0][i]);
+    if !varies(lsb) || (offset..offset + width).any(|i| !varies(i) && packets[0][i] != 0) {
+        return false;
+    }
+    let mask = u64::MAX >> (64 - 8 * width);
+    let steps = values
+        .windows(2)
+        .filter(|w| w[1] == w[0].wrapping_add(1) & mask)
+        .count();
+    steps as f64 >= COUNTER_STEPS * (values.len() - 1) as f64
+}
+
+/// A checksum in the last bytes of every packet of the bytes before them from the same offset,
+/// the first of `CHECKSUMS` that matches
+fn tail_checksum(packets: &[&[u8]], shortest: usize) -> Option<SuggestedField> {
+    for (name, checksum) in CHECKSUMS {
+        let width = checksum.width() as usize;
+        if !width.is_multiple_of(8) || width / 8 >= shortest {
+            continue;
+        }
+        let width = width / 8;
+        for start in 0..=MAX_CHECKSUM_START.min(shortest - width - 1) {
+            for byte_order in [Endianness::Big, Endianness::Little] {
+                if width == 1 && byte_order == Endianness::Little {
+                    continue;
+                }
+                let matches = packets.iter().all(|p| {
+                    let end = p.len() - width;
+                    read_uint(&p[end..], byte_order) == checksum.compute(&p[start..end])
+                });
+                if matches {
+                    return Some(SuggestedField {
+                        offset: shortest - width,
+                        len: Some(width),
+                        byte_order,
+                        guess: FieldGuess::Checksum {
+                            algorithm: name,
+                            start,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Fields of a protocol made from the suggested fields, in a protocol of big-endian byte order,
+/// described with what they were guessed to be
+pub fn skeleton_fields(fields: &[SuggestedField]) -> Vec<FieldRule> {
+    let mut rules: Vec<FieldRule> = Vec::new();
+    for field in fields {
+        let base = match field.guess {
+            FieldGuess::Constant(_) if field.offset == 0 => "sync".to_string(),
+            FieldGuess::Constant(_) => format!("constant_{}", field.offset),
+            FieldGuess::Counter => "counter".to_string(),
+            FieldGuess::Length { .. } => "length".to_string(),
+            FieldGuess::Checksum { .. } => "checksum".to_string(),
+            FieldGuess::Varying if field.len.is_none() => "payload".to_string(),
+            FieldGuess::Varying => format!("field_{}", field.offset),
+        };
+        let mut id = base.clone();
+        let mut n = 2;
+        while rules.iter().any(|r| r.id == id) {
+            id = format!("{}_{}", base, n);
+            n += 1;
+        }
+        let field_type = match field.guess {
+            FieldGuess::Constant(value) => FieldType::Fixed(value),
+            _ => FieldType::Input,
+        };
+        let length = match field.len {
+            Some(len) => FieldLength::Fixed(len as u32 * 8),
+            None => FieldLength::Variable,
+        };
+        let mut rule = FieldRule::new(&id, field_type, length);
+        rule.description = Some(format!("Suggested from sample packets: {}", field.label()));
+        if field.byte_order == Endianness::Little && field.len.is_some_and(|len| len > 1) {
+            rule.endianness = Some(Endianness::Little);
+        }
+        rules.push(rule);
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::checksum::checksum_by_name;
+
+    #[test]
+    fn test_suggest_fields() {
+        let crc = checksum_by_name("CRC-16/MODBUS").unwrap();
+        let packets: Vec<Vec<u8>> = (0..20u16)
+            .map(|seq| {
+                let payload_len = 2 + seq as usize % 3;
+                let mut packet = vec![0xaa, 0x55];
+                packet.extend((250 + seq).to_be_bytes());
+                packet.push(6 + payload_len as u8);
+                packet.extend((0..payload_len).map(|i| (seq as u8).wrapping_mul(31) ^ i as u8));
+                let crc = crc.compute(&packet[2..]) as u16;
+                packet.extend(crc.to_le_bytes());
+                packet
+            })
+            .collect();
+        let packets: Vec<&[u8]> = packets.iter().map(Vec::as_slice).collect();
+
+        let fields = suggest_fields(&packets).unwrap();
+        let summary: Vec<(usize, Option<usize>, FieldGuess)> = fields
+            .iter()
+            .map(|f| (f.offset, f.len, f.guess.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, Some(2), FieldGuess::Constant(0xaa55)),
+                (2, Some(2), FieldGuess::Counter),
+                (4, Some(1), FieldGuess::Length { excluded: 1 }),
+                (5, None, FieldGuess::Varying),
+                (
+                    7,
+                    Some(2),
+                    FieldGuess::Checksum {
+                        algorithm: "CRC-16/MODBUS",
+                        start: 2
+                    }
+                ),
+            ]
+        );
+        assert_eq!(fields[4].byte_order, Endianness::Little);
+
+        let rules = skeleton_fields(&fields);
+        let ids: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["sync", "counter", "length", "payload", "checksum"]);
+        assert_eq!(rules[0].field_type, FieldType::Fixed(0xaa55));
+        assert_eq!(rules[3].length, FieldLength::Variable);
+        assert_eq!(rules[4].endianness, Some(Endianness::Little));
+
+        assert!(suggest_fields(&packets[..1]).is_err());
+    }
+}
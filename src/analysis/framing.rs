@@ -0,0 +1,307 @@
+//! Guessing how a raw binary log of unknown format is split into records, from how often each
+//! byte occurs and how alike the records each candidate framing splits it into are.
+
+use crate::capture::{BinaryLogFormat, Framing, read_binary_log, read_uint};
+use crate::models::protocol::Endianness;
+
+/// Bytes of a log analyzed; a larger log is judged by its start
+const SAMPLE_SIZE: usize = 256 * 1024;
+/// Bytes compared with those a record size after them to find the size of fixed size records
+const REPEAT_SAMPLE_SIZE: usize = 64 * 1024;
+/// Largest record size looked for in logs of fixed size records
+const MAX_RECORD_SIZE: usize = 1024;
+/// Fixed sizes tried, of those the bytes repeat at most often
+const MAX_REPEAT_SIZES: usize = 3;
+/// Bytes at the start of the records compared between them
+const HEADER_BYTES: usize = 4;
+/// Fewest records a framing must split a log into to be suggested
+const MIN_RECORDS: usize = 4;
+const MAX_SUGGESTIONS: usize = 5;
+/// Byte values listed as the most frequent
+const MAX_FREQUENT: usize = 8;
+
+/// A framing a log might be split by
+#[derive(Clone, PartialEq, Debug)]
+pub struct FramingSuggestion {
+    pub framing: Framing,
+    /// 0 to 1, from how much more alike the first bytes of the records are than chance, and
+    /// how many records there are
+    pub confidence: f64,
+    pub records: usize,
+    pub shortest: usize,
+    pub longest: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FramingAnalysis {
+    /// bytes of the log analyzed
+    pub analyzed: usize,
+    /// the most frequent byte values, with the share of the bytes they are
+    pub frequent: Vec<(u8, f64)>,
+    /// the likeliest framing first
+    pub suggestions: Vec<FramingSuggestion>,
+}
+
+/// How often each byte value occurs
+pub fn byte_frequency(bytes: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    counts
+}
+
+/// Suggest framings for a log of records without timestamp headers: the fixed sizes its bytes
+/// repeat at, each byte frequent enough and CR LF as delimiters, and lengths of 1 or 2 bytes
+/// near the start of each record that step from record to record without overrunning the log.
+/// Each is rated by how alike the starts of the records it splits the log into are.
+pub fn analyze_framing(bytes: &[u8]) -> FramingAnalysis {
+    let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+    let frequency = byte_frequency(sample);
+    let share = |b: u8| frequency[b as usize] as f64 / sample.len().max(1) as f64;
+    let mut suggestions = Vec::new();
+
+    for size in repeat_sizes(sample) {
+        let framing = Framing::Fixed { size };
+        let whole = &sample[..sample.len() - sample.len() % size];
+        suggestions.extend(split(whole, &framing).and_then(|r| rate(framing, &r, share)));
+    }
+
+    let mut delimiters: Vec<Vec<u8>> = (0..=255u8)
+        .filter(|&b| (MIN_RECORDS..=sample.len() / 2).contains(&frequency[b as usize]))
+        .map(|b| vec![b])
+        .collect();
+    delimiters.push(b"\r\n".to_vec());
+    for delimiter in delimiters {
+        let framing = Framing::Delimiter(delimiter);
+        suggestions.extend(split(sample, &framing).and_then(|r| rate(framing, &r, share)));
+    }
+
+    for offset in 0..=8 {
+        for (width, byte_order) in [
+            (1, Endianness::Big),
+            (2, Endianness::Big),
+            (2, Endianness::Little),
+        ] {
+            for adjustment in -((offset + width) as i64)..=8 {
+                let framing = Framing::LengthPrefix {
+                    offset,
+                    width,
+                    byte_order,
+                    adjustment,
+                };
+                if let Some(records) = prefixed_records(bytes, sample.len(), &framing) {
+                    suggestions.extend(rate(framing, &records, share));
+                }
+            }
+        }
+    }
+
+    suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    suggestions.truncate(MAX_SUGGESTIONS);
+    let mut frequent: Vec<(u8, f64)> = (0..=255u8)
+        .filter(|&b| frequency[b as usize] > 0)
+        .map(|b| (b, share(b)))
+        .collect();
+    frequent.sort_by(|a, b| b.1.total_cmp(&a.1));
+    frequent.truncate(MAX_FREQUENT);
+    FramingAnalysis {
+        analyzed: sample.len(),
+        frequent,
+        suggestions,
+    }
+}
+
+/// Record sizes the bytes repeat at most often above what their frequency makes likely. A
+/// size is replaced by its smallest divisor the bytes repeat at nearly as often, as records
+/// that alternate between a few values repeat at a multiple of their size.
+fn repeat_sizes(sample: &[u8]) -> Vec<usize> {
+    let sample = &sample[..sample.len().min(REPEAT_SAMPLE_SIZE)];
+    let frequency = byte_frequency(sample);
+    let len = sample.len().max(1) as f64;
+    // chance of two bytes being equal
+    let baseline: f64 = frequency.iter().map(|&n| (n as f64 / len).powi(2)).sum();
+    let mut scores = vec![0.0; MAX_RECORD_SIZE.min(sample.len() / MIN_RECORDS) + 1];
+    for (size, score) in scores.iter_mut().enumerate().skip(2) {
+        let pairs = sample.len() - size;
+        let equal = (0..pairs)
+            .filter(|&i| sample[i] == sample[i + size])
+            .count();
+        *score = equal as f64 / pairs as f64 - baseline;
+    }
+    let mut ranked: Vec<usize> = (2..scores.len()).filter(|&s| scores[s] > 0.0).collect();
+    ranked.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+
+    let mut sizes: Vec<usize> = Vec::new();
+    for size in ranked {
+        let size = (2..=size)
+            .find(|&d| size.is_multiple_of(d) && scores[d] >= 0.9 * scores[size])
+            .unwrap_or(size);
+        if !sizes.iter().any(|s| size.is_multiple_of(*s)) {
+            sizes.push(size);
+        }
+        if sizes.len() == MAX_REPEAT_SIZES {
+            break;
+        }
+    }
+    sizes
+}
+
+/// The records a framing splits a log into, if it splits all of it
+fn split<'a>(bytes: &'a [u8], framing: &Framing) -> Option<Vec<&'a [u8]>> {
+    let format = BinaryLogFormat {
+        framing: framing.clone(),
+        timestamp: None,
+    };
+    let lengths: Vec<usize> = read_binary_log(bytes, &format)
+        .ok()?
+        .iter()
+        .map(|p| p.data.len())
+        .collect();
+    let delimiter = match framing {
+        Framing::Delimiter(delimiter) => delimiter.len(),
+        _ => 0,
+    };
+    let mut records = Vec::with_capacity(lengths.len());
+    let mut offset = 0;
+    for len in lengths {
+        records.push(&bytes[offset..offset + len]);
+        offset = (offset + len + delimiter).min(bytes.len());
+    }
+    Some(records)
+}
+
+/// The records a length prefix splits a log into up to the end of the sample of it analyzed,
+/// if none of the records overruns the log
+fn prefixed_records<'a>(log: &'a [u8], sample: usize, framing: &Framing) -> Option<Vec<&'a [u8]>> {
+    let Framing::LengthPrefix {
+        offset,
+        width,
+        byte_order,
+        adjustment,
+    } = *framing
+    else {
+        return None;
+    };
+    let mut records = Vec::new();
+    let mut at = 0;
+    while at < sample {
+        let rest = &log[at..];
+        let end = offset + width;
+        let length = read_uint(rest.get(offset..end)?, byte_order);
+        let size = usize::try_from(end as i128 + length as i128 + adjustment as i128)
+            .ok()
+            .filter(|&size| size >= end && size <= rest.len())?;
+        records.push(&rest[..size]);
+        at += size;
+    }
+    Some(records)
+}
+
+/// Rate a framing by how alike the first bytes of its records are, above what the frequency
+/// of those bytes in the log makes likely, for a framing splitting the log into enough records
+/// of more than a byte
+fn rate(
+    framing: Framing,
+    records: &[&[u8]],
+    share: impl Fn(u8) -> f64,
+) -> Option<FramingSuggestion> {
+    let shortest = records.iter().map(|r| r.len()).min()?;
+    let longest = records.iter().map(|r| r.len()).max()?;
+    if records.len() < MIN_RECORDS || longest < 2 {
+        return None;
+    }
+    let count = records.len() as f64;
+    let alike: f64 = (0..HEADER_BYTES)
+        .map(|i| {
+            let counts = byte_frequency(
+                &records
+                    .iter()
+                    .filter_map(|r| r.get(i).copied())
+                    .collect::<Vec<_>>(),
+            );
+            let (byte, n) = (0..=255u8)
+                .map(|b| (b, counts[b as usize]))
+                .max_by_key(|(_, n)| *n)
+                .unwrap_or((0, 0));
+            let chance = share(byte);
+            if chance >= 1.0 {
+                return 0.0;
+            }
+            ((n as f64 / count - chance) / (1.0 - chance)).max(0.0)
+        })
+        .sum::<f64>()
+        / HEADER_BYTES as f64;
+    let empty = records.iter().filter(|r| r.is_empty()).count() as f64 / count;
+    Some(FramingSuggestion {
+        framing,
+        confidence: alike * (1.0 - empty) * count / (count + 10.0),
+        records: records.len(),
+        shortest,
+        longest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes that look random
+    fn noise(seed: &mut u32) -> u8 {
+        *seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (*seed >> 16) as u8
+    }
+
+    #[test]
+    fn test_fixed_size() {
+        let mut seed = 1;
+        let mut log = Vec::new();
+        for i in 0..50u8 {
+            log.extend([0xaa, 0x55, i, i % 3]);
+            log.extend((0..6).map(|_| noise(&mut seed)));
+        }
+        let analysis = analyze_framing(&log);
+        let best = &analysis.suggestions[0];
+        assert_eq!(best.framing, Framing::Fixed { size: 10 });
+        assert_eq!(best.records, 50);
+    }
+
+    #[test]
+    fn test_length_prefix() {
+        let mut seed = 7;
+        let mut log = Vec::new();
+        for _ in 0..40 {
+            let len = 1 + noise(&mut seed) as usize % 6;
+            log.extend([0x7e, len as u8]);
+            log.extend((0..len).map(|_| noise(&mut seed)));
+        }
+        let best = &analyze_framing(&log).suggestions[0];
+        assert_eq!(
+            best.framing,
+            Framing::LengthPrefix {
+                offset: 1,
+                width: 1,
+                byte_order: Endianness::Big,
+                adjustment: 0,
+            }
+        );
+        assert_eq!((best.records, best.shortest, best.longest), (40, 3, 8));
+    }
+
+    #[test]
+    fn test_delimiter() {
+        let mut seed = 3;
+        let mut log = Vec::new();
+        for i in 0..30 {
+            log.extend([0x01, 0x10]);
+            log.extend((0..4 + i % 5).map(|_| noise(&mut seed) & 0x7f));
+            log.push(0xff);
+        }
+        let analysis = analyze_framing(&log);
+        assert_eq!(
+            analysis.suggestions[0].framing,
+            Framing::Delimiter(vec![0xff])
+        );
+        assert_eq!(analysis.frequent[0].0, 0x01);
+    }
+}
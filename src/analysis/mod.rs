@@ -0,0 +1,5 @@
+pub mod entropy;
+pub mod fields;
+pub mod framing;
+pub mod sequence;
+pub mod timing;
@@ -0,0 +1,265 @@
+//! Checking the sequence numbers of the packets of a capture for dropped, repeated and
+//! reordered packets, as on a lossy link.
+
+use crate::codec::Value;
+use crate::codec::decode::DecodedPacket;
+use crate::models::protocol::ProtocolRegistry;
+use std::time::Duration;
+
+/// Something off between a packet and the one before it in the same sequence
+#[derive(Clone, PartialEq, Debug)]
+pub enum SequenceEvent {
+    /// sequence numbers were skipped, `missing` packets dropped
+    Gap {
+        expected: i128,
+        found: i128,
+        missing: u128,
+    },
+    /// the same sequence number again
+    Duplicate { value: i128 },
+    /// the sequence number went past the largest value of the field and started over
+    WrapAround { from: i128, to: i128 },
+    /// the sequence number went back, from a reordered packet or a restart of the sender
+    Backwards { from: i128, to: i128 },
+}
+
+impl SequenceEvent {
+    /// What happened, for display
+    pub fn description(&self) -> String {
+        match self {
+            SequenceEvent::Gap {
+                expected,
+                found,
+                missing: 1,
+            } => format!("1 packet missing: expected {}, found {}", expected, found),
+            SequenceEvent::Gap {
+                expected,
+                found,
+                missing,
+            } => format!(
+                "{} packets missing: expected {}, found {}",
+                missing, expected, found
+            ),
+            SequenceEvent::Duplicate { value } => format!("Duplicate of {}", value),
+            SequenceEvent::WrapAround { from, to } => format!("Wrapped from {} to {}", from, to),
+            SequenceEvent::Backwards { from, to } => format!("Went back from {} to {}", from, to),
+        }
+    }
+}
+
+/// An event found at a packet
+#[derive(Clone, PartialEq, Debug)]
+pub struct SequenceIssue {
+    /// index of the packet in the packets checked
+    pub packet: usize,
+    pub timestamp: Duration,
+    /// time since the packet before it in the sequence
+    pub since_previous: Duration,
+    pub event: SequenceEvent,
+}
+
+/// The packets numbered by one sequence number field
+#[derive(Clone, PartialEq, Debug)]
+pub struct SequenceReport {
+    /// protocol declaring the sequence number field
+    pub protocol_id: String,
+    pub field_id: String,
+    /// bits of the field, which its numbers wrap around at; `None` for virtual fields
+    pub bits: Option<u32>,
+    pub packets: usize,
+    /// packets dropped in all the gaps
+    pub missing: u128,
+    pub issues: Vec<SequenceIssue>,
+}
+
+impl SequenceReport {
+    /// Issues of a kind, e.g. `|e| matches!(e, SequenceEvent::Duplicate { .. })`
+    pub fn count(&self, kind: impl Fn(&SequenceEvent) -> bool) -> usize {
+        self.issues.iter().filter(|i| kind(&i.event)).count()
+    }
+}
+
+/// Check the sequence numbers of the decoded packets of a capture, each with its time, one
+/// report for each protocol declaring a sequence number field in the order they are first
+/// seen. A number further ahead of the one before it than half the range of the field is
+/// taken as going back, and one wrapping past the end of the range as going ahead. Packets
+/// that did not decode or whose protocol has no sequence number field are left out.
+pub fn check_sequences(
+    registry: &ProtocolRegistry,
+    packets: &[(Duration, Option<&DecodedPacket>)],
+) -> Vec<SequenceReport> {
+    // each report with the time and number of the last packet in it
+    let mut reports: Vec<(SequenceReport, Option<(Duration, i128)>)> = Vec::new();
+    for (i, (timestamp, decoded)) in packets.iter().enumerate() {
+        let Some(decoded) = decoded else {
+            continue;
+        };
+        let Some((field_id, protocol_id)) = registry.sequence_field(&decoded.protocol_id) else {
+            continue;
+        };
+        let Some(field) = decoded.get(field_id) else {
+            continue;
+        };
+        let Value::Int(value) = field.value else {
+            continue;
+        };
+        let n = match reports
+            .iter()
+            .position(|(r, _)| r.protocol_id == protocol_id && r.field_id == field_id)
+        {
+            Some(n) => n,
+            None => {
+                let bits =
+                    (!field.is_virtual && field.bit_len < 128).then_some(field.bit_len as u32);
+                reports.push((
+                    SequenceReport {
+                        protocol_id: protocol_id.to_string(),
+                        field_id: field_id.to_string(),
+                        bits,
+                        packets: 0,
+                        missing: 0,
+                        issues: Vec::new(),
+                    },
+                    None,
+                ));
+                reports.len() - 1
+            }
+        };
+        let (report, last) = &mut reports[n];
+        report.packets += 1;
+        if let Some((previous_time, previous)) = *last {
+            let mut issue = |event| {
+                report.issues.push(SequenceIssue {
+                    packet: i,
+                    timestamp: *timestamp,
+                    since_previous: timestamp.saturating_sub(previous_time),
+                    event,
+                })
+            };
+            let step = match report.bits {
+                Some(bits) => {
+                    let range = 1i128 << bits;
+                    let step = (value - previous).rem_euclid(range);
+                    if step > range / 2 { step - range } else { step }
+                }
+                None => value - previous,
+            };
+            if step > 0 && value < previous {
+                issue(SequenceEvent::WrapAround {
+                    from: previous,
+                    to: value,
+                });
+            }
+            match step {
+                0 => issue(SequenceEvent::Duplicate { value }),
+                1 => {}
+                step if step > 1 => {
+                    let expected = match report.bits {
+                        Some(bits) => (previous + 1).rem_euclid(1i128 << bits),
+                        None => previous + 1,
+                    };
+                    report.missing += step as u128 - 1;
+                    issue(SequenceEvent::Gap {
+                        expected,
+                        found: value,
+                        missing: step as u128 - 1,
+                    });
+                }
+                _ => issue(SequenceEvent::Backwards {
+                    from: previous,
+                    to: value,
+                }),
+            }
+        }
+        *last = Some((*timestamp, value));
+    }
+    reports.into_iter().map(|(report, _)| report).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode::DecodedField;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_check_sequences() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("telemetry", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("telemetry", |p| {
+                p.add_field(FieldRule::new(
+                    "seq",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.sequence_field = Some("seq".to_string());
+                Ok(())
+            })
+            .unwrap();
+        let packet = |seq| DecodedPacket {
+            protocol_id: "telemetry".to_string(),
+            fields: vec![DecodedField {
+                rule_id: "seq".to_string(),
+                protocol_id: "telemetry".to_string(),
+                bit_offset: 0,
+                bit_len: 8,
+                value: Value::Int(seq),
+                is_virtual: false,
+            }],
+            issues: Vec::new(),
+        };
+        let decoded: Vec<DecodedPacket> = [250, 251, 254, 254, 255, 1, 200, 201]
+            .into_iter()
+            .map(packet)
+            .collect();
+        let mut packets: Vec<(Duration, Option<&DecodedPacket>)> = decoded
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (Duration::from_millis(10 * i as u64), Some(p)))
+            .collect();
+        packets.insert(1, (Duration::from_millis(5), None));
+
+        let reports = check_sequences(&registry, &packets);
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(
+            (report.bits, report.packets, report.missing),
+            (Some(8), 8, 3)
+        );
+        let events: Vec<(usize, SequenceEvent)> = report
+            .issues
+            .iter()
+            .map(|i| (i.packet, i.event.clone()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (
+                    3,
+                    SequenceEvent::Gap {
+                        expected: 252,
+                        found: 254,
+                        missing: 2
+                    }
+                ),
+                (4, SequenceEvent::Duplicate { value: 254 }),
+                (6, SequenceEvent::WrapAround { from: 255, to: 1 }),
+                (
+                    6,
+                    SequenceEvent::Gap {
+                        expected: 0,
+                        found: 1,
+                        missing: 1
+                    }
+                ),
+                (7, SequenceEvent::Backwards { from: 1, to: 200 }),
+            ]
+        );
+        assert_eq!(report.issues[0].timestamp, Duration::from_millis(20));
+        assert_eq!(report.issues[0].since_previous, Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,128 @@
+//! Time between the packets of each protocol in a capture, for checking that periodic
+//! telemetry keeps to its rate.
+
+use crate::codec::decode::DecodedPacket;
+use std::time::Duration;
+
+/// Times between the packets of one protocol, in seconds
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimingStats {
+    pub protocol_id: String,
+    pub packets: usize,
+    /// from each packet of the protocol to the next, in order of time
+    pub intervals: Vec<f64>,
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+    /// standard deviation of the intervals
+    pub jitter: f64,
+}
+
+impl TimingStats {
+    /// Number of intervals in each of `bins` bins of equal width from `min` to `max`
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins.max(1)];
+        let width = (self.max - self.min) / counts.len() as f64;
+        for interval in &self.intervals {
+            let bin = if width > 0.0 {
+                ((interval - self.min) / width) as usize
+            } else {
+                0
+            };
+            let last = counts.len() - 1;
+            counts[bin.min(last)] += 1;
+        }
+        counts
+    }
+
+    /// Intervals further than `tolerance` from `period`, both in seconds
+    pub fn outside(&self, period: f64, tolerance: f64) -> usize {
+        self.intervals
+            .iter()
+            .filter(|i| (**i - period).abs() > tolerance)
+            .count()
+    }
+}
+
+/// Times between the decoded packets of a capture, each with its time, by the protocol they
+/// decoded as, so that each subprotocol has its own. Protocols are in the order they are first
+/// seen; those with fewer than two packets and packets that did not decode are left out.
+pub fn inter_arrival(packets: &[(Duration, Option<&DecodedPacket>)]) -> Vec<TimingStats> {
+    let mut times: Vec<(&str, Vec<Duration>)> = Vec::new();
+    for (timestamp, decoded) in packets {
+        let Some(decoded) = decoded else {
+            continue;
+        };
+        match times.iter_mut().find(|(id, _)| *id == decoded.protocol_id) {
+            Some((_, t)) => t.push(*timestamp),
+            None => times.push((&decoded.protocol_id, vec![*timestamp])),
+        }
+    }
+    times
+        .into_iter()
+        .filter(|(_, t)| t.len() > 1)
+        .map(|(protocol_id, mut t)| {
+            t.sort();
+            let intervals: Vec<f64> = t.windows(2).map(|w| (w[1] - w[0]).as_secs_f64()).collect();
+            let n = intervals.len() as f64;
+            let mean = intervals.iter().sum::<f64>() / n;
+            let variance = intervals.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / n;
+            TimingStats {
+                protocol_id: protocol_id.to_string(),
+                packets: t.len(),
+                min: intervals.iter().copied().fold(f64::INFINITY, f64::min),
+                max: intervals.iter().copied().fold(0.0, f64::max),
+                mean,
+                jitter: variance.sqrt(),
+                intervals,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inter_arrival() {
+        let packet = |id: &str| DecodedPacket {
+            protocol_id: id.to_string(),
+            fields: Vec::new(),
+            issues: Vec::new(),
+        };
+        let (status, position, event) = (packet("status"), packet("position"), packet("event"));
+        let ms = Duration::from_millis;
+        let packets = [
+            (ms(0), Some(&status)),
+            (ms(5), Some(&position)),
+            (ms(100), Some(&status)),
+            (ms(105), Some(&position)),
+            (ms(110), None),
+            (ms(120), Some(&event)),
+            (ms(230), Some(&status)),
+            (ms(205), Some(&position)),
+            (ms(300), Some(&status)),
+        ];
+
+        let stats = inter_arrival(&packets);
+        let ids: Vec<&str> = stats.iter().map(|s| s.protocol_id.as_str()).collect();
+        assert_eq!(ids, ["status", "position"]);
+
+        let status = &stats[0];
+        assert_eq!(status.packets, 4);
+        assert!((status.min - 0.07).abs() < 1e-9);
+        assert!((status.mean - 0.1).abs() < 1e-9);
+        assert!((status.max - 0.13).abs() < 1e-9);
+        assert!((status.jitter - 0.0245).abs() < 1e-4);
+        assert_eq!(status.histogram(3), vec![1, 1, 1]);
+        assert_eq!(status.outside(0.1, 0.02), 2);
+
+        let position = &stats[1];
+        assert_eq!(
+            (position.min, position.max, position.jitter),
+            (0.1, 0.1, 0.0)
+        );
+        assert_eq!(position.histogram(4), vec![2, 0, 0, 0]);
+    }
+}
@@ -1,33 +1,440 @@
+use crate::ui::bit_calculator::BitCalculator;
+use crate::ui::capture::CaptureState;
+use crate::ui::checksum_calculator::ChecksumCalculator;
+use crate::ui::codegen_dialog::CodegenDialog;
+use crate::ui::coverage::CoverageState;
+use crate::ui::crash::{self, CrashReport};
+use crate::ui::export_dialog::PendingExport;
+use crate::ui::field_editor::FieldEditor;
+use crate::ui::hex_view::{OrderPreview, PacketTabs};
+use crate::ui::import_dialog::PendingImport;
+use crate::ui::open_dialog;
+use crate::ui::packet_builder::BuilderState;
+use crate::ui::protocol_designer::LayoutView;
+use crate::ui::replay::{ReplaySettings, RunningReplay};
+use crate::ui::scheduler::{RunningSchedule, Schedule};
+use crate::ui::scrub_dialog::ScrubDialog;
+use crate::ui::simulator::{RunningSimulator, SimulatorSettings};
+use crate::ui::skeleton_dialog::SkeletonDialog;
+use crate::ui::theme::{self, Appearance};
+use bitloom::analysis::entropy::OffsetStats;
+use bitloom::codec::compiled::CompiledCodec;
+use bitloom::codec::decode::{DecodeFailure, DecodedPacket, decode_partial};
+use bitloom::i18n::Localize;
+use bitloom::models::bookmark::{AnnotatedPacket, Bookmark};
+use bitloom::models::field::{DisplayFormat, FieldRule};
+use bitloom::models::history::RevisionHistory;
+use bitloom::models::integrity::IntegrityIssue;
+use bitloom::models::preset::PacketPreset;
+use bitloom::models::project::{BitLoomProject, PROJECT_VERSION};
+use bitloom::models::protocol::ProtocolRegistry;
+use bitloom::models::trash::{Deletion, Trash};
+use bitloom::replay::ReplayEvent;
+use bitloom::scheduler::TransmitEvent;
+use bitloom::script::console::{Console, ConsoleOutput};
+use bitloom::script::plugins::{PLUGIN_DIR, Plugin, load_plugins};
+use bitloom::script::{ScriptEngine, ScriptError};
+use bitloom::server::{ApiServer, LoggedRequest};
+use bitloom::simulator::SimulatorEvent;
+use bitloom::tr;
 use eframe::egui;
+use egui_commonmark::CommonMarkCache;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 #[derive(PartialEq)]
 pub enum ViewPage {
     ProtocolDesigner,
     PacketBuilder,
+    Capture,
+    Scripts,
+    Console,
 }
 
 pub struct BitLoomApp {
     pub current_page: ViewPage,
+    pub registry: ProtocolRegistry,
+    pub history: RevisionHistory,
+    pub selected_protocol: Option<String>,
+    pub selected_field: Option<String>,
+    /// fields of the selected protocol selected together in the field table
+    pub selected_fields: HashSet<String>,
+    /// zoom and position of the layout diagram of the protocol designer
+    pub layout_view: LayoutView,
+    pub show_where_used: bool,
+    pub show_coverage: bool,
+    pub coverage: CoverageState,
+    pub show_compare: bool,
+    /// the (old, new) protocol IDs selected in the compare window
+    pub compare_ids: (Option<String>, Option<String>),
+    pub show_history: bool,
+    pub show_simulator: bool,
+    pub simulator: SimulatorSettings,
+    pub running_simulator: Option<RunningSimulator>,
+    pub simulator_log: Vec<SimulatorEvent>,
+    pub show_replay: bool,
+    pub replay: ReplaySettings,
+    pub running_replay: Option<RunningReplay>,
+    pub replay_log: Vec<ReplayEvent>,
+    pub show_scheduler: bool,
+    pub schedule: Schedule,
+    pub running_schedule: Option<RunningSchedule>,
+    pub transmit_log: Vec<TransmitEvent>,
+    pub show_api_server: bool,
+    pub show_appearance: bool,
+    pub show_trash: bool,
+    pub show_bit_accounting: bool,
+    pub show_bit_calculator: bool,
+    pub bit_calculator: BitCalculator,
+    pub show_checksum_calculator: bool,
+    pub checksum_calculator: ChecksumCalculator,
+    pub appearance: Appearance,
+    pub api_server_address: String,
+    pub api_server: Option<ApiServer>,
+    pub api_server_log: Vec<LoggedRequest>,
+    pub builder: BuilderState,
+    /// named packets saved in the project
+    pub presets: Vec<PacketPreset>,
+    pub capture: CaptureState,
+    /// whether the hex view and inspector are in windows of their own
+    pub hex_view_detached: bool,
+    pub inspector_detached: bool,
+    /// raw bytes of the packet shown in the hex view
+    pub packet_data: Vec<u8>,
+    /// the packets open in the tabs of the hex view
+    pub packet_tabs: PacketTabs,
+    /// the packet currently shown in the inspector
+    pub decoded: Option<DecodedPacket>,
+    /// why `packet_data` could not be decoded as the selected protocol, and how far it got
+    pub decode_error: Option<DecodeFailure>,
+    /// hex dump text being pasted into the hex view
+    pub hex_dump_input: String,
+    /// byte of the packet under the pointer in the hex view
+    pub hex_cursor: Option<usize>,
+    /// bytes selected in the hex view, as the byte the selection was started at and the one
+    /// it was dragged to
+    pub hex_selection: Option<(usize, usize)>,
+    /// byte the hex view scrolls to on the next frame
+    pub hex_scroll_to: Option<usize>,
+    /// how the bytes at each offset of the captured packets vary, shown over the hex view
+    pub offset_stats: Option<Vec<OffsetStats>>,
+    /// the byte or bit order the packet is decoded in instead of the protocol's
+    pub order_preview: OrderPreview,
+    /// packets with bookmarked ranges, saved in the project
+    pub bookmarks: Vec<AnnotatedPacket>,
+    pub show_bookmarks: bool,
+    /// bookmark being named before it is added to the packet in the hex view
+    pub pending_bookmark: Option<Bookmark>,
+    pub field_editor: Option<FieldEditor>,
+    /// fields copied from the field table, to be pasted into a protocol
+    pub field_clipboard: Vec<FieldRule>,
+    pub pending_export: Option<PendingExport>,
+    pub pending_import: Option<PendingImport>,
+    /// path of the project file being chosen to open
+    pub pending_open: Option<String>,
+    /// broken references between protocols being repaired, shown until closed
+    pub integrity_issues: Option<Vec<IntegrityIssue>>,
+    /// protocol deletion waiting for the user to confirm it
+    pub pending_delete: Option<Deletion>,
+    /// protocols deleted this session, for undo and the Recently Deleted window
+    pub trash: Trash,
+    pub codegen_dialog: Option<CodegenDialog>,
+    pub scrub_dialog: Option<ScrubDialog>,
+    pub skeleton_dialog: Option<SkeletonDialog>,
+    pub script_engine: ScriptEngine,
+    /// source of the project script library as being edited
+    pub script_library: String,
+    /// error from the last attempt to save the script library
+    pub script_library_error: Option<ScriptError>,
+    pub console: Console,
+    /// command being typed into the console
+    pub console_input: String,
+    /// commands run in the console so far, with what they produced
+    pub console_log: Vec<(String, ConsoleOutput)>,
+    pub plugins: Vec<Plugin>,
+    /// plugin formatter chosen for a field ID, as (plugin index, formatter index)
+    pub field_formatters: HashMap<String, (usize, usize)>,
+    /// display format chosen for a field ID in the inspector, over that of the field
+    pub display_overrides: HashMap<String, DisplayFormat>,
+    pub markdown_cache: CommonMarkCache,
+    /// error message shown in a dialog until dismissed
+    pub error: Option<String>,
+    /// the crash of the last run, shown until dismissed
+    pub crash: Option<CrashReport>,
 }
 
 impl BitLoomApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
-        // Restore app state using cc.storage (requires the "persistence" feature).
-        // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
-        // for e.g. egui::PaintCallback.
-        Self {
+    /// The app with the project at `project_path` open, if given
+    pub fn new(cc: &eframe::CreationContext<'_>, project_path: Option<String>) -> Self {
+        let appearance: Appearance = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, theme::STORAGE_KEY))
+            .unwrap_or_default();
+        appearance.apply(&cc.egui_ctx);
+        let (plugins, plugin_errors) = load_plugins(Path::new(PLUGIN_DIR));
+        let mut app = Self {
             current_page: ViewPage::ProtocolDesigner,
+            registry: ProtocolRegistry::new(),
+            history: RevisionHistory::new(),
+            selected_protocol: None,
+            selected_field: None,
+            selected_fields: HashSet::new(),
+            layout_view: LayoutView::default(),
+            show_where_used: false,
+            show_coverage: false,
+            coverage: CoverageState::default(),
+            show_compare: false,
+            compare_ids: (None, None),
+            show_history: false,
+            show_simulator: false,
+            simulator: SimulatorSettings::default(),
+            running_simulator: None,
+            simulator_log: Vec::new(),
+            show_replay: false,
+            replay: ReplaySettings::default(),
+            running_replay: None,
+            replay_log: Vec::new(),
+            show_scheduler: false,
+            schedule: Schedule::new(),
+            running_schedule: None,
+            transmit_log: Vec::new(),
+            show_api_server: false,
+            show_appearance: false,
+            show_trash: false,
+            show_bit_accounting: false,
+            show_bit_calculator: false,
+            bit_calculator: BitCalculator::default(),
+            show_checksum_calculator: false,
+            checksum_calculator: ChecksumCalculator::default(),
+            appearance,
+            api_server_address: "127.0.0.1:8710".to_string(),
+            api_server: None,
+            api_server_log: Vec::new(),
+            builder: BuilderState::default(),
+            presets: Vec::new(),
+            capture: CaptureState::default(),
+            hex_view_detached: false,
+            inspector_detached: false,
+            packet_data: Vec::new(),
+            packet_tabs: PacketTabs::default(),
+            decoded: None,
+            decode_error: None,
+            hex_dump_input: String::new(),
+            hex_cursor: None,
+            hex_selection: None,
+            hex_scroll_to: None,
+            offset_stats: None,
+            order_preview: OrderPreview::default(),
+            bookmarks: Vec::new(),
+            show_bookmarks: false,
+            pending_bookmark: None,
+            field_editor: None,
+            field_clipboard: Vec::new(),
+            pending_export: None,
+            pending_import: None,
+            pending_open: None,
+            integrity_issues: None,
+            pending_delete: None,
+            trash: Trash::new(),
+            codegen_dialog: None,
+            scrub_dialog: None,
+            skeleton_dialog: None,
+            script_engine: ScriptEngine::new(),
+            script_library: String::new(),
+            script_library_error: None,
+            console: Console::new(),
+            console_input: String::new(),
+            console_log: Vec::new(),
+            plugins,
+            field_formatters: HashMap::new(),
+            display_overrides: HashMap::new(),
+            markdown_cache: CommonMarkCache::default(),
+            error: (!plugin_errors.is_empty()).then(|| plugin_errors.join("\n")),
+            crash: crash::take_report(),
+        };
+        if let Some(path) = project_path {
+            let result = open_dialog::open_project(&mut app, &path);
+            app.report(result);
+        }
+        app
+    }
+}
+
+impl BitLoomApp {
+    /// Show the result of an operation to the user if it failed
+    pub fn report<T, E: Localize>(&mut self, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error = Some(e.localize());
+                None
+            }
+        }
+    }
+
+    /// Decode the packet in the hex view as the selected protocol
+    pub fn decode_packet(&mut self) {
+        let Some(protocol_id) = &self.selected_protocol else {
+            return;
+        };
+        let result = match CompiledCodec::compile(&self.registry, &self.script_engine, protocol_id)
+        {
+            Ok(codec) if self.order_preview.is_active() => {
+                self.order_preview
+                    .decode(codec, &self.script_engine, &self.packet_data)
+            }
+            _ => decode_partial(
+                &self.registry,
+                &self.script_engine,
+                protocol_id,
+                &self.packet_data,
+            ),
+        };
+        match result {
+            Ok(packet) => {
+                self.decoded = Some(packet);
+                self.decode_error = None;
+            }
+            Err(e) => {
+                self.decoded = None;
+                self.decode_error = Some(*e);
+            }
+        }
+    }
+
+    /// How each field of a protocol and its parents is displayed, by field ID: the format
+    /// chosen in the inspector, else the format of the field, else the global one
+    pub fn display_formats(&self, protocol_id: &str) -> HashMap<String, DisplayFormat> {
+        let layout = self.registry.layout(protocol_id).unwrap_or_default();
+        layout
+            .fields
+            .iter()
+            .map(|field| {
+                let format = self
+                    .display_overrides
+                    .get(&field.id)
+                    .copied()
+                    .or(field.display)
+                    .unwrap_or(self.appearance.display);
+                (field.id.clone(), format)
+            })
+            .collect()
+    }
+
+    /// Run a plugin menu action, logging its output in the console
+    pub fn run_plugin_action(&mut self, plugin: usize, action: usize) {
+        let plugin = &self.plugins[plugin];
+        let hook = &plugin.actions[action];
+        let output = self.console.call(
+            &mut self.registry,
+            &mut self.script_engine,
+            self.decoded.as_ref(),
+            &plugin.ast,
+            &hook.function,
+            Vec::new(),
+        );
+        if let Err(e) = &output.result {
+            self.error = Some(tr!(
+                "menu-plugin-failed",
+                action = hook.label.as_str(),
+                error = e.as_str()
+            ));
+        }
+        self.console_log
+            .push((format!("[{}] {}", plugin.name, hook.label), output));
+    }
+
+    /// The project as it is saved: the protocols in their listed order, their history, the
+    /// script library, the packet presets, the bookmarks and the capture columns
+    pub fn project(&self) -> BitLoomProject {
+        BitLoomProject {
+            project_version: PROJECT_VERSION,
+            protocols: self.registry.iter().cloned().collect(),
+            history: self.history.clone(),
+            script_library: self.script_library.clone(),
+            presets: self.presets.clone(),
+            bookmarks: self.bookmarks.clone(),
+            packet_columns: self.capture.columns.clone(),
+        }
+    }
+
+    fn show_error(&mut self, ctx: &egui::Context) {
+        let Some(error) = &self.error else {
+            return;
+        };
+
+        let mut dismissed = false;
+        egui::Window::new(tr!("dialog-error"))
+            .id(egui::Id::new("error_dialog"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(error);
+                if ui.button(tr!("dialog-ok")).clicked() {
+                    dismissed = true;
+                }
+            });
+        if dismissed {
+            self.error = None;
         }
     }
 }
 
 impl eframe::App for BitLoomApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, theme::STORAGE_KEY, &self.appearance);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        crash::guard(self, |app| app.show(ctx));
+    }
+}
+
+impl BitLoomApp {
+    fn show(&mut self, ctx: &egui::Context) {
+        crate::ui::simulator::poll(self, ctx);
+        crate::ui::api_server::poll(self, ctx);
+        crate::ui::capture::poll(self, ctx);
+        crate::ui::replay::poll(self, ctx);
+        crate::ui::scheduler::poll(self, ctx);
         crate::ui::top_panel::show(self, ctx);
+        crate::ui::status_bar::show(self, ctx);
         crate::ui::sidebar::show(self, ctx);
         crate::ui::hex_view::show(self, ctx);
         crate::ui::inspector::show(self, ctx);
-        crate::ui::protocol_designer::show(self, ctx);
+        match self.current_page {
+            ViewPage::ProtocolDesigner => crate::ui::protocol_designer::show(self, ctx),
+            ViewPage::PacketBuilder => crate::ui::packet_builder::show(self, ctx),
+            ViewPage::Capture => crate::ui::capture::show(self, ctx),
+            ViewPage::Scripts => crate::ui::script_library::show(self, ctx),
+            ViewPage::Console => crate::ui::console::show(self, ctx),
+        }
+        crate::ui::where_used::show(self, ctx);
+        crate::ui::coverage::show(self, ctx);
+        crate::ui::compare::show(self, ctx);
+        crate::ui::history::show(self, ctx);
+        crate::ui::simulator::show(self, ctx);
+        crate::ui::replay::show(self, ctx);
+        crate::ui::scheduler::show(self, ctx);
+        crate::ui::api_server::show(self, ctx);
+        crate::ui::theme::show(self, ctx);
+        crate::ui::trash::show(self, ctx);
+        crate::ui::bit_accounting::show(self, ctx);
+        crate::ui::bit_calculator::show(self, ctx);
+        crate::ui::checksum_calculator::show(self, ctx);
+        crate::ui::bookmarks::show(self, ctx);
+        crate::ui::bookmarks::show_new(self, ctx);
+        crate::ui::field_editor::show(self, ctx);
+        crate::ui::export_dialog::show(self, ctx);
+        crate::ui::import_dialog::show(self, ctx);
+        crate::ui::open_dialog::show(self, ctx);
+        crate::ui::integrity::show(self, ctx);
+        crate::ui::delete_dialog::show(self, ctx);
+        crate::ui::codegen_dialog::show(self, ctx);
+        crate::ui::scrub_dialog::show(self, ctx);
+        crate::ui::skeleton_dialog::show(self, ctx);
+        crate::ui::crash::show(self, ctx);
+        self.show_error(ctx);
     }
 }
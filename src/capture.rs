@@ -0,0 +1,811 @@
+//! Recorded packets: read from pcap files or binary logs, or received live from a transport.
+
+use crate::codec::framing::StreamFramer;
+use crate::models::protocol::{Endianness, ProtocolRegistry};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+/// Link types of pcap files this module can find UDP payloads in
+pub const LINKTYPE_ETHERNET: u32 = 1;
+pub const LINKTYPE_RAW: u32 = 101;
+pub const LINKTYPE_LINUX_SLL: u32 = 113;
+pub const LINKTYPE_IPV4: u32 = 228;
+pub const LINKTYPE_IPV6: u32 = 229;
+/// Link type reserved for private use, for packets without link, IP or transport headers
+pub const LINKTYPE_USER0: u32 = 147;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const IP_PROTOCOL_TCP: u8 = 6;
+const IP_PROTOCOL_UDP: u8 = 17;
+
+const TCP_FLAG_SYN: u8 = 0x02;
+
+/// Segments of a TCP stream kept waiting for a missing one before the gap is skipped
+const MAX_OUT_OF_ORDER: usize = 64;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CapturedPacket {
+    /// time since the Unix epoch
+    pub timestamp: Duration,
+    pub data: Vec<u8>,
+}
+
+/// The packets of a pcap file
+#[derive(Debug)]
+pub struct PcapFile {
+    pub link_type: u32,
+    pub packets: Vec<CapturedPacket>,
+}
+
+/// Read a classic libpcap file with microsecond or nanosecond timestamps in either byte order
+pub fn read_pcap(bytes: &[u8]) -> Result<PcapFile, String> {
+    let magic: [u8; 4] = bytes
+        .get(..4)
+        .and_then(|m| m.try_into().ok())
+        .ok_or("File is too short to be a pcap file")?;
+    let (little, nanos) = match magic {
+        [0xd4, 0xc3, 0xb2, 0xa1] => (true, false),
+        [0xa1, 0xb2, 0xc3, 0xd4] => (false, false),
+        [0x4d, 0x3c, 0xb2, 0xa1] => (true, true),
+        [0xa1, 0xb2, 0x3c, 0x4d] => (false, true),
+        [0x0a, 0x0d, 0x0d, 0x0a] => {
+            return Err("pcapng files are not supported; save the capture as pcap".to_string());
+        }
+        _ => return Err("Not a pcap file".to_string()),
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        let word: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little {
+            u32::from_le_bytes(word)
+        } else {
+            u32::from_be_bytes(word)
+        })
+    };
+
+    let link_type = u32_at(20).ok_or("pcap header is truncated")?;
+    let mut packets = Vec::new();
+    let mut offset = 24;
+    while offset < bytes.len() {
+        let (Some(seconds), Some(fraction), Some(length)) =
+            (u32_at(offset), u32_at(offset + 4), u32_at(offset + 8))
+        else {
+            return Err(format!("Record {} is truncated", packets.len() + 1));
+        };
+        let start = offset + 16;
+        let data = bytes
+            .get(start..start + length as usize)
+            .ok_or_else(|| format!("Record {} is truncated", packets.len() + 1))?;
+        let fraction = if nanos {
+            fraction
+        } else {
+            fraction.saturating_mul(1000)
+        };
+        packets.push(CapturedPacket {
+            timestamp: Duration::new(seconds as u64, fraction),
+            data: data.to_vec(),
+        });
+        offset = start + length as usize;
+    }
+    Ok(PcapFile { link_type, packets })
+}
+
+/// Write a little endian pcap file with nanosecond timestamps
+pub fn write_pcap(file: &PcapFile) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(0xa1b23c4du32.to_le_bytes());
+    out.extend(2u16.to_le_bytes());
+    out.extend(4u16.to_le_bytes());
+    out.extend([0; 8]); // time zone and timestamp accuracy
+    let snaplen = file.packets.iter().map(|p| p.data.len()).max().unwrap_or(0);
+    out.extend((snaplen.max(65_535) as u32).to_le_bytes());
+    out.extend(file.link_type.to_le_bytes());
+    for packet in &file.packets {
+        out.extend((packet.timestamp.as_secs() as u32).to_le_bytes());
+        out.extend(packet.timestamp.subsec_nanos().to_le_bytes());
+        out.extend((packet.data.len() as u32).to_le_bytes());
+        out.extend((packet.data.len() as u32).to_le_bytes());
+        out.extend(&packet.data);
+    }
+    out
+}
+
+/// How the records of a raw binary log are delimited
+#[derive(Clone, PartialEq, Debug)]
+pub enum Framing {
+    /// every record has the same size in bytes
+    Fixed { size: usize },
+    /// each record holds its length in an unsigned integer
+    LengthPrefix {
+        /// offset of the length in the record, in bytes
+        offset: usize,
+        /// size of the length in bytes, 1 to 8
+        width: usize,
+        byte_order: Endianness,
+        /// added to the length to get the number of bytes after it; negative when the length
+        /// counts the bytes before it too
+        adjustment: i64,
+    },
+    /// each record ends with a sequence of bytes, which is not part of the record
+    Delimiter(Vec<u8>),
+}
+
+impl Framing {
+    /// Short name of the framing for display
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Framing::Fixed { .. } => "Fixed size",
+            Framing::LengthPrefix { .. } => "Length prefix",
+            Framing::Delimiter(_) => "Delimiter",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimeUnit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl TimeUnit {
+    pub const ALL: [TimeUnit; 4] = [
+        TimeUnit::Seconds,
+        TimeUnit::Milliseconds,
+        TimeUnit::Microseconds,
+        TimeUnit::Nanoseconds,
+    ];
+
+    pub fn duration(self, count: u64) -> Duration {
+        match self {
+            TimeUnit::Seconds => Duration::from_secs(count),
+            TimeUnit::Milliseconds => Duration::from_millis(count),
+            TimeUnit::Microseconds => Duration::from_micros(count),
+            TimeUnit::Nanoseconds => Duration::from_nanos(count),
+        }
+    }
+}
+
+/// A timestamp before each record of a binary log: an unsigned integer count of a time unit
+/// since the Unix epoch
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimestampHeader {
+    /// size in bytes, 1 to 8
+    pub width: usize,
+    pub byte_order: Endianness,
+    pub unit: TimeUnit,
+}
+
+/// Layout of a raw binary log of back-to-back records
+#[derive(Clone, PartialEq, Debug)]
+pub struct BinaryLogFormat {
+    pub framing: Framing,
+    /// header before each record, which is not part of the packet
+    pub timestamp: Option<TimestampHeader>,
+}
+
+impl Default for BinaryLogFormat {
+    fn default() -> Self {
+        Self {
+            framing: Framing::Fixed { size: 8 },
+            timestamp: None,
+        }
+    }
+}
+
+/// Split a raw binary log into its records. Records without a timestamp header have the time 0.
+pub fn read_binary_log(
+    bytes: &[u8],
+    format: &BinaryLogFormat,
+) -> Result<Vec<CapturedPacket>, String> {
+    match &format.framing {
+        Framing::Fixed { size: 0 } => return Err("Frame size must be at least 1".to_string()),
+        Framing::LengthPrefix { width, .. } if !(1..=8).contains(width) => {
+            return Err("Length must be 1 to 8 bytes".to_string());
+        }
+        Framing::Delimiter(delimiter) if delimiter.is_empty() => {
+            return Err("Delimiter must not be empty".to_string());
+        }
+        _ => {}
+    }
+    if let Some(header) = &format.timestamp
+        && !(1..=8).contains(&header.width)
+    {
+        return Err("Timestamp must be 1 to 8 bytes".to_string());
+    }
+
+    let mut packets = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let record = packets.len() + 1;
+        let truncated = || format!("Record {} is truncated", record);
+        let timestamp = match &format.timestamp {
+            Some(header) => {
+                let count = bytes
+                    .get(offset..offset + header.width)
+                    .ok_or_else(truncated)?;
+                offset += header.width;
+                header.unit.duration(read_uint(count, header.byte_order))
+            }
+            None => Duration::ZERO,
+        };
+
+        let rest = &bytes[offset..];
+        let (data, consumed) = match &format.framing {
+            Framing::Fixed { size } => (rest.get(..*size).ok_or_else(truncated)?, *size),
+            Framing::LengthPrefix {
+                offset: at,
+                width,
+                byte_order,
+                adjustment,
+            } => {
+                let end = at + width;
+                let length = read_uint(rest.get(*at..end).ok_or_else(truncated)?, *byte_order);
+                let size = usize::try_from(end as i128 + length as i128 + *adjustment as i128)
+                    .ok()
+                    .filter(|&size| size >= end)
+                    .ok_or_else(|| {
+                        format!(
+                            "Record {} has length {}, which ends before the length does",
+                            record, length
+                        )
+                    })?;
+                (rest.get(..size).ok_or_else(truncated)?, size)
+            }
+            // the last record may go without a delimiter
+            Framing::Delimiter(delimiter) => match rest
+                .windows(delimiter.len())
+                .position(|w| w == delimiter.as_slice())
+            {
+                Some(end) => (&rest[..end], end + delimiter.len()),
+                None => (rest, rest.len()),
+            },
+        };
+        packets.push(CapturedPacket {
+            timestamp,
+            data: data.to_vec(),
+        });
+        offset += consumed;
+    }
+    Ok(packets)
+}
+
+/// An unsigned integer of up to 8 bytes
+pub(crate) fn read_uint(bytes: &[u8], byte_order: Endianness) -> u64 {
+    let fold = |acc: u64, &b: &u8| (acc << 8) | b as u64;
+    match byte_order {
+        Endianness::Big => bytes.iter().fold(0, fold),
+        Endianness::Little => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+/// The payload of a UDP datagram in a captured frame, if the frame holds one
+pub fn udp_payload(link_type: u32, frame: &[u8]) -> Option<&[u8]> {
+    let (_, _, protocol, udp) = ip_parts(ip_packet(link_type, frame)?)?;
+    if protocol != IP_PROTOCOL_UDP {
+        return None;
+    }
+    let length = u16::from_be_bytes(udp.get(4..6)?.try_into().ok()?) as usize;
+    udp.get(8..length.max(8))
+}
+
+/// One direction of a TCP connection
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TcpFlow {
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+}
+
+/// A TCP segment in a captured frame
+#[derive(Clone, PartialEq, Debug)]
+pub struct TcpSegment<'a> {
+    pub flow: TcpFlow,
+    /// sequence number of the first byte of the payload, or of the SYN
+    pub seq: u32,
+    /// whether the segment opens the connection
+    pub syn: bool,
+    pub payload: &'a [u8],
+}
+
+/// The TCP segment in a captured frame, if the frame holds one
+pub fn tcp_segment(link_type: u32, frame: &[u8]) -> Option<TcpSegment<'_>> {
+    let (source, destination, protocol, tcp) = ip_parts(ip_packet(link_type, frame)?)?;
+    if protocol != IP_PROTOCOL_TCP {
+        return None;
+    }
+    let port = |offset: usize| {
+        Some(u16::from_be_bytes(
+            tcp.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    let header = (*tcp.get(12)? >> 4) as usize * 4;
+    Some(TcpSegment {
+        flow: TcpFlow {
+            source: SocketAddr::new(source, port(0)?),
+            destination: SocketAddr::new(destination, port(2)?),
+        },
+        seq: u32::from_be_bytes(tcp.get(4..8)?.try_into().ok()?),
+        syn: tcp.get(13)? & TCP_FLAG_SYN != 0,
+        payload: tcp.get(header..)?,
+    })
+}
+
+/// The IP packet in a captured frame, if the frame holds one
+fn ip_packet(link_type: u32, frame: &[u8]) -> Option<&[u8]> {
+    let (ethertype, packet) = match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ethertype = u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+            while ethertype == ETHERTYPE_VLAN {
+                offset += 4;
+                ethertype = u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+            }
+            (ethertype, frame.get(offset + 2..)?)
+        }
+        LINKTYPE_LINUX_SLL => (
+            u16::from_be_bytes(frame.get(14..16)?.try_into().ok()?),
+            frame.get(16..)?,
+        ),
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => return Some(frame),
+        _ => return None,
+    };
+    matches!(ethertype, ETHERTYPE_IPV4 | ETHERTYPE_IPV6).then_some(packet)
+}
+
+/// Source and destination address, protocol number and payload of an IP packet. The payload
+/// ends where the IP header says, leaving out any padding of the link layer.
+fn ip_parts(packet: &[u8]) -> Option<(IpAddr, IpAddr, u8, &[u8])> {
+    // a length of 0 is left by capturing before segmentation offload
+    let end = |length: usize, header: usize| match length {
+        0 => packet.len(),
+        length => (header + length).min(packet.len()),
+    };
+    match packet.first()? >> 4 {
+        4 => {
+            let header = ((packet[0] & 0x0f) as usize) * 4;
+            let total = u16::from_be_bytes(packet.get(2..4)?.try_into().ok()?) as usize;
+            let source: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            Some((
+                source.into(),
+                destination.into(),
+                *packet.get(9)?,
+                packet.get(header..end(total.saturating_sub(header), header))?,
+            ))
+        }
+        6 => {
+            let length = u16::from_be_bytes(packet.get(4..6)?.try_into().ok()?) as usize;
+            let source: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            Some((
+                source.into(),
+                destination.into(),
+                *packet.get(6)?,
+                packet.get(40..end(length, 40))?,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Puts the payloads of TCP segments back in order, separately for each direction of each
+/// connection, dropping retransmitted bytes
+#[derive(Default)]
+pub struct TcpReassembler {
+    streams: HashMap<TcpFlow, TcpStreamState>,
+}
+
+#[derive(Default)]
+struct TcpStreamState {
+    /// sequence number of the next byte expected
+    next_seq: u32,
+    /// segments that arrived ahead of a missing one, with their sequence numbers
+    out_of_order: Vec<(u32, Vec<u8>)>,
+}
+
+impl TcpReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a segment and take the bytes of its stream that are now in order: its payload and
+    /// any segments that were waiting for it. The first segment seen of a stream starts it.
+    /// When too many segments are waiting for a lost one, the stream skips the gap.
+    pub fn push(&mut self, segment: &TcpSegment) -> Vec<u8> {
+        // a SYN takes up a sequence number of its own
+        let seq = segment.seq.wrapping_add(segment.syn as u32);
+        let stream = match self.streams.get_mut(&segment.flow) {
+            Some(stream) if !segment.syn => stream,
+            _ => {
+                let stream = TcpStreamState {
+                    next_seq: seq,
+                    out_of_order: Vec::new(),
+                };
+                self.streams.insert(segment.flow, stream);
+                self.streams.get_mut(&segment.flow).unwrap()
+            }
+        };
+        stream.out_of_order.push((seq, segment.payload.to_vec()));
+
+        let mut data = Vec::new();
+        loop {
+            // how far each waiting segment starts before the next expected byte
+            let next = stream.next_seq;
+            let behind = |seq: u32| next.wrapping_sub(seq) as i32;
+            stream.out_of_order.retain(|(seq, payload)| {
+                behind(*seq) < 0 || (behind(*seq) as usize) < payload.len()
+            });
+            let ready = stream
+                .out_of_order
+                .iter()
+                .position(|(seq, _)| behind(*seq) >= 0);
+            let index = match ready {
+                Some(index) => index,
+                None if stream.out_of_order.len() > MAX_OUT_OF_ORDER => {
+                    let (index, (seq, _)) = stream
+                        .out_of_order
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, (seq, _))| seq.wrapping_sub(next))
+                        .unwrap();
+                    stream.next_seq = *seq;
+                    index
+                }
+                None => break,
+            };
+            let (seq, payload) = stream.out_of_order.swap_remove(index);
+            let skip = stream.next_seq.wrapping_sub(seq) as usize;
+            data.extend_from_slice(&payload[skip..]);
+            stream.next_seq = stream.next_seq.wrapping_add((payload.len() - skip) as u32);
+        }
+        data
+    }
+}
+
+/// Splits the TCP connections in captured frames into the messages of a protocol, found by
+/// the length of the protocol in each direction of each connection
+#[derive(Default)]
+pub struct TcpMessageSplitter {
+    reassembler: TcpReassembler,
+    framers: HashMap<TcpFlow, StreamFramer>,
+}
+
+impl TcpMessageSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a captured frame and take the messages it completes, each with the time of the
+    /// frame. Frames without a TCP segment are skipped.
+    pub fn push(
+        &mut self,
+        link_type: u32,
+        frame: &CapturedPacket,
+        registry: &ProtocolRegistry,
+        protocol_id: &str,
+    ) -> Result<Vec<CapturedPacket>, String> {
+        let Some(segment) = tcp_segment(link_type, &frame.data) else {
+            return Ok(Vec::new());
+        };
+        let data = self.reassembler.push(&segment);
+        let framer = self.framers.entry(segment.flow).or_default();
+        let framed = framer.push(registry, protocol_id, &data).map_err(|e| {
+            format!(
+                "Stream {} → {}: {}",
+                segment.flow.source, segment.flow.destination, e
+            )
+        })?;
+        Ok(framed
+            .into_iter()
+            .map(|data| CapturedPacket {
+                timestamp: frame.timestamp,
+                data,
+            })
+            .collect())
+    }
+}
+
+/// The messages of a protocol carried by the TCP connections in a pcap file, see
+/// [`TcpMessageSplitter`]
+pub fn tcp_messages(
+    file: &PcapFile,
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+) -> Result<Vec<CapturedPacket>, String> {
+    let mut splitter = TcpMessageSplitter::new();
+    let mut messages = Vec::new();
+    for packet in &file.packets {
+        messages.extend(splitter.push(file.link_type, packet, registry, protocol_id)?);
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::LengthField;
+
+    /// An Ethernet frame with an IPv4 UDP datagram carrying `payload`
+    fn ethernet_udp(payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0; 12];
+        frame.extend([0x08, 0x00]);
+        let total = 20 + 8 + payload.len();
+        let mut ip = vec![0x45, 0, (total >> 8) as u8, total as u8, 0, 0, 0, 0, 64, 17];
+        ip.resize(20, 0);
+        frame.extend(ip);
+        let udp_len = 8 + payload.len();
+        frame.extend([
+            0x13,
+            0x88,
+            0x13,
+            0x88,
+            (udp_len >> 8) as u8,
+            udp_len as u8,
+            0,
+            0,
+        ]);
+        frame.extend(payload);
+        frame
+    }
+
+    fn pcap(little: bool, link_type: u32, frames: &[&[u8]]) -> Vec<u8> {
+        let word = |v: u32| {
+            if little {
+                v.to_le_bytes()
+            } else {
+                v.to_be_bytes()
+            }
+        };
+        let mut out = Vec::new();
+        out.extend(word(0xa1b2c3d4));
+        let version: [u16; 2] = [2, 4];
+        for v in version {
+            out.extend(if little {
+                v.to_le_bytes()
+            } else {
+                v.to_be_bytes()
+            });
+        }
+        out.extend(word(0));
+        out.extend(word(0));
+        out.extend(word(65535));
+        out.extend(word(link_type));
+        for (i, frame) in frames.iter().enumerate() {
+            out.extend(word(1_700_000_000 + i as u32));
+            out.extend(word(500));
+            out.extend(word(frame.len() as u32));
+            out.extend(word(frame.len() as u32));
+            out.extend(*frame);
+        }
+        out
+    }
+
+    #[test]
+    fn test_read_pcap() {
+        let frame = ethernet_udp(&[0xde, 0xad]);
+        for little in [true, false] {
+            let file = read_pcap(&pcap(little, LINKTYPE_ETHERNET, &[&frame, &[1, 2, 3]])).unwrap();
+            assert_eq!(file.link_type, LINKTYPE_ETHERNET);
+            assert_eq!(file.packets.len(), 2);
+            assert_eq!(file.packets[0].data, frame);
+            assert_eq!(
+                file.packets[1].timestamp,
+                Duration::new(1_700_000_001, 500_000)
+            );
+        }
+
+        let file = read_pcap(&pcap(true, LINKTYPE_ETHERNET, &[&frame])).unwrap();
+        let written = read_pcap(&write_pcap(&file)).unwrap();
+        assert_eq!(
+            (written.link_type, written.packets),
+            (file.link_type, file.packets)
+        );
+
+        let mut truncated = pcap(true, LINKTYPE_ETHERNET, &[&frame]);
+        truncated.pop();
+        assert!(read_pcap(&truncated).is_err());
+        assert!(
+            read_pcap(&[0x0a, 0x0d, 0x0d, 0x0a])
+                .unwrap_err()
+                .contains("pcapng")
+        );
+    }
+
+    #[test]
+    fn test_read_binary_log() {
+        let data = |packets: &[CapturedPacket]| -> Vec<Vec<u8>> {
+            packets.iter().map(|p| p.data.clone()).collect()
+        };
+
+        let fixed = BinaryLogFormat {
+            framing: Framing::Fixed { size: 2 },
+            timestamp: None,
+        };
+        assert_eq!(
+            data(&read_binary_log(&[1, 2, 3, 4], &fixed).unwrap()),
+            vec![vec![1, 2], vec![3, 4]]
+        );
+        assert!(read_binary_log(&[1, 2, 3], &fixed).is_err());
+
+        // a 16 bit length after a sync byte, counting the whole record
+        let prefixed = BinaryLogFormat {
+            framing: Framing::LengthPrefix {
+                offset: 1,
+                width: 2,
+                byte_order: Endianness::Little,
+                adjustment: -3,
+            },
+            timestamp: Some(TimestampHeader {
+                width: 4,
+                byte_order: Endianness::Big,
+                unit: TimeUnit::Milliseconds,
+            }),
+        };
+        let log = [0, 0, 0, 5, 0xaa, 4, 0, 9, 0, 0, 7, 0xd0, 0xaa, 3, 0];
+        let packets = read_binary_log(&log, &prefixed).unwrap();
+        assert_eq!(data(&packets), vec![vec![0xaa, 4, 0, 9], vec![0xaa, 3, 0]]);
+        assert_eq!(packets[1].timestamp, Duration::from_secs(2));
+        assert!(read_binary_log(&[0, 0, 0, 5, 0xaa, 2, 0], &prefixed).is_err());
+
+        let delimited = BinaryLogFormat {
+            framing: Framing::Delimiter(vec![0x0d, 0x0a]),
+            timestamp: None,
+        };
+        assert_eq!(
+            data(&read_binary_log(b"ab\r\n\r\nc", &delimited).unwrap()),
+            vec![b"ab".to_vec(), vec![], b"c".to_vec()]
+        );
+    }
+
+    /// An Ethernet frame with an IPv4 TCP segment from 10.0.0.1:1000 to 10.0.0.2:2000
+    fn ethernet_tcp(seq: u32, syn: bool, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0; 12];
+        frame.extend([0x08, 0x00]);
+        let total = 20 + 20 + payload.len();
+        frame.extend([
+            0x45,
+            0,
+            (total >> 8) as u8,
+            total as u8,
+            0,
+            0,
+            0,
+            0,
+            64,
+            6,
+            0,
+            0,
+        ]);
+        frame.extend([10, 0, 0, 1, 10, 0, 0, 2]);
+        frame.extend([0x03, 0xe8, 0x07, 0xd0]);
+        frame.extend(seq.to_be_bytes());
+        frame.extend([
+            0,
+            0,
+            0,
+            0,
+            0x50,
+            if syn { 0x02 } else { 0x18 },
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ]);
+        frame.extend(payload);
+        frame
+    }
+
+    #[test]
+    fn test_tcp_segment() {
+        let mut frame = ethernet_tcp(7, false, &[1, 2]);
+        frame.extend([0; 4]); // Ethernet padding
+        let segment = tcp_segment(LINKTYPE_ETHERNET, &frame).unwrap();
+        assert_eq!(segment.flow.source, "10.0.0.1:1000".parse().unwrap());
+        assert_eq!(segment.flow.destination, "10.0.0.2:2000".parse().unwrap());
+        assert_eq!((segment.seq, segment.syn), (7, false));
+        assert_eq!(segment.payload, &[1, 2]);
+        assert_eq!(tcp_segment(LINKTYPE_ETHERNET, &ethernet_udp(&[1])), None);
+    }
+
+    #[test]
+    fn test_reassemble_tcp_stream() {
+        let frames = [
+            ethernet_tcp(99, true, &[]),
+            ethernet_tcp(103, false, &[4, 5]),
+            ethernet_tcp(100, false, &[1, 2, 3]),
+            ethernet_tcp(100, false, &[1, 2, 3]), // retransmitted
+            ethernet_tcp(104, false, &[5, 6]),    // overlapping
+        ];
+        let mut reassembler = TcpReassembler::new();
+        let data: Vec<Vec<u8>> = frames
+            .iter()
+            .map(|f| reassembler.push(&tcp_segment(LINKTYPE_ETHERNET, f).unwrap()))
+            .collect();
+        assert_eq!(
+            data,
+            vec![vec![], vec![], vec![1, 2, 3, 4, 5], vec![], vec![6]]
+        );
+
+        // a lost segment is given up on once enough others are waiting
+        let mut reassembler = TcpReassembler::new();
+        let segment = |seq| ethernet_tcp(seq, false, &[seq as u8]);
+        reassembler.push(&tcp_segment(LINKTYPE_ETHERNET, &segment(0)).unwrap());
+        let mut data = Vec::new();
+        for seq in 2..=MAX_OUT_OF_ORDER as u32 + 2 {
+            data = reassembler.push(&tcp_segment(LINKTYPE_ETHERNET, &segment(seq)).unwrap());
+        }
+        assert_eq!(data.len(), MAX_OUT_OF_ORDER + 1);
+        assert_eq!(data[0], 2);
+    }
+
+    #[test]
+    fn test_tcp_messages() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "length",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))?;
+                p.length_field = Some(LengthField {
+                    field_id: "length".to_string(),
+                    adjustment: 1,
+                });
+                Ok(())
+            })
+            .unwrap();
+
+        // three messages split across two segments
+        let segments = [
+            ethernet_tcp(0, false, &[2, 1, 1, 1]),
+            ethernet_tcp(4, false, &[9, 1, 8]),
+        ];
+        let frames: Vec<&[u8]> = segments.iter().map(Vec::as_slice).collect();
+        let file = read_pcap(&pcap(true, LINKTYPE_ETHERNET, &frames)).unwrap();
+        let messages = tcp_messages(&file, &registry, "msg").unwrap();
+        let data: Vec<&[u8]> = messages.iter().map(|m| m.data.as_slice()).collect();
+        assert_eq!(data, vec![&[2, 1, 1][..], &[1, 9], &[1, 8]]);
+        assert_eq!(messages[1].timestamp, file.packets[1].timestamp);
+    }
+
+    #[test]
+    fn test_udp_payload() {
+        let frame = ethernet_udp(&[0xde, 0xad]);
+        assert_eq!(
+            udp_payload(LINKTYPE_ETHERNET, &frame),
+            Some(&[0xde, 0xad][..])
+        );
+        assert_eq!(
+            udp_payload(LINKTYPE_IPV4, &frame[14..]),
+            Some(&[0xde, 0xad][..])
+        );
+
+        let mut tagged = frame[..12].to_vec();
+        tagged.extend([0x81, 0x00, 0x00, 0x05]);
+        tagged.extend(&frame[12..]);
+        assert_eq!(
+            udp_payload(LINKTYPE_ETHERNET, &tagged),
+            Some(&[0xde, 0xad][..])
+        );
+
+        let mut tcp = frame.clone();
+        tcp[14 + 9] = 6;
+        assert_eq!(udp_payload(LINKTYPE_ETHERNET, &tcp), None);
+        assert_eq!(udp_payload(LINKTYPE_ETHERNET, &frame[..20]), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Decoding and encoding many packets at once on every core, e.g. a large capture or a sweep,
+//! with the results coming back one by one while the rest are still in progress.
+
+use crate::codec::Value;
+use crate::codec::compiled::{CodecCache, CompiledCodec};
+use crate::codec::decode::DecodedPacket;
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::{EngineSettings, ScriptEngine};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, channel};
+use std::thread::{self, JoinHandle};
+
+/// Work running on a pool of threads, one result per input
+pub struct Batch<T> {
+    receiver: Receiver<(usize, T)>,
+    cancelled: Arc<AtomicBool>,
+    total: usize,
+    received: usize,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Batch<T> {
+    /// Run `work` on each input, with an engine set up from `settings` on every thread and
+    /// what `prepare` makes with it, e.g. a compiled codec
+    fn spawn<I: Send + Sync + 'static, S>(
+        inputs: Vec<I>,
+        settings: EngineSettings,
+        prepare: impl Fn(&ScriptEngine) -> S + Send + Sync + 'static,
+        work: impl Fn(&ScriptEngine, &S, &I) -> T + Send + Sync + 'static,
+    ) -> Self {
+        let (sender, receiver) = channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let total = inputs.len();
+        let stop = cancelled.clone();
+        let thread = thread::spawn(move || {
+            let workers = thread::available_parallelism()
+                .map_or(1, |n| n.get())
+                .min(inputs.len());
+            let next = AtomicUsize::new(0);
+            thread::scope(|scope| {
+                for _ in 0..workers {
+                    let sender = sender.clone();
+                    scope.spawn(|| {
+                        let engine = ScriptEngine::from_settings(&settings);
+                        let prepared = prepare(&engine);
+                        let sender = sender;
+                        while !stop.load(Ordering::Relaxed) {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(input) = inputs.get(index) else {
+                                break;
+                            };
+                            if sender
+                                .send((index, work(&engine, &prepared, input)))
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    });
+                }
+            });
+        });
+        Self {
+            receiver,
+            cancelled,
+            total,
+            received: 0,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl<T> Batch<T> {
+    /// Results finished since the last call, with the index of their input, in the order they
+    /// finished
+    pub fn try_recv(&mut self) -> Vec<(usize, T)> {
+        let results: Vec<_> = self.receiver.try_iter().collect();
+        self.received += results.len();
+        results
+    }
+
+    /// Wait for the remaining results and return all of them in the order of the inputs,
+    /// leaving out those already taken with [`Batch::try_recv`]
+    pub fn wait(mut self) -> Vec<T> {
+        let mut results: Vec<_> = self.receiver.iter().collect();
+        self.received += results.len();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Results taken so far and the number of inputs
+    pub fn progress(&self) -> (usize, usize) {
+        (self.received, self.total)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.received == self.total
+    }
+
+    /// Stop the threads after the inputs they are working on
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Drop for Batch<T> {
+    fn drop(&mut self) {
+        self.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Decode each packet as `protocol_id`, and with `dispatch` then as the subprotocol it
+/// matches, as [`decode_dispatched`](crate::codec::dispatch::decode_dispatched) does
+pub fn decode_batch(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    dispatch: bool,
+    packets: Vec<Vec<u8>>,
+) -> Batch<Result<DecodedPacket, String>> {
+    let registry = registry.clone();
+    let protocol_id = protocol_id.to_string();
+    Batch::spawn(
+        packets,
+        engine.settings(),
+        |_| RefCell::new(CodecCache::new()),
+        move |engine, codecs, data| {
+            // each protocol is compiled the first time a packet is decoded as it
+            let mut codecs = codecs.borrow_mut();
+            if dispatch {
+                codecs.decode_dispatched(&registry, engine, &protocol_id, data)
+            } else {
+                codecs.decode(&registry, engine, &protocol_id, data)
+            }
+        },
+    )
+}
+
+/// Encode each set of field values as a packet of `protocol_id`
+pub fn encode_batch(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    values: Vec<HashMap<String, Value>>,
+) -> Batch<Result<Vec<u8>, String>> {
+    let registry = registry.clone();
+    let protocol_id = protocol_id.to_string();
+    Batch::spawn(
+        values,
+        engine.settings(),
+        move |engine| CompiledCodec::compile(&registry, engine, &protocol_id),
+        |engine, codec, values| codec.as_ref().map_err(Clone::clone)?.encode(engine, values),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_batch_round_trip() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "value",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "check",
+                    FieldType::Expr("double(value) & 0xff".to_string()),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let mut engine = ScriptEngine::new();
+        engine.set_library("fn double(x) { x * 2 }").unwrap();
+
+        let values: Vec<_> = (0..500)
+            .map(|i| HashMap::from([("value".to_string(), Value::Int(i * 3))]))
+            .collect();
+        let encoded: Vec<Vec<u8>> = encode_batch(&registry, &engine, "msg", values)
+            .wait()
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(encoded.len(), 500);
+        assert_eq!(encoded[100], vec![0x01, 0x2c, 0x58]);
+
+        let mut batch = decode_batch(&registry, &engine, "msg", false, encoded);
+        let mut decoded = vec![None; 500];
+        while !batch.is_finished() {
+            for (index, result) in batch.try_recv() {
+                decoded[index] = Some(result.unwrap());
+            }
+        }
+        assert_eq!(batch.progress(), (500, 500));
+        for (i, packet) in decoded.iter().enumerate() {
+            let packet = packet.as_ref().unwrap();
+            assert_eq!(
+                packet.get("value").unwrap().value,
+                Value::Int(i as i128 * 3)
+            );
+        }
+
+        let failed = decode_batch(&registry, &engine, "msg", true, vec![vec![1]]).wait();
+        assert!(failed[0].is_err());
+    }
+}
@@ -0,0 +1,344 @@
+/// Reads bit-granular values MSB-first from a byte buffer
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    /// current position in bits
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Current position in bits from the start of the buffer
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bits left to read
+    pub fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    /// Read `bits` bits and return them right-aligned in `ceil(bits / 8)` big-endian bytes.
+    pub fn read_bits(&mut self, bits: usize) -> Result<Vec<u8>, String> {
+        Ok(self.read_slice(bits)?.to_bits(false).into_bytes())
+    }
+
+    /// Read `bits` bits as a view into the buffer, without copying them
+    pub fn read_slice(&mut self, bits: usize) -> Result<BitSlice<'a>, String> {
+        let slice = BitSlice::new(self.data, self.pos, bits).ok_or_else(|| {
+            format!(
+                "Unexpected end of data: needed {} bits at bit offset {}, only {} left",
+                bits,
+                self.pos,
+                self.remaining()
+            )
+        })?;
+        self.pos += bits;
+        Ok(slice)
+    }
+}
+
+/// A run of bits of a byte buffer, MSB-first, viewed in place
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BitSlice<'a> {
+    data: &'a [u8],
+    /// in bits from the start of `data`
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> BitSlice<'a> {
+    /// The `len` bits from bit `offset` of `data`, if the buffer holds them
+    pub fn new(data: &'a [u8], offset: usize, len: usize) -> Option<Self> {
+        (offset + len <= data.len() * 8).then_some(Self { data, offset, len })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bytes in place, if the slice starts and ends on byte boundaries
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        (self.offset.is_multiple_of(8) && self.len.is_multiple_of(8))
+            .then(|| &self.data[self.offset / 8..(self.offset + self.len) / 8])
+    }
+
+    /// Bits in the first byte of the right-aligned value, when the length is not a whole
+    /// number of bytes
+    fn lead(&self) -> usize {
+        self.len % 8
+    }
+
+    /// The `k`th whole byte after the lead bits
+    fn byte(&self, k: usize) -> u8 {
+        let pos = self.offset + self.lead() + 8 * k;
+        let (i, shift) = (pos / 8, pos % 8);
+        if shift == 0 {
+            self.data[i]
+        } else {
+            (self.data[i] << shift) | (self.data[i + 1] >> (8 - shift))
+        }
+    }
+
+    fn lead_bits(&self) -> u8 {
+        (0..self.lead()).fold(0, |acc, i| {
+            let pos = self.offset + i;
+            (acc << 1) | ((self.data[pos / 8] >> (7 - pos % 8)) & 1)
+        })
+    }
+
+    /// The bits right-aligned in big-endian bytes. With `swap_bytes`, a whole number of bytes is
+    /// put least significant byte first.
+    pub fn to_bits(&self, swap_bytes: bool) -> Bits {
+        let mut bytes = match self.as_bytes() {
+            Some(bytes) => bytes.to_vec(),
+            None => {
+                let mut bytes = Vec::with_capacity(self.len.div_ceil(8));
+                if self.lead() > 0 {
+                    bytes.push(self.lead_bits());
+                }
+                bytes.extend((0..self.len / 8).map(|k| self.byte(k)));
+                bytes
+            }
+        };
+        if swap_bytes && self.lead() == 0 {
+            bytes.reverse();
+        }
+        Bits {
+            bytes,
+            len: self.len,
+        }
+    }
+
+    /// The bits as an unsigned integer, if it fits in 128 bits, read as [`Self::to_bits`] does
+    /// but without copying
+    pub fn to_u128(&self, swap_bytes: bool) -> Option<u128> {
+        let push =
+            |acc: u128, byte: u8| (acc.leading_zeros() >= 8).then_some((acc << 8) | byte as u128);
+        let whole = self.len / 8;
+        if swap_bytes && self.lead() == 0 {
+            return (0..whole)
+                .rev()
+                .try_fold(0, |acc, k| push(acc, self.byte(k)));
+        }
+        (0..whole).try_fold(self.lead_bits() as u128, |acc, k| push(acc, self.byte(k)))
+    }
+}
+
+/// Bits owned as right-aligned big-endian bytes: the last `len` bits of the bytes are the
+/// value, and the bits before them are zero
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    /// The low `len` bits of right-aligned big-endian `bytes`, without the bytes before them
+    pub fn new(bytes: &[u8], len: usize) -> Result<Self, String> {
+        if len > bytes.len() * 8 {
+            return Err(format!("{} bits do not fit in {} bytes", len, bytes.len()));
+        }
+        let mut bytes = bytes[bytes.len() - len.div_ceil(8)..].to_vec();
+        if !len.is_multiple_of(8) {
+            bytes[0] &= (1 << (len % 8)) - 1;
+        }
+        Ok(Self { bytes, len })
+    }
+
+    /// Every bit of `bytes`
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        let len = bytes.len() * 8;
+        Self { bytes, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn as_slice(&self) -> BitSlice<'_> {
+        BitSlice {
+            data: &self.bytes,
+            offset: self.bytes.len() * 8 - self.len,
+            len: self.len,
+        }
+    }
+}
+
+/// Writes bit-granular values MSB-first into a growing byte buffer
+#[derive(Default)]
+pub struct BitWriter {
+    data: Vec<u8>,
+    /// current position in bits
+    pos: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current position in bits from the start of the buffer
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Write the low `bits` bits of `value`, given as right-aligned big-endian bytes
+    /// like those returned by [`BitReader::read_bits`].
+    pub fn write_bits(&mut self, value: &[u8], bits: usize) -> Result<(), String> {
+        if bits > value.len() * 8 {
+            return Err(format!(
+                "Cannot write {} bits from a {} byte value",
+                bits,
+                value.len()
+            ));
+        }
+
+        self.data.resize((self.pos + bits).div_ceil(8), 0);
+        for i in 0..bits {
+            // position counted from the least significant bit of the input
+            let src = bits - 1 - i;
+            let bit = (value[value.len() - 1 - src / 8] >> (src % 8)) & 1;
+            let dst = self.pos + i;
+            self.data[dst / 8] |= bit << (7 - dst % 8);
+        }
+
+        self.pos += bits;
+        Ok(())
+    }
+
+    /// The written bytes; a partial last byte is padded with zero bits
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Interpret big-endian bytes as an unsigned integer, if it fits in 128 bits.
+pub fn bytes_to_u128(bytes: &[u8]) -> Option<u128> {
+    let significant = bytes.iter().skip_while(|b| **b == 0).count();
+    if significant > 16 {
+        return None;
+    }
+    Some(bytes.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_unaligned_bits() {
+        let data = [0b1011_0011, 0b1100_0000];
+        let mut reader = BitReader::new(&data);
+
+        assert_eq!(reader.read_bits(3).unwrap(), vec![0b101]);
+        assert_eq!(reader.read_bits(7).unwrap(), vec![0b100_1111]);
+        assert_eq!(reader.position(), 10);
+        assert_eq!(reader.remaining(), 6);
+    }
+
+    #[test]
+    fn test_read_multi_byte_right_aligned() {
+        let data = [0xAB, 0xCD, 0xE0];
+        let mut reader = BitReader::new(&data);
+
+        assert_eq!(reader.read_bits(4).unwrap(), vec![0x0A]);
+        assert_eq!(reader.read_bits(12).unwrap(), vec![0x0B, 0xCD]);
+    }
+
+    #[test]
+    fn test_read_past_end() {
+        let data = [0xFF];
+        let mut reader = BitReader::new(&data);
+
+        assert!(reader.read_bits(9).is_err());
+        assert_eq!(reader.position(), 0); // failed read does not advance
+    }
+
+    #[test]
+    fn test_write_unaligned_bits() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(&[0b101], 3).unwrap();
+        writer.write_bits(&[0b100_1111], 7).unwrap();
+        assert_eq!(writer.position(), 10);
+        assert_eq!(writer.into_bytes(), vec![0b1011_0011, 0b1100_0000]);
+    }
+
+    #[test]
+    fn test_write_read_roundtrip() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(&[0x0A], 4).unwrap();
+        writer.write_bits(&[0x0B, 0xCD], 12).unwrap();
+        assert!(writer.write_bits(&[0xFF], 9).is_err());
+
+        let data = writer.into_bytes();
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.read_bits(4).unwrap(), vec![0x0A]);
+        assert_eq!(reader.read_bits(12).unwrap(), vec![0x0B, 0xCD]);
+    }
+
+    #[test]
+    fn test_bit_slices() {
+        let data = [0b1011_0011, 0b1100_0101, 0xAB];
+        let mut reader = BitReader::new(&data);
+        let head = reader.read_slice(3).unwrap();
+        let middle = reader.read_slice(13).unwrap();
+        let tail = reader.read_slice(8).unwrap();
+        assert!(reader.read_slice(1).is_err());
+
+        assert_eq!(head.to_u128(false), Some(0b101));
+        assert_eq!(head.as_bytes(), None);
+        assert_eq!(middle.to_bits(false).as_bytes(), &[0b1_0011, 0b1100_0101]);
+        assert_eq!(middle.to_u128(true), middle.to_u128(false));
+        assert_eq!(tail.as_bytes(), Some(&data[2..]));
+
+        let word = BitSlice::new(&data, 8, 16).unwrap();
+        assert_eq!(word.to_u128(false), Some(0xC5AB));
+        assert_eq!(word.to_u128(true), Some(0xABC5));
+        assert_eq!(word.to_bits(true).into_bytes(), vec![0xAB, 0xC5]);
+        assert_eq!(
+            BitSlice::new(&[0xFF; 17], 0, 136).unwrap().to_u128(false),
+            None
+        );
+        assert_eq!(
+            BitSlice::new(&[0; 20], 0, 160).unwrap().to_u128(false),
+            Some(0)
+        );
+
+        // bits before the significant ones are dropped, so the length is never ambiguous
+        let bits = Bits::new(&[0xFF, 0xFF], 10).unwrap();
+        assert_eq!(bits.as_bytes(), &[0b11, 0xFF]);
+        assert_eq!(bits.as_slice().to_u128(false), Some(0x3FF));
+        assert_eq!(
+            Bits::new(&[0xFF], 4).unwrap(),
+            Bits::new(&[0x0F], 4).unwrap()
+        );
+        assert!(Bits::new(&[0xFF], 9).is_err());
+        assert_eq!(Bits::from_bytes(vec![1, 2]).len(), 16);
+    }
+
+    #[test]
+    fn test_bytes_to_u128() {
+        assert_eq!(bytes_to_u128(&[0x01, 0x02]), Some(0x0102));
+        assert_eq!(bytes_to_u128(&[0u8; 20]), Some(0));
+        assert_eq!(bytes_to_u128(&[0xFF; 17]), None);
+    }
+}
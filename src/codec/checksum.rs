@@ -0,0 +1,237 @@
+//! Checksums of packet bytes: CRCs in the parameter model of the CRC catalogues, and the
+//! simple sums used by many serial protocols.
+
+/// The parameters of a CRC as the CRC catalogues list them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CrcParams {
+    /// width in bits, 1 to 64
+    pub width: u32,
+    /// generator polynomial without its top bit, in normal (MSB first) form
+    pub poly: u64,
+    /// register value before the first byte, unreflected
+    pub init: u64,
+    /// whether each input byte is processed least significant bit first
+    pub refin: bool,
+    /// whether the register is reflected before `xorout` is applied
+    pub refout: bool,
+    pub xorout: u64,
+}
+
+impl CrcParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=64).contains(&self.width) {
+            return Err(format!(
+                "CRC width must be from 1 to 64 bits, not {}",
+                self.width
+            ));
+        }
+        for (name, value) in [
+            ("polynomial", self.poly),
+            ("initial value", self.init),
+            ("final XOR", self.xorout),
+        ] {
+            if value & !mask(self.width) != 0 {
+                return Err(format!(
+                    "The {} 0x{:X} is wider than {} bits",
+                    name, value, self.width
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The CRC of `data`, computed bit by bit
+    pub fn compute(&self, data: &[u8]) -> u64 {
+        let top = 1u64 << (self.width - 1);
+        let mut register = self.init & mask(self.width);
+        for &byte in data {
+            let byte = if self.refin {
+                byte.reverse_bits()
+            } else {
+                byte
+            };
+            for i in (0..8).rev() {
+                let bit = (byte >> i) & 1 == 1;
+                let feedback = (register & top != 0) != bit;
+                register = (register << 1) & mask(self.width);
+                if feedback {
+                    register ^= self.poly;
+                }
+            }
+        }
+        if self.refout {
+            register = register.reverse_bits() >> (64 - self.width);
+        }
+        (register ^ self.xorout) & mask(self.width)
+    }
+}
+
+/// The low `bits` bits set
+fn mask(bits: u32) -> u64 {
+    u64::MAX.checked_shr(64 - bits).unwrap_or(0)
+}
+
+/// An algorithm computing a checksum of some bytes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Checksum {
+    Crc(CrcParams),
+    /// sum of the bytes modulo 256
+    Sum8,
+    /// two's complement of the sum of the bytes modulo 256, so the bytes and the checksum sum
+    /// to zero
+    Sum8Complement,
+    /// XOR of the bytes
+    Xor8,
+    /// Fletcher's checksum with two 8-bit sums, the second one in the high byte
+    Fletcher16,
+    /// ones' complement of the ones' complement sum of big-endian 16-bit words, as in IP,
+    /// UDP and TCP headers
+    Internet,
+}
+
+impl Checksum {
+    /// Width of the checksum in bits
+    pub fn width(&self) -> u32 {
+        match self {
+            Checksum::Crc(params) => params.width,
+            Checksum::Sum8 | Checksum::Sum8Complement | Checksum::Xor8 => 8,
+            Checksum::Fletcher16 | Checksum::Internet => 16,
+        }
+    }
+
+    pub fn compute(&self, data: &[u8]) -> u64 {
+        match self {
+            Checksum::Crc(params) => params.compute(data),
+            Checksum::Sum8 => data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) as u64,
+            Checksum::Sum8Complement => data
+                .iter()
+                .fold(0u8, |sum, &b| sum.wrapping_add(b))
+                .wrapping_neg() as u64,
+            Checksum::Xor8 => data.iter().fold(0u8, |sum, &b| sum ^ b) as u64,
+            Checksum::Fletcher16 => {
+                let (mut low, mut high) = (0u16, 0u16);
+                for &byte in data {
+                    low = (low + byte as u16) % 255;
+                    high = (high + low) % 255;
+                }
+                ((high << 8) | low) as u64
+            }
+            Checksum::Internet => {
+                let mut sum: u32 = data
+                    .chunks(2)
+                    .map(|word| u16::from_be_bytes([word[0], *word.get(1).unwrap_or(&0)]) as u32)
+                    .sum();
+                while sum > 0xffff {
+                    sum = (sum & 0xffff) + (sum >> 16);
+                }
+                !(sum as u16) as u64
+            }
+        }
+    }
+}
+
+const fn crc(width: u32, poly: u64, init: u64, reflect: bool, xorout: u64) -> Checksum {
+    Checksum::Crc(CrcParams {
+        width,
+        poly,
+        init,
+        refin: reflect,
+        refout: reflect,
+        xorout,
+    })
+}
+
+/// Common checksums by name; CRCs are named as in the CRC catalogues
+pub const CHECKSUMS: &[(&str, Checksum)] = &[
+    ("CRC-8", crc(8, 0x07, 0, false, 0)),
+    ("CRC-8/MAXIM", crc(8, 0x31, 0, true, 0)),
+    ("CRC-16/ARC", crc(16, 0x8005, 0, true, 0)),
+    ("CRC-16/CCITT-FALSE", crc(16, 0x1021, 0xffff, false, 0)),
+    ("CRC-16/KERMIT", crc(16, 0x1021, 0, true, 0)),
+    ("CRC-16/MODBUS", crc(16, 0x8005, 0xffff, true, 0)),
+    ("CRC-16/XMODEM", crc(16, 0x1021, 0, false, 0)),
+    (
+        "CRC-32",
+        crc(32, 0x04c1_1db7, 0xffff_ffff, true, 0xffff_ffff),
+    ),
+    (
+        "CRC-32C",
+        crc(32, 0x1edc_6f41, 0xffff_ffff, true, 0xffff_ffff),
+    ),
+    (
+        "CRC-64/XZ",
+        crc(64, 0x42f0_e1eb_a9ea_3693, u64::MAX, true, u64::MAX),
+    ),
+    ("Sum8", Checksum::Sum8),
+    ("Sum8 (two's complement)", Checksum::Sum8Complement),
+    ("XOR8", Checksum::Xor8),
+    ("Fletcher-16", Checksum::Fletcher16),
+    ("Internet", Checksum::Internet),
+];
+
+/// The checksum of `CHECKSUMS` with a name, ignoring case
+pub fn checksum_by_name(name: &str) -> Option<Checksum> {
+    CHECKSUMS
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, checksum)| *checksum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogue_check_values() {
+        let expected = [
+            ("CRC-8", 0xf4),
+            ("CRC-8/MAXIM", 0xa1),
+            ("CRC-16/ARC", 0xbb3d),
+            ("CRC-16/CCITT-FALSE", 0x29b1),
+            ("CRC-16/KERMIT", 0x2189),
+            ("CRC-16/MODBUS", 0x4b37),
+            ("CRC-16/XMODEM", 0x31c3),
+            ("CRC-32", 0xcbf4_3926),
+            ("CRC-32C", 0xe306_9283),
+            ("CRC-64/XZ", 0x995d_c9bb_df19_39fa),
+            ("Sum8", 0xdd),
+            ("Sum8 (two's complement)", 0x23),
+            ("XOR8", 0x31),
+            ("Fletcher-16", 0x1ede),
+        ];
+        for (name, check) in expected {
+            let checksum = checksum_by_name(name).unwrap();
+            assert_eq!(checksum.compute(b"123456789"), check, "{}", name);
+        }
+        assert!(checksum_by_name("crc-16/modbus").is_some());
+        assert!(checksum_by_name("CRC-7").is_none());
+    }
+
+    #[test]
+    fn test_internet_checksum() {
+        // an IPv4 header with its checksum field zeroed
+        let header = [
+            0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 0xc0, 0xa8,
+            0x00, 0x01, 0xc0, 0xa8, 0x00, 0xc7,
+        ];
+        assert_eq!(Checksum::Internet.compute(&header), 0xb861);
+        assert_eq!(Checksum::Internet.compute(&[0xff]), 0x00ff);
+    }
+
+    #[test]
+    fn test_crc_params() {
+        let odd = CrcParams {
+            width: 5,
+            poly: 0x05,
+            init: 0x1f,
+            refin: true,
+            refout: true,
+            xorout: 0x1f,
+        };
+        assert_eq!(odd.validate(), Ok(()));
+        // CRC-5/USB
+        assert_eq!(odd.compute(b"123456789"), 0x19);
+        assert!(CrcParams { width: 0, ..odd }.validate().is_err());
+        assert!(CrcParams { poly: 0x25, ..odd }.validate().is_err());
+    }
+}
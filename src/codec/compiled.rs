@@ -0,0 +1,609 @@
+//! A protocol chain prepared once for encoding and decoding many packets: the fields of every
+//! protocol in the chain flattened in wire order with their byte order, and the scripts of
+//! expression fields and validators compiled, so no packet looks up or parses them again.
+
+use super::Value;
+use super::bits::{BitReader, BitWriter};
+use super::decode::{
+    DecodeFailure, DecodedField, DecodedPacket, ValidationIssue, check_padding, decode_field,
+    validate_value,
+};
+use super::dispatch::dispatch_with;
+use super::encode::encode_field;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, ProtocolRegistry, Severity};
+use crate::script::ScriptEngine;
+use rhai::AST;
+use std::collections::{HashMap, HashSet};
+
+/// Encoder and decoder of one protocol, compiled for the engine it is used with. Compile it
+/// again after the protocol or its ancestors change, or keep it in a [`CodecCache`], which
+/// does so itself.
+pub struct CompiledCodec {
+    protocol_id: String,
+    fields: Vec<CompiledField>,
+    /// frame length in bits and the protocol declaring it
+    frame: Option<(u32, String)>,
+    validators: Vec<CompiledValidator>,
+}
+
+/// Codecs compiled the first time a packet is encoded or decoded as their protocol, kept until
+/// the registry changes, for callers handling packet after packet. The scripts do not depend
+/// on the script library, which is only added when they run.
+#[derive(Default)]
+pub struct CodecCache {
+    /// [`ProtocolRegistry::revision`] of the registry the codecs were compiled from
+    revision: u64,
+    codecs: HashMap<String, Result<CompiledCodec, String>>,
+}
+
+impl CodecCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The codec of a protocol, compiled unless it was since the registry last changed
+    pub fn get(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+    ) -> Result<&CompiledCodec, String> {
+        if self.revision != registry.revision() {
+            self.codecs.clear();
+            self.revision = registry.revision();
+        }
+        self.codecs
+            .entry(protocol_id.to_string())
+            .or_insert_with(|| CompiledCodec::compile(registry, engine, protocol_id))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Decode `data` as [`crate::codec::decode::decode`] does
+    pub fn decode(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+        data: &[u8],
+    ) -> Result<DecodedPacket, String> {
+        self.get(registry, engine, protocol_id)?
+            .decode(engine, data)
+    }
+
+    /// Decode `data` as [`crate::codec::dispatch::decode_dispatched`] does
+    pub fn decode_dispatched(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+        data: &[u8],
+    ) -> Result<DecodedPacket, String> {
+        dispatch_with(registry, protocol_id, |id| {
+            self.decode(registry, engine, id, data)
+        })
+    }
+
+    /// Encode field values as [`crate::codec::encode::encode`] does
+    pub fn encode(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+        values: &HashMap<String, Value>,
+    ) -> Result<Vec<u8>, String> {
+        self.get(registry, engine, protocol_id)?
+            .encode(engine, values)
+    }
+}
+
+struct CompiledField {
+    rule: FieldRule,
+    /// ID of the protocol in the chain that defines the field
+    protocol_id: String,
+    byte_order: Endianness,
+    /// the script of an expression or derived field; a syntax error is reported when the field
+    /// is evaluated, as it would be without compiling
+    script: Option<Result<AST, String>>,
+}
+
+struct CompiledValidator {
+    protocol_id: String,
+    name: String,
+    severity: Severity,
+    script: Result<AST, String>,
+}
+
+impl CompiledCodec {
+    pub fn compile(
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+    ) -> Result<Self, String> {
+        let chain = registry.get_inheritance_chain(protocol_id);
+        if chain.is_empty() {
+            return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+        }
+        let compile = |script: &str| engine.compile(script).map_err(|e| e.to_string());
+
+        let mut fields = Vec::new();
+        let mut validators = Vec::new();
+        for proto in &chain {
+            for rule in &proto.fields {
+                fields.push(CompiledField {
+                    rule: rule.clone(),
+                    protocol_id: proto.id.clone(),
+                    byte_order: rule.byte_order(proto.endianness),
+                    script: rule.field_type.script().map(compile),
+                });
+            }
+            for validator in &proto.validators {
+                validators.push(CompiledValidator {
+                    protocol_id: proto.id.clone(),
+                    name: validator.name.clone(),
+                    severity: validator.severity,
+                    script: compile(&validator.script),
+                });
+            }
+        }
+        Ok(Self {
+            protocol_id: protocol_id.to_string(),
+            fields,
+            frame: registry
+                .frame_length(protocol_id)
+                .map(|(bits, id)| (bits, id.to_string())),
+            validators,
+        })
+    }
+
+    pub fn protocol_id(&self) -> &str {
+        &self.protocol_id
+    }
+
+    /// Override the rules of some fields, as [`crate::models::field::Field::ignore_rules`]
+    /// does: their values are encoded and decoded even if they break the rules, which are
+    /// reported as warnings of the packet instead
+    pub fn ignore_rules(&mut self, field_ids: &HashSet<String>) {
+        for field in &mut self.fields {
+            if field_ids.contains(&field.rule.id) {
+                field.rule.severity = Severity::Warning;
+            }
+        }
+    }
+
+    /// Read and write every multi-byte field in the opposite byte order, to see what a packet
+    /// would be if the protocol had the other endianness
+    pub fn swap_byte_order(&mut self) {
+        for field in &mut self.fields {
+            field.byte_order = match field.byte_order {
+                Endianness::Big => Endianness::Little,
+                Endianness::Little => Endianness::Big,
+            };
+        }
+    }
+
+    /// Decode `data`, as [`crate::codec::decode::decode`] does
+    pub fn decode(&self, engine: &ScriptEngine, data: &[u8]) -> Result<DecodedPacket, String> {
+        self.decode_partial(engine, data)
+            .map_err(|failure| failure.message)
+    }
+
+    /// Like [`Self::decode`], but a failure keeps the fields decoded before the one that failed
+    pub fn decode_partial(
+        &self,
+        engine: &ScriptEngine,
+        data: &[u8],
+    ) -> Result<DecodedPacket, Box<DecodeFailure>> {
+        let fail =
+            |message, decoded: &[Option<DecodedField>], field_id: &str, bit_offset, bit_len| {
+                Box::new(DecodeFailure {
+                    message,
+                    packet: DecodedPacket {
+                        protocol_id: self.protocol_id.clone(),
+                        fields: decoded.iter().flatten().cloned().collect(),
+                        issues: Vec::new(),
+                    },
+                    field_id: Some(field_id.to_string()),
+                    bit_offset,
+                    bit_len,
+                })
+            };
+
+        // a variable length field ends where the frame does, not at the end of the data
+        let framed = match &self.frame {
+            Some((bits, _)) => &data[..data.len().min((*bits as usize).div_ceil(8))],
+            None => data,
+        };
+        let mut reader = BitReader::new(framed);
+        let mut decoded: Vec<Option<DecodedField>> = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            if field.rule.is_virtual() {
+                decoded.push(None);
+                continue;
+            }
+            let bit_offset = reader.position();
+            match decode_field(
+                &mut reader,
+                &field.rule,
+                &field.protocol_id,
+                field.byte_order,
+            ) {
+                Ok(value) => decoded.push(Some(value)),
+                Err(message) => {
+                    let bit_len = match field.rule.length {
+                        FieldLength::Fixed(bits) => bits as usize,
+                        FieldLength::Variable => reader.remaining(),
+                    };
+                    return Err(fail(message, &decoded, &field.rule.id, bit_offset, bit_len));
+                }
+            }
+        }
+
+        for (i, field) in self.fields.iter().enumerate() {
+            let Some(script) = &field.script else {
+                continue;
+            };
+            if decoded[i].is_some() || !matches!(field.rule.field_type, FieldType::Derived(_)) {
+                continue;
+            }
+            let vars: Vec<(&str, &Value)> = decoded
+                .iter()
+                .flatten()
+                .map(|d| (d.rule_id.as_str(), &d.value))
+                .collect();
+            let value = match script
+                .as_ref()
+                .map_err(Clone::clone)
+                .and_then(|ast| engine.eval_ast(ast, &vars))
+            {
+                Ok(value) => value,
+                Err(e) => {
+                    let message = format!(
+                        "Failed to evaluate derived field '{}': {}",
+                        field.rule.id, e
+                    );
+                    return Err(fail(
+                        message,
+                        &decoded,
+                        &field.rule.id,
+                        reader.position(),
+                        0,
+                    ));
+                }
+            };
+            decoded[i] = Some(DecodedField {
+                rule_id: field.rule.id.clone(),
+                protocol_id: field.protocol_id.clone(),
+                bit_offset: reader.position(),
+                bit_len: 0,
+                value,
+                is_virtual: true,
+            });
+        }
+
+        let mut packet = DecodedPacket {
+            protocol_id: self.protocol_id.clone(),
+            fields: decoded.into_iter().flatten().collect(),
+            issues: Vec::new(),
+        };
+        if let Some((bits, frame_id)) = &self.frame {
+            packet.issues = check_padding(&mut reader, data.len(), *bits, frame_id);
+        }
+        packet.issues.extend(self.validate(engine, &packet));
+        Ok(packet)
+    }
+
+    /// Check the values of fields whose rules are of warning severity, then run the packet
+    /// validators of the protocol and its parents, root first. A validator that fails to run
+    /// is reported as an error issue.
+    pub fn validate(&self, engine: &ScriptEngine, packet: &DecodedPacket) -> Vec<ValidationIssue> {
+        let vars: Vec<(&str, &Value)> = packet
+            .fields
+            .iter()
+            .map(|f| (f.rule_id.as_str(), &f.value))
+            .collect();
+
+        let mut issues = Vec::new();
+        for field in &self.fields {
+            if field.rule.severity != Severity::Warning {
+                continue;
+            }
+            let Some(decoded) = packet.get(&field.rule.id) else {
+                continue;
+            };
+            if let Err(message) = validate_value(&field.rule, &decoded.value) {
+                issues.push(ValidationIssue {
+                    protocol_id: field.protocol_id.clone(),
+                    validator: format!("field '{}'", field.rule.id),
+                    severity: Severity::Warning,
+                    message,
+                });
+            }
+        }
+        for validator in &self.validators {
+            let issue = |severity, message| ValidationIssue {
+                protocol_id: validator.protocol_id.clone(),
+                validator: validator.name.clone(),
+                severity,
+                message,
+            };
+            let result = validator
+                .script
+                .as_ref()
+                .map_err(Clone::clone)
+                .and_then(|ast| engine.validate_ast(ast, &vars));
+            match result {
+                Ok(messages) => issues.extend(
+                    messages
+                        .into_iter()
+                        .map(|message| issue(validator.severity, message)),
+                ),
+                Err(e) => issues.push(issue(
+                    Severity::Error,
+                    format!("Validator failed to run: {}", e),
+                )),
+            }
+        }
+        issues
+    }
+
+    /// Encode field values keyed by field ID, as [`crate::codec::encode::encode`] does
+    pub fn encode(
+        &self,
+        engine: &ScriptEngine,
+        values: &HashMap<String, Value>,
+    ) -> Result<Vec<u8>, String> {
+        let wire: Vec<&CompiledField> = self
+            .fields
+            .iter()
+            .filter(|f| !f.rule.is_virtual())
+            .collect();
+        let mut resolved: Vec<Option<Value>> = Vec::with_capacity(wire.len());
+        for field in &wire {
+            resolved.push(match &field.rule.field_type {
+                FieldType::Fixed(v) => Some(Value::Int(*v)),
+                FieldType::Expr(_) => None,
+                _ => Some(
+                    values
+                        .get(&field.rule.id)
+                        .cloned()
+                        .ok_or_else(|| format!("No value given for field '{}'", field.rule.id))?,
+                ),
+            });
+        }
+
+        for (i, field) in wire.iter().enumerate() {
+            let (None, Some(script)) = (&resolved[i], &field.script) else {
+                continue;
+            };
+            let vars: Vec<(&str, &Value)> = wire
+                .iter()
+                .zip(&resolved)
+                .filter_map(|(f, v)| Some((f.rule.id.as_str(), v.as_ref()?)))
+                .collect();
+            let value = script
+                .as_ref()
+                .map_err(Clone::clone)
+                .and_then(|ast| engine.eval_ast(ast, &vars))
+                .map_err(|e| {
+                    format!(
+                        "Failed to evaluate expression of '{}': {}",
+                        field.rule.id, e
+                    )
+                })?;
+            resolved[i] = Some(value);
+        }
+
+        let mut writer = BitWriter::new();
+        for (field, value) in wire.iter().zip(&resolved) {
+            let value = value.as_ref().expect("all field values are resolved");
+            // out-of-spec values of warning severity are encoded as they are, e.g. for
+            // negative testing
+            if field.rule.severity == Severity::Error {
+                validate_value(&field.rule, value)?;
+            }
+            encode_field(&mut writer, &field.rule, field.byte_order, value)?;
+        }
+
+        if let Some((frame_bits, frame_id)) = &self.frame {
+            let padding = (*frame_bits as usize)
+                .checked_sub(writer.position())
+                .ok_or_else(|| {
+                    format!(
+                        "Fields take {} bits, more than the frame length of {} bits of '{}'",
+                        writer.position(),
+                        frame_bits,
+                        frame_id
+                    )
+                })?;
+            writer.write_bits(&vec![0; padding.div_ceil(8)], padding)?;
+        }
+        Ok(writer.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::protocol::PacketValidator;
+
+    #[test]
+    fn test_compiled_codec_round_trip() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("base", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .create_protocol("msg", None, Endianness::Little, Some("base".to_string()))
+            .unwrap();
+        registry
+            .edit_protocol("base", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.validators.push(PacketValidator {
+                    name: "kind".to_string(),
+                    severity: Severity::Warning,
+                    script: "if kind > 5 { \"kind too high\" }".to_string(),
+                });
+                Ok(())
+            })
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "value",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "sum",
+                    FieldType::Expr("(kind + value) & 0xff".to_string()),
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "broken",
+                    FieldType::Derived("value +".to_string()),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let codec = CompiledCodec::compile(&registry, &engine, "msg").unwrap();
+        assert_eq!(codec.protocol_id(), "msg");
+
+        for (kind, value) in [(1, 0x0102), (7, 0xfffe)] {
+            let values = HashMap::from([
+                ("kind".to_string(), Value::Int(kind)),
+                ("value".to_string(), Value::Int(value)),
+            ]);
+            let data = codec.encode(&engine, &values).unwrap();
+            assert_eq!(data[1..3], (value as u16).to_le_bytes());
+            assert_eq!(data[3], ((kind + value) & 0xff) as u8);
+
+            // the syntax error of the derived field shows when it is evaluated
+            let failure = codec.decode_partial(&engine, &data).unwrap_err();
+            assert_eq!(failure.field_id.as_deref(), Some("broken"));
+            assert_eq!(failure.packet.fields.len(), 3);
+        }
+        let failure = codec.decode_partial(&engine, &[1]).unwrap_err();
+        assert_eq!(failure.field_id.as_deref(), Some("value"));
+
+        registry
+            .edit_protocol("msg", |p| p.remove_field("broken"))
+            .unwrap();
+        let codec = CompiledCodec::compile(&registry, &engine, "msg").unwrap();
+        let packet = codec.decode(&engine, &[7, 0, 0, 7]).unwrap();
+        assert_eq!(packet.issues.len(), 1);
+        assert_eq!(packet.issues[0].message, "kind too high");
+        assert!(CompiledCodec::compile(&registry, &engine, "missing").is_err());
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "level",
+                    FieldType::Range {
+                        min: 0,
+                        max: 100,
+                        is_signed: false,
+                    },
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let values = HashMap::from([("level".to_string(), Value::Int(200))]);
+        let mut codec = CompiledCodec::compile(&registry, &engine, "msg").unwrap();
+        assert!(codec.encode(&engine, &values).is_err());
+        assert!(codec.decode(&engine, &[200]).is_err());
+
+        codec.ignore_rules(&HashSet::from(["level".to_string()]));
+        assert_eq!(codec.encode(&engine, &values).unwrap(), [200]);
+        let packet = codec.decode(&engine, &[200]).unwrap();
+        assert_eq!(packet.issues.len(), 1);
+        assert_eq!(packet.issues[0].severity, Severity::Warning);
+        assert_eq!(packet.issues[0].validator, "field 'level'");
+    }
+
+    #[test]
+    fn test_swap_byte_order() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "length",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                let mut counter =
+                    FieldRule::new("counter", FieldType::Input, FieldLength::Fixed(16));
+                counter.endianness = Some(Endianness::Little);
+                p.add_field(counter)
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let mut codec = CompiledCodec::compile(&registry, &engine, "msg").unwrap();
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let packet = codec.decode(&engine, &data).unwrap();
+        assert_eq!(packet.get("length").unwrap().value, Value::Int(0x0102));
+        assert_eq!(packet.get("counter").unwrap().value, Value::Int(0x0403));
+
+        codec.swap_byte_order();
+        let packet = codec.decode(&engine, &data).unwrap();
+        assert_eq!(packet.get("length").unwrap().value, Value::Int(0x0201));
+        assert_eq!(packet.get("counter").unwrap().value, Value::Int(0x0304));
+    }
+
+    #[test]
+    fn test_codec_cache() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let mut codecs = CodecCache::new();
+        let first: *const CompiledCodec = codecs.get(&registry, &engine, "msg").unwrap();
+        let again: *const CompiledCodec = codecs.get(&registry, &engine, "msg").unwrap();
+        assert_eq!(first, again);
+        assert!(codecs.decode(&registry, &engine, "missing", &[1]).is_err());
+
+        // a change to the protocols compiles them again
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "value",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let packet = codecs.decode(&registry, &engine, "msg", &[1, 2]).unwrap();
+        assert_eq!(packet.get("value").unwrap().value, Value::Int(2));
+        registry
+            .create_protocol("missing", None, Endianness::Big, None)
+            .unwrap();
+        assert!(codecs.decode(&registry, &engine, "missing", &[]).is_ok());
+    }
+}
@@ -0,0 +1,655 @@
+use super::Value;
+use super::bits::BitReader;
+use super::compiled::CompiledCodec;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, ProtocolRegistry, Severity};
+use crate::script::ScriptEngine;
+
+/// A single field value decoded from a packet, or computed for a virtual field
+#[derive(Clone, PartialEq, Debug)]
+pub struct DecodedField {
+    pub rule_id: String,
+    /// ID of the protocol in the inheritance chain that defines the field
+    pub protocol_id: String,
+    /// Offset from the start of the packet in bits
+    pub bit_offset: usize,
+    /// Length on the wire in bits; always 0 for virtual fields
+    pub bit_len: usize,
+    pub value: Value,
+    pub is_virtual: bool,
+}
+
+/// A problem reported by one of the packet validators of a protocol
+#[derive(Clone, PartialEq, Debug)]
+pub struct ValidationIssue {
+    /// ID of the protocol in the inheritance chain that defines the validator
+    pub protocol_id: String,
+    pub validator: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DecodedPacket {
+    pub protocol_id: String,
+    pub fields: Vec<DecodedField>,
+    /// problems reported by the packet validators of the protocol and its parents
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl DecodedPacket {
+    pub fn get(&self, field_id: &str) -> Option<&DecodedField> {
+        self.fields.iter().find(|f| f.rule_id == field_id)
+    }
+}
+
+/// Why decoding a packet failed, with what was decoded up to that point
+#[derive(Clone, PartialEq, Debug)]
+pub struct DecodeFailure {
+    pub message: String,
+    /// the fields decoded before the failure, without validation issues
+    pub packet: DecodedPacket,
+    /// ID of the field that could not be decoded or evaluated, if any
+    pub field_id: Option<String>,
+    /// offset of the failed field in bits, where the undecoded part of the packet starts
+    pub bit_offset: usize,
+    /// length of the failed field in bits; 0 for virtual fields
+    pub bit_len: usize,
+}
+
+/// Decode `data` as an instance of `protocol_id`, including all fields inherited from its parents.
+///
+/// Wire fields are decoded in order first; virtual fields are then evaluated in declaration order
+/// with every wire field (and any earlier virtual field) in scope. Finally the packet validators
+/// of the inheritance chain are run on the complete packet.
+///
+/// The scripts of the protocols are compiled for every call; decode packet after packet with a
+/// [`CodecCache`](super::compiled::CodecCache) instead.
+pub fn decode(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    data: &[u8],
+) -> Result<DecodedPacket, String> {
+    decode_partial(registry, engine, protocol_id, data).map_err(|failure| failure.message)
+}
+
+/// Like [`decode`], but a failure keeps the fields decoded before the one that failed
+pub fn decode_partial(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    data: &[u8],
+) -> Result<DecodedPacket, Box<DecodeFailure>> {
+    let codec = CompiledCodec::compile(registry, engine, protocol_id).map_err(|message| {
+        Box::new(DecodeFailure {
+            message,
+            packet: DecodedPacket {
+                protocol_id: protocol_id.to_string(),
+                fields: Vec::new(),
+                issues: Vec::new(),
+            },
+            field_id: None,
+            bit_offset: 0,
+            bit_len: 0,
+        })
+    })?;
+    codec.decode_partial(engine, data)
+}
+
+/// Check that a packet of a protocol with a frame length has that length, and that the rest of
+/// the frame after the fields is zero padding. Problems are reported as warnings.
+pub(super) fn check_padding(
+    reader: &mut BitReader,
+    data_len: usize,
+    frame_bits: u32,
+    frame_id: &str,
+) -> Vec<ValidationIssue> {
+    let issue = |message| ValidationIssue {
+        protocol_id: frame_id.to_string(),
+        validator: "frame length".to_string(),
+        severity: Severity::Warning,
+        message,
+    };
+
+    let mut issues = Vec::new();
+    let frame_bits = frame_bits as usize;
+    if data_len * 8 < frame_bits {
+        issues.push(issue(format!(
+            "Packet is {} bits, shorter than the frame length of {} bits",
+            data_len * 8,
+            frame_bits
+        )));
+    } else if data_len > frame_bits.div_ceil(8) {
+        issues.push(issue(match data_len - frame_bits.div_ceil(8) {
+            1 => format!("1 byte follows the end of the {} bit frame", frame_bits),
+            extra => format!(
+                "{} bytes follow the end of the {} bit frame",
+                extra, frame_bits
+            ),
+        }));
+    }
+
+    let start = reader.position();
+    let padding = frame_bits
+        .min(start + reader.remaining())
+        .saturating_sub(start);
+    if let Ok(bits) = reader.read_bits(padding)
+        && bits.iter().any(|&b| b != 0)
+    {
+        issues.push(issue(format!(
+            "Padding from bit {} to the end of the frame is not zero",
+            start
+        )));
+    }
+    issues
+}
+
+/// Run the packet validators of the packet's protocol and its parents, root first.
+/// A validator that fails to run is reported as an error issue.
+pub fn validate_packet(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    packet: &DecodedPacket,
+) -> Vec<ValidationIssue> {
+    match CompiledCodec::compile(registry, engine, &packet.protocol_id) {
+        Ok(codec) => codec.validate(engine, packet),
+        Err(e) => vec![ValidationIssue {
+            protocol_id: packet.protocol_id.clone(),
+            validator: "validators".to_string(),
+            severity: Severity::Error,
+            message: format!("Validators failed to run: {}", e),
+        }],
+    }
+}
+
+pub(super) fn decode_field(
+    reader: &mut BitReader,
+    rule: &FieldRule,
+    protocol_id: &str,
+    endianness: Endianness,
+) -> Result<DecodedField, String> {
+    let bit_offset = reader.position();
+    let bit_len = match rule.length {
+        FieldLength::Fixed(bits) => bits as usize,
+        FieldLength::Variable => reader.remaining(),
+    };
+
+    let slice = reader
+        .read_slice(bit_len)
+        .map_err(|e| format!("Failed to decode field '{}': {}", rule.id, e))?;
+    let swap_bytes = endianness == Endianness::Little;
+
+    // integers are read straight from the packet; only byte strings are copied out. Fields
+    // wider than 128 bits are always byte strings, even when their value would fit
+    let value = match rule.length {
+        FieldLength::Fixed(bits) if bits <= 128 => {
+            let v = slice.to_u128(swap_bytes).unwrap_or_default();
+            if rule.is_signed() {
+                Value::Int(rule.sign_encoding.decode(v, bits))
+            } else {
+                Value::Int(v as i128)
+            }
+        }
+        _ => Value::Bytes(slice.to_bits(swap_bytes).into_bytes()),
+    };
+    // values breaking a rule of warning severity are reported once the packet is decoded
+    if rule.severity == Severity::Error {
+        validate_value(rule, &value)?;
+    }
+
+    Ok(DecodedField {
+        rule_id: rule.id.clone(),
+        protocol_id: protocol_id.to_string(),
+        bit_offset,
+        bit_len,
+        value,
+        is_virtual: false,
+    })
+}
+
+/// Check a decoded wire value against the constraints of its field rule
+pub(super) fn validate_value(rule: &FieldRule, value: &Value) -> Result<(), String> {
+    let Value::Int(v) = *value else {
+        return Ok(());
+    };
+
+    match &rule.field_type {
+        FieldType::Fixed(expected) if v != *expected => Err(format!(
+            "Field '{}' has value {}, expected fixed value {}",
+            rule.id, v, expected
+        )),
+        FieldType::Enum(variants) if !variants.iter().any(|var| var.value == v) => Err(format!(
+            "Field '{}' has value {} which is not a defined enum variant",
+            rule.id, v
+        )),
+        FieldType::Range { min, max, .. } if v < *min || v > *max => Err(format!(
+            "Field '{}' has value {} outside of range {}..={}",
+            rule.id, v, min, max
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::EnumVariant;
+    use crate::models::protocol::PacketValidator;
+
+    fn registry_with(fields: Vec<FieldRule>, endianness: Endianness) -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("proto", None, endianness, None)
+            .unwrap();
+        registry
+            .edit_protocol("proto", |p| {
+                for field in fields {
+                    p.add_field(field)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_decode_fixed_fields() {
+        let registry = registry_with(
+            vec![
+                FieldRule::new("version", FieldType::Fixed(4), FieldLength::Fixed(4)),
+                FieldRule::new("flags", FieldType::Input, FieldLength::Fixed(4)),
+                FieldRule::new("length", FieldType::Input, FieldLength::Fixed(16)),
+            ],
+            Endianness::Big,
+        );
+
+        let packet = decode(
+            &registry,
+            &ScriptEngine::new(),
+            "proto",
+            &[0x4A, 0x01, 0x02],
+        )
+        .unwrap();
+        assert_eq!(packet.fields.len(), 3);
+        assert_eq!(packet.get("flags").unwrap().value, Value::Int(0xA));
+        assert_eq!(packet.get("length").unwrap().value, Value::Int(0x0102));
+        assert_eq!(packet.get("length").unwrap().bit_offset, 8);
+    }
+
+    #[test]
+    fn test_decode_signed_fields() {
+        let signed = |id: &str, bits| {
+            FieldRule::new(
+                id,
+                FieldType::Range {
+                    min: -2048,
+                    max: 2047,
+                    is_signed: true,
+                },
+                FieldLength::Fixed(bits),
+            )
+        };
+        let mut sign_magnitude = signed("offset", 8);
+        sign_magnitude.sign_encoding = crate::models::field::SignEncoding::SignMagnitude;
+        let registry = registry_with(
+            vec![signed("level", 12), signed("trim", 4), sign_magnitude],
+            Endianness::Big,
+        );
+        let engine = ScriptEngine::new();
+
+        let data = [0xFF, 0xE7, 0x85];
+        let packet = decode(&registry, &engine, "proto", &data).unwrap();
+        assert_eq!(packet.get("level").unwrap().value, Value::Int(-2));
+        assert_eq!(packet.get("trim").unwrap().value, Value::Int(7));
+        assert_eq!(packet.get("offset").unwrap().value, Value::Int(-5));
+
+        let values = packet
+            .fields
+            .iter()
+            .map(|f| (f.rule_id.clone(), f.value.clone()))
+            .collect();
+        let encoded = crate::codec::encode::encode(&registry, &engine, "proto", &values);
+        assert_eq!(encoded.unwrap(), data);
+    }
+
+    #[test]
+    fn test_warning_severity_field() {
+        let mut level = FieldRule::new(
+            "level",
+            FieldType::Range {
+                min: 0,
+                max: 100,
+                is_signed: false,
+            },
+            FieldLength::Fixed(8),
+        );
+        level.severity = Severity::Warning;
+        let mut registry = registry_with(vec![level], Endianness::Big);
+        let engine = ScriptEngine::new();
+
+        let values = [("level".to_string(), Value::Int(200))].into();
+        let data = crate::codec::encode::encode(&registry, &engine, "proto", &values).unwrap();
+        let packet = decode(&registry, &engine, "proto", &data).unwrap();
+        assert_eq!(packet.get("level").unwrap().value, Value::Int(200));
+        assert_eq!(packet.issues.len(), 1);
+        assert_eq!(packet.issues[0].severity, Severity::Warning);
+        assert_eq!(packet.issues[0].validator, "field 'level'");
+        assert!(
+            decode(&registry, &engine, "proto", &[50])
+                .unwrap()
+                .issues
+                .is_empty()
+        );
+
+        registry
+            .edit_protocol("proto", |p| {
+                p.edit_field("level", |f| {
+                    f.severity = Severity::Error;
+                    Ok(())
+                })
+            })
+            .unwrap();
+        assert!(decode(&registry, &engine, "proto", &data).is_err());
+        assert!(crate::codec::encode::encode(&registry, &engine, "proto", &values).is_err());
+    }
+
+    #[test]
+    fn test_decode_little_endian() {
+        let registry = registry_with(
+            vec![FieldRule::new(
+                "length",
+                FieldType::Input,
+                FieldLength::Fixed(16),
+            )],
+            Endianness::Little,
+        );
+
+        let packet = decode(&registry, &ScriptEngine::new(), "proto", &[0x01, 0x02]).unwrap();
+        assert_eq!(packet.get("length").unwrap().value, Value::Int(0x0201));
+    }
+
+    #[test]
+    fn test_decode_field_byte_order_override() {
+        let mut big = FieldRule::new("big", FieldType::Input, FieldLength::Fixed(16));
+        big.endianness = Some(Endianness::Big);
+        let registry = registry_with(
+            vec![
+                big,
+                FieldRule::new("little", FieldType::Input, FieldLength::Fixed(16)),
+            ],
+            Endianness::Little,
+        );
+
+        let packet = decode(
+            &registry,
+            &ScriptEngine::new(),
+            "proto",
+            &[0x01, 0x02, 0x01, 0x02],
+        )
+        .unwrap();
+        assert_eq!(packet.get("big").unwrap().value, Value::Int(0x0102));
+        assert_eq!(packet.get("little").unwrap().value, Value::Int(0x0201));
+    }
+
+    #[test]
+    fn test_decode_frame_padding() {
+        let mut registry = registry_with(
+            vec![FieldRule::new(
+                "id",
+                FieldType::Input,
+                FieldLength::Fixed(8),
+            )],
+            Endianness::Big,
+        );
+        registry
+            .edit_protocol("proto", |p| {
+                p.frame_length = Some(32);
+                Ok(())
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let messages = |data: &[u8]| -> Vec<String> {
+            let packet = decode(&registry, &engine, "proto", data).unwrap();
+            assert_eq!(packet.get("id").unwrap().value, Value::Int(7));
+            packet.issues.into_iter().map(|i| i.message).collect()
+        };
+
+        assert!(messages(&[7, 0, 0, 0]).is_empty());
+        assert_eq!(
+            messages(&[7, 0, 1, 0]),
+            vec!["Padding from bit 8 to the end of the frame is not zero"]
+        );
+        assert_eq!(
+            messages(&[7, 0]),
+            vec!["Packet is 16 bits, shorter than the frame length of 32 bits"]
+        );
+        assert_eq!(
+            messages(&[7, 0, 0, 0, 0xFF]),
+            vec!["1 byte follows the end of the 32 bit frame"]
+        );
+        assert_eq!(
+            messages(&[7, 0, 0, 0, 0xFF, 0]),
+            vec!["2 bytes follow the end of the 32 bit frame"]
+        );
+    }
+
+    #[test]
+    fn test_decode_wide_fields() {
+        let registry = registry_with(
+            vec![FieldRule::new(
+                "key",
+                FieldType::Input,
+                FieldLength::Fixed(136),
+            )],
+            Endianness::Big,
+        );
+        let engine = ScriptEngine::new();
+
+        // the type of the value depends on the width of the field, not on the value
+        let mut data = [0u8; 17];
+        data[16] = 0x01;
+        let packet = decode(&registry, &engine, "proto", &data).unwrap();
+        assert_eq!(
+            packet.get("key").unwrap().value,
+            Value::Bytes(data.to_vec())
+        );
+        data[0] = 0xFF;
+        let packet = decode(&registry, &engine, "proto", &data).unwrap();
+        assert_eq!(
+            packet.get("key").unwrap().value,
+            Value::Bytes(data.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_decode_variable_length_payload() {
+        let registry = registry_with(
+            vec![
+                FieldRule::new("kind", FieldType::Input, FieldLength::Fixed(8)),
+                FieldRule::new("payload", FieldType::Input, FieldLength::Variable),
+            ],
+            Endianness::Big,
+        );
+
+        let packet = decode(
+            &registry,
+            &ScriptEngine::new(),
+            "proto",
+            &[0x01, 0xAA, 0xBB],
+        )
+        .unwrap();
+        assert_eq!(
+            packet.get("payload").unwrap().value,
+            Value::Bytes(vec![0xAA, 0xBB])
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_enum_value() {
+        let variants = vec![EnumVariant {
+            value: 1,
+            name: Some("Ping".to_string()),
+            description: None,
+        }];
+        let registry = registry_with(
+            vec![FieldRule::new(
+                "kind",
+                FieldType::Enum(variants),
+                FieldLength::Fixed(8),
+            )],
+            Endianness::Big,
+        );
+
+        assert!(decode(&registry, &ScriptEngine::new(), "proto", &[0x01]).is_ok());
+        assert!(decode(&registry, &ScriptEngine::new(), "proto", &[0x02]).is_err());
+    }
+
+    #[test]
+    fn test_decode_truncated_packet() {
+        let registry = registry_with(
+            vec![FieldRule::new(
+                "length",
+                FieldType::Input,
+                FieldLength::Fixed(16),
+            )],
+            Endianness::Big,
+        );
+
+        assert!(decode(&registry, &ScriptEngine::new(), "proto", &[0x01]).is_err());
+    }
+
+    #[test]
+    fn test_decode_partial_keeps_prefix() {
+        let registry = registry_with(
+            vec![
+                FieldRule::new("version", FieldType::Input, FieldLength::Fixed(8)),
+                FieldRule::new("kind", FieldType::Fixed(7), FieldLength::Fixed(4)),
+                FieldRule::new("length", FieldType::Input, FieldLength::Fixed(16)),
+            ],
+            Endianness::Big,
+        );
+        let engine = ScriptEngine::new();
+
+        let failure = decode_partial(&registry, &engine, "proto", &[0x01, 0x30]).unwrap_err();
+        assert_eq!(failure.field_id.as_deref(), Some("kind"));
+        assert_eq!((failure.bit_offset, failure.bit_len), (8, 4));
+        assert_eq!(failure.packet.fields.len(), 1);
+        assert_eq!(failure.packet.fields[0].value, Value::Int(1));
+
+        let failure = decode_partial(&registry, &engine, "proto", &[0x01, 0x70]).unwrap_err();
+        assert_eq!(failure.field_id.as_deref(), Some("length"));
+        assert_eq!((failure.bit_offset, failure.bit_len), (12, 16));
+        assert_eq!(failure.packet.fields.len(), 2);
+        assert_eq!(
+            decode(&registry, &engine, "proto", &[0x01, 0x70]),
+            Err(failure.message)
+        );
+    }
+
+    #[test]
+    fn test_decode_derived_fields() {
+        let registry = registry_with(
+            vec![
+                FieldRule::new(
+                    "temperature_celsius",
+                    FieldType::Derived("raw_temp / 10.0".to_string()),
+                    FieldLength::Fixed(0),
+                ),
+                FieldRule::new("raw_temp", FieldType::Input, FieldLength::Fixed(16)),
+                FieldRule::new(
+                    "is_hot",
+                    FieldType::Derived("temperature_celsius > 30.0".to_string()),
+                    FieldLength::Fixed(0),
+                ),
+            ],
+            Endianness::Big,
+        );
+
+        let packet = decode(&registry, &ScriptEngine::new(), "proto", &[0x00, 0xEB]).unwrap();
+        let ids: Vec<&str> = packet.fields.iter().map(|f| f.rule_id.as_str()).collect();
+        assert_eq!(ids, vec!["temperature_celsius", "raw_temp", "is_hot"]);
+
+        let temperature = packet.get("temperature_celsius").unwrap();
+        assert!(temperature.is_virtual);
+        assert_eq!(temperature.bit_len, 0);
+        assert_eq!(temperature.value, Value::Float(23.5));
+        assert_eq!(packet.get("is_hot").unwrap().value, Value::Bool(false));
+    }
+
+    #[test]
+    fn test_decode_inherited_fields() {
+        let mut registry = registry_with(
+            vec![FieldRule::new(
+                "kind",
+                FieldType::Fixed(1),
+                FieldLength::Fixed(8),
+            )],
+            Endianness::Big,
+        );
+        registry
+            .create_protocol("child", None, Endianness::Big, Some("proto".to_string()))
+            .unwrap();
+        registry
+            .edit_protocol("child", |p| {
+                p.add_field(FieldRule::new(
+                    "value",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+
+        let packet = decode(&registry, &ScriptEngine::new(), "child", &[0x01, 0x7F]).unwrap();
+        assert_eq!(packet.get("kind").unwrap().protocol_id, "proto");
+        assert_eq!(packet.get("value").unwrap().protocol_id, "child");
+        assert_eq!(packet.get("value").unwrap().value, Value::Int(0x7F));
+    }
+
+    #[test]
+    fn test_decode_runs_validators() {
+        let mut registry = registry_with(
+            vec![
+                FieldRule::new("length", FieldType::Input, FieldLength::Fixed(8)),
+                FieldRule::new("payload", FieldType::Input, FieldLength::Variable),
+            ],
+            Endianness::Big,
+        );
+        registry
+            .edit_protocol("proto", |p| {
+                p.validators.push(PacketValidator {
+                    name: "length".to_string(),
+                    severity: Severity::Warning,
+                    script: r#"if length != payload.len() { "length inconsistent with payload" }"#
+                        .to_string(),
+                });
+                p.validators.push(PacketValidator {
+                    name: "broken".to_string(),
+                    severity: Severity::Warning,
+                    script: "missing_field > 0".to_string(),
+                });
+                Ok(())
+            })
+            .unwrap();
+
+        let packet = decode(&registry, &ScriptEngine::new(), "proto", &[0x03, 0xAA]).unwrap();
+        assert_eq!(packet.issues.len(), 2);
+        assert_eq!(packet.issues[0].severity, Severity::Warning);
+        assert_eq!(packet.issues[0].message, "length inconsistent with payload");
+        assert_eq!(packet.issues[1].validator, "broken");
+        assert_eq!(packet.issues[1].severity, Severity::Error);
+
+        let mut packet = decode(&registry, &ScriptEngine::new(), "proto", &[0x01, 0xAA]).unwrap();
+        assert_eq!(packet.issues.len(), 1);
+
+        // a packet of a protocol that can no longer be compiled is not valid
+        packet.protocol_id = "gone".to_string();
+        let issues = validate_packet(&registry, &ScriptEngine::new(), &packet);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(
+            issues[0].message,
+            "Validators failed to run: Protocol with ID 'gone' does not exist"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use super::Value;
+use super::decode::{DecodedPacket, decode};
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+
+/// A parent field value required by a subprotocol, compared with the decoded packet
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConstraintCheck {
+    pub field_id: String,
+    pub expected: i128,
+    /// the value the packet has for the field; `None` if it has no such field
+    pub actual: Option<Value>,
+}
+
+impl ConstraintCheck {
+    pub fn is_met(&self) -> bool {
+        self.actual == Some(Value::Int(self.expected))
+    }
+}
+
+/// A subprotocol the packet could have been decoded as, with its constraints checked
+#[derive(Clone, PartialEq, Debug)]
+pub struct DispatchCandidate {
+    pub protocol_id: String,
+    /// whether the packet was decoded as this subprotocol or one of its own subprotocols
+    pub chosen: bool,
+    pub checks: Vec<ConstraintCheck>,
+}
+
+impl DispatchCandidate {
+    /// Whether the packet has every parent field value the subprotocol requires
+    pub fn matches(&self) -> bool {
+        self.checks.iter().all(ConstraintCheck::is_met)
+    }
+}
+
+/// The subprotocols of one protocol in the inheritance chain of a decoded packet
+#[derive(Clone, PartialEq, Debug)]
+pub struct DispatchStep {
+    pub parent_id: String,
+    pub candidates: Vec<DispatchCandidate>,
+}
+
+/// For each protocol in the inheritance chain of a decoded packet that has subprotocols, how
+/// the packet compares with the `parent_constraints` of each of them: the one in the chain it
+/// was decoded as, and its siblings the packet was not decoded as, with the reason why.
+pub fn explain_dispatch(registry: &ProtocolRegistry, packet: &DecodedPacket) -> Vec<DispatchStep> {
+    let chain = registry.get_inheritance_chain(&packet.protocol_id);
+
+    chain
+        .iter()
+        .map(|parent| {
+            let mut children: Vec<_> = registry.children_of(&parent.id).collect();
+            children.sort_by(|a, b| a.id.cmp(&b.id));
+            let candidates = children
+                .into_iter()
+                .map(|child| {
+                    let mut checks: Vec<ConstraintCheck> = child
+                        .parent_constraints
+                        .iter()
+                        .map(|(field_id, &expected)| ConstraintCheck {
+                            field_id: field_id.clone(),
+                            expected,
+                            actual: packet.get(field_id).map(|f| f.value.clone()),
+                        })
+                        .collect();
+                    checks.sort_by(|a, b| a.field_id.cmp(&b.field_id));
+                    DispatchCandidate {
+                        protocol_id: child.id.clone(),
+                        chosen: chain.iter().any(|p| p.id == child.id),
+                        checks,
+                    }
+                })
+                .collect();
+            DispatchStep {
+                parent_id: parent.id.clone(),
+                candidates,
+            }
+        })
+        .filter(|step: &DispatchStep| !step.candidates.is_empty())
+        .collect()
+}
+
+/// Decode `data` as `protocol_id`, then as the deepest subprotocol whose parent constraints
+/// the packet meets, e.g. the message selected by an ID field. Where several match, the one
+/// constraining the most fields is tried first; one the packet does not decode as is skipped.
+/// Like [`decode`], it compiles the protocols for every call, unlike
+/// [`CodecCache::decode_dispatched`](super::compiled::CodecCache::decode_dispatched).
+pub fn decode_dispatched(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    data: &[u8],
+) -> Result<DecodedPacket, String> {
+    dispatch_with(registry, protocol_id, |id| {
+        decode(registry, engine, id, data)
+    })
+}
+
+/// Like [`decode_dispatched`], decoding the packet as each protocol with `decode_as`
+pub fn dispatch_with(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    mut decode_as: impl FnMut(&str) -> Result<DecodedPacket, String>,
+) -> Result<DecodedPacket, String> {
+    let mut packet = decode_as(protocol_id)?;
+    'deeper: loop {
+        let children =
+            registry.matching_children(&packet.protocol_id, |id| match packet.get(id)?.value {
+                Value::Int(v) => Some(v),
+                _ => None,
+            });
+        for child in children {
+            if let Ok(decoded) = decode_as(&child) {
+                packet = decoded;
+                continue 'deeper;
+            }
+        }
+        return Ok(packet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode::decode;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+    use crate::script::ScriptEngine;
+
+    #[test]
+    fn test_explain_dispatch_of_siblings() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        for (id, kind) in [("ping", 1), ("data", 2), ("data_v2", 2)] {
+            registry
+                .create_protocol(id, None, Endianness::Big, Some("frame".to_string()))
+                .unwrap();
+            registry
+                .edit_protocol(id, |p| {
+                    p.set_parent_constraint("kind", kind);
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        let packet = decode(&registry, &ScriptEngine::new(), "data", &[2]).unwrap();
+        let steps = explain_dispatch(&registry, &packet);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].parent_id, "frame");
+
+        let summary: Vec<(&str, bool, bool)> = steps[0]
+            .candidates
+            .iter()
+            .map(|c| (c.protocol_id.as_str(), c.chosen, c.matches()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("data", true, true),
+                ("data_v2", false, true),
+                ("ping", false, false)
+            ]
+        );
+        assert_eq!(steps[0].candidates[2].checks[0].actual, Some(Value::Int(2)));
+    }
+
+    #[test]
+    fn test_decode_dispatched() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "version",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        // hundreds of messages, one per kind
+        for kind in 0..200 {
+            let id = format!("msg_{}", kind);
+            registry
+                .create_protocol(&id, None, Endianness::Big, Some("frame".to_string()))
+                .unwrap();
+            registry
+                .edit_protocol(&id, |p| {
+                    p.set_parent_constraint("kind", kind);
+                    Ok(())
+                })
+                .unwrap();
+        }
+        // a more specific version of kind 7, and a payload it requires
+        registry
+            .create_protocol("msg_7_v2", None, Endianness::Big, Some("frame".to_string()))
+            .unwrap();
+        registry
+            .edit_protocol("msg_7_v2", |p| {
+                p.set_parent_constraint("kind", 7);
+                p.set_parent_constraint("version", 2);
+                p.add_field(FieldRule::new(
+                    "value",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let protocol_of = |data: &[u8]| {
+            decode_dispatched(&registry, &engine, "frame", data)
+                .unwrap()
+                .protocol_id
+        };
+
+        assert_eq!(protocol_of(&[150, 1]), "msg_150");
+        assert_eq!(protocol_of(&[7, 2, 9]), "msg_7_v2");
+        // too short for the more specific one
+        assert_eq!(protocol_of(&[7, 2]), "msg_7");
+        assert_eq!(protocol_of(&[250, 1]), "frame");
+        assert!(decode_dispatched(&registry, &engine, "frame", &[1]).is_err());
+
+        // the index follows edits of the constraints
+        registry
+            .edit_protocol("msg_150", |p| {
+                p.set_parent_constraint("kind", 250);
+                Ok(())
+            })
+            .unwrap();
+        let protocol_of = |data: &[u8]| {
+            decode_dispatched(&registry, &engine, "frame", data)
+                .unwrap()
+                .protocol_id
+        };
+        assert_eq!(protocol_of(&[250, 1]), "msg_150");
+        assert_eq!(protocol_of(&[150, 1]), "frame");
+    }
+}
@@ -0,0 +1,288 @@
+use super::Value;
+use super::bits::BitWriter;
+use super::compiled::CompiledCodec;
+use crate::models::field::{FieldLength, FieldRule, fits_in_bits};
+use crate::models::protocol::{Endianness, ProtocolRegistry};
+use crate::script::ScriptEngine;
+use std::collections::HashMap;
+
+/// Encode an instance of `protocol_id` from field values keyed by field ID, including all
+/// fields inherited from its parents.
+///
+/// Fixed fields always use their fixed value and need no entry in `values`. Expression fields
+/// are evaluated after all other fields are known, in declaration order, with every wire field
+/// and any earlier expression field in scope. Virtual fields are not encoded.
+pub fn encode(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    values: &HashMap<String, Value>,
+) -> Result<Vec<u8>, String> {
+    CompiledCodec::compile(registry, engine, protocol_id)?.encode(engine, values)
+}
+
+/// Overwrite a field of a packet with a new value, leaving the other bits of the packet as
+/// they are. `bit_offset` is where the field starts, as decoded.
+pub fn patch_field(
+    data: &mut [u8],
+    rule: &FieldRule,
+    endianness: Endianness,
+    bit_offset: usize,
+    value: &Value,
+) -> Result<(), String> {
+    let mut writer = BitWriter::new();
+    encode_field(&mut writer, rule, endianness, value)?;
+    let bits = writer.position();
+    if bit_offset + bits > data.len() * 8 {
+        return Err(format!(
+            "Field '{}' ends past the end of the packet",
+            rule.id
+        ));
+    }
+    let raw = writer.into_bytes();
+    for i in 0..bits {
+        let bit = (raw[i / 8] >> (7 - i % 8)) & 1;
+        let dst = bit_offset + i;
+        data[dst / 8] = (data[dst / 8] & !(1 << (7 - dst % 8))) | (bit << (7 - dst % 8));
+    }
+    Ok(())
+}
+
+pub(super) fn encode_field(
+    writer: &mut BitWriter,
+    rule: &FieldRule,
+    endianness: Endianness,
+    value: &Value,
+) -> Result<(), String> {
+    let mut raw = match (&rule.length, value) {
+        (FieldLength::Variable, Value::Bytes(bytes)) => bytes.clone(),
+        (FieldLength::Variable, Value::Str(s)) => s.as_bytes().to_vec(),
+        (FieldLength::Fixed(bits), Value::Int(v)) => {
+            let v = &if rule.is_signed() {
+                rule.sign_encoding.encode(*v, *bits).ok_or_else(|| {
+                    format!(
+                        "Field '{}' has value {} which does not fit in {} signed bits",
+                        rule.id, v, bits
+                    )
+                })?
+            } else {
+                *v
+            };
+            if !fits_in_bits(*v, *bits, *v < 0) {
+                return Err(format!(
+                    "Field '{}' has value {} which does not fit in {} bits",
+                    rule.id, v, bits
+                ));
+            }
+            let bytes = v.to_be_bytes();
+            let len = (*bits as usize).div_ceil(8).min(bytes.len());
+            let mut raw = vec![if *v < 0 { 0xFF } else { 0 }; (*bits as usize).div_ceil(8) - len];
+            raw.extend_from_slice(&bytes[bytes.len() - len..]);
+            raw
+        }
+        (FieldLength::Fixed(bits), Value::Bool(b)) if *bits >= 1 => {
+            let mut raw = vec![0; (*bits as usize).div_ceil(8)];
+            raw[0] = *b as u8;
+            raw.reverse();
+            raw
+        }
+        (FieldLength::Fixed(bits), Value::Bytes(bytes))
+            if bytes.len() == (*bits as usize).div_ceil(8) =>
+        {
+            bytes.clone()
+        }
+        _ => {
+            return Err(format!("Field '{}' cannot encode value {}", rule.id, value));
+        }
+    };
+
+    let bits = match rule.length {
+        FieldLength::Fixed(bits) => bits as usize,
+        FieldLength::Variable => raw.len() * 8,
+    };
+    if endianness == Endianness::Little && bits % 8 == 0 {
+        raw.reverse();
+    }
+    writer
+        .write_bits(&raw, bits)
+        .map_err(|e| format!("Failed to encode field '{}': {}", rule.id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode::decode;
+    use crate::models::field::FieldType;
+
+    fn registry_with(fields: Vec<FieldRule>, endianness: Endianness) -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("proto", None, endianness, None)
+            .unwrap();
+        registry
+            .edit_protocol("proto", |p| {
+                for field in fields {
+                    p.add_field(field)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+        registry
+    }
+
+    fn values(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(id, v)| (id.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_fixed_and_input_fields() {
+        let registry = registry_with(
+            vec![
+                FieldRule::new("version", FieldType::Fixed(4), FieldLength::Fixed(4)),
+                FieldRule::new("flags", FieldType::Input, FieldLength::Fixed(4)),
+                FieldRule::new("length", FieldType::Input, FieldLength::Fixed(16)),
+            ],
+            Endianness::Big,
+        );
+
+        let data = encode(
+            &registry,
+            &ScriptEngine::new(),
+            "proto",
+            &values(&[("flags", Value::Int(0xA)), ("length", Value::Int(0x0102))]),
+        )
+        .unwrap();
+        assert_eq!(data, vec![0x4A, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn test_encode_expression_and_payload_roundtrip() {
+        let registry = registry_with(
+            vec![
+                FieldRule::new(
+                    "length",
+                    FieldType::Expr("payload.len()".to_string()),
+                    FieldLength::Fixed(16),
+                ),
+                FieldRule::new("payload", FieldType::Input, FieldLength::Variable),
+            ],
+            Endianness::Big,
+        );
+        let engine = ScriptEngine::new();
+
+        let data = encode(
+            &registry,
+            &engine,
+            "proto",
+            &values(&[("payload", Value::Bytes(vec![0xAA, 0xBB, 0xCC]))]),
+        )
+        .unwrap();
+        assert_eq!(data, vec![0x00, 0x03, 0xAA, 0xBB, 0xCC]);
+
+        let packet = decode(&registry, &engine, "proto", &data).unwrap();
+        assert_eq!(packet.get("length").unwrap().value, Value::Int(3));
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let registry = registry_with(
+            vec![FieldRule::new(
+                "value",
+                FieldType::Input,
+                FieldLength::Fixed(4),
+            )],
+            Endianness::Big,
+        );
+        let engine = ScriptEngine::new();
+
+        assert!(encode(&registry, &engine, "proto", &HashMap::new()).is_err());
+        assert!(
+            encode(
+                &registry,
+                &engine,
+                "proto",
+                &values(&[("value", Value::Int(16))])
+            )
+            .is_err()
+        );
+        assert_eq!(
+            encode(
+                &registry,
+                &engine,
+                "proto",
+                &values(&[("value", Value::Int(-1))])
+            ),
+            Ok(vec![0xF0])
+        );
+    }
+
+    #[test]
+    fn test_encode_pads_to_frame_length() {
+        let mut registry = registry_with(
+            vec![FieldRule::new(
+                "id",
+                FieldType::Input,
+                FieldLength::Fixed(12),
+            )],
+            Endianness::Big,
+        );
+        registry
+            .edit_protocol("proto", |p| {
+                p.frame_length = Some(32);
+                Ok(())
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+
+        let data = encode(
+            &registry,
+            &engine,
+            "proto",
+            &values(&[("id", Value::Int(0xABC))]),
+        )
+        .unwrap();
+        assert_eq!(data, vec![0xAB, 0xC0, 0x00, 0x00]);
+        let packet = decode(&registry, &engine, "proto", &data).unwrap();
+        assert!(packet.issues.is_empty());
+
+        registry
+            .edit_protocol("proto", |p| {
+                p.frame_length = Some(8);
+                Ok(())
+            })
+            .unwrap();
+        assert!(
+            encode(
+                &registry,
+                &engine,
+                "proto",
+                &values(&[("id", Value::Int(1))])
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_patch_field() {
+        let mode = FieldRule::new("mode", FieldType::Input, FieldLength::Fixed(3));
+        let mut data = vec![0xFF, 0x00];
+        patch_field(&mut data, &mode, Endianness::Big, 6, &Value::Int(0b001)).unwrap();
+        assert_eq!(data, vec![0xFC, 0x80]);
+
+        let length = FieldRule::new("length", FieldType::Input, FieldLength::Fixed(16));
+        patch_field(
+            &mut data,
+            &length,
+            Endianness::Little,
+            0,
+            &Value::Int(0x0102),
+        )
+        .unwrap();
+        assert_eq!(data, vec![0x02, 0x01]);
+        assert!(patch_field(&mut data, &length, Endianness::Big, 8, &Value::Int(1)).is_err());
+        assert!(patch_field(&mut data, &mode, Endianness::Big, 0, &Value::Int(8)).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Finding where messages end in a byte stream, e.g. a TCP connection or serial line, where
+//! one read may hold part of a message or several of them
+
+use super::bits::BitSlice;
+use crate::models::field::FieldLength;
+use crate::models::protocol::{Endianness, ProtocolLength, ProtocolRegistry};
+
+/// Length in bytes of the message at the start of `data` as a packet of a protocol, or `None`
+/// if `data` is too short to tell.
+///
+/// The length is the frame length of the protocol if it has one, else the value of its length
+/// field, else its total length if all of its fields have a fixed length.
+pub fn message_length(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    data: &[u8],
+) -> Result<Option<usize>, String> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    if chain.is_empty() {
+        return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+    }
+    if let Some((bits, _)) = registry.frame_length(protocol_id) {
+        return Ok(Some((bits as usize).div_ceil(8)));
+    }
+
+    let Some(length) = registry.length_field(protocol_id) else {
+        return match registry.get_total_length(protocol_id) {
+            ProtocolLength::Fixed(bits) if bits > 0 => Ok(Some((bits as usize).div_ceil(8))),
+            _ => Err(format!(
+                "Protocol '{}' has variable length and no length field, so its messages cannot be found in a byte stream",
+                protocol_id
+            )),
+        };
+    };
+
+    let (proto, rule) = chain
+        .iter()
+        .find_map(|p| Some((p, p.fields.iter().find(|f| f.id == length.field_id)?)))
+        .ok_or_else(|| {
+            format!(
+                "Length field '{}' is not a field of '{}'",
+                length.field_id, protocol_id
+            )
+        })?;
+    let (&FieldLength::Fixed(bits), false) = (&rule.length, rule.is_virtual()) else {
+        return Err(format!(
+            "Length field '{}' must be a wire field of fixed length",
+            rule.id
+        ));
+    };
+    let offset = registry
+        .field_offsets(protocol_id)?
+        .get(&rule.id)
+        .copied()
+        .ok_or_else(|| format!("Length field '{}' follows a variable length field", rule.id))?;
+    let (offset, bits) = (offset as usize, bits as usize);
+    if data.len() * 8 < offset + bits {
+        return Ok(None);
+    }
+
+    let swap_bytes = rule.byte_order(proto.endianness) == Endianness::Little;
+    let value = BitSlice::new(data, offset, bits)
+        .and_then(|slice| slice.to_u128(swap_bytes))
+        .unwrap_or(u128::MAX);
+    let header = (offset + bits).div_ceil(8);
+    match usize::try_from(value as i128 + length.adjustment as i128) {
+        Ok(bytes) if bytes >= header.max(1) => Ok(Some(bytes)),
+        _ => Err(format!(
+            "Length field '{}' has value {}, too small for a message that includes it",
+            rule.id, value
+        )),
+    }
+}
+
+/// Splits a byte stream into messages of a protocol, keeping the start of an incomplete message
+/// until the rest of it arrives
+#[derive(Default)]
+pub struct StreamFramer {
+    buffer: Vec<u8>,
+}
+
+impl StreamFramer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes received that are not part of a complete message yet
+    pub fn pending(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Add bytes read from the stream and take the messages they complete. When the length of
+    /// a message cannot be worked out the stream is out of step: the messages before it are
+    /// returned, and the next call fails and drops the bytes received so far.
+    pub fn push(
+        &mut self,
+        registry: &ProtocolRegistry,
+        protocol_id: &str,
+        data: &[u8],
+    ) -> Result<Vec<Vec<u8>>, String> {
+        self.buffer.extend_from_slice(data);
+        let mut messages = Vec::new();
+        let mut start = 0;
+        while start < self.buffer.len() {
+            match message_length(registry, protocol_id, &self.buffer[start..]) {
+                Ok(Some(length)) if start + length <= self.buffer.len() => {
+                    messages.push(self.buffer[start..start + length].to_vec());
+                    start += length;
+                }
+                Ok(_) => break,
+                Err(e) if messages.is_empty() => {
+                    self.buffer.clear();
+                    return Err(e);
+                }
+                Err(_) => break,
+            }
+        }
+        self.buffer.drain(..start);
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldRule, FieldType};
+    use crate::models::protocol::LengthField;
+
+    fn registry_with(
+        fields: Vec<FieldRule>,
+        length_field: Option<LengthField>,
+    ) -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("proto", None, Endianness::Little, None)
+            .unwrap();
+        registry
+            .edit_protocol("proto", |p| {
+                for field in fields {
+                    p.add_field(field)?;
+                }
+                p.length_field = length_field;
+                Ok(())
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_frame_messages_by_length_field() {
+        // a sync byte and a little endian length of the payload
+        let registry = registry_with(
+            vec![
+                FieldRule::new("sync", FieldType::Fixed(0xaa), FieldLength::Fixed(8)),
+                FieldRule::new("length", FieldType::Input, FieldLength::Fixed(16)),
+                FieldRule::new("payload", FieldType::Input, FieldLength::Variable),
+            ],
+            Some(LengthField {
+                field_id: "length".to_string(),
+                adjustment: 3,
+            }),
+        );
+        assert_eq!(message_length(&registry, "proto", &[0xaa, 2]), Ok(None));
+        assert_eq!(
+            message_length(&registry, "proto", &[0xaa, 2, 0]),
+            Ok(Some(5))
+        );
+
+        let mut framer = StreamFramer::new();
+        let messages = framer
+            .push(&registry, "proto", &[0xaa, 1, 0, 7, 0xaa, 2])
+            .unwrap();
+        assert_eq!(messages, vec![vec![0xaa, 1, 0, 7]]);
+        assert_eq!(framer.pending(), &[0xaa, 2]);
+        let messages = framer
+            .push(&registry, "proto", &[0, 8, 9, 0xaa, 0, 0])
+            .unwrap();
+        assert_eq!(messages, vec![vec![0xaa, 2, 0, 8, 9], vec![0xaa, 0, 0]]);
+        assert!(framer.pending().is_empty());
+    }
+
+    #[test]
+    fn test_frame_fixed_length_messages() {
+        let registry = registry_with(
+            vec![FieldRule::new(
+                "id",
+                FieldType::Input,
+                FieldLength::Fixed(16),
+            )],
+            None,
+        );
+        let mut framer = StreamFramer::new();
+        assert_eq!(
+            framer.push(&registry, "proto", &[1, 2, 3]),
+            Ok(vec![vec![1, 2]])
+        );
+
+        let variable = registry_with(
+            vec![FieldRule::new(
+                "data",
+                FieldType::Input,
+                FieldLength::Variable,
+            )],
+            None,
+        );
+        assert!(framer.push(&variable, "proto", &[4]).is_err());
+        assert!(framer.pending().is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+//! Hex dumps as Wireshark shows them and text2pcap reads them: each line starts with the
+//! offset of its first byte in hex, followed by the bytes and optionally their ASCII rendering.
+
+use std::fmt::Write;
+
+/// Bytes per line of a written dump
+pub const BYTES_PER_LINE: usize = 16;
+
+/// Parse the packets of a hex dump. A line with offset 0 starts a new packet; lines that do not
+/// start with an offset, such as timestamps or comments, are ignored. The bytes of a line end at
+/// the first token that is not a two-digit hex number or at a gap of three or more spaces, and a
+/// line is cut short where the next line's offset says it ends.
+pub fn parse_hex_dump(text: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut packets = Vec::new();
+    let mut packet: Vec<u8> = Vec::new();
+
+    for (line_no, line) in text.lines().enumerate() {
+        let Some((offset, rest)) = split_offset(line) else {
+            continue;
+        };
+        if offset == 0 && !packet.is_empty() {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if offset > packet.len() {
+            return Err(format!(
+                "Line {}: offset {:x} skips bytes after offset {:x}",
+                line_no + 1,
+                offset,
+                packet.len()
+            ));
+        }
+        packet.truncate(offset);
+        packet.extend(line_bytes(rest));
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
+/// The offset at the start of a line and the rest of the line
+fn split_offset(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_start();
+    let end = line
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(line.len());
+    let (digits, rest) = line.split_at(end);
+    let rest = rest.strip_prefix(':').unwrap_or(rest);
+    if digits.len() < 2 || !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+    Some((usize::from_str_radix(digits, 16).ok()?, rest))
+}
+
+fn line_bytes(rest: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut rest = rest.trim_start();
+    while let Some(pair) = rest.get(..2) {
+        let after = &rest[2..];
+        let Ok(byte) = u8::from_str_radix(pair, 16) else {
+            break;
+        };
+        if !(after.is_empty() || after.starts_with(char::is_whitespace)) {
+            break;
+        }
+        bytes.push(byte);
+
+        let trimmed = after.trim_start();
+        // the ASCII rendering follows a wider gap
+        if after.len() - trimmed.len() >= 3 {
+            break;
+        }
+        rest = trimmed;
+    }
+    bytes
+}
+
+/// Write packets as a hex dump with an ASCII column, separated by blank lines
+pub fn format_hex_dump(packets: &[Vec<u8>]) -> String {
+    let mut out = String::new();
+    for (i, packet) in packets.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        for (line, chunk) in packet.chunks(BYTES_PER_LINE).enumerate() {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            let _ = writeln!(
+                out,
+                "{:04x}  {:<width$}   {}",
+                line * BYTES_PER_LINE,
+                hex.join(" "),
+                ascii,
+                width = BYTES_PER_LINE * 3 - 1
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wireshark_dump() {
+        let text = "\
+Frame 1
+0000   45 00 00 3c 1c 46 40 00 40 06 b1 e6 ac 10 00 01   E..<.F@.@.......
+0010   ac 10 00 0c                                       ....
+
+0000  de ad be ef  ab cd
+";
+        let packets = parse_hex_dump(text).unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].len(), 20);
+        assert_eq!(packets[0][16..], [0xac, 0x10, 0x00, 0x0c]);
+        // the "ab cd" after two spaces are bytes, not ASCII
+        assert_eq!(packets[1], vec![0xde, 0xad, 0xbe, 0xef, 0xab, 0xcd]);
+    }
+
+    #[test]
+    fn test_next_offset_cuts_ascii() {
+        // the ASCII column "ab" looks like a byte but the next offset shows where bytes end
+        let text = "0000 61 62 ab\n0002 63\n";
+        assert_eq!(parse_hex_dump(text).unwrap(), vec![vec![0x61, 0x62, 0x63]]);
+        assert!(parse_hex_dump("0000 01\n0010 02").is_err());
+    }
+
+    #[test]
+    fn test_format_roundtrip() {
+        let packets = vec![(0..=40).collect::<Vec<u8>>(), b"AB c".to_vec()];
+        let text = format_hex_dump(&packets);
+        assert!(text.starts_with("0000  00 01 02"));
+        assert!(text.contains("\n\n0000  41 42 20 63"));
+        assert!(text.ends_with("   AB c\n"));
+        assert_eq!(parse_hex_dump(&text).unwrap(), packets);
+    }
+}
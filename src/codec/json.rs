@@ -0,0 +1,98 @@
+//! Field values as JSON, in the format of the HTTP API's encode requests: an object with a
+//! value for each field ID, e.g. `{"id": 16, "flags": true, "payload": [1, 2, 255]}`. Value
+//! sets in this format can be kept under version control or generated by scripts.
+
+use super::Value;
+use serde_json::{Map, Number, json};
+use std::collections::{BTreeMap, HashMap};
+
+/// A value as JSON: bytes become arrays of numbers and integers that do not fit in 64 bits
+/// decimal strings
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Int(v) => i64::try_from(*v)
+            .map(Number::from)
+            .or_else(|_| u64::try_from(*v).map(Number::from))
+            .map_or_else(|_| v.to_string().into(), serde_json::Value::Number),
+        Value::Float(v) => json!(v),
+        Value::Bool(v) => json!(v),
+        Value::Str(v) => json!(v),
+        Value::Bytes(v) => json!(v),
+    }
+}
+
+/// A value from JSON written by [`value_to_json`] or by hand
+pub fn json_to_value(field: &str, value: &serde_json::Value) -> Result<Value, String> {
+    let invalid = || format!("Invalid value for field '{}': {}", field, value);
+    match value {
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(|v| Value::Int(v as i128))
+            .or_else(|| n.as_u64().map(|v| Value::Int(v as i128)))
+            .or_else(|| n.as_f64().map(Value::Float))
+            .ok_or_else(invalid),
+        serde_json::Value::Bool(v) => Ok(Value::Bool(*v)),
+        serde_json::Value::String(v) => Ok(Value::Str(v.clone())),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_u64()
+                    .and_then(|b| u8::try_from(b).ok())
+                    .ok_or_else(invalid)
+            })
+            .collect::<Result<Vec<u8>, String>>()
+            .map(Value::Bytes),
+        _ => Err(invalid()),
+    }
+}
+
+/// Write field values as a JSON object, sorted by field ID
+pub fn values_to_json(values: &HashMap<String, Value>) -> String {
+    let values: BTreeMap<&String, serde_json::Value> =
+        values.iter().map(|(k, v)| (k, value_to_json(v))).collect();
+    serde_json::to_string_pretty(&values).unwrap_or_default()
+}
+
+/// Read field values from a JSON object
+pub fn values_from_json(text: &str) -> Result<HashMap<String, Value>, String> {
+    let values = serde_json::from_str::<Map<String, serde_json::Value>>(text)
+        .map_err(|e| format!("Invalid JSON object: {}", e))?;
+    values
+        .iter()
+        .map(|(k, v)| Ok((k.clone(), json_to_value(k, v)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_integers() {
+        assert_eq!(value_to_json(&Value::Int(-1)), json!(-1));
+        assert_eq!(
+            value_to_json(&Value::Int(u64::MAX as i128)),
+            json!(u64::MAX)
+        );
+        assert_eq!(
+            value_to_json(&Value::Int(1 << 70)),
+            json!("1180591620717411303424")
+        );
+        assert!(json_to_value("f", &json!([256])).is_err());
+    }
+
+    #[test]
+    fn test_values_roundtrip() {
+        let values = HashMap::from([
+            ("id".to_string(), Value::Int(16)),
+            ("flag".to_string(), Value::Bool(true)),
+            ("name".to_string(), Value::Str("abc".to_string())),
+            ("payload".to_string(), Value::Bytes(vec![1, 2, 255])),
+        ]);
+        let json = values_to_json(&values);
+        assert!(json.starts_with("{\n  \"flag\": true,\n  \"id\": 16,"));
+        assert_eq!(values_from_json(&json), Ok(values));
+        assert!(values_from_json("[1]").is_err());
+        assert!(values_from_json(r#"{"id": null}"#).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+pub mod batch;
+pub mod bits;
+pub mod checksum;
+pub mod compiled;
+pub mod decode;
+pub mod dispatch;
+pub mod encode;
+pub mod framing;
+pub mod hexdump;
+pub mod json;
+pub mod stream;
+
+use crate::models::field::{DisplayFormat, parse_int};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A decoded or computed field value
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    /// Parse a literal typed by the user: an integer (decimal, `0x`, `0b`, `'A'`), a float,
+    /// `true`/`false`, a double-quoted string, or hex bytes in brackets like `[01 A2 FF]`.
+    pub fn parse_literal(text: &str) -> Result<Value, String> {
+        let text = text.trim();
+        if let Ok(v) = parse_int(text) {
+            return Ok(Value::Int(v));
+        }
+        if let Ok(v) = text.parse::<f64>() {
+            return Ok(Value::Float(v));
+        }
+        match text {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            _ => {}
+        }
+        if let Some(s) = text
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+        {
+            return Ok(Value::Str(s.to_string()));
+        }
+        if let Some(hex) = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            && let Ok(bytes) = parse_hex(hex)
+        {
+            return Ok(Value::Bytes(bytes));
+        }
+        Err(format!("'{}' is not a valid value", text))
+    }
+}
+
+/// Parse hex digits into bytes, ignoring whitespace, e.g. `01 a2FF`
+pub fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("Hex data must have an even number of digits".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("'{}' is not valid hex data", text.trim()))
+        })
+        .collect()
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Str(v) => write!(f, "\"{}\"", v),
+            Value::Bytes(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+                write!(f, "{}", hex.join(" "))
+            }
+        }
+    }
+}
+
+impl Value {
+    /// The value as a literal that [`Value::parse_literal`] reads back
+    pub fn literal(&self) -> String {
+        match self {
+            Value::Int(v) => v.to_string(),
+            Value::Float(v) => format!("{:?}", v),
+            Value::Bool(v) => v.to_string(),
+            Value::Str(v) => format!("\"{}\"", v),
+            Value::Bytes(_) => format!("[{}]", self),
+        }
+    }
+
+    /// Order of values when sorting by them: numbers by value, then booleans, strings and bytes
+    pub fn sort_cmp(&self, other: &Value) -> Ordering {
+        let rank = |value: &Value| match value {
+            Value::Int(_) | Value::Float(_) => 0,
+            Value::Bool(_) => 1,
+            Value::Str(_) => 2,
+            Value::Bytes(_) => 3,
+        };
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Int(a), Value::Float(b)) => (*a as f64).total_cmp(b),
+            (Value::Float(a), Value::Int(b)) => a.total_cmp(&(*b as f64)),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+
+    /// Write the value in a display format. Integers are padded to the `bits` they take on the
+    /// wire, with negative ones shown as their two's complement; 0 leaves them unpadded.
+    /// Floats, booleans and strings are written as usual in every format.
+    pub fn format(&self, format: DisplayFormat, bits: usize) -> String {
+        match self {
+            Value::Int(v) => format_int(*v, format, bits),
+            Value::Bytes(bytes) => match format {
+                DisplayFormat::Hex => self.to_string(),
+                DisplayFormat::Decimal => {
+                    let items: Vec<String> = bytes.iter().map(|b| b.to_string()).collect();
+                    format!("[{}]", items.join(", "))
+                }
+                DisplayFormat::Binary => {
+                    let items: Vec<String> = bytes.iter().map(|b| format!("{:08b}", b)).collect();
+                    items.join(" ")
+                }
+                DisplayFormat::Octal => {
+                    let items: Vec<String> = bytes.iter().map(|b| format!("{:03o}", b)).collect();
+                    items.join(" ")
+                }
+                DisplayFormat::Ascii => format!("\"{}\"", ascii(bytes)),
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+fn format_int(v: i128, format: DisplayFormat, bits: usize) -> String {
+    // two's complement of negative values within the field
+    let unsigned = if bits > 0 && bits < 128 {
+        (v as u128) & ((1u128 << bits) - 1)
+    } else {
+        v as u128
+    };
+    match format {
+        DisplayFormat::Decimal => v.to_string(),
+        DisplayFormat::Hex => format!("0x{:0width$X}", unsigned, width = bits.div_ceil(4)),
+        DisplayFormat::Binary => format!("0b{:0width$b}", unsigned, width = bits),
+        DisplayFormat::Octal => format!("0o{:0width$o}", unsigned, width = bits.div_ceil(3)),
+        DisplayFormat::Ascii => {
+            let len = if bits > 0 {
+                bits.div_ceil(8).min(16)
+            } else {
+                (128 - unsigned.leading_zeros() as usize).div_ceil(8).max(1)
+            };
+            format!("'{}'", ascii(&unsigned.to_be_bytes()[16 - len..]))
+        }
+    }
+}
+
+/// Printable ASCII characters, with a dot for any other byte
+fn ascii(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_literal() {
+        assert_eq!(Value::parse_literal("0x10"), Ok(Value::Int(16)));
+        assert_eq!(Value::parse_literal(" -3 "), Ok(Value::Int(-3)));
+        assert_eq!(Value::parse_literal("2.5"), Ok(Value::Float(2.5)));
+        assert_eq!(Value::parse_literal("true"), Ok(Value::Bool(true)));
+        assert_eq!(
+            Value::parse_literal("\"abc\""),
+            Ok(Value::Str("abc".to_string()))
+        );
+        assert_eq!(
+            Value::parse_literal("[01 a2 FF]"),
+            Ok(Value::Bytes(vec![0x01, 0xA2, 0xFF]))
+        );
+        assert!(Value::parse_literal("[0]").is_err());
+        assert!(Value::parse_literal("abc").is_err());
+    }
+
+    #[test]
+    fn test_literal_roundtrip() {
+        for value in [
+            Value::Int(-3),
+            Value::Float(1.0),
+            Value::Bool(false),
+            Value::Str("a \"b\"".to_string()),
+            Value::Bytes(vec![0x01, 0xA2]),
+            Value::Bytes(vec![]),
+        ] {
+            assert_eq!(Value::parse_literal(&value.literal()), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_sort_cmp() {
+        let mut values = vec![
+            Value::Bytes(vec![1]),
+            Value::Str("a".to_string()),
+            Value::Int(3),
+            Value::Bool(false),
+            Value::Float(2.5),
+            Value::Int(-1),
+        ];
+        values.sort_by(Value::sort_cmp);
+        assert_eq!(
+            values,
+            vec![
+                Value::Int(-1),
+                Value::Float(2.5),
+                Value::Int(3),
+                Value::Bool(false),
+                Value::Str("a".to_string()),
+                Value::Bytes(vec![1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("01 a2FF\n"), Ok(vec![0x01, 0xA2, 0xFF]));
+        assert_eq!(parse_hex(""), Ok(vec![]));
+        assert!(parse_hex("0g").is_err());
+        assert!(parse_hex("012").is_err());
+        assert!(parse_hex("é1").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let flags = Value::Int(0x29);
+        assert_eq!(flags.format(DisplayFormat::Decimal, 8), "41");
+        assert_eq!(flags.format(DisplayFormat::Hex, 12), "0x029");
+        assert_eq!(flags.format(DisplayFormat::Binary, 8), "0b00101001");
+        assert_eq!(flags.format(DisplayFormat::Octal, 8), "0o051");
+        assert_eq!(Value::Int(-1).format(DisplayFormat::Hex, 16), "0xFFFF");
+        assert_eq!(Value::Int(5).format(DisplayFormat::Binary, 0), "0b101");
+        assert_eq!(Value::Int(0x4f4b).format(DisplayFormat::Ascii, 16), "'OK'");
+        assert_eq!(Value::Int(0x41).format(DisplayFormat::Ascii, 0), "'A'");
+
+        let bytes = Value::Bytes(vec![0x48, 0x69, 0x00]);
+        assert_eq!(bytes.format(DisplayFormat::Hex, 24), "48 69 00");
+        assert_eq!(bytes.format(DisplayFormat::Decimal, 24), "[72, 105, 0]");
+        assert_eq!(bytes.format(DisplayFormat::Ascii, 24), "\"Hi.\"");
+        assert_eq!(Value::Float(1.5).format(DisplayFormat::Hex, 32), "1.5");
+    }
+}
@@ -0,0 +1,121 @@
+//! Decoding the messages of a protocol from a continuous byte stream, such as a serial port or
+//! a TCP connection, where a read can hold part of a message or several.
+
+use super::compiled::CodecCache;
+use super::decode::DecodedPacket;
+use super::framing::StreamFramer;
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+
+/// A complete message taken from the stream, with what it decodes as
+#[derive(Clone, PartialEq, Debug)]
+pub struct FramedPacket {
+    pub data: Vec<u8>,
+    pub decoded: Result<DecodedPacket, String>,
+}
+
+/// Push-based decoder: feed it the bytes as they arrive in chunks of any size, and it returns
+/// each message once all of it is there. Messages are found by the length of the protocol, so
+/// it needs a fixed length or a length field.
+#[derive(Default)]
+pub struct StreamDecoder {
+    framer: StreamFramer,
+    /// decode messages further as the subprotocol they match, see
+    /// [`decode_dispatched`](super::dispatch::decode_dispatched)
+    pub dispatch: bool,
+    codecs: CodecCache,
+}
+
+impl StreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes received that are not part of a complete message yet
+    pub fn pending(&self) -> &[u8] {
+        self.framer.pending()
+    }
+
+    /// Add bytes read from the stream and decode the messages they complete as `protocol_id`.
+    /// Fails when the stream is out of step, as [`StreamFramer::push`] does.
+    pub fn feed(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+        data: &[u8],
+    ) -> Result<Vec<FramedPacket>, String> {
+        let messages = self.framer.push(registry, protocol_id, data)?;
+        Ok(messages
+            .into_iter()
+            .map(|data| {
+                let decoded = if self.dispatch {
+                    self.codecs
+                        .decode_dispatched(registry, engine, protocol_id, &data)
+                } else {
+                    self.codecs.decode(registry, engine, protocol_id, &data)
+                };
+                FramedPacket { data, decoded }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Value;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::{Endianness, LengthField};
+
+    #[test]
+    fn test_feed_chunks() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "length",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))?;
+                p.length_field = Some(LengthField {
+                    field_id: "length".to_string(),
+                    adjustment: 1,
+                });
+                Ok(())
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let mut decoder = StreamDecoder::new();
+
+        // a message split across reads, then two in one read
+        let feed = |decoder: &mut StreamDecoder, data: &[u8]| {
+            decoder.feed(&registry, &engine, "msg", data).unwrap()
+        };
+        assert!(feed(&mut decoder, &[3, 0xaa]).is_empty());
+        assert_eq!(decoder.pending(), &[3, 0xaa]);
+        let packets = feed(&mut decoder, &[0xbb, 0xcc, 1, 0xdd, 5]);
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].data, vec![3, 0xaa, 0xbb, 0xcc]);
+        let payload = &packets[0]
+            .decoded
+            .as_ref()
+            .unwrap()
+            .get("payload")
+            .unwrap()
+            .value;
+        assert_eq!(*payload, Value::Bytes(vec![0xaa, 0xbb, 0xcc]));
+        assert_eq!(packets[1].data, vec![1, 0xdd]);
+        assert_eq!(decoder.pending(), &[5]);
+        assert_eq!(feed(&mut decoder, &[1, 2, 3, 4, 5]).len(), 1);
+        assert!(decoder.pending().is_empty());
+    }
+}
@@ -0,0 +1,506 @@
+//! Go output: a struct per protocol read and written field by field with `encoding/binary`.
+//!
+//! Fields that start and end on byte boundaries with a Go integer size are struct fields of
+//! that type, so `binary.Read` and `binary.Write` handle them with their byte order. Fields
+//! sharing bytes, such as bit flags, are kept as a raw byte array with accessor methods.
+
+use super::{Layout, WireField, camel_case, summary, variant_name};
+use crate::models::field::FieldType;
+use crate::models::protocol::Endianness;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GoOptions {
+    pub package: String,
+    /// also generate an `Encode` method per struct
+    pub encoder: bool,
+}
+
+impl Default for GoOptions {
+    fn default() -> Self {
+        Self {
+            package: "protocol".to_string(),
+            encoder: true,
+        }
+    }
+}
+
+/// Consecutive fields read together: a single byte-aligned field or bits sharing bytes
+struct Word<'l, 'a> {
+    /// offset in bytes
+    offset: u32,
+    bytes: u32,
+    fields: Vec<&'l WireField<'a>>,
+}
+
+impl Word<'_, '_> {
+    /// The struct field type when the word is one field `binary.Read` can read directly
+    fn direct_type(&self) -> Option<String> {
+        let [field] = self.fields.as_slice() else {
+            return None;
+        };
+        // sign-magnitude fields need accessors, as binary.Read takes two's complement
+        if !field.offset.is_multiple_of(8)
+            || !field.bits.is_multiple_of(8)
+            || field.is_sign_magnitude()
+        {
+            return None;
+        }
+        match field.bits {
+            8 | 16 | 32 | 64 => Some(value_type(field)),
+            bits if bits > 64 => Some(format!("[{}]byte", bits / 8)),
+            _ => None,
+        }
+    }
+
+    /// Name of the struct field holding the raw bytes of a word with accessors
+    fn raw_name(&self) -> String {
+        let names: Vec<String> = self.fields.iter().map(|f| camel_case(&f.rule.id)).collect();
+        format!("Raw{}", names.concat())
+    }
+}
+
+fn words<'l, 'a>(layout: &'l Layout<'a>) -> Vec<Word<'l, 'a>> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut open = false;
+    for field in &layout.fields {
+        if !open {
+            words.push(Word {
+                offset: field.offset / 8,
+                bytes: 0,
+                fields: Vec::new(),
+            });
+        }
+        let word = words.last_mut().expect("word was pushed");
+        word.fields.push(field);
+        let end = field.offset + field.bits;
+        word.bytes = end.div_ceil(8) - word.offset;
+        open = !end.is_multiple_of(8);
+    }
+    words
+}
+
+pub fn generate(protocol_id: &str, layouts: &[Layout], options: &GoOptions) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Code generated by BitLoom from protocol '{}'. DO NOT EDIT.",
+        protocol_id
+    );
+    out.push('\n');
+    let _ = writeln!(out, "package {}\n", options.package);
+    out.push_str("import (\n\t\"bytes\"\n\t\"encoding/binary\"\n)\n\n");
+    out.push_str(
+        "// wireField is one struct field with the byte order it has on the wire\n\
+         type wireField struct {\n\torder binary.ByteOrder\n\tvalue any\n}\n",
+    );
+
+    let mut enums = HashSet::new();
+    let mut accessors = false;
+    for layout in layouts {
+        for field in &layout.fields {
+            if let FieldType::Enum(variants) = &field.rule.field_type
+                && enums.insert(enum_type(field))
+            {
+                out.push('\n');
+                let _ = writeln!(
+                    out,
+                    "type {} {}\n\nconst (",
+                    enum_type(field),
+                    unsigned_type(field.bits)
+                );
+                for variant in variants {
+                    let _ = writeln!(
+                        out,
+                        "\t{}{} {} = {}",
+                        enum_type(field),
+                        variant_name(variant.name.as_deref(), variant.value),
+                        enum_type(field),
+                        variant.value
+                    );
+                }
+                out.push_str(")\n");
+            }
+        }
+
+        out.push('\n');
+        accessors |= protocol_struct(&mut out, layout, options);
+    }
+
+    if accessors {
+        out.push('\n');
+        out.push_str(HELPERS);
+    }
+    out
+}
+
+/// Write the struct and functions of one protocol, returning whether it has accessors
+fn protocol_struct(out: &mut String, layout: &Layout, options: &GoOptions) -> bool {
+    let name = camel_case(&layout.protocol.id);
+    let words = words(layout);
+
+    let _ = writeln!(
+        out,
+        "// {} is protocol '{}'",
+        name,
+        layout
+            .protocol
+            .name
+            .as_deref()
+            .unwrap_or(&layout.protocol.id)
+    );
+    if let Some(description) = summary(layout.protocol.description.as_deref()) {
+        let _ = writeln!(out, "// {}", description);
+    }
+    if !layout.derived.is_empty() {
+        let ids: Vec<&str> = layout.derived.iter().map(|f| f.id.as_str()).collect();
+        let _ = writeln!(out, "// Derived fields left out: {}", ids.join(", "));
+    }
+    let _ = writeln!(out, "type {} struct {{", name);
+    for word in &words {
+        match word.direct_type() {
+            Some(go_type) => {
+                let field = word.fields[0];
+                let _ = write!(out, "\t{} {}", camel_case(&field.rule.id), go_type);
+                field_comment(out, field);
+            }
+            None => {
+                let _ = write!(out, "\t{} [{}]byte", word.raw_name(), word.bytes);
+                let ids: Vec<&str> = word.fields.iter().map(|f| f.rule.id.as_str()).collect();
+                let _ = writeln!(out, " // {}", ids.join(", "));
+            }
+        }
+    }
+    if let Some(tail) = layout.tail {
+        let _ = write!(out, "\t{} []byte", camel_case(&tail.id));
+        if let Some(description) = summary(tail.description.as_deref()) {
+            let _ = write!(out, " // {}", description);
+        }
+        out.push('\n');
+    }
+    out.push_str("}\n");
+
+    out.push('\n');
+    let _ = writeln!(out, "func (p *{}) wireFields() []wireField {{", name);
+    out.push_str("\treturn []wireField{\n");
+    for word in &words {
+        let (order, value) = match word.direct_type() {
+            Some(_) => {
+                let field = word.fields[0];
+                let order = if field.is_little_endian() && field.is_integer() {
+                    "LittleEndian"
+                } else {
+                    "BigEndian"
+                };
+                (order, camel_case(&field.rule.id))
+            }
+            None => ("BigEndian", word.raw_name()),
+        };
+        let _ = writeln!(out, "\t\t{{binary.{}, &p.{}}},", order, value);
+    }
+    out.push_str("\t}\n}\n");
+
+    out.push('\n');
+    let _ = writeln!(
+        out,
+        "// Decode{} reads a {} from the start of data",
+        name, name
+    );
+    let _ = writeln!(
+        out,
+        "func Decode{}(data []byte) (*{}, error) {{",
+        name, name
+    );
+    let _ = writeln!(out, "\tp := &{}{{}}", name);
+    out.push_str("\tr := bytes.NewReader(data)\n");
+    out.push_str("\tfor _, f := range p.wireFields() {\n");
+    out.push_str("\t\tif err := binary.Read(r, f.order, f.value); err != nil {\n");
+    out.push_str("\t\t\treturn nil, err\n\t\t}\n\t}\n");
+    if let Some(tail) = layout.tail {
+        let _ = writeln!(
+            out,
+            "\tp.{} = data[len(data)-r.Len():]",
+            camel_case(&tail.id)
+        );
+    }
+    out.push_str("\treturn p, nil\n}\n");
+
+    if options.encoder {
+        out.push('\n');
+        let _ = writeln!(out, "// Encode writes the {} in its wire format", name);
+        let _ = writeln!(out, "func (p *{}) Encode() ([]byte, error) {{", name);
+        out.push_str("\tvar buf bytes.Buffer\n");
+        out.push_str("\tfor _, f := range p.wireFields() {\n");
+        out.push_str("\t\tif err := binary.Write(&buf, f.order, f.value); err != nil {\n");
+        out.push_str("\t\t\treturn nil, err\n\t\t}\n\t}\n");
+        if let Some(tail) = layout.tail {
+            let _ = writeln!(out, "\tbuf.Write(p.{})", camel_case(&tail.id));
+        }
+        out.push_str("\treturn buf.Bytes(), nil\n}\n");
+    }
+
+    let mut accessors = false;
+    for word in &words {
+        if word.direct_type().is_some() {
+            continue;
+        }
+        for field in &word.fields {
+            accessors = true;
+            accessor(out, &name, word, field);
+        }
+    }
+    accessors
+}
+
+fn accessor(out: &mut String, struct_name: &str, word: &Word, field: &WireField) {
+    let method = camel_case(&field.rule.id);
+    let go_type = value_type(field);
+    let raw = word.raw_name();
+    let bit = field.offset - word.offset * 8;
+    let bytes = field.bits / 8;
+
+    let mut read = format!("getBits(p.{}[:], {}, {})", raw, bit, field.bits);
+    if field.is_little_endian() {
+        read = format!("swapBytes({}, {})", read, bytes);
+    }
+    if field.is_sign_magnitude() {
+        read = format!("fromSignMagnitude({}, {})", read, field.bits);
+    } else if field.is_signed() {
+        read = format!("signExtend({}, {})", read, field.bits);
+    }
+    out.push('\n');
+    if let Some(description) = summary(field.rule.description.as_deref()) {
+        let _ = writeln!(out, "// {} {}", method, description);
+    }
+    let _ = writeln!(
+        out,
+        "func (p *{}) {}() {} {{\n\treturn {}({})\n}}",
+        struct_name, method, go_type, go_type, read
+    );
+
+    let mut value = if field.is_sign_magnitude() {
+        format!("toSignMagnitude(int64(v), {})", field.bits)
+    } else {
+        "uint64(v)".to_string()
+    };
+    if field.is_little_endian() {
+        value = format!("swapBytes({}, {})", value, bytes);
+    }
+    out.push('\n');
+    let _ = writeln!(
+        out,
+        "func (p *{}) Set{}(v {}) {{\n\tputBits(p.{}[:], {}, {}, {})\n}}",
+        struct_name, method, go_type, raw, bit, field.bits, value
+    );
+}
+
+fn field_comment(out: &mut String, field: &WireField) {
+    let mut comments = Vec::new();
+    if let Some(description) = summary(field.rule.description.as_deref()) {
+        comments.push(description.to_string());
+    }
+    if let FieldType::Expr(script) = &field.rule.field_type {
+        comments.push(format!("computed by {}", script.trim().replace('\n', " ")));
+    }
+    if field.bits > 64 && field.endianness == Endianness::Little {
+        comments.push("bytes as on the wire".to_string());
+    }
+    if !comments.is_empty() {
+        let _ = write!(out, " // {}", comments.join("; "));
+    }
+    out.push('\n');
+}
+
+/// Go type of a field value of up to 64 bits
+fn value_type(field: &WireField) -> String {
+    if matches!(field.rule.field_type, FieldType::Enum(_)) {
+        return enum_type(field);
+    }
+    let unsigned = unsigned_type(field.bits);
+    if field.is_signed() {
+        unsigned.trim_start_matches('u').to_string()
+    } else {
+        unsigned
+    }
+}
+
+/// Enums are named after the protocol defining the field, so subprotocols share them
+fn enum_type(field: &WireField) -> String {
+    format!(
+        "{}{}",
+        camel_case(&field.owner.id),
+        camel_case(&field.rule.id)
+    )
+}
+
+fn unsigned_type(bits: u32) -> String {
+    let size = [8, 16, 32, 64]
+        .into_iter()
+        .find(|s| bits <= *s)
+        .unwrap_or(64);
+    format!("uint{}", size)
+}
+
+const HELPERS: &str = "\
+// getBits reads width bits starting at bit offset, most significant bit first
+func getBits(b []byte, offset, width uint) uint64 {
+	var v uint64
+	for i := offset; i < offset+width; i++ {
+		v = v<<1 | uint64(b[i/8]>>(7-i%8)&1)
+	}
+	return v
+}
+
+// putBits writes the low width bits of v starting at bit offset
+func putBits(b []byte, offset, width uint, v uint64) {
+	for i := offset; i < offset+width; i++ {
+		bit := byte(v>>(width-1-(i-offset))) & 1
+		b[i/8] = b[i/8]&^(1<<(7-i%8)) | bit<<(7-i%8)
+	}
+}
+
+// signExtend interprets the low width bits of v as a two's complement number
+func signExtend(v uint64, width uint) int64 {
+	shift := 64 - width
+	return int64(v<<shift) >> shift
+}
+
+// fromSignMagnitude interprets the low width bits of v as a sign bit and a magnitude
+func fromSignMagnitude(v uint64, width uint) int64 {
+	magnitude := int64(v & (1<<(width-1) - 1))
+	if v>>(width-1)&1 == 1 {
+		return -magnitude
+	}
+	return magnitude
+}
+
+// toSignMagnitude encodes v as a sign bit and a magnitude in width bits
+func toSignMagnitude(v int64, width uint) uint64 {
+	if v < 0 {
+		return 1<<(width-1) | uint64(-v)
+	}
+	return uint64(v)
+}
+
+// swapBytes reverses the order of the low n bytes of v
+func swapBytes(v uint64, n uint) uint64 {
+	var r uint64
+	for i := uint(0); i < n; i++ {
+		r = r<<8 | v>>(8*i)&0xff
+	}
+	return r
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::layouts;
+    use crate::models::field::{EnumVariant, FieldLength, FieldRule, SignEncoding};
+    use crate::models::protocol::ProtocolRegistry;
+
+    fn registry() -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Little, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "version",
+                    FieldType::Input,
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -8,
+                        max: 7,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(vec![EnumVariant {
+                        value: 1,
+                        name: Some("status".to_string()),
+                        description: None,
+                    }]),
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "length",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_go_struct() {
+        let registry = registry();
+        let layouts = layouts(&registry, "frame").unwrap();
+        let code = generate("frame", &layouts, &GoOptions::default());
+        assert!(code.contains("package protocol\n"));
+        assert!(
+            code.contains("type FrameKind uint8\n\nconst (\n\tFrameKindStatus FrameKind = 1\n)")
+        );
+        assert!(code.contains(
+            "type Frame struct {\n\tRawVersionOffset [1]byte // version, offset\n\tKind FrameKind\n\tLength uint16\n\tPayload []byte\n}"
+        ));
+        assert!(code.contains("\t\t{binary.LittleEndian, &p.Length},\n"));
+        assert!(code.contains("\tp.Payload = data[len(data)-r.Len():]\n"));
+        assert!(code.contains("func (p *Frame) Offset() int8 {\n\treturn int8(signExtend(getBits(p.RawVersionOffset[:], 4, 4), 4))\n}"));
+        assert!(code.contains("func (p *Frame) SetVersion(v uint8) {\n\tputBits(p.RawVersionOffset[:], 0, 4, uint64(v))\n}"));
+        assert!(code.contains("func (p *Frame) Encode() ([]byte, error) {"));
+        assert!(code.contains("func swapBytes("));
+
+        let options = GoOptions {
+            package: "frames".to_string(),
+            encoder: false,
+        };
+        let code = generate("frame", &layouts, &options);
+        assert!(code.contains("package frames\n"));
+        assert!(!code.contains("Encode()"));
+    }
+
+    #[test]
+    fn test_go_sign_magnitude() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("trim", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("trim", |p| {
+                let mut rule = FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -127,
+                        max: 127,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                );
+                rule.sign_encoding = SignEncoding::SignMagnitude;
+                p.add_field(rule)
+            })
+            .unwrap();
+
+        let layouts = layouts(&registry, "trim").unwrap();
+        let code = generate("trim", &layouts, &GoOptions::default());
+        assert!(code.contains("type Trim struct {\n\tRawOffset [1]byte // offset\n}"));
+        assert!(
+            code.contains("\treturn int8(fromSignMagnitude(getBits(p.RawOffset[:], 0, 8), 8))\n")
+        );
+        assert!(code.contains("\tputBits(p.RawOffset[:], 0, 8, toSignMagnitude(int64(v), 8))\n"));
+    }
+}
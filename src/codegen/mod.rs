@@ -0,0 +1,310 @@
+//! Source code generation of protocol decoders and encoders for other languages.
+//!
+//! Every target works from the same [`Layout`]: the wire fields of a protocol and its
+//! ancestors with their bit offsets, so generated code reads a frame in one pass without
+//! following the inheritance at runtime.
+
+pub mod go;
+pub mod rust;
+pub mod typescript;
+
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, Protocol, ProtocolRegistry};
+
+/// A language to generate code for, with its options
+#[derive(Clone, PartialEq, Debug)]
+pub enum Target {
+    Go(go::GoOptions),
+    Rust(rust::RustOptions),
+    TypeScript(typescript::TypeScriptOptions),
+}
+
+impl Target {
+    /// Every target with default options
+    pub fn all() -> Vec<Target> {
+        vec![
+            Target::Go(go::GoOptions::default()),
+            Target::Rust(rust::RustOptions::default()),
+            Target::TypeScript(typescript::TypeScriptOptions::default()),
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Target::Go(_) => "Go",
+            Target::Rust(_) => "Rust",
+            Target::TypeScript(_) => "TypeScript",
+        }
+    }
+
+    pub fn file_name(&self, protocol_id: &str) -> String {
+        match self {
+            Target::Go(_) => format!("{}.go", protocol_id),
+            Target::Rust(_) => format!("{}.rs", protocol_id),
+            Target::TypeScript(_) => format!("{}.ts", protocol_id),
+        }
+    }
+
+    /// Generate code for a protocol, its ancestors' fields and its subprotocols
+    pub fn generate(
+        &self,
+        registry: &ProtocolRegistry,
+        protocol_id: &str,
+    ) -> Result<String, String> {
+        let layouts = layouts(registry, protocol_id)?;
+        match self {
+            Target::Go(options) => Ok(go::generate(protocol_id, &layouts, options)),
+            Target::Rust(options) => Ok(rust::generate(protocol_id, &layouts, options)),
+            Target::TypeScript(options) => Ok(typescript::generate(protocol_id, &layouts, options)),
+        }
+    }
+}
+
+/// A field on the wire at a known position
+#[derive(Debug)]
+pub struct WireField<'a> {
+    pub rule: &'a FieldRule,
+    /// the protocol in the chain that defines the field
+    pub owner: &'a Protocol,
+    /// offset in bits from the start of the frame
+    pub offset: u32,
+    pub bits: u32,
+    pub endianness: Endianness,
+}
+
+impl WireField<'_> {
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self.rule.field_type,
+            FieldType::Range {
+                is_signed: true,
+                ..
+            }
+        )
+    }
+
+    /// Whether negative values have a sign bit and the magnitude rather than being two's
+    /// complement
+    pub fn is_sign_magnitude(&self) -> bool {
+        self.rule.is_sign_magnitude()
+    }
+
+    /// Whether the value is a number rather than raw bytes
+    pub fn is_integer(&self) -> bool {
+        self.bits <= 64
+    }
+
+    /// Whether the bytes of the value are swapped on the wire, as for every little-endian
+    /// field whose length is a whole number of bytes
+    pub fn is_little_endian(&self) -> bool {
+        self.endianness == Endianness::Little && self.bits.is_multiple_of(8) && self.bits > 8
+    }
+}
+
+/// The flattened wire format of one protocol
+#[derive(Debug)]
+pub struct Layout<'a> {
+    pub protocol: &'a Protocol,
+    /// fixed-length fields of the protocol and its ancestors, in wire order
+    pub fields: Vec<WireField<'a>>,
+    /// a variable-length field taking the rest of the frame
+    pub tail: Option<&'a FieldRule>,
+    /// derived fields, which generated code leaves out
+    pub derived: Vec<&'a FieldRule>,
+    /// length of the fixed-length fields in bits
+    pub fixed_bits: u32,
+}
+
+impl Layout<'_> {
+    pub fn fixed_bytes(&self) -> u32 {
+        self.fixed_bits.div_ceil(8)
+    }
+}
+
+/// Layouts of a protocol, its ancestors and its subprotocols
+pub fn layouts<'a>(
+    registry: &'a ProtocolRegistry,
+    protocol_id: &str,
+) -> Result<Vec<Layout<'a>>, String> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    if chain.is_empty() {
+        return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+    }
+    let mut ids: Vec<String> = chain.iter().map(|p| p.id.clone()).collect();
+    let mut descendants = registry.get_descendant_ids(protocol_id);
+    descendants.sort();
+    ids.extend(descendants);
+
+    ids.iter().map(|id| layout(registry, id)).collect()
+}
+
+fn layout<'a>(registry: &'a ProtocolRegistry, protocol_id: &str) -> Result<Layout<'a>, String> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    let protocol = *chain
+        .last()
+        .ok_or_else(|| format!("Protocol with ID '{}' does not exist", protocol_id))?;
+
+    let mut layout = Layout {
+        protocol,
+        fields: Vec::new(),
+        tail: None,
+        derived: Vec::new(),
+        fixed_bits: 0,
+    };
+    for proto in chain {
+        for rule in &proto.fields {
+            if rule.is_virtual() {
+                layout.derived.push(rule);
+                continue;
+            }
+            if let Some(tail) = layout.tail {
+                return Err(format!(
+                    "Protocol '{}': field '{}' follows the variable-length field '{}'",
+                    protocol_id, rule.id, tail.id
+                ));
+            }
+            match rule.length {
+                FieldLength::Fixed(bits) => {
+                    layout.fields.push(WireField {
+                        rule,
+                        owner: proto,
+                        offset: layout.fixed_bits,
+                        bits,
+                        endianness: rule.byte_order(proto.endianness),
+                    });
+                    layout.fixed_bits += bits;
+                }
+                FieldLength::Variable => layout.tail = Some(rule),
+            }
+        }
+    }
+    Ok(layout)
+}
+
+/// A type name from an ID, e.g. `sensor_reading` → `SensorReading`
+pub fn camel_case(id: &str) -> String {
+    let mut name: String = id
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'P');
+    }
+    name
+}
+
+/// An ID as an identifier of a C-like language: what cannot appear in one becomes `_`, and
+/// one starting with a digit gets a `_` before it
+pub fn identifier(id: &str) -> String {
+    let mut ident: String = id
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+/// The name of an enum variant, falling back to its value
+pub fn variant_name(name: Option<&str>, value: i128) -> String {
+    match name {
+        Some(name) if name.chars().any(|c| c.is_ascii_alphanumeric()) => camel_case(name),
+        _ if value < 0 => format!("Minus{}", -value),
+        _ => format!("Value{}", value),
+    }
+}
+
+/// The first line of a description, for a one-line comment
+pub fn summary(description: Option<&str>) -> Option<&str> {
+    description
+        .and_then(|d| d.trim().lines().next())
+        .filter(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_flattens_chain() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .create_protocol("child", None, Endianness::Little, Some("frame".to_string()))
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Input,
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "double",
+                    FieldType::Derived("kind * 2".to_string()),
+                    FieldLength::Fixed(0),
+                ))
+            })
+            .unwrap();
+        registry
+            .edit_protocol("child", |p| {
+                p.add_field(FieldRule::new(
+                    "count",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        let all = layouts(&registry, "frame").unwrap();
+        assert_eq!(all.len(), 2);
+        let child = &all[1];
+        assert_eq!(child.protocol.id, "child");
+        assert_eq!(child.fields[1].offset, 4);
+        assert!(child.fields[1].is_little_endian());
+        assert_eq!(child.fixed_bytes(), 3);
+        assert_eq!(child.tail.unwrap().id, "data");
+        assert_eq!(child.derived[0].id, "double");
+
+        // a subprotocol cannot add fields after an inherited variable-length field
+        registry
+            .create_protocol("trailer", None, Endianness::Big, Some("child".to_string()))
+            .unwrap();
+        registry
+            .edit_protocol("trailer", |p| {
+                p.add_field(FieldRule::new(
+                    "crc",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        assert!(layouts(&registry, "trailer").is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(camel_case("sensor_reading"), "SensorReading");
+        assert_eq!(camel_case("802-frame"), "P802Frame");
+        assert_eq!(identifier("not ready"), "not_ready");
+        assert_eq!(identifier("2nd"), "_2nd");
+        assert_eq!(variant_name(Some("not ready"), 1), "NotReady");
+        assert_eq!(variant_name(None, -2), "Minus2");
+    }
+}
@@ -0,0 +1,698 @@
+//! Rust output: a struct per protocol with `decode` and `encode` methods over byte slices.
+//!
+//! The code has no dependencies, except proptest for the optional `Arbitrary` impls. Integer
+//! fields get the smallest Rust integer type holding them, fields wider than 64 bits are byte
+//! arrays, and enum values are associated constants so unknown values still decode.
+
+use super::{Layout, WireField, summary, variant_name};
+use crate::models::field::FieldType;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RustOptions {
+    /// also generate an `encode` method per struct
+    pub encoder: bool,
+    /// also generate a proptest `Arbitrary` impl per struct, making values the field rules
+    /// allow, for tests or with a `proptest` feature
+    pub proptest: bool,
+}
+
+impl Default for RustOptions {
+    fn default() -> Self {
+        Self {
+            encoder: true,
+            proptest: false,
+        }
+    }
+}
+
+pub fn generate(protocol_id: &str, layouts: &[Layout], options: &RustOptions) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated by BitLoom from protocol '{}'",
+        protocol_id
+    );
+    out.push_str(
+        "\n/// The data is shorter than the fixed-length fields of the protocol\n\
+         #[derive(Clone, Copy, Debug, PartialEq, Eq)]\n\
+         pub struct DecodeError {\n    pub needed: usize,\n    pub found: usize,\n}\n",
+    );
+    for layout in layouts {
+        out.push('\n');
+        protocol_code(&mut out, layout, options);
+        if options.proptest {
+            out.push('\n');
+            arbitrary_code(&mut out, layout, &parent_constraints(layouts, layout));
+        }
+    }
+    let mut helpers = String::new();
+    for (name, source) in HELPERS {
+        if out.contains(&format!("{}(", name)) {
+            helpers.push('\n');
+            helpers.push_str(source);
+        }
+    }
+    out.push_str(&helpers);
+    out
+}
+
+/// Name of the generated struct of a protocol
+pub fn struct_name(protocol_id: &str) -> String {
+    super::camel_case(protocol_id)
+}
+
+fn protocol_code(out: &mut String, layout: &Layout, options: &RustOptions) {
+    let name = struct_name(&layout.protocol.id);
+    let fixed = layout.fixed_bytes();
+
+    let _ = writeln!(
+        out,
+        "/// Protocol '{}'",
+        layout
+            .protocol
+            .name
+            .as_deref()
+            .unwrap_or(&layout.protocol.id)
+    );
+    out.push_str("#[derive(Clone, Debug, PartialEq, Eq)]\n");
+    let _ = writeln!(out, "pub struct {} {{", name);
+    for field in &layout.fields {
+        let mut comments = Vec::new();
+        if let Some(description) = summary(field.rule.description.as_deref()) {
+            comments.push(description.to_string());
+        }
+        if let FieldType::Expr(script) = &field.rule.field_type {
+            comments.push(format!(
+                "computed by `{}`",
+                script.trim().replace('\n', " ")
+            ));
+        }
+        if !comments.is_empty() {
+            let _ = writeln!(out, "    /// {}", comments.join("; "));
+        }
+        let _ = writeln!(
+            out,
+            "    pub {}: {},",
+            identifier(&field.rule.id),
+            value_type(field)
+        );
+    }
+    if let Some(tail) = layout.tail {
+        if let Some(description) = summary(tail.description.as_deref()) {
+            let _ = writeln!(out, "    /// {}", description);
+        }
+        let _ = writeln!(out, "    pub {}: Vec<u8>,", identifier(&tail.id));
+    }
+    out.push_str("}\n\n");
+
+    let _ = writeln!(out, "impl {} {{", name);
+    out.push_str("    /// Length of the fixed-length fields in bytes\n");
+    let _ = writeln!(out, "    pub const LEN: usize = {};", fixed);
+    for field in &layout.fields {
+        let FieldType::Enum(variants) = &field.rule.field_type else {
+            continue;
+        };
+        if !field.is_integer() {
+            continue;
+        }
+        for variant in variants {
+            let _ = writeln!(
+                out,
+                "    pub const {}_{}: {} = {};",
+                field.rule.id.to_ascii_uppercase(),
+                screaming_case(&variant_name(variant.name.as_deref(), variant.value)),
+                value_type(field),
+                variant.value
+            );
+        }
+    }
+
+    out.push('\n');
+    out.push_str("    pub fn decode(data: &[u8]) -> Result<Self, DecodeError> {\n");
+    out.push_str("        if data.len() < Self::LEN {\n");
+    out.push_str(
+        "            return Err(DecodeError {\n                needed: Self::LEN,\n                found: data.len(),\n            });\n        }\n",
+    );
+    out.push_str("        Ok(Self {\n");
+    for field in &layout.fields {
+        let _ = writeln!(
+            out,
+            "            {}: {},",
+            identifier(&field.rule.id),
+            read_expression(field)
+        );
+    }
+    if let Some(tail) = layout.tail {
+        let _ = writeln!(
+            out,
+            "            {}: data[Self::LEN..].to_vec(),",
+            identifier(&tail.id)
+        );
+    }
+    out.push_str("        })\n    }\n");
+
+    if options.encoder {
+        out.push('\n');
+        out.push_str("    pub fn encode(&self) -> Vec<u8> {\n");
+        out.push_str("        let mut data = vec![0; Self::LEN];\n");
+        for field in &layout.fields {
+            let _ = writeln!(out, "        {};", write_statement(field));
+        }
+        if let Some(tail) = layout.tail {
+            let _ = writeln!(
+                out,
+                "        data.extend_from_slice(&self.{});",
+                identifier(&tail.id)
+            );
+        }
+        out.push_str("        data\n    }\n");
+    }
+    out.push_str("}\n");
+}
+
+/// An `Arbitrary` impl making values within the rules of the fields: enum fields take one of
+/// their values, range fields a value in their range, and fields a parent constraint of the
+/// protocol or its ancestors is on the value it requires. Other fields take any value of their
+/// width, including fields computed by an expression, which the struct does not compute.
+fn arbitrary_code(out: &mut String, layout: &Layout, constraints: &HashMap<&str, i128>) {
+    let mut items: Vec<(String, String)> = layout
+        .fields
+        .iter()
+        .map(|field| {
+            let constraint = constraints.get(field.rule.id.as_str()).copied();
+            (strategy(field, constraint), identifier(&field.rule.id))
+        })
+        .collect();
+    if let Some(tail) = layout.tail {
+        let max_bytes = layout
+            .protocol
+            .max_length
+            .map_or(64, |bits| bits.saturating_sub(layout.fixed_bits) / 8);
+        items.push((
+            format!("proptest::collection::vec(any::<u8>(), 0..={})", max_bytes),
+            identifier(&tail.id),
+        ));
+    }
+    let names: Vec<String> = items.iter().map(|(_, name)| name.clone()).collect();
+
+    out.push_str("#[cfg(any(test, feature = \"proptest\"))]\n");
+    let _ = writeln!(
+        out,
+        "impl proptest::arbitrary::Arbitrary for {} {{",
+        struct_name(&layout.protocol.id)
+    );
+    out.push_str("    type Parameters = ();\n");
+    out.push_str("    type Strategy = proptest::strategy::BoxedStrategy<Self>;\n\n");
+    out.push_str("    fn arbitrary_with(_: ()) -> Self::Strategy {\n");
+    out.push_str("        use proptest::prelude::*;\n");
+    if items.is_empty() {
+        out.push_str("        Just(Self {}).boxed()\n");
+    } else {
+        let (strategy, pattern) = tuple(items);
+        let _ = writeln!(out, "        {}", strategy);
+        let _ = writeln!(
+            out,
+            "            .prop_map(|{}| Self {{ {} }})",
+            pattern,
+            names.join(", ")
+        );
+        out.push_str("            .boxed()\n");
+    }
+    out.push_str("    }\n}\n");
+}
+
+/// The values the parent constraints of a protocol and its ancestors require, by field ID
+fn parent_constraints<'a>(layouts: &'a [Layout], layout: &'a Layout) -> HashMap<&'a str, i128> {
+    let ancestors = std::iter::successors(Some(layout.protocol), |protocol| {
+        let parent_id = protocol.parent_id.as_deref()?;
+        layouts
+            .iter()
+            .find(|l| l.protocol.id == parent_id)
+            .map(|l| l.protocol)
+    });
+    let mut constraints = HashMap::new();
+    for protocol in ancestors.take(layouts.len()) {
+        for (field_id, value) in &protocol.parent_constraints {
+            constraints.entry(field_id.as_str()).or_insert(*value);
+        }
+    }
+    constraints
+}
+
+/// A proptest strategy for the values of a field, `constraint` being the value a parent
+/// constraint requires
+fn strategy(field: &WireField, constraint: Option<i128>) -> String {
+    let rust_type = value_type(field);
+    if !field.is_integer() {
+        let len = field.bits.div_ceil(8);
+        // the bits above the width in the first byte stay zero
+        let mask = 0xffu8 >> (len * 8 - field.bits);
+        return format!(
+            "proptest::collection::vec(any::<u8>(), {len}).prop_map(|v| {{ \
+             let mut bytes = [0; {len}]; bytes.copy_from_slice(&v); bytes[0] &= 0x{mask:02x}; \
+             bytes }})"
+        );
+    }
+    let (min, max) = if field.is_sign_magnitude() {
+        let max = (1i128 << (field.bits - 1)) - 1;
+        (-max, max)
+    } else if field.is_signed() {
+        (
+            -(1i128 << (field.bits - 1)),
+            (1i128 << (field.bits - 1)) - 1,
+        )
+    } else {
+        (0, (1i128 << field.bits) - 1)
+    };
+    let values: Vec<i128> = match (constraint, &field.rule.field_type) {
+        (Some(value), _) | (None, &FieldType::Fixed(value)) => vec![value],
+        (None, FieldType::Enum(variants)) => variants.iter().map(|v| v.value).collect(),
+        _ => Vec::new(),
+    };
+    let values: Vec<String> = values
+        .into_iter()
+        .filter(|value| (min..=max).contains(value))
+        .map(|value| format!("{}{}", value, rust_type))
+        .collect();
+    match values.as_slice() {
+        [value] => return format!("Just({})", value),
+        [] => {}
+        values => return format!("proptest::sample::select(vec![{}])", values.join(", ")),
+    }
+
+    let (low, high) = match field.rule.field_type {
+        FieldType::Range {
+            min: low,
+            max: high,
+            ..
+        } if low.max(min) <= high.min(max) => (low.max(min), high.min(max)),
+        _ => (min, max),
+    };
+    let type_bits: u32 = rust_type[1..].parse().unwrap_or(64);
+    // sign-magnitude has no bit pattern for the smallest value of the type
+    if (low, high) == (min, max) && field.bits == type_bits && !field.is_sign_magnitude() {
+        format!("any::<{}>()", rust_type)
+    } else {
+        format!("{}{}..={}{}", low, rust_type, high, rust_type)
+    }
+}
+
+/// Strategies combined into tuples of at most 10, for which proptest implements `Strategy`,
+/// with the pattern taking their values apart
+fn tuple(items: Vec<(String, String)>) -> (String, String) {
+    if items.len() > 10 {
+        let groups = items
+            .chunks(10)
+            .map(|chunk| tuple(chunk.to_vec()))
+            .collect();
+        return tuple(groups);
+    }
+    let (strategies, patterns): (Vec<String>, Vec<String>) = items.into_iter().unzip();
+    let comma = if strategies.len() == 1 { "," } else { "" };
+    (
+        format!("({}{})", strategies.join(", "), comma),
+        format!("({}{})", patterns.join(", "), comma),
+    )
+}
+
+fn read_expression(field: &WireField) -> String {
+    if !field.is_integer() {
+        return format!("get_bytes(data, {}, {})", field.offset, field.bits);
+    }
+    let mut read = format!("get_bits(data, {}, {})", field.offset, field.bits);
+    if field.is_little_endian() {
+        read = format!("swap_bytes({}, {})", read, field.bits / 8);
+    }
+    if field.is_sign_magnitude() {
+        read = format!("from_sign_magnitude({}, {})", read, field.bits);
+    } else if field.is_signed() {
+        read = format!("sign_extend({}, {})", read, field.bits);
+    }
+    match value_type(field).as_str() {
+        "u64" => read,
+        rust_type => format!("{} as {}", read, rust_type),
+    }
+}
+
+fn write_statement(field: &WireField) -> String {
+    let value = format!("self.{}", identifier(&field.rule.id));
+    if !field.is_integer() {
+        return format!(
+            "put_bytes(&mut data, {}, {}, &{})",
+            field.offset, field.bits, value
+        );
+    }
+    let mut write = match value_type(field).as_str() {
+        "i64" if field.is_sign_magnitude() => {
+            format!("to_sign_magnitude({}, {})", value, field.bits)
+        }
+        _ if field.is_sign_magnitude() => {
+            format!("to_sign_magnitude({} as i64, {})", value, field.bits)
+        }
+        "u64" => value,
+        _ => format!("{} as u64", value),
+    };
+    if field.is_little_endian() {
+        write = format!("swap_bytes({}, {})", write, field.bits / 8);
+    }
+    format!(
+        "put_bits(&mut data, {}, {}, {})",
+        field.offset, field.bits, write
+    )
+}
+
+fn value_type(field: &WireField) -> String {
+    if !field.is_integer() {
+        return format!("[u8; {}]", field.bits.div_ceil(8));
+    }
+    let size = [8, 16, 32, 64]
+        .into_iter()
+        .find(|s| field.bits <= *s)
+        .unwrap_or(64);
+    let sign = if field.is_signed() { 'i' } else { 'u' };
+    format!("{}{}", sign, size)
+}
+
+/// A field ID as a Rust identifier, escaping keywords
+pub fn identifier(id: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "true", "type",
+        "unsafe", "use", "where", "while",
+    ];
+    let ident = super::identifier(id);
+    if KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{}", ident)
+    } else {
+        ident
+    }
+}
+
+/// `NotReady` → `NOT_READY`
+fn screaming_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+/// Helper functions, each emitted when the generated code calls it
+const HELPERS: &[(&str, &str)] = &[
+    (
+        "get_bits",
+        "\
+/// Read `width` bits starting at bit `offset`, most significant bit first
+fn get_bits(data: &[u8], offset: usize, width: usize) -> u64 {
+    (offset..offset + width).fold(0, |value, i| {
+        (value << 1) | u64::from((data[i / 8] >> (7 - i % 8)) & 1)
+    })
+}
+",
+    ),
+    (
+        "get_bytes",
+        "\
+/// Read `width` bits as big-endian bytes, right-aligned
+fn get_bytes<const N: usize>(data: &[u8], offset: usize, width: usize) -> [u8; N] {
+    let mut bytes = [0; N];
+    let start = N * 8 - width;
+    for i in 0..width {
+        let bit = (data[(offset + i) / 8] >> (7 - (offset + i) % 8)) & 1;
+        bytes[(start + i) / 8] |= bit << (7 - (start + i) % 8);
+    }
+    bytes
+}
+",
+    ),
+    (
+        "swap_bytes",
+        "\
+/// Reverse the order of the low `count` bytes of `value`
+fn swap_bytes(value: u64, count: usize) -> u64 {
+    (0..count).fold(0, |result, i| (result << 8) | ((value >> (8 * i)) & 0xff))
+}
+",
+    ),
+    (
+        "sign_extend",
+        "\
+/// Interpret the low `width` bits of `value` as a two's complement number
+fn sign_extend(value: u64, width: usize) -> i64 {
+    let shift = 64 - width;
+    ((value << shift) as i64) >> shift
+}
+",
+    ),
+    (
+        "from_sign_magnitude",
+        "\
+/// Interpret the low `width` bits of `value` as a sign bit followed by the magnitude
+fn from_sign_magnitude(value: u64, width: usize) -> i64 {
+    let magnitude = (value & ((1 << (width - 1)) - 1)) as i64;
+    if (value >> (width - 1)) & 1 == 1 { -magnitude } else { magnitude }
+}
+",
+    ),
+    (
+        "to_sign_magnitude",
+        "\
+/// `value` as a sign bit followed by the magnitude in `width` bits
+fn to_sign_magnitude(value: i64, width: usize) -> u64 {
+    let sign = if value < 0 { 1 << (width - 1) } else { 0 };
+    sign | value.unsigned_abs()
+}
+",
+    ),
+    (
+        "put_bits",
+        "\
+/// Write the low `width` bits of `value` starting at bit `offset`
+fn put_bits(data: &mut [u8], offset: usize, width: usize, value: u64) {
+    for i in 0..width {
+        let bit = ((value >> (width - 1 - i)) & 1) as u8;
+        let position = offset + i;
+        let mask = 1 << (7 - position % 8);
+        data[position / 8] = (data[position / 8] & !mask) | (bit << (7 - position % 8));
+    }
+}
+",
+    ),
+    (
+        "put_bytes",
+        "\
+/// Write right-aligned big-endian bytes as `width` bits
+fn put_bytes<const N: usize>(data: &mut [u8], offset: usize, width: usize, bytes: &[u8; N]) {
+    let start = N * 8 - width;
+    for i in 0..width {
+        let bit = (bytes[(start + i) / 8] >> (7 - (start + i) % 8)) & 1;
+        let position = offset + i;
+        let mask = 1 << (7 - position % 8);
+        data[position / 8] = (data[position / 8] & !mask) | (bit << (7 - position % 8));
+    }
+}
+",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::layouts;
+    use crate::models::field::{EnumVariant, FieldLength, FieldRule, SignEncoding};
+    use crate::models::protocol::{Endianness, ProtocolRegistry};
+
+    #[test]
+    fn test_rust_module() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Little, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "type",
+                    FieldType::Enum(vec![EnumVariant {
+                        value: 1,
+                        name: Some("Not Ready".to_string()),
+                        description: None,
+                    }]),
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "delta",
+                    FieldType::Range {
+                        min: -2048,
+                        max: 2047,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(12),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "id",
+                    FieldType::Input,
+                    FieldLength::Fixed(80),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        let layouts = layouts(&registry, "frame").unwrap();
+        let code = generate("frame", &layouts, &RustOptions::default());
+        assert!(code.contains("    pub r#type: u8,\n    pub delta: i16,\n    pub id: [u8; 10],\n    pub data: Vec<u8>,\n"));
+        assert!(code.contains("    pub const LEN: usize = 12;\n"));
+        assert!(code.contains("    pub const TYPE_NOT_READY: u8 = 1;\n"));
+        assert!(
+            code.contains("            delta: sign_extend(get_bits(data, 4, 12), 12) as i16,\n")
+        );
+        assert!(code.contains("            id: get_bytes(data, 16, 80),\n"));
+        assert!(code.contains("        put_bits(&mut data, 4, 12, self.delta as u64);\n"));
+        assert!(code.contains("fn put_bytes"));
+
+        assert!(!code.contains("Arbitrary"));
+
+        let options = RustOptions {
+            encoder: false,
+            ..Default::default()
+        };
+        let code = generate("frame", &layouts, &options);
+        assert!(!code.contains("fn encode"));
+        assert!(!code.contains("fn put_bits"));
+    }
+
+    #[test]
+    fn test_rust_sign_magnitude() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("trim", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("trim", |p| {
+                let mut rule = FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -127,
+                        max: 127,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                );
+                rule.sign_encoding = SignEncoding::SignMagnitude;
+                p.add_field(rule)
+            })
+            .unwrap();
+
+        let layouts = layouts(&registry, "trim").unwrap();
+        let options = RustOptions {
+            proptest: true,
+            ..Default::default()
+        };
+        let code = generate("trim", &layouts, &options);
+        assert!(
+            code.contains(
+                "            offset: from_sign_magnitude(get_bits(data, 0, 8), 8) as i8,\n"
+            )
+        );
+        assert!(code.contains(
+            "        put_bits(&mut data, 0, 8, to_sign_magnitude(self.offset as i64, 8));\n"
+        ));
+        assert!(code.contains("fn from_sign_magnitude"));
+        assert!(code.contains("fn to_sign_magnitude"));
+        assert!(!code.contains("fn sign_extend"));
+        assert!(code.contains("-127i8..=127i8"));
+    }
+
+    #[test]
+    fn test_rust_arbitrary() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .create_protocol("reset", None, Endianness::Big, Some("frame".to_string()))
+            .unwrap();
+        let variants = [1, 2, 300]
+            .into_iter()
+            .map(|value| EnumVariant {
+                value,
+                name: None,
+                description: None,
+            })
+            .collect();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(variants),
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "level",
+                    FieldType::Range {
+                        min: -100,
+                        max: 5000,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(12),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "flags",
+                    FieldType::Input,
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "serial",
+                    FieldType::Input,
+                    FieldLength::Fixed(70),
+                ))
+            })
+            .unwrap();
+        registry
+            .edit_protocol("reset", |p| {
+                p.set_parent_constraint("kind", 2);
+                p.max_length = Some(8 * 20);
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        let layouts = layouts(&registry, "frame").unwrap();
+        let options = RustOptions {
+            proptest: true,
+            ..Default::default()
+        };
+        let code = generate("frame", &layouts, &options);
+        assert!(code.contains("impl proptest::arbitrary::Arbitrary for Frame {\n"));
+        // the variant that does not fit in 8 bits is left out
+        assert!(code.contains(
+            "(proptest::sample::select(vec![1u8, 2u8]), -100i16..=2047i16, 0u8..=15u8, "
+        ));
+        assert!(code.contains("bytes[0] &= 0x3f;"));
+        assert!(code.contains("            .prop_map(|(kind, level, flags, serial)| Self { kind, level, flags, serial })\n"));
+        assert!(code.contains("(Just(2u8), -100i16..=2047i16, 0u8..=15u8, "));
+        assert!(code.contains("proptest::collection::vec(any::<u8>(), 0..=8))"));
+
+        let items = (0..12)
+            .map(|i| (format!("s{}", i), format!("v{}", i)))
+            .collect();
+        let (strategy, pattern) = tuple(items);
+        assert_eq!(
+            pattern,
+            "((v0, v1, v2, v3, v4, v5, v6, v7, v8, v9), (v10, v11))"
+        );
+        assert!(strategy.starts_with("((s0, "));
+    }
+}
@@ -0,0 +1,507 @@
+//! TypeScript output: an interface per protocol with `decode` and `encode` functions on a
+//! `DataView`.
+//!
+//! Byte-aligned fields of 8, 16, 32 or 64 bits use the `DataView` getters and setters; other
+//! fields go through small bit helpers working on `bigint`, which are only emitted when used.
+
+use super::{Layout, WireField, camel_case, summary, variant_name};
+use crate::models::field::FieldType;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TypeScriptOptions {
+    /// type fields wider than 32 bits as `bigint` instead of `number`, which loses precision
+    /// above 53 bits
+    pub bigint: bool,
+    /// also generate an `encode` function per protocol
+    pub encoder: bool,
+}
+
+impl Default for TypeScriptOptions {
+    fn default() -> Self {
+        Self {
+            bigint: true,
+            encoder: true,
+        }
+    }
+}
+
+pub fn generate(protocol_id: &str, layouts: &[Layout], options: &TypeScriptOptions) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated by BitLoom from protocol '{}'",
+        protocol_id
+    );
+
+    let mut enums = HashSet::new();
+    let mut helpers = BTreeSet::new();
+    for layout in layouts {
+        for field in &layout.fields {
+            if let FieldType::Enum(variants) = &field.rule.field_type
+                && enums.insert(enum_type(field))
+            {
+                out.push('\n');
+                let _ = writeln!(out, "export enum {} {{", enum_type(field));
+                for variant in variants {
+                    let _ = writeln!(
+                        out,
+                        "  {} = {},",
+                        variant_name(variant.name.as_deref(), variant.value),
+                        variant.value
+                    );
+                }
+                out.push_str("}\n");
+            }
+        }
+
+        out.push('\n');
+        protocol_code(&mut out, layout, options, &mut helpers);
+    }
+
+    for helper in helpers {
+        out.push('\n');
+        out.push_str(helper_source(helper));
+    }
+    out
+}
+
+fn protocol_code(
+    out: &mut String,
+    layout: &Layout,
+    options: &TypeScriptOptions,
+    helpers: &mut BTreeSet<Helper>,
+) {
+    let name = camel_case(&layout.protocol.id);
+    let fixed = layout.fixed_bytes();
+
+    let _ = writeln!(
+        out,
+        "/** Protocol '{}' */",
+        layout
+            .protocol
+            .name
+            .as_deref()
+            .unwrap_or(&layout.protocol.id)
+    );
+    let _ = writeln!(out, "export interface {} {{", name);
+    for field in &layout.fields {
+        let mut comments = Vec::new();
+        if let Some(description) = summary(field.rule.description.as_deref()) {
+            comments.push(description.to_string());
+        }
+        if let FieldType::Expr(script) = &field.rule.field_type {
+            comments.push(format!("computed by {}", script.trim().replace('\n', " ")));
+        }
+        if !comments.is_empty() {
+            let _ = writeln!(out, "  /** {} */", comments.join("; "));
+        }
+        let _ = writeln!(out, "  {}: {};", field.rule.id, value_type(field, options));
+    }
+    if let Some(tail) = layout.tail {
+        if let Some(description) = summary(tail.description.as_deref()) {
+            let _ = writeln!(out, "  /** {} */", description);
+        }
+        let _ = writeln!(out, "  {}: Uint8Array;", tail.id);
+    }
+    out.push_str("}\n");
+
+    out.push('\n');
+    let _ = writeln!(
+        out,
+        "export function decode{}(view: DataView): {} {{",
+        name, name
+    );
+    let _ = writeln!(
+        out,
+        "  if (view.byteLength < {}) {{\n    throw new RangeError(\"{} needs at least {} bytes\");\n  }}",
+        fixed, layout.protocol.id, fixed
+    );
+    out.push_str("  return {\n");
+    for field in &layout.fields {
+        let _ = writeln!(
+            out,
+            "    {}: {},",
+            field.rule.id,
+            read_expression(field, options, helpers)
+        );
+    }
+    if let Some(tail) = layout.tail {
+        let _ = writeln!(
+            out,
+            "    {}: new Uint8Array(view.buffer, view.byteOffset + {}, view.byteLength - {}),",
+            tail.id, fixed, fixed
+        );
+    }
+    out.push_str("  };\n}\n");
+
+    if options.encoder {
+        out.push('\n');
+        let _ = writeln!(
+            out,
+            "export function encode{}(value: {}): Uint8Array {{",
+            name, name
+        );
+        match layout.tail {
+            Some(tail) => {
+                let _ = writeln!(
+                    out,
+                    "  const bytes = new Uint8Array({} + value.{}.length);",
+                    fixed, tail.id
+                );
+            }
+            None => {
+                let _ = writeln!(out, "  const bytes = new Uint8Array({});", fixed);
+            }
+        }
+        out.push_str("  const view = new DataView(bytes.buffer);\n");
+        for field in &layout.fields {
+            let _ = writeln!(out, "  {};", write_statement(field, options, helpers));
+        }
+        if let Some(tail) = layout.tail {
+            let _ = writeln!(out, "  bytes.set(value.{}, {});", tail.id, fixed);
+        }
+        out.push_str("  return bytes;\n}\n");
+    }
+}
+
+/// Whether a field is read with a `DataView` getter
+fn native(field: &WireField) -> bool {
+    field.offset.is_multiple_of(8)
+        && matches!(field.bits, 8 | 16 | 32 | 64)
+        && !field.is_sign_magnitude()
+}
+
+fn read_expression(
+    field: &WireField,
+    options: &TypeScriptOptions,
+    helpers: &mut BTreeSet<Helper>,
+) -> String {
+    let bigint = value_type(field, options) == "bigint";
+    let offset = field.offset / 8;
+    if !field.is_integer() {
+        helpers.insert(Helper::GetBytes);
+        helpers.insert(Helper::GetBits);
+        return format!("getBytes(view, {}, {})", field.offset, field.bits);
+    }
+    if native(field) {
+        let getter = getter(field);
+        return match field.bits {
+            8 => format!("view.{}({})", getter, offset),
+            64 if !bigint => format!(
+                "Number(view.{}({}, {}))",
+                getter,
+                offset,
+                field.is_little_endian()
+            ),
+            _ => format!("view.{}({}, {})", getter, offset, field.is_little_endian()),
+        };
+    }
+
+    helpers.insert(Helper::GetBits);
+    let mut read = format!("getBits(view, {}, {})", field.offset, field.bits);
+    if field.is_little_endian() {
+        helpers.insert(Helper::SwapBytes);
+        read = format!("swapBytes({}, {})", read, field.bits / 8);
+    }
+    if field.is_sign_magnitude() {
+        helpers.insert(Helper::FromSignMagnitude);
+        read = format!("fromSignMagnitude({}, {})", read, field.bits);
+    } else if field.is_signed() {
+        read = format!("BigInt.asIntN({}, {})", field.bits, read);
+    }
+    if bigint {
+        read
+    } else {
+        format!("Number({})", read)
+    }
+}
+
+fn write_statement(
+    field: &WireField,
+    options: &TypeScriptOptions,
+    helpers: &mut BTreeSet<Helper>,
+) -> String {
+    let bigint = value_type(field, options) == "bigint";
+    let value = format!("value.{}", field.rule.id);
+    if !field.is_integer() {
+        helpers.insert(Helper::PutBytes);
+        helpers.insert(Helper::PutBits);
+        return format!(
+            "putBytes(view, {}, {}, {})",
+            field.offset, field.bits, value
+        );
+    }
+    if native(field) {
+        let setter = getter(field).replacen("get", "set", 1);
+        let offset = field.offset / 8;
+        return match field.bits {
+            8 => format!("view.{}({}, {})", setter, offset, value),
+            64 if !bigint => format!(
+                "view.{}({}, BigInt({}), {})",
+                setter,
+                offset,
+                value,
+                field.is_little_endian()
+            ),
+            _ => format!(
+                "view.{}({}, {}, {})",
+                setter,
+                offset,
+                value,
+                field.is_little_endian()
+            ),
+        };
+    }
+
+    helpers.insert(Helper::PutBits);
+    let mut write = if bigint {
+        value
+    } else {
+        format!("BigInt({})", value)
+    };
+    if field.is_sign_magnitude() {
+        helpers.insert(Helper::ToSignMagnitude);
+        write = format!("toSignMagnitude({}, {})", write, field.bits);
+    }
+    if field.is_little_endian() {
+        helpers.insert(Helper::SwapBytes);
+        write = format!("swapBytes({}, {})", write, field.bits / 8);
+    }
+    format!("putBits(view, {}, {}, {})", field.offset, field.bits, write)
+}
+
+/// Name of the `DataView` getter for a native field
+fn getter(field: &WireField) -> String {
+    let prefix = if field.bits == 64 { "Big" } else { "" };
+    let sign = if field.is_signed() { "Int" } else { "Uint" };
+    format!("get{}{}{}", prefix, sign, field.bits)
+}
+
+fn value_type(field: &WireField, options: &TypeScriptOptions) -> String {
+    if !field.is_integer() {
+        "Uint8Array".to_string()
+    } else if matches!(field.rule.field_type, FieldType::Enum(_)) && field.bits <= 32 {
+        enum_type(field)
+    } else if field.bits > 32 && options.bigint {
+        "bigint".to_string()
+    } else {
+        "number".to_string()
+    }
+}
+
+/// Enums are named after the protocol defining the field, so subprotocols share them
+fn enum_type(field: &WireField) -> String {
+    format!(
+        "{}{}",
+        camel_case(&field.owner.id),
+        camel_case(&field.rule.id)
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Helper {
+    GetBits,
+    PutBits,
+    SwapBytes,
+    FromSignMagnitude,
+    ToSignMagnitude,
+    GetBytes,
+    PutBytes,
+}
+
+fn helper_source(helper: Helper) -> &'static str {
+    match helper {
+        Helper::GetBits => {
+            "\
+/** Read `width` bits starting at bit `offset`, most significant bit first */
+function getBits(view: DataView, offset: number, width: number): bigint {
+  let value = 0n;
+  for (let i = offset; i < offset + width; i++) {
+    value = (value << 1n) | BigInt((view.getUint8(i >> 3) >> (7 - (i & 7))) & 1);
+  }
+  return value;
+}
+"
+        }
+        Helper::PutBits => {
+            "\
+/** Write the low `width` bits of `value` starting at bit `offset` */
+function putBits(view: DataView, offset: number, width: number, value: bigint): void {
+  for (let i = 0; i < width; i++) {
+    const bit = Number((value >> BigInt(width - 1 - i)) & 1n);
+    const position = offset + i;
+    const mask = 1 << (7 - (position & 7));
+    const byte = view.getUint8(position >> 3);
+    view.setUint8(position >> 3, bit ? byte | mask : byte & ~mask);
+  }
+}
+"
+        }
+        Helper::SwapBytes => {
+            "\
+/** Reverse the order of the low `count` bytes of `value` */
+function swapBytes(value: bigint, count: number): bigint {
+  let result = 0n;
+  for (let i = 0; i < count; i++) {
+    result = (result << 8n) | ((value >> BigInt(8 * i)) & 0xffn);
+  }
+  return result;
+}
+"
+        }
+        Helper::FromSignMagnitude => {
+            "\
+/** Interpret the low `width` bits of `value` as a sign bit followed by the magnitude */
+function fromSignMagnitude(value: bigint, width: number): bigint {
+  const magnitude = value & ((1n << BigInt(width - 1)) - 1n);
+  return (value >> BigInt(width - 1)) & 1n ? -magnitude : magnitude;
+}
+"
+        }
+        Helper::ToSignMagnitude => {
+            "\
+/** `value` as a sign bit followed by the magnitude in `width` bits */
+function toSignMagnitude(value: bigint, width: number): bigint {
+  return value < 0n ? (1n << BigInt(width - 1)) | -value : value;
+}
+"
+        }
+        Helper::GetBytes => {
+            "\
+/** Read `width` bits as big-endian bytes, right-aligned */
+function getBytes(view: DataView, offset: number, width: number): Uint8Array {
+  const bytes = new Uint8Array(Math.ceil(width / 8));
+  let value = getBits(view, offset, width);
+  for (let i = bytes.length - 1; i >= 0; i--) {
+    bytes[i] = Number(value & 0xffn);
+    value >>= 8n;
+  }
+  return bytes;
+}
+"
+        }
+        Helper::PutBytes => {
+            "\
+/** Write right-aligned big-endian bytes as `width` bits */
+function putBytes(view: DataView, offset: number, width: number, bytes: Uint8Array): void {
+  let value = 0n;
+  for (const byte of bytes) {
+    value = (value << 8n) | BigInt(byte);
+  }
+  putBits(view, offset, width, value);
+}
+"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::layouts;
+    use crate::models::field::{EnumVariant, FieldLength, FieldRule, SignEncoding};
+    use crate::models::protocol::{Endianness, ProtocolRegistry};
+
+    fn registry() -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Little, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(vec![EnumVariant {
+                        value: 2,
+                        name: None,
+                        description: None,
+                    }]),
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "delta",
+                    FieldType::Range {
+                        min: -2048,
+                        max: 2047,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(12),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "time",
+                    FieldType::Input,
+                    FieldLength::Fixed(64),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_typescript_module() {
+        let registry = registry();
+        let layouts = layouts(&registry, "frame").unwrap();
+        let code = generate("frame", &layouts, &TypeScriptOptions::default());
+        assert!(code.contains("export enum FrameKind {\n  Value2 = 2,\n}"));
+        assert!(code.contains(
+            "  kind: FrameKind;\n  delta: number;\n  time: bigint;\n  data: Uint8Array;\n"
+        ));
+        assert!(code.contains("    kind: Number(getBits(view, 0, 4)),\n"));
+        assert!(code.contains("    delta: Number(BigInt.asIntN(12, getBits(view, 4, 12))),\n"));
+        assert!(code.contains("    time: view.getBigUint64(2, true),\n"));
+        assert!(code.contains(
+            "    data: new Uint8Array(view.buffer, view.byteOffset + 10, view.byteLength - 10),\n"
+        ));
+        assert!(code.contains("  const bytes = new Uint8Array(10 + value.data.length);\n"));
+        assert!(code.contains("  putBits(view, 4, 12, BigInt(value.delta));\n"));
+        assert!(code.contains("function putBits("));
+        assert!(!code.contains("function swapBytes("));
+
+        let options = TypeScriptOptions {
+            bigint: false,
+            encoder: false,
+        };
+        let code = generate("frame", &layouts, &options);
+        assert!(code.contains("    time: Number(view.getBigUint64(2, true)),\n"));
+        assert!(!code.contains("encodeFrame"));
+        assert!(!code.contains("function putBits("));
+    }
+
+    #[test]
+    fn test_typescript_sign_magnitude() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("trim", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("trim", |p| {
+                let mut rule = FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -127,
+                        max: 127,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                );
+                rule.sign_encoding = SignEncoding::SignMagnitude;
+                p.add_field(rule)
+            })
+            .unwrap();
+
+        let layouts = layouts(&registry, "trim").unwrap();
+        let code = generate("trim", &layouts, &TypeScriptOptions::default());
+        assert!(code.contains("    offset: Number(fromSignMagnitude(getBits(view, 0, 8), 8)),\n"));
+        assert!(
+            code.contains("  putBits(view, 0, 8, toSignMagnitude(BigInt(value.offset), 8));\n")
+        );
+        assert!(!code.contains("getInt8"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Pairing requests with their responses in a capture by a transaction ID or sequence number
+//! they share, to follow command/ack exchanges and their round-trip times.
+
+use crate::codec::decode::DecodedPacket;
+use crate::script::ScriptEngine;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Name of the script function that returns the pairing key of a packet
+pub const PAIR_KEY_FUNCTION: &str = "pair_key";
+
+/// Parts of field IDs that suggest a field identifies a transaction
+const KEY_FIELD_HINTS: &[&str] = &[
+    "seq",
+    "transaction",
+    "txn",
+    "tid",
+    "tag",
+    "token",
+    "msg_id",
+    "request_id",
+];
+
+/// How to tell which packets belong to the same exchange
+#[derive(Clone, PartialEq, Debug)]
+pub enum PairBy {
+    /// packets with the same values of these fields
+    Fields(Vec<String>),
+    /// a script with a `pair_key(packet)` function returning the key of a packet, or `()` to
+    /// leave the packet out
+    Script(String),
+}
+
+/// A request and the response that has the same key, if one came
+#[derive(Clone, PartialEq, Debug)]
+pub struct Exchange {
+    pub key: String,
+    /// index of the request in the packets paired
+    pub request: usize,
+    pub response: Option<usize>,
+    /// times the request was sent again, identical, before the response
+    pub retransmissions: usize,
+    pub round_trip: Option<Duration>,
+}
+
+/// Fields of the packets whose IDs suggest they identify a transaction, e.g. `seq_no` or
+/// `transaction_id`
+pub fn suggest_key_fields<'a>(packets: impl IntoIterator<Item = &'a DecodedPacket>) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for packet in packets {
+        for field in &packet.fields {
+            let id = field.rule_id.to_lowercase();
+            if KEY_FIELD_HINTS.iter().any(|hint| id.contains(hint))
+                && !fields.contains(&field.rule_id)
+            {
+                fields.push(field.rule_id.clone());
+            }
+        }
+    }
+    fields
+}
+
+/// Pair the packets of a capture, each with its time and decoded fields if it decoded. A
+/// packet is a request unless an earlier request with the same key is still unanswered, in
+/// which case it is the response, or a retransmission if its fields are the same. Packets
+/// without a key are left out.
+pub fn pair_packets(
+    engine: &ScriptEngine,
+    packets: &[(Duration, Option<&DecodedPacket>)],
+    by: &PairBy,
+) -> Result<Vec<Exchange>, String> {
+    let (key_fields, ast) = match by {
+        PairBy::Fields(fields) if fields.is_empty() => {
+            return Err("Choose the fields to pair packets by".to_string());
+        }
+        PairBy::Fields(fields) => (fields.as_slice(), None),
+        PairBy::Script(script) => {
+            let ast = engine.compile(script).map_err(|e| e.to_string())?;
+            if !ast
+                .iter_functions()
+                .any(|f| f.name == PAIR_KEY_FUNCTION && f.params.len() == 1)
+            {
+                return Err(format!(
+                    "Script must define a function {}(packet)",
+                    PAIR_KEY_FUNCTION
+                ));
+            }
+            (&[][..], Some(ast))
+        }
+    };
+
+    let mut exchanges: Vec<Exchange> = Vec::new();
+    // unanswered requests by key, as indices into `exchanges`
+    let mut open: HashMap<String, usize> = HashMap::new();
+    for (index, (timestamp, packet)) in packets.iter().enumerate() {
+        let Some(packet) = packet else {
+            continue;
+        };
+        let key = match &ast {
+            Some(ast) => {
+                let fields: Vec<_> = packet
+                    .fields
+                    .iter()
+                    .map(|f| (f.rule_id.as_str(), &f.value))
+                    .collect();
+                engine
+                    .call_with_packet(ast, PAIR_KEY_FUNCTION, &fields)
+                    .map_err(|e| format!("Packet {}: {}", index + 1, e))?
+                    .map(|key| key.to_string())
+            }
+            None => key_fields
+                .iter()
+                .map(|id| Some(packet.get(id)?.value.to_string()))
+                .collect::<Option<Vec<_>>>()
+                .map(|values| values.join(", ")),
+        };
+        let Some(key) = key else {
+            continue;
+        };
+
+        match open.get(&key).map(|&e| &mut exchanges[e]) {
+            Some(exchange)
+                if packets[exchange.request].1.map(|r| &r.fields) == Some(&packet.fields) =>
+            {
+                exchange.retransmissions += 1;
+            }
+            Some(exchange) => {
+                exchange.response = Some(index);
+                exchange.round_trip = Some(timestamp.saturating_sub(packets[exchange.request].0));
+                open.remove(&key);
+            }
+            None => {
+                open.insert(key.clone(), exchanges.len());
+                exchanges.push(Exchange {
+                    key,
+                    request: index,
+                    response: None,
+                    retransmissions: 0,
+                    round_trip: None,
+                });
+            }
+        }
+    }
+    Ok(exchanges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Value;
+    use crate::codec::decode::DecodedField;
+
+    fn packet(fields: &[(&str, i128)]) -> DecodedPacket {
+        DecodedPacket {
+            protocol_id: "msg".to_string(),
+            fields: fields
+                .iter()
+                .map(|(id, value)| DecodedField {
+                    rule_id: id.to_string(),
+                    protocol_id: "msg".to_string(),
+                    bit_offset: 0,
+                    bit_len: 8,
+                    value: Value::Int(*value),
+                    is_virtual: false,
+                })
+                .collect(),
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pair_by_fields_and_script() {
+        let engine = ScriptEngine::new();
+        let decoded = [
+            packet(&[("kind", 1), ("seq_no", 5)]),
+            packet(&[("kind", 1), ("seq_no", 6)]),
+            packet(&[("kind", 1), ("seq_no", 6)]),
+            packet(&[("kind", 2), ("seq_no", 6)]),
+            packet(&[("kind", 2), ("seq_no", 5)]),
+        ];
+        assert_eq!(suggest_key_fields(&decoded), vec!["seq_no"]);
+
+        let mut packets: Vec<_> = decoded
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (Duration::from_millis(10 * i as u64), Some(p)))
+            .collect();
+        packets.insert(1, (Duration::from_millis(5), None));
+        let exchanges = pair_packets(
+            &engine,
+            &packets,
+            &PairBy::Fields(vec!["seq_no".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(exchanges.len(), 2);
+        assert_eq!(exchanges[0].key, "5");
+        assert_eq!(exchanges[0].response, Some(5));
+        assert_eq!(exchanges[0].round_trip, Some(Duration::from_millis(40)));
+        assert_eq!((exchanges[1].request, exchanges[1].response), (2, Some(4)));
+        assert_eq!(exchanges[1].retransmissions, 1);
+
+        // the script leaves out the packets of kind 2, so no request is answered
+        let script = r#"
+            fn pair_key(packet) {
+                if packet.kind == 2 { return; }
+                packet.seq_no * 10
+            }
+        "#;
+        let exchanges =
+            pair_packets(&engine, &packets, &PairBy::Script(script.to_string())).unwrap();
+        let keys: Vec<&str> = exchanges.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["50", "60"]);
+        assert!(exchanges.iter().all(|e| e.response.is_none()));
+        assert!(pair_packets(&engine, &packets, &PairBy::Script("1".to_string())).is_err());
+        assert!(pair_packets(&engine, &packets, &PairBy::Fields(vec![])).is_err());
+    }
+}
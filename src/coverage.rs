@@ -0,0 +1,232 @@
+//! Which enum variants and subprotocols a set of packets exercises, to find the message types
+//! a capture or a regression suite of saved packets never contains.
+
+use crate::codec::Value;
+use crate::codec::decode::DecodedPacket;
+use crate::error::BitLoomError;
+use crate::models::field::FieldType;
+use crate::models::preset::PacketPreset;
+use crate::models::protocol::ProtocolRegistry;
+use std::collections::HashMap;
+
+/// A packet to count: the protocol it is of and the integer values of its fields by field ID
+#[derive(Clone, PartialEq, Debug)]
+pub struct Sample {
+    pub protocol_id: String,
+    pub values: HashMap<String, i128>,
+}
+
+impl From<&DecodedPacket> for Sample {
+    fn from(packet: &DecodedPacket) -> Self {
+        let values = packet
+            .fields
+            .iter()
+            .filter_map(|f| match f.value {
+                Value::Int(v) => Some((f.rule_id.clone(), v)),
+                _ => None,
+            })
+            .collect();
+        Sample {
+            protocol_id: packet.protocol_id.clone(),
+            values,
+        }
+    }
+}
+
+impl From<&PacketPreset> for Sample {
+    fn from(preset: &PacketPreset) -> Self {
+        let values = preset
+            .values
+            .iter()
+            .filter_map(|(id, literal)| match Value::parse_literal(literal) {
+                Ok(Value::Int(v)) => Some((id.clone(), v)),
+                _ => None,
+            })
+            .collect();
+        Sample {
+            protocol_id: preset.protocol_id.clone(),
+            values,
+        }
+    }
+}
+
+/// How many packets had an enum field set to one of its variants
+#[derive(Clone, PartialEq, Debug)]
+pub struct VariantCoverage {
+    pub protocol_id: String,
+    pub field_id: String,
+    pub value: i128,
+    pub name: Option<String>,
+    pub count: usize,
+}
+
+/// How many packets were of a protocol or one of its subprotocols
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProtocolCoverage {
+    pub protocol_id: String,
+    pub count: usize,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CoverageReport {
+    /// the variants of the enum fields of the protocol, its ancestors and its subprotocols
+    pub variants: Vec<VariantCoverage>,
+    /// the protocol and its subprotocols
+    pub protocols: Vec<ProtocolCoverage>,
+    /// packets that were not of the protocol or one of its subprotocols
+    pub ignored: usize,
+}
+
+impl CoverageReport {
+    pub fn missing_variants(&self) -> impl Iterator<Item = &VariantCoverage> {
+        self.variants.iter().filter(|v| v.count == 0)
+    }
+
+    pub fn missing_protocols(&self) -> impl Iterator<Item = &ProtocolCoverage> {
+        self.protocols.iter().filter(|p| p.count == 0)
+    }
+}
+
+/// Count the packets of the protocol `protocol_id` and each of its subprotocols, and the
+/// packets setting each enum field of them to each of its variants. Protocols and variants
+/// are listed with the protocol's ancestors first and its subprotocols by generation.
+pub fn coverage(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    samples: &[Sample],
+) -> Result<CoverageReport, BitLoomError> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    if chain.is_empty() {
+        return Err(BitLoomError::ProtocolNotFound {
+            protocol_id: protocol_id.to_string(),
+        });
+    }
+    let mut report = CoverageReport::default();
+    let subprotocols = registry.get_descendant_ids(protocol_id);
+    let scope = chain.into_iter().chain(
+        subprotocols
+            .iter()
+            .filter_map(|id| registry.get_protocol(id)),
+    );
+    for protocol in scope {
+        if protocol.id == protocol_id || subprotocols.contains(&protocol.id) {
+            report.protocols.push(ProtocolCoverage {
+                protocol_id: protocol.id.clone(),
+                count: 0,
+            });
+        }
+        for field in &protocol.fields {
+            if let FieldType::Enum(variants) = &field.field_type {
+                report
+                    .variants
+                    .extend(variants.iter().map(|variant| VariantCoverage {
+                        protocol_id: protocol.id.clone(),
+                        field_id: field.id.clone(),
+                        value: variant.value,
+                        name: variant.name.clone(),
+                        count: 0,
+                    }));
+            }
+        }
+    }
+
+    for sample in samples {
+        let chain: Vec<&str> = registry
+            .get_inheritance_chain(&sample.protocol_id)
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect();
+        if !chain.contains(&protocol_id) {
+            report.ignored += 1;
+            continue;
+        }
+        for protocol in &mut report.protocols {
+            if chain.contains(&protocol.protocol_id.as_str()) {
+                protocol.count += 1;
+            }
+        }
+        for variant in &mut report.variants {
+            if chain.contains(&variant.protocol_id.as_str())
+                && sample.values.get(&variant.field_id) == Some(&variant.value)
+            {
+                variant.count += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{EnumVariant, FieldLength, FieldRule};
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_coverage() {
+        let mut registry = ProtocolRegistry::new();
+        for (id, parent) in [
+            ("frame", None),
+            ("command", Some("frame")),
+            ("reset", Some("command")),
+            ("status", Some("frame")),
+            ("other", None),
+        ] {
+            registry
+                .create_protocol(id, None, Endianness::Big, parent.map(str::to_string))
+                .unwrap();
+        }
+        let variants = (1..=3)
+            .map(|value| EnumVariant {
+                value,
+                name: Some(format!("kind{}", value)),
+                description: None,
+            })
+            .collect();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(variants),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+
+        let sample = |protocol_id: &str, kind: i128| Sample {
+            protocol_id: protocol_id.to_string(),
+            values: HashMap::from([("kind".to_string(), kind)]),
+        };
+        let samples = [
+            sample("reset", 1),
+            sample("reset", 1),
+            sample("frame", 2),
+            sample("other", 3),
+        ];
+        let report = coverage(&registry, "frame", &samples).unwrap();
+        let counts: Vec<(&str, usize)> = report
+            .protocols
+            .iter()
+            .map(|p| (p.protocol_id.as_str(), p.count))
+            .collect();
+        assert_eq!(
+            counts,
+            [("frame", 3), ("command", 2), ("status", 0), ("reset", 2)]
+        );
+        let missing: Vec<i128> = report.missing_variants().map(|v| v.value).collect();
+        assert_eq!(missing, [3]);
+        assert_eq!(report.variants[0].count, 2);
+        assert_eq!(report.ignored, 1);
+
+        // only the protocol and its subprotocols are listed, but its ancestors' enums are too
+        let report = coverage(&registry, "command", &samples).unwrap();
+        let missing: Vec<&str> = report
+            .missing_protocols()
+            .map(|p| p.protocol_id.as_str())
+            .collect();
+        assert!(missing.is_empty());
+        assert_eq!(report.variants.len(), 3);
+        assert_eq!(report.ignored, 2);
+        assert!(coverage(&registry, "gone", &samples).is_err());
+    }
+}
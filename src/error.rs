@@ -0,0 +1,92 @@
+//! Errors of the protocol model, with the protocols and fields they are about, so that callers
+//! can tell them apart and present them as they see fit.
+
+use std::fmt;
+use thiserror::Error;
+
+/// What is wrong with a protocol or field ID
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IdentifierProblem {
+    Empty,
+    /// a character other than an ASCII letter, digit or underscore
+    InvalidChar(char),
+    LeadingDigit,
+}
+
+impl fmt::Display for IdentifierProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentifierProblem::Empty => write!(f, "cannot be empty"),
+            IdentifierProblem::InvalidChar(c) => {
+                write!(f, "may only contain letters, digits and '_', not '{}'", c)
+            }
+            IdentifierProblem::LeadingDigit => write!(f, "cannot start with a digit"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Error)]
+pub enum BitLoomError {
+    #[error("Protocol with ID '{protocol_id}' does not exist")]
+    ProtocolNotFound { protocol_id: String },
+    #[error("Protocol with ID '{protocol_id}' already exists")]
+    ProtocolExists { protocol_id: String },
+    #[error("Parent protocol with ID '{parent_id}' does not exist")]
+    ParentNotFound { parent_id: String },
+    #[error("Field with ID '{field_id}' not found in protocol '{protocol_id}'")]
+    FieldNotFound {
+        protocol_id: String,
+        field_id: String,
+    },
+    #[error("Field with ID '{field_id}' already exists in protocol '{protocol_id}'")]
+    FieldExists {
+        protocol_id: String,
+        field_id: String,
+    },
+    /// `kind` is what the ID is of, e.g. "Field"
+    #[error("{kind} ID '{id}' {problem}")]
+    InvalidIdentifier {
+        kind: &'static str,
+        id: String,
+        problem: IdentifierProblem,
+    },
+    /// Only the last field on the wire may have a variable length
+    #[error(
+        "Field '{field_id}' cannot follow variable length field '{variable_id}' in protocol \
+         '{protocol_id}'"
+    )]
+    FieldAfterVariable {
+        protocol_id: String,
+        field_id: String,
+        variable_id: String,
+    },
+    #[error("The ID of field '{field_id}' cannot be changed by editing it; rename it instead")]
+    FieldIdChanged { field_id: String },
+    #[error(
+        "The ID of protocol '{protocol_id}' cannot be changed by editing it; rename it instead"
+    )]
+    ProtocolIdChanged { protocol_id: String },
+    #[error("The parent of protocol '{protocol_id}' cannot be changed after it is created")]
+    ParentChanged { protocol_id: String },
+    /// A failure of an edit made by the caller
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<String> for BitLoomError {
+    fn from(message: String) -> Self {
+        BitLoomError::Other(message)
+    }
+}
+
+impl From<&str> for BitLoomError {
+    fn from(message: &str) -> Self {
+        BitLoomError::Other(message.to_string())
+    }
+}
+
+impl From<BitLoomError> for String {
+    fn from(error: BitLoomError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,377 @@
+use crate::codegen::identifier;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, Protocol, ProtocolRegistry};
+use std::fmt::Write;
+
+/// Generate an 010 Editor binary template (`.bt`) that parses a file holding one instance of
+/// a protocol. The fields of the inheritance chain are laid out in one struct, followed by the
+/// fields of whichever subprotocol's parent constraints match, nested like the protocol tree.
+///
+/// Fixed fields, ranges and expressions are described in comments; derived fields are left
+/// out. 010 bitfields are big-endian here, so little-endian fields that do not start on a byte
+/// boundary come out big-endian.
+pub fn binary_template(registry: &ProtocolRegistry, protocol_id: &str) -> Result<String, String> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    if chain.is_empty() {
+        return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+    }
+    let struct_name = identifier(protocol_id).to_uppercase();
+
+    let mut enums = String::new();
+    let mut body = String::new();
+    let mut offset = 0;
+    for proto in &chain {
+        protocol_fields(&mut enums, &mut body, proto, 1, &mut offset)?;
+    }
+    subprotocols(registry, &mut enums, &mut body, protocol_id, 1, offset)?;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "//------------------------------------------------");
+    let _ = writeln!(out, "//--- 010 Editor Binary Template");
+    let _ = writeln!(out, "//   File: {}.bt", protocol_id);
+    let _ = writeln!(
+        out,
+        "// Generated by BitLoom from protocol '{}'",
+        protocol_id
+    );
+    let _ = writeln!(out, "//------------------------------------------------");
+    out.push_str("BitfieldDisablePadding();\nBitfieldLeftToRight();\n\n");
+    if !enums.is_empty() {
+        out.push_str(&enums);
+        out.push('\n');
+    }
+    out.push_str("typedef struct {\n");
+    out.push_str(&body);
+    let _ = writeln!(out, "}} {};\n", struct_name);
+    let _ = writeln!(out, "{} {};", struct_name, identifier(protocol_id));
+    Ok(out)
+}
+
+/// Emit the children of `parent_id` as `if` branches on their parent constraints, recursively
+fn subprotocols(
+    registry: &ProtocolRegistry,
+    enums: &mut String,
+    body: &mut String,
+    parent_id: &str,
+    depth: usize,
+    offset: u32,
+) -> Result<(), String> {
+    let mut children: Vec<&Protocol> = registry
+        .list_protocols()
+        .into_iter()
+        .filter(|p| p.parent_id.as_deref() == Some(parent_id))
+        .collect();
+    children.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let indent = "    ".repeat(depth);
+    for (i, child) in children.iter().enumerate() {
+        let mut constraints: Vec<_> = child.parent_constraints.iter().collect();
+        constraints.sort();
+        let condition = if constraints.is_empty() {
+            "true".to_string()
+        } else {
+            constraints
+                .iter()
+                .map(|(field_id, value)| format!("{} == {}", field_id, value))
+                .collect::<Vec<_>>()
+                .join(" && ")
+        };
+        let keyword = if i == 0 { "if" } else { "} else if" };
+        let _ = writeln!(body, "{}{} ({}) {{", indent, keyword, condition);
+        let _ = writeln!(body, "{}    // {}", indent, child.id);
+
+        let mut child_offset = offset;
+        protocol_fields(enums, body, child, depth + 1, &mut child_offset)?;
+        subprotocols(registry, enums, body, &child.id, depth + 1, child_offset)?;
+    }
+    if !children.is_empty() {
+        let _ = writeln!(body, "{}}}", indent);
+    }
+    Ok(())
+}
+
+fn byte_order_statement(endianness: Endianness) -> &'static str {
+    match endianness {
+        Endianness::Big => "BigEndian();",
+        Endianness::Little => "LittleEndian();",
+    }
+}
+
+/// Emit the fields a protocol defines itself; `offset` is the bit offset in the packet
+fn protocol_fields(
+    enums: &mut String,
+    body: &mut String,
+    proto: &Protocol,
+    depth: usize,
+    offset: &mut u32,
+) -> Result<(), String> {
+    let indent = "    ".repeat(depth);
+    let mut endianness = proto.endianness;
+    let _ = writeln!(body, "{}{}", indent, byte_order_statement(endianness));
+
+    for field in proto.fields.iter().filter(|f| !f.is_virtual()) {
+        // fields with a byte order of their own switch it for the fields that follow
+        if field.byte_order(proto.endianness) != endianness {
+            endianness = field.byte_order(proto.endianness);
+            let _ = writeln!(body, "{}{}", indent, byte_order_statement(endianness));
+        }
+        let bits = match field.length {
+            FieldLength::Fixed(bits) => bits,
+            FieldLength::Variable => {
+                let _ = writeln!(body, "{}ubyte {}[FileSize() - FTell()];", indent, field.id);
+                continue;
+            }
+        };
+        let aligned = offset.is_multiple_of(8);
+        *offset += bits;
+
+        let signed = matches!(
+            field.field_type,
+            FieldType::Range {
+                is_signed: true,
+                ..
+            }
+        );
+        if field.is_sign_magnitude() {
+            return Err(format!(
+                "Field '{}' is sign-magnitude, which an 010 Editor template cannot express",
+                field.id
+            ));
+        }
+        let Some(base) = integer_type(bits, signed) else {
+            if aligned && bits.is_multiple_of(8) {
+                let _ = writeln!(body, "{}ubyte {}[{}];", indent, field.id, bits / 8);
+                continue;
+            }
+            return Err(format!(
+                "Field '{}' of {} bits cannot be expressed in an 010 Editor template",
+                field.id, bits
+            ));
+        };
+
+        let type_name = match &field.field_type {
+            FieldType::Enum(variants) => {
+                let name = format!(
+                    "{}_{}_T",
+                    identifier(&proto.id).to_uppercase(),
+                    field.id.to_uppercase()
+                );
+                let entries: Vec<String> = variants
+                    .iter()
+                    .map(|v| {
+                        let label = v.name.as_deref().map(identifier).unwrap_or_default();
+                        let label = if !label.is_empty() {
+                            label.to_uppercase()
+                        } else if v.value < 0 {
+                            // a minus sign cannot appear in a label
+                            format!("VALUE_M{}", v.value.unsigned_abs())
+                        } else {
+                            format!("VALUE_{}", v.value)
+                        };
+                        format!("{} = {}", label, v.value)
+                    })
+                    .collect();
+                let _ = writeln!(
+                    enums,
+                    "typedef enum <{}> {{ {} }} {};",
+                    base,
+                    entries.join(", "),
+                    name
+                );
+                name
+            }
+            _ => base.to_string(),
+        };
+        let standard = aligned && bits == type_width(base);
+        let declaration = if standard {
+            format!("{} {}", type_name, field.id)
+        } else {
+            format!("{} {} : {}", type_name, field.id, bits)
+        };
+        let attributes = field
+            .name
+            .as_ref()
+            .map(|name| format!(" <name=\"{}\">", name.replace('"', "'")))
+            .unwrap_or_default();
+        let _ = writeln!(
+            body,
+            "{}{}{};{}",
+            indent,
+            declaration,
+            attributes,
+            field_comment(field)
+        );
+    }
+    Ok(())
+}
+
+fn field_comment(field: &FieldRule) -> String {
+    match &field.field_type {
+        FieldType::Fixed(value) => format!(" // always {}", value),
+        FieldType::Range { min, max, .. } => format!(" // {}..={}", min, max),
+        FieldType::Expr(script) => format!(" // = {}", script.trim().replace('\n', " ")),
+        _ => String::new(),
+    }
+}
+
+/// The smallest 010 integer type holding `bits` bits
+fn integer_type(bits: u32, signed: bool) -> Option<&'static str> {
+    Some(match (bits, signed) {
+        (1..=8, false) => "ubyte",
+        (1..=8, true) => "byte",
+        (9..=16, false) => "ushort",
+        (9..=16, true) => "short",
+        (17..=32, false) => "uint",
+        (17..=32, true) => "int",
+        (33..=64, false) => "uint64",
+        (33..=64, true) => "int64",
+        _ => return None,
+    })
+}
+
+fn type_width(base: &str) -> u32 {
+    match base {
+        "ubyte" | "byte" => 8,
+        "ushort" | "short" => 16,
+        "uint" | "int" => 32,
+        _ => 64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{EnumVariant, SignEncoding};
+
+    #[test]
+    fn test_binary_template() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "version",
+                    FieldType::Fixed(2),
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "flags",
+                    FieldType::Input,
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(vec![EnumVariant {
+                        value: 1,
+                        name: Some("Status report".to_string()),
+                        description: None,
+                    }]),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        registry
+            .create_protocol(
+                "status",
+                None,
+                Endianness::Little,
+                Some("frame".to_string()),
+            )
+            .unwrap();
+        registry
+            .edit_protocol("status", |p| {
+                p.set_parent_constraint("kind", 1);
+                p.add_field(FieldRule::new(
+                    "count",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        let template = binary_template(&registry, "frame").unwrap();
+        assert!(template.contains("typedef enum <ubyte> { STATUS_REPORT = 1 } FRAME_KIND_T;\n"));
+        assert!(template.contains("    ubyte version : 4; // always 2\n"));
+        assert!(template.contains("    FRAME_KIND_T kind;\n"));
+        assert!(template.contains(
+            "    if (kind == 1) {\n        // status\n        LittleEndian();\n        ushort count;\n"
+        ));
+        assert!(template.contains("        ubyte payload[FileSize() - FTell()];\n    }\n"));
+        assert!(template.ends_with("} FRAME;\n\nFRAME frame;\n"));
+    }
+
+    #[test]
+    fn test_unnamed_enum_values() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("p", None, Endianness::Big, None)
+            .unwrap();
+        let variant = |value| EnumVariant {
+            value,
+            name: None,
+            description: None,
+        };
+        registry
+            .edit_protocol("p", |p| {
+                p.add_field(FieldRule::new(
+                    "level",
+                    FieldType::Enum(vec![variant(-1), variant(2)]),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let template = binary_template(&registry, "p").unwrap();
+        assert!(template.contains("{ VALUE_M1 = -1, VALUE_2 = 2 } P_LEVEL_T;\n"));
+    }
+
+    #[test]
+    fn test_unsupported_field() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("p", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("p", |p| {
+                p.add_field(FieldRule::new(
+                    "bit",
+                    FieldType::Input,
+                    FieldLength::Fixed(1),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "huge",
+                    FieldType::Input,
+                    FieldLength::Fixed(72),
+                ))
+            })
+            .unwrap();
+        assert!(binary_template(&registry, "p").is_err());
+        assert!(binary_template(&registry, "missing").is_err());
+
+        registry
+            .create_protocol("trim", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("trim", |p| {
+                let mut rule = FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -127,
+                        max: 127,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                );
+                rule.sign_encoding = SignEncoding::SignMagnitude;
+                p.add_field(rule)
+            })
+            .unwrap();
+        assert!(binary_template(&registry, "trim").is_err());
+    }
+}
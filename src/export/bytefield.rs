@@ -0,0 +1,160 @@
+//! The layout of a protocol as markup of the LaTeX `bytefield` package, for interface control
+//! documents written in LaTeX.
+
+use crate::export::diagram::{label, wire_rows};
+use crate::models::protocol::ProtocolRegistry;
+
+/// The fields of a protocol and its ancestors as a `bytefield` environment with `row_bits` bits
+/// a row. A field is named in the first box it is in, and a field spanning rows is split into
+/// boxes with no borders between them. Rows taken up by one field are drawn as one box.
+pub fn bytefield(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    row_bits: u32,
+) -> Result<String, String> {
+    let (fields, rows) = wire_rows(registry, protocol_id, row_bits)?;
+    let row_bits = row_bits.max(1);
+    let whole = |row: &[usize]| row.len() == row_bits as usize && row.iter().all(|o| *o == row[0]);
+
+    let mut named = vec![false; fields.len()];
+    let mut name = |owner: usize| {
+        let text = if named[owner] {
+            String::new()
+        } else {
+            escape(&label(&fields[owner]))
+        };
+        named[owner] = true;
+        text
+    };
+    let mut lines = vec![format!("\\bitheader{{0-{}}}", row_bits - 1)];
+    let mut i = 0;
+    while i < rows.len() {
+        let above = i.checked_sub(1).map(|r| rows[r].as_slice());
+        if whole(&rows[i]) {
+            let owner = rows[i][0];
+            let count = rows[i..]
+                .iter()
+                .take_while(|row| whole(row) && row[0] == owner)
+                .count();
+            let below = rows.get(i + count).map(Vec::as_slice);
+            lines.push(format!(
+                "\\wordbox{}{{{}}}{{{}}}",
+                borders(owner, above, below),
+                count,
+                name(owner)
+            ));
+            i += count;
+            continue;
+        }
+        let below = rows.get(i + 1).map(Vec::as_slice);
+        let row = &rows[i];
+        let mut boxes = Vec::new();
+        let mut start = 0;
+        while start < row.len() {
+            let owner = row[start];
+            let len = row[start..].iter().take_while(|o| **o == owner).count();
+            boxes.push(format!(
+                "\\bitbox{}{{{}}}{{{}}}",
+                borders(owner, above, below),
+                len,
+                name(owner)
+            ));
+            start += len;
+        }
+        lines.push(boxes.join(" "));
+        i += 1;
+    }
+
+    Ok(format!(
+        "% Layout of protocol '{}', for \\usepackage{{bytefield}}\n\
+         \\begin{{bytefield}}[bitwidth=1.1em]{{{}}}\n  {}\n\\end{{bytefield}}\n",
+        protocol_id,
+        row_bits,
+        lines.join(" \\\\\n  ")
+    ))
+}
+
+/// Borders of the box of a field, leaving out the top or bottom one where the field continues
+/// in the row above or below
+fn borders(owner: usize, above: Option<&[usize]>, below: Option<&[usize]>) -> String {
+    let top = !above.is_some_and(|row| row.contains(&owner));
+    let bottom = !below.is_some_and(|row| row.contains(&owner));
+    if top && bottom {
+        return String::new();
+    }
+    format!(
+        "[lr{}{}]",
+        if top { "t" } else { "" },
+        if bottom { "b" } else { "" }
+    )
+}
+
+/// Text with the characters LaTeX treats specially escaped
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_bytefield() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                let fields = [
+                    ("kind", 4),
+                    ("flags", 4),
+                    ("data_len", 8),
+                    ("address", 32),
+                    ("time", 24),
+                ];
+                for (id, bits) in fields {
+                    p.add_field(FieldRule::new(
+                        id,
+                        FieldType::Input,
+                        FieldLength::Fixed(bits),
+                    ))?;
+                }
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        assert_eq!(
+            bytefield(&registry, "msg", 16).unwrap(),
+            concat!(
+                "% Layout of protocol 'msg', for \\usepackage{bytefield}\n",
+                "\\begin{bytefield}[bitwidth=1.1em]{16}\n",
+                "  \\bitheader{0-15} \\\\\n",
+                "  \\bitbox{4}{kind} \\bitbox{4}{flags} \\bitbox{8}{data\\_len} \\\\\n",
+                "  \\wordbox{2}{address} \\\\\n",
+                "  \\wordbox[lrt]{1}{time} \\\\\n",
+                "  \\bitbox[lrb]{8}{} \\bitbox{8}{data (variable)}\n",
+                "\\end{bytefield}\n",
+            )
+        );
+    }
+}
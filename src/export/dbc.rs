@@ -0,0 +1,267 @@
+use crate::import::dbc::{CAN_ID_KEY, DLC_KEY, NO_NODE, TRANSMITTER_KEY, parse_scaling};
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, ProtocolRegistry};
+use std::fmt::Write;
+
+/// Generate a Vector DBC file with one message for a protocol and each of its subprotocols
+/// that has a `can_id` in its metadata. Every wire field of up to 64 bits, including the
+/// inherited ones, becomes a signal.
+///
+/// Scaling and unit are read from a derived field `<field>_phys` as written by the DBC import,
+/// and the `reserved_*` padding fields it creates are left out.
+pub fn dbc_database(registry: &ProtocolRegistry, protocol_id: &str) -> Result<String, String> {
+    if registry.get_protocol(protocol_id).is_none() {
+        return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+    }
+    let mut ids = vec![protocol_id.to_string()];
+    let mut descendants = registry.get_descendant_ids(protocol_id);
+    descendants.sort();
+    ids.extend(descendants);
+
+    let mut nodes: Vec<String> = Vec::new();
+    let mut messages = String::new();
+    let mut comments = String::new();
+    let mut value_tables = String::new();
+    for id in &ids {
+        let Some(proto) = registry.get_protocol(id) else {
+            continue;
+        };
+        let Some(can_id) = proto.metadata.get(CAN_ID_KEY) else {
+            continue;
+        };
+        let can_id: u32 = can_id.trim().parse().map_err(|_| {
+            format!(
+                "Protocol '{}' has invalid {} '{}'",
+                proto.id, CAN_ID_KEY, can_id
+            )
+        })?;
+        let transmitter = proto
+            .metadata
+            .get(TRANSMITTER_KEY)
+            .cloned()
+            .unwrap_or_else(|| NO_NODE.to_string());
+        if transmitter != NO_NODE && !nodes.contains(&transmitter) {
+            nodes.push(transmitter.clone());
+        }
+
+        let chain = registry.get_inheritance_chain(id);
+        let fields: Vec<(&FieldRule, Endianness)> = chain
+            .iter()
+            .flat_map(|p| p.fields.iter().map(|f| (f, f.byte_order(p.endianness))))
+            .collect();
+
+        let mut signals = String::new();
+        let mut offset = 0u32;
+        for (field, endianness) in &fields {
+            let FieldLength::Fixed(bits) = field.length else {
+                continue;
+            };
+            if field.is_virtual() {
+                continue;
+            }
+            if field.is_sign_magnitude() {
+                return Err(format!(
+                    "Field '{}' is sign-magnitude, which DBC signals cannot express",
+                    field.id
+                ));
+            }
+            let field_offset = offset;
+            offset += bits;
+            if bits > 64 || is_padding(field) {
+                continue;
+            }
+
+            let (factor, scale_offset, unit) = fields
+                .iter()
+                .find(|(f, _)| f.id == format!("{}_phys", field.id))
+                .and_then(|(f, _)| {
+                    let (factor, offset) = parse_scaling(f.field_type.script()?, &field.id)?;
+                    let unit = f
+                        .name
+                        .as_deref()
+                        .and_then(|n| n.strip_suffix(']'))
+                        .and_then(|n| n.rsplit_once('['))
+                        .map_or(String::new(), |(_, unit)| unit.to_string());
+                    Some((factor, offset, unit))
+                })
+                .unwrap_or((1.0, 0.0, String::new()));
+
+            let intel = *endianness == Endianness::Little
+                && bits > 8
+                && bits.is_multiple_of(8)
+                && field_offset.is_multiple_of(8);
+            let (start, order) = if intel {
+                (field_offset, 1)
+            } else {
+                // Motorola signals start at their most significant bit
+                ((field_offset / 8) * 8 + 7 - field_offset % 8, 0)
+            };
+            let (signed, min, max) = match field.field_type {
+                FieldType::Range {
+                    min,
+                    max,
+                    is_signed,
+                } => (
+                    is_signed,
+                    min as f64 * factor + scale_offset,
+                    max as f64 * factor + scale_offset,
+                ),
+                _ => (false, 0.0, 0.0),
+            };
+            let _ = writeln!(
+                signals,
+                " SG_ {} : {}|{}@{}{} ({},{}) [{}|{}] \"{}\" {}",
+                field.id,
+                start,
+                bits,
+                order,
+                if signed { '-' } else { '+' },
+                factor,
+                scale_offset,
+                min.min(max),
+                min.max(max),
+                unit.replace('"', "'"),
+                NO_NODE
+            );
+
+            if let Some(description) = &field.description {
+                let _ = writeln!(
+                    comments,
+                    "CM_ SG_ {} {} \"{}\";",
+                    can_id,
+                    field.id,
+                    quote(description)
+                );
+            }
+            if let FieldType::Enum(variants) = &field.field_type {
+                let _ = write!(value_tables, "VAL_ {} {}", can_id, field.id);
+                for variant in variants {
+                    let label = variant
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| variant.value.to_string());
+                    let _ = write!(value_tables, " {} \"{}\"", variant.value, quote(&label));
+                }
+                value_tables.push_str(" ;\n");
+            }
+        }
+
+        let dlc = match proto.metadata.get(DLC_KEY) {
+            Some(dlc) => dlc.trim().parse::<u32>().map_err(|_| {
+                format!("Protocol '{}' has invalid {} '{}'", proto.id, DLC_KEY, dlc)
+            })?,
+            None => offset.div_ceil(8),
+        };
+        let _ = writeln!(
+            messages,
+            "BO_ {} {}: {} {}",
+            can_id,
+            message_name(&proto.id),
+            dlc,
+            transmitter
+        );
+        messages.push_str(&signals);
+        messages.push('\n');
+        if let Some(description) = &proto.description {
+            let _ = writeln!(comments, "CM_ BO_ {} \"{}\";", can_id, quote(description));
+        }
+    }
+
+    if messages.is_empty() {
+        return Err(format!(
+            "Neither '{}' nor its subprotocols have a '{}' in their metadata",
+            protocol_id, CAN_ID_KEY
+        ));
+    }
+
+    let mut out = String::new();
+    out.push_str("VERSION \"\"\n\n\nNS_ :\n\tCM_\n\tVAL_\n\nBS_:\n\n");
+    let _ = writeln!(out, "BU_: {}\n\n", nodes.join(" "));
+    out.push_str(&messages);
+    if !comments.is_empty() {
+        out.push('\n');
+        out.push_str(&comments);
+    }
+    if !value_tables.is_empty() {
+        out.push('\n');
+        out.push_str(&value_tables);
+    }
+    Ok(out)
+}
+
+/// Whether a field is padding added by the DBC import
+fn is_padding(field: &FieldRule) -> bool {
+    field.id.starts_with("reserved_") && field.field_type == FieldType::Input
+}
+
+/// DBC names are C identifiers
+fn message_name(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::dbc::import_dbc;
+
+    const DBC: &str = r#"VERSION ""
+
+BU_: Engine
+
+BO_ 256 EngineData: 8 Engine
+ SG_ EngineSpeed : 7|16@0+ (0.25,0) [0|16383.75] "rpm" Vector__XXX
+ SG_ Gear : 19|4@0+ (1,0) [0|0] "" Vector__XXX
+ SG_ Temperature : 31|8@0- (1,-40) [-40|87] "degC" Vector__XXX
+
+BO_ 512 Status: 4 Vector__XXX
+ SG_ Counter : 0|16@1+ (1,0) [0|0] "" Vector__XXX
+ SG_ Mode : 16|8@1+ (1,0) [0|0] "" Vector__XXX
+
+CM_ SG_ 256 EngineSpeed "Crankshaft \"speed\"";
+VAL_ 256 Gear 0 "Neutral" 1 "First" ;
+"#;
+
+    fn registry() -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .add_protocols(import_dbc(DBC).unwrap().protocols)
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let registry = registry();
+        let engine = dbc_database(&registry, "EngineData").unwrap();
+        let expected = DBC.lines().filter(|l| {
+            ["256", "Engine", "Gear", "Temperature"]
+                .iter()
+                .any(|s| l.contains(s))
+        });
+        for line in expected.filter(|l| !l.starts_with("BU_")) {
+            assert!(engine.contains(line), "missing '{}' in\n{}", line, engine);
+        }
+        assert!(engine.contains("BU_: Engine\n"));
+        assert!(!engine.contains("reserved"));
+
+        let status = dbc_database(&registry, "Status").unwrap();
+        assert!(status.contains(" SG_ Counter : 0|16@1+ (1,0) [0|0] \"\" Vector__XXX\n"));
+        assert!(status.contains(" SG_ Mode : 23|8@0+"));
+    }
+
+    #[test]
+    fn test_without_can_id() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("plain", None, Endianness::Big, None)
+            .unwrap();
+        assert!(dbc_database(&registry, "plain").is_err());
+        assert!(dbc_database(&registry, "missing").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::models::history::RevisionHistory;
+use crate::models::preset::PacketPreset;
+use crate::models::project::{BitLoomProject, PROJECT_VERSION};
+use crate::models::protocol::{Protocol, ProtocolRegistry};
+use std::collections::BTreeMap;
+
+/// Export a protocol with its ancestors and subprotocols as a `.bitloom` project document,
+/// conforming to [`crate::models::schema::project_schema`]. The script library is included,
+/// as the protocols' expressions may call its functions, and so are the packet presets of the
+/// exported protocols; the revision history and bookmarks are not.
+pub fn protocol_definitions(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    script_library: &str,
+    presets: &[PacketPreset],
+) -> Result<String, String> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    if chain.is_empty() {
+        return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+    }
+    let descendants = registry.get_descendant_ids(protocol_id);
+    let protocols: Vec<Protocol> = chain
+        .into_iter()
+        .chain(
+            descendants
+                .iter()
+                .filter_map(|id| registry.get_protocol(id)),
+        )
+        .cloned()
+        .collect();
+
+    let presets = presets
+        .iter()
+        .filter(|preset| protocols.iter().any(|p| p.id == preset.protocol_id))
+        .cloned()
+        .collect();
+
+    let project = BitLoomProject {
+        project_version: PROJECT_VERSION,
+        protocols,
+        history: RevisionHistory::new(),
+        script_library: script_library.to_string(),
+        presets,
+        bookmarks: Vec::new(),
+        packet_columns: BTreeMap::new(),
+    };
+    serde_json::to_string_pretty(&project)
+        .map_err(|e| format!("Failed to serialize protocol '{}': {}", protocol_id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_definitions_roundtrip() {
+        let mut registry = ProtocolRegistry::new();
+        for (id, parent) in [("root", None), ("mid", Some("root")), ("leaf", Some("mid"))] {
+            registry
+                .create_protocol(id, None, Endianness::Big, parent.map(str::to_string))
+                .unwrap();
+        }
+        registry
+            .create_protocol("other", None, Endianness::Big, None)
+            .unwrap();
+
+        let presets: Vec<PacketPreset> = ["leaf", "other"]
+            .into_iter()
+            .map(|id| PacketPreset {
+                name: format!("{} packet", id),
+                folder: String::new(),
+                protocol_id: id.to_string(),
+                values: Default::default(),
+                notes: String::new(),
+            })
+            .collect();
+
+        let json = protocol_definitions(&registry, "mid", "const X = 1;", &presets).unwrap();
+        let project: BitLoomProject = serde_json::from_str(&json).unwrap();
+        let ids: Vec<&str> = project.protocols.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["root", "mid", "leaf"]);
+        assert_eq!(project.script_library, "const X = 1;");
+        assert_eq!(project.presets, presets[..1]);
+
+        let mut imported = ProtocolRegistry::new();
+        imported.add_protocols(project.protocols).unwrap();
+        assert_eq!(imported.get_protocol("leaf"), registry.get_protocol("leaf"));
+    }
+}
@@ -0,0 +1,189 @@
+//! The layout of a protocol as the text diagram of RFCs, for code comments, commit messages and
+//! chat:
+//!
+//! ```text
+//!  0                   1
+//!  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! | kind  | flags |    length     |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! ```
+
+use crate::models::field::{FieldLength, FieldRule};
+use crate::models::protocol::ProtocolRegistry;
+
+/// Bits in a row of a diagram, as in the diagrams of most RFCs
+pub const ROW_BITS: u32 = 32;
+
+/// The fields of a protocol and its ancestors on the wire, and the field each bit belongs to
+/// as an index into them, in rows of `row_bits` bits. A variable length field fills the rest of
+/// its row, and the fields after it start on the next row.
+pub(crate) fn wire_rows(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    row_bits: u32,
+) -> Result<(Vec<FieldRule>, Vec<Vec<usize>>), String> {
+    let row_bits = row_bits.max(1) as usize;
+    let mut fields = Vec::new();
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    let mut row: Vec<usize> = Vec::new();
+    for field in registry.resolve_fields(protocol_id)? {
+        let bits = match field.length {
+            _ if field.is_virtual() => continue,
+            FieldLength::Fixed(0) => continue,
+            FieldLength::Fixed(bits) => bits as usize,
+            FieldLength::Variable => row_bits - row.len(),
+        };
+        for _ in 0..bits {
+            row.push(fields.len());
+            if row.len() == row_bits {
+                rows.push(std::mem::take(&mut row));
+            }
+        }
+        fields.push(field);
+    }
+    if !row.is_empty() {
+        rows.push(row);
+    }
+    if rows.is_empty() {
+        return Err(format!(
+            "Protocol '{}' has no fields on the wire",
+            protocol_id
+        ));
+    }
+    Ok((fields, rows))
+}
+
+/// Label of a field in a diagram, which says if its length varies
+pub(crate) fn label(field: &FieldRule) -> String {
+    match field.length {
+        FieldLength::Variable => format!("{} (variable)", field.id),
+        FieldLength::Fixed(_) => field.id.clone(),
+    }
+}
+
+/// The fields of a protocol and its ancestors drawn in rows of `row_bits` bits, each bit two
+/// characters wide. A field is named in the first row it is in; the lines between rows are left
+/// out where a field continues.
+pub fn ascii_diagram(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    row_bits: u32,
+) -> Result<String, String> {
+    let (fields, rows) = wire_rows(registry, protocol_id, row_bits)?;
+    let labels: Vec<String> = fields.iter().map(label).collect();
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = String::new();
+    let digit = |i: usize, tens: bool| {
+        let digit = if tens { i / 10 % 10 } else { i % 10 };
+        if tens && !i.is_multiple_of(10) {
+            "  ".to_string()
+        } else {
+            format!(" {}", digit)
+        }
+    };
+    for tens in [true, false] {
+        let line: String = (0..width).map(|i| digit(i, tens)).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    let mut named = vec![false; labels.len()];
+    out.push_str(&separator(&[], &rows[0]));
+    for (i, row) in rows.iter().enumerate() {
+        let mut line = String::new();
+        let mut start = 0;
+        while start < row.len() {
+            let owner = row[start];
+            let len = row[start..].iter().take_while(|o| **o == owner).count();
+            let label = if named[owner] { "" } else { &labels[owner] };
+            named[owner] = true;
+            line.push('|');
+            line.push_str(&centered(label, len * 2 - 1));
+            start += len;
+        }
+        line.push_str("|\n");
+        out.push_str(&line);
+        out.push_str(&separator(row, rows.get(i + 1).map_or(&[], |r| r)));
+    }
+    Ok(out)
+}
+
+/// The line between two rows, open where a field continues from one to the other
+fn separator(above: &[usize], below: &[usize]) -> String {
+    let len = above.len().max(below.len());
+    let same = |i: usize| matches!((above.get(i), below.get(i)), (Some(a), Some(b)) if a == b);
+    let mut line = String::new();
+    for i in 0..=len {
+        // a corner is open only inside a field spanning both sides of it on both rows
+        let inside = i > 0 && i < len && same(i - 1) && same(i) && above[i - 1] == above[i];
+        line.push(if inside { ' ' } else { '+' });
+        if i < len {
+            line.push(if same(i) { ' ' } else { '-' });
+        }
+    }
+    line.push('\n');
+    line
+}
+
+/// Text centered in a width, cut to fit it
+fn centered(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width).collect();
+    let len = text.chars().count();
+    let left = (width - len) / 2;
+    format!(
+        "{}{}{}",
+        " ".repeat(left),
+        text,
+        " ".repeat(width - len - left)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_ascii_diagram() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                for (id, bits) in [("kind", 4), ("flags", 4), ("length", 8), ("time", 24)] {
+                    p.add_field(FieldRule::new(
+                        id,
+                        FieldType::Input,
+                        FieldLength::Fixed(bits),
+                    ))?;
+                }
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        let diagram = ascii_diagram(&registry, "msg", 16).unwrap();
+        assert_eq!(
+            diagram,
+            concat!(
+                " 0                   1\n",
+                " 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5\n",
+                "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+\n",
+                "| kind  | flags |    length     |\n",
+                "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+\n",
+                "|             time              |\n",
+                "+               +-+-+-+-+-+-+-+-+\n",
+                "|               |data (variable)|\n",
+                "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+\n",
+            )
+        );
+        assert!(ascii_diagram(&registry, "missing", 32).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! Golden-file regression tests: the saved packets of a protocol as a JSON bundle of packet
+//! bytes and the values BitLoom decodes from them, and a Rust test checking the generated
+//! decoder against the bundle, so a firmware repository's CI catches protocol regressions.
+
+use crate::codec::Value;
+use crate::codec::decode::decode;
+use crate::codec::encode::encode;
+use crate::codec::json::value_to_json;
+use crate::codegen::rust::{RustOptions, generate, identifier, struct_name};
+use crate::codegen::{Layout, WireField, layouts};
+use crate::export::fuzz::module_name;
+use crate::models::preset::PacketPreset;
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// A saved packet with the values its fields must decode to
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GoldenCase {
+    /// the folder and name of the saved packet
+    pub name: String,
+    pub protocol: String,
+    /// the packet as hex digits
+    pub hex: String,
+    /// value of each wire field by field ID, as the generated Rust decoder represents it
+    pub expected: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GoldenBundle {
+    pub protocol: String,
+    pub cases: Vec<GoldenCase>,
+}
+
+/// The file name of the bundle of a protocol, which the test harness includes
+pub fn bundle_file_name(protocol_id: &str) -> String {
+    format!("{}.golden.json", module_name(protocol_id))
+}
+
+/// Encode the saved packets of a protocol and its subprotocols and decode them again,
+/// recording the bytes and the decoded values of every wire field
+pub fn golden_bundle(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    presets: &[PacketPreset],
+) -> Result<GoldenBundle, String> {
+    let layouts = layouts(registry, protocol_id)?;
+    let mut cases = Vec::new();
+    for preset in presets {
+        let Some(layout) = layouts.iter().find(|l| l.protocol.id == preset.protocol_id) else {
+            continue;
+        };
+        if !registry
+            .get_inheritance_chain(&preset.protocol_id)
+            .iter()
+            .any(|p| p.id == protocol_id)
+        {
+            continue;
+        }
+        let values = preset
+            .values
+            .iter()
+            .map(|(id, literal)| {
+                let value = Value::parse_literal(literal).map_err(|e| {
+                    format!("Saved packet '{}', field '{}': {}", preset.path(), id, e)
+                })?;
+                Ok((id.clone(), value))
+            })
+            .collect::<Result<HashMap<String, Value>, String>>()?;
+        let data = encode(registry, engine, &preset.protocol_id, &values)
+            .map_err(|e| format!("Saved packet '{}': {}", preset.path(), e))?;
+        let packet = decode(registry, engine, &preset.protocol_id, &data)
+            .map_err(|e| format!("Saved packet '{}' does not decode: {}", preset.path(), e))?;
+        cases.push(GoldenCase {
+            name: preset.path(),
+            protocol: preset.protocol_id.clone(),
+            hex: data.iter().map(|b| format!("{:02x}", b)).collect(),
+            expected: expected_values(layout, |id| packet.get(id).map(|f| &f.value)),
+        });
+    }
+    if cases.is_empty() {
+        return Err(format!(
+            "Protocol '{}' and its subprotocols have no saved packets",
+            protocol_id
+        ));
+    }
+    Ok(GoldenBundle {
+        protocol: protocol_id.to_string(),
+        cases,
+    })
+}
+
+/// The decoded values of the wire fields of a layout in the types of the generated Rust
+/// decoder: signed fields sign-extended and fields wider than 64 bits as bytes in wire order
+fn expected_values<'a>(
+    layout: &Layout,
+    value: impl Fn(&str) -> Option<&'a Value>,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut expected = BTreeMap::new();
+    for field in &layout.fields {
+        if let Some(value) = value(&field.rule.id) {
+            expected.insert(
+                field.rule.id.clone(),
+                value_to_json(&wire_value(field, value)),
+            );
+        }
+    }
+    if let Some(tail) = layout.tail
+        && let Some(value) = value(&tail.id)
+    {
+        expected.insert(tail.id.clone(), value_to_json(value));
+    }
+    expected
+}
+
+fn wire_value(field: &WireField, value: &Value) -> Value {
+    let bits = field.bits as usize;
+    match value {
+        Value::Int(v) if field.is_integer() => {
+            if field.is_signed() && bits > 0 && *v >= 1 << (bits - 1) {
+                Value::Int(v - (1 << bits))
+            } else {
+                Value::Int(*v)
+            }
+        }
+        Value::Int(v) => {
+            let len = bits.div_ceil(8);
+            let bytes = v.to_be_bytes();
+            let mut bytes = bytes[bytes.len().saturating_sub(len)..].to_vec();
+            bytes.splice(
+                0..0,
+                std::iter::repeat_n(0, len.saturating_sub(bytes.len())),
+            );
+            if field.is_little_endian() {
+                bytes.reverse();
+            }
+            Value::Bytes(bytes)
+        }
+        Value::Bytes(bytes) if field.is_little_endian() => {
+            Value::Bytes(bytes.iter().rev().copied().collect())
+        }
+        value => value.clone(),
+    }
+}
+
+/// A Rust integration test decoding every packet of the bundle of a protocol with the
+/// generated decoder, which it includes as a module, and comparing the fields with the values
+/// in the bundle. It goes in a crate's `tests` directory next to the bundle and needs
+/// `serde_json` as a dev-dependency.
+pub fn golden_harness(registry: &ProtocolRegistry, protocol_id: &str) -> Result<String, String> {
+    let layouts = layouts(registry, protocol_id)?;
+    let options = RustOptions {
+        encoder: false,
+        ..Default::default()
+    };
+    let decoder = generate(protocol_id, &layouts, &options);
+    let module = module_name(protocol_id);
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Golden-file regression test generated by BitLoom from protocol '{}'",
+        protocol_id
+    );
+    let _ = writeln!(
+        out,
+        "// Put it in the crate's `tests` directory next to `{}` and add `serde_json` to the\n// dev-dependencies.",
+        bundle_file_name(protocol_id)
+    );
+    out.push_str("\nuse serde_json::{Value, json};\n\n");
+    let _ = writeln!(
+        out,
+        "const BUNDLE: &str = include_str!(\"{}\");\n",
+        bundle_file_name(protocol_id)
+    );
+    out.push_str(
+        "fn hex(text: &str) -> Vec<u8> {\n    (0..text.len())\n        .step_by(2)\n        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())\n        .collect()\n}\n\n",
+    );
+    out.push_str("/// The fields of a packet decoded as `protocol`, by field ID\n");
+    out.push_str("fn decode(protocol: &str, data: &[u8]) -> Option<Value> {\n");
+    out.push_str("    match protocol {\n");
+    for layout in &layouts {
+        let _ = writeln!(
+            out,
+            "        {:?} => {{\n            let packet = {}::{}::decode(data).ok()?;",
+            layout.protocol.id,
+            module,
+            struct_name(&layout.protocol.id)
+        );
+        let fields: Vec<String> = layout
+            .fields
+            .iter()
+            .map(|field| &field.rule.id)
+            .chain(layout.tail.map(|tail| &tail.id))
+            .map(|id| format!("{:?}: packet.{}", id, identifier(id)))
+            .collect();
+        let _ = writeln!(
+            out,
+            "            Some(json!({{ {} }}))\n        }}",
+            fields.join(", ")
+        );
+    }
+    out.push_str("        _ => None,\n    }\n}\n\n");
+    out.push_str("#[test]\nfn golden_packets() {\n");
+    out.push_str("    let bundle: Value = serde_json::from_str(BUNDLE).unwrap();\n");
+    out.push_str("    for case in bundle[\"cases\"].as_array().unwrap() {\n");
+    out.push_str("        let name = case[\"name\"].as_str().unwrap();\n");
+    out.push_str("        let protocol = case[\"protocol\"].as_str().unwrap();\n");
+    out.push_str("        let data = hex(case[\"hex\"].as_str().unwrap());\n");
+    out.push_str(
+        "        let decoded = decode(protocol, &data)\n            .unwrap_or_else(|| panic!(\"{}: does not decode as {}\", name, protocol));\n",
+    );
+    out.push_str("        for (field, expected) in case[\"expected\"].as_object().unwrap() {\n");
+    out.push_str(
+        "            assert_eq!(&decoded[field], expected, \"{}: field {}\", name, field);\n",
+    );
+    out.push_str("        }\n    }\n}\n\n");
+
+    out.push_str("#[allow(dead_code)]\n");
+    let _ = writeln!(out, "mod {} {{", module);
+    for line in decoder.lines() {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            let _ = writeln!(out, "    {}", line);
+        }
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType, SignEncoding};
+    use crate::models::protocol::Endianness;
+
+    fn preset(name: &str, protocol_id: &str, values: &[(&str, &str)]) -> PacketPreset {
+        PacketPreset {
+            name: name.to_string(),
+            folder: String::new(),
+            protocol_id: protocol_id.to_string(),
+            values: values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_golden_bundle() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .create_protocol("reading", None, Endianness::Big, Some("frame".to_string()))
+            .unwrap();
+        registry
+            .create_protocol("trim", None, Endianness::Big, Some("frame".to_string()))
+            .unwrap();
+        registry
+            .create_protocol("other", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Fixed(1),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        registry
+            .edit_protocol("reading", |p| {
+                p.add_field(FieldRule::new(
+                    "level",
+                    FieldType::Range {
+                        min: -100,
+                        max: 100,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        registry
+            .edit_protocol("trim", |p| {
+                let mut rule = FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -127,
+                        max: 127,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                );
+                rule.sign_encoding = SignEncoding::SignMagnitude;
+                p.add_field(rule)
+            })
+            .unwrap();
+
+        let engine = ScriptEngine::new();
+        let presets = [
+            preset("low", "reading", &[("level", "5"), ("data", "[0a 0b]")]),
+            preset("unrelated", "other", &[]),
+            preset("negative", "reading", &[("level", "-2"), ("data", "[]")]),
+            preset("trimmed", "trim", &[("offset", "-2")]),
+        ];
+        let bundle = golden_bundle(&registry, &engine, "frame", &presets).unwrap();
+        assert_eq!(bundle.cases.len(), 3);
+        let case = &bundle.cases[0];
+        assert_eq!(case.hex, "01050a0b");
+        assert_eq!(case.expected["kind"], serde_json::json!(1));
+        assert_eq!(case.expected["level"], serde_json::json!(5));
+        assert_eq!(case.expected["data"], serde_json::json!([10, 11]));
+        let case = &bundle.cases[1];
+        assert_eq!(case.hex, "01fe");
+        assert_eq!(case.expected["level"], serde_json::json!(-2));
+        // sign-magnitude: the sign bit and a magnitude of 2
+        let case = &bundle.cases[2];
+        assert_eq!(case.hex, "0182");
+        assert_eq!(case.expected["offset"], serde_json::json!(-2));
+        assert!(golden_bundle(&registry, &engine, "frame", &presets[1..2]).is_err());
+
+        let harness = golden_harness(&registry, "frame").unwrap();
+        assert!(harness.contains("const BUNDLE: &str = include_str!(\"frame.golden.json\");"));
+        assert!(harness.contains("let packet = frame::Reading::decode(data).ok()?;"));
+        assert!(harness.contains(
+            "Some(json!({ \"kind\": packet.kind, \"level\": packet.level, \"data\": packet.data }))"
+        ));
+        assert!(!harness.contains("fn encode"));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::models::field::{FieldLength, FieldType};
+use crate::models::protocol::{ProtocolLength, ProtocolRegistry};
+use std::fmt::Write;
+
+/// Generate Markdown documentation for a protocol, including inherited fields.
+/// Descriptions are already Markdown and are embedded as-is.
+pub fn protocol_documentation(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+) -> Result<String, String> {
+    let proto = registry
+        .get_protocol(protocol_id)
+        .ok_or_else(|| format!("Protocol with ID '{}' does not exist", protocol_id))?;
+    let chain = registry.get_inheritance_chain(protocol_id);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", proto.name.as_deref().unwrap_or(&proto.id));
+    let _ = writeln!(out, "- ID: `{}`", proto.id);
+    if let Some(parent_id) = &proto.parent_id {
+        let _ = writeln!(out, "- Parent: `{}`", parent_id);
+    }
+    let _ = writeln!(out, "- Endianness: {:?}", proto.endianness);
+    let _ = writeln!(
+        out,
+        "- Total length: {}",
+        match registry.get_total_length(protocol_id) {
+            ProtocolLength::Fixed(bits) => format!("{} bits", bits),
+            ProtocolLength::Variable(bits) => format!("variable, at least {} bits", bits),
+        }
+    );
+    let mut constraints: Vec<_> = proto.parent_constraints.iter().collect();
+    constraints.sort();
+    for (field_id, value) in constraints {
+        let _ = writeln!(out, "- Applies when `{}` == {}", field_id, value);
+    }
+    out.push('\n');
+
+    if let Some(description) = &proto.description {
+        let _ = writeln!(out, "{}\n", description.trim());
+    }
+
+    out.push_str("## Fields\n\n");
+    out.push_str("| Field | Name | Type | Length | Defined in |\n");
+    out.push_str("|---|---|---|---|---|\n");
+    for p in &chain {
+        for field in &p.fields {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | `{}` |",
+                field.id,
+                table_cell(field.name.as_deref().unwrap_or("")),
+                field.field_type.kind_name(),
+                match field.length {
+                    _ if field.is_virtual() => "-".to_string(),
+                    FieldLength::Fixed(bits) => format!("{} bits", bits),
+                    FieldLength::Variable => "variable".to_string(),
+                },
+                p.id
+            );
+        }
+    }
+    out.push('\n');
+
+    for field in chain.iter().flat_map(|p| &p.fields) {
+        let _ = writeln!(out, "### `{}`\n", field.id);
+        if let Some(description) = &field.description {
+            let _ = writeln!(out, "{}\n", description.trim());
+        }
+
+        match &field.field_type {
+            FieldType::Fixed(value) => {
+                let _ = writeln!(out, "Always `{}`.\n", value);
+            }
+            FieldType::Range {
+                min,
+                max,
+                is_signed,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "Range `{}..={}`{}.\n",
+                    min,
+                    max,
+                    if *is_signed { ", signed" } else { "" }
+                );
+            }
+            FieldType::Expr(script) | FieldType::Derived(script) => {
+                let _ = writeln!(out, "```rhai\n{}\n```\n", script.trim());
+            }
+            FieldType::Enum(variants) => {
+                for variant in variants {
+                    let _ = write!(out, "- `{}`", variant.value);
+                    if let Some(name) = &variant.name {
+                        let _ = write!(out, " **{}**", name);
+                    }
+                    if let Some(description) = &variant.description {
+                        // keep multi-line descriptions inside the list item
+                        let _ = write!(out, ": {}", description.trim().replace('\n', "\n  "));
+                    }
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
+            FieldType::Input => {}
+        }
+    }
+
+    Ok(out)
+}
+
+/// Text for a cell of a Markdown table, which ends at a `|` or a line break
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{EnumVariant, FieldRule};
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_protocol_documentation() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol(
+                "proto",
+                Some("Telemetry".to_string()),
+                Endianness::Big,
+                None,
+            )
+            .unwrap();
+        registry
+            .edit_protocol("proto", |p| {
+                p.description = Some("Sent every **100 ms**.".to_string());
+                let mut kind = FieldRule::new(
+                    "kind",
+                    FieldType::Enum(vec![EnumVariant {
+                        value: 1,
+                        name: Some("Status".to_string()),
+                        description: Some("- first\n- second".to_string()),
+                    }]),
+                    FieldLength::Fixed(8),
+                );
+                kind.description = Some("Message `kind`.".to_string());
+                p.add_field(kind)?;
+                let mut flags = FieldRule::new("flags", FieldType::Input, FieldLength::Fixed(8));
+                flags.name = Some("Ready | Busy\nflags".to_string());
+                p.add_field(flags)
+            })
+            .unwrap();
+
+        let doc = protocol_documentation(&registry, "proto").unwrap();
+        assert!(doc.starts_with("# Telemetry\n"));
+        assert!(doc.contains("Sent every **100 ms**."));
+        assert!(doc.contains("| `kind` |  | Enum | 8 bits | `proto` |"));
+        assert!(doc.contains("| `flags` | Ready \\| Busy flags | Input | 8 bits | `proto` |"));
+        assert!(doc.contains("Message `kind`."));
+        assert!(doc.contains("- `1` **Status**: - first\n  - second\n"));
+    }
+
+    #[test]
+    fn test_missing_protocol() {
+        assert!(protocol_documentation(&ProtocolRegistry::new(), "missing").is_err());
+    }
+}
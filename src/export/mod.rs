@@ -0,0 +1,12 @@
+pub mod binary_template;
+pub mod bytefield;
+pub mod dbc;
+pub mod definitions;
+pub mod diagram;
+pub mod fuzz;
+pub mod golden;
+pub mod markdown;
+pub mod negative;
+pub mod parquet;
+pub mod report;
+pub mod scapy;
@@ -0,0 +1,201 @@
+//! Negative tests: systematically malformed packets of a protocol, as a corpus for testing that
+//! a parser rejects them without crashing.
+
+use crate::codec::Value;
+use crate::codec::compiled::CompiledCodec;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// A malformed packet, with how it breaks the protocol
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct NegativeCase {
+    pub name: String,
+    pub description: String,
+    /// the packet as hex digits
+    pub hex: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct NegativeCorpus {
+    pub protocol: String,
+    /// the valid packet the malformed ones are made from
+    pub valid: String,
+    pub cases: Vec<NegativeCase>,
+}
+
+/// Values for the fields of a protocol that make a valid packet: the first variant of enums,
+/// the minimum of ranges, and zero or no bytes for inputs
+pub fn default_values(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+) -> Result<HashMap<String, Value>, String> {
+    let mut values = HashMap::new();
+    for field in registry.resolve_fields(protocol_id)? {
+        let value = match (&field.field_type, &field.length) {
+            (FieldType::Fixed(_) | FieldType::Expr(_) | FieldType::Derived(_), _) => continue,
+            (_, FieldLength::Variable) => Value::Bytes(Vec::new()),
+            (FieldType::Enum(variants), _) => Value::Int(variants.first().map_or(0, |v| v.value)),
+            (FieldType::Range { min, .. }, _) => Value::Int(*min),
+            (FieldType::Input, _) => Value::Int(0),
+        };
+        values.insert(field.id.clone(), value);
+    }
+    Ok(values)
+}
+
+/// Malformed variants of the packet encoded from `values`: the packet cut short before each of
+/// its fields, each enum field set to a value no variant has, and each range field set just
+/// outside its range. Values that do not fit in their field on the wire are left out.
+pub fn negative_corpus(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    values: &HashMap<String, Value>,
+) -> Result<NegativeCorpus, String> {
+    let codec = CompiledCodec::compile(registry, engine, protocol_id)?;
+    let valid = codec.encode(engine, values)?;
+    let packet = codec.decode(engine, &valid)?;
+    let mut cases = Vec::new();
+
+    let mut lengths = BTreeSet::new();
+    for field in packet.fields.iter().filter(|f| !f.is_virtual) {
+        let len = field.bit_offset / 8;
+        if len < valid.len() && lengths.insert(len) {
+            cases.push(NegativeCase {
+                name: format!("truncated_before_{}", field.rule_id),
+                description: format!(
+                    "Cut short to {} bytes, before field '{}'",
+                    len, field.rule_id
+                ),
+                hex: hex(&valid[..len]),
+            });
+        }
+    }
+
+    for field in registry.resolve_fields(protocol_id)? {
+        for (name, description, value) in invalid_values(&field) {
+            let mut codec = CompiledCodec::compile(registry, engine, protocol_id)?;
+            codec.ignore_rules(&HashSet::from([field.id.clone()]));
+            let mut values = values.clone();
+            values.insert(field.id.clone(), Value::Int(value));
+            if let Ok(data) = codec.encode(engine, &values) {
+                cases.push(NegativeCase {
+                    name: format!("{}_{}", field.id, name),
+                    description: format!("Field '{}' {}", field.id, description),
+                    hex: hex(&data),
+                });
+            }
+        }
+    }
+
+    Ok(NegativeCorpus {
+        protocol: protocol_id.to_string(),
+        valid: hex(&valid),
+        cases,
+    })
+}
+
+/// Values breaking the rules of a field, with a name and description of each
+fn invalid_values(field: &FieldRule) -> Vec<(&'static str, String, i128)> {
+    match &field.field_type {
+        FieldType::Enum(variants) => {
+            let defined: HashSet<i128> = variants.iter().map(|v| v.value).collect();
+            (0..=defined.len() as i128)
+                .find(|v| !defined.contains(v))
+                .map(|v| {
+                    vec![(
+                        "undefined",
+                        format!("set to {}, which no variant has", v),
+                        v,
+                    )]
+                })
+                .unwrap_or_default()
+        }
+        FieldType::Range { min, max, .. } => vec![
+            (
+                "below_min",
+                format!("set to {}, below its minimum {}", min - 1, min),
+                min - 1,
+            ),
+            (
+                "above_max",
+                format!("set to {}, above its maximum {}", max + 1, max),
+                max + 1,
+            ),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::EnumVariant;
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_negative_corpus() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                let variant = |value| EnumVariant {
+                    value,
+                    name: None,
+                    description: None,
+                };
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(vec![variant(0), variant(2)]),
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "level",
+                    FieldType::Range {
+                        min: 1,
+                        max: 255,
+                        is_signed: false,
+                    },
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let mut values = default_values(&registry, "msg").unwrap();
+        assert_eq!(values["level"], Value::Int(1));
+        values.insert("data".to_string(), Value::Bytes(vec![0xaa]));
+
+        let corpus = negative_corpus(&registry, &engine, "msg", &values).unwrap();
+        assert_eq!(corpus.valid, "0001aa");
+        let cases: Vec<(&str, &str)> = corpus
+            .cases
+            .iter()
+            .map(|c| (c.name.as_str(), c.hex.as_str()))
+            .collect();
+        // 256 does not fit in the level field, so there is no case above its maximum
+        assert_eq!(
+            cases,
+            vec![
+                ("truncated_before_kind", ""),
+                ("truncated_before_level", "00"),
+                ("truncated_before_data", "0001"),
+                ("kind_undefined", "0101aa"),
+                ("level_below_min", "0000aa"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Decoded packets as an Apache Parquet file: one row per packet and one typed column per
+//! field, so large captures load into data tools without the size and parsing cost of CSV.
+
+use crate::codec::Value;
+use crate::codec::decode::DecodedPacket;
+use crate::export::report::ReportPacket;
+use ::parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+use ::parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use ::parquet::schema::types::Type;
+use std::sync::Arc;
+
+/// Rows written to the file at once
+const ROW_GROUP_SIZE: usize = 65_536;
+
+/// Columns every row has, before the columns of the fields
+const PACKET_COLUMNS: [&str; 5] = ["_number", "_time", "_length", "_protocol", "_error"];
+
+/// The values of a column, `None` where a packet has no value
+enum Column {
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+    Text(Vec<Option<String>>),
+    Bytes(Vec<Option<Vec<u8>>>),
+}
+
+impl Column {
+    /// A column of the values of a field, of the type all of them have, or of text if they
+    /// differ or an integer does not fit in 64 bits
+    fn of(values: Vec<Option<&Value>>) -> Column {
+        let present = || values.iter().flatten();
+        if present().all(|v| matches!(v, Value::Int(i) if i64::try_from(*i).is_ok())) {
+            Column::Int(values.iter().map(|v| v.map(int)).collect())
+        } else if present().all(|v| matches!(v, Value::Float(_) | Value::Int(_))) {
+            Column::Float(values.iter().map(|v| v.map(float)).collect())
+        } else if present().all(|v| matches!(v, Value::Bool(_))) {
+            Column::Bool(
+                values
+                    .iter()
+                    .map(|v| v.map(|v| *v == Value::Bool(true)))
+                    .collect(),
+            )
+        } else if present().all(|v| matches!(v, Value::Bytes(_))) {
+            Column::Bytes(values.iter().map(|v| v.map(bytes)).collect())
+        } else {
+            Column::Text(values.iter().map(|v| v.map(text)).collect())
+        }
+    }
+
+    fn schema(&self, name: &str) -> Result<Type, String> {
+        let (physical, logical) = match self {
+            Column::Int(_) => (PhysicalType::INT64, None),
+            Column::Float(_) => (PhysicalType::DOUBLE, None),
+            Column::Bool(_) => (PhysicalType::BOOLEAN, None),
+            Column::Text(_) => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+            Column::Bytes(_) => (PhysicalType::BYTE_ARRAY, None),
+        };
+        Type::primitive_type_builder(name, physical)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_logical_type(logical)
+            .build()
+            .map_err(|e| format!("Invalid Parquet column '{}': {}", name, e))
+    }
+
+    /// Write the rows of a row group to the column writer
+    fn write(
+        &self,
+        writer: &mut SerializedColumnWriter,
+        rows: std::ops::Range<usize>,
+    ) -> Result<(), String> {
+        fn split<T: Clone, U>(values: &[Option<T>], f: impl Fn(T) -> U) -> (Vec<U>, Vec<i16>) {
+            let levels = values.iter().map(|v| v.is_some() as i16).collect();
+            let present = values.iter().flatten().cloned().map(f).collect();
+            (present, levels)
+        }
+        let result = match self {
+            Column::Int(values) => {
+                let (values, levels) = split(&values[rows], |v| v);
+                writer
+                    .typed::<Int64Type>()
+                    .write_batch(&values, Some(&levels), None)
+            }
+            Column::Float(values) => {
+                let (values, levels) = split(&values[rows], |v| v);
+                writer
+                    .typed::<DoubleType>()
+                    .write_batch(&values, Some(&levels), None)
+            }
+            Column::Bool(values) => {
+                let (values, levels) = split(&values[rows], |v| v);
+                writer
+                    .typed::<BoolType>()
+                    .write_batch(&values, Some(&levels), None)
+            }
+            Column::Text(values) => {
+                let (values, levels) = split(&values[rows], |v| ByteArray::from(v.into_bytes()));
+                writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&values, Some(&levels), None)
+            }
+            Column::Bytes(values) => {
+                let (values, levels) = split(&values[rows], ByteArray::from);
+                writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&values, Some(&levels), None)
+            }
+        };
+        result
+            .map(|_| ())
+            .map_err(|e| format!("Failed to write Parquet column: {}", e))
+    }
+}
+
+fn int(value: &Value) -> i64 {
+    match value {
+        Value::Int(v) => *v as i64,
+        _ => 0,
+    }
+}
+
+fn float(value: &Value) -> f64 {
+    match value {
+        Value::Int(v) => *v as f64,
+        Value::Float(v) => *v,
+        _ => 0.0,
+    }
+}
+
+fn bytes(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Bytes(v) => v.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::Str(v) => v.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn decoded<'a>(packet: &ReportPacket<'a>) -> Option<&'a DecodedPacket> {
+    packet.decoded.and_then(|d| d.as_ref().ok())
+}
+
+/// The packets as a Parquet file. Each row has the number of the packet from 1, its time in
+/// seconds, its length in bytes, the protocol it decoded as and why it did not decode, in
+/// columns named with a leading underscore, and a column for every field any packet has,
+/// empty where a packet does not have the field.
+pub fn decoded_parquet(packets: &[ReportPacket]) -> Result<Vec<u8>, String> {
+    let mut field_ids: Vec<&str> = Vec::new();
+    for packet in packets.iter().filter_map(decoded) {
+        for field in &packet.fields {
+            if !field_ids.contains(&field.rule_id.as_str()) {
+                field_ids.push(&field.rule_id);
+            }
+        }
+    }
+
+    let mut columns: Vec<(String, Column)> = vec![
+        (
+            PACKET_COLUMNS[0].to_string(),
+            Column::Int((1..=packets.len() as i64).map(Some).collect()),
+        ),
+        (
+            PACKET_COLUMNS[1].to_string(),
+            Column::Float(packets.iter().map(|p| Some(p.time)).collect()),
+        ),
+        (
+            PACKET_COLUMNS[2].to_string(),
+            Column::Int(packets.iter().map(|p| Some(p.data.len() as i64)).collect()),
+        ),
+        (
+            PACKET_COLUMNS[3].to_string(),
+            Column::Text(
+                packets
+                    .iter()
+                    .map(|p| decoded(p).map(|d| d.protocol_id.clone()))
+                    .collect(),
+            ),
+        ),
+        (
+            PACKET_COLUMNS[4].to_string(),
+            Column::Text(
+                packets
+                    .iter()
+                    .map(|p| p.decoded.and_then(|d| d.as_ref().err()).cloned())
+                    .collect(),
+            ),
+        ),
+    ];
+    for id in field_ids {
+        let values = packets
+            .iter()
+            .map(|p| decoded(p).and_then(|d| d.get(id)).map(|f| &f.value))
+            .collect();
+        let name = if PACKET_COLUMNS.contains(&id) {
+            format!("{}_field", id)
+        } else {
+            id.to_string()
+        };
+        columns.push((name, Column::of(values)));
+    }
+
+    let fields = columns
+        .iter()
+        .map(|(name, column)| column.schema(name).map(Arc::new))
+        .collect::<Result<Vec<_>, _>>()?;
+    let schema = Type::group_type_builder("packet")
+        .with_fields(fields)
+        .build()
+        .map_err(|e| format!("Invalid Parquet schema: {}", e))?;
+    let properties = WriterProperties::builder().build();
+    let error = |e: ::parquet::errors::ParquetError| format!("Failed to write Parquet file: {}", e);
+    let mut writer = SerializedFileWriter::new(Vec::new(), Arc::new(schema), Arc::new(properties))
+        .map_err(error)?;
+    for start in (0..packets.len()).step_by(ROW_GROUP_SIZE) {
+        let rows = start..(start + ROW_GROUP_SIZE).min(packets.len());
+        let mut group = writer.next_row_group().map_err(error)?;
+        for (_, column) in &columns {
+            let Some(mut column_writer) = group.next_column().map_err(error)? else {
+                break;
+            };
+            column.write(&mut column_writer, rows.clone())?;
+            column_writer.close().map_err(error)?;
+        }
+        group.close().map_err(error)?;
+    }
+    writer.into_inner().map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode::DecodedField;
+    use ::parquet::file::metadata::ParquetMetaDataReader;
+
+    fn packet(fields: &[(&str, Value)]) -> Result<DecodedPacket, String> {
+        Ok(DecodedPacket {
+            protocol_id: "msg".to_string(),
+            fields: fields
+                .iter()
+                .map(|(id, value)| DecodedField {
+                    rule_id: id.to_string(),
+                    protocol_id: "msg".to_string(),
+                    bit_offset: 0,
+                    bit_len: 8,
+                    value: value.clone(),
+                    is_virtual: false,
+                })
+                .collect(),
+            issues: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_decoded_parquet() {
+        let decoded = [
+            packet(&[
+                ("kind", Value::Int(1)),
+                ("length", Value::Int(2)),
+                ("data", Value::Bytes(vec![0xaa])),
+            ]),
+            packet(&[("kind", Value::Int(2)), ("scale", Value::Float(0.5))]),
+            Err("too short".to_string()),
+            packet(&[("kind", Value::Int(3)), ("scale", Value::Int(1))]),
+        ];
+        let packets: Vec<ReportPacket> = decoded
+            .iter()
+            .map(|d| ReportPacket {
+                time: 0.0,
+                data: &[0, 1],
+                decoded: Some(d),
+            })
+            .collect();
+        let file = decoded_parquet(&packets).unwrap();
+
+        assert!(file.starts_with(b"PAR1") && file.ends_with(b"PAR1"));
+        let footer = file.len() - 8;
+        let len = u32::from_le_bytes(file[footer..footer + 4].try_into().unwrap()) as usize;
+        let metadata = ParquetMetaDataReader::decode_metadata(&file[footer - len..footer]).unwrap();
+        assert_eq!(metadata.file_metadata().num_rows(), 4);
+        let columns: Vec<(String, PhysicalType)> = metadata
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|c| (c.name().to_string(), c.physical_type()))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("_number".to_string(), PhysicalType::INT64),
+                ("_time".to_string(), PhysicalType::DOUBLE),
+                ("_length".to_string(), PhysicalType::INT64),
+                ("_protocol".to_string(), PhysicalType::BYTE_ARRAY),
+                ("_error".to_string(), PhysicalType::BYTE_ARRAY),
+                ("kind".to_string(), PhysicalType::INT64),
+                ("length".to_string(), PhysicalType::INT64),
+                ("data".to_string(), PhysicalType::BYTE_ARRAY),
+                ("scale".to_string(), PhysicalType::DOUBLE),
+            ]
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! A capture summarized as a self-contained HTML page: how the packets decoded, how often the
+//! packet validators failed, statistics of the field values and a sample of the anomalous
+//! packets, to share with people who do not use BitLoom.
+
+use crate::codec::Value;
+use crate::codec::decode::DecodedPacket;
+use crate::models::protocol::{ProtocolRegistry, Severity};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Most anomalous packets listed in the report
+const MAX_ANOMALIES: usize = 25;
+/// Bytes of an anomalous packet shown in the report
+const MAX_SAMPLE_BYTES: usize = 64;
+/// Distinct values of a field counted before only known values are counted further
+const MAX_DISTINCT: usize = 1000;
+
+/// A packet of a capture, with how it decoded
+pub struct ReportPacket<'a> {
+    /// seconds since the first packet of the capture
+    pub time: f64,
+    pub data: &'a [u8],
+    /// the packet decoded, or why that failed; `None` if it was not decoded
+    pub decoded: Option<&'a Result<DecodedPacket, String>>,
+}
+
+/// How often a packet validator failed
+#[derive(Clone, PartialEq, Debug)]
+pub struct ValidatorStats {
+    pub protocol_id: String,
+    pub validator: String,
+    pub severity: Severity,
+    /// whether the validator computes a checksum
+    pub checksum: bool,
+    /// packets of the protocol or its subprotocols the validator ran on
+    pub checked: usize,
+    pub failed: usize,
+}
+
+/// The values a field took in the decoded packets
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldStats {
+    pub field_id: String,
+    /// packets the field was decoded in
+    pub count: usize,
+    /// the smallest, largest and mean value, for numeric fields
+    pub range: Option<(f64, f64, f64)>,
+    /// number of distinct values, and whether there were more than were counted
+    pub distinct: (usize, bool),
+    /// the most common values with their counts
+    pub common: Vec<(String, usize)>,
+}
+
+/// A packet that did not decode or failed a validator
+#[derive(Clone, PartialEq, Debug)]
+pub struct Anomaly {
+    /// number of the packet in the capture, from 1
+    pub number: usize,
+    pub time: f64,
+    pub length: usize,
+    pub reason: String,
+    /// the first bytes of the packet as hex digits
+    pub hex: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CaptureSummary {
+    pub packets: usize,
+    pub bytes: usize,
+    /// seconds from the first packet to the last
+    pub duration: f64,
+    pub decoded: usize,
+    pub failed: usize,
+    /// packets decoded as each protocol, most common first
+    pub protocols: Vec<(String, usize)>,
+    pub validators: Vec<ValidatorStats>,
+    pub fields: Vec<FieldStats>,
+    /// the first anomalous packets
+    pub anomalies: Vec<Anomaly>,
+    pub anomaly_count: usize,
+}
+
+/// Counts of the values of a field while the packets are summarized
+#[derive(Default)]
+struct FieldTally {
+    count: usize,
+    numbers: Option<(f64, f64, f64, usize)>,
+    values: HashMap<String, usize>,
+    capped: bool,
+}
+
+impl FieldTally {
+    fn add(&mut self, value: &Value) {
+        self.count += 1;
+        let number = match value {
+            Value::Int(v) => Some(*v as f64),
+            Value::Float(v) => Some(*v),
+            _ => None,
+        };
+        if let Some(n) = number {
+            let (min, max, sum, count) = self.numbers.get_or_insert((n, n, 0.0, 0));
+            *min = min.min(n);
+            *max = max.max(n);
+            *sum += n;
+            *count += 1;
+        }
+        let text = value.to_string();
+        if let Some(count) = self.values.get_mut(&text) {
+            *count += 1;
+        } else if self.values.len() < MAX_DISTINCT {
+            self.values.insert(text, 1);
+        } else {
+            self.capped = true;
+        }
+    }
+
+    fn stats(self, field_id: String) -> FieldStats {
+        let mut common: Vec<(String, usize)> =
+            self.values.iter().map(|(v, c)| (v.clone(), *c)).collect();
+        common.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        common.truncate(3);
+        FieldStats {
+            field_id,
+            count: self.count,
+            range: self
+                .numbers
+                .map(|(min, max, sum, count)| (min, max, sum / count as f64)),
+            distinct: (self.values.len(), self.capped),
+            common,
+        }
+    }
+}
+
+/// Count how the packets of a capture decoded and what their fields held
+pub fn summarize_capture(registry: &ProtocolRegistry, packets: &[ReportPacket]) -> CaptureSummary {
+    let mut summary = CaptureSummary {
+        packets: packets.len(),
+        bytes: packets.iter().map(|p| p.data.len()).sum(),
+        duration: packets.iter().map(|p| p.time).fold(0.0, f64::max),
+        ..Default::default()
+    };
+    let mut protocols: HashMap<String, usize> = HashMap::new();
+    // the validators of the chain of each protocol, by protocol ID
+    let mut chains: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut fields: Vec<(String, FieldTally)> = Vec::new();
+
+    for (i, packet) in packets.iter().enumerate() {
+        let reason = match packet.decoded {
+            None => continue,
+            Some(Err(e)) => {
+                summary.failed += 1;
+                Some(format!("Does not decode: {}", e))
+            }
+            Some(Ok(decoded)) => {
+                summary.decoded += 1;
+                *protocols.entry(decoded.protocol_id.clone()).or_default() += 1;
+                let validators = chains
+                    .entry(decoded.protocol_id.clone())
+                    .or_insert_with(|| {
+                        chain_validators(registry, &decoded.protocol_id, &mut summary.validators)
+                    });
+                for &v in validators.iter() {
+                    let stats = &mut summary.validators[v];
+                    stats.checked += 1;
+                    if decoded.issues.iter().any(|i| {
+                        i.protocol_id == stats.protocol_id && i.validator == stats.validator
+                    }) {
+                        stats.failed += 1;
+                    }
+                }
+                for field in &decoded.fields {
+                    let tally = match fields.iter_mut().find(|(id, _)| *id == field.rule_id) {
+                        Some((_, tally)) => tally,
+                        None => {
+                            fields.push((field.rule_id.clone(), FieldTally::default()));
+                            &mut fields.last_mut().unwrap().1
+                        }
+                    };
+                    tally.add(&field.value);
+                }
+                let issues: Vec<String> = decoded
+                    .issues
+                    .iter()
+                    .map(|i| format!("{}: {}", i.validator, i.message))
+                    .collect();
+                (!issues.is_empty()).then(|| issues.join("; "))
+            }
+        };
+        if let Some(reason) = reason {
+            summary.anomaly_count += 1;
+            if summary.anomalies.len() < MAX_ANOMALIES {
+                summary.anomalies.push(Anomaly {
+                    number: i + 1,
+                    time: packet.time,
+                    length: packet.data.len(),
+                    reason,
+                    hex: hex(packet.data),
+                });
+            }
+        }
+    }
+
+    summary.protocols = protocols.into_iter().collect();
+    summary
+        .protocols
+        .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    summary.fields = fields
+        .into_iter()
+        .map(|(id, tally)| tally.stats(id))
+        .collect();
+    summary
+}
+
+/// Indices in `validators` of the validators of a protocol and its ancestors, adding the ones
+/// not seen yet
+fn chain_validators(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    validators: &mut Vec<ValidatorStats>,
+) -> Vec<usize> {
+    let mut indices = Vec::new();
+    for proto in registry.get_inheritance_chain(protocol_id) {
+        for validator in &proto.validators {
+            let index = validators
+                .iter()
+                .position(|v| v.protocol_id == proto.id && v.validator == validator.name)
+                .unwrap_or_else(|| {
+                    validators.push(ValidatorStats {
+                        protocol_id: proto.id.clone(),
+                        validator: validator.name.clone(),
+                        severity: validator.severity,
+                        checksum: validator.script.contains("checksum("),
+                        checked: 0,
+                        failed: 0,
+                    });
+                    validators.len() - 1
+                });
+            indices.push(index);
+        }
+    }
+    indices
+}
+
+fn hex(data: &[u8]) -> String {
+    let mut text: Vec<String> = data
+        .iter()
+        .take(MAX_SAMPLE_BYTES)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if data.len() > MAX_SAMPLE_BYTES {
+        text.push("…".to_string());
+    }
+    text.join(" ")
+}
+
+/// Text with the characters HTML gives a meaning escaped
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn percent(part: usize, whole: usize) -> String {
+    if whole == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}%", part as f64 / whole as f64 * 100.0)
+    }
+}
+
+/// The summary of a capture as an HTML page with its styles inline
+pub fn html_report(title: &str, summary: &CaptureSummary) -> String {
+    let mut out = String::new();
+    let title = escape(title);
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; color: #222; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; }}\n\
+         th {{ background: #f0f0f0; }}\n\
+         td.num {{ text-align: right; }}\n\
+         .warn {{ color: #b35900; }}\n\
+         code {{ font-family: monospace; }}\n\
+         </style>\n</head>\n<body>\n<h1>{}</h1>",
+        title, title
+    );
+
+    out.push_str("<h2>Overview</h2>\n<table>\n");
+    for (label, value) in [
+        ("Packets", summary.packets.to_string()),
+        ("Bytes", summary.bytes.to_string()),
+        ("Duration", format!("{:.3} s", summary.duration)),
+        (
+            "Decoded",
+            format!(
+                "{} ({})",
+                summary.decoded,
+                percent(summary.decoded, summary.packets)
+            ),
+        ),
+        (
+            "Failed to decode",
+            format!(
+                "{} ({})",
+                summary.failed,
+                percent(summary.failed, summary.packets)
+            ),
+        ),
+        (
+            "Anomalous",
+            format!(
+                "{} ({})",
+                summary.anomaly_count,
+                percent(summary.anomaly_count, summary.packets)
+            ),
+        ),
+    ] {
+        let _ = writeln!(
+            out,
+            "<tr><th>{}</th><td class=\"num\">{}</td></tr>",
+            label, value
+        );
+    }
+    out.push_str("</table>\n");
+
+    if !summary.protocols.is_empty() {
+        out.push_str("<h2>Protocols</h2>\n<table>\n<tr><th>Protocol</th><th>Packets</th><th>Share</th></tr>\n");
+        for (protocol, count) in &summary.protocols {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(protocol),
+                count,
+                percent(*count, summary.decoded)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !summary.validators.is_empty() {
+        out.push_str(
+            "<h2>Validators</h2>\n<table>\n<tr><th>Validator</th><th>Protocol</th><th>Kind</th>\
+             <th>Checked</th><th>Failed</th><th>Failure rate</th></tr>\n",
+        );
+        for v in &summary.validators {
+            let kind = match (v.checksum, v.severity) {
+                (true, _) => "checksum",
+                (false, Severity::Error) => "error",
+                (false, Severity::Warning) => "warning",
+            };
+            let class = if v.failed > 0 { " class=\"warn\"" } else { "" };
+            let _ = writeln!(
+                out,
+                "<tr{}><td>{}</td><td><code>{}</code></td><td>{}</td><td class=\"num\">{}</td>\
+                 <td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                class,
+                escape(&v.validator),
+                escape(&v.protocol_id),
+                kind,
+                v.checked,
+                v.failed,
+                percent(v.failed, v.checked)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !summary.fields.is_empty() {
+        out.push_str(
+            "<h2>Fields</h2>\n<table>\n<tr><th>Field</th><th>Packets</th><th>Min</th><th>Max</th>\
+             <th>Mean</th><th>Distinct</th><th>Most common</th></tr>\n",
+        );
+        for f in &summary.fields {
+            let (min, max, mean) = match f.range {
+                Some((min, max, mean)) => {
+                    (min.to_string(), max.to_string(), format!("{:.2}", mean))
+                }
+                None => ("-".to_string(), "-".to_string(), "-".to_string()),
+            };
+            let distinct = match f.distinct {
+                (n, true) => format!("over {}", n),
+                (n, false) => n.to_string(),
+            };
+            let common: Vec<String> = f
+                .common
+                .iter()
+                .map(|(value, count)| format!("<code>{}</code> ×{}", escape(value), count))
+                .collect();
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+                 <td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+                 <td>{}</td></tr>",
+                escape(&f.field_id),
+                f.count,
+                min,
+                max,
+                mean,
+                distinct,
+                common.join(", ")
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !summary.anomalies.is_empty() {
+        out.push_str("<h2>Anomalous Packets</h2>\n");
+        if summary.anomaly_count > summary.anomalies.len() {
+            let _ = writeln!(
+                out,
+                "<p>The first {} of {}.</p>",
+                summary.anomalies.len(),
+                summary.anomaly_count
+            );
+        }
+        out.push_str(
+            "<table>\n<tr><th>No.</th><th>Time</th><th>Length</th><th>Reason</th><th>Bytes</th></tr>\n",
+        );
+        for a in &summary.anomalies {
+            let _ = writeln!(
+                out,
+                "<tr><td class=\"num\">{}</td><td class=\"num\">{:.6}</td><td class=\"num\">{}</td>\
+                 <td>{}</td><td><code>{}</code></td></tr>",
+                a.number,
+                a.time,
+                a.length,
+                escape(&a.reason),
+                a.hex
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::decode::decode;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::{Endianness, PacketValidator};
+    use crate::script::ScriptEngine;
+
+    #[test]
+    fn test_summarize_capture() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.validators.push(PacketValidator {
+                    name: "sum".to_string(),
+                    severity: Severity::Error,
+                    script: r#"if checksum("XOR8", payload) != check { "bad checksum" }"#
+                        .to_string(),
+                });
+                p.add_field(FieldRule::new(
+                    "check",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let data: Vec<Vec<u8>> = vec![vec![1, 1], vec![2, 2], vec![2, 0], vec![]];
+        let decoded: Vec<Result<DecodedPacket, String>> = data
+            .iter()
+            .map(|d| decode(&registry, &engine, "msg", d))
+            .collect();
+        let packets: Vec<ReportPacket> = data
+            .iter()
+            .zip(&decoded)
+            .enumerate()
+            .map(|(i, (data, decoded))| ReportPacket {
+                time: i as f64 * 0.5,
+                data,
+                decoded: Some(decoded),
+            })
+            .collect();
+
+        let summary = summarize_capture(&registry, &packets);
+        assert_eq!(summary.packets, 4);
+        assert_eq!(summary.bytes, 6);
+        assert_eq!(summary.duration, 1.5);
+        assert_eq!((summary.decoded, summary.failed), (3, 1));
+        assert_eq!(summary.protocols, vec![("msg".to_string(), 3)]);
+        let sum = &summary.validators[0];
+        assert!(sum.checksum);
+        assert_eq!((sum.checked, sum.failed), (3, 1));
+
+        let check = &summary.fields[0];
+        assert_eq!(check.field_id, "check");
+        assert_eq!(check.count, 3);
+        assert_eq!(check.range, Some((1.0, 2.0, 5.0 / 3.0)));
+        assert_eq!(check.distinct, (2, false));
+        assert_eq!(check.common[0], ("2".to_string(), 2));
+        assert_eq!(summary.fields[1].range, None);
+
+        let anomalies: Vec<usize> = summary.anomalies.iter().map(|a| a.number).collect();
+        assert_eq!(anomalies, vec![3, 4]);
+        assert_eq!(summary.anomaly_count, 2);
+
+        let html = html_report("Capture <1>", &summary);
+        assert!(html.contains("<title>Capture &lt;1&gt;</title>"));
+        assert!(html.contains("33.3%"));
+    }
+}
@@ -0,0 +1,371 @@
+use crate::codegen::camel_case;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, Protocol, ProtocolRegistry};
+use std::fmt::Write;
+
+/// Generate a Python module with a Scapy `Packet` subclass for a protocol, its ancestors and
+/// its subprotocols. Each class holds only the fields its protocol defines; the inheritance
+/// is expressed with `bind_layers` from the parent constraints, so Scapy dissects a parent
+/// into the matching subprotocol.
+///
+/// Expression fields become plain fields with their script as a comment, and derived fields
+/// are left out, as they are not on the wire. Scapy has no little-endian bit fields, so
+/// little-endian fields that do not start on a byte boundary come out big-endian. Nor has it
+/// sign-magnitude fields, so protocols with them are an error.
+pub fn scapy_module(registry: &ProtocolRegistry, protocol_id: &str) -> Result<String, String> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    if chain.is_empty() {
+        return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+    }
+    let mut descendants = registry.get_descendant_ids(protocol_id);
+    descendants.sort();
+    let protocols: Vec<&Protocol> = chain
+        .into_iter()
+        .chain(
+            descendants
+                .iter()
+                .filter_map(|id| registry.get_protocol(id)),
+        )
+        .collect();
+    if let Some(field) = protocols
+        .iter()
+        .flat_map(|p| &p.fields)
+        .find(|f| f.is_sign_magnitude())
+    {
+        return Err(format!(
+            "Field '{}' is sign-magnitude, which Scapy fields cannot express",
+            field.id
+        ));
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Generated by BitLoom from protocol '{}'",
+        protocol_id
+    );
+    out.push_str("from scapy.all import *\n");
+
+    for proto in &protocols {
+        out.push_str("\n\n");
+        packet_class(&mut out, proto);
+    }
+
+    let mut bindings = String::new();
+    for proto in &protocols {
+        let Some(parent) = proto
+            .parent_id
+            .as_deref()
+            .and_then(|id| registry.get_protocol(id))
+        else {
+            continue;
+        };
+        let mut constraints: Vec<_> = proto.parent_constraints.iter().collect();
+        constraints.sort();
+        // bind_layers can only match fields of the layer directly below
+        let (direct, inherited): (Vec<_>, Vec<_>) = constraints
+            .into_iter()
+            .partition(|(field_id, _)| parent.fields.iter().any(|f| &&f.id == field_id));
+        for (field_id, value) in inherited {
+            let _ = writeln!(
+                bindings,
+                "# {}: constraint {} == {} is on an ancestor of {} and cannot be bound",
+                camel_case(&proto.id),
+                field_id,
+                value,
+                camel_case(&parent.id)
+            );
+        }
+        let args: Vec<String> = direct
+            .into_iter()
+            .map(|(field_id, value)| format!(", {}={}", field_id, value))
+            .collect();
+        let _ = writeln!(
+            bindings,
+            "bind_layers({}, {}{})",
+            camel_case(&parent.id),
+            camel_case(&proto.id),
+            args.concat()
+        );
+    }
+    if !bindings.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(&bindings);
+    }
+
+    Ok(out)
+}
+
+fn packet_class(out: &mut String, proto: &Protocol) {
+    let _ = writeln!(out, "class {}(Packet):", camel_case(&proto.id));
+    if let Some(description) = &proto.description {
+        for line in description.trim().lines() {
+            let _ = writeln!(out, "    # {}", line);
+        }
+    }
+    let _ = writeln!(
+        out,
+        "    name = {}",
+        python_string(proto.name.as_deref().unwrap_or(&proto.id))
+    );
+    out.push_str("    fields_desc = [\n");
+    // offset in bits from the start of the layer, to know when fields are byte-aligned
+    let mut offset = 0u32;
+    for field in &proto.fields {
+        if let Some(script) = field.field_type.script() {
+            let kind = if field.is_virtual() {
+                "derived, not on the wire"
+            } else {
+                "computed by"
+            };
+            let _ = writeln!(
+                out,
+                "        # {}: {} {}",
+                field.id,
+                kind,
+                script.trim().replace('\n', " ")
+            );
+        }
+        if field.is_virtual() {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "        {},",
+            field_definition(field, field.byte_order(proto.endianness), offset)
+        );
+        if let FieldLength::Fixed(bits) = field.length {
+            offset += bits;
+        }
+    }
+    out.push_str("    ]\n");
+}
+
+fn field_definition(field: &FieldRule, endianness: Endianness, offset: u32) -> String {
+    let name = python_string(&field.id);
+    let bits = match field.length {
+        FieldLength::Fixed(bits) => bits,
+        FieldLength::Variable => return format!("StrField({}, b\"\")", name),
+    };
+    let default = default_value(&field.field_type);
+    let signed = matches!(
+        field.field_type,
+        FieldType::Range {
+            is_signed: true,
+            ..
+        }
+    );
+    let little = endianness == Endianness::Little;
+
+    let sized = if offset.is_multiple_of(8) {
+        sized_field(bits, signed, little)
+    } else {
+        None
+    };
+    match (&field.field_type, sized) {
+        (FieldType::Enum(variants), sized) => {
+            let entries: Vec<String> = variants
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{}: {}",
+                        v.value,
+                        python_string(v.name.as_deref().unwrap_or(&v.value.to_string()))
+                    )
+                })
+                .collect();
+            let enum_dict = format!("{{{}}}", entries.join(", "));
+            match sized.and_then(enum_field) {
+                Some(class) => format!("{}({}, {}, {})", class, name, default, enum_dict),
+                None => format!(
+                    "BitEnumField({}, {}, {}, {})",
+                    name, default, bits, enum_dict
+                ),
+            }
+        }
+        (_, Some(class)) => format!("{}({}, {})", class, name, default),
+        (_, None) if offset.is_multiple_of(8) && bits.is_multiple_of(8) && bits > 64 => {
+            format!("StrFixedLenField({}, b\"\", length={})", name, bits / 8)
+        }
+        (_, None) => format!("BitField({}, {}, {})", name, default, bits),
+    }
+}
+
+/// The Scapy field class for a byte-aligned integer of a standard size
+fn sized_field(bits: u32, signed: bool, little: bool) -> Option<&'static str> {
+    Some(match (bits, signed, little) {
+        (8, false, _) => "ByteField",
+        (8, true, _) => "SignedByteField",
+        (16, false, false) => "ShortField",
+        (16, false, true) => "LEShortField",
+        (16, true, false) => "SignedShortField",
+        (16, true, true) => "LESignedShortField",
+        (24, false, false) => "ThreeBytesField",
+        (24, false, true) => "LEThreeBytesField",
+        (32, false, false) => "IntField",
+        (32, false, true) => "LEIntField",
+        (32, true, false) => "SignedIntField",
+        (32, true, true) => "LESignedIntField",
+        (64, false, false) => "LongField",
+        (64, false, true) => "LELongField",
+        (64, true, false) => "SignedLongField",
+        (64, true, true) => "LESignedLongField",
+        _ => return None,
+    })
+}
+
+/// The enum variant of a sized field class, where Scapy has one
+fn enum_field(class: &str) -> Option<&'static str> {
+    Some(match class {
+        "ByteField" => "ByteEnumField",
+        "ShortField" => "ShortEnumField",
+        "LEShortField" => "LEShortEnumField",
+        "IntField" => "IntEnumField",
+        "LEIntField" => "LEIntEnumField",
+        _ => return None,
+    })
+}
+
+fn default_value(field_type: &FieldType) -> i128 {
+    match field_type {
+        FieldType::Fixed(value) => *value,
+        FieldType::Enum(variants) => variants.first().map_or(0, |v| v.value),
+        // not `clamp`, which panics on a range loaded with its bounds the wrong way round
+        FieldType::Range { min, max, .. } => 0.max(*min).min(*max),
+        _ => 0,
+    }
+}
+
+fn python_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{EnumVariant, SignEncoding};
+
+    fn registry() -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", Some("Frame".to_string()), Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "version",
+                    FieldType::Fixed(2),
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "flags",
+                    FieldType::Input,
+                    FieldLength::Fixed(4),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Enum(vec![EnumVariant {
+                        value: 1,
+                        name: Some("Status".to_string()),
+                        description: None,
+                    }]),
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "length",
+                    FieldType::Expr("payload.len()".to_string()),
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "doubled",
+                    FieldType::Derived("length * 2".to_string()),
+                    FieldLength::Fixed(0),
+                ))
+            })
+            .unwrap();
+        registry
+            .create_protocol(
+                "status_msg",
+                None,
+                Endianness::Little,
+                Some("frame".to_string()),
+            )
+            .unwrap();
+        registry
+            .edit_protocol("status_msg", |p| {
+                p.set_parent_constraint("kind", 1);
+                p.add_field(FieldRule::new(
+                    "temperature",
+                    FieldType::Range {
+                        min: -40,
+                        max: 125,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_scapy_module() {
+        let module = scapy_module(&registry(), "frame").unwrap();
+        assert!(module.contains("class Frame(Packet):\n    name = \"Frame\"\n"));
+        assert!(module.contains("        BitField(\"version\", 2, 4),\n"));
+        assert!(module.contains("        ByteEnumField(\"kind\", 1, {1: \"Status\"}),\n"));
+        assert!(module.contains("        # length: computed by payload.len()\n"));
+        assert!(module.contains("        ShortField(\"length\", 0),\n"));
+        assert!(!module.contains("\"doubled\""));
+        assert!(module.contains("class StatusMsg(Packet):"));
+        assert!(module.contains("        LESignedShortField(\"temperature\", 0),\n"));
+        assert!(module.contains("        StrField(\"payload\", b\"\"),\n"));
+        assert!(module.ends_with("bind_layers(Frame, StatusMsg, kind=1)\n"));
+    }
+
+    #[test]
+    fn test_default_value() {
+        let range = |min, max| FieldType::Range {
+            min,
+            max,
+            is_signed: true,
+        };
+        assert_eq!(default_value(&range(-5, 5)), 0);
+        assert_eq!(default_value(&range(3, 10)), 3);
+        assert_eq!(default_value(&range(5, 1)), 1);
+    }
+
+    #[test]
+    fn test_missing_protocol() {
+        assert!(scapy_module(&ProtocolRegistry::new(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_sign_magnitude() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("trim", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("trim", |p| {
+                let mut rule = FieldRule::new(
+                    "offset",
+                    FieldType::Range {
+                        min: -127,
+                        max: 127,
+                        is_signed: true,
+                    },
+                    FieldLength::Fixed(8),
+                );
+                rule.sign_encoding = SignEncoding::SignMagnitude;
+                p.add_field(rule)
+            })
+            .unwrap();
+        assert!(scapy_module(&registry, "trim").is_err());
+    }
+}
@@ -0,0 +1,827 @@
+# Menüs
+menu-file = Datei
+menu-new = Neu
+menu-open = Öffnen
+menu-import = Importieren
+menu-export = Exportieren
+menu-generate-code = Code erzeugen
+menu-edit = Bearbeiten
+menu-undo-delete = Löschen rückgängig
+menu-undo-delete-protocol = Löschen von '{ $protocol }' rückgängig
+menu-recently-deleted = Zuletzt gelöscht
+menu-check-integrity = Integrität prüfen
+menu-view = Ansicht
+menu-where-used = Verwendungen
+menu-compare = Protokolle vergleichen
+menu-coverage = Abdeckung
+menu-bit-accounting = Bitbilanz
+menu-history = Versionsverlauf
+menu-bookmarks = Lesezeichen
+menu-simulator = Gerätesimulator
+menu-replay = Aufzeichnung abspielen
+menu-scheduler = Sendeplaner
+menu-api-server = HTTP-API-Server
+menu-bit-calculator = Bitrechner
+menu-checksum-calculator = Prüfsummenrechner
+menu-appearance = Darstellung
+menu-plugins = Plugins
+menu-no-plugins = Keine Plugins in '{ $dir }' gefunden
+menu-no-actions = Keine Aktionen
+menu-plugin-failed = Plugin-Aktion '{ $action }' ist fehlgeschlagen: { $error }
+menu-help = Hilfe
+menu-about = Über
+export-documentation = Dokumentation (Markdown)
+export-documentation-title = Dokumentation
+export-ascii-diagram = Als ASCII-Diagramm kopieren
+export-ascii-diagram-hint = Das Layout des Protokolls als Textdiagramm wie in RFCs, für Code-Kommentare und Chats
+export-bytefield = Layout-Diagramm (LaTeX bytefield)
+export-bytefield-title = LaTeX-bytefield
+export-scapy = Scapy-Klassen (Python)
+export-scapy-title = Scapy-Klassen
+export-binary-template = 010-Editor-Vorlage
+export-binary-template-title = 010-Editor-Vorlage
+export-dbc = CAN-Datenbank (DBC)
+export-dbc-title = CAN-Datenbank
+export-fuzz = cargo-fuzz-Ziel (Rust)
+export-fuzz-title = cargo-fuzz-Ziel
+export-golden-bundle = Referenzpakete (JSON)
+export-golden-bundle-title = Referenzpakete
+export-golden-harness = Referenzpaket-Test (Rust)
+export-golden-harness-title = Referenzpaket-Test
+export-negative-corpus = Fehlerhafte Pakete (JSON)
+export-negative-corpus-hint = Gekürzte Pakete und Pakete mit unzulässigen Werten, aus den Werten im Paketbaukasten
+export-negative-corpus-title = Fehlerhafte Pakete
+export-capture-report = Mitschnitt-Analysebericht (HTML)
+export-capture-report-hint = Paketzahlen, Validator- und Prüfsummenfehler, Feldstatistiken und auffällige Pakete des Mitschnitts
+export-capture-report-title = Mitschnitt-Analysebericht
+export-capture-report-decoded = Mitschnitt dekodiert als { $protocol }
+export-capture-report-capture = Mitschnitt
+export-capture-parquet = Dekodierter Mitschnitt (Parquet)
+export-capture-parquet-hint = Die dekodierten Felder jedes mitgeschnittenen Pakets als typisierte Spalten, für Datenwerkzeuge
+export-capture-parquet-title = Dekodierter Mitschnitt
+export-capture-parquet-description = Parquet-Datei mit { $bytes } Bytes und einer Zeile für jedes der { $packets } mitgeschnittenen Pakete
+export-definitions = Protokolldefinitionen (JSON)
+export-definitions-title = Protokolldefinitionen
+export-schema = JSON-Schema des Projekts
+export-schema-title = JSON-Schema des Projekts
+export-plugin-failed = Plugin-Export '{ $exporter }' ist fehlgeschlagen: { $error }
+
+# Seiten
+page-protocol-designer = Protokolldesigner
+page-packet-builder = Paketbaukasten
+page-capture = Aufzeichnung
+page-scripts = Skripte
+page-console = Konsole
+
+# Seitenleiste
+sidebar-protocols = Protokolle
+sidebar-sort = Protokolle nach ID sortieren
+sidebar-move-up = Nach oben
+sidebar-move-down = Nach unten
+sidebar-delete = Löschen…
+sidebar-new = Neues Protokoll
+sidebar-subprotocol = { $name }, Unterprotokoll von { $parent }
+
+# Statusleiste
+status-no-protocol = Kein Protokoll ausgewählt
+status-offset = Offset 0x{ $hex } ({ $offset }), Bit { $bit }
+status-no-offset = Offset -
+status-selected = { $bytes } Bytes ({ $bits } Bits) ab 0x{ $hex } ausgewählt
+status-decode-failed = ⛔ Dekodierung fehlgeschlagen
+status-decoded = Dekodiert als '{ $protocol }'
+status-issues = ⚠ { $count } Probleme
+status-not-decoded = Nicht dekodiert
+status-api-server = API-Server auf { $address }
+status-simulator = Simulator läuft
+status-replaying = Wiedergabe, { $sent } von { $total } gesendet
+status-scheduling = { $count } Pakete werden periodisch gesendet
+status-builder = Baukasten verbunden über { $transport }
+status-capturing = Aufzeichnung läuft, { $count } Pakete
+
+# Dialoge
+dialog-error = Fehler
+dialog-ok = OK
+dialog-path = Pfad
+dialog-apply = Übernehmen
+dialog-cancel = Abbrechen
+dialog-start = Starten
+dialog-stop = Stoppen
+dialog-clear-log = Protokoll leeren
+open-title = Projekt öffnen
+open-button = Öffnen
+open-read-failed = '{ $path }' konnte nicht gelesen werden: { $error }
+open-invalid = Ungültiges Projekt '{ $path }': { $error }
+open-too-new = Projekt '{ $path }' hat Version { $version }, neuer als diese Version unterstützt ({ $supported })
+crash-title = BitLoom ist abgestürzt
+crash-message = BitLoom wurde beim letzten Mal unerwartet beendet. Der folgende Bericht beschreibt, was schiefging.
+crash-project-saved = Das Projekt wurde unter { $path } gespeichert.
+crash-project-lost = Das Projekt konnte nicht gespeichert werden.
+crash-restore = Projekt wiederherstellen
+crash-copy = Bericht kopieren
+bookmarks-title = Lesezeichen
+bookmarks-none = Keine Lesezeichen. Bytes in der Hex-Ansicht auswählen und auf Lesezeichen klicken.
+bookmarks-packet = Paket mit { $bytes } Bytes
+bookmarks-shown = in der Hex-Ansicht angezeigt
+bookmarks-jump = In der Hex-Ansicht zeigen
+bookmarks-remove = Lesezeichen entfernen
+bookmark-new-title = Neues Lesezeichen
+bookmark-name = Name
+bookmark-comment = Kommentar
+bookmark-bit-offset = Bit-Offset
+bookmark-bit-length = Bits
+bookmark-add = Hinzufügen
+coverage-title = Abdeckung
+coverage-capture = Aufgezeichnete Pakete
+coverage-presets = Gespeicherte Pakete
+coverage-only-missing = Nur nie abgedeckte
+coverage-select-protocol = Ein Protokoll auswählen, um zu sehen, welche seiner Unterprotokolle und Enum-Werte die Pakete abdecken
+coverage-analyze = '{ $protocol }' analysieren
+coverage-summary = '{ $protocol }': { $protocols } von { $protocols_total } Protokollen und { $variants } von { $variants_total } Enum-Werten abgedeckt
+coverage-ignored = { $count } Pakete anderer Protokolle nicht berücksichtigt
+coverage-protocols = Protokolle
+coverage-variants = Enum-Werte
+integrity-title = Projektintegrität
+integrity-ok = Alle Verweise zwischen Protokollen sind gültig
+integrity-repair = Reparieren
+integrity-repair-all = Alle reparieren
+
+# Export
+export-dialog-title = { $title } exportieren
+export-dialog-save = Speichern
+export-dialog-copy = Kopieren
+export-dialog-write-failed = '{ $path }' konnte nicht geschrieben werden: { $error }
+
+# Codegenerierung
+codegen-title = Code generieren
+codegen-target = Ziel
+codegen-package = Paket
+codegen-encode-methods = Methoden zum Kodieren
+codegen-encode-functions = Funktionen zum Kodieren
+codegen-proptest = proptest-Arbitrary-Implementierungen
+codegen-proptest-hint = Strategien, die von den Feldregeln erlaubte Werte erzeugen, für Property-Tests
+codegen-bigint = bigint für Felder über 32 Bits
+codegen-generate = Generieren
+codegen-export-title = { $language }-Code
+
+# Feldeditor
+field-editor-id-exists = Ein Feld mit der ID '{ $field }' existiert bereits in '{ $protocol }'
+field-editor-script-error = Das Skript hat einen Syntaxfehler
+field-editor-invalid-numbers = Einige Zahleneingaben sind keine gültigen Zahlen
+field-editor-edit-title = Feld '{ $field }' bearbeiten
+field-editor-add-title = Feld hinzufügen
+field-editor-id = ID
+field-editor-name = Name
+field-editor-use-id = ID '{ $id }' verwenden
+field-editor-use-id-hint = Die ID aus dem Namen ableiten
+field-editor-type = Typ
+field-editor-length = Länge
+field-editor-byte-order = Bytereihenfolge
+field-editor-protocol-default = Wie das Protokoll
+field-editor-out-of-spec = Werte außerhalb der Spezifikation
+field-editor-out-of-spec-hint = Ein Fehler bricht Dekodieren und Kodieren ab; eine Warnung lässt den Wert durch und meldet ihn, z. B. für Negativtests
+field-editor-negative-values = Negative Werte
+field-editor-display = Anzeige
+field-editor-display-default = Standard
+field-editor-color = Farbe
+field-editor-description = Beschreibung
+field-editor-subfields = Teilfelder ({ $count })
+field-editor-mapping = Wertzuordnung
+field-editor-variable = Variabel
+field-editor-bits-unit = Bits
+field-editor-value = Wert
+field-editor-min = Min
+field-editor-max = Max
+field-editor-signed = Vorzeichenbehaftet
+field-editor-script = Skript
+field-editor-input-hint = Der Wert wird beim Bauen eines Pakets angegeben
+field-editor-duplicate-value = Doppelter Wert
+field-editor-remove-variant = Variante entfernen
+field-editor-add-variant = Variante hinzufügen
+field-editor-import-clipboard = Aus der Zwischenablage importieren
+field-editor-import-hint = Eine Variante pro Zeile: Wert, Name, Beschreibung
+field-editor-import-variants = { $count } Varianten importieren
+field-editor-lowest-bit = Niedrigstes Bit
+field-editor-bits = Bits
+field-editor-remove-subfield = Teilfeld entfernen
+field-editor-subfield-hint = Bits werden ab dem niederwertigsten Bit des Werts gezählt
+field-editor-add-subfield = Teilfeld hinzufügen
+field-editor-mapping-none = Keine
+field-editor-mapping-table = Tabelle
+field-editor-mapping-curve = Kurve
+field-editor-mapping-hint = Rohwerte für die Anzeige übersetzen, z. B. ADC-Werte in eine Temperatur
+field-editor-raw = Roh
+field-editor-shown-as = Angezeigt als
+field-editor-remove-entry = Eintrag entfernen
+field-editor-add-entry = Eintrag hinzufügen
+field-editor-unit = Einheit
+field-editor-remove-point = Punkt entfernen
+field-editor-curve-hint = Werte zwischen Punkten werden linear interpoliert
+field-editor-add-point = Punkt hinzufügen
+
+# Werte
+endianness-big = Big-Endian
+endianness-little = Little-Endian
+severity-error = Fehler
+severity-warning = Warnung
+field-type-fixed = Fest
+field-type-enum = Enum
+field-type-range = Bereich
+field-type-expr = Ausdruck
+field-type-derived = Abgeleitet
+field-type-input = Eingabe
+display-decimal = Dezimal
+display-hex = Hex
+display-binary = Binär
+display-octal = Oktal
+display-ascii = ASCII
+sign-twos-complement = Zweierkomplement
+sign-magnitude = Vorzeichen und Betrag
+widgets-custom = Eigene
+transport = Transport
+transport-udp = UDP
+transport-serial = Seriell
+transport-tcp = TCP
+transport-socketcan = SocketCAN
+transport-listen-on = Lauschen auf
+transport-remote-hint = Absender der Anfrage
+transport-port = Port
+transport-baud-rate = Baudrate
+transport-connect-to = Verbinden mit
+transport-interface = Schnittstelle
+
+# Protokolldesigner
+designer-select-protocol = Wähle ein Protokoll, um seine Felder zu bearbeiten
+designer-add-field = Feld hinzufügen
+designer-copy = Kopieren
+designer-copy-hint = Die ausgewählten Felder kopieren (Strg+C)
+designer-paste = Einfügen
+designer-paste-hint = Die { $count } kopierten Felder diesem Protokoll hinzufügen (Strg+V)
+designer-id = ID
+designer-type = Typ
+designer-length = Länge
+designer-bit-offset = Bit-Offset
+designer-byte-offset = Byte-Offset
+designer-from = Aus
+designer-row-hint = Doppelklick oder Enter zum Bearbeiten, Strg- oder Umschalt-Klick, um mehrere auszuwählen
+designer-row-name = { $field }, { $kind }, { $length }
+designer-row-name-offset = { $field }, { $kind }, { $length }, Bit-Offset { $offset }
+designer-edit = Bearbeiten
+designer-insert-above = Darüber einfügen
+designer-insert-below = Darunter einfügen
+designer-constraints = Bedingungen der Unterprotokolle ({ $count })
+designer-validators = Paketprüfungen ({ $count })
+designer-notes = Notizen
+designer-notes-hint = Beobachtungen, z. B. wie ein Gerät auf Pakete dieses Protokolls reagiert
+designer-bits = { $bits } Bits
+designer-variable = variabel
+designer-variable-length = variable Länge
+designer-bit-of-byte = Bit { $bit } von Byte { $byte }
+designer-inherited-fields = Geerbte Felder ({ $count })
+designer-inherited-hint = Geerbt von '{ $protocol }'; Doppelklick, um es dort zu bearbeiten
+designer-total-length = Gesamtlänge
+designer-length-fixed = { $bits } Bits ({ $bytes })
+designer-length-variable = variabel, mindestens { $bits } Bits ({ $bytes })
+designer-bytes = { $bytes } Bytes
+designer-budget = Budget
+designer-budget-hint = Höchstzahl an Bits, die ein Paket dieses Protokolls und seiner Unterprotokolle belegen darf
+designer-frame = Rahmen
+designer-frame-hint = Genaue Bitzahl eines Pakets dieses Protokolls und seiner Unterprotokolle. Der Rest des Rahmens nach dem letzten Feld wird mit Nullbits aufgefüllt.
+designer-frame-padding = { $padding } Bits Auffüllung füllen den { $frame }-Bit-Rahmen
+designer-frame-padding-inherited = { $padding } Bits Auffüllung füllen den { $frame }-Bit-Rahmen von '{ $protocol }'
+designer-frame-exceeded = ⚠ Die Felder überschreiten den { $frame }-Bit-Rahmen um { $bits } Bits
+designer-frame-exceeded-inherited = ⚠ Die Felder überschreiten den { $frame }-Bit-Rahmen von '{ $protocol }' um { $bits } Bits
+designer-inherited = { $field } (geerbt)
+designer-none = Keines
+designer-length-field = Längenfeld
+designer-length-field-hint = Feld mit der Paketlänge in Bytes, das zeigt, wo Nachrichten in einem TCP- oder seriellen Bytestrom enden
+designer-plus = plus
+designer-bytes-unit = Bytes
+designer-bits-unit = Bits
+designer-adjustment-hint = Wird zum Feldwert addiert, um die Paketlänge zu erhalten, z. B. die Größe des Kopfs, wenn das Feld nur die Nutzdaten zählt
+designer-sequence-field = Sequenznummer
+designer-sequence-field-hint = Feld, das von Paket zu Paket hochzählt und das die Sequenzanalyse einer Aufzeichnung auf verlorene und wiederholte Pakete prüft
+designer-selected = { $count } ausgewählt
+designer-delete = Löschen
+designer-move-up = Nach oben
+designer-move-down = Nach unten
+designer-set-length = Länge setzen
+designer-set-length-hint = Den ausgewählten Feldern diese feste Länge geben
+designer-byte-order = Bytereihenfolge
+designer-protocol-default = Wie das Protokoll
+designer-zoom-out = Verkleinern
+designer-zoom-reset = Zoom zurücksetzen; Strg+Scrollen über dem Diagramm zoomt auch
+designer-zoom-in = Vergrößern
+designer-jump = Zu Feld springen
+designer-go-to = Zu '{ $field }'
+designer-no-such-field = kein solches Feld
+designer-applies-when = Gilt, wenn
+designer-and = und
+designer-go-to-field-in = Zum Feld in '{ $protocol }'
+designer-no-field = Kein Feld von '{ $protocol }' hat diese ID
+designer-constrained-by = Von Unterprotokollen eingeschränkt
+designer-constrained-hint = { $count } Unterprotokolle wählen anhand dieses Felds
+designer-layout-hint = { $field }: { $length } ab Bit { $bit }
+designer-minimap-hint = Klicken oder ziehen, um das Diagramm dorthin zu scrollen
+designer-subprotocol = Unterprotokoll
+designer-go-to-field = Zum Feld
+designer-open-subprotocol = Unterprotokoll öffnen
+designer-any = beliebig
+designer-not-constrained = Nicht eingeschränkt
+designer-overlap = ⚠ '{ $a }' und '{ $b }' können beide auf dasselbe Paket passen
+designer-gap = ⚠ Kein Unterprotokoll behandelt { $field } = { $values }
+designer-no-conflicts = Jedes Paket passt auf höchstens ein Unterprotokoll
+designer-validators-hint = Skripte laufen auf jedem dekodierten Paket. Gib true oder () zurück, wenn das Paket gültig ist, sonst eine Meldung oder ein Array von Meldungen.
+designer-remove-validator = Prüfung entfernen
+designer-add-validator = Prüfung hinzufügen
+
+# Paketbaukasten
+builder-invalid-value = Ungültiger Wert für das Feld '{ $field }': { $error }
+builder-select-protocol = Wähle ein Protokoll, um ein Paket davon zu bauen
+builder-select-protocol-first = Wähle zuerst ein Protokoll
+builder-id = ID
+builder-type = Typ
+builder-value = Wert
+builder-override = Übergehen
+builder-overridden-hint = Die Prüfung ist für dieses Feld übergangen
+builder-values-json = Werte-JSON
+builder-load = Laden
+builder-load-hint = Die Felder aus einem JSON-Objekt von Feld-ID zu Wert setzen
+builder-export = Exportieren
+builder-export-title = Feldwerte
+builder-read-failed = '{ $path }' konnte nicht gelesen werden: { $error }
+builder-paste-values = Werte einfügen
+builder-paste-hint = Eine Spalte von Werten, einer pro Feld, oder Zeilen Feld=Wert
+builder-current = Aktuell
+builder-pasted = Eingefügt
+builder-fix-invalid = Zuerst die ungültigen Werte korrigieren
+builder-discard = Verwerfen
+builder-override-hint = Den Wert kodieren, auch wenn er die Regeln des Felds verletzt
+builder-override-name = Prüfung von { $field } übergehen
+builder-overridden-banner = ⚠ Prüfung übergangen für { $fields }: dieses Paket kann das Protokoll verletzen
+builder-allow-sending = Dieses Paket trotzdem senden lassen
+builder-clear-overrides = Übergehungen aufheben
+builder-send-section = Senden
+builder-send-to = Senden an
+builder-disconnect = Trennen
+builder-connect = Verbinden
+builder-send = Senden
+builder-send-disabled = Zuerst verbinden und gültige Werte eingeben
+builder-send-unconfirmed = Zuerst das Senden des Pakets mit übergangenen Feldern erlauben
+builder-computed = beim Kodieren berechnet
+builder-variant-name = Variante von { $field }
+builder-presets = Vorlagen
+builder-preset-name = Name
+builder-folder = Ordner
+builder-folder-hint = Verschachtelte Ordner mit '/' trennen
+builder-save = Speichern
+builder-save-hint = Die aktuellen Werte speichern und eine Vorlage gleichen Namens ersetzen
+builder-notes = Notizen
+builder-notes-hint = z. B. das Gerät lehnt dies ab, außer Bit 3 ist gesetzt
+builder-preset-hint = Paket von '{ $protocol }'
+builder-duplicate = Duplizieren
+builder-move-to-folder = In Ordner verschieben
+builder-delete = Löschen
+builder-move = Verschieben
+
+# Aufzeichnung
+capture-title = Aufzeichnung
+capture-decode-as = Dekodieren als
+capture-select = Auswählen...
+capture-subprotocols = Unterprotokolle
+capture-subprotocols-hint = Jedes Paket weiter als das Unterprotokoll dekodieren, dessen Bedingungen es erfüllt
+capture-scrub = Bereinigen...
+capture-scrub-hint = Identifizierende Feldwerte ersetzen, um die Aufzeichnung weiterzugeben
+capture-suggest-fields = Felder vorschlagen...
+capture-suggest-fields-hint = Konstante Bytes, Zähler, Längen und eine Prüfsumme aus den Paketen, alle eines Typs, als neues Protokoll zum Verfeinern erraten
+capture-clear = Leeren
+capture-decoding = Dekodiere { $done } / { $total }
+capture-view-packets = Pakete
+capture-view-conversations = Konversationen
+capture-view-conversations-hint = Anfragen mit ihren Antworten gepaart, mit Umlaufzeiten
+capture-view-sequence = Sequenz
+capture-view-sequence-hint = Verlorene, wiederholte und vertauschte Pakete nach ihren Sequenznummern
+capture-view-timing = Zeitverhalten
+capture-view-timing-hint = Zeit zwischen den Paketen jedes Protokolls, gemessen an seiner Rate
+capture-tcp-needs-protocol = Wählen Sie das Protokoll zum Dekodieren; seine Länge teilt TCP-Streams in Nachrichten
+capture-live = Live
+capture-transport = Transport
+capture-interface = Schnittstelle
+capture-interface-hint = Jeden Frame einer Netzwerkschnittstelle mit libpcap aufzeichnen
+capture-file = Datei
+capture-binary-log = Binärlog
+capture-binary-log-hint = Rohe Datensätze hintereinander, wie von einem Datenlogger geschrieben
+capture-load = Laden
+capture-detect-framing = Rahmung erkennen
+capture-detect-framing-hint = Vorschlagen, wie die Datensätze des Logs abgegrenzt sind, danach, wie oft seine Bytes vorkommen und wie ähnlich die Datensätze jeder Rahmung beginnen
+capture-read-failed = '{ $path }' konnte nicht gelesen werden: { $error }
+capture-payload-frame = Ganze Frames
+capture-payload-frame-hint = Jeden Frame mit seinen Link-, IP- und Transport-Headern laden
+capture-payload-udp = UDP-Nutzdaten
+capture-payload-udp-hint = Ethernet-, IP- und UDP-Header entfernen; andere Frames werden übersprungen
+capture-payload-tcp = TCP-Nachrichten
+capture-payload-tcp-hint = TCP-Segmente wieder ordnen und die Streams nach der Länge des dekodierten Protokolls in Nachrichten aufteilen
+capture-suggested-framing = Vorgeschlagene Rahmung
+capture-close = Schließen
+capture-frequent-bytes = Häufigste Bytes der ersten { $count } Bytes: { $bytes }
+capture-no-framing = Keine Rahmung teilt das Log in ähnlich aussehende Datensätze
+capture-framing-records = { $records } Datensätze mit { $shortest }-{ $longest } Bytes
+capture-framing-confidence-hint = Wie viel ähnlicher die Datensätze beginnen als zufällig
+capture-use = Verwenden
+capture-framing-fixed = Feste Größe von { $size } Bytes
+capture-framing-length-one = Länge von 1 Byte bei Byte { $offset }, { $order }
+capture-framing-length = Länge von { $width } Bytes bei Byte { $offset }, { $order }
+capture-framing-delimiter = Endet mit { $delimiter }
+capture-big-endian = Big-Endian
+capture-little-endian = Little-Endian
+capture-split = In Nachrichten aufteilen
+capture-split-hint = Das Ende von Nachrichten an der Länge des dekodierten Protokolls finden, für TCP und serielle Verbindungen, bei denen ein Lesevorgang einen Teil einer Nachricht oder mehrere enthalten kann
+capture-start = Starten
+capture-stop = Stoppen
+capture-refresh = Aktualisieren
+capture-filter = Filter
+capture-filter-example = z. B. udp port 5000
+capture-filter-hint = BPF-Filterausdruck, wie in den Aufzeichnungsfiltern von tcpdump und Wireshark
+capture-framing = Rahmung
+capture-framing-kind-fixed = Feste Größe
+capture-framing-kind-length-prefix = Längenpräfix
+capture-framing-kind-delimiter = Trennzeichen
+capture-record-size = Datensatzgröße
+capture-bytes-unit = Bytes
+capture-length-at = Länge bei
+capture-byte-prefix = Byte
+capture-length-size = Längengröße
+capture-adjustment = Korrektur
+capture-adjustment-hint = Wird zur Länge addiert, um die Anzahl der folgenden Bytes zu erhalten
+capture-delimiter = Trennzeichen
+capture-delimiter-example = z. B. 0d 0a
+capture-timestamp = Zeitstempel
+capture-timestamp-before = vor jedem Datensatz
+capture-timestamp-hint = Eine Anzahl von Zeiteinheiten seit der Unix-Epoche, nicht Teil des Pakets
+capture-column-number = Nr.
+capture-column-time = Zeit
+capture-column-length = Länge
+capture-column-decoded = Dekodiert
+capture-sort-hint = Nach dieser Spalte sortieren
+capture-columns = Spalten ({ $count })
+capture-columns-hint = Felder von '{ $protocol }', die als Spalten gezeigt werden; die Spalten werden im Projekt gespeichert
+capture-show-summary = Zusammenfassung zeigen
+capture-show-summary-hint = Stattdessen alle Felder jedes Pakets in einer Spalte auflisten
+capture-unsort = Sortierung aufheben
+capture-pair-by = Paaren nach
+capture-pair-fields = Feldern
+capture-pair-script = Skript
+capture-choose = Auswählen...
+capture-decode-first = Dekodieren Sie die Pakete zuerst als ein Protokoll
+capture-suggest = Vorschlagen
+capture-suggest-hint = Felder, die wie eine Sequenznummer oder Transaktions-ID heißen
+capture-pair = Paaren
+capture-wait-decoding = Warten Sie, bis die Pakete dekodiert sind
+capture-pair-prompt = Wählen Sie, wie Anfragen und Antworten zugeordnet werden, und klicken Sie auf Paaren
+capture-exchanges = { $count } Austausche, { $unanswered } unbeantwortet
+capture-exchanges-round-trip = { $count } Austausche, { $unanswered } unbeantwortet, Umlaufzeit { $min } / { $mean } / { $max } ms (Min. / Mittel / Max.)
+capture-column-request = Anfr.
+capture-column-response = Antw.
+capture-column-rtt = RTT (ms)
+capture-column-retries = Wdh.
+capture-column-key = Schlüssel
+capture-check = Prüfen
+capture-check-hint = Die dekodierten Pakete anhand des im Designer für ihr Protokoll gewählten Sequenznummernfelds durchgehen
+capture-check-prompt = Klicken Sie auf Prüfen, um nach Lücken in den Sequenznummern der Pakete zu suchen
+capture-no-sequence = Keines der dekodierten Pakete hat eine Sequenznummer; wählen Sie das Feld für ihr Protokoll im Designer
+capture-sequence-summary = { $field }: { $packets } Pakete, { $missing } fehlend in { $gaps } Lücken, { $duplicates } Duplikate, { $wraps } Überläufe, { $back } Rücksprünge
+capture-column-after = Nach (ms)
+capture-column-event = Ereignis
+capture-measure = Messen
+capture-measure-hint = Die dekodierten Pakete zeitlich messen, getrennt für jedes Unterprotokoll
+capture-tolerance = Toleranz
+capture-tolerance-hint = Wie weit die Zeit zwischen Paketen von ihrer erwarteten Periode abweichen darf
+capture-measure-prompt = Klicken Sie auf Messen, um die Pakete jedes Protokolls zeitlich zu messen
+capture-nothing-to-time = Kein Protokoll hat zwei oder mehr dekodierte Pakete zum Messen
+capture-timing-summary = { $packets } Pakete, { $min } / { $mean } / { $max } ms (Min. / Mittel / Max.), Jitter { $jitter } ms, { $rate } pro Sekunde
+capture-expected-every = Erwartet alle
+capture-off-period = { $outside } von { $total } Intervallen weichen um mehr als { $tolerance } ms ab
+capture-histogram-hint = { $from } bis { $to } ms: { $count } Intervalle
+
+# Hex-Ansicht
+hex-title = Hex-Ansicht
+detached-dock = Andocken
+detached-dock-hint = Zurück ins Hauptfenster verschieben
+detached-pop-out = Abkoppeln
+detached-pop-out-hint = In einem eigenen Fenster öffnen
+hex-preview-swapped = der anderen Byte-Reihenfolge
+hex-preview-reversed = umgekehrten Bits in jedem Byte
+hex-preview-and = und
+hex-preview = Vorschau: dekodiert mit { $changes }. Das Protokoll bleibt unverändert.
+hex-packet-tab = Paket { $number }
+hex-tab-hint = { $bytes } Bytes; Rechtsklick für mehr
+hex-tab-title = Titel
+hex-pin = Als Referenz anheften
+hex-pin-hint = Eine Kopie dieses Pakets behalten, um die danach angezeigten Pakete damit zu vergleichen, und die abweichenden Bytes einfärben
+hex-duplicate = Duplizieren
+hex-close = Schließen
+hex-new-tab-hint = Ein leeres Paket in einem neuen Tab öffnen
+hex-entropy-title = Byte-Entropie von { $count } aufgezeichneten Paketen
+hex-entropy-legend = niedrig: konstant, hoch: Zähler und Zufallsdaten
+hex-entropy-refresh = Aus der Aufzeichnung neu berechnen
+hex-reference-equal = Das Paket gleicht der Referenz
+hex-reference-shorter = { $changed } Bytes weichen ab, und das Paket ist { $shorter } Bytes kürzer
+hex-reference-differ = { $changed } Bytes weichen ab
+hex-reference = Referenz: { $title } ({ $bytes } Bytes)
+hex-empty-packet = Leeres Paket
+hex-tint-changes = Änderungen einfärben
+hex-unpin = Lösen
+hex-decode = Dekodieren
+hex-decode-hint = Das Paket als das ausgewählte Protokoll dekodieren
+hex-preview-order = Reihenfolge vorschauen
+hex-swap-bytes = Byte-Reihenfolge tauschen
+hex-swap-bytes-hint = Mehrbyte-Felder lesen, als hätte das Protokoll die andere Byte-Reihenfolge
+hex-reverse-bits = Bit-Reihenfolge umkehren
+hex-reverse-bits-hint = Die Bits jedes Bytes mit dem niederwertigsten zuerst lesen
+hex-copy-dump = Hex-Dump kopieren
+hex-export-dump = Hex-Dump exportieren
+hex-export-dump-title = Hex-Dump
+hex-entropy = Entropie
+hex-entropy-hint = Zeigen, wie stark die Bytes an jedem Offset über die aufgezeichneten Pakete variieren, was konstante Header, Zähler und verschlüsselte oder komprimierte Daten unterscheidet
+hex-bookmark = Lesezeichen
+hex-bookmark-hint = Die ausgewählten Bytes benennen, mit einem Kommentar
+hex-stop-preview = Vorschau beenden
+hex-not-decoded = { $bytes } Bytes ab Offset { $offset } wurden nicht dekodiert
+hex-import = Hex-Dump importieren
+hex-no-packet = Kein Paket geladen
+hex-load-bytes = { $bytes } Bytes laden
+hex-load-first = Erstes von { $count } Paketen laden
+hex-strip-entropy = Byte { $byte }: { $entropy } Bits Entropie, { $distinct } verschiedene Werte
+hex-strip-most-common = Am häufigsten { $value } in { $count } von { $packets } Paketen
+hex-strip-mean = Mittelwert { $mean }, Standardabweichung { $deviation }
+
+# Inspektor
+inspector-title = Inspektor
+inspector-no-packet = Kein Paket dekodiert
+inspector-fields = { $protocol } ({ $count } Felder)
+inspector-derived-hint = Abgeleitetes Feld, nicht im Paket serialisiert
+inspector-raw-value = Rohwert { $value }
+inspector-subfield-hint = { $range } von { $field }
+inspector-failure-bits = { $bits } Bits bei Bit-Offset { $offset }
+inspector-dispatch = Zuordnung
+inspector-subprotocols-of = Unterprotokolle von '{ $protocol }'
+inspector-no-constraints = keine Bedingungen, gilt für jedes Paket
+inspector-check-actual = { $field } ist { $actual }, benötigt { $expected }
+inspector-check-missing = { $field } ist nicht im Paket, benötigt { $expected }
+inspector-chosen-mismatch = Als dieses dekodiert, obwohl das Paket nicht passt
+inspector-also-matches = Passt ebenfalls auf das Paket
+inspector-show-as = Anzeigen als
+inspector-field-default = Feldstandard
+inspector-format-with = Formatieren mit
+inspector-default = Standard
+inspector-reported-by = Gemeldet von Validator '{ $validator }' von '{ $protocol }'
+
+# Bitrechner
+bit-calculator-title = Bitrechner
+bit-calculator-value = Wert
+bit-calculator-width = Breite
+bit-calculator-bits-unit = Bits
+bit-calculator-truncated = Der Wert passt nicht in { $bits } Bits und wird abgeschnitten
+bit-calculator-use-result = Ergebnis übernehmen
+bit-calculator-use-result-hint = Mit dem Ergebnis weiterrechnen
+bit-calculator-unsigned = Vorzeichenlos
+bit-calculator-hex = Hex
+bit-calculator-binary = Binär
+bit-calculator-twos-complement = Zweierkomplement
+bit-calculator-sign-magnitude = Vorzeichen und Betrag
+bit-calculator-ascii = ASCII
+bit-calculator-copy = Kopieren
+bit-calculator-bit = Bit { $bit }
+
+# Prüfsummenrechner
+checksum-calculator-title = Prüfsummenrechner
+checksum-calculator-hex = Hex
+checksum-calculator-text = Text
+checksum-calculator-use-packet = Paket übernehmen
+checksum-calculator-use-packet-hint = Das Paket in der Hex-Ansicht prüfen, oder die darin ausgewählten Bytes
+checksum-calculator-custom = Eigener CRC
+checksum-calculator-customize = Anpassen
+checksum-calculator-customize-hint = Einen eigenen CRC mit diesen Parametern beginnen
+checksum-calculator-width = Breite
+checksum-calculator-bits-unit = Bits
+checksum-calculator-polynomial = Polynom
+checksum-calculator-init = Startwert
+checksum-calculator-xorout = Abschließendes XOR
+checksum-calculator-reflect = Spiegeln
+checksum-calculator-reflect-input = Eingabe
+checksum-calculator-reflect-output = Ausgabe
+checksum-calculator-bytes = { $bytes } Bytes
+checksum-calculator-decimal = Dezimal
+checksum-calculator-copy = Kopieren
+checksum-calculator-expression = In einem Feldausdruck:
+
+# Bitbilanz
+bit-accounting-title = Bitbilanz
+bit-accounting-select-protocol = Wählen Sie ein Protokoll fester Länge, um seine Bits aufzuschlüsseln
+bit-accounting-export = CSV exportieren
+bit-accounting-export-title = Bitbilanz
+bit-accounting-frame-length = Rahmenlänge { $bits } Bits, festgelegt von '{ $protocol }'
+bit-accounting-bits = Bits
+bit-accounting-length = Länge
+bit-accounting-use = Verwendung
+bit-accounting-field = Feld
+bit-accounting-padding = Rahmenauffüllung
+bit-accounting-used = belegt
+bit-accounting-reserved = reserviert
+bit-accounting-unassigned = nicht zugewiesen
+bit-accounting-part = { $bits } { $kind } ({ $percent } %)
+bit-accounting-summary = { $bits } Bits: { $used }, { $reserved }, { $unassigned }
+
+# Vorgeschlagene Felder
+skeleton-title = Vorgeschlagene Felder
+skeleton-guessed = Aus { $count } aufgezeichneten Paketen erraten, die als ein Typ angenommen werden
+skeleton-bytes = Bytes
+skeleton-length = Länge
+skeleton-looks-like = Sieht aus wie
+skeleton-length-little = { $bytes } Bytes, Little-Endian
+skeleton-length-bytes = { $bytes } Bytes
+skeleton-variable = variabel
+skeleton-protocol-id = Protokoll-ID
+skeleton-create = Protokoll erstellen
+skeleton-create-hint = Ein neues Protokoll mit diesen Feldern, zum Verfeinern im Designer
+
+# Externer Editor
+external-editor-open = ✏ Externer Editor
+external-editor-open-hint = Das Skript in dem mit $VISUAL oder $EDITOR angegebenen Editor bearbeiten (einem, der ein eigenes Fenster öffnet, z. B. "code --wait"), sonst in der Standardanwendung für .rhai-Dateien. Das Skript wird hier bei jedem Speichern der Datei neu geladen.
+external-editor-stop = Beenden
+external-editor-stop-hint = Das Skript nicht mehr aus dem externen Editor neu laden
+external-editor-editing = Wird extern bearbeitet
+external-editor-create-failed = '{ $path }' konnte nicht erstellt werden: { $error }
+external-editor-write-failed = '{ $path }' konnte nicht geschrieben werden: { $error }
+external-editor-read-failed = '{ $path }' konnte nicht gelesen werden: { $error }
+external-editor-start-failed = Der externe Editor konnte nicht gestartet werden: { $error }
+
+# Verwendungen
+where-used-title = Verwendungen
+where-used-select-field = Ein Feld auswählen, um zu sehen, wo es verwendet wird
+where-used-references = Verweise auf '{ $protocol }.{ $field }'
+where-used-none = Keine Verweise
+where-used-constraint = Bedingung in '{ $protocol }': == { $value }
+where-used-expression = Ausdruck von '{ $protocol }.{ $field }'
+where-used-validator = Validator '{ $validator }' von '{ $protocol }'
+where-used-length-field = Längenfeld von '{ $protocol }'
+where-used-sequence-field = Sequenznummernfeld von '{ $protocol }'
+
+# Protokolle vergleichen
+compare-title = Protokolle vergleichen
+compare-select-protocols = Zwei Protokolle zum Vergleichen auswählen
+compare-copy-report = Bericht kopieren
+compare-none = (keines)
+
+# Versionsverlauf
+history-title = Versionsverlauf
+history-select-protocol = Ein Protokoll auswählen, um seinen Verlauf zu sehen
+history-message = Beschreibung der Version
+history-commit = Version speichern
+history-none = Noch keine Versionen gespeichert
+history-restore = Wiederherstellen
+history-diff = Unterschiede zum aktuellen Stand
+
+# Zuletzt gelöscht
+trash-title = Zuletzt gelöscht
+trash-none = In dieser Sitzung wurden keine Protokolle gelöscht
+trash-discard = Endgültig löschen
+trash-restore = Wiederherstellen
+trash-with = mit { $subprotocols } Unterprotokollen und { $presets } gespeicherten Paketen
+trash-empty-button = Papierkorb leeren
+
+# Protokoll löschen
+delete-title = Protokoll löschen
+delete-confirm = Protokoll '{ $protocol }' löschen?
+delete-restore-hint = Gespeicherte Versionen bleiben im Verlauf. Wiederherstellen unter { $menu } > { $item }.
+delete-button = Löschen
+delete-no-subprotocols = Es hat keine Unterprotokolle.
+delete-subprotocols = Diese { $count } Unterprotokolle werden mitgelöscht:
+delete-presets = Diese { $count } gespeicherten Pakete werden ebenfalls gelöscht:
+
+# Gerätesimulator
+simulator-title = Gerätesimulator
+simulator-running = Läuft
+simulator-select-protocols = Die Protokolle für Anfrage und Antwort auswählen
+simulator-reply-to = Antworten an
+simulator-request = Anfrage
+simulator-response = Antwort
+simulator-split = In Nachrichten aufteilen
+simulator-split-hint = Das Ende der Anfragen an der Länge des Anfrageprotokolls erkennen, für TCP- und serielle Verbindungen, bei denen ein Lesevorgang einen Teil einer Anfrage oder mehrere enthalten kann
+simulator-select = Auswählen...
+
+# HTTP-API-Server
+api-server-title = HTTP-API-Server
+api-server-running = Läuft auf http://{ $address }
+api-server-endpoints = Endpunkte
+api-server-list = Protokoll-IDs
+api-server-decode = Hex-Inhalt → Feldwerte
+api-server-encode = Feldwerte → Hex
+api-server-validate = Hex-Inhalt → Validierungsprobleme
+
+# Mitschnitt wiedergeben
+replay-title = Mitschnitt wiedergeben
+replay-progress = { $sent } von { $total } gesendet
+replay-start-hint = Die Pakete der Aufzeichnungsseite mit ihren aufgezeichneten Abständen senden
+replay-select-protocol = Zum Umschreiben von Feldern auf der Aufzeichnungsseite das Protokoll zum Dekodieren auswählen
+replay-packets = { $count } Pakete auf der Aufzeichnungsseite
+replay-send-to = Senden an
+replay-speed = Geschwindigkeit
+replay-rewrite = Felder umschreiben
+replay-rewrite-hint = Jedes Paket als das Protokoll der Aufzeichnung dekodieren und Felder mit einem Skript ändern
+
+# Sendeplaner
+scheduler-title = Sendeplaner
+scheduler-connection = Sendet über die { $transport }-Verbindung des Paketbaukastens
+scheduler-not-connected = Zuerst im Abschnitt { $section } des Paketbaukastens verbinden
+scheduler-running = Läuft, { $sent } gesendet
+scheduler-no-presets = Pakete im Paketbaukasten als Vorlagen speichern, um sie regelmäßig zu senden
+scheduler-every = alle
+scheduler-none-checked = Die zu sendenden Vorlagen ankreuzen
+
+# Mitschnitt bereinigen
+scrub-title = Mitschnitt bereinigen
+scrub-no-fields = Die aufgezeichneten Pakete als ein Protokoll dekodieren, um die zu bereinigenden Felder auszuwählen
+scrub-keep = Behalten
+scrub-fixed = Fest
+scrub-randomize = Zufällig
+scrub-apply = Pakete bereinigen
+scrub-apply-hint = Die Pakete auf der Aufzeichnungsseite umschreiben; Pakete, die sich nicht dekodieren lassen, werden verworfen, da ihre Felder nicht bereinigt werden können
+scrub-save = pcap speichern
+scrub-save-hint = Pakete, die keine ganzen Frames sind, werden mit dem privaten Link-Typ USER0 gespeichert
+scrub-closed = Der Dialog zum Bereinigen ist geschlossen
+scrub-select-protocol = Das Protokoll auswählen, als das die Pakete dekodiert werden
+scrub-invalid-value = Ungültiger Wert für Feld '{ $field }': { $error }
+scrub-no-choice = Die zu bereinigenden Felder auswählen
+scrub-done = { $scrubbed } Pakete bereinigt, { $dropped } nicht dekodierbare verworfen
+scrub-no-path = Den Pfad zum Speichern eingeben
+scrub-write-failed = '{ $path }' konnte nicht geschrieben werden: { $error }
+
+# Ausdruckseditor
+expr-preview = Vorschau
+expr-fix-syntax = Den Syntaxfehler beheben, um das Ergebnis in der Vorschau zu sehen
+expr-sample-hint = Ganzzahl, Gleitkommazahl, true/false, "Zeichenkette" oder [Hex-Bytes]
+expr-invalid-samples = Gültige Beispielwerte eingeben, um das Ergebnis zu sehen
+expr-result = Ergebnis:
+
+# Import
+import-dbc = CAN-Datenbank (DBC)
+import-c-header = C-Header (Structs)
+import-title = { $format } importieren
+import-button = Importieren
+import-read-failed = '{ $path }' konnte nicht gelesen werden: { $error }
+import-done = { $count } Protokolle importiert
+
+# Konsole
+console-title = Konsole
+console-intro = Skripte laufen auf dem Projekt und können es verändern.
+console-clear = Leeren
+console-reset = Zurücksetzen
+console-reset-hint = Von früheren Befehlen definierte Variablen vergessen
+console-api = API
+console-run = Ausführen
+console-api-protocols = IDs aller Protokolle
+console-api-protocol = Map mit ID, Name, Elternprotokoll, Byte-Reihenfolge, Metadaten und Feldern
+console-api-set-metadata = einen Metadateneintrag setzen
+console-api-add-field = ein Eingabefeld anhängen
+console-api-add-expression-field = ein Ausdrucksfeld anhängen
+console-api-remove-field = ein Feld entfernen
+console-api-rename-field = ein Feld umbenennen und Verweise anpassen
+console-api-packet = Feldwerte des aktuellen Pakets, oder ()
+console-api-decode = Bytes in eine Map von Feldwerten dekodieren
+console-api-encode = eine Map von Feldwerten in Bytes kodieren
+
+# Skriptbibliothek
+library-title = Skriptbibliothek
+library-intro = Hier definierte Funktionen und Konstanten können in jedem Feldausdruck verwendet werden.
+library-intro-hint = Innerhalb von Funktionen auf Konstanten der Bibliothek als global::NAME verweisen
+library-save = Speichern
+library-loaded = Geladen: { $functions }
+
+# Darstellung
+appearance-title = Darstellung
+appearance-language = Sprache
+appearance-theme = Design
+appearance-accent = Akzentfarbe
+appearance-field-colors = Feldfarben
+appearance-custom = Eigene
+appearance-custom-hint = Mit der aktuellen Palette beginnen
+appearance-values = Werte
+appearance-remove = Entfernen
+appearance-remove-hint = Rechtsklick zum Entfernen
+appearance-add-color = Farbe hinzufügen
+
+# Fehler
+kind-protocol = Protokoll
+kind-field = Feld
+error-protocol-not-found = Protokoll mit der ID '{ $protocol }' existiert nicht
+error-protocol-exists = Protokoll mit der ID '{ $protocol }' existiert bereits
+error-parent-not-found = Übergeordnetes Protokoll mit der ID '{ $protocol }' existiert nicht
+error-field-not-found = Feld mit der ID '{ $field }' nicht in Protokoll '{ $protocol }' gefunden
+error-field-exists = Feld mit der ID '{ $field }' existiert bereits in Protokoll '{ $protocol }'
+error-id-empty = { $kind }-ID darf nicht leer sein
+error-id-char = { $kind }-ID '{ $id }' darf nur Buchstaben, Ziffern und '_' enthalten, nicht '{ $char }'
+error-id-digit = { $kind }-ID '{ $id }' darf nicht mit einer Ziffer beginnen
+error-field-after-variable = Feld '{ $field }' kann nicht auf das Feld variabler Länge '{ $variable }' in Protokoll '{ $protocol }' folgen
+error-field-id-changed = Die ID von Feld '{ $field }' kann nicht beim Bearbeiten geändert werden; benennen Sie es stattdessen um
+error-protocol-id-changed = Die ID von Protokoll '{ $protocol }' kann nicht beim Bearbeiten geändert werden; benennen Sie es stattdessen um
+error-parent-changed = Das übergeordnete Protokoll von '{ $protocol }' kann nach dem Erstellen nicht geändert werden
@@ -0,0 +1,827 @@
+# Menus
+menu-file = File
+menu-new = New
+menu-open = Open
+menu-import = Import
+menu-export = Export
+menu-generate-code = Generate Code
+menu-edit = Edit
+menu-undo-delete = Undo Delete
+menu-undo-delete-protocol = Undo Delete '{ $protocol }'
+menu-recently-deleted = Recently Deleted
+menu-check-integrity = Check Integrity
+menu-view = View
+menu-where-used = Where Used
+menu-compare = Compare Protocols
+menu-coverage = Coverage
+menu-bit-accounting = Bit Accounting
+menu-history = Revision History
+menu-bookmarks = Bookmarks
+menu-simulator = Device Simulator
+menu-replay = Replay Capture
+menu-scheduler = Transmit Scheduler
+menu-api-server = HTTP API Server
+menu-bit-calculator = Bit Calculator
+menu-checksum-calculator = Checksum Calculator
+menu-appearance = Appearance
+menu-plugins = Plugins
+menu-no-plugins = No plugins found in '{ $dir }'
+menu-no-actions = No actions
+menu-plugin-failed = Plugin action '{ $action }' failed: { $error }
+menu-help = Help
+menu-about = About
+export-documentation = Documentation (Markdown)
+export-documentation-title = Documentation
+export-ascii-diagram = Copy as ASCII Diagram
+export-ascii-diagram-hint = The layout of the protocol as the text diagram of RFCs, for code comments and chat
+export-bytefield = Layout Diagram (LaTeX bytefield)
+export-bytefield-title = LaTeX bytefield
+export-scapy = Scapy Classes (Python)
+export-scapy-title = Scapy Classes
+export-binary-template = 010 Editor Template
+export-binary-template-title = 010 Editor Template
+export-dbc = CAN Database (DBC)
+export-dbc-title = CAN Database
+export-fuzz = cargo-fuzz Target (Rust)
+export-fuzz-title = cargo-fuzz Target
+export-golden-bundle = Golden Packets (JSON)
+export-golden-bundle-title = Golden Packets
+export-golden-harness = Golden Packet Test (Rust)
+export-golden-harness-title = Golden Packet Test
+export-negative-corpus = Malformed Packets (JSON)
+export-negative-corpus-hint = Packets cut short and with out-of-spec values, made from the values in the packet builder
+export-negative-corpus-title = Malformed Packets
+export-capture-report = Capture Analysis Report (HTML)
+export-capture-report-hint = Packet counts, validator and checksum failures, field statistics and anomalous packets of the capture
+export-capture-report-title = Capture Analysis Report
+export-capture-report-decoded = Capture decoded as { $protocol }
+export-capture-report-capture = Capture
+export-capture-parquet = Decoded Capture (Parquet)
+export-capture-parquet-hint = The decoded fields of every captured packet as typed columns, for data tools
+export-capture-parquet-title = Decoded Capture
+export-capture-parquet-description = Parquet file of { $bytes } bytes with a row for each of the { $packets } captured packets
+export-definitions = Protocol Definitions (JSON)
+export-definitions-title = Protocol Definitions
+export-schema = Project JSON Schema
+export-schema-title = Project JSON Schema
+export-plugin-failed = Plugin exporter '{ $exporter }' failed: { $error }
+
+# Pages
+page-protocol-designer = Protocol Designer
+page-packet-builder = Packet Builder
+page-capture = Capture
+page-scripts = Scripts
+page-console = Console
+
+# Sidebar
+sidebar-protocols = Protocols
+sidebar-sort = Sort protocols by ID
+sidebar-move-up = Move Up
+sidebar-move-down = Move Down
+sidebar-delete = Delete…
+sidebar-new = New protocol
+sidebar-subprotocol = { $name }, subprotocol of { $parent }
+
+# Status bar
+status-no-protocol = No protocol selected
+status-offset = Offset 0x{ $hex } ({ $offset }), bit { $bit }
+status-no-offset = Offset -
+status-selected = Selected { $bytes } bytes ({ $bits } bits) at 0x{ $hex }
+status-decode-failed = ⛔ Decode failed
+status-decoded = Decoded as '{ $protocol }'
+status-issues = ⚠ { $count } issues
+status-not-decoded = Not decoded
+status-api-server = API server on { $address }
+status-simulator = Simulator running
+status-replaying = Replaying, { $sent } of { $total } sent
+status-scheduling = Sending { $count } packets periodically
+status-builder = Builder connected over { $transport }
+status-capturing = Capturing, { $count } packets
+
+# Dialogs
+dialog-error = Error
+dialog-ok = OK
+dialog-path = Path
+dialog-apply = Apply
+dialog-cancel = Cancel
+dialog-start = Start
+dialog-stop = Stop
+dialog-clear-log = Clear Log
+open-title = Open Project
+open-button = Open
+open-read-failed = Failed to read '{ $path }': { $error }
+open-invalid = Invalid project '{ $path }': { $error }
+open-too-new = Project '{ $path }' is of version { $version }, newer than this build supports ({ $supported })
+crash-title = BitLoom Crashed
+crash-message = BitLoom closed unexpectedly the last time it ran. The report below describes what went wrong.
+crash-project-saved = The project was saved to { $path }.
+crash-project-lost = The project could not be saved.
+crash-restore = Restore Project
+crash-copy = Copy Report
+bookmarks-title = Bookmarks
+bookmarks-none = No bookmarks. Select bytes in the hex view and click Bookmark.
+bookmarks-packet = Packet of { $bytes } bytes
+bookmarks-shown = shown in the hex view
+bookmarks-jump = Show in the hex view
+bookmarks-remove = Remove the bookmark
+bookmark-new-title = New Bookmark
+bookmark-name = Name
+bookmark-comment = Comment
+bookmark-bit-offset = Bit offset
+bookmark-bit-length = Bits
+bookmark-add = Add
+coverage-title = Coverage
+coverage-capture = Captured packets
+coverage-presets = Saved packets
+coverage-only-missing = Only never exercised
+coverage-select-protocol = Select a protocol to see which of its subprotocols and enum values the packets exercise
+coverage-analyze = Analyze '{ $protocol }'
+coverage-summary = '{ $protocol }': { $protocols } of { $protocols_total } protocols and { $variants } of { $variants_total } enum values exercised
+coverage-ignored = { $count } packets of other protocols left out
+coverage-protocols = Protocols
+coverage-variants = Enum values
+integrity-title = Project Integrity
+integrity-ok = All references between protocols resolve
+integrity-repair = Repair
+integrity-repair-all = Repair All
+
+# Export
+export-dialog-title = Export { $title }
+export-dialog-save = Save
+export-dialog-copy = Copy
+export-dialog-write-failed = Failed to write '{ $path }': { $error }
+
+# Code generation
+codegen-title = Generate Code
+codegen-target = Target
+codegen-package = Package
+codegen-encode-methods = Encode methods
+codegen-encode-functions = Encode functions
+codegen-proptest = proptest Arbitrary impls
+codegen-proptest-hint = Strategies making values the field rules allow, for property tests
+codegen-bigint = bigint for fields over 32 bits
+codegen-generate = Generate
+codegen-export-title = { $language } Code
+
+# Field editor
+field-editor-id-exists = Field with ID '{ $field }' already exists in '{ $protocol }'
+field-editor-script-error = Script has a syntax error
+field-editor-invalid-numbers = Some numeric inputs are not valid numbers
+field-editor-edit-title = Edit Field '{ $field }'
+field-editor-add-title = Add Field
+field-editor-id = ID
+field-editor-name = Name
+field-editor-use-id = Use ID '{ $id }'
+field-editor-use-id-hint = Derive the ID from the name
+field-editor-type = Type
+field-editor-length = Length
+field-editor-byte-order = Byte Order
+field-editor-protocol-default = Protocol Default
+field-editor-out-of-spec = Out-of-spec Values
+field-editor-out-of-spec-hint = An error stops decoding and encoding; a warning lets the value through and reports it, e.g. for negative testing
+field-editor-negative-values = Negative Values
+field-editor-display = Display
+field-editor-display-default = Default
+field-editor-color = Color
+field-editor-description = Description
+field-editor-subfields = Sub-fields ({ $count })
+field-editor-mapping = Value Mapping
+field-editor-variable = Variable
+field-editor-bits-unit = bits
+field-editor-value = Value
+field-editor-min = Min
+field-editor-max = Max
+field-editor-signed = Signed
+field-editor-script = Script
+field-editor-input-hint = Value is provided when building a packet
+field-editor-duplicate-value = Duplicate value
+field-editor-remove-variant = Remove variant
+field-editor-add-variant = Add variant
+field-editor-import-clipboard = Import from clipboard
+field-editor-import-hint = One variant per line: value, name, description
+field-editor-import-variants = Import { $count } variants
+field-editor-lowest-bit = Lowest Bit
+field-editor-bits = Bits
+field-editor-remove-subfield = Remove sub-field
+field-editor-subfield-hint = Bits are counted from the least significant bit of the value
+field-editor-add-subfield = Add sub-field
+field-editor-mapping-none = None
+field-editor-mapping-table = Table
+field-editor-mapping-curve = Curve
+field-editor-mapping-hint = Translate raw values for display, e.g. ADC counts to a temperature
+field-editor-raw = Raw
+field-editor-shown-as = Shown As
+field-editor-remove-entry = Remove entry
+field-editor-add-entry = Add entry
+field-editor-unit = Unit
+field-editor-remove-point = Remove point
+field-editor-curve-hint = Values between points are interpolated linearly
+field-editor-add-point = Add point
+
+# Values
+endianness-big = Big
+endianness-little = Little
+severity-error = Error
+severity-warning = Warning
+field-type-fixed = Fixed
+field-type-enum = Enum
+field-type-range = Range
+field-type-expr = Expr
+field-type-derived = Derived
+field-type-input = Input
+display-decimal = Decimal
+display-hex = Hex
+display-binary = Binary
+display-octal = Octal
+display-ascii = ASCII
+sign-twos-complement = Two's Complement
+sign-magnitude = Sign-Magnitude
+widgets-custom = Custom
+transport = Transport
+transport-udp = UDP
+transport-serial = Serial
+transport-tcp = TCP
+transport-socketcan = SocketCAN
+transport-listen-on = Listen on
+transport-remote-hint = sender of the request
+transport-port = Port
+transport-baud-rate = Baud rate
+transport-connect-to = Connect to
+transport-interface = Interface
+
+# Protocol designer
+designer-select-protocol = Select a protocol to edit its fields
+designer-add-field = Add Field
+designer-copy = Copy
+designer-copy-hint = Copy the selected fields (Ctrl+C)
+designer-paste = Paste
+designer-paste-hint = Add the { $count } copied fields to this protocol (Ctrl+V)
+designer-id = ID
+designer-type = Type
+designer-length = Length
+designer-bit-offset = Bit Offset
+designer-byte-offset = Byte Offset
+designer-from = From
+designer-row-hint = Double-click or press Enter to edit, Ctrl-click or Shift-click to select several
+designer-row-name = { $field }, { $kind }, { $length }
+designer-row-name-offset = { $field }, { $kind }, { $length }, bit offset { $offset }
+designer-edit = Edit
+designer-insert-above = Insert Above
+designer-insert-below = Insert Below
+designer-constraints = Subprotocol Constraints ({ $count })
+designer-validators = Packet Validators ({ $count })
+designer-notes = Notes
+designer-notes-hint = Observations, e.g. how a device reacts to packets of this protocol
+designer-bits = { $bits } bits
+designer-variable = variable
+designer-variable-length = variable length
+designer-bit-of-byte = Bit { $bit } of byte { $byte }
+designer-inherited-fields = Inherited Fields ({ $count })
+designer-inherited-hint = Inherited from '{ $protocol }'; double-click to edit it there
+designer-total-length = Total length
+designer-length-fixed = { $bits } bits ({ $bytes })
+designer-length-variable = variable, at least { $bits } bits ({ $bytes })
+designer-bytes = { $bytes } bytes
+designer-budget = Budget
+designer-budget-hint = Most bits a packet of this protocol and its subprotocols may take
+designer-frame = Frame
+designer-frame-hint = Exact bits of a packet of this protocol and its subprotocols. The rest of the frame after the last field is padded with zero bits.
+designer-frame-padding = { $padding } bits of padding fill the { $frame } bit frame
+designer-frame-padding-inherited = { $padding } bits of padding fill the { $frame } bit frame of '{ $protocol }'
+designer-frame-exceeded = ⚠ Fields exceed the { $frame } bit frame by { $bits } bits
+designer-frame-exceeded-inherited = ⚠ Fields exceed the { $frame } bit frame of '{ $protocol }' by { $bits } bits
+designer-inherited = { $field } (inherited)
+designer-none = None
+designer-length-field = Length field
+designer-length-field-hint = Field giving the packet length in bytes, which finds where messages end in a TCP or serial byte stream
+designer-plus = plus
+designer-bytes-unit = bytes
+designer-bits-unit = bits
+designer-adjustment-hint = Added to the field value to get the packet length, e.g. the size of the header when the field counts the payload only
+designer-sequence-field = Sequence number
+designer-sequence-field-hint = Field counting up from packet to packet, which the sequence analysis of a capture checks for dropped and repeated packets
+designer-selected = { $count } selected
+designer-delete = Delete
+designer-move-up = Move up
+designer-move-down = Move down
+designer-set-length = Set Length
+designer-set-length-hint = Give the selected wire fields this fixed length
+designer-byte-order = Byte Order
+designer-protocol-default = Protocol Default
+designer-zoom-out = Zoom out
+designer-zoom-reset = Reset the zoom; Ctrl+scroll over the diagram zooms too
+designer-zoom-in = Zoom in
+designer-jump = Jump to field
+designer-go-to = Go to '{ $field }'
+designer-no-such-field = no such field
+designer-applies-when = Applies when
+designer-and = and
+designer-go-to-field-in = Go to the field in '{ $protocol }'
+designer-no-field = No field of '{ $protocol }' has this ID
+designer-constrained-by = Constrained by subprotocols
+designer-constrained-hint = { $count } subprotocols select on this field
+designer-layout-hint = { $field }: { $length } at bit { $bit }
+designer-minimap-hint = Click or drag to scroll the diagram there
+designer-subprotocol = Subprotocol
+designer-go-to-field = Go to the field
+designer-open-subprotocol = Open subprotocol
+designer-any = any
+designer-not-constrained = Not constrained
+designer-overlap = ⚠ '{ $a }' and '{ $b }' can both match the same packet
+designer-gap = ⚠ No subprotocol handles { $field } = { $values }
+designer-no-conflicts = Every packet matches at most one subprotocol
+designer-validators-hint = Scripts run on every decoded packet. Return true or () if the packet is valid, otherwise a message or an array of messages.
+designer-remove-validator = Remove validator
+designer-add-validator = Add Validator
+
+# Packet builder
+builder-invalid-value = Invalid value for field '{ $field }': { $error }
+builder-select-protocol = Select a protocol to build a packet of it
+builder-select-protocol-first = Select a protocol first
+builder-id = ID
+builder-type = Type
+builder-value = Value
+builder-override = Override
+builder-overridden-hint = Validation is overridden for this field
+builder-values-json = Values JSON
+builder-load = Load
+builder-load-hint = Set the fields in a JSON object of field ID to value
+builder-export = Export
+builder-export-title = Field Values
+builder-read-failed = Failed to read '{ $path }': { $error }
+builder-paste-values = Paste Values
+builder-paste-hint = A column of values, one per field, or field=value lines
+builder-current = Current
+builder-pasted = Pasted
+builder-fix-invalid = Fix the invalid values first
+builder-discard = Discard
+builder-override-hint = Encode the value even if it breaks the rules of the field
+builder-override-name = Override validation of { $field }
+builder-overridden-banner = ⚠ Validation overridden for { $fields }: this packet may break the protocol
+builder-allow-sending = Allow sending this packet anyway
+builder-clear-overrides = Clear Overrides
+builder-send-section = Send
+builder-send-to = Send to
+builder-disconnect = Disconnect
+builder-connect = Connect
+builder-send = Send
+builder-send-disabled = Connect and enter valid values first
+builder-send-unconfirmed = Allow sending the packet with overridden fields first
+builder-computed = computed on encode
+builder-variant-name = { $field } variant
+builder-presets = Presets
+builder-preset-name = Name
+builder-folder = Folder
+builder-folder-hint = Separate nested folders with '/'
+builder-save = Save
+builder-save-hint = Save the current values, replacing a preset of the same name
+builder-notes = Notes
+builder-notes-hint = e.g. the device rejects this unless bit 3 is set
+builder-preset-hint = Packet of '{ $protocol }'
+builder-duplicate = Duplicate
+builder-move-to-folder = Move to Folder
+builder-delete = Delete
+builder-move = Move
+
+# Capture
+capture-title = Capture
+capture-decode-as = Decode as
+capture-select = Select...
+capture-subprotocols = Subprotocols
+capture-subprotocols-hint = Decode each packet further as the subprotocol whose parent constraints it meets
+capture-scrub = Scrub...
+capture-scrub-hint = Replace identifying field values to share the capture
+capture-suggest-fields = Suggest Fields...
+capture-suggest-fields-hint = Guess constant bytes, counters, lengths and a checksum from the packets, all of one type, as a new protocol to refine
+capture-clear = Clear
+capture-decoding = Decoding { $done } / { $total }
+capture-view-packets = Packets
+capture-view-conversations = Conversations
+capture-view-conversations-hint = Requests paired with their responses, with round-trip times
+capture-view-sequence = Sequence
+capture-view-sequence-hint = Dropped, repeated and reordered packets by their sequence numbers
+capture-view-timing = Timing
+capture-view-timing-hint = Time between the packets of each protocol, against its rate
+capture-tcp-needs-protocol = Choose the protocol to decode as; its length splits TCP streams into messages
+capture-live = Live
+capture-transport = Transport
+capture-interface = Interface
+capture-interface-hint = Capture every frame on a network interface with libpcap
+capture-file = File
+capture-binary-log = Binary log
+capture-binary-log-hint = Raw records back to back, as written by a data logger
+capture-load = Load
+capture-detect-framing = Detect Framing
+capture-detect-framing-hint = Suggest how the records of the log are delimited, from how often its bytes occur and how alike the records of each framing start
+capture-read-failed = Failed to read '{ $path }': { $error }
+capture-payload-frame = Whole frames
+capture-payload-frame-hint = Load each frame with its link, IP and transport headers
+capture-payload-udp = UDP payloads
+capture-payload-udp-hint = Strip the Ethernet, IP and UDP headers; other frames are skipped
+capture-payload-tcp = TCP messages
+capture-payload-tcp-hint = Put TCP segments back in order and split the streams into messages by the length of the protocol decoded as
+capture-suggested-framing = Suggested framing
+capture-close = Close
+capture-frequent-bytes = Most frequent bytes of the first { $count } bytes: { $bytes }
+capture-no-framing = No framing splits the log into records that look alike
+capture-framing-records = { $records } records of { $shortest }-{ $longest } bytes
+capture-framing-confidence-hint = How much more alike the records start than by chance
+capture-use = Use
+capture-framing-fixed = Fixed size of { $size } bytes
+capture-framing-length-one = Length of 1 byte at byte { $offset }, { $order }
+capture-framing-length = Length of { $width } bytes at byte { $offset }, { $order }
+capture-framing-delimiter = Ends with { $delimiter }
+capture-big-endian = big-endian
+capture-little-endian = little-endian
+capture-split = Split into messages
+capture-split-hint = Find where messages end by the length of the protocol decoded as, for TCP and serial links where a read may hold part of a message or several
+capture-start = Start
+capture-stop = Stop
+capture-refresh = Refresh
+capture-filter = Filter
+capture-filter-example = e.g. udp port 5000
+capture-filter-hint = BPF filter expression, as in tcpdump and Wireshark capture filters
+capture-framing = Framing
+capture-framing-kind-fixed = Fixed size
+capture-framing-kind-length-prefix = Length prefix
+capture-framing-kind-delimiter = Delimiter
+capture-record-size = Record size
+capture-bytes-unit = bytes
+capture-length-at = Length at
+capture-byte-prefix = byte
+capture-length-size = Length size
+capture-adjustment = Adjustment
+capture-adjustment-hint = Added to the length to get the number of bytes after it
+capture-delimiter = Delimiter
+capture-delimiter-example = e.g. 0d 0a
+capture-timestamp = Timestamp
+capture-timestamp-before = before each record
+capture-timestamp-hint = A count of time units since the Unix epoch, left out of the packet
+capture-column-number = No.
+capture-column-time = Time
+capture-column-length = Length
+capture-column-decoded = Decoded
+capture-sort-hint = Sort by this column
+capture-columns = Columns ({ $count })
+capture-columns-hint = Fields of '{ $protocol }' shown as columns; the columns are saved in the project
+capture-show-summary = Show Summary
+capture-show-summary-hint = List every field of each packet in one column instead
+capture-unsort = Unsort
+capture-pair-by = Pair by
+capture-pair-fields = Fields
+capture-pair-script = Script
+capture-choose = Choose...
+capture-decode-first = Decode the packets as a protocol first
+capture-suggest = Suggest
+capture-suggest-hint = Fields named like a sequence number or transaction ID
+capture-pair = Pair
+capture-wait-decoding = Wait for the packets to be decoded
+capture-pair-prompt = Choose how requests and responses are matched and press Pair
+capture-exchanges = { $count } exchanges, { $unanswered } unanswered
+capture-exchanges-round-trip = { $count } exchanges, { $unanswered } unanswered, round trip { $min } / { $mean } / { $max } ms (min / mean / max)
+capture-column-request = Req.
+capture-column-response = Resp.
+capture-column-rtt = RTT (ms)
+capture-column-retries = Retries
+capture-column-key = Key
+capture-check = Check
+capture-check-hint = Walk the decoded packets by the sequence number field chosen for their protocol in the designer
+capture-check-prompt = Press Check to look for gaps in the sequence numbers of the packets
+capture-no-sequence = None of the decoded packets has a sequence number; choose the field for their protocol in the designer
+capture-sequence-summary = { $field }: { $packets } packets, { $missing } missing in { $gaps } gaps, { $duplicates } duplicates, { $wraps } wrap-arounds, { $back } steps back
+capture-column-after = After (ms)
+capture-column-event = Event
+capture-measure = Measure
+capture-measure-hint = Time the decoded packets, separately for each subprotocol
+capture-tolerance = Tolerance
+capture-tolerance-hint = How far off its expected period the time between packets may be
+capture-measure-prompt = Press Measure to time the packets of each protocol
+capture-nothing-to-time = No protocol has two or more decoded packets to time
+capture-timing-summary = { $packets } packets, { $min } / { $mean } / { $max } ms (min / mean / max), jitter { $jitter } ms, { $rate } per second
+capture-expected-every = Expected every
+capture-off-period = { $outside } of { $total } intervals off by more than { $tolerance } ms
+capture-histogram-hint = { $from } to { $to } ms: { $count } intervals
+
+# Hex view
+hex-title = Hex View
+detached-dock = Dock
+detached-dock-hint = Move back into the main window
+detached-pop-out = Pop Out
+detached-pop-out-hint = Open in a separate window
+hex-preview-swapped = the other byte order
+hex-preview-reversed = the bits of each byte reversed
+hex-preview-and = and
+hex-preview = Preview: decoded with { $changes }. The protocol is unchanged.
+hex-packet-tab = Packet { $number }
+hex-tab-hint = { $bytes } bytes; right-click for more
+hex-tab-title = Title
+hex-pin = Pin as Reference
+hex-pin-hint = Keep a copy of this packet to compare the packets viewed afterwards with, tinting the bytes they differ in
+hex-duplicate = Duplicate
+hex-close = Close
+hex-new-tab-hint = Open an empty packet in a new tab
+hex-entropy-title = Byte entropy of { $count } captured packets
+hex-entropy-legend = low: constant, high: counters and random data
+hex-entropy-refresh = Recompute from the capture
+hex-reference-equal = The packet equals the reference
+hex-reference-shorter = { $changed } bytes differ, and the packet is { $shorter } bytes shorter
+hex-reference-differ = { $changed } bytes differ
+hex-reference = Reference: { $title } ({ $bytes } bytes)
+hex-empty-packet = Empty packet
+hex-tint-changes = Tint changes
+hex-unpin = Unpin
+hex-decode = Decode
+hex-decode-hint = Decode the packet as the selected protocol
+hex-preview-order = Preview Order
+hex-swap-bytes = Swap byte order
+hex-swap-bytes-hint = Read multi-byte fields as if the protocol had the other endianness
+hex-reverse-bits = Reverse bit order
+hex-reverse-bits-hint = Read the bits of each byte least significant first
+hex-copy-dump = Copy Hex Dump
+hex-export-dump = Export Hex Dump
+hex-export-dump-title = Hex Dump
+hex-entropy = Entropy
+hex-entropy-hint = Show how much the bytes at each offset vary across the captured packets, which sets apart constant headers, counters and encrypted or compressed data
+hex-bookmark = Bookmark
+hex-bookmark-hint = Name the selected bytes, with a comment
+hex-stop-preview = Stop Preview
+hex-not-decoded = { $bytes } bytes from offset { $offset } were not decoded
+hex-import = Import hex dump
+hex-no-packet = No packet loaded
+hex-load-bytes = Load { $bytes } bytes
+hex-load-first = Load first of { $count } packets
+hex-strip-entropy = Byte { $byte }: { $entropy } bits of entropy, { $distinct } distinct values
+hex-strip-most-common = Most common { $value } in { $count } of { $packets } packets
+hex-strip-mean = Mean { $mean }, standard deviation { $deviation }
+
+# Inspector
+inspector-title = Inspector
+inspector-no-packet = No packet decoded
+inspector-fields = { $protocol } ({ $count } fields)
+inspector-derived-hint = Derived field, not serialized into the packet
+inspector-raw-value = Raw value { $value }
+inspector-subfield-hint = { $range } of { $field }
+inspector-failure-bits = { $bits } bits at bit offset { $offset }
+inspector-dispatch = Dispatch
+inspector-subprotocols-of = Subprotocols of '{ $protocol }'
+inspector-no-constraints = no constraints, applies to any packet
+inspector-check-actual = { $field } is { $actual }, needs { $expected }
+inspector-check-missing = { $field } is not in the packet, needs { $expected }
+inspector-chosen-mismatch = Decoded as this, though the packet does not match it
+inspector-also-matches = Also matches the packet
+inspector-show-as = Show as
+inspector-field-default = Field Default
+inspector-format-with = Format with
+inspector-default = Default
+inspector-reported-by = Reported by validator '{ $validator }' of '{ $protocol }'
+
+# Bit calculator
+bit-calculator-title = Bit Calculator
+bit-calculator-value = Value
+bit-calculator-width = Width
+bit-calculator-bits-unit = bits
+bit-calculator-truncated = The value does not fit in { $bits } bits and is truncated
+bit-calculator-use-result = Use Result
+bit-calculator-use-result-hint = Continue calculating with the result
+bit-calculator-unsigned = Unsigned
+bit-calculator-hex = Hex
+bit-calculator-binary = Binary
+bit-calculator-twos-complement = Two's complement
+bit-calculator-sign-magnitude = Sign-magnitude
+bit-calculator-ascii = ASCII
+bit-calculator-copy = Copy
+bit-calculator-bit = bit { $bit }
+
+# Checksum calculator
+checksum-calculator-title = Checksum Calculator
+checksum-calculator-hex = Hex
+checksum-calculator-text = Text
+checksum-calculator-use-packet = Use Packet
+checksum-calculator-use-packet-hint = Check the packet in the hex view, or the bytes selected in it
+checksum-calculator-custom = Custom CRC
+checksum-calculator-customize = Customize
+checksum-calculator-customize-hint = Start a custom CRC from these parameters
+checksum-calculator-width = Width
+checksum-calculator-bits-unit = bits
+checksum-calculator-polynomial = Polynomial
+checksum-calculator-init = Initial value
+checksum-calculator-xorout = Final XOR
+checksum-calculator-reflect = Reflect
+checksum-calculator-reflect-input = input
+checksum-calculator-reflect-output = output
+checksum-calculator-bytes = { $bytes } bytes
+checksum-calculator-decimal = Decimal
+checksum-calculator-copy = Copy
+checksum-calculator-expression = In a field expression:
+
+# Bit accounting
+bit-accounting-title = Bit Accounting
+bit-accounting-select-protocol = Select a fixed-length protocol to account for its bits
+bit-accounting-export = Export CSV
+bit-accounting-export-title = Bit Accounting
+bit-accounting-frame-length = Frame length { $bits } bits, declared by '{ $protocol }'
+bit-accounting-bits = Bits
+bit-accounting-length = Length
+bit-accounting-use = Use
+bit-accounting-field = Field
+bit-accounting-padding = frame padding
+bit-accounting-used = used
+bit-accounting-reserved = reserved
+bit-accounting-unassigned = unassigned
+bit-accounting-part = { $bits } { $kind } ({ $percent }%)
+bit-accounting-summary = { $bits } bits: { $used }, { $reserved }, { $unassigned }
+
+# Suggested fields
+skeleton-title = Suggested Fields
+skeleton-guessed = Guessed from { $count } captured packets, assumed to be of one type
+skeleton-bytes = Bytes
+skeleton-length = Length
+skeleton-looks-like = Looks like
+skeleton-length-little = { $bytes } bytes, little-endian
+skeleton-length-bytes = { $bytes } bytes
+skeleton-variable = variable
+skeleton-protocol-id = Protocol ID
+skeleton-create = Create Protocol
+skeleton-create-hint = A new protocol with these fields, to refine in the designer
+
+# External editor
+external-editor-open = ✏ External Editor
+external-editor-open-hint = Edit the script in the editor named by $VISUAL or $EDITOR (one that opens its own window, e.g. "code --wait"), or else the default application for .rhai files. The script is reloaded here every time the file is saved.
+external-editor-stop = Stop
+external-editor-stop-hint = Stop reloading the script from the external editor
+external-editor-editing = Editing externally
+external-editor-create-failed = Failed to create '{ $path }': { $error }
+external-editor-write-failed = Failed to write '{ $path }': { $error }
+external-editor-read-failed = Failed to read '{ $path }': { $error }
+external-editor-start-failed = Failed to start the external editor: { $error }
+
+# Where used
+where-used-title = Where Used
+where-used-select-field = Select a field to see where it is used
+where-used-references = References to '{ $protocol }.{ $field }'
+where-used-none = No references
+where-used-constraint = Constraint in '{ $protocol }': == { $value }
+where-used-expression = Expression of '{ $protocol }.{ $field }'
+where-used-validator = Validator '{ $validator }' of '{ $protocol }'
+where-used-length-field = Length field of '{ $protocol }'
+where-used-sequence-field = Sequence number field of '{ $protocol }'
+
+# Compare protocols
+compare-title = Compare Protocols
+compare-select-protocols = Select two protocols to compare
+compare-copy-report = Copy report
+compare-none = (none)
+
+# Revision history
+history-title = Revision History
+history-select-protocol = Select a protocol to see its history
+history-message = Commit message
+history-commit = Commit
+history-none = No revisions committed yet
+history-restore = Restore
+history-diff = Diff against current
+
+# Recently deleted
+trash-title = Recently Deleted
+trash-none = No protocols deleted this session
+trash-discard = Delete for good
+trash-restore = Restore
+trash-with = with { $subprotocols } subprotocols and { $presets } saved packets
+trash-empty-button = Empty Trash
+
+# Delete protocol
+delete-title = Delete Protocol
+delete-confirm = Delete protocol '{ $protocol }'?
+delete-restore-hint = Committed revisions stay in the history. Restore it from { $menu } > { $item }.
+delete-button = Delete
+delete-no-subprotocols = It has no subprotocols.
+delete-subprotocols = These { $count } subprotocols are deleted with it:
+delete-presets = These { $count } saved packets are deleted too:
+
+# Device simulator
+simulator-title = Device Simulator
+simulator-running = Running
+simulator-select-protocols = Select the request and response protocols
+simulator-reply-to = Reply to
+simulator-request = Request
+simulator-response = Response
+simulator-split = Split into messages
+simulator-split-hint = Find where requests end by the length of the request protocol, for TCP and serial links where a read may hold part of a request or several
+simulator-select = Select...
+
+# HTTP API server
+api-server-title = HTTP API Server
+api-server-running = Running on http://{ $address }
+api-server-endpoints = Endpoints
+api-server-list = protocol IDs
+api-server-decode = hex body → field values
+api-server-encode = field values → hex
+api-server-validate = hex body → validation issues
+
+# Replay capture
+replay-title = Replay Capture
+replay-progress = Sent { $sent } of { $total }
+replay-start-hint = Send the packets on the capture page with their recorded gaps
+replay-select-protocol = Select the protocol to decode as on the capture page to rewrite fields
+replay-packets = { $count } packets on the capture page
+replay-send-to = Send to
+replay-speed = Speed
+replay-rewrite = Rewrite fields
+replay-rewrite-hint = Decode each packet as the capture protocol and change fields with a script
+
+# Transmit scheduler
+scheduler-title = Transmit Scheduler
+scheduler-connection = Sending over the packet builder's { $transport } connection
+scheduler-not-connected = Connect in the { $section } section of the packet builder first
+scheduler-running = Running, { $sent } sent
+scheduler-no-presets = Save packets as presets in the packet builder to send them periodically
+scheduler-every = every
+scheduler-none-checked = Check the presets to send
+
+# Scrub capture
+scrub-title = Scrub Capture
+scrub-no-fields = Decode the captured packets as a protocol to choose fields to scrub
+scrub-keep = Keep
+scrub-fixed = Fixed
+scrub-randomize = Randomize
+scrub-apply = Scrub Packets
+scrub-apply-hint = Rewrite the packets on the capture page; packets that do not decode are dropped, as their fields cannot be scrubbed
+scrub-save = Save pcap
+scrub-save-hint = Packets that are not whole frames are saved with the private link type USER0
+scrub-closed = Scrub dialog is closed
+scrub-select-protocol = Choose the protocol to decode the packets as
+scrub-invalid-value = Invalid value for field '{ $field }': { $error }
+scrub-no-choice = Choose the fields to scrub
+scrub-done = Scrubbed { $scrubbed } packets, dropped { $dropped } that did not decode
+scrub-no-path = Enter the path to save to
+scrub-write-failed = Failed to write '{ $path }': { $error }
+
+# Expression editor
+expr-preview = Preview
+expr-fix-syntax = Fix the syntax error to preview the result
+expr-sample-hint = Integer, float, true/false, "string" or [hex bytes]
+expr-invalid-samples = Enter valid sample values to see the result
+expr-result = Result:
+
+# Import
+import-dbc = CAN Database (DBC)
+import-c-header = C Header (structs)
+import-title = Import { $format }
+import-button = Import
+import-read-failed = Failed to read '{ $path }': { $error }
+import-done = Imported { $count } protocols
+
+# Console
+console-title = Console
+console-intro = Scripts run against the project and may modify it.
+console-clear = Clear
+console-reset = Reset
+console-reset-hint = Forget variables defined by earlier commands
+console-api = API
+console-run = Run
+console-api-protocols = IDs of all protocols
+console-api-protocol = map with id, name, parent, endianness, metadata and fields
+console-api-set-metadata = set a metadata entry
+console-api-add-field = append an input field
+console-api-add-expression-field = append an expression field
+console-api-remove-field = remove a field
+console-api-rename-field = rename a field and update references
+console-api-packet = field values of the current packet, or ()
+console-api-decode = decode bytes into a map of field values
+console-api-encode = encode a map of field values into bytes
+
+# Script library
+library-title = Script Library
+library-intro = Functions and constants defined here can be used by every field expression.
+library-intro-hint = Inside functions, refer to library constants as global::NAME
+library-save = Save
+library-loaded = Loaded: { $functions }
+
+# Appearance
+appearance-title = Appearance
+appearance-language = Language
+appearance-theme = Theme
+appearance-accent = Accent
+appearance-field-colors = Field colors
+appearance-custom = Custom
+appearance-custom-hint = Start from the current palette
+appearance-values = Values
+appearance-remove = Remove
+appearance-remove-hint = Right-click to remove
+appearance-add-color = Add a color
+
+# Errors
+kind-protocol = Protocol
+kind-field = Field
+error-protocol-not-found = Protocol with ID '{ $protocol }' does not exist
+error-protocol-exists = Protocol with ID '{ $protocol }' already exists
+error-parent-not-found = Parent protocol with ID '{ $protocol }' does not exist
+error-field-not-found = Field with ID '{ $field }' not found in protocol '{ $protocol }'
+error-field-exists = Field with ID '{ $field }' already exists in protocol '{ $protocol }'
+error-id-empty = { $kind } ID '' cannot be empty
+error-id-char = { $kind } ID '{ $id }' may only contain letters, digits and '_', not '{ $char }'
+error-id-digit = { $kind } ID '{ $id }' cannot start with a digit
+error-field-after-variable = Field '{ $field }' cannot follow variable length field '{ $variable }' in protocol '{ $protocol }'
+error-field-id-changed = The ID of field '{ $field }' cannot be changed by editing it; rename it instead
+error-protocol-id-changed = The ID of protocol '{ $protocol }' cannot be changed by editing it; rename it instead
+error-parent-changed = The parent of protocol '{ $protocol }' cannot be changed after it is created
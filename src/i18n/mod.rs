@@ -0,0 +1,238 @@
+//! Translations of the user interface and error messages, as Fluent resources per language.
+//! English is the base: a message missing from another language is shown in English, and one
+//! missing from English as its ID.
+
+use crate::error::{BitLoomError, IdentifierProblem};
+use fluent::{FluentArgs, FluentBundle, FluentResource};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use unic_langid::LanguageIdentifier;
+
+pub use fluent::FluentValue;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Name of the language in itself, for choosing it
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("en.ftl"),
+            Language::German => include_str!("de.ftl"),
+        }
+    }
+
+    fn bundle(self) -> FluentBundle<FluentResource> {
+        let id: LanguageIdentifier = self.code().parse().unwrap_or_default();
+        let mut bundle = FluentBundle::new(vec![id]);
+        // the Unicode isolation marks around arguments show up as boxes in some fonts
+        bundle.set_use_isolating(false);
+        let resource = FluentResource::try_new(self.source().to_string())
+            .unwrap_or_else(|(resource, _)| resource);
+        let _ = bundle.add_resource(resource);
+        bundle
+    }
+}
+
+thread_local! {
+    static LANGUAGE: Cell<Language> = const { Cell::new(Language::English) };
+    static BUNDLES: RefCell<HashMap<Language, FluentBundle<FluentResource>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Switch the language messages are translated to on this thread, e.g. the UI thread
+pub fn set_language(language: Language) {
+    LANGUAGE.with(|l| l.set(language));
+}
+
+pub fn language() -> Language {
+    LANGUAGE.with(Cell::get)
+}
+
+/// The message with ID `id` in the current language, with its `{ $name }` arguments filled in
+pub fn translate(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let args = (!args.is_empty()).then(|| {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        fluent_args
+    });
+    [language(), Language::English]
+        .into_iter()
+        .find_map(|language| format_in(language, id, args.as_ref()))
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn format_in(language: Language, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+    BUNDLES.with(|bundles| {
+        let mut bundles = bundles.borrow_mut();
+        let bundle = bundles.entry(language).or_insert_with(|| language.bundle());
+        let pattern = bundle.get_message(id)?.value()?;
+        let mut errors = Vec::new();
+        Some(
+            bundle
+                .format_pattern(pattern, args, &mut errors)
+                .into_owned(),
+        )
+    })
+}
+
+/// Translate a message, e.g. `tr!("menu-file")` or `tr!("status-capturing", count = 3)`
+#[macro_export]
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::translate($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $id,
+            &[$((stringify!($name), $crate::i18n::FluentValue::from($value))),+],
+        )
+    };
+}
+
+/// Something to show the user, e.g. an error, in the current language
+pub trait Localize {
+    fn localize(&self) -> String;
+}
+
+/// Messages made by code that is not translated yet are shown as they are
+impl Localize for String {
+    fn localize(&self) -> String {
+        self.clone()
+    }
+}
+
+impl Localize for BitLoomError {
+    fn localize(&self) -> String {
+        error_message(self)
+    }
+}
+
+fn error_message(error: &BitLoomError) -> String {
+    match error {
+        BitLoomError::ProtocolNotFound { protocol_id } => {
+            tr!("error-protocol-not-found", protocol = protocol_id.as_str())
+        }
+        BitLoomError::ProtocolExists { protocol_id } => {
+            tr!("error-protocol-exists", protocol = protocol_id.as_str())
+        }
+        BitLoomError::ParentNotFound { parent_id } => {
+            tr!("error-parent-not-found", protocol = parent_id.as_str())
+        }
+        BitLoomError::FieldNotFound {
+            protocol_id,
+            field_id,
+        } => tr!(
+            "error-field-not-found",
+            protocol = protocol_id.as_str(),
+            field = field_id.as_str()
+        ),
+        BitLoomError::FieldExists {
+            protocol_id,
+            field_id,
+        } => tr!(
+            "error-field-exists",
+            protocol = protocol_id.as_str(),
+            field = field_id.as_str()
+        ),
+        BitLoomError::InvalidIdentifier { kind, id, problem } => {
+            let id = id.as_str();
+            let kind = match *kind {
+                "Protocol" => tr!("kind-protocol"),
+                _ => tr!("kind-field"),
+            };
+            match problem {
+                IdentifierProblem::Empty => tr!("error-id-empty", kind = kind),
+                IdentifierProblem::InvalidChar(c) => {
+                    tr!("error-id-char", kind = kind, id = id, char = c.to_string())
+                }
+                IdentifierProblem::LeadingDigit => tr!("error-id-digit", kind = kind, id = id),
+            }
+        }
+        BitLoomError::FieldAfterVariable {
+            protocol_id,
+            field_id,
+            variable_id,
+        } => tr!(
+            "error-field-after-variable",
+            protocol = protocol_id.as_str(),
+            field = field_id.as_str(),
+            variable = variable_id.as_str()
+        ),
+        BitLoomError::FieldIdChanged { field_id } => {
+            tr!("error-field-id-changed", field = field_id.as_str())
+        }
+        BitLoomError::ProtocolIdChanged { protocol_id } => {
+            tr!("error-protocol-id-changed", protocol = protocol_id.as_str())
+        }
+        BitLoomError::ParentChanged { protocol_id } => {
+            tr!("error-parent-changed", protocol = protocol_id.as_str())
+        }
+        BitLoomError::Other(message) => message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_with_fallback() {
+        set_language(Language::English);
+        assert_eq!(tr!("menu-file"), "File");
+        let error = BitLoomError::FieldNotFound {
+            protocol_id: "frame".to_string(),
+            field_id: "crc".to_string(),
+        };
+        // the English messages match those of the errors themselves
+        assert_eq!(error.localize(), error.to_string());
+
+        set_language(Language::German);
+        assert_eq!(tr!("menu-file"), "Datei");
+        assert_eq!(
+            tr!("status-capturing", count = 3),
+            "Aufzeichnung läuft, 3 Pakete"
+        );
+        assert_eq!(tr!("no-such-message"), "no-such-message");
+        set_language(Language::English);
+
+        // every message has an English text
+        let english = Language::English.bundle();
+        for language in Language::ALL {
+            let ids = language
+                .source()
+                .lines()
+                .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+                .filter_map(|line| line.split_once(" ="));
+            for (id, _) in ids {
+                assert!(english.has_message(id), "{}", id);
+            }
+        }
+        for (_, id) in crate::script::console::CONSOLE_API {
+            assert!(english.has_message(id), "{}", id);
+        }
+    }
+}
@@ -0,0 +1,1002 @@
+//! Import of simple C headers: every struct becomes a protocol, with its members as fields.
+//!
+//! Layout follows GCC on a little-endian target: members are naturally aligned unless the
+//! struct is `__attribute__((packed))` or inside `#pragma pack(1)`, and bitfields fill their
+//! storage unit from the least significant bit. Enums become enum fields, nested structs are
+//! inlined with the member name as prefix, and a trailing `// comment` becomes the description.
+
+use crate::models::field::{EnumVariant, FieldLength, FieldRule, FieldType, parse_int};
+use crate::models::protocol::{Endianness, Protocol};
+use std::collections::HashMap;
+
+/// Protocols read from a header, with what could not be imported
+#[derive(Debug)]
+pub struct HeaderImport {
+    pub protocols: Vec<Protocol>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum CType {
+    Int {
+        bits: u32,
+        signed: bool,
+    },
+    Float {
+        bits: u32,
+    },
+    Enum {
+        bits: u32,
+        variants: Vec<EnumVariant>,
+    },
+    /// index into the parsed structs
+    Struct(usize),
+}
+
+#[derive(Debug)]
+struct Member {
+    name: String,
+    ctype: CType,
+    /// `None` for a scalar, `Some(None)` for a flexible array member
+    array: Option<Option<u32>>,
+    bitfield: Option<u32>,
+    description: Option<String>,
+}
+
+#[derive(Debug)]
+struct CStruct {
+    name: String,
+    packed: bool,
+    members: Vec<Member>,
+    /// fields and alignment in bytes once laid out
+    layout: Option<(Vec<FieldRule>, u32)>,
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    line: usize,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// trailing comments by line
+    comments: HashMap<usize, String>,
+    /// whether `#pragma pack(1)` is in effect
+    pragma_packed: bool,
+    constants: HashMap<String, i128>,
+    types: HashMap<String, CType>,
+    structs: Vec<CStruct>,
+    warnings: Vec<String>,
+}
+
+/// Parse a header into one protocol per struct, in the order the structs are defined
+pub fn import_c_header(text: &str) -> Result<HeaderImport, String> {
+    let (tokens, comments) = tokenize(text)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        comments,
+        pragma_packed: false,
+        constants: HashMap::new(),
+        types: HashMap::new(),
+        structs: Vec::new(),
+        warnings: Vec::new(),
+    };
+    parser.parse()?;
+
+    let mut protocols = Vec::new();
+    for i in 0..parser.structs.len() {
+        let (fields, _) = parser.layout(i)?;
+        let mut protocol = Protocol::new(&parser.structs[i].name, None, Endianness::Little, None);
+        for field in fields {
+            protocol
+                .add_field(field)
+                .map_err(|e| format!("Struct '{}': {}", parser.structs[i].name, e))?;
+        }
+        protocols.push(protocol);
+    }
+    Ok(HeaderImport {
+        protocols,
+        warnings: parser.warnings,
+    })
+}
+
+impl Parser {
+    fn peek(&self) -> &str {
+        self.tokens.get(self.pos).map_or("", |t| t.text.as_str())
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos.min(self.tokens.len().saturating_sub(1)))
+            .map_or(0, |t| t.line)
+    }
+
+    fn next(&mut self) -> String {
+        let token = self.peek().to_string();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.peek() == text;
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), String> {
+        if self.eat(text) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}', found '{}'", text, self.peek())))
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Line {}: {}", self.line(), message)
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let token = self.peek();
+        if token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            Ok(self.next())
+        } else {
+            Err(self.error(&format!("expected a name, found '{}'", token)))
+        }
+    }
+
+    fn parse(&mut self) -> Result<(), String> {
+        while self.pos < self.tokens.len() {
+            match self.peek() {
+                "#pack" => {
+                    self.pos += 1;
+                    self.pragma_packed = self.next() == "1";
+                }
+                "#define" => {
+                    self.pos += 1;
+                    let name = self.next();
+                    let value = self.next();
+                    if let Ok(value) = parse_c_int(&value) {
+                        self.constants.insert(name, value);
+                    }
+                }
+                "typedef" => {
+                    self.pos += 1;
+                    self.typedef()?;
+                }
+                "struct" | "union" if self.is_definition() => {
+                    self.struct_definition()?;
+                    self.skip_statement();
+                }
+                "enum" if self.is_definition() => {
+                    self.enum_definition()?;
+                    self.skip_statement();
+                }
+                _ => self.pos += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the `struct`/`enum` keyword at the cursor starts a definition with a body
+    fn is_definition(&self) -> bool {
+        self.tokens[self.pos + 1..]
+            .iter()
+            .map(|t| t.text.as_str())
+            .find(|t| !t.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_'))
+            .is_some_and(|t| t == "{" || t == "(" || t == ":")
+    }
+
+    /// Skip to after the next `;`
+    fn skip_statement(&mut self) {
+        while self.pos < self.tokens.len() && self.next() != ";" {}
+    }
+
+    fn typedef(&mut self) -> Result<(), String> {
+        let ctype = match self.peek() {
+            "struct" | "union" if self.is_definition() => CType::Struct(self.struct_definition()?),
+            "enum" if self.is_definition() => self.enum_definition()?,
+            _ => self.type_specifier()?,
+        };
+        self.attributes();
+        let name = self.identifier()?;
+        self.attributes();
+        self.expect(";")?;
+
+        if let CType::Struct(index) = ctype {
+            // the typedef name is the better protocol ID than a struct tag
+            if self.structs[index].name.is_empty() || self.structs[index].name.starts_with('_') {
+                self.structs[index].name = name.clone();
+            }
+        }
+        self.types.insert(name, ctype);
+        Ok(())
+    }
+
+    /// Skip `__attribute__((...))` and similar, returning whether one of them asks for packing
+    fn attributes(&mut self) -> bool {
+        let mut packed = false;
+        loop {
+            match self.peek() {
+                "__packed" | "PACKED" => {
+                    packed = true;
+                    self.pos += 1;
+                }
+                "__attribute__" | "__attribute" | "__declspec" | "alignas" | "_Alignas" => {
+                    self.pos += 1;
+                    let mut depth = 0;
+                    while self.pos < self.tokens.len() {
+                        match self.next().as_str() {
+                            "(" => depth += 1,
+                            ")" => {
+                                depth -= 1;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            "packed" | "__packed__" => packed = true,
+                            _ => {}
+                        }
+                    }
+                }
+                _ => return packed,
+            }
+        }
+    }
+
+    /// `struct [tag] [attributes] { members } [attributes]`, returning the struct's index
+    fn struct_definition(&mut self) -> Result<usize, String> {
+        let keyword = self.next();
+        if keyword == "union" {
+            return Err(self.error("unions are not supported"));
+        }
+        let mut packed = self.attributes();
+        let tag = if self.peek() == "{" {
+            String::new()
+        } else {
+            self.identifier()?
+        };
+        packed |= self.attributes();
+        self.expect("{")?;
+
+        let mut members = Vec::new();
+        while !self.eat("}") {
+            if self.pos >= self.tokens.len() {
+                return Err(self.error(&format!("struct '{}' is not closed", tag)));
+            }
+            self.members(&mut members)?;
+        }
+        packed |= self.attributes() || self.pragma_packed;
+
+        self.structs.push(CStruct {
+            name: tag.clone(),
+            packed,
+            members,
+            layout: None,
+        });
+        let index = self.structs.len() - 1;
+        if !tag.is_empty() {
+            self.types
+                .insert(format!("struct {}", tag), CType::Struct(index));
+        }
+        Ok(index)
+    }
+
+    /// One member declaration, possibly declaring several names
+    fn members(&mut self, members: &mut Vec<Member>) -> Result<(), String> {
+        let ctype = self.type_specifier()?;
+        loop {
+            if self.peek() == "*" {
+                return Err(self.error("pointer members are not supported"));
+            }
+            let name = self.identifier()?;
+            let array = if self.eat("[") {
+                let length = if self.peek() == "]" {
+                    None
+                } else {
+                    Some(self.size("array length")?)
+                };
+                self.expect("]")?;
+                Some(length)
+            } else {
+                None
+            };
+            let bitfield = if self.eat(":") {
+                Some(self.size("bitfield width")?)
+            } else {
+                None
+            };
+            self.attributes();
+
+            members.push(Member {
+                name,
+                ctype: ctype.clone(),
+                array,
+                bitfield,
+                description: None,
+            });
+            if self.eat(";") {
+                break;
+            }
+            self.expect(",")?;
+        }
+        if let Some(last) = members.last_mut() {
+            last.description = self.comments.get(&self.tokens[self.pos - 1].line).cloned();
+        }
+        Ok(())
+    }
+
+    /// `enum [tag] [: type] { A [= value], ... } [attributes]`
+    fn enum_definition(&mut self) -> Result<CType, String> {
+        self.expect("enum")?;
+        let mut packed = self.attributes();
+        let tag = if self.peek() == "{" || self.peek() == ":" {
+            None
+        } else {
+            Some(self.identifier()?)
+        };
+        let mut bits = None;
+        if self.eat(":") {
+            let CType::Int { bits: b, .. } = self.type_specifier()? else {
+                return Err(self.error("enum base type must be an integer"));
+            };
+            bits = Some(b);
+        }
+        self.expect("{")?;
+
+        let mut variants = Vec::new();
+        let mut next = 0;
+        while !self.eat("}") {
+            let name = self.identifier()?;
+            let value = if self.eat("=") {
+                self.constant()?
+            } else {
+                next
+            };
+            self.constants.insert(name.clone(), value);
+            variants.push(EnumVariant {
+                value,
+                name: Some(name),
+                description: self.comments.get(&self.line()).cloned(),
+            });
+            next = value + 1;
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+        packed |= self.attributes();
+
+        let bits = bits.unwrap_or_else(|| {
+            if !packed {
+                return 32;
+            }
+            // packed enums take the smallest integer holding all values
+            let (min, max) = variants
+                .iter()
+                .fold((0, 0), |(lo, hi), v| (v.value.min(lo), v.value.max(hi)));
+            [8, 16, 32]
+                .into_iter()
+                .find(|bits| {
+                    let signed = min < 0;
+                    let limit = if signed {
+                        1i128 << (bits - 1)
+                    } else {
+                        1i128 << bits
+                    };
+                    max < limit && min >= -limit
+                })
+                .unwrap_or(64)
+        });
+        let ctype = CType::Enum { bits, variants };
+        if let Some(tag) = tag {
+            self.types.insert(format!("enum {}", tag), ctype.clone());
+        }
+        Ok(ctype)
+    }
+
+    /// A type like `uint16_t`, `unsigned long long`, `struct tag` or a typedef name
+    fn type_specifier(&mut self) -> Result<CType, String> {
+        while matches!(self.peek(), "const" | "volatile" | "static") {
+            self.pos += 1;
+        }
+        match self.peek() {
+            "struct" | "union" if self.is_definition() => {
+                return Err(self.error("nested struct definitions are not supported"));
+            }
+            "struct" | "enum" | "union" => {
+                let keyword = self.next();
+                let tag = self.identifier()?;
+                let key = format!("{} {}", keyword, tag);
+                return self
+                    .types
+                    .get(&key)
+                    .cloned()
+                    .ok_or_else(|| self.error(&format!("unknown type '{}'", key)));
+            }
+            _ => {}
+        }
+
+        const WORDS: &[&str] = &["unsigned", "signed", "char", "short", "int", "long"];
+        let mut words = Vec::new();
+        while WORDS.contains(&self.peek()) {
+            words.push(self.next());
+        }
+        if !words.is_empty() {
+            let signed = !words.iter().any(|w| w == "unsigned");
+            let bits = match words.iter().filter(|w| *w == "long").count() {
+                2 => 64,
+                _ if words.iter().any(|w| w == "char") => 8,
+                _ if words.iter().any(|w| w == "short") => 16,
+                _ => 32,
+            };
+            return Ok(CType::Int { bits, signed });
+        }
+
+        let name = self.identifier()?;
+        let builtin = match name.as_str() {
+            "uint8_t" | "bool" | "_Bool" => Some((8, false)),
+            "int8_t" => Some((8, true)),
+            "uint16_t" => Some((16, false)),
+            "int16_t" => Some((16, true)),
+            "uint32_t" | "size_t" => Some((32, false)),
+            "int32_t" => Some((32, true)),
+            "uint64_t" => Some((64, false)),
+            "int64_t" => Some((64, true)),
+            "float" => return Ok(CType::Float { bits: 32 }),
+            "double" => return Ok(CType::Float { bits: 64 }),
+            _ => None,
+        };
+        match builtin {
+            Some((bits, signed)) => Ok(CType::Int { bits, signed }),
+            None => self
+                .types
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| self.error(&format!("unknown type '{}'", name))),
+        }
+    }
+
+    /// An integer literal or a previously defined constant
+    fn constant(&mut self) -> Result<i128, String> {
+        let negative = self.eat("-");
+        let token = self.next();
+        let value = parse_c_int(&token)
+            .ok()
+            .or_else(|| self.constants.get(&token).copied())
+            .ok_or_else(|| self.error(&format!("'{}' is not a supported constant", token)))?;
+        Ok(if negative { -value } else { value })
+    }
+
+    /// A constant that is a size, such as an array length
+    fn size(&mut self, what: &str) -> Result<u32, String> {
+        let value = self.constant()?;
+        u32::try_from(value).map_err(|_| self.error(&format!("{} {} is out of range", what, value)))
+    }
+
+    /// Fields of a struct and its alignment in bytes
+    fn layout(&mut self, index: usize) -> Result<(Vec<FieldRule>, u32), String> {
+        if let Some(layout) = &self.structs[index].layout {
+            return Ok(layout.clone());
+        }
+        let name = self.structs[index].name.clone();
+        let packed = self.structs[index].packed;
+        let members = std::mem::take(&mut self.structs[index].members);
+
+        let mut fields: Vec<FieldRule> = Vec::new();
+        let mut offset = 0u32; // in bits
+        let mut alignment = 1u32;
+        let mut unit: Option<BitfieldUnit> = None;
+
+        for member in &members {
+            if let Some(width) = member.bitfield {
+                let (bits, field_type) = scalar(&member.ctype).ok_or_else(|| {
+                    format!(
+                        "Struct '{}': bitfield '{}' must be an integer",
+                        name, member.name
+                    )
+                })?;
+                if width > bits {
+                    return Err(format!(
+                        "Struct '{}': bitfield '{}' is wider than its type",
+                        name, member.name
+                    ));
+                }
+                let fits = unit
+                    .as_ref()
+                    .is_some_and(|u| u.bits == bits && u.used + width <= bits);
+                if !fits || width == 0 {
+                    if let Some(unit) = unit.take() {
+                        offset = unit.emit(&mut fields, &name)?;
+                    }
+                    if !packed {
+                        offset = offset.next_multiple_of(bits);
+                        alignment = alignment.max(bits / 8);
+                    }
+                    if width == 0 {
+                        continue;
+                    }
+                    unit = Some(BitfieldUnit {
+                        offset,
+                        bits,
+                        used: 0,
+                        fields: Vec::new(),
+                    });
+                }
+                let unit = unit.as_mut().expect("unit was opened");
+                // signed bitfields hold the range of their width, not of their type
+                let field_type = match field_type {
+                    FieldType::Range {
+                        is_signed: true, ..
+                    } => signed_range(width),
+                    field_type => field_type,
+                };
+                let mut field = FieldRule::new(&member.name, field_type, FieldLength::Fixed(width));
+                field.description = member.description.clone();
+                unit.fields.push((unit.used, field));
+                unit.used += width;
+                continue;
+            }
+            if let Some(unit) = unit.take() {
+                offset = unit.emit(&mut fields, &name)?;
+            }
+
+            let (member_fields, member_alignment) = match &member.ctype {
+                CType::Struct(nested) => {
+                    let (nested_fields, nested_alignment) = self.layout(*nested)?;
+                    let fields = nested_fields
+                        .into_iter()
+                        .map(|mut f| {
+                            f.id = format!("{}_{}", member.name, f.id);
+                            f
+                        })
+                        .collect();
+                    (fields, nested_alignment)
+                }
+                ctype => {
+                    let (bits, field_type) = scalar(ctype).expect("not a struct");
+                    let mut field =
+                        FieldRule::new(&member.name, field_type, FieldLength::Fixed(bits));
+                    field.description = member.description.clone();
+                    (vec![field], bits / 8)
+                }
+            };
+            if !packed {
+                offset = offset.next_multiple_of(member_alignment * 8);
+                alignment = alignment.max(member_alignment);
+            }
+            pad(&mut fields, offset)?;
+
+            let element_bits: u32 = member_fields.iter().map(field_bits).sum();
+            let array_bits = |count: u32, bits: u32| {
+                count
+                    .checked_mul(bits)
+                    .filter(|total| offset.checked_add(*total).is_some())
+                    .ok_or_else(|| {
+                        format!(
+                            "Struct '{}': array '{}' of {} elements is too large",
+                            name, member.name, count
+                        )
+                    })
+            };
+            match member.array {
+                None => {
+                    fields.extend(member_fields);
+                    offset += element_bits;
+                }
+                Some(None) => {
+                    // flexible array member
+                    let mut field =
+                        FieldRule::new(&member.name, FieldType::Input, FieldLength::Variable);
+                    field.description = member.description.clone();
+                    fields.push(field);
+                }
+                // byte arrays such as strings are one field
+                Some(Some(count)) if element_bits == 8 && member_fields.len() == 1 => {
+                    let bits = array_bits(count, 8)?;
+                    let mut field =
+                        FieldRule::new(&member.name, FieldType::Input, FieldLength::Fixed(bits));
+                    field.description = member.description.clone();
+                    fields.push(field);
+                    offset += bits;
+                }
+                Some(Some(count)) => {
+                    let bits = array_bits(count, element_bits)?;
+                    for i in 0..count {
+                        fields.extend(member_fields.iter().cloned().map(|mut f| {
+                            f.id = match member.ctype {
+                                CType::Struct(_) => f.id.replacen(
+                                    &member.name,
+                                    &format!("{}_{}", member.name, i),
+                                    1,
+                                ),
+                                _ => format!("{}_{}", f.id, i),
+                            };
+                            f
+                        }));
+                    }
+                    offset += bits;
+                }
+            }
+        }
+        if let Some(unit) = unit.take() {
+            offset = unit.emit(&mut fields, &name)?;
+        }
+        if !packed {
+            let end = offset.next_multiple_of(alignment * 8);
+            pad(&mut fields, end)?;
+        }
+        if !packed && fields.iter().any(|f| f.id.starts_with("reserved_")) {
+            self.warnings.push(format!(
+                "Struct '{}' is not packed; alignment padding was added as reserved fields",
+                name
+            ));
+        }
+
+        self.structs[index].members = members;
+        self.structs[index].layout = Some((fields.clone(), alignment));
+        Ok((fields, alignment))
+    }
+}
+
+/// Bitfields sharing a storage unit, filled from the least significant bit
+struct BitfieldUnit {
+    /// bit offset of the unit in the struct
+    offset: u32,
+    bits: u32,
+    used: u32,
+    /// fields with their position from the least significant bit
+    fields: Vec<(u32, FieldRule)>,
+}
+
+impl BitfieldUnit {
+    /// Add the unit's fields in wire order, returning the offset after the unit. On a
+    /// little-endian target the unit's first byte holds its least significant bits.
+    fn emit(self, fields: &mut Vec<FieldRule>, struct_name: &str) -> Result<u32, String> {
+        pad(fields, self.offset)?;
+        let mut positioned: Vec<(u32, FieldRule)> = Vec::new();
+        for (lsb, field) in self.fields {
+            let width = field_bits(&field);
+            let byte = lsb / 8;
+            let wire = if lsb % 8 + width <= 8 {
+                // within one byte, read from its most significant bit
+                byte * 8 + 8 - lsb % 8 - width
+            } else if lsb.is_multiple_of(8) && width.is_multiple_of(8) {
+                lsb
+            } else {
+                return Err(format!(
+                    "Struct '{}': bitfield '{}' crosses a byte boundary, which cannot be represented",
+                    struct_name, field.id
+                ));
+            };
+            positioned.push((wire, field));
+        }
+        positioned.sort_by_key(|(wire, _)| *wire);
+
+        for (wire, field) in positioned {
+            pad(fields, self.offset + wire)?;
+            fields.push(field);
+        }
+        let end = self.offset + self.bits;
+        pad(fields, end)?;
+        Ok(end)
+    }
+}
+
+/// Add a reserved field to reach `offset`, if the fields end before it
+fn pad(fields: &mut Vec<FieldRule>, offset: u32) -> Result<(), String> {
+    let end: u32 = fields.iter().map(field_bits).sum();
+    if end < offset {
+        fields.push(FieldRule::new(
+            &format!("reserved_{}", end),
+            FieldType::Input,
+            FieldLength::Fixed(offset - end),
+        ));
+    } else if end > offset {
+        return Err(format!("Internal layout error at bit {}", offset));
+    }
+    Ok(())
+}
+
+fn field_bits(field: &FieldRule) -> u32 {
+    match field.length {
+        FieldLength::Fixed(bits) if !field.is_virtual() => bits,
+        _ => 0,
+    }
+}
+
+/// Size in bits and field type of a non-struct type
+fn scalar(ctype: &CType) -> Option<(u32, FieldType)> {
+    Some(match ctype {
+        CType::Int { bits, signed: true } => (*bits, signed_range(*bits)),
+        CType::Int { bits, .. } | CType::Float { bits } => (*bits, FieldType::Input),
+        CType::Enum { bits, variants } => (*bits, FieldType::Enum(variants.clone())),
+        CType::Struct(_) => return None,
+    })
+}
+
+/// The full range of a two's complement integer of `bits` bits
+fn signed_range(bits: u32) -> FieldType {
+    FieldType::Range {
+        min: -(1i128 << (bits - 1)),
+        max: (1i128 << (bits - 1)) - 1,
+        is_signed: true,
+    }
+}
+
+/// An integer literal with optional C suffixes like `U` or `UL`
+fn parse_c_int(text: &str) -> Result<i128, String> {
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    parse_int(digits)
+}
+
+/// Split the header into tokens, recording trailing comments by line. Preprocessor lines
+/// are dropped except `#define NAME value` and `#pragma pack`, which becomes `#pack 0|1`.
+fn tokenize(text: &str) -> Result<(Vec<Token>, HashMap<usize, String>), String> {
+    let mut tokens = Vec::new();
+    let mut comments: HashMap<usize, String> = HashMap::new();
+    let mut in_block_comment = false;
+
+    for (i, raw_line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let mut code = String::new();
+        let mut rest = raw_line;
+        loop {
+            if in_block_comment {
+                match rest.find("*/") {
+                    Some(end) => {
+                        rest = &rest[end + 2..];
+                        in_block_comment = false;
+                    }
+                    None => break,
+                }
+            }
+            let line_comment = rest.find("//");
+            let block_comment = rest.find("/*");
+            match (line_comment, block_comment) {
+                (Some(l), b) if b.is_none_or(|b| l < b) => {
+                    code.push_str(&rest[..l]);
+                    add_comment(&mut comments, line_no, &rest[l + 2..]);
+                    break;
+                }
+                (_, Some(b)) => {
+                    code.push_str(&rest[..b]);
+                    let after = &rest[b + 2..];
+                    match after.find("*/") {
+                        Some(end) => {
+                            add_comment(&mut comments, line_no, &after[..end]);
+                            code.push(' ');
+                            rest = &after[end + 2..];
+                        }
+                        None => {
+                            in_block_comment = true;
+                            break;
+                        }
+                    }
+                }
+                _ => {
+                    code.push_str(rest);
+                    break;
+                }
+            }
+        }
+
+        let trimmed = code.trim();
+        if let Some(directive) = trimmed.strip_prefix('#') {
+            let words: Vec<&str> = directive
+                .split(|c: char| c.is_whitespace() || "(),".contains(c))
+                .filter(|w| !w.is_empty())
+                .collect();
+            match words.as_slice() {
+                ["define", name, value] => {
+                    for text in ["#define", name, value] {
+                        tokens.push(Token {
+                            text: text.to_string(),
+                            line: line_no,
+                        });
+                    }
+                }
+                ["pragma", "pack", args @ ..] => {
+                    let packed = args.contains(&"1");
+                    for text in ["#pack", if packed { "1" } else { "0" }] {
+                        tokens.push(Token {
+                            text: text.to_string(),
+                            line: line_no,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let mut chars = trimmed.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let mut end = start + c.len_utf8();
+            if c.is_ascii_alphanumeric() || c == '_' {
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+            } else if c == '"' {
+                for (i, c) in chars.by_ref() {
+                    end = i + c.len_utf8();
+                    if c == '"' {
+                        break;
+                    }
+                }
+            }
+            tokens.push(Token {
+                text: trimmed[start..end].to_string(),
+                line: line_no,
+            });
+        }
+    }
+    if in_block_comment {
+        return Err("Comment is not closed".to_string());
+    }
+    Ok((tokens, comments))
+}
+
+fn add_comment(comments: &mut HashMap<usize, String>, line: usize, text: &str) {
+    let text = text.trim().trim_start_matches(['*', '<', '!', '/']).trim();
+    if !text.is_empty() {
+        comments.insert(line, text.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_ids(protocol: &Protocol) -> Vec<&str> {
+        protocol.fields.iter().map(|f| f.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_packed_struct_with_bitfields_and_enum() {
+        let header = r#"
+#include <stdint.h>
+#define NAME_LEN 4
+
+typedef enum __attribute__((packed)) {
+    MODE_IDLE = 0, /**< waiting */
+    MODE_RUN,
+    MODE_FAULT = 0x10,
+} mode_t;
+
+typedef struct __attribute__((packed)) {
+    uint8_t version : 4;  // protocol version
+    uint8_t flags : 4;
+    mode_t mode;
+    int16_t temperature;  /* 0.1 degC */
+    char name[NAME_LEN];
+    uint16_t samples[2];
+    uint8_t payload[];
+} telemetry_t;
+"#;
+        let import = import_c_header(header).unwrap();
+        let telemetry = &import.protocols[0];
+        assert_eq!(telemetry.id, "telemetry_t");
+        assert_eq!(telemetry.endianness, Endianness::Little);
+        assert_eq!(
+            field_ids(telemetry),
+            vec![
+                "flags",
+                "version",
+                "mode",
+                "temperature",
+                "name",
+                "samples_0",
+                "samples_1",
+                "payload"
+            ]
+        );
+        assert_eq!(
+            telemetry.fields[1].description.as_deref(),
+            Some("protocol version")
+        );
+        assert_eq!(telemetry.fields[3].description.as_deref(), Some("0.1 degC"));
+        let FieldType::Enum(variants) = &telemetry.fields[2].field_type else {
+            panic!("mode is not an enum");
+        };
+        assert_eq!(variants[1].value, 1);
+        assert_eq!(variants[2].value, 0x10);
+        assert_eq!(variants[0].description.as_deref(), Some("waiting"));
+        assert_eq!(telemetry.fields[2].length, FieldLength::Fixed(8));
+        assert_eq!(telemetry.fields[4].length, FieldLength::Fixed(32));
+        assert_eq!(telemetry.fields[7].length, FieldLength::Variable);
+    }
+
+    #[test]
+    fn test_alignment_and_nested_structs() {
+        let header = r#"
+struct point { int16_t x; int16_t y; };
+
+#pragma pack(push, 1)
+typedef struct { uint8_t kind; uint32_t id; } packed_t;
+#pragma pack(pop)
+
+typedef struct {
+    uint8_t kind;
+    uint32_t id;
+    struct point origin;
+    uint8_t last;
+} aligned_t;
+"#;
+        let import = import_c_header(header).unwrap();
+        assert_eq!(import.protocols[0].id, "point");
+        assert_eq!(field_ids(&import.protocols[1]), vec!["kind", "id"]);
+        assert_eq!(
+            field_ids(&import.protocols[2]),
+            vec![
+                "kind",
+                "reserved_8",
+                "id",
+                "origin_x",
+                "origin_y",
+                "last",
+                "reserved_104"
+            ]
+        );
+        assert_eq!(import.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_multi_byte_bitfield_unit() {
+        let header = "typedef struct __attribute__((packed)) {
+            uint16_t low : 4;
+            uint16_t mid : 4;
+            uint16_t high : 8;
+        } bits_t;";
+        let import = import_c_header(header).unwrap();
+        assert_eq!(field_ids(&import.protocols[0]), vec!["mid", "low", "high"]);
+
+        let straddling = "struct s { uint16_t a : 4; uint16_t b : 8; };";
+        assert!(import_c_header(straddling).unwrap_err().contains("'b'"));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert!(import_c_header("struct s { uint8_t *p; };").is_err());
+        assert!(import_c_header("struct s { unknown_t x; };").is_err());
+        assert!(import_c_header("union u { uint8_t a; };").is_err());
+        assert!(import_c_header("struct s { uint8_t a[-1]; };").is_err());
+        assert!(import_c_header("struct s { uint8_t a[0x20000000]; };").is_err());
+        assert!(import_c_header("struct s { uint32_t a[0x8000000]; };").is_err());
+        assert!(import_c_header("struct s { uint8_t a : 9; };").is_err());
+    }
+
+    #[test]
+    fn test_signed_bitfield() {
+        let header = "typedef struct __attribute__((packed)) {
+            int8_t x : 3;
+            uint8_t y : 5;
+        } bits_t;";
+        let import = import_c_header(header).unwrap();
+        let x = import.protocols[0]
+            .fields
+            .iter()
+            .find(|f| f.id == "x")
+            .unwrap();
+        assert_eq!(
+            x.field_type,
+            FieldType::Range {
+                min: -4,
+                max: 3,
+                is_signed: true
+            }
+        );
+        assert!(x.validate().is_empty());
+    }
+}
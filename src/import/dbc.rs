@@ -0,0 +1,551 @@
+//! Vector DBC (CAN database) import. Every message becomes a protocol with its CAN ID, DLC
+//! and transmitter in the metadata, and every signal a field at the same bit position.
+//!
+//! Signal scaling is kept as a derived field `<signal>_phys` computing the physical value,
+//! named `<signal> [<unit>]` when the signal has a unit; the DBC export reads it back.
+
+use crate::models::field::{EnumVariant, FieldLength, FieldRule, FieldType};
+use crate::models::protocol::{Endianness, Protocol};
+use std::collections::HashMap;
+
+/// Metadata key holding the CAN identifier of a message, as written in the DBC file
+pub const CAN_ID_KEY: &str = "can_id";
+/// Metadata key holding the data length of a message in bytes
+pub const DLC_KEY: &str = "dlc";
+/// Metadata key holding the node that sends a message
+pub const TRANSMITTER_KEY: &str = "transmitter";
+/// Placeholder DBC uses for "no node"
+pub const NO_NODE: &str = "Vector__XXX";
+
+/// Protocols read from a DBC file, with what could not be imported
+#[derive(Debug)]
+pub struct DbcImport {
+    pub protocols: Vec<Protocol>,
+    pub warnings: Vec<String>,
+}
+
+/// Byte order of a signal: `@1` is Intel (little-endian), `@0` Motorola (big-endian)
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ByteOrder {
+    Intel,
+    Motorola,
+}
+
+#[derive(Debug)]
+struct Signal {
+    name: String,
+    multiplexed: bool,
+    start: u32,
+    bits: u32,
+    order: ByteOrder,
+    signed: bool,
+    factor: f64,
+    offset: f64,
+    min: f64,
+    max: f64,
+    unit: String,
+}
+
+#[derive(Debug)]
+struct Message {
+    id: u32,
+    name: String,
+    dlc: u32,
+    transmitter: String,
+    signals: Vec<Signal>,
+}
+
+/// Parse a DBC file into one protocol per message
+pub fn import_dbc(text: &str) -> Result<DbcImport, String> {
+    let mut messages: Vec<Message> = Vec::new();
+    let mut message_comments: HashMap<u32, String> = HashMap::new();
+    let mut signal_comments: HashMap<(u32, String), String> = HashMap::new();
+    let mut values: HashMap<(u32, String), Vec<EnumVariant>> = HashMap::new();
+
+    for (line_no, statement) in statements(text) {
+        let err = |e: String| format!("Line {}: {}", line_no, e);
+        let tokens = tokenize(&statement).map_err(err)?;
+        let words: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+        match words.as_slice() {
+            ["BO_", id, name, ":", dlc, transmitter, ..] => messages.push(Message {
+                id: parse_number(id).map_err(err)?,
+                name: name.to_string(),
+                dlc: parse_number(dlc).map_err(err)?,
+                transmitter: transmitter.to_string(),
+                signals: Vec::new(),
+            }),
+            ["SG_", ..] => {
+                let signal = parse_signal(&words).map_err(err)?;
+                messages
+                    .last_mut()
+                    .ok_or_else(|| err("Signal outside of a message".to_string()))?
+                    .signals
+                    .push(signal);
+            }
+            ["CM_", "BO_", id, comment, ..] => {
+                message_comments.insert(parse_number(id).map_err(err)?, unquote(comment));
+            }
+            ["CM_", "SG_", id, signal, comment, ..] => {
+                signal_comments.insert(
+                    (parse_number(id).map_err(err)?, signal.to_string()),
+                    unquote(comment),
+                );
+            }
+            ["VAL_", id, signal, rest @ ..] => {
+                let mut variants = Vec::new();
+                for pair in rest.chunks_exact(2) {
+                    variants.push(EnumVariant {
+                        value: parse_number::<i128>(pair[0]).map_err(err)?,
+                        name: Some(unquote(pair[1])),
+                        description: None,
+                    });
+                }
+                values.insert(
+                    (parse_number(id).map_err(err)?, signal.to_string()),
+                    variants,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut import = DbcImport {
+        protocols: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for message in messages {
+        // the pseudo message holding signals of no message
+        if message.name == "VECTOR__INDEPENDENT_SIG_MSG" {
+            continue;
+        }
+        let protocol = message_protocol(
+            &message,
+            message_comments.get(&message.id),
+            &signal_comments,
+            &values,
+            &mut import.warnings,
+        )
+        .map_err(|e| format!("Message '{}': {}", message.name, e))?;
+        import.protocols.push(protocol);
+    }
+    Ok(import)
+}
+
+fn message_protocol(
+    message: &Message,
+    comment: Option<&String>,
+    signal_comments: &HashMap<(u32, String), String>,
+    values: &HashMap<(u32, String), Vec<EnumVariant>>,
+    warnings: &mut Vec<String>,
+) -> Result<Protocol, String> {
+    let mut placed: Vec<(u32, &Signal)> = Vec::new();
+    for signal in &message.signals {
+        if signal.multiplexed {
+            warnings.push(format!(
+                "Message '{}': multiplexed signal '{}' was skipped",
+                message.name, signal.name
+            ));
+            continue;
+        }
+        placed.push((bit_offset(signal)?, signal));
+    }
+    placed.sort_by_key(|(offset, _)| *offset);
+
+    // only multi-byte signals tell the byte order apart
+    let little = placed
+        .iter()
+        .any(|(_, s)| s.order == ByteOrder::Intel && s.bits > 8);
+    if little
+        && let Some((_, s)) = placed
+            .iter()
+            .find(|(_, s)| s.order == ByteOrder::Motorola && s.bits > 8 && s.bits.is_multiple_of(8))
+    {
+        return Err(format!(
+            "Motorola signal '{}' cannot be mixed with Intel signals",
+            s.name
+        ));
+    }
+    let endianness = if little {
+        Endianness::Little
+    } else {
+        Endianness::Big
+    };
+
+    let mut protocol = Protocol::new(&message.name, None, endianness, None);
+    protocol.description = comment.cloned();
+    protocol.update_metadata(CAN_ID_KEY, &message.id.to_string());
+    protocol.update_metadata(DLC_KEY, &message.dlc.to_string());
+    if message.transmitter != NO_NODE {
+        protocol.update_metadata(TRANSMITTER_KEY, &message.transmitter);
+    }
+
+    let mut cursor = 0;
+    for (offset, signal) in placed {
+        if offset < cursor {
+            return Err(format!("Signal '{}' overlaps another signal", signal.name));
+        }
+        if offset > cursor {
+            protocol.add_field(reserved(cursor, offset - cursor))?;
+        }
+        cursor = offset + signal.bits;
+
+        let key = (message.id, signal.name.clone());
+        let field_type = if let Some(variants) = values.get(&key) {
+            FieldType::Enum(variants.clone())
+        } else if signal.min != signal.max {
+            let (min, max) = raw_range(signal);
+            FieldType::Range {
+                min,
+                max,
+                is_signed: signal.signed,
+            }
+        } else if signal.signed {
+            FieldType::Range {
+                min: -(1i128 << (signal.bits - 1)),
+                max: (1i128 << (signal.bits - 1)) - 1,
+                is_signed: true,
+            }
+        } else {
+            FieldType::Input
+        };
+        let mut field = FieldRule::new(&signal.name, field_type, FieldLength::Fixed(signal.bits));
+        field.description = signal_comments.get(&key).cloned();
+        protocol.add_field(field)?;
+
+        if signal.factor != 1.0 || signal.offset != 0.0 || !signal.unit.is_empty() {
+            let mut physical = FieldRule::new(
+                &format!("{}_phys", signal.name),
+                FieldType::Derived(scaling_script(&signal.name, signal.factor, signal.offset)),
+                FieldLength::Fixed(0),
+            );
+            if !signal.unit.is_empty() {
+                physical.name = Some(format!("{} [{}]", signal.name, signal.unit));
+            }
+            protocol.add_field(physical)?;
+        }
+    }
+    let frame_bits = message.dlc * 8;
+    if cursor < frame_bits {
+        protocol.add_field(reserved(cursor, frame_bits - cursor))?;
+    }
+    Ok(protocol)
+}
+
+/// The script of the derived field holding the physical value of a signal
+pub fn scaling_script(field_id: &str, factor: f64, offset: f64) -> String {
+    format!("{} * {:?} + {:?}", field_id, factor, offset)
+}
+
+/// Factor and offset of a script written by [`scaling_script`] for `field_id`
+pub fn parse_scaling(script: &str, field_id: &str) -> Option<(f64, f64)> {
+    let rest = script.trim().strip_prefix(field_id)?.trim_start();
+    let (factor, offset) = rest.strip_prefix('*')?.split_once('+')?;
+    Some((factor.trim().parse().ok()?, offset.trim().parse().ok()?))
+}
+
+/// Padding between signals; the DBC export leaves these out
+fn reserved(offset: u32, bits: u32) -> FieldRule {
+    FieldRule::new(
+        &format!("reserved_{}", offset),
+        FieldType::Input,
+        FieldLength::Fixed(bits),
+    )
+}
+
+/// Offset of the most significant bit of a signal, counted MSB-first from the start of the frame
+fn bit_offset(signal: &Signal) -> Result<u32, String> {
+    let (byte, bit) = (signal.start / 8, signal.start % 8);
+    match signal.order {
+        // the start bit is the most significant bit
+        ByteOrder::Motorola => Ok(byte * 8 + 7 - bit),
+        // the start bit is the least significant bit
+        ByteOrder::Intel if bit + signal.bits <= 8 => Ok(byte * 8 + 8 - bit - signal.bits),
+        ByteOrder::Intel if bit == 0 && signal.bits.is_multiple_of(8) => Ok(signal.start),
+        ByteOrder::Intel => Err(format!(
+            "Intel signal '{}' is not byte-aligned, which is not supported",
+            signal.name
+        )),
+    }
+}
+
+/// Raw value range of a signal from its physical minimum and maximum
+fn raw_range(signal: &Signal) -> (i128, i128) {
+    let raw = |physical: f64| ((physical - signal.offset) / signal.factor).round() as i128;
+    let (a, b) = (raw(signal.min), raw(signal.max));
+    (a.min(b), a.max(b))
+}
+
+/// ` SG_ name [M|mN] : start|bits@order sign (factor,offset) [min|max] "unit" receivers`
+fn parse_signal(words: &[&str]) -> Result<Signal, String> {
+    let (name, multiplexing, rest) = match words {
+        ["SG_", name, ":", rest @ ..] => (*name, None, rest),
+        ["SG_", name, mux, ":", rest @ ..] => (*name, Some(*mux), rest),
+        _ => return Err("Invalid signal".to_string()),
+    };
+    let [
+        start,
+        "|",
+        bits,
+        "@",
+        order_sign,
+        "(",
+        factor,
+        ",",
+        offset,
+        ")",
+        "[",
+        min,
+        "|",
+        max,
+        "]",
+        unit,
+        ..,
+    ] = rest
+    else {
+        return Err(format!("Invalid definition of signal '{}'", name));
+    };
+
+    let (order, sign) = order_sign.split_at(1.min(order_sign.len()));
+    let bits: u32 = parse_number(bits)?;
+    if bits == 0 || bits > 64 {
+        return Err(format!("Signal '{}' has invalid length {}", name, bits));
+    }
+    Ok(Signal {
+        name: name.to_string(),
+        // the multiplexor `M` itself is an ordinary signal
+        multiplexed: multiplexing.is_some_and(|m| m.starts_with('m')),
+        start: parse_number(start)?,
+        bits,
+        order: match order {
+            "1" => ByteOrder::Intel,
+            "0" => ByteOrder::Motorola,
+            _ => return Err(format!("Signal '{}' has invalid byte order", name)),
+        },
+        signed: sign == "-",
+        factor: parse_number(factor)?,
+        offset: parse_number(offset)?,
+        min: parse_number(min)?,
+        max: parse_number(max)?,
+        unit: unquote(unit),
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("'{}' is not a valid number", text))
+}
+
+fn unquote(token: &str) -> String {
+    let inner = token
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(token);
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            c => text.push(c),
+        }
+    }
+    text
+}
+
+/// Split the file into statements with the line they start on. `CM_` and `VAL_` statements
+/// run until their `;`, the others are one line; the `NS_` block is skipped.
+fn statements(text: &str) -> Vec<(usize, String)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut statements = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        let keyword = lines[i].split_whitespace().next().unwrap_or_default();
+        i += 1;
+        match keyword {
+            // the names listed below `NS_ :` are indented
+            "NS_" => {
+                while i < lines.len()
+                    && !lines[i].trim().is_empty()
+                    && lines[i].starts_with(char::is_whitespace)
+                {
+                    i += 1;
+                }
+            }
+            "CM_" | "VAL_" => {
+                let mut statement = lines[start].to_string();
+                while !is_terminated(&statement) && i < lines.len() {
+                    statement.push('\n');
+                    statement.push_str(lines[i]);
+                    i += 1;
+                }
+                statements.push((start + 1, statement));
+            }
+            _ => statements.push((start + 1, lines[start].to_string())),
+        }
+    }
+    statements
+}
+
+/// Whether a statement ends with `;` outside of a string
+fn is_terminated(statement: &str) -> bool {
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut terminated = false;
+    for c in statement.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => terminated = true,
+            c if !c.is_whitespace() => terminated = false,
+            _ => {}
+        }
+    }
+    terminated
+}
+
+/// Split a statement into words, quoted strings (with quotes) and punctuation
+fn tokenize(statement: &str) -> Result<Vec<String>, String> {
+    const PUNCTUATION: &[char] = &[':', '|', '@', '(', ')', ',', '[', ']', ';'];
+    let mut tokens = Vec::new();
+    let mut chars = statement.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if PUNCTUATION.contains(&c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if c == '"' {
+            let mut token = String::from(chars.next().unwrap_or_default());
+            let mut closed = false;
+            while let Some(c) = chars.next() {
+                token.push(c);
+                match c {
+                    '\\' => token.extend(chars.next()),
+                    '"' => {
+                        closed = true;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            if !closed {
+                return Err("Unterminated string".to_string());
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' || PUNCTUATION.contains(&c) {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DBC: &str = r#"VERSION ""
+
+NS_ :
+	CM_
+	VAL_
+
+BS_:
+
+BU_: Engine Dash
+
+BO_ 256 EngineData: 8 Engine
+ SG_ EngineSpeed : 7|16@0+ (0.25,0) [0|16383.75] "rpm" Dash
+ SG_ Gear : 19|4@0+ (1,0) [0|0] "" Dash
+ SG_ Temperature : 31|8@0- (1,-40) [-40|87] "degC" Dash
+
+BO_ 2364540158 Status: 4 Vector__XXX
+ SG_ Counter : 0|16@1+ (1,0) [0|0] "" Dash
+ SG_ Mode M : 16|8@1+ (1,0) [0|0] "" Dash
+ SG_ Detail m1 : 24|8@1+ (1,0) [0|0] "" Dash
+
+CM_ BO_ 256 "Sent every 10 ms";
+CM_ SG_ 256 EngineSpeed "Crankshaft speed;
+measured at the flywheel";
+VAL_ 256 Gear 0 "Neutral" 1 "First" ;
+"#;
+
+    #[test]
+    fn test_import_messages() {
+        let import = import_dbc(DBC).unwrap();
+        assert_eq!(import.protocols.len(), 2);
+
+        let engine = &import.protocols[0];
+        assert_eq!(engine.id, "EngineData");
+        assert_eq!(engine.endianness, Endianness::Big);
+        assert_eq!(engine.metadata[CAN_ID_KEY], "256");
+        assert_eq!(engine.metadata[TRANSMITTER_KEY], "Engine");
+        assert_eq!(engine.description.as_deref(), Some("Sent every 10 ms"));
+
+        let ids: Vec<&str> = engine.fields.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "EngineSpeed",
+                "EngineSpeed_phys",
+                "reserved_16",
+                "Gear",
+                "Temperature",
+                "Temperature_phys",
+                "reserved_32"
+            ]
+        );
+        assert_eq!(
+            engine.fields[0].description.as_deref(),
+            Some("Crankshaft speed;\nmeasured at the flywheel")
+        );
+        assert_eq!(
+            engine.fields[1].field_type,
+            FieldType::Derived("EngineSpeed * 0.25 + 0.0".to_string())
+        );
+        assert_eq!(engine.fields[1].name.as_deref(), Some("EngineSpeed [rpm]"));
+        assert!(matches!(&engine.fields[3].field_type, FieldType::Enum(v) if v.len() == 2));
+        assert_eq!(
+            engine.fields[4].field_type,
+            FieldType::Range {
+                min: 0,
+                max: 127,
+                is_signed: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_import_intel_and_multiplexed() {
+        let import = import_dbc(DBC).unwrap();
+        let status = &import.protocols[1];
+        assert_eq!(status.endianness, Endianness::Little);
+        assert!(!status.metadata.contains_key(TRANSMITTER_KEY));
+        let ids: Vec<&str> = status.fields.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["Counter", "Mode", "reserved_24"]);
+        assert_eq!(import.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_scaling_roundtrip() {
+        let script = scaling_script("speed", 0.5, -10.0);
+        assert_eq!(parse_scaling(&script, "speed"), Some((0.5, -10.0)));
+        assert_eq!(parse_scaling("speed * 2", "speed"), None);
+        assert_eq!(parse_scaling("other * 2.0 + 1.0", "speed"), None);
+    }
+
+    #[test]
+    fn test_invalid_signal() {
+        let error = import_dbc("BO_ 1 M: 8 X\n SG_ S : 3|12@1+ (1,0) [0|0] \"\" X").unwrap_err();
+        assert!(error.contains("not byte-aligned"));
+        assert!(import_dbc(" SG_ S : 0|8@1+ (1,0) [0|0] \"\" X").is_err());
+    }
+}
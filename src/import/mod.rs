@@ -0,0 +1,2 @@
+pub mod c_header;
+pub mod dbc;
@@ -0,0 +1,19 @@
+pub mod analysis;
+pub mod capture;
+pub mod codec;
+pub mod codegen;
+pub mod conversation;
+pub mod coverage;
+pub mod error;
+pub mod export;
+pub mod i18n;
+pub mod import;
+pub mod live_capture;
+pub mod models;
+pub mod replay;
+pub mod scheduler;
+pub mod script;
+pub mod scrub;
+pub mod server;
+pub mod simulator;
+pub mod transport;
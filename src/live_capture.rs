@@ -0,0 +1,149 @@
+//! Capturing frames live from a network interface with libpcap, read on a thread of its own.
+//! Needs the `pcap` feature, and usually the permission to capture (root or `CAP_NET_RAW`).
+
+use crate::capture::CapturedPacket;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::thread::JoinHandle;
+
+/// A network interface frames can be captured on
+#[derive(Clone, PartialEq, Debug)]
+pub struct Interface {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Frames being captured from an interface until dropped
+pub struct LiveCapture {
+    /// pcap link type of the frames, as in pcap files
+    link_type: u32,
+    receiver: Receiver<Result<CapturedPacket, String>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LiveCapture {
+    /// The interfaces that can be captured on
+    pub fn interfaces() -> Result<Vec<Interface>, String> {
+        imp::interfaces()
+    }
+
+    /// Start capturing the frames on an interface that match a BPF filter such as
+    /// `udp port 5000`; an empty filter matches every frame
+    pub fn start(interface: &str, filter: &str) -> Result<Self, String> {
+        imp::start(interface, filter)
+    }
+
+    pub fn link_type(&self) -> u32 {
+        self.link_type
+    }
+
+    /// The next captured frame if one has arrived, without blocking
+    pub fn try_recv(&mut self) -> Result<Option<CapturedPacket>, String> {
+        match self.receiver.try_recv() {
+            Ok(result) => result.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err("Capture stopped".to_string()),
+        }
+    }
+}
+
+impl Drop for LiveCapture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(feature = "pcap")]
+mod imp {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// How long a read waits for a frame before checking whether to stop, in milliseconds
+    const READ_TIMEOUT_MS: i32 = 100;
+
+    /// Most bytes captured of each frame
+    const SNAPLEN: i32 = 65_535;
+
+    pub fn interfaces() -> Result<Vec<Interface>, String> {
+        let devices =
+            pcap::Device::list().map_err(|e| format!("Failed to list interfaces: {}", e))?;
+        Ok(devices
+            .into_iter()
+            .map(|d| Interface {
+                name: d.name,
+                description: d.desc,
+            })
+            .collect())
+    }
+
+    pub fn start(interface: &str, filter: &str) -> Result<LiveCapture, String> {
+        let open_error = |e: pcap::Error| format!("Failed to capture on '{}': {}", interface, e);
+        let mut capture = pcap::Capture::from_device(interface)
+            .map_err(open_error)?
+            .promisc(true)
+            .snaplen(SNAPLEN)
+            .timeout(READ_TIMEOUT_MS)
+            .immediate_mode(true)
+            .open()
+            .map_err(open_error)?;
+        if !filter.trim().is_empty() {
+            capture
+                .filter(filter.trim(), true)
+                .map_err(|e| format!("Invalid filter '{}': {}", filter.trim(), e))?;
+        }
+        let link_type = capture.get_datalink().0 as u32;
+
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                let result = match capture.next_packet() {
+                    Ok(packet) => {
+                        let ts = packet.header.ts;
+                        Ok(CapturedPacket {
+                            timestamp: Duration::from_secs(ts.tv_sec as u64)
+                                + Duration::from_micros(ts.tv_usec as u64),
+                            data: packet.data.to_vec(),
+                        })
+                    }
+                    Err(pcap::Error::TimeoutExpired) => continue,
+                    Err(e) => Err(format!("Capture failed: {}", e)),
+                };
+                let failed = result.is_err();
+                if sender.send(result).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(LiveCapture {
+            link_type,
+            receiver,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+#[cfg(not(feature = "pcap"))]
+mod imp {
+    use super::*;
+
+    const NOT_BUILT: &str =
+        "Live interface capture needs bitloom built with the 'pcap' feature and libpcap installed";
+
+    pub fn interfaces() -> Result<Vec<Interface>, String> {
+        Err(NOT_BUILT.to_string())
+    }
+
+    pub fn start(_interface: &str, _filter: &str) -> Result<LiveCapture, String> {
+        Err(NOT_BUILT.to_string())
+    }
+}
@@ -1,11 +1,13 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
-mod models;
 mod ui;
 use eframe::egui;
 
 fn main() -> eframe::Result {
+    ui::crash::install_hook();
+    // a project to open, e.g. `bitloom project.bitloom` or a file opened from a file manager
+    let project_path = std::env::args().nth(1);
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1000.0, 700.0]),
         ..Default::default()
@@ -13,6 +15,6 @@ fn main() -> eframe::Result {
     eframe::run_native(
         "BitLoom",
         native_options,
-        Box::new(|cc| Ok(Box::new(app::BitLoomApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::BitLoomApp::new(cc, project_path)))),
     )
 }
@@ -0,0 +1,304 @@
+use super::field::{FieldLength, FieldRule, FieldType};
+use super::protocol::ProtocolRegistry;
+
+/// Words in the ID or name of a field that mark it as reserved for future use
+const RESERVED_WORDS: [&str; 4] = ["reserved", "spare", "unused", "padding"];
+
+/// What the bits of a span of a packet are for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BitUse {
+    Used,
+    /// a field kept for future use, named e.g. `reserved` or `spare`
+    Reserved,
+    /// bits no field or sub-field is assigned to
+    Unassigned,
+}
+
+impl BitUse {
+    /// Name of the use in exports, which the user interface shows translated instead
+    pub fn label(self) -> &'static str {
+        match self {
+            BitUse::Used => "used",
+            BitUse::Reserved => "reserved",
+            BitUse::Unassigned => "unassigned",
+        }
+    }
+}
+
+/// Consecutive bits of a packet with one use
+#[derive(Clone, PartialEq, Debug)]
+pub struct BitSpan {
+    /// offset from the start of the packet in bits
+    pub start: u32,
+    pub bits: u32,
+    pub usage: BitUse,
+    /// the field the bits belong to, with the sub-field if any; `None` for frame padding
+    pub owner: Option<String>,
+}
+
+/// Where every bit of a fixed-length protocol goes, as reviewers of an interface control
+/// document check it
+#[derive(Clone, PartialEq, Debug)]
+pub struct BitAccounting {
+    /// bits taken by the fields, or the frame length if it is declared
+    pub total_bits: u32,
+    /// declared frame length in bits, with the protocol declaring it
+    pub frame_length: Option<(u32, String)>,
+    pub spans: Vec<BitSpan>,
+    /// gaps and overlaps, as messages
+    pub issues: Vec<String>,
+}
+
+impl BitAccounting {
+    /// Number of bits with a use
+    pub fn count(&self, usage: BitUse) -> u32 {
+        self.spans
+            .iter()
+            .filter(|s| s.usage == usage)
+            .map(|s| s.bits)
+            .sum()
+    }
+}
+
+fn is_reserved(field: &FieldRule) -> bool {
+    let id = field.id.to_lowercase();
+    let name = field.name.as_deref().unwrap_or("").to_lowercase();
+    RESERVED_WORDS
+        .iter()
+        .any(|word| id.contains(word) || name.contains(word))
+}
+
+/// Account for every bit of a protocol and its ancestors: the bits of each field, the bits of
+/// fields with sub-fields that no sub-field covers, and the padding up to the frame length.
+/// Fails for protocols with a variable length field.
+pub fn bit_accounting(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+) -> Result<BitAccounting, String> {
+    let fields = registry.resolve_fields(protocol_id)?;
+    let mut spans = Vec::new();
+    let mut issues = Vec::new();
+    let mut offset = 0;
+    for field in fields.iter().filter(|f| !f.is_virtual()) {
+        let FieldLength::Fixed(bits) = field.length else {
+            return Err(format!(
+                "Protocol '{}' is not of fixed length: field '{}' has a variable length",
+                protocol_id, field.id
+            ));
+        };
+        if bits == 0 {
+            continue;
+        }
+        let usage = if is_reserved(field) {
+            BitUse::Reserved
+        } else {
+            BitUse::Used
+        };
+        if field.subfields.is_empty() || matches!(field.field_type, FieldType::Fixed(_)) {
+            spans.push(BitSpan {
+                start: offset,
+                bits,
+                usage,
+                owner: Some(field.id.clone()),
+            });
+        } else {
+            subfield_spans(field, offset, bits, usage, &mut spans, &mut issues);
+        }
+        offset += bits;
+    }
+
+    let frame_length = registry
+        .frame_length(protocol_id)
+        .map(|(bits, id)| (bits, id.to_string()));
+    let mut total_bits = offset;
+    if let Some((frame_bits, frame_id)) = &frame_length {
+        if offset < *frame_bits {
+            spans.push(BitSpan {
+                start: offset,
+                bits: frame_bits - offset,
+                usage: BitUse::Unassigned,
+                owner: None,
+            });
+            issues.push(format!(
+                "Bits {}-{} after the last field are padding up to the frame length of '{}'",
+                offset,
+                frame_bits - 1,
+                frame_id
+            ));
+        } else if offset > *frame_bits {
+            issues.push(format!(
+                "The fields take {} bits, {} more than the frame length of {} bits of '{}'",
+                offset,
+                offset - frame_bits,
+                frame_bits,
+                frame_id
+            ));
+        }
+        total_bits = total_bits.max(*frame_bits);
+    }
+    if let Some(max) = registry
+        .get_inheritance_chain(protocol_id)
+        .iter()
+        .filter_map(|p| p.max_length)
+        .min()
+        && offset > max
+    {
+        issues.push(format!(
+            "The fields take {} bits, more than the maximum length of {} bits",
+            offset, max
+        ));
+    }
+    if !offset.is_multiple_of(8) && frame_length.is_none() {
+        issues.push(format!(
+            "The fields take {} bits, {} short of a whole number of bytes",
+            offset,
+            8 - offset % 8
+        ));
+    }
+
+    Ok(BitAccounting {
+        total_bits,
+        frame_length,
+        spans,
+        issues,
+    })
+}
+
+/// Spans of a field with sub-fields, from the most significant bit of its value down as in a
+/// big-endian field, with the gaps between the sub-fields reported. Sub-fields do not overlap,
+/// as validating the field checks.
+fn subfield_spans(
+    field: &FieldRule,
+    offset: u32,
+    bits: u32,
+    usage: BitUse,
+    spans: &mut Vec<BitSpan>,
+    issues: &mut Vec<String>,
+) {
+    // the sub-field owning each bit of the value, most significant first
+    let mut owners: Vec<Option<&str>> = vec![None; bits as usize];
+    for sub in &field.subfields {
+        for bit in sub.lsb..(sub.lsb + sub.bits).min(bits) {
+            owners[(bits - 1 - bit) as usize] = Some(&sub.id);
+        }
+    }
+
+    let mut start = 0;
+    while start < owners.len() {
+        let owner = owners[start];
+        let len = owners[start..].iter().take_while(|o| **o == owner).count();
+        let (usage, name) = match owner {
+            Some(sub) => (usage, format!("{}.{}", field.id, sub)),
+            None => {
+                let msb = bits - 1 - start as u32;
+                let lsb = msb + 1 - len as u32;
+                let range = if len == 1 {
+                    format!("Bit {} of field '{}' is", msb, field.id)
+                } else {
+                    format!("Bits {}-{} of field '{}' are", lsb, msb, field.id)
+                };
+                issues.push(format!("{} in no sub-field", range));
+                (BitUse::Unassigned, field.id.clone())
+            }
+        };
+        spans.push(BitSpan {
+            start: offset + start as u32,
+            bits: len as u32,
+            usage,
+            owner: Some(name),
+        });
+        start += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::SubField;
+    use crate::models::protocol::Endianness;
+
+    fn sub(id: &str, lsb: u32, bits: u32) -> SubField {
+        SubField {
+            id: id.to_string(),
+            name: None,
+            description: None,
+            lsb,
+            bits,
+        }
+    }
+
+    #[test]
+    fn test_bit_accounting() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.frame_length = Some(40);
+                let mut flags = FieldRule::new("flags", FieldType::Input, FieldLength::Fixed(8));
+                flags.subfields = vec![sub("mode", 5, 3), sub("ack", 0, 1)];
+                p.add_field(flags)?;
+                p.add_field(FieldRule::new(
+                    "value",
+                    FieldType::Input,
+                    FieldLength::Fixed(16),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "reserved_1",
+                    FieldType::Fixed(0),
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+
+        let accounting = bit_accounting(&registry, "frame").unwrap();
+        assert_eq!(accounting.total_bits, 40);
+        let spans: Vec<(u32, u32, BitUse, Option<&str>)> = accounting
+            .spans
+            .iter()
+            .map(|s| (s.start, s.bits, s.usage, s.owner.as_deref()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 3, BitUse::Used, Some("flags.mode")),
+                (3, 4, BitUse::Unassigned, Some("flags")),
+                (7, 1, BitUse::Used, Some("flags.ack")),
+                (8, 16, BitUse::Used, Some("value")),
+                (24, 8, BitUse::Reserved, Some("reserved_1")),
+                (32, 8, BitUse::Unassigned, None),
+            ]
+        );
+        assert_eq!(accounting.count(BitUse::Used), 20);
+        assert_eq!(accounting.count(BitUse::Reserved), 8);
+        assert_eq!(accounting.count(BitUse::Unassigned), 12);
+        assert_eq!(
+            accounting.issues,
+            vec![
+                "Bits 1-4 of field 'flags' are in no sub-field",
+                "Bits 32-39 after the last field are padding up to the frame length of 'frame'",
+            ]
+        );
+
+        registry
+            .edit_protocol("frame", |p| {
+                p.frame_length = Some(24);
+                Ok(())
+            })
+            .unwrap();
+        let accounting = bit_accounting(&registry, "frame").unwrap();
+        assert!(accounting.issues[1].contains("8 more than the frame length"));
+
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+        assert!(bit_accounting(&registry, "frame").is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+/// A named range of bits in a packet, e.g. a region of an unknown payload worked out so far
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Bookmark {
+    pub name: String,
+    #[serde(default)]
+    pub comment: String,
+    pub bit_offset: usize,
+    pub bit_len: usize,
+}
+
+impl Bookmark {
+    /// The bytes the bookmark covers, including partly covered ones
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        self.bit_offset / 8..(self.bit_offset + self.bit_len).div_ceil(8)
+    }
+
+    /// Where the bookmark is, in bytes when it is whole bytes, e.g. `bytes 4-7` or `bits 33-35`
+    pub fn range_label(&self) -> String {
+        let last_bit = self.bit_offset + self.bit_len - 1;
+        if self.bit_offset.is_multiple_of(8) && self.bit_len.is_multiple_of(8) {
+            let bytes = self.byte_range();
+            if bytes.len() == 1 {
+                format!("byte {}", bytes.start)
+            } else {
+                format!("bytes {}-{}", bytes.start, bytes.end - 1)
+            }
+        } else if self.bit_len == 1 {
+            format!("bit {}", self.bit_offset)
+        } else {
+            format!("bits {}-{}", self.bit_offset, last_bit)
+        }
+    }
+}
+
+/// A packet with the bookmarks set on it
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AnnotatedPacket {
+    pub data: Vec<u8>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// The bookmarks of the packet with the bytes `data`
+pub fn bookmarks_of<'a>(packets: &'a [AnnotatedPacket], data: &[u8]) -> &'a [Bookmark] {
+    packets
+        .iter()
+        .find(|p| p.data == data)
+        .map(|p| p.bookmarks.as_slice())
+        .unwrap_or_default()
+}
+
+/// Bookmark a range of the packet with the bytes `data`, keeping the packet's bookmarks in
+/// order of their offset
+pub fn add_bookmark(
+    packets: &mut Vec<AnnotatedPacket>,
+    data: &[u8],
+    bookmark: Bookmark,
+) -> Result<(), String> {
+    if bookmark.name.trim().is_empty() {
+        return Err("A bookmark name is required".to_string());
+    }
+    if bookmark.bit_len == 0 {
+        return Err("A bookmark must cover at least one bit".to_string());
+    }
+    if bookmark.bit_offset + bookmark.bit_len > data.len() * 8 {
+        return Err(format!(
+            "Bookmark '{}' ends past the end of the {} byte packet",
+            bookmark.name,
+            data.len()
+        ));
+    }
+    let packet = match packets.iter().position(|p| p.data == data) {
+        Some(i) => &mut packets[i],
+        None => {
+            packets.push(AnnotatedPacket {
+                data: data.to_vec(),
+                bookmarks: Vec::new(),
+            });
+            packets.last_mut().unwrap()
+        }
+    };
+    let index = packet
+        .bookmarks
+        .partition_point(|b| (b.bit_offset, b.bit_len) <= (bookmark.bit_offset, bookmark.bit_len));
+    packet.bookmarks.insert(index, bookmark);
+    Ok(())
+}
+
+/// Remove a bookmark of a packet, and the packet once it has none
+pub fn remove_bookmark(packets: &mut Vec<AnnotatedPacket>, packet: usize, bookmark: usize) {
+    let Some(annotated) = packets.get_mut(packet) else {
+        return;
+    };
+    if bookmark < annotated.bookmarks.len() {
+        annotated.bookmarks.remove(bookmark);
+    }
+    if annotated.bookmarks.is_empty() {
+        packets.remove(packet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(name: &str, bit_offset: usize, bit_len: usize) -> Bookmark {
+        Bookmark {
+            name: name.to_string(),
+            comment: String::new(),
+            bit_offset,
+            bit_len,
+        }
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut packets = Vec::new();
+        let data = [0x01, 0x02, 0x03, 0x04];
+        add_bookmark(&mut packets, &data, bookmark("counter", 16, 16)).unwrap();
+        add_bookmark(&mut packets, &data, bookmark("flags", 3, 2)).unwrap();
+        add_bookmark(&mut packets, &[0xff], bookmark("status", 0, 8)).unwrap();
+        assert!(add_bookmark(&mut packets, &data, bookmark("past end", 24, 16)).is_err());
+        assert!(add_bookmark(&mut packets, &data, bookmark(" ", 0, 8)).is_err());
+
+        let names: Vec<&str> = bookmarks_of(&packets, &data)
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(names, ["flags", "counter"]);
+        assert_eq!(packets.len(), 2);
+        assert!(bookmarks_of(&packets, &[0x00]).is_empty());
+
+        let labels: Vec<String> = bookmarks_of(&packets, &data)
+            .iter()
+            .map(Bookmark::range_label)
+            .collect();
+        assert_eq!(labels, ["bits 3-4", "bytes 2-3"]);
+        assert_eq!(bookmarks_of(&packets, &data)[0].byte_range(), 0..1);
+
+        remove_bookmark(&mut packets, 1, 0);
+        assert_eq!(packets.len(), 1);
+    }
+}
@@ -0,0 +1,196 @@
+use super::field::{FieldLength, FieldRule, FieldType};
+use super::protocol::ProtocolRegistry;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+
+/// Largest number of values of a discriminator field that are checked for gaps
+const MAX_DOMAIN_SIZE: i128 = 1 << 16;
+
+/// The `parent_constraints` of the subprotocols of a protocol side by side
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConstraintMatrix {
+    /// the parent fields constrained by any subprotocol, sorted
+    pub fields: Vec<String>,
+    /// each subprotocol, sorted by ID, with the value it requires of each of `fields`
+    pub rows: Vec<(String, Vec<Option<i128>>)>,
+    /// pairs of subprotocols that a packet can match both of
+    pub overlaps: Vec<(String, String)>,
+    /// values of a field that no subprotocol applies to, where every subprotocol constrains
+    /// the field and its possible values are known
+    pub gaps: Vec<(String, Vec<RangeInclusive<i128>>)>,
+}
+
+impl ConstraintMatrix {
+    /// Whether a subprotocol shares packets with another one
+    pub fn overlaps(&self, protocol_id: &str) -> bool {
+        self.overlaps
+            .iter()
+            .any(|(a, b)| a == protocol_id || b == protocol_id)
+    }
+}
+
+/// Tabulate the constraints of the direct subprotocols of a protocol, finding subprotocols that
+/// apply to the same packets and discriminator values that none applies to
+pub fn constraint_matrix(
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+) -> Result<ConstraintMatrix, String> {
+    let parent_fields = registry.resolve_fields(protocol_id)?;
+    let mut children: Vec<_> = registry.children_of(protocol_id).collect();
+    children.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let fields: Vec<String> = children
+        .iter()
+        .flat_map(|c| c.parent_constraints.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let rows: Vec<(String, Vec<Option<i128>>)> = children
+        .iter()
+        .map(|child| {
+            let values = fields
+                .iter()
+                .map(|f| child.parent_constraints.get(f).copied())
+                .collect();
+            (child.id.clone(), values)
+        })
+        .collect();
+
+    // two subprotocols share packets unless they require different values of some field
+    let mut overlaps = Vec::new();
+    for (i, (a, a_values)) in rows.iter().enumerate() {
+        for (b, b_values) in &rows[i + 1..] {
+            let disjoint = a_values
+                .iter()
+                .zip(b_values)
+                .any(|(x, y)| matches!((x, y), (Some(x), Some(y)) if x != y));
+            if !disjoint {
+                overlaps.push((a.clone(), b.clone()));
+            }
+        }
+    }
+
+    let mut gaps = Vec::new();
+    for (column, field_id) in fields.iter().enumerate() {
+        let Some(handled) = rows
+            .iter()
+            .map(|(_, values)| values[column])
+            .collect::<Option<BTreeSet<i128>>>()
+        else {
+            continue; // a subprotocol without a constraint on the field takes every value
+        };
+        let Some(domain) = parent_fields
+            .iter()
+            .find(|f| f.id == *field_id)
+            .and_then(field_domain)
+        else {
+            continue;
+        };
+        let missing: Vec<i128> = domain
+            .into_iter()
+            .filter(|v| !handled.contains(v))
+            .collect();
+        if !missing.is_empty() {
+            gaps.push((field_id.clone(), to_ranges(&missing)));
+        }
+    }
+
+    Ok(ConstraintMatrix {
+        fields,
+        rows,
+        overlaps,
+        gaps,
+    })
+}
+
+/// Every value a field can have, if there are few enough to list
+fn field_domain(field: &FieldRule) -> Option<Vec<i128>> {
+    match &field.field_type {
+        FieldType::Enum(variants) => Some(variants.iter().map(|v| v.value).collect()),
+        FieldType::Fixed(value) => Some(vec![*value]),
+        FieldType::Range { min, max, .. } if max - min < MAX_DOMAIN_SIZE => {
+            Some((*min..=*max).collect())
+        }
+        FieldType::Input => match field.length {
+            FieldLength::Fixed(bits) if bits < 16 => Some((0..1i128 << bits).collect()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Sorted values as ranges of consecutive values
+fn to_ranges(values: &[i128]) -> Vec<RangeInclusive<i128>> {
+    let mut ranges: Vec<RangeInclusive<i128>> = Vec::new();
+    for &value in values {
+        match ranges.last_mut() {
+            Some(range) if *range.end() + 1 == value => *range = *range.start()..=value,
+            _ => ranges.push(value..=value),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_matrix_overlaps_and_gaps() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "kind",
+                    FieldType::Input,
+                    FieldLength::Fixed(3),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "version",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let children: [(&str, &[(&str, i128)]); 4] = [
+            ("ping", &[("kind", 0)]),
+            ("data", &[("kind", 2), ("version", 1)]),
+            ("data_v2", &[("kind", 2), ("version", 2)]),
+            ("data_any", &[("kind", 2)]),
+        ];
+        for (id, constraints) in children {
+            registry
+                .create_protocol(id, None, Endianness::Big, Some("frame".to_string()))
+                .unwrap();
+            registry
+                .edit_protocol(id, |p| {
+                    for (field, value) in constraints {
+                        p.set_parent_constraint(field, *value);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        let matrix = constraint_matrix(&registry, "frame").unwrap();
+        assert_eq!(matrix.fields, vec!["kind", "version"]);
+        assert_eq!(matrix.rows[0], ("data".to_string(), vec![Some(2), Some(1)]));
+        assert_eq!(matrix.rows[3], ("ping".to_string(), vec![Some(0), None]));
+        let overlaps: Vec<(&str, &str)> = matrix
+            .overlaps
+            .iter()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .collect();
+        assert_eq!(
+            overlaps,
+            vec![("data", "data_any"), ("data_any", "data_v2")]
+        );
+        assert!(!matrix.overlaps("ping"));
+        // version is left open by ping and data_any, so only kind has gaps
+        assert_eq!(matrix.gaps, vec![("kind".to_string(), vec![1..=1, 3..=7])]);
+    }
+}
@@ -0,0 +1,459 @@
+use super::field::{EnumVariant, FieldLength, FieldRule, FieldType};
+use super::protocol::{LengthField, Protocol, ProtocolLength};
+use std::fmt::Write;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum FieldChange {
+    Added(FieldRule),
+    Removed(FieldRule),
+    /// A field with the same ID exists in both protocols but differs; holds readable descriptions
+    Modified {
+        id: String,
+        details: Vec<String>,
+    },
+    Moved {
+        id: String,
+        from: usize,
+        to: usize,
+    },
+}
+
+/// Differences between two versions of a protocol, matching fields by ID
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProtocolDiff {
+    pub old_id: String,
+    pub new_id: String,
+    /// changes to protocol level properties such as name, endianness and length
+    pub protocol_changes: Vec<String>,
+    pub field_changes: Vec<FieldChange>,
+}
+
+impl ProtocolDiff {
+    pub fn is_empty(&self) -> bool {
+        self.protocol_changes.is_empty() && self.field_changes.is_empty()
+    }
+
+    /// Render the diff as a Markdown change report
+    pub fn to_report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Changes: `{}` → `{}`\n", self.old_id, self.new_id);
+
+        if self.is_empty() {
+            out.push_str("No changes.\n");
+            return out;
+        }
+
+        if !self.protocol_changes.is_empty() {
+            out.push_str("## Protocol\n\n");
+            for change in &self.protocol_changes {
+                let _ = writeln!(out, "- {}", change);
+            }
+            out.push('\n');
+        }
+
+        if !self.field_changes.is_empty() {
+            out.push_str("## Fields\n\n");
+            for change in &self.field_changes {
+                match change {
+                    FieldChange::Added(field) => {
+                        let _ = writeln!(
+                            out,
+                            "- **Added** `{}` ({}, {})",
+                            field.id,
+                            field.field_type.kind_name(),
+                            length_str(&field.length)
+                        );
+                    }
+                    FieldChange::Removed(field) => {
+                        let _ = writeln!(out, "- **Removed** `{}`", field.id);
+                    }
+                    FieldChange::Modified { id, details } => {
+                        let _ = writeln!(out, "- **Modified** `{}`", id);
+                        for detail in details {
+                            let _ = writeln!(out, "  - {}", detail);
+                        }
+                    }
+                    FieldChange::Moved { id, from, to } => {
+                        let _ =
+                            writeln!(out, "- **Moved** `{}` from position {} to {}", id, from, to);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Compare two protocols, e.g. two siblings or two revisions of the same protocol
+pub fn diff_protocols(old: &Protocol, new: &Protocol) -> ProtocolDiff {
+    let mut protocol_changes = Vec::new();
+    if old.name != new.name {
+        protocol_changes.push(format!(
+            "Name changed from {} to {}",
+            opt_str(&old.name),
+            opt_str(&new.name)
+        ));
+    }
+    if old.endianness != new.endianness {
+        protocol_changes.push(format!(
+            "Endianness changed from {:?} to {:?}",
+            old.endianness, new.endianness
+        ));
+    }
+    if old.length != new.length {
+        protocol_changes.push(format!(
+            "Length changed from {} to {}",
+            protocol_length_str(&old.length),
+            protocol_length_str(&new.length)
+        ));
+    }
+    if old.description != new.description {
+        protocol_changes.push("Description changed".to_string());
+    }
+    if old.notes != new.notes {
+        protocol_changes.push("Notes changed".to_string());
+    }
+    if old.validators != new.validators {
+        protocol_changes.push("Packet validators changed".to_string());
+    }
+    if old.max_length != new.max_length {
+        protocol_changes.push(format!(
+            "Length budget changed from {} to {}",
+            optional_bits_str(old.max_length),
+            optional_bits_str(new.max_length)
+        ));
+    }
+    if old.frame_length != new.frame_length {
+        protocol_changes.push(format!(
+            "Frame length changed from {} to {}",
+            optional_bits_str(old.frame_length),
+            optional_bits_str(new.frame_length)
+        ));
+    }
+    if old.length_field != new.length_field {
+        protocol_changes.push(format!(
+            "Length field changed from {} to {}",
+            length_field_str(old.length_field.as_ref()),
+            length_field_str(new.length_field.as_ref())
+        ));
+    }
+    if old.sequence_field != new.sequence_field {
+        protocol_changes.push(format!(
+            "Sequence number field changed from {} to {}",
+            optional_field_str(old.sequence_field.as_deref()),
+            optional_field_str(new.sequence_field.as_deref())
+        ));
+    }
+
+    let mut field_changes = Vec::new();
+    for old_field in &old.fields {
+        if !new.fields.iter().any(|f| f.id == old_field.id) {
+            field_changes.push(FieldChange::Removed(old_field.clone()));
+        }
+    }
+
+    // positions among the fields common to both versions, so that an insertion
+    // or removal does not report every following field as moved
+    let common_old: Vec<&str> = old
+        .fields
+        .iter()
+        .filter(|f| new.fields.iter().any(|n| n.id == f.id))
+        .map(|f| f.id.as_str())
+        .collect();
+    let common_new: Vec<&str> = new
+        .fields
+        .iter()
+        .filter(|f| old.fields.iter().any(|o| o.id == f.id))
+        .map(|f| f.id.as_str())
+        .collect();
+
+    for new_field in &new.fields {
+        let Some(old_field) = old.fields.iter().find(|f| f.id == new_field.id) else {
+            field_changes.push(FieldChange::Added(new_field.clone()));
+            continue;
+        };
+
+        let details = diff_fields(old_field, new_field);
+        if !details.is_empty() {
+            field_changes.push(FieldChange::Modified {
+                id: new_field.id.clone(),
+                details,
+            });
+        }
+
+        let from = common_old.iter().position(|id| *id == new_field.id);
+        let to = common_new.iter().position(|id| *id == new_field.id);
+        if let (Some(from), Some(to)) = (from, to)
+            && from != to
+        {
+            field_changes.push(FieldChange::Moved {
+                id: new_field.id.clone(),
+                from,
+                to,
+            });
+        }
+    }
+
+    ProtocolDiff {
+        old_id: old.id.clone(),
+        new_id: new.id.clone(),
+        protocol_changes,
+        field_changes,
+    }
+}
+
+fn diff_fields(old: &FieldRule, new: &FieldRule) -> Vec<String> {
+    let mut details = Vec::new();
+    if old.name != new.name {
+        details.push(format!(
+            "Name changed from {} to {}",
+            opt_str(&old.name),
+            opt_str(&new.name)
+        ));
+    }
+    if old.length != new.length {
+        details.push(format!(
+            "Length changed from {} to {}",
+            length_str(&old.length),
+            length_str(&new.length)
+        ));
+    }
+    if old.description != new.description {
+        details.push("Description changed".to_string());
+    }
+    if old.subfields != new.subfields {
+        details.push("Sub-fields changed".to_string());
+    }
+    if old.sign_encoding != new.sign_encoding {
+        details.push(format!(
+            "Sign encoding changed from {} to {}",
+            old.sign_encoding.label(),
+            new.sign_encoding.label()
+        ));
+    }
+    if old.severity != new.severity {
+        details.push(format!(
+            "Severity changed from {:?} to {:?}",
+            old.severity, new.severity
+        ));
+    }
+    if old.mapping != new.mapping {
+        details.push("Value mapping changed".to_string());
+    }
+
+    match (&old.field_type, &new.field_type) {
+        (FieldType::Enum(old_variants), FieldType::Enum(new_variants)) => {
+            details.extend(diff_variants(old_variants, new_variants));
+        }
+        (old_type, new_type) if old_type.kind_name() != new_type.kind_name() => {
+            details.push(format!(
+                "Type changed from {} to {}",
+                old_type.kind_name(),
+                new_type.kind_name()
+            ));
+        }
+        (FieldType::Fixed(a), FieldType::Fixed(b)) if a != b => {
+            details.push(format!("Fixed value changed from {} to {}", a, b));
+        }
+        (
+            FieldType::Range {
+                min: old_min,
+                max: old_max,
+                is_signed: old_signed,
+            },
+            FieldType::Range {
+                min: new_min,
+                max: new_max,
+                is_signed: new_signed,
+            },
+        ) if (old_min, old_max, old_signed) != (new_min, new_max, new_signed) => {
+            details.push(format!(
+                "Range changed from {}..={}{} to {}..={}{}",
+                old_min,
+                old_max,
+                if *old_signed { " (signed)" } else { "" },
+                new_min,
+                new_max,
+                if *new_signed { " (signed)" } else { "" },
+            ));
+        }
+        (old_type, new_type) if old_type.script() != new_type.script() => {
+            details.push(format!(
+                "Script changed from `{}` to `{}`",
+                old_type.script().unwrap_or_default(),
+                new_type.script().unwrap_or_default()
+            ));
+        }
+        _ => {}
+    }
+    details
+}
+
+fn diff_variants(old: &[EnumVariant], new: &[EnumVariant]) -> Vec<String> {
+    let mut details = Vec::new();
+    for variant in old {
+        if !new.iter().any(|v| v.value == variant.value) {
+            details.push(format!("Enum variant {} removed", variant_str(variant)));
+        }
+    }
+    for variant in new {
+        match old.iter().find(|v| v.value == variant.value) {
+            None => details.push(format!("Enum variant {} added", variant_str(variant))),
+            Some(old_variant) if old_variant.name != variant.name => details.push(format!(
+                "Enum variant {} renamed to {}",
+                variant_str(old_variant),
+                opt_str(&variant.name)
+            )),
+            Some(old_variant) if old_variant.description != variant.description => details.push(
+                format!("Enum variant {} description changed", variant_str(variant)),
+            ),
+            _ => {}
+        }
+    }
+    details
+}
+
+fn opt_str(value: &Option<String>) -> String {
+    match value {
+        Some(v) => format!("'{}'", v),
+        None => "(none)".to_string(),
+    }
+}
+
+fn variant_str(variant: &EnumVariant) -> String {
+    match &variant.name {
+        Some(name) => format!("{} ({})", variant.value, name),
+        None => variant.value.to_string(),
+    }
+}
+
+fn length_str(length: &FieldLength) -> String {
+    match length {
+        FieldLength::Fixed(bits) => format!("{} bits", bits),
+        FieldLength::Variable => "variable".to_string(),
+    }
+}
+
+fn optional_bits_str(bits: Option<u32>) -> String {
+    bits.map_or("none".to_string(), |bits| format!("{} bits", bits))
+}
+
+fn optional_field_str(field_id: Option<&str>) -> String {
+    field_id.map_or("none".to_string(), |id| format!("'{}'", id))
+}
+
+fn length_field_str(length: Option<&LengthField>) -> String {
+    match length {
+        None => "none".to_string(),
+        Some(length) if length.adjustment == 0 => format!("'{}'", length.field_id),
+        Some(length) => format!("'{}' {:+} bytes", length.field_id, length.adjustment),
+    }
+}
+
+fn protocol_length_str(length: &ProtocolLength) -> String {
+    match length {
+        ProtocolLength::Fixed(bits) => format!("{} bits", bits),
+        ProtocolLength::Variable(bits) => format!("variable ({} bits fixed prefix)", bits),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::protocol::Endianness;
+
+    fn proto(fields: Vec<FieldRule>) -> Protocol {
+        let mut proto = Protocol::new("proto", None, Endianness::Big, None);
+        for field in fields {
+            proto.add_field(field).unwrap();
+        }
+        proto
+    }
+
+    fn variant(value: i128, name: &str) -> EnumVariant {
+        EnumVariant {
+            value,
+            name: Some(name.to_string()),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_identical_protocols() {
+        let a = proto(vec![FieldRule::default()]);
+        let diff = diff_protocols(&a, &a.clone());
+        assert!(diff.is_empty());
+        assert!(diff.to_report().contains("No changes"));
+    }
+
+    #[test]
+    fn test_added_removed_and_modified_fields() {
+        let old = proto(vec![
+            FieldRule::new("version", FieldType::Fixed(1), FieldLength::Fixed(8)),
+            FieldRule::new("legacy", FieldType::Input, FieldLength::Fixed(8)),
+        ]);
+        let new = proto(vec![
+            FieldRule::new("version", FieldType::Fixed(2), FieldLength::Fixed(8)),
+            FieldRule::new("flags", FieldType::Input, FieldLength::Fixed(16)),
+        ]);
+
+        let diff = diff_protocols(&old, &new);
+        assert_eq!(
+            diff.protocol_changes,
+            vec!["Length changed from 16 bits to 24 bits"]
+        );
+        assert_eq!(diff.field_changes.len(), 3);
+        assert!(matches!(&diff.field_changes[0], FieldChange::Removed(f) if f.id == "legacy"));
+        assert_eq!(
+            diff.field_changes[1],
+            FieldChange::Modified {
+                id: "version".to_string(),
+                details: vec!["Fixed value changed from 1 to 2".to_string()],
+            }
+        );
+        assert!(matches!(&diff.field_changes[2], FieldChange::Added(f) if f.id == "flags"));
+    }
+
+    #[test]
+    fn test_enum_variant_changes() {
+        let old = proto(vec![FieldRule::new(
+            "kind",
+            FieldType::Enum(vec![variant(1, "Ping"), variant(2, "Pong")]),
+            FieldLength::Fixed(8),
+        )]);
+        let new = proto(vec![FieldRule::new(
+            "kind",
+            FieldType::Enum(vec![variant(1, "Echo"), variant(3, "Reset")]),
+            FieldLength::Fixed(8),
+        )]);
+
+        let diff = diff_protocols(&old, &new);
+        let FieldChange::Modified { details, .. } = &diff.field_changes[0] else {
+            panic!("expected a modified field");
+        };
+        assert_eq!(
+            details,
+            &vec![
+                "Enum variant 2 (Pong) removed".to_string(),
+                "Enum variant 1 (Ping) renamed to 'Echo'".to_string(),
+                "Enum variant 3 (Reset) added".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_moved_field() {
+        let a = FieldRule::new("a", FieldType::Input, FieldLength::Fixed(8));
+        let b = FieldRule::new("b", FieldType::Input, FieldLength::Fixed(8));
+        let old = proto(vec![a.clone(), b.clone()]);
+        let new = proto(vec![b, a]);
+
+        let diff = diff_protocols(&old, &new);
+        assert!(diff.field_changes.contains(&FieldChange::Moved {
+            id: "a".to_string(),
+            from: 0,
+            to: 1
+        }));
+        assert!(diff.to_report().contains("**Moved** `a`"));
+    }
+}
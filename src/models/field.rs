@@ -1,3 +1,6 @@
+use super::ident::check_identifier;
+use super::protocol::{Endianness, Severity};
+use crate::codec::bits::Bits;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -7,6 +10,126 @@ pub struct EnumVariant {
     pub description: Option<String>,
 }
 
+impl EnumVariant {
+    /// Parse variants from pasted text with one `value name [description]` entry per line.
+    /// Values may be decimal, `0x` hex or `0b` binary; columns may be separated by tabs,
+    /// commas, semicolons, `=` or spaces, so rows copied from spreadsheets and specs work.
+    pub fn parse_list(text: &str) -> Result<Vec<EnumVariant>, String> {
+        let mut variants = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut columns = line
+                .split(['\t', ',', ';', '='])
+                .map(str::trim)
+                .filter(|c| !c.is_empty());
+            let first = columns.next().unwrap_or_default();
+            // without an explicit separator, split the value off at the first space
+            let (value, name) = match first.split_once(char::is_whitespace) {
+                Some((value, name)) => (value, Some(name.trim())),
+                None => (first, columns.next()),
+            };
+
+            let value = parse_int(value).map_err(|e| format!("Line {}: {}", line_no + 1, e))?;
+            let description = columns.collect::<Vec<_>>().join(", ");
+            variants.push(EnumVariant {
+                value,
+                name: name.map(str::to_string),
+                description: (!description.is_empty()).then_some(description),
+            });
+        }
+        Ok(variants)
+    }
+}
+
+/// Add `imported` variants to `variants`; existing values get their name and description updated
+pub fn merge_enum_variants(variants: &mut Vec<EnumVariant>, imported: Vec<EnumVariant>) {
+    for variant in imported {
+        match variants.iter_mut().find(|v| v.value == variant.value) {
+            Some(existing) => {
+                if variant.name.is_some() {
+                    existing.name = variant.name;
+                }
+                if variant.description.is_some() {
+                    existing.description = variant.description;
+                }
+            }
+            None => variants.push(variant),
+        }
+    }
+}
+
+/// Parse an integer written in decimal, `0x` hex or `0b` binary, optionally negative, or a
+/// character literal like `'A'` or `'\n'`
+pub fn parse_int(text: &str) -> Result<i128, String> {
+    let text = text.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    if let Some(quoted) = digits
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return parse_char(quoted)
+            .map(|v| if negative { -v } else { v })
+            .ok_or_else(|| format!("{} is not a valid character", text));
+    }
+    let digits = digits.replace('_', "");
+
+    let (digits, radix) = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        (hex, 16)
+    } else if let Some(bin) = digits
+        .strip_prefix("0b")
+        .or_else(|| digits.strip_prefix("0B"))
+    {
+        (bin, 2)
+    } else {
+        (digits.as_str(), 10)
+    };
+
+    // the sign was taken off above; from_str_radix would accept a second one
+    if digits.starts_with(['+', '-']) {
+        return Err(format!("'{}' is not a valid number", text));
+    }
+    i128::from_str_radix(digits, radix)
+        .map(|v| if negative { -v } else { v })
+        .map_err(|_| format!("'{}' is not a valid number", text))
+}
+
+/// The code point of the character between the quotes of a character literal, which may be
+/// an escape: `\n`, `\r`, `\t`, `\0`, `\\`, `\'` or `\x41`
+fn parse_char(quoted: &str) -> Option<i128> {
+    let mut chars = quoted.chars();
+    let c = match (chars.next()?, chars.next()) {
+        (c, None) if c != '\\' => c,
+        ('\\', Some(escape)) => {
+            let rest = chars.as_str();
+            let c = match escape {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                '\\' | '\'' => escape,
+                'x' if rest.len() == 2 => return u8::from_str_radix(rest, 16).ok().map(i128::from),
+                _ => return None,
+            };
+            if !rest.is_empty() {
+                return None;
+            }
+            c
+        }
+        _ => return None,
+    };
+    Some(c as i128)
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub enum FieldType {
     Fixed(i128),
@@ -16,8 +139,38 @@ pub enum FieldType {
         max: i128,
         is_signed: bool,
     },
-    Expr(String), // rhai script to compute the value
-    Input,        // data provided by user input
+    Expr(String),    // rhai script to compute the value
+    Derived(String), // rhai script evaluated on decode, not serialized into the packet
+    Input,           // data provided by user input
+}
+
+impl FieldType {
+    /// Short name of the field type for display
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            FieldType::Fixed(_) => "Fixed",
+            FieldType::Enum(_) => "Enum",
+            FieldType::Range { .. } => "Range",
+            FieldType::Expr(_) => "Expr",
+            FieldType::Derived(_) => "Derived",
+            FieldType::Input => "Input",
+        }
+    }
+
+    /// The rhai script of an expression or derived field
+    pub fn script(&self) -> Option<&str> {
+        match self {
+            FieldType::Expr(script) | FieldType::Derived(script) => Some(script),
+            _ => None,
+        }
+    }
+
+    pub fn script_mut(&mut self) -> Option<&mut String> {
+        match self {
+            FieldType::Expr(script) | FieldType::Derived(script) => Some(script),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -27,6 +180,226 @@ pub enum FieldLength {
     Variable,
 }
 
+/// How the values of a field are written in the inspector and packet lists
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum DisplayFormat {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
+    Octal,
+    Ascii,
+}
+
+impl DisplayFormat {
+    pub const ALL: [DisplayFormat; 5] = [
+        DisplayFormat::Decimal,
+        DisplayFormat::Hex,
+        DisplayFormat::Binary,
+        DisplayFormat::Octal,
+        DisplayFormat::Ascii,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DisplayFormat::Decimal => "Decimal",
+            DisplayFormat::Hex => "Hex",
+            DisplayFormat::Binary => "Binary",
+            DisplayFormat::Octal => "Octal",
+            DisplayFormat::Ascii => "ASCII",
+        }
+    }
+}
+
+/// How the negative values of a signed field are written on the wire
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SignEncoding {
+    #[default]
+    TwosComplement,
+    /// the most significant bit is the sign and the other bits are the magnitude
+    SignMagnitude,
+}
+
+impl SignEncoding {
+    pub const ALL: [SignEncoding; 2] = [SignEncoding::TwosComplement, SignEncoding::SignMagnitude];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SignEncoding::TwosComplement => "Two's Complement",
+            SignEncoding::SignMagnitude => "Sign-Magnitude",
+        }
+    }
+
+    /// The signed value of the low `bits` bits of `raw`
+    pub fn decode(self, raw: u128, bits: u32) -> i128 {
+        if bits == 0 || bits > 128 {
+            return raw as i128;
+        }
+        match self {
+            // shifted up and back down to copy the sign bit into the bits above it
+            SignEncoding::TwosComplement => ((raw << (128 - bits)) as i128) >> (128 - bits),
+            SignEncoding::SignMagnitude => {
+                let magnitude = (raw & ((1u128 << (bits - 1)) - 1)) as i128;
+                if (raw >> (bits - 1)) & 1 == 1 {
+                    -magnitude
+                } else {
+                    magnitude
+                }
+            }
+        }
+    }
+
+    /// The unsigned value whose low `bits` bits represent `value`, or `None` if it does not
+    /// fit. Negative values stay negative in two's complement, whose encoding sign-extends them.
+    pub fn encode(self, value: i128, bits: u32) -> Option<i128> {
+        if !self.fits(value, bits) {
+            return None;
+        }
+        match self {
+            SignEncoding::SignMagnitude if value < 0 => Some((1i128 << (bits - 1)) | -value),
+            _ => Some(value),
+        }
+    }
+
+    /// Whether a signed field of `bits` bits can hold `value`
+    pub fn fits(self, value: i128, bits: u32) -> bool {
+        match self {
+            SignEncoding::TwosComplement => fits_in_bits(value, bits, true),
+            // no negative zero, and the sign bit of 128-bit values is out of reach
+            SignEncoding::SignMagnitude => {
+                (1..128).contains(&bits) && value.unsigned_abs() < 1u128 << (bits - 1)
+            }
+        }
+    }
+}
+
+/// A raw value and the text it is shown as
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MappingEntry {
+    pub raw: i128,
+    pub shown: String,
+}
+
+/// A point of a calibration curve: a raw value and the physical value it stands for
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct CurvePoint {
+    pub raw: f64,
+    pub value: f64,
+}
+
+/// How raw values of a field are translated for display, e.g. ADC counts to a temperature.
+/// Unlike an enum, a mapping does not restrict the values the field may have.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum ValueMapping {
+    /// raw values with the text they are shown as; other values are shown as they are
+    Table(Vec<MappingEntry>),
+    /// points of a curve in order of their raw values, interpolated linearly between them and
+    /// extended past the first and last
+    Curve {
+        points: Vec<CurvePoint>,
+        unit: Option<String>,
+    },
+}
+
+impl ValueMapping {
+    /// The text a raw value is shown as, if the mapping covers it
+    pub fn map(&self, raw: i128) -> Option<String> {
+        match self {
+            ValueMapping::Table(entries) => entries
+                .iter()
+                .find(|e| e.raw == raw)
+                .map(|e| e.shown.clone()),
+            ValueMapping::Curve { points, unit } => {
+                let value = interpolate(points, raw as f64)?;
+                // rounded so that float noise does not show
+                let value = (value * 1000.0).round() / 1000.0;
+                Some(match unit.as_deref().filter(|u| !u.is_empty()) {
+                    Some(unit) => format!("{} {}", value, unit),
+                    None => value.to_string(),
+                })
+            }
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        match self {
+            ValueMapping::Table(entries) => {
+                for (i, entry) in entries.iter().enumerate() {
+                    if entries[..i].iter().filter(|e| e.raw == entry.raw).count() == 1 {
+                        errors.push(format!(
+                            "Mapped value {} is listed more than once",
+                            entry.raw
+                        ));
+                    }
+                }
+            }
+            ValueMapping::Curve { points, .. } => {
+                if points.len() < 2 {
+                    errors.push("A curve needs at least two points".to_string());
+                }
+                if points.windows(2).any(|w| w[0].raw >= w[1].raw) {
+                    errors
+                        .push("Curve points must be in increasing order of raw value".to_string());
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// The value of a piecewise-linear curve at `x`, using the first or last segment past its ends
+fn interpolate(points: &[CurvePoint], x: f64) -> Option<f64> {
+    let segment = points
+        .windows(2)
+        .find(|w| x <= w[1].raw)
+        .or_else(|| points.windows(2).last())?;
+    let (a, b) = (segment[0], segment[1]);
+    if a.raw == b.raw {
+        return None;
+    }
+    Some(a.value + (x - a.raw) * (b.value - a.value) / (b.raw - a.raw))
+}
+
+/// A named range of bits within the value of a field, e.g. the mode in bits 4-6 of a status
+/// byte, shown and edited on its own but encoded as part of the field
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SubField {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// the lowest bit of the range, counted from the least significant bit of the value as in
+    /// datasheets
+    pub lsb: u32,
+    pub bits: u32,
+}
+
+impl SubField {
+    fn mask(&self) -> i128 {
+        (1i128 << self.bits) - 1
+    }
+
+    /// The value of the sub-field in the value of its field
+    pub fn extract(&self, value: i128) -> i128 {
+        (value >> self.lsb) & self.mask()
+    }
+
+    /// The value of the field with the sub-field set to `sub_value`, which is truncated to
+    /// the sub-field's width
+    pub fn insert(&self, value: i128, sub_value: i128) -> i128 {
+        (value & !(self.mask() << self.lsb)) | ((sub_value & self.mask()) << self.lsb)
+    }
+
+    /// Where the sub-field is in the value, e.g. `bits 4-6` or `bit 7`
+    pub fn range_label(&self) -> String {
+        if self.bits == 1 {
+            format!("bit {}", self.lsb)
+        } else {
+            format!("bits {}-{}", self.lsb, self.lsb + self.bits - 1)
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct FieldRule {
     pub id: String,
@@ -34,6 +407,28 @@ pub struct FieldRule {
     pub field_type: FieldType,
     pub length: FieldLength,
     pub description: Option<String>,
+    /// RGB color the field is shown in; the next color of the palette when not set
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    /// how values are shown; the application-wide default when not set
+    #[serde(default)]
+    pub display: Option<DisplayFormat>,
+    /// byte order of the field when it differs from that of its protocol
+    #[serde(default)]
+    pub endianness: Option<Endianness>,
+    /// named bit ranges within the value of the field
+    #[serde(default)]
+    pub subfields: Vec<SubField>,
+    /// how values are translated for display
+    #[serde(default)]
+    pub mapping: Option<ValueMapping>,
+    /// how negative values are written, for signed range fields
+    #[serde(default)]
+    pub sign_encoding: SignEncoding,
+    /// how a value breaking the fixed value, enum or range of the field is reported: an error
+    /// stops decoding and encoding, a warning is reported as an issue of the packet
+    #[serde(default)]
+    pub severity: Severity,
 }
 
 impl FieldRule {
@@ -44,8 +439,193 @@ impl FieldRule {
             field_type,
             length,
             description: None,
+            color: None,
+            display: None,
+            endianness: None,
+            subfields: Vec::new(),
+            mapping: None,
+            sign_encoding: SignEncoding::TwosComplement,
+            severity: Severity::Error,
         }
     }
+
+    /// Byte order of the field in a protocol with the given byte order
+    pub fn byte_order(&self, protocol: Endianness) -> Endianness {
+        self.endianness.unwrap_or(protocol)
+    }
+
+    /// Whether values are signed numbers on the wire, as for signed range fields
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self.field_type,
+            FieldType::Range {
+                is_signed: true,
+                ..
+            }
+        )
+    }
+
+    /// Whether negative values have a sign bit and the magnitude rather than being two's
+    /// complement
+    pub fn is_sign_magnitude(&self) -> bool {
+        self.is_signed() && self.sign_encoding == SignEncoding::SignMagnitude
+    }
+
+    /// Virtual fields are computed from other fields and take up no space on the wire.
+    pub fn is_virtual(&self) -> bool {
+        matches!(self.field_type, FieldType::Derived(_))
+    }
+
+    /// Check the rule for inconsistencies, returning a message for each problem found
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Err(e) = check_identifier("Field", &self.id) {
+            errors.push(e.to_string());
+        }
+
+        let bits = match self.length {
+            _ if self.is_virtual() => None,
+            FieldLength::Fixed(0) => {
+                errors.push("Field length must be at least 1 bit".to_string());
+                None
+            }
+            FieldLength::Fixed(bits) => Some(bits),
+            FieldLength::Variable => None,
+        };
+
+        match &self.field_type {
+            FieldType::Fixed(value) => {
+                if let Some(bits) = bits
+                    && !fits_in_bits(*value, bits, *value < 0)
+                {
+                    errors.push(format!(
+                        "Fixed value {} does not fit in {} bits",
+                        value, bits
+                    ));
+                }
+            }
+            FieldType::Enum(variants) => {
+                if variants.is_empty() {
+                    errors.push("Enum must have at least one variant".to_string());
+                }
+                for (i, variant) in variants.iter().enumerate() {
+                    let earlier = variants[..i].iter().filter(|v| v.value == variant.value);
+                    if earlier.count() == 1 {
+                        // reported once, on the second occurrence
+                        errors.push(format!(
+                            "Enum value {} is defined more than once",
+                            variant.value
+                        ));
+                    }
+                    if let Some(bits) = bits
+                        && !fits_in_bits(variant.value, bits, variant.value < 0)
+                    {
+                        errors.push(format!(
+                            "Enum value {} does not fit in {} bits",
+                            variant.value, bits
+                        ));
+                    }
+                }
+            }
+            FieldType::Range {
+                min,
+                max,
+                is_signed,
+            } => {
+                if min > max {
+                    errors.push(format!(
+                        "Range minimum {} is greater than maximum {}",
+                        min, max
+                    ));
+                }
+                if !is_signed && *min < 0 {
+                    errors.push("Unsigned range cannot have a negative minimum".to_string());
+                }
+                if let Some(bits) = bits {
+                    for bound in [min, max] {
+                        let fits = if *is_signed {
+                            self.sign_encoding.fits(*bound, bits)
+                        } else {
+                            fits_in_bits(*bound, bits, false)
+                        };
+                        if !fits {
+                            errors.push(format!(
+                                "Range bound {} does not fit in {} bits",
+                                bound, bits
+                            ));
+                        }
+                    }
+                }
+            }
+            FieldType::Expr(script) | FieldType::Derived(script) => {
+                if script.trim().is_empty() {
+                    errors.push("Expression cannot be empty".to_string());
+                }
+            }
+            FieldType::Input => {}
+        }
+
+        if !self.subfields.is_empty() {
+            errors.extend(self.validate_subfields(bits));
+        }
+        if let Some(mapping) = &self.mapping {
+            errors.extend(mapping.validate());
+        }
+        errors
+    }
+
+    fn validate_subfields(&self, bits: Option<u32>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let Some(bits) = bits.filter(|bits| *bits <= 128) else {
+            errors.push(
+                "Only fixed-length fields of at most 128 bits can have sub-fields".to_string(),
+            );
+            return errors;
+        };
+        for (i, sub) in self.subfields.iter().enumerate() {
+            if let Err(e) = check_identifier("Sub-field", &sub.id) {
+                errors.push(e.to_string());
+            }
+            if self.subfields[..i].iter().any(|s| s.id == sub.id) {
+                errors.push(format!("Sub-field ID '{}' is used more than once", sub.id));
+            }
+            if !(1..=64).contains(&sub.bits) {
+                errors.push(format!("Sub-field '{}' must be 1 to 64 bits wide", sub.id));
+            } else if sub.lsb + sub.bits > bits {
+                errors.push(format!(
+                    "Sub-field '{}' ({}) does not fit in the {} bit field",
+                    sub.id,
+                    sub.range_label(),
+                    bits
+                ));
+            } else if let Some(other) = self.subfields[..i]
+                .iter()
+                .find(|s| s.lsb < sub.lsb + sub.bits && sub.lsb < s.lsb + s.bits)
+            {
+                errors.push(format!(
+                    "Sub-fields '{}' and '{}' overlap",
+                    other.id, sub.id
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// Whether `value` can be represented in `bits` bits, as two's complement if `signed`
+pub fn fits_in_bits(value: i128, bits: u32, signed: bool) -> bool {
+    if bits >= 128 {
+        return signed || value >= 0;
+    }
+    if signed {
+        if bits == 0 {
+            return value == 0;
+        }
+        let limit = 1i128 << (bits - 1);
+        (-limit..limit).contains(&value)
+    } else {
+        value >= 0 && value < (1i128 << bits)
+    }
 }
 
 impl Default for FieldRule {
@@ -56,6 +636,13 @@ impl Default for FieldRule {
             field_type: FieldType::Fixed(0),
             length: FieldLength::Fixed(8),
             description: None,
+            color: None,
+            display: None,
+            endianness: None,
+            subfields: Vec::new(),
+            mapping: None,
+            sign_encoding: SignEncoding::TwosComplement,
+            severity: Severity::Error,
         }
     }
 }
@@ -63,12 +650,13 @@ impl Default for FieldRule {
 /// An instance of a field in a protocol message
 pub struct Field {
     pub rule_id: String,
-    pub value: Vec<u8>,
+    /// exactly the bits of the field, so a value shorter than a byte is not ambiguous
+    pub value: Bits,
     pub ignore_rules: bool,
 }
 
 impl Field {
-    pub fn new(rule_id: &str, value: Vec<u8>, ignore_rules: bool) -> Self {
+    pub fn new(rule_id: &str, value: Bits, ignore_rules: bool) -> Self {
         Self {
             rule_id: rule_id.to_string(),
             value,
@@ -76,7 +664,7 @@ impl Field {
         }
     }
 
-    pub fn set_value(&mut self, value: Vec<u8>) {
+    pub fn set_value(&mut self, value: Bits) {
         self.value = value;
     }
 
@@ -98,4 +686,236 @@ mod tests {
         assert_eq!(custom_field.id, "version");
         assert_eq!(custom_field.field_type, FieldType::Fixed(4));
     }
+
+    #[test]
+    fn test_fits_in_bits() {
+        assert!(fits_in_bits(255, 8, false));
+        assert!(!fits_in_bits(256, 8, false));
+        assert!(!fits_in_bits(-1, 8, false));
+        assert!(fits_in_bits(-128, 8, true));
+        assert!(!fits_in_bits(128, 8, true));
+        assert!(fits_in_bits(i128::MAX, 128, false));
+    }
+
+    #[test]
+    fn test_sign_encoding() {
+        let twos = SignEncoding::TwosComplement;
+        assert_eq!(twos.decode(0xFFF, 12), -1);
+        assert_eq!(twos.decode(0x800, 12), -2048);
+        assert_eq!(twos.decode(0x7FF, 12), 2047);
+        assert_eq!(twos.decode(1, 1), -1);
+        assert_eq!(twos.decode(u128::MAX, 128), -1);
+        assert_eq!(twos.encode(-2048, 12), Some(-2048));
+        assert_eq!(twos.encode(2048, 12), None);
+
+        let sign_magnitude = SignEncoding::SignMagnitude;
+        assert_eq!(sign_magnitude.decode(0b1000_0101, 8), -5);
+        assert_eq!(sign_magnitude.decode(0b0000_0101, 8), 5);
+        // negative zero
+        assert_eq!(sign_magnitude.decode(0b1000_0000, 8), 0);
+        assert_eq!(sign_magnitude.encode(-5, 8), Some(0b1000_0101));
+        assert_eq!(sign_magnitude.encode(127, 8), Some(127));
+        assert_eq!(sign_magnitude.encode(-128, 8), None);
+
+        let mut field = FieldRule::new(
+            "offset",
+            FieldType::Range {
+                min: -128,
+                max: 127,
+                is_signed: true,
+            },
+            FieldLength::Fixed(8),
+        );
+        assert!(field.is_signed());
+        assert!(field.validate().is_empty());
+        field.sign_encoding = sign_magnitude;
+        assert_eq!(
+            field.validate(),
+            ["Range bound -128 does not fit in 8 bits"]
+        );
+    }
+
+    #[test]
+    fn test_validate_field_rule() {
+        assert!(FieldRule::default().validate().is_empty());
+
+        let too_big = FieldRule::new("f", FieldType::Fixed(300), FieldLength::Fixed(8));
+        assert_eq!(
+            too_big.validate(),
+            vec!["Fixed value 300 does not fit in 8 bits"]
+        );
+
+        let inverted = FieldRule::new(
+            "f",
+            FieldType::Range {
+                min: 10,
+                max: 5,
+                is_signed: false,
+            },
+            FieldLength::Fixed(8),
+        );
+        assert_eq!(
+            inverted.validate(),
+            vec!["Range minimum 10 is greater than maximum 5"]
+        );
+
+        let signed = FieldRule::new(
+            "f",
+            FieldType::Range {
+                min: -128,
+                max: 127,
+                is_signed: true,
+            },
+            FieldLength::Fixed(8),
+        );
+        assert!(signed.validate().is_empty());
+
+        let empty = FieldRule::new("", FieldType::Enum(vec![]), FieldLength::Fixed(0));
+        assert_eq!(empty.validate().len(), 3);
+    }
+
+    #[test]
+    fn test_validate_duplicate_enum_values() {
+        let variant = |value| EnumVariant {
+            value,
+            name: None,
+            description: None,
+        };
+        let field = FieldRule::new(
+            "kind",
+            FieldType::Enum(vec![variant(1), variant(2), variant(1), variant(1)]),
+            FieldLength::Fixed(8),
+        );
+        assert_eq!(
+            field.validate(),
+            vec!["Enum value 1 is defined more than once"]
+        );
+    }
+
+    #[test]
+    fn test_subfields() {
+        let sub = |id: &str, lsb, bits| SubField {
+            id: id.to_string(),
+            name: None,
+            description: None,
+            lsb,
+            bits,
+        };
+        let mode = sub("mode", 4, 3);
+        assert_eq!(mode.extract(0b1101_0110), 0b101);
+        assert_eq!(mode.insert(0b1101_0110, 0b010), 0b1010_0110);
+        assert_eq!(mode.insert(0, 0xff), 0b0111_0000);
+        assert_eq!(mode.range_label(), "bits 4-6");
+
+        let mut field = FieldRule::new("status", FieldType::Input, FieldLength::Fixed(8));
+        field.subfields = vec![mode.clone(), sub("ready", 7, 1)];
+        assert!(field.validate().is_empty());
+        field.subfields = vec![mode.clone(), sub("error", 6, 2), sub("mode", 0, 9)];
+        assert_eq!(
+            field.validate(),
+            vec![
+                "Sub-fields 'mode' and 'error' overlap",
+                "Sub-field ID 'mode' is used more than once",
+                "Sub-field 'mode' (bits 0-8) does not fit in the 8 bit field",
+            ]
+        );
+        field.length = FieldLength::Variable;
+        field.subfields = vec![mode];
+        assert_eq!(field.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_value_mapping() {
+        let table = ValueMapping::Table(vec![
+            MappingEntry {
+                raw: 0xFF,
+                shown: "not connected".to_string(),
+            },
+            MappingEntry {
+                raw: 0xFF,
+                shown: "again".to_string(),
+            },
+        ]);
+        assert_eq!(table.map(0xFF).as_deref(), Some("not connected"));
+        assert_eq!(table.map(1), None);
+        assert_eq!(table.validate().len(), 1);
+
+        let point = |raw, value| CurvePoint { raw, value };
+        let curve = ValueMapping::Curve {
+            points: vec![point(0.0, -40.0), point(1000.0, 60.0), point(2000.0, 80.0)],
+            unit: Some("°C".to_string()),
+        };
+        assert!(curve.validate().is_empty());
+        assert_eq!(curve.map(500).as_deref(), Some("10 °C"));
+        assert_eq!(curve.map(1500).as_deref(), Some("70 °C"));
+        // extended past the ends
+        assert_eq!(curve.map(3000).as_deref(), Some("100 °C"));
+        assert_eq!(curve.map(-100).as_deref(), Some("-50 °C"));
+
+        let unordered = ValueMapping::Curve {
+            points: vec![point(1.0, 0.0), point(1.0, 1.0)],
+            unit: None,
+        };
+        assert_eq!(unordered.map(1), None);
+        assert_eq!(unordered.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_enum_variant_list() {
+        let text = "1\tPing\tLiveness check\n0x02, Pong\n\n3 = Reset\n0b100 Shutdown now";
+        let variants = EnumVariant::parse_list(text).unwrap();
+
+        assert_eq!(variants.len(), 4);
+        assert_eq!(variants[0].name.as_deref(), Some("Ping"));
+        assert_eq!(variants[0].description.as_deref(), Some("Liveness check"));
+        assert_eq!(variants[1].value, 2);
+        assert_eq!(variants[2].name.as_deref(), Some("Reset"));
+        assert_eq!(variants[3].value, 4);
+        assert_eq!(variants[3].name.as_deref(), Some("Shutdown now"));
+
+        assert!(EnumVariant::parse_list("one, Ping").is_err());
+    }
+
+    #[test]
+    fn test_merge_enum_variants() {
+        let mut variants = EnumVariant::parse_list("1 Ping\n2 Pong").unwrap();
+        let imported = EnumVariant::parse_list("2 Echo\n3 Reset").unwrap();
+
+        merge_enum_variants(&mut variants, imported);
+        let names: Vec<_> = variants
+            .iter()
+            .map(|v| v.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, vec!["Ping", "Echo", "Reset"]);
+    }
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int("42"), Ok(42));
+        assert_eq!(parse_int("-0x10"), Ok(-16));
+        assert_eq!(parse_int("0b1010_1010"), Ok(0xAA));
+        assert!(parse_int("12a").is_err());
+        assert!(parse_int("--5").is_err());
+        assert!(parse_int("-+5").is_err());
+        assert!(parse_int("+5").is_err());
+        assert!(parse_int("0x-5").is_err());
+        assert!(parse_int("-0b+1").is_err());
+        assert_eq!(parse_int("'A'"), Ok(65));
+        assert_eq!(parse_int(" '\\n' "), Ok(10));
+        assert_eq!(parse_int("'\\x7f'"), Ok(0x7f));
+        assert_eq!(parse_int("'_'"), Ok(95));
+        assert!(parse_int("'AB'").is_err());
+        assert!(parse_int("''").is_err());
+    }
+
+    #[test]
+    fn test_derived_field_is_virtual() {
+        let derived = FieldRule::new(
+            "temperature_celsius",
+            FieldType::Derived("raw_temp / 10".to_string()),
+            FieldLength::Fixed(0),
+        );
+        assert!(derived.is_virtual());
+        assert!(!FieldRule::default().is_virtual());
+    }
 }
@@ -0,0 +1,167 @@
+use super::protocol::Protocol;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A snapshot of a protocol recorded by an explicit commit
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ProtocolRevision {
+    pub message: String,
+    /// seconds since the Unix epoch
+    pub timestamp: u64,
+    pub snapshot: Protocol,
+}
+
+impl ProtocolRevision {
+    /// Commit time formatted as `YYYY-MM-DD HH:MM UTC`
+    pub fn formatted_time(&self) -> String {
+        format_timestamp(self.timestamp)
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Seconds since the Unix epoch formatted as `YYYY-MM-DD HH:MM UTC`
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let secs_of_day = timestamp % 86400;
+
+    // civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
+
+/// Committed revisions of all protocols in a project, oldest first
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct RevisionHistory {
+    revisions: Vec<ProtocolRevision>,
+}
+
+impl RevisionHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a snapshot of the protocol's current state
+    pub fn commit(&mut self, protocol: &Protocol, message: &str) -> Result<(), String> {
+        if message.trim().is_empty() {
+            return Err("A commit message is required".to_string());
+        }
+
+        if let Some(last) = self.latest(&protocol.id)
+            && last.snapshot == *protocol
+        {
+            return Err(format!(
+                "Protocol '{}' has no changes since the last revision",
+                protocol.id
+            ));
+        }
+
+        self.revisions.push(ProtocolRevision {
+            message: message.trim().to_string(),
+            timestamp: now_timestamp(),
+            snapshot: protocol.clone(),
+        });
+        Ok(())
+    }
+
+    /// Revisions of a protocol, newest first
+    pub fn revisions_of(&self, protocol_id: &str) -> Vec<&ProtocolRevision> {
+        self.revisions
+            .iter()
+            .rev()
+            .filter(|r| r.snapshot.id == protocol_id)
+            .collect()
+    }
+
+    pub fn latest(&self, protocol_id: &str) -> Option<&ProtocolRevision> {
+        self.revisions_of(protocol_id).into_iter().next()
+    }
+
+    /// Keep the history attached to a protocol whose ID changed
+    pub fn rename_protocol(&mut self, old_id: &str, new_id: &str) {
+        for revision in &mut self.revisions {
+            if revision.snapshot.id == old_id {
+                revision.snapshot.id = new_id.to_string();
+            }
+            if revision.snapshot.parent_id.as_deref() == Some(old_id) {
+                revision.snapshot.parent_id = Some(new_id.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::FieldRule;
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_commit_and_list_revisions() {
+        let mut history = RevisionHistory::new();
+        let mut proto = Protocol::new("proto", None, Endianness::Big, None);
+
+        history.commit(&proto, "initial").unwrap();
+        proto.add_field(FieldRule::default()).unwrap();
+        history.commit(&proto, "add field").unwrap();
+
+        let revisions = history.revisions_of("proto");
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].message, "add field");
+        assert_eq!(revisions[1].snapshot.fields.len(), 0);
+        assert!(history.revisions_of("other").is_empty());
+    }
+
+    #[test]
+    fn test_commit_without_changes_or_message() {
+        let mut history = RevisionHistory::new();
+        let proto = Protocol::new("proto", None, Endianness::Big, None);
+
+        assert!(history.commit(&proto, "  ").is_err());
+        history.commit(&proto, "initial").unwrap();
+        assert!(history.commit(&proto, "again").is_err());
+    }
+
+    #[test]
+    fn test_rename_protocol() {
+        let mut history = RevisionHistory::new();
+        let proto = Protocol::new("proto", None, Endianness::Big, None);
+        history.commit(&proto, "initial").unwrap();
+
+        history.rename_protocol("proto", "renamed");
+        assert!(history.revisions_of("proto").is_empty());
+        assert_eq!(history.revisions_of("renamed").len(), 1);
+    }
+
+    #[test]
+    fn test_formatted_time() {
+        let revision = ProtocolRevision {
+            message: "initial".to_string(),
+            timestamp: 1_700_000_000,
+            snapshot: Protocol::new("proto", None, Endianness::Big, None),
+        };
+        assert_eq!(revision.formatted_time(), "2023-11-14 22:13 UTC");
+    }
+}
@@ -0,0 +1,91 @@
+//! Rules for protocol and field IDs. IDs are variable names in expressions and identifiers in
+//! generated code, so they are limited to what every target language accepts.
+
+use crate::error::{BitLoomError, IdentifierProblem};
+
+/// Check that an ID is ASCII letters, digits and underscores, and does not start with a digit.
+/// `kind` names what the ID is of in the message, e.g. "Field".
+pub fn check_identifier(kind: &'static str, id: &str) -> Result<(), BitLoomError> {
+    let problem = if id.is_empty() {
+        IdentifierProblem::Empty
+    } else if let Some(c) = id
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_'))
+    {
+        IdentifierProblem::InvalidChar(c)
+    } else if id.starts_with(|c: char| c.is_ascii_digit()) {
+        IdentifierProblem::LeadingDigit
+    } else {
+        return Ok(());
+    };
+    Err(BitLoomError::InvalidIdentifier {
+        kind,
+        id: id.to_string(),
+        problem,
+    })
+}
+
+/// An ID made from a display name, e.g. `sensor_temperature` from "Sensor Température".
+/// Accented Latin letters lose their accents, and other characters become underscores.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'à'..='å' => 'a',
+            'ç' => 'c',
+            'è'..='ë' => 'e',
+            'ì'..='ï' => 'i',
+            'ñ' => 'n',
+            'ò'..='ö' | 'ø' => 'o',
+            'ù'..='ü' => 'u',
+            'ý' | 'ÿ' => 'y',
+            'ß' => {
+                slug.push_str("ss");
+                continue;
+            }
+            c if c.is_ascii_alphanumeric() => c,
+            _ => '_',
+        };
+        // one underscore for a run of spaces and symbols
+        if c != '_' || !slug.ends_with('_') {
+            slug.push(c);
+        }
+    }
+    let slug = slug.trim_matches('_');
+    if slug.is_empty() {
+        "_".to_string()
+    } else if slug.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", slug)
+    } else {
+        slug.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifiers() {
+        assert!(check_identifier("Field", "crc_16").is_ok());
+        assert!(check_identifier("Field", "_reserved").is_ok());
+        assert!(check_identifier("Field", "").is_err());
+        assert!(matches!(
+            check_identifier("Field", "2nd"),
+            Err(BitLoomError::InvalidIdentifier {
+                problem: IdentifierProblem::LeadingDigit,
+                ..
+            })
+        ));
+        assert!(check_identifier("Field", "msg type").is_err());
+        assert!(check_identifier("Protocol", "größe").is_err());
+
+        assert_eq!(slugify("Sensor Température"), "sensor_temperature");
+        assert_eq!(slugify("  Größe (mm) "), "grosse_mm");
+        assert_eq!(slugify("2nd byte"), "_2nd_byte");
+        assert_eq!(slugify("→"), "_");
+        for name in ["Sensor Température", "2nd byte", "→", "a-b"] {
+            assert!(check_identifier("Field", &slugify(name)).is_ok());
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Finding and repairing references between protocols that lead nowhere, e.g. in a project
+//! file edited by hand or saved by an older build.
+
+use super::protocol::ProtocolRegistry;
+use crate::error::BitLoomError;
+use std::fmt;
+
+/// A reference in a protocol that does not resolve
+#[derive(Clone, PartialEq, Debug)]
+pub enum IntegrityIssue {
+    /// The parent protocol does not exist
+    MissingParent {
+        protocol_id: String,
+        parent_id: String,
+    },
+    /// The protocol is among its own ancestors
+    ParentCycle { protocol_id: String },
+    /// A parent constraint is on a field that none of the ancestors has
+    MissingConstraintField {
+        protocol_id: String,
+        field_id: String,
+    },
+    /// The length field is not a field of the protocol or its ancestors
+    MissingLengthField {
+        protocol_id: String,
+        field_id: String,
+    },
+    /// The sequence number field is not a field of the protocol or its ancestors
+    MissingSequenceField {
+        protocol_id: String,
+        field_id: String,
+    },
+}
+
+impl IntegrityIssue {
+    pub fn protocol_id(&self) -> &str {
+        match self {
+            IntegrityIssue::MissingParent { protocol_id, .. }
+            | IntegrityIssue::ParentCycle { protocol_id }
+            | IntegrityIssue::MissingConstraintField { protocol_id, .. }
+            | IntegrityIssue::MissingLengthField { protocol_id, .. }
+            | IntegrityIssue::MissingSequenceField { protocol_id, .. } => protocol_id,
+        }
+    }
+
+    /// What [`ProtocolRegistry::repair`] does about the issue
+    pub fn repair_description(&self) -> String {
+        match self {
+            IntegrityIssue::MissingParent { protocol_id, .. }
+            | IntegrityIssue::ParentCycle { protocol_id } => format!(
+                "Make '{}' a top-level protocol, dropping its parent constraints",
+                protocol_id
+            ),
+            IntegrityIssue::MissingConstraintField { field_id, .. } => {
+                format!("Remove the constraint on '{}'", field_id)
+            }
+            IntegrityIssue::MissingLengthField { .. } => "Remove the length field".to_string(),
+            IntegrityIssue::MissingSequenceField { .. } => {
+                "Remove the sequence number field".to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityIssue::MissingParent {
+                protocol_id,
+                parent_id,
+            } => write!(
+                f,
+                "Protocol '{}' has parent '{}', which does not exist",
+                protocol_id, parent_id
+            ),
+            IntegrityIssue::ParentCycle { protocol_id } => {
+                write!(f, "Protocol '{}' is its own ancestor", protocol_id)
+            }
+            IntegrityIssue::MissingConstraintField {
+                protocol_id,
+                field_id,
+            } => write!(
+                f,
+                "Protocol '{}' constrains field '{}', which its parents do not have",
+                protocol_id, field_id
+            ),
+            IntegrityIssue::MissingLengthField {
+                protocol_id,
+                field_id,
+            } => write!(
+                f,
+                "Protocol '{}' takes its length from field '{}', which does not exist",
+                protocol_id, field_id
+            ),
+            IntegrityIssue::MissingSequenceField {
+                protocol_id,
+                field_id,
+            } => write!(
+                f,
+                "Protocol '{}' numbers its packets with field '{}', which does not exist",
+                protocol_id, field_id
+            ),
+        }
+    }
+}
+
+impl ProtocolRegistry {
+    /// Find the references between protocols that do not resolve, in the listed order of the
+    /// protocols. A cycle of parents is reported once, for the protocol of it listed first.
+    pub fn check_integrity(&self) -> Vec<IntegrityIssue> {
+        let mut issues = Vec::new();
+        let mut in_cycles: Vec<String> = Vec::new();
+        for protocol in self.iter() {
+            if let Some(parent_id) = &protocol.parent_id {
+                if self.get_protocol(parent_id).is_none() {
+                    issues.push(IntegrityIssue::MissingParent {
+                        protocol_id: protocol.id.clone(),
+                        parent_id: parent_id.clone(),
+                    });
+                    continue;
+                }
+                if let Some(cycle) = self.parent_cycle(&protocol.id) {
+                    if !in_cycles.contains(&protocol.id) {
+                        issues.push(IntegrityIssue::ParentCycle {
+                            protocol_id: protocol.id.clone(),
+                        });
+                        in_cycles.extend(cycle);
+                    }
+                    continue;
+                }
+                let parent_fields = self.resolve_fields(parent_id).unwrap_or_default();
+                let mut constrained: Vec<&String> = protocol.parent_constraints.keys().collect();
+                constrained.sort();
+                for field_id in constrained {
+                    if !parent_fields.iter().any(|f| &f.id == field_id) {
+                        issues.push(IntegrityIssue::MissingConstraintField {
+                            protocol_id: protocol.id.clone(),
+                            field_id: field_id.clone(),
+                        });
+                    }
+                }
+            }
+            if let Some(length_field) = &protocol.length_field {
+                let fields = self.resolve_fields(&protocol.id).unwrap_or_default();
+                if !fields.iter().any(|f| f.id == length_field.field_id) {
+                    issues.push(IntegrityIssue::MissingLengthField {
+                        protocol_id: protocol.id.clone(),
+                        field_id: length_field.field_id.clone(),
+                    });
+                }
+            }
+            if let Some(sequence_field) = &protocol.sequence_field {
+                let fields = self.resolve_fields(&protocol.id).unwrap_or_default();
+                if !fields.iter().any(|f| &f.id == sequence_field) {
+                    issues.push(IntegrityIssue::MissingSequenceField {
+                        protocol_id: protocol.id.clone(),
+                        field_id: sequence_field.clone(),
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Fix an issue found by [`Self::check_integrity`], as its
+    /// [`IntegrityIssue::repair_description`] says
+    pub fn repair(&mut self, issue: &IntegrityIssue) -> Result<(), BitLoomError> {
+        let protocol_id = issue.protocol_id();
+        let protocol =
+            self.protocol_mut(protocol_id)
+                .ok_or_else(|| BitLoomError::ProtocolNotFound {
+                    protocol_id: protocol_id.to_string(),
+                })?;
+        match issue {
+            IntegrityIssue::MissingParent { .. } | IntegrityIssue::ParentCycle { .. } => {
+                protocol.parent_id = None;
+                protocol.parent_constraints.clear();
+            }
+            IntegrityIssue::MissingConstraintField { field_id, .. } => {
+                protocol.parent_constraints.remove(field_id);
+            }
+            IntegrityIssue::MissingLengthField { .. } => protocol.length_field = None,
+            IntegrityIssue::MissingSequenceField { .. } => protocol.sequence_field = None,
+        }
+        Ok(())
+    }
+
+    /// The protocols of the cycle a protocol is in, if its ancestors lead back to it
+    fn parent_cycle(&self, protocol_id: &str) -> Option<Vec<String>> {
+        let mut cycle = vec![protocol_id.to_string()];
+        let mut current = self.get_protocol(protocol_id)?.parent_id.as_deref();
+        while let Some(id) = current {
+            if id == protocol_id {
+                return Some(cycle);
+            }
+            if cycle.iter().any(|c| c == id) {
+                return None; // a cycle further up, which its own protocols report
+            }
+            cycle.push(id.to_string());
+            current = self.get_protocol(id)?.parent_id.as_deref();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::{Endianness, LengthField, Protocol};
+
+    #[test]
+    fn test_check_and_repair() {
+        let protocol = |id: &str, parent: Option<&str>| {
+            let mut p = Protocol::new(id, None, Endianness::Big, parent.map(str::to_string));
+            p.add_field(FieldRule::new(
+                &format!("{}_kind", id),
+                FieldType::Input,
+                FieldLength::Fixed(8),
+            ))
+            .unwrap();
+            p
+        };
+        let mut frame = protocol("frame", None);
+        frame.length_field = Some(LengthField {
+            field_id: "size".to_string(),
+            adjustment: 0,
+        });
+        let mut data = protocol("data", Some("frame"));
+        data.set_parent_constraint("frame_kind", 1);
+        data.set_parent_constraint("version", 2);
+        data.sequence_field = Some("seq".to_string());
+        let protocols = vec![
+            frame,
+            data,
+            protocol("orphan", Some("gone")),
+            protocol("a", Some("b")),
+            protocol("b", Some("a")),
+            protocol("below_cycle", Some("a")),
+        ];
+        let mut registry = ProtocolRegistry::from_protocols(protocols).unwrap();
+
+        let issues = registry.check_integrity();
+        assert_eq!(
+            issues,
+            vec![
+                IntegrityIssue::MissingLengthField {
+                    protocol_id: "frame".to_string(),
+                    field_id: "size".to_string(),
+                },
+                IntegrityIssue::MissingConstraintField {
+                    protocol_id: "data".to_string(),
+                    field_id: "version".to_string(),
+                },
+                IntegrityIssue::MissingSequenceField {
+                    protocol_id: "data".to_string(),
+                    field_id: "seq".to_string(),
+                },
+                IntegrityIssue::MissingParent {
+                    protocol_id: "orphan".to_string(),
+                    parent_id: "gone".to_string(),
+                },
+                IntegrityIssue::ParentCycle {
+                    protocol_id: "a".to_string(),
+                },
+            ]
+        );
+        for issue in &issues {
+            registry.repair(issue).unwrap();
+        }
+        assert!(registry.check_integrity().is_empty());
+        assert_eq!(registry.get_descendant_ids("a"), vec!["b", "below_cycle"]);
+        let data = registry.get_protocol("data").unwrap();
+        assert_eq!(data.parent_constraints.len(), 1);
+    }
+}
@@ -1,3 +1,13 @@
+pub mod accounting;
+pub mod bookmark;
+pub mod constraints;
+pub mod diff;
 pub mod field;
+pub mod history;
+pub mod ident;
+pub mod integrity;
+pub mod preset;
 pub mod project;
 pub mod protocol;
+pub mod schema;
+pub mod trash;
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A named packet saved in a project, loaded into the packet builder with one click
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PacketPreset {
+    pub name: String,
+    /// folder the preset is listed in, with `/` between nested folders; empty for the top level
+    #[serde(default)]
+    pub folder: String,
+    pub protocol_id: String,
+    /// value of each field by field ID, as a literal like `0x10`, `"text"` or `[01 02]`
+    pub values: BTreeMap<String, String>,
+    /// free-form observations about the packet, e.g. how a device answers it
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl PacketPreset {
+    /// Folder and name, e.g. `commands/reset`
+    pub fn path(&self) -> String {
+        if self.folder.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}/{}", self.folder, self.name)
+        }
+    }
+}
+
+/// Save a preset, replacing the one with the same folder and name if there is one, whose
+/// notes it keeps when it has none. Returns its index.
+pub fn save_preset(presets: &mut Vec<PacketPreset>, preset: PacketPreset) -> Result<usize, String> {
+    if preset.name.trim().is_empty() {
+        return Err("A preset name is required".to_string());
+    }
+    if preset.name.contains('/') {
+        return Err("Preset names cannot contain '/'".to_string());
+    }
+    match presets
+        .iter()
+        .position(|p| p.folder == preset.folder && p.name == preset.name)
+    {
+        Some(i) => {
+            let mut preset = preset;
+            if preset.notes.is_empty() {
+                preset.notes = std::mem::take(&mut presets[i].notes);
+            }
+            presets[i] = preset;
+            Ok(i)
+        }
+        None => {
+            presets.push(preset);
+            Ok(presets.len() - 1)
+        }
+    }
+}
+
+/// Add a copy of a preset after it, named like `heartbeat (2)`. Returns the index of the copy.
+pub fn duplicate_preset(presets: &mut Vec<PacketPreset>, index: usize) -> Result<usize, String> {
+    let mut copy = presets
+        .get(index)
+        .cloned()
+        .ok_or_else(|| format!("Preset {} does not exist", index))?;
+    let base = copy.name.clone();
+    copy.name = (2..)
+        .map(|n| format!("{} ({})", base, n))
+        .find(|name| {
+            !presets
+                .iter()
+                .any(|p| p.folder == copy.folder && p.name == *name)
+        })
+        .unwrap_or(base);
+    presets.insert(index + 1, copy);
+    Ok(index + 1)
+}
+
+/// Move a preset into another folder, failing if one with its name is already there
+pub fn move_preset(presets: &mut [PacketPreset], index: usize, folder: &str) -> Result<(), String> {
+    let folder = folder.trim_matches('/').to_string();
+    let name = presets
+        .get(index)
+        .map(|p| p.name.clone())
+        .ok_or_else(|| format!("Preset {} does not exist", index))?;
+    if presets
+        .iter()
+        .enumerate()
+        .any(|(i, p)| i != index && p.folder == folder && p.name == name)
+    {
+        return Err(format!(
+            "Folder '{}' already has a preset '{}'",
+            folder, name
+        ));
+    }
+    presets[index].folder = folder;
+    Ok(())
+}
+
+/// Indices of the presets grouped by folder, top level first and the folders sorted
+pub fn presets_by_folder(presets: &[PacketPreset]) -> BTreeMap<&str, Vec<usize>> {
+    let mut folders: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, preset) in presets.iter().enumerate() {
+        folders.entry(preset.folder.as_str()).or_default().push(i);
+    }
+    folders
+}
+
+/// Field values pasted from a spreadsheet, as (field ID, literal) pairs in the order they were
+/// pasted. The text is either one `field=value` or tab-separated `field<TAB>value` pair per
+/// line, or a column of values, one per line, for the fields in `field_ids` in order.
+pub fn parse_pasted_values(
+    text: &str,
+    field_ids: &[&str],
+) -> Result<Vec<(String, String)>, String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let pair = |line: &str| {
+        let (id, value) = line.split_once('\t').or_else(|| line.split_once('='))?;
+        let id = id.trim();
+        let is_id = !id.is_empty()
+            && !id.starts_with(|c: char| c.is_ascii_digit())
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        is_id.then(|| (id.to_string(), value.trim().to_string()))
+    };
+
+    let pairs: Vec<Option<(String, String)>> = lines.iter().map(|line| pair(line)).collect();
+    if !pairs.is_empty() && pairs.iter().all(Option::is_some) {
+        return pairs
+            .into_iter()
+            .flatten()
+            .map(|(id, value)| {
+                if field_ids.contains(&id.as_str()) {
+                    Ok((id, value))
+                } else {
+                    Err(format!("No field '{}' to paste a value into", id))
+                }
+            })
+            .collect();
+    }
+    if lines.len() > field_ids.len() {
+        return Err(format!(
+            "{} values were pasted, but only {} fields take one",
+            lines.len(),
+            field_ids.len()
+        ));
+    }
+    Ok(field_ids
+        .iter()
+        .zip(&lines)
+        .map(|(id, value)| (id.to_string(), value.trim().to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(folder: &str, name: &str) -> PacketPreset {
+        PacketPreset {
+            name: name.to_string(),
+            folder: folder.to_string(),
+            protocol_id: "frame".to_string(),
+            values: BTreeMap::from([("id".to_string(), "0x10".to_string())]),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_duplicate() {
+        let mut presets = Vec::new();
+        assert_eq!(save_preset(&mut presets, preset("", "heartbeat")), Ok(0));
+        assert_eq!(
+            save_preset(&mut presets, preset("commands", "reset")),
+            Ok(1)
+        );
+        let mut changed = preset("", "heartbeat");
+        changed.values.insert("id".to_string(), "1".to_string());
+        assert_eq!(save_preset(&mut presets, changed.clone()), Ok(0));
+        assert_eq!(presets[0], changed);
+        presets[0].notes = "answered with an ack".to_string();
+        assert_eq!(save_preset(&mut presets, changed.clone()), Ok(0));
+        assert_eq!(presets[0].notes, "answered with an ack");
+        assert!(save_preset(&mut presets, preset("", " ")).is_err());
+        assert!(save_preset(&mut presets, preset("", "a/b")).is_err());
+
+        assert_eq!(duplicate_preset(&mut presets, 0), Ok(1));
+        assert_eq!(duplicate_preset(&mut presets, 0), Ok(1));
+        let names: Vec<String> = presets.iter().map(|p| p.path()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "heartbeat",
+                "heartbeat (3)",
+                "heartbeat (2)",
+                "commands/reset"
+            ]
+        );
+        assert!(duplicate_preset(&mut presets, 9).is_err());
+    }
+
+    #[test]
+    fn test_move_and_group() {
+        let mut presets = vec![
+            preset("", "heartbeat"),
+            preset("commands", "reset"),
+            preset("", "reset"),
+        ];
+        assert!(move_preset(&mut presets, 2, "commands").is_err());
+        move_preset(&mut presets, 0, "/telemetry/").unwrap();
+        assert_eq!(presets[0].path(), "telemetry/heartbeat");
+
+        let folders = presets_by_folder(&presets);
+        let grouped: Vec<(&str, Vec<usize>)> = folders.into_iter().collect();
+        assert_eq!(
+            grouped,
+            vec![("", vec![2]), ("commands", vec![1]), ("telemetry", vec![0])]
+        );
+    }
+
+    #[test]
+    fn test_parse_pasted_values() {
+        let fields = ["kind", "length", "data"];
+        let pairs = |values: &[(&str, &str)]| {
+            values
+                .iter()
+                .map(|(id, value)| (id.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            parse_pasted_values("3\r\n0x10\r\n\r\n[01 02]\r\n", &fields),
+            Ok(pairs(&[
+                ("kind", "3"),
+                ("length", "0x10"),
+                ("data", "[01 02]")
+            ]))
+        );
+        assert_eq!(
+            parse_pasted_values("data\t[ff]\nkind = 2", &fields),
+            Ok(pairs(&[("data", "[ff]"), ("kind", "2")]))
+        );
+        assert!(parse_pasted_values("flags=1", &fields).is_err());
+        assert!(parse_pasted_values("1\n2\n3\n4", &fields).is_err());
+        // a value containing '=' is not taken for a pair
+        assert_eq!(
+            parse_pasted_values("\"a=b\"", &fields),
+            Ok(pairs(&[("kind", "\"a=b\"")]))
+        );
+        assert_eq!(parse_pasted_values("", &fields), Ok(Vec::new()));
+    }
+}
@@ -1,8 +1,30 @@
+use super::bookmark::AnnotatedPacket;
+use super::history::RevisionHistory;
+use super::preset::PacketPreset;
 use super::protocol::Protocol;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+/// Version of the project format written by this build
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A `.bitloom` project file; its JSON Schema is [`super::schema::project_schema`]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BitLoomProject {
     pub project_version: u32,
     pub protocols: Vec<Protocol>,
+    #[serde(default)]
+    pub history: RevisionHistory,
+    /// rhai functions and constants shared by all field expressions
+    #[serde(default)]
+    pub script_library: String,
+    /// named packets for the packet builder
+    #[serde(default)]
+    pub presets: Vec<PacketPreset>,
+    /// packets with bookmarked ranges, e.g. of payloads being worked out
+    #[serde(default)]
+    pub bookmarks: Vec<AnnotatedPacket>,
+    /// fields shown as columns of the capture packet list, in order, by protocol ID
+    #[serde(default)]
+    pub packet_columns: BTreeMap<String, Vec<String>>,
 }
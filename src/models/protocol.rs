@@ -1,6 +1,12 @@
-use super::field::{Field, FieldLength, FieldRule, FieldType};
+use super::field::{Field, FieldLength, FieldRule};
+use super::ident::check_identifier;
+use crate::codec::bits::Bits;
+use crate::error::BitLoomError;
+use crate::script::idents::{references_identifier, rename_identifier};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, iter};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
 pub enum Endianness {
@@ -17,6 +23,33 @@ pub enum ProtocolLength {
     Variable(u32),
 }
 
+/// How serious a problem reported about a packet is
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+/// A rhai script that checks a decoded packet as a whole, e.g. that a length field matches
+/// the actual payload. It sees every decoded field as a variable and in the `fields` map, and
+/// returns `true` or `()` if the packet is fine, otherwise a message or an array of messages.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PacketValidator {
+    pub name: String,
+    pub severity: Severity,
+    pub script: String,
+}
+
+/// A field holding the length of the packet, which tells where a message ends in a byte stream
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LengthField {
+    pub field_id: String,
+    /// added to the field value to get the packet length in bytes, e.g. the size of the header
+    /// when the field counts the payload only
+    pub adjustment: i64,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct Protocol {
     pub id: String,
@@ -28,6 +61,45 @@ pub struct Protocol {
     pub metadata: HashMap<String, String>,
     pub parent_id: Option<String>,                 // parent protocol ID
     pub parent_constraints: HashMap<String, i128>, // (field_id, value): constraints on parent fields for this subprotocol to apply
+    /// checks run on every decoded packet of this protocol and its subprotocols
+    #[serde(default)]
+    pub validators: Vec<PacketValidator>,
+    /// most bits a packet of this protocol and its subprotocols may take, e.g. a frame size or MTU
+    #[serde(default)]
+    pub max_length: Option<u32>,
+    /// exact length in bits of a packet of this protocol and its subprotocols, e.g. a 64-byte
+    /// frame; the space after the last field is padding of zero bits
+    #[serde(default)]
+    pub frame_length: Option<u32>,
+    /// field giving the length of a packet of this protocol and its subprotocols
+    #[serde(default)]
+    pub length_field: Option<LengthField>,
+    /// field numbering the packets of this protocol and its subprotocols in sequence, for
+    /// finding dropped and repeated packets in a capture
+    #[serde(default)]
+    pub sequence_field: Option<String>,
+    /// free-form observations, e.g. how a device reacts to the protocol, unlike the description
+    /// of the protocol itself
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// A place in the registry that refers to a field by its ID
+#[derive(Clone, PartialEq, Debug)]
+pub enum FieldReference {
+    /// A subprotocol constrains the field to a value in its `parent_constraints`
+    ParentConstraint { protocol_id: String, value: i128 },
+    /// The script of an expression or derived field mentions the field
+    Expression {
+        protocol_id: String,
+        field_id: String,
+    },
+    /// The script of a packet validator mentions the field
+    Validator { protocol_id: String, name: String },
+    /// A protocol takes its packet length from the field
+    LengthField { protocol_id: String },
+    /// A protocol numbers its packets in sequence with the field
+    SequenceField { protocol_id: String },
 }
 
 impl Protocol {
@@ -47,6 +119,12 @@ impl Protocol {
             metadata: HashMap::new(),
             parent_id,
             parent_constraints: HashMap::new(),
+            validators: Vec::new(),
+            max_length: None,
+            frame_length: None,
+            length_field: None,
+            sequence_field: None,
+            notes: String::new(),
         }
     }
 
@@ -54,77 +132,167 @@ impl Protocol {
         self.metadata.insert(key.to_string(), value.to_string());
     }
 
-    pub fn add_field(&mut self, field_rule: FieldRule) -> Result<(), String> {
+    pub fn add_field(&mut self, field_rule: FieldRule) -> Result<(), BitLoomError> {
+        self.insert_field(self.fields.len(), field_rule)
+    }
+
+    /// Insert a field before the one at `index`, or at the end if the index is past it
+    pub fn insert_field(
+        &mut self,
+        index: usize,
+        field_rule: FieldRule,
+    ) -> Result<(), BitLoomError> {
+        check_identifier("Field", &field_rule.id)?;
         if self.fields.iter().any(|f| f.id == field_rule.id) {
-            return Err(format!(
-                "Field with ID '{}' already exists in protocol '{}'",
-                field_rule.id, self.id
-            ));
+            return Err(BitLoomError::FieldExists {
+                protocol_id: self.id.clone(),
+                field_id: field_rule.id,
+            });
         }
 
-        if let Some(last_field) = self.fields.last() {
-            if let FieldLength::Variable = last_field.length {
-                return Err(format!(
-                    "Cannot add field '{}' after variable length field '{}' in protocol '{}'",
-                    field_rule.id, last_field.id, self.id
-                ));
+        // virtual fields take up no space on the wire, so they may follow a variable length field
+        let index = index.min(self.fields.len());
+        if !field_rule.is_virtual() {
+            let (before, after) = self.fields.split_at(index);
+            if let Some(variable) = before
+                .iter()
+                .find(|f| !f.is_virtual() && f.length == FieldLength::Variable)
+            {
+                return Err(BitLoomError::FieldAfterVariable {
+                    protocol_id: self.id.clone(),
+                    field_id: field_rule.id,
+                    variable_id: variable.id.clone(),
+                });
+            }
+            if field_rule.length == FieldLength::Variable
+                && let Some(next) = after.iter().find(|f| !f.is_virtual())
+            {
+                return Err(BitLoomError::FieldAfterVariable {
+                    protocol_id: self.id.clone(),
+                    field_id: next.id.clone(),
+                    variable_id: field_rule.id,
+                });
             }
         }
 
-        self.fields.push(field_rule);
+        self.fields.insert(index, field_rule);
         self.calculate_length();
         Ok(())
     }
 
-    pub fn remove_field(&mut self, field_id: &str) -> Result<(), String> {
+    pub fn remove_field(&mut self, field_id: &str) -> Result<(), BitLoomError> {
         let old_len = self.fields.len();
         self.fields.retain(|f| f.id != field_id);
 
         if self.fields.len() == old_len {
-            return Err(format!(
-                "Field with ID '{}' not found in protocol '{}'",
-                field_id, self.id
-            ));
+            return Err(self.field_not_found(field_id));
         }
 
         self.calculate_length();
         Ok(())
     }
 
-    pub fn move_field(&mut self, field_id: &str, new_index: usize) -> Result<(), String> {
+    pub fn move_field(&mut self, field_id: &str, new_index: usize) -> Result<(), BitLoomError> {
         if let Some(pos) = self.fields.iter().position(|f| f.id == field_id) {
             let field = self.fields.remove(pos);
             let new_index = new_index.min(self.fields.len()); // ensure new_index is within bounds
             self.fields.insert(new_index, field);
             Ok(())
         } else {
-            Err(format!(
-                "Field with ID '{}' not found in protocol '{}'",
-                field_id, self.id
-            ))
+            Err(self.field_not_found(field_id))
         }
     }
 
-    pub fn update_field_id(&mut self, old_id: &str, new_id: &str) -> Result<(), String> {
+    /// Move the given fields one place up or down together, each past the nearest field that is
+    /// not being moved. Fields already at the top or bottom stay where they are.
+    pub fn move_fields(&mut self, field_ids: &[String], up: bool) -> Result<(), BitLoomError> {
+        if let Some(id) = field_ids
+            .iter()
+            .find(|id| !self.fields.iter().any(|f| &f.id == *id))
+        {
+            return Err(self.field_not_found(id));
+        }
+
+        let moving = |field: &FieldRule| field_ids.contains(&field.id);
+        let mut fields = self.fields.clone();
+        if up {
+            for i in 1..fields.len() {
+                if moving(&fields[i]) && !moving(&fields[i - 1]) {
+                    fields.swap(i, i - 1);
+                }
+            }
+        } else {
+            for i in (1..fields.len()).rev() {
+                if moving(&fields[i - 1]) && !moving(&fields[i]) {
+                    fields.swap(i, i - 1);
+                }
+            }
+        }
+
+        // a variable length field must stay the last one on the wire
+        let mut wire = fields.iter().filter(|f| !f.is_virtual());
+        if let Some(variable) = wire.by_ref().find(|f| f.length == FieldLength::Variable)
+            && let Some(next) = wire.next()
+        {
+            return Err(BitLoomError::FieldAfterVariable {
+                protocol_id: self.id.clone(),
+                field_id: next.id.clone(),
+                variable_id: variable.id.clone(),
+            });
+        }
+
+        self.fields = fields;
+        Ok(())
+    }
+
+    /// Change the ID of a field, and update references to it in the scripts of sibling fields.
+    /// Use [`ProtocolRegistry::rename_field`] to also update subprotocols.
+    pub fn update_field_id(&mut self, old_id: &str, new_id: &str) -> Result<(), BitLoomError> {
         if old_id == new_id {
             return Ok(()); // no change needed
         }
 
+        check_identifier("Field", new_id)?;
         if self.fields.iter().any(|f| f.id == new_id) {
-            return Err(format!("Field with ID '{}' already exists", new_id));
+            return Err(BitLoomError::FieldExists {
+                protocol_id: self.id.clone(),
+                field_id: new_id.to_string(),
+            });
         }
 
         if let Some(field) = self.fields.iter_mut().find(|f| f.id == old_id) {
             field.id = new_id.to_string();
+            self.rename_field_references(old_id, new_id);
             Ok(())
         } else {
-            Err(format!("Field with ID '{}' does not exist", old_id))
+            Err(self.field_not_found(old_id))
         }
     }
 
-    pub fn edit_field<F>(&mut self, field_id: &str, f: F) -> Result<(), String>
+    /// Rewrite references to a renamed field in the scripts of this protocol's fields and
+    /// validators, and in its length and sequence number fields
+    fn rename_field_references(&mut self, old_id: &str, new_id: &str) {
+        for field in &mut self.fields {
+            if let Some(script) = field.field_type.script_mut() {
+                *script = rename_identifier(script, old_id, new_id);
+            }
+        }
+        for validator in &mut self.validators {
+            validator.script = rename_identifier(&validator.script, old_id, new_id);
+        }
+        if let Some(length) = &mut self.length_field
+            && length.field_id == old_id
+        {
+            length.field_id = new_id.to_string();
+        }
+        if self.sequence_field.as_deref() == Some(old_id) {
+            self.sequence_field = Some(new_id.to_string());
+        }
+    }
+
+    pub fn edit_field<F>(&mut self, field_id: &str, f: F) -> Result<(), BitLoomError>
     where
-        F: FnOnce(&mut FieldRule) -> Result<(), String>,
+        F: FnOnce(&mut FieldRule) -> Result<(), BitLoomError>,
     {
         // find the field to edit
         if let Some(field) = self.fields.iter_mut().find(|f| f.id == field_id) {
@@ -138,10 +306,9 @@ impl Protocol {
             // cannot change field ID through this method
             if field.id != backup.id {
                 *field = backup; // revert applied changes
-                return Err(
-                    "Field ID cannot be changed through edit_field; use update_field_id instead"
-                        .to_string(),
-                );
+                return Err(BitLoomError::FieldIdChanged {
+                    field_id: field_id.to_string(),
+                });
             }
 
             if field.length != backup.length {
@@ -150,10 +317,14 @@ impl Protocol {
 
             Ok(())
         } else {
-            Err(format!(
-                "Field with ID '{}' not found in protocol '{}'",
-                field_id, self.id
-            ))
+            Err(self.field_not_found(field_id))
+        }
+    }
+
+    fn field_not_found(&self, field_id: &str) -> BitLoomError {
+        BitLoomError::FieldNotFound {
+            protocol_id: self.id.clone(),
+            field_id: field_id.to_string(),
         }
     }
 
@@ -164,10 +335,11 @@ impl Protocol {
 
     /// Calculate the total length of the protocol based on its fields.
     /// If any field has variable length, the protocol length is variable.
+    /// Virtual fields do not contribute to the length.
     /// Must be called after any change to the fields to keep the protocol length up to date.
     fn calculate_length(&mut self) {
         let mut total_fixed_bits = 0;
-        for field in &self.fields {
+        for field in self.fields.iter().filter(|f| !f.is_virtual()) {
             match field.length {
                 FieldLength::Fixed(bits) => total_fixed_bits += bits,
                 // variable field is always at the end
@@ -181,80 +353,525 @@ impl Protocol {
     }
 }
 
+/// The fields of a protocol and its ancestors in packet order, with where each starts
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct FieldLayout {
+    pub fields: Vec<FieldRule>,
+    /// bit offset of each wire field from the start of the packet; virtual fields and fields
+    /// after a variable length one have none
+    pub offsets: HashMap<String, u32>,
+}
+
+impl FieldLayout {
+    fn new(fields: Vec<FieldRule>) -> Self {
+        let mut offsets = HashMap::new();
+        let mut offset = Some(0);
+        for field in fields.iter().filter(|f| !f.is_virtual()) {
+            let Some(start) = offset else {
+                break;
+            };
+            offsets.insert(field.id.clone(), start);
+            offset = match field.length {
+                FieldLength::Fixed(bits) => Some(start + bits),
+                FieldLength::Variable => None,
+            };
+        }
+        Self { fields, offsets }
+    }
+}
+
+/// The subprotocols of every protocol by the parent field values they require
+#[derive(Default, Debug)]
+struct ConstraintIndex {
+    /// by parent ID
+    parents: HashMap<String, Vec<ConstraintGroup>>,
+}
+
+/// Subprotocols of one protocol that constrain the same parent fields
+#[derive(Debug)]
+struct ConstraintGroup {
+    /// sorted
+    fields: Vec<String>,
+    /// by the values they require of `fields`, in that order
+    children: HashMap<Vec<i128>, Vec<String>>,
+}
+
+impl ConstraintIndex {
+    fn new(protocols: &HashMap<String, Protocol>) -> Self {
+        let mut index = Self::default();
+        for child in protocols.values() {
+            let Some(parent_id) = &child.parent_id else {
+                continue;
+            };
+            let mut constraints: Vec<(&String, &i128)> = child.parent_constraints.iter().collect();
+            constraints.sort();
+            let fields: Vec<String> = constraints.iter().map(|(id, _)| (*id).clone()).collect();
+            let values: Vec<i128> = constraints.iter().map(|(_, v)| **v).collect();
+
+            let groups = index.parents.entry(parent_id.clone()).or_default();
+            let group = match groups.iter().position(|g| g.fields == fields) {
+                Some(i) => &mut groups[i],
+                None => {
+                    groups.push(ConstraintGroup {
+                        fields,
+                        children: HashMap::new(),
+                    });
+                    groups.last_mut().expect("group was just added")
+                }
+            };
+            group
+                .children
+                .entry(values)
+                .or_default()
+                .push(child.id.clone());
+        }
+        for groups in index.parents.values_mut() {
+            // subprotocols constraining more fields are more specific, so they come first
+            groups.sort_by(|a, b| {
+                b.fields
+                    .len()
+                    .cmp(&a.fields.len())
+                    .then(a.fields.cmp(&b.fields))
+            });
+            for children in groups.iter_mut().flat_map(|g| g.children.values_mut()) {
+                children.sort();
+            }
+        }
+        index
+    }
+}
+
 pub struct ProtocolRegistry {
     /// map from protocol ID to Protocol definition
     protocols: HashMap<String, Protocol>,
+    /// IDs of all protocols in the order they are listed: as added, unless moved or sorted
+    order: Vec<String>,
+    /// layouts resolved since the protocols in their chains last changed, by protocol ID
+    layouts: RwLock<HashMap<String, Arc<FieldLayout>>>,
+    /// built when first needed after the protocols last changed
+    constraint_index: RwLock<Option<Arc<ConstraintIndex>>>,
+    /// see [`Self::revision`]
+    revision: u64,
+}
+
+/// Last revision given to a registry, so that no two registries in different states share one
+static REVISIONS: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    REVISIONS.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+impl Clone for ProtocolRegistry {
+    fn clone(&self) -> Self {
+        Self {
+            protocols: self.protocols.clone(),
+            order: self.order.clone(),
+            layouts: RwLock::new(self.layouts.read().map(|l| l.clone()).unwrap_or_default()),
+            constraint_index: RwLock::new(
+                self.constraint_index
+                    .read()
+                    .map(|i| i.clone())
+                    .unwrap_or_default(),
+            ),
+            revision: self.revision,
+        }
+    }
 }
 
 impl ProtocolRegistry {
     pub fn new() -> Self {
         Self {
             protocols: HashMap::new(),
+            order: Vec::new(),
+            layouts: RwLock::new(HashMap::new()),
+            constraint_index: RwLock::new(None),
+            revision: next_revision(),
         }
     }
 
+    /// A registry of protocols as they were saved, e.g. in a project file. Unlike
+    /// [`Self::add_protocols`] their references are not checked, so that a damaged project
+    /// still opens; see [`Self::check_integrity`]. Only the IDs must be unique.
+    pub fn from_protocols(protocols: Vec<Protocol>) -> Result<Self, BitLoomError> {
+        let mut registry = Self::new();
+        for protocol in protocols {
+            if registry.protocols.contains_key(&protocol.id) {
+                return Err(BitLoomError::ProtocolExists {
+                    protocol_id: protocol.id.clone(),
+                });
+            }
+            registry.order.push(protocol.id.clone());
+            registry.protocols.insert(protocol.id.clone(), protocol);
+        }
+        Ok(registry)
+    }
+
+    /// A protocol to change anything of, including its `parent_id`, for repairs
+    pub(super) fn protocol_mut(&mut self, protocol_id: &str) -> Option<&mut Protocol> {
+        self.invalidate_all();
+        self.protocols.get_mut(protocol_id)
+    }
+
+    /// Forget the layouts of a protocol and its subprotocols, after it changed
+    fn invalidate(&mut self, protocol_id: &str) {
+        let descendants = self.get_descendant_ids(protocol_id);
+        let layouts = self.layouts.get_mut().unwrap_or_else(|e| e.into_inner());
+        layouts.remove(protocol_id);
+        for id in descendants {
+            layouts.remove(&id);
+        }
+        self.invalidate_index();
+    }
+
+    fn invalidate_all(&mut self) {
+        self.layouts
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.invalidate_index();
+    }
+
+    fn invalidate_index(&mut self) {
+        *self
+            .constraint_index
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner()) = None;
+        self.revision = next_revision();
+    }
+
+    /// A number that changes whenever any protocol changes, and differs between registries
+    /// unless one is an unchanged clone of the other, for caches of what is derived from the
+    /// protocols such as [`crate::codec::compiled::CodecCache`]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The subprotocols of a protocol whose parent constraints a packet meets, given the
+    /// integer value of each of its fields. Those constraining more fields come first, then
+    /// by ID. The subprotocols are found by their constraint values in a few lookups, however
+    /// many there are.
+    pub fn matching_children(
+        &self,
+        parent_id: &str,
+        value_of: impl Fn(&str) -> Option<i128>,
+    ) -> Vec<String> {
+        let index = self.constraint_index.read().ok().and_then(|i| i.clone());
+        let index = index.unwrap_or_else(|| {
+            let index = Arc::new(ConstraintIndex::new(&self.protocols));
+            if let Ok(mut cached) = self.constraint_index.write() {
+                *cached = Some(index.clone());
+            }
+            index
+        });
+        let Some(groups) = index.parents.get(parent_id) else {
+            return Vec::new();
+        };
+        let mut children = Vec::new();
+        for group in groups {
+            let values: Option<Vec<i128>> = group.fields.iter().map(|id| value_of(id)).collect();
+            if let Some(matching) = values.and_then(|v| group.children.get(&v)) {
+                children.extend(matching.iter().cloned());
+            }
+        }
+        children
+    }
+
     pub fn create_protocol(
         &mut self,
         id: &str,
         name: Option<String>,
         endianness: Endianness,
         parent_id: Option<String>,
-    ) -> Result<(), String> {
+    ) -> Result<(), BitLoomError> {
+        check_identifier("Protocol", id)?;
         if self.protocols.contains_key(id) {
-            return Err(format!("Protocol with ID '{}' already exists", id));
+            return Err(BitLoomError::ProtocolExists {
+                protocol_id: id.to_string(),
+            });
         }
 
-        if let Some(pid) = &parent_id {
-            if !self.protocols.contains_key(pid) {
-                return Err(format!("Parent protocol with ID '{}' does not exist", pid));
-            }
+        if let Some(pid) = &parent_id
+            && !self.protocols.contains_key(pid)
+        {
+            return Err(BitLoomError::ParentNotFound {
+                parent_id: pid.clone(),
+            });
         }
 
         let protocol = Protocol::new(id, name, endianness, parent_id);
         self.protocols.insert(id.to_string(), protocol);
+        self.order.push(id.to_string());
+        self.invalidate_index();
         Ok(())
     }
 
-    /// Remove a protocol and all its subprotocols recursively
-    pub fn remove_protocol(&mut self, protocol_id: &str) -> Result<(), String> {
+    /// Add complete protocols, e.g. from an import. Either all are added or none: every ID must
+    /// be new, and every parent must exist or come earlier in `protocols`.
+    pub fn add_protocols(&mut self, protocols: Vec<Protocol>) -> Result<(), BitLoomError> {
+        let mut added: Vec<&str> = Vec::new();
+        for protocol in &protocols {
+            check_identifier("Protocol", &protocol.id)?;
+            for field in &protocol.fields {
+                check_identifier("Field", &field.id)?;
+            }
+            if self.protocols.contains_key(&protocol.id) || added.contains(&protocol.id.as_str()) {
+                return Err(BitLoomError::ProtocolExists {
+                    protocol_id: protocol.id.clone(),
+                });
+            }
+            if let Some(pid) = &protocol.parent_id
+                && !self.protocols.contains_key(pid)
+                && !added.contains(&pid.as_str())
+            {
+                return Err(BitLoomError::ParentNotFound {
+                    parent_id: pid.clone(),
+                });
+            }
+            added.push(&protocol.id);
+        }
+
+        for protocol in protocols {
+            self.order.push(protocol.id.clone());
+            self.protocols.insert(protocol.id.clone(), protocol);
+        }
+        self.invalidate_index();
+        Ok(())
+    }
+
+    /// Remove a protocol and all its subprotocols recursively. Returns the removed protocols,
+    /// each after its parent, so that [`Self::add_protocols`] can put them back.
+    pub fn remove_protocol(&mut self, protocol_id: &str) -> Result<Vec<Protocol>, BitLoomError> {
         if !self.protocols.contains_key(protocol_id) {
-            return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+            return Err(BitLoomError::ProtocolNotFound {
+                protocol_id: protocol_id.to_string(),
+            });
         }
 
+        self.invalidate(protocol_id);
         let mut to_remove = vec![protocol_id.to_string()];
+        to_remove.extend(self.get_descendant_ids(protocol_id));
+        self.order.retain(|id| !to_remove.contains(id));
+
+        Ok(to_remove
+            .into_iter()
+            .filter_map(|id| self.protocols.remove(&id))
+            .collect())
+    }
+
+    /// Get the IDs of all subprotocols of a protocol recursively, not including the protocol itself
+    pub fn get_descendant_ids(&self, protocol_id: &str) -> Vec<String> {
+        let mut descendants = Vec::new();
         let mut i = 0;
+        let mut current_id = protocol_id.to_string();
 
-        while i < to_remove.len() {
-            let current_id = &to_remove[i];
+        loop {
+            // a cycle of parents would go on forever
             let children: Vec<String> = self
-                .protocols
-                .values()
-                .filter(|p| p.parent_id.as_deref() == Some(current_id))
+                .children_of(&current_id)
+                .filter(|p| p.id != protocol_id && !descendants.contains(&p.id))
                 .map(|p| p.id.clone())
                 .collect();
-            to_remove.extend(children);
+            descendants.extend(children);
+
+            if i >= descendants.len() {
+                return descendants;
+            }
+            current_id = descendants[i].clone();
             i += 1;
         }
+    }
+
+    /// Append copies of fields, e.g. copied from another protocol, to a protocol. A field whose
+    /// ID is already used in the protocol, its ancestors or its subprotocols gets a free one like
+    /// `flags_2`, and references to it in the scripts of the other added fields follow the rename.
+    /// Returns the IDs of the added fields.
+    pub fn paste_fields(
+        &mut self,
+        protocol_id: &str,
+        mut fields: Vec<FieldRule>,
+    ) -> Result<Vec<String>, BitLoomError> {
+        let mut taken: HashSet<String> = self
+            .get_inheritance_chain(protocol_id)
+            .into_iter()
+            .chain(
+                self.get_descendant_ids(protocol_id)
+                    .iter()
+                    .filter_map(|id| self.protocols.get(id)),
+            )
+            .flat_map(|p| p.fields.iter().map(|f| f.id.clone()))
+            .collect();
+        // new IDs must not clash with the pasted ones either, so that renames do not chain
+        let pasted: HashSet<String> = fields.iter().map(|f| f.id.clone()).collect();
+
+        let mut renames = Vec::new();
+        for field in &mut fields {
+            if taken.contains(&field.id) {
+                let id = (2..)
+                    .map(|n| format!("{}_{}", field.id, n))
+                    .find(|id| !taken.contains(id) && !pasted.contains(id))
+                    .unwrap_or_default();
+                renames.push((field.id.clone(), id.clone()));
+                field.id = id;
+            }
+            taken.insert(field.id.clone());
+        }
+        for field in &mut fields {
+            if let Some(script) = field.field_type.script_mut() {
+                for (old, new) in &renames {
+                    *script = rename_identifier(script, old, new);
+                }
+            }
+        }
 
-        for id in to_remove {
-            self.protocols.remove(&id);
+        let ids = fields.iter().map(|f| f.id.clone()).collect();
+        self.edit_protocol(protocol_id, |p| {
+            fields.into_iter().try_for_each(|field| p.add_field(field))
+        })?;
+        Ok(ids)
+    }
+
+    /// The protocol among the ancestors of a protocol, the protocol itself and its subprotocols
+    /// that has a field with an ID, which a field added to or renamed in the protocol would clash
+    /// with
+    pub fn related_field_owner(&self, protocol_id: &str, field_id: &str) -> Option<&Protocol> {
+        let descendants = self.get_descendant_ids(protocol_id);
+        self.get_inheritance_chain(protocol_id)
+            .into_iter()
+            .chain(descendants.iter().filter_map(|id| self.protocols.get(id)))
+            .find(|p| p.fields.iter().any(|f| f.id == field_id))
+    }
+
+    /// Change the ID of a field in a protocol, and update all references to it:
+    /// scripts in the protocol and its subprotocols, and subprotocols' `parent_constraints`.
+    /// The new ID must not be used in the ancestors or subprotocols either. A subprotocol with
+    /// a field of the old ID of its own keeps the references to it, as do its subprotocols.
+    pub fn rename_field(
+        &mut self,
+        protocol_id: &str,
+        old_id: &str,
+        new_id: &str,
+    ) -> Result<(), BitLoomError> {
+        if old_id == new_id {
+            return Ok(());
+        }
+        if let Some(owner) = self.related_field_owner(protocol_id, new_id) {
+            return Err(BitLoomError::FieldExists {
+                protocol_id: owner.id.clone(),
+                field_id: new_id.to_string(),
+            });
+        }
+        let proto =
+            self.protocols
+                .get_mut(protocol_id)
+                .ok_or_else(|| BitLoomError::ProtocolNotFound {
+                    protocol_id: protocol_id.to_string(),
+                })?;
+        proto.update_field_id(old_id, new_id)?;
+        self.invalidate(protocol_id);
+
+        // each subprotocol whose parent sees the renamed field, and whether it has a field of
+        // the old ID of its own, which its scripts refer to instead
+        let mut visible = Vec::new();
+        for id in self.get_descendant_ids(protocol_id) {
+            let chain = self.get_inheritance_chain(&id);
+            let Some(start) = chain.iter().position(|p| p.id == protocol_id) else {
+                continue;
+            };
+            let declares = |p: &&Protocol| p.fields.iter().any(|f| f.id == old_id);
+            if !chain[start + 1..chain.len() - 1].iter().any(declares) {
+                visible.push((id, chain.last().is_some_and(declares)));
+            }
+        }
+        for (id, shadows) in visible {
+            if let Some(child) = self.protocols.get_mut(&id) {
+                if !shadows {
+                    child.rename_field_references(old_id, new_id);
+                }
+                if let Some(value) = child.parent_constraints.remove(old_id) {
+                    child.parent_constraints.insert(new_id.to_string(), value);
+                }
+            }
         }
         Ok(())
     }
 
+    /// List everything that refers to a field of a protocol, i.e. what would be affected by
+    /// renaming or removing it. Only the protocol itself and its subprotocols can see the field.
+    pub fn get_field_references(&self, protocol_id: &str, field_id: &str) -> Vec<FieldReference> {
+        let mut scope = vec![protocol_id.to_string()];
+        scope.extend(self.get_descendant_ids(protocol_id));
+
+        let mut references = Vec::new();
+        for proto in scope.iter().filter_map(|id| self.protocols.get(id)) {
+            if proto.id != protocol_id
+                && let Some(value) = proto.parent_constraints.get(field_id)
+            {
+                references.push(FieldReference::ParentConstraint {
+                    protocol_id: proto.id.clone(),
+                    value: *value,
+                });
+            }
+
+            for field in &proto.fields {
+                if field.id != field_id
+                    && let Some(script) = field.field_type.script()
+                    && references_identifier(script, field_id)
+                {
+                    references.push(FieldReference::Expression {
+                        protocol_id: proto.id.clone(),
+                        field_id: field.id.clone(),
+                    });
+                }
+            }
+
+            for validator in &proto.validators {
+                if references_identifier(&validator.script, field_id) {
+                    references.push(FieldReference::Validator {
+                        protocol_id: proto.id.clone(),
+                        name: validator.name.clone(),
+                    });
+                }
+            }
+
+            if proto
+                .length_field
+                .as_ref()
+                .is_some_and(|length| length.field_id == field_id)
+            {
+                references.push(FieldReference::LengthField {
+                    protocol_id: proto.id.clone(),
+                });
+            }
+
+            if proto.sequence_field.as_deref() == Some(field_id) {
+                references.push(FieldReference::SequenceField {
+                    protocol_id: proto.id.clone(),
+                });
+            }
+        }
+        references
+    }
+
     /// Change the ID of a protocol, and update all references to it (e.g. parent_id in child protocols)
-    pub fn update_protocol_id(&mut self, old_id: &str, new_id: &str) -> Result<(), String> {
+    pub fn update_protocol_id(&mut self, old_id: &str, new_id: &str) -> Result<(), BitLoomError> {
         if old_id == new_id {
             return Ok(()); // no change needed
         }
 
+        check_identifier("Protocol", new_id)?;
         if self.protocols.contains_key(new_id) {
-            return Err(format!("Protocol with ID '{}' already exists", new_id));
+            return Err(BitLoomError::ProtocolExists {
+                protocol_id: new_id.to_string(),
+            });
         }
 
         if let Some(mut proto) = self.protocols.remove(old_id) {
+            self.invalidate_all();
             proto.id = new_id.to_string();
             self.protocols.insert(new_id.to_string(), proto);
+            for id in self.order.iter_mut().filter(|id| *id == old_id) {
+                *id = new_id.to_string();
+            }
 
             // Update parent references in child protocols
             for p in self.protocols.values_mut() {
@@ -264,7 +881,9 @@ impl ProtocolRegistry {
             }
             Ok(())
         } else {
-            Err(format!("Protocol with ID '{}' does not exist", old_id))
+            Err(BitLoomError::ProtocolNotFound {
+                protocol_id: old_id.to_string(),
+            })
         }
     }
 
@@ -272,16 +891,75 @@ impl ProtocolRegistry {
         self.protocols.get(protocol_id)
     }
 
+    /// All protocols, in their listed order
+    pub fn list_protocols(&self) -> Vec<&Protocol> {
+        self.iter().collect()
+    }
+
+    /// All protocols, in their listed order
+    pub fn iter(&self) -> impl Iterator<Item = &Protocol> {
+        self.order.iter().filter_map(|id| self.protocols.get(id))
+    }
+
+    /// The protocols without a parent, in their listed order
+    pub fn iter_roots(&self) -> impl Iterator<Item = &Protocol> {
+        self.iter().filter(|p| p.parent_id.is_none())
+    }
+
+    /// The direct subprotocols of a protocol, in their listed order
+    pub fn children_of<'a>(&'a self, protocol_id: &'a str) -> impl Iterator<Item = &'a Protocol> {
+        self.iter()
+            .filter(move |p| p.parent_id.as_deref() == Some(protocol_id))
+    }
+
+    /// Move a protocol up or down past the next protocol with the same parent
+    pub fn move_protocol(&mut self, protocol_id: &str, up: bool) -> Result<(), BitLoomError> {
+        let parent_id = self
+            .get_protocol(protocol_id)
+            .ok_or_else(|| BitLoomError::ProtocolNotFound {
+                protocol_id: protocol_id.to_string(),
+            })?
+            .parent_id
+            .clone();
+        let siblings: Vec<&str> = self
+            .iter()
+            .filter(|p| p.parent_id == parent_id)
+            .map(|p| p.id.as_str())
+            .collect();
+        let index = siblings
+            .iter()
+            .position(|id| *id == protocol_id)
+            .unwrap_or(0);
+        let neighbour = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1)
+        };
+        let Some(&neighbour) = neighbour.and_then(|i| siblings.get(i)) else {
+            return Ok(()); // already first or last
+        };
+        let position = |id: &str| self.order.iter().position(|o| o == id).unwrap_or(0);
+        let (a, b) = (position(protocol_id), position(neighbour));
+        self.order.swap(a, b);
+        Ok(())
+    }
+
+    /// List the protocols sorted by ID
+    pub fn sort_protocols(&mut self) {
+        self.order.sort();
+    }
+
     /// Edits the properties of an existing protocol using the provided closure.
     ///
     /// ### Constraints
     /// - The protocol `id` cannot be modified within this closure, please use [`Self::update_protocol_id`] instead.
     /// - The `parent_id` is immutable after creation to
     ///   ensure the stability of the inheritance tree.
-    pub fn edit_protocol<F>(&mut self, protocol_id: &str, f: F) -> Result<(), String>
+    pub fn edit_protocol<F>(&mut self, protocol_id: &str, f: F) -> Result<(), BitLoomError>
     where
-        F: FnOnce(&mut Protocol) -> Result<(), String>,
+        F: FnOnce(&mut Protocol) -> Result<(), BitLoomError>,
     {
+        self.invalidate(protocol_id);
         if let Some(proto) = self.protocols.get_mut(protocol_id) {
             let backup = proto.clone();
 
@@ -293,33 +971,98 @@ impl ProtocolRegistry {
 
             if proto.id != backup.id {
                 *proto = backup;
-                return Err("Protocol ID cannot be changed through edit_protocol; use rename_protocol instead".to_string());
+                return Err(BitLoomError::ProtocolIdChanged {
+                    protocol_id: protocol_id.to_string(),
+                });
             }
 
             if proto.parent_id != backup.parent_id {
                 *proto = backup;
-                return Err(
-                    "Inheritance relationship (parent_id) is immutable after creation".to_string(),
-                );
+                return Err(BitLoomError::ParentChanged {
+                    protocol_id: protocol_id.to_string(),
+                });
             }
 
             Ok(())
         } else {
-            Err(format!("Protocol with ID '{}' does not exist", protocol_id))
+            Err(BitLoomError::ProtocolNotFound {
+                protocol_id: protocol_id.to_string(),
+            })
         }
     }
 
+    /// Restore a protocol to a previously recorded snapshot.
+    /// The protocol's ID and `parent_id` are kept as they are.
+    pub fn restore_protocol(&mut self, snapshot: &Protocol) -> Result<(), BitLoomError> {
+        self.edit_protocol(&snapshot.id, |p| {
+            *p = Protocol {
+                id: p.id.clone(),
+                parent_id: p.parent_id.clone(),
+                ..snapshot.clone()
+            };
+            Ok(())
+        })
+    }
+
+    /// Why a protocol does not fit the length budgets of itself and its ancestors, if it does not.
+    /// A variable length protocol is checked with its fixed prefix, as the shortest it can be.
+    pub fn length_budget_issue(&self, protocol_id: &str) -> Option<String> {
+        let bits = match self.get_total_length(protocol_id) {
+            ProtocolLength::Fixed(bits) | ProtocolLength::Variable(bits) => bits,
+        };
+        self.get_inheritance_chain(protocol_id)
+            .into_iter()
+            .rev()
+            .find_map(|proto| {
+                let max = proto.max_length.filter(|max| bits > *max)?;
+                Some(format!(
+                    "{} bits exceed the budget of {} bits set on '{}' by {} bits",
+                    bits,
+                    max,
+                    proto.id,
+                    bits - max
+                ))
+            })
+    }
+
+    /// Frame length of a protocol: its own, else that of its nearest ancestor declaring one.
+    /// Returns the frame length in bits with the ID of the protocol declaring it.
+    pub fn frame_length(&self, protocol_id: &str) -> Option<(u32, &str)> {
+        self.get_inheritance_chain(protocol_id)
+            .into_iter()
+            .rev()
+            .find_map(|proto| Some((proto.frame_length?, proto.id.as_str())))
+    }
+
+    /// Length field of a protocol: its own, else that of its nearest ancestor declaring one
+    pub fn length_field(&self, protocol_id: &str) -> Option<&LengthField> {
+        self.get_inheritance_chain(protocol_id)
+            .into_iter()
+            .rev()
+            .find_map(|proto| proto.length_field.as_ref())
+    }
+
+    /// Sequence number field of a protocol: its own, else that of its nearest ancestor
+    /// declaring one. Returns the field ID with the ID of the protocol declaring it.
+    pub fn sequence_field(&self, protocol_id: &str) -> Option<(&str, &str)> {
+        self.get_inheritance_chain(protocol_id)
+            .into_iter()
+            .rev()
+            .find_map(|proto| Some((proto.sequence_field.as_deref()?, proto.id.as_str())))
+    }
+
     /// Get the full inheritance chain of a protocol, starting from the root ancestor down to the protocol itself.
     pub fn get_inheritance_chain(&self, protocol_id: &str) -> Vec<&Protocol> {
         let mut chain = Vec::new();
         let mut current_id = Some(protocol_id);
 
         while let Some(id) = current_id {
-            if let Some(proto) = self.protocols.get(id) {
-                chain.push(proto);
-                current_id = proto.parent_id.as_deref();
-            } else {
-                break; // invalid parent reference, stop the chain
+            match self.protocols.get(id) {
+                Some(proto) if !chain.iter().any(|p: &&Protocol| p.id == proto.id) => {
+                    chain.push(proto);
+                    current_id = proto.parent_id.as_deref();
+                }
+                _ => break, // invalid parent reference or a cycle, stop the chain
             }
         }
 
@@ -327,6 +1070,15 @@ impl ProtocolRegistry {
         chain
     }
 
+    /// The protocol in the inheritance chain of a protocol that defines a field, i.e. the one
+    /// to edit the field in
+    pub fn field_owner(&self, protocol_id: &str, field_id: &str) -> Option<&Protocol> {
+        self.get_inheritance_chain(protocol_id)
+            .into_iter()
+            .rev()
+            .find(|p| p.fields.iter().any(|f| f.id == field_id))
+    }
+
     /// Calculate the total length of a protocol by summing the lengths of all fields in its inheritance chain.
     pub fn get_total_length(&self, protocol_id: &str) -> ProtocolLength {
         let mut total_fixed_bits = 0;
@@ -343,18 +1095,49 @@ impl ProtocolRegistry {
         ProtocolLength::Fixed(total_fixed_bits)
     }
 
-    /// Flatten and resolve all fields from the inheritance chain of a protocol.
-    pub fn resolve_fields(&self, protocol_id: &str) -> Result<Vec<FieldRule>, String> {
+    /// The fields of a protocol's inheritance chain with their offsets. Layouts are kept until
+    /// a protocol in the chain is edited, so asking again, e.g. every frame, is cheap.
+    pub fn layout(&self, protocol_id: &str) -> Result<Arc<FieldLayout>, BitLoomError> {
+        if let Some(layout) = self
+            .layouts
+            .read()
+            .ok()
+            .and_then(|l| l.get(protocol_id).cloned())
+        {
+            return Ok(layout);
+        }
         let chain = self.get_inheritance_chain(protocol_id);
         if chain.is_empty() {
-            return Err(format!("Protocol with ID '{}' does not exist", protocol_id));
+            return Err(BitLoomError::ProtocolNotFound {
+                protocol_id: protocol_id.to_string(),
+            });
         }
-
-        let mut resolved_fields = Vec::new();
-        for proto in chain {
-            resolved_fields.extend(proto.fields.iter().cloned());
+        let fields = chain
+            .iter()
+            .flat_map(|p| p.fields.iter().cloned())
+            .collect();
+        let layout = Arc::new(FieldLayout::new(fields));
+        if let Ok(mut layouts) = self.layouts.write() {
+            layouts.insert(protocol_id.to_string(), layout.clone());
         }
-        Ok(resolved_fields)
+        Ok(layout)
+    }
+
+    /// Flatten and resolve all fields from the inheritance chain of a protocol.
+    pub fn resolve_fields(&self, protocol_id: &str) -> Result<Vec<FieldRule>, BitLoomError> {
+        Ok(self.layout(protocol_id)?.fields.clone())
+    }
+
+    /// Bit offset of each wire field of a protocol from the start of the packet, counting the
+    /// fields of its ancestors. Virtual fields and fields after a variable length one have none.
+    pub fn field_offsets(&self, protocol_id: &str) -> Result<HashMap<String, u32>, BitLoomError> {
+        Ok(self.layout(protocol_id)?.offsets.clone())
+    }
+}
+
+impl Default for ProtocolRegistry {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -369,12 +1152,12 @@ impl Packet {
             protocol_id: protocol_id.to_string(),
             field_values: field_rules
                 .into_iter()
-                .map(|rule| Field::new(&rule.id, vec![], false))
+                .map(|rule| Field::new(&rule.id, Bits::default(), false))
                 .collect(),
         }
     }
 
-    pub fn set_field_value(&mut self, index: usize, value: Vec<u8>) -> Result<(), String> {
+    pub fn set_field_value(&mut self, index: usize, value: Bits) -> Result<(), String> {
         if let Some(field) = self.field_values.get_mut(index) {
             field.set_value(value);
             Ok(())
@@ -391,6 +1174,7 @@ impl Packet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::field::FieldType;
 
     #[test]
     fn test_add_field_success() {
@@ -425,6 +1209,40 @@ mod tests {
         assert_eq!(proto.fields.len(), 1);
     }
 
+    #[test]
+    fn test_insert_field() {
+        let mut proto = Protocol::test_protocol();
+        proto.with_f("field1", 8).with_f("field3", 8);
+        let field = |id: &str, length| FieldRule::new(id, FieldType::Input, length);
+
+        proto
+            .insert_field(1, field("field2", FieldLength::Fixed(4)))
+            .unwrap();
+        proto
+            .insert_field(9, field("payload", FieldLength::Variable))
+            .unwrap();
+        let ids: Vec<&str> = proto.fields.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["field1", "field2", "field3", "payload"]);
+        assert_eq!(proto.length, ProtocolLength::Variable(20));
+
+        assert!(
+            proto
+                .insert_field(0, field("field1", FieldLength::Fixed(8)))
+                .is_err()
+        );
+        assert!(
+            proto
+                .insert_field(4, field("crc", FieldLength::Fixed(8)))
+                .is_err()
+        );
+        assert!(
+            proto
+                .insert_field(0, field("rest", FieldLength::Variable))
+                .is_err()
+        );
+        assert_eq!(proto.fields.len(), 4);
+    }
+
     #[test]
     fn test_remove_field_success() {
         let mut proto = Protocol::test_protocol();
@@ -483,6 +1301,46 @@ mod tests {
         assert_eq!(proto.fields[2].id, "field1");
     }
 
+    #[test]
+    fn test_move_fields_together() {
+        let mut proto = Protocol::test_protocol();
+        proto
+            .with_f("a", 8)
+            .with_f("b", 8)
+            .with_f("c", 8)
+            .with_f("d", 8);
+        let ids = |proto: &Protocol| -> Vec<String> {
+            proto.fields.iter().map(|f| f.id.clone()).collect()
+        };
+
+        let selection = vec!["a".to_string(), "c".to_string()];
+        proto.move_fields(&selection, true).unwrap();
+        assert_eq!(ids(&proto), vec!["a", "c", "b", "d"]);
+        proto.move_fields(&selection, false).unwrap();
+        assert_eq!(ids(&proto), vec!["b", "a", "c", "d"]);
+        proto.move_fields(&selection, false).unwrap();
+        assert_eq!(ids(&proto), vec!["b", "d", "a", "c"]);
+        proto.move_fields(&selection, false).unwrap();
+        assert_eq!(ids(&proto), vec!["b", "d", "a", "c"]);
+        assert_eq!(
+            proto.move_fields(&["x".to_string()], true),
+            Err(BitLoomError::FieldNotFound {
+                protocol_id: "test_proto".to_string(),
+                field_id: "x".to_string(),
+            })
+        );
+
+        proto
+            .add_field(FieldRule::new(
+                "payload",
+                FieldType::Input,
+                FieldLength::Variable,
+            ))
+            .unwrap();
+        assert!(proto.move_fields(&["payload".to_string()], true).is_err());
+        assert_eq!(proto.fields[4].id, "payload");
+    }
+
     #[test]
     fn test_update_field_id_success() {
         let mut proto = Protocol::test_protocol();
@@ -501,6 +1359,50 @@ mod tests {
         assert_eq!(proto.fields[0].id, "field1"); // ID should remain unchanged
     }
 
+    #[test]
+    fn test_update_field_id_renames_script_references() {
+        let mut proto = Protocol::test_protocol();
+        proto.with_f("raw_temp", 16);
+        let derived = FieldRule::new(
+            "temperature",
+            FieldType::Derived("raw_temp / 10.0".to_string()),
+            FieldLength::Fixed(0),
+        );
+        proto.add_field(derived).unwrap();
+
+        proto.update_field_id("raw_temp", "temp_counts").unwrap();
+        assert_eq!(
+            proto.fields[1].field_type,
+            FieldType::Derived("temp_counts / 10.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rename_field_updates_validators() {
+        let mut registry = ProtocolRegistry::new();
+        registry.with_proto("proto", None);
+        let proto = registry.protocols.get_mut("proto").unwrap();
+        proto.with_f("length", 8);
+        proto.validators.push(PacketValidator {
+            name: "length matches".to_string(),
+            severity: Severity::Error,
+            script: "length == payload.len()".to_string(),
+        });
+
+        assert_eq!(
+            registry.get_field_references("proto", "length"),
+            vec![FieldReference::Validator {
+                protocol_id: "proto".to_string(),
+                name: "length matches".to_string(),
+            }]
+        );
+        registry.rename_field("proto", "length", "len").unwrap();
+        assert_eq!(
+            registry.get_protocol("proto").unwrap().validators[0].script,
+            "len == payload.len()"
+        );
+    }
+
     #[test]
     fn test_edit_field_success() {
         let mut proto = Protocol::test_protocol();
@@ -549,6 +1451,33 @@ mod tests {
         assert_eq!(proto.length, ProtocolLength::Variable(24));
     }
 
+    #[test]
+    fn test_derived_field_after_variable_length_field() {
+        let mut proto = Protocol::test_protocol();
+        proto.with_f("raw_temp", 16);
+        let payload = FieldRule::new("payload", FieldType::Input, FieldLength::Variable);
+        let derived = FieldRule::new(
+            "temperature_celsius",
+            FieldType::Derived("raw_temp / 10".to_string()),
+            FieldLength::Fixed(0),
+        );
+
+        assert!(proto.add_field(payload).is_ok());
+        assert!(proto.add_field(derived).is_ok());
+        assert_eq!(proto.length, ProtocolLength::Variable(16));
+
+        // a wire field still cannot follow the variable length field
+        let field3 = FieldRule::new("field3", FieldType::Fixed(0), FieldLength::Fixed(8));
+        assert_eq!(
+            proto.add_field(field3),
+            Err(BitLoomError::FieldAfterVariable {
+                protocol_id: "test_proto".to_string(),
+                field_id: "field3".to_string(),
+                variable_id: "payload".to_string(),
+            })
+        );
+    }
+
     #[test]
     fn test_empty_protocol_length() {
         let proto = Protocol::test_protocol();
@@ -563,11 +1492,34 @@ mod tests {
                 .create_protocol("proto1", None, Endianness::Big, None)
                 .is_ok()
         );
+        assert!(matches!(
+            registry.create_protocol("proto1", None, Endianness::Little, None),
+            Err(BitLoomError::ProtocolExists { .. })
+        ));
+        assert!(matches!(
+            registry.create_protocol("1st", None, Endianness::Little, None),
+            Err(BitLoomError::InvalidIdentifier { .. })
+        ));
+    }
+
+    #[test]
+    fn test_add_protocols_all_or_nothing() {
+        let mut registry = ProtocolRegistry::new();
+        registry.with_proto("a", None);
+
+        let child = Protocol::new("c", None, Endianness::Big, Some("b".to_string()));
+        let duplicate = Protocol::new("a", None, Endianness::Big, None);
+        let parent = Protocol::new("b", None, Endianness::Big, Some("a".to_string()));
+        assert!(registry.add_protocols(vec![child.clone()]).is_err());
         assert!(
             registry
-                .create_protocol("proto1", None, Endianness::Little, None)
+                .add_protocols(vec![parent.clone(), duplicate])
                 .is_err()
         );
+        assert!(registry.get_protocol("b").is_none());
+
+        registry.add_protocols(vec![parent, child]).unwrap();
+        assert_eq!(registry.get_inheritance_chain("c").len(), 3);
     }
 
     #[test]
@@ -576,6 +1528,38 @@ mod tests {
         assert!(registry.get_protocol("nonexistent_proto").is_none());
     }
 
+    #[test]
+    fn test_list_protocols_ordered() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("b", None)
+            .with_proto("a", None)
+            .with_proto("b2", Some("b".to_string()))
+            .with_proto("b1", Some("b".to_string()));
+        let ids = |registry: &ProtocolRegistry| -> Vec<String> {
+            registry.iter().map(|p| p.id.clone()).collect()
+        };
+        assert_eq!(ids(&registry), vec!["b", "a", "b2", "b1"]);
+        let roots: Vec<&str> = registry.iter_roots().map(|p| p.id.as_str()).collect();
+        assert_eq!(roots, vec!["b", "a"]);
+
+        // moved past the next sibling, skipping protocols with another parent
+        registry.move_protocol("b1", true).unwrap();
+        registry.move_protocol("b", false).unwrap();
+        assert_eq!(ids(&registry), vec!["a", "b", "b1", "b2"]);
+        let children: Vec<&str> = registry.children_of("b").map(|p| p.id.as_str()).collect();
+        assert_eq!(children, vec!["b1", "b2"]);
+        registry.move_protocol("a", true).unwrap();
+        assert!(registry.move_protocol("c", true).is_err());
+
+        registry.update_protocol_id("a", "z").unwrap();
+        assert_eq!(ids(&registry), vec!["z", "b", "b1", "b2"]);
+        registry.sort_protocols();
+        assert_eq!(ids(&registry), vec!["b", "b1", "b2", "z"]);
+        registry.remove_protocol("b").unwrap();
+        assert_eq!(ids(&registry), vec!["z"]);
+    }
+
     #[test]
     fn test_remove_protocol_with_subprotocols() {
         let mut registry = ProtocolRegistry::new();
@@ -584,8 +1568,11 @@ mod tests {
             .with_proto("child_proto", Some("parent_proto".to_string()));
 
         assert_eq!(registry.protocols.len(), 2);
-        assert!(registry.remove_protocol("parent_proto").is_ok());
+        let removed = registry.remove_protocol("parent_proto").unwrap();
         assert_eq!(registry.protocols.len(), 0); // both parent and child should be removed
+
+        registry.add_protocols(removed).unwrap();
+        assert_eq!(registry.protocols.len(), 2);
     }
 
     #[test]
@@ -637,7 +1624,7 @@ mod tests {
 
         let result = registry.edit_protocol("proto1", |p| {
             p.name = Some("Another Name".to_string());
-            Err("Failed to edit protocol".to_string())
+            Err("Failed to edit protocol".into())
         });
 
         assert!(result.is_err());
@@ -656,12 +1643,26 @@ mod tests {
             p.parent_id = Some("proto1".to_string()); // attempt to change parent_id
             Ok(())
         });
+        assert!(matches!(result, Err(BitLoomError::ParentChanged { .. })));
 
         assert!(result.is_err());
         let proto2 = registry.get_protocol("proto2").unwrap();
         assert_eq!(proto2.parent_id, None);
     }
 
+    #[test]
+    fn test_restore_protocol() {
+        let mut registry = ProtocolRegistry::new();
+        registry.with_proto("proto1", None);
+        let snapshot = registry.get_protocol("proto1").unwrap().clone();
+
+        registry
+            .edit_protocol("proto1", |p| p.add_field(FieldRule::default()))
+            .unwrap();
+        assert!(registry.restore_protocol(&snapshot).is_ok());
+        assert_eq!(registry.get_protocol("proto1").unwrap(), &snapshot);
+    }
+
     #[test]
     fn test_get_inheritance_chain() {
         let mut registry = ProtocolRegistry::new();
@@ -677,6 +1678,307 @@ mod tests {
         assert_eq!(chain[2].id, "child");
     }
 
+    #[test]
+    fn test_rename_field_updates_subprotocols() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("parent", None)
+            .with_proto("child", Some("parent".to_string()))
+            .with_proto("grandchild", Some("child".to_string()));
+
+        registry
+            .protocols
+            .get_mut("parent")
+            .unwrap()
+            .with_f("msg_type", 8);
+        let child = registry.protocols.get_mut("child").unwrap();
+        child.set_parent_constraint("msg_type", 1);
+        let expr = FieldRule::new(
+            "echo",
+            FieldType::Expr("msg_type + 1".to_string()),
+            FieldLength::Fixed(8),
+        );
+        registry
+            .protocols
+            .get_mut("grandchild")
+            .unwrap()
+            .add_field(expr)
+            .unwrap();
+
+        assert_eq!(registry.get_field_references("parent", "msg_type").len(), 2);
+        registry.rename_field("parent", "msg_type", "kind").unwrap();
+
+        let child = registry.get_protocol("child").unwrap();
+        assert_eq!(child.parent_constraints.get("kind"), Some(&1));
+        assert!(!child.parent_constraints.contains_key("msg_type"));
+        let grandchild = registry.get_protocol("grandchild").unwrap();
+        assert_eq!(
+            grandchild.fields[0].field_type,
+            FieldType::Expr("kind + 1".to_string())
+        );
+        assert!(
+            registry
+                .get_field_references("parent", "msg_type")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_rename_field_rejects_ids_of_related_protocols() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("parent", None)
+            .with_proto("child", Some("parent".to_string()))
+            .with_proto("other", None);
+        registry
+            .protocols
+            .get_mut("parent")
+            .unwrap()
+            .with_f("kind", 8);
+        registry
+            .protocols
+            .get_mut("child")
+            .unwrap()
+            .with_f("value", 8)
+            .with_f("crc", 8);
+        registry.protocols.get_mut("other").unwrap().with_f("id", 8);
+
+        let err = registry.rename_field("parent", "kind", "crc").unwrap_err();
+        assert_eq!(
+            err,
+            BitLoomError::FieldExists {
+                protocol_id: "child".to_string(),
+                field_id: "crc".to_string(),
+            }
+        );
+        let err = registry.rename_field("child", "value", "kind").unwrap_err();
+        assert_eq!(
+            err,
+            BitLoomError::FieldExists {
+                protocol_id: "parent".to_string(),
+                field_id: "kind".to_string(),
+            }
+        );
+        // unrelated protocols may use the ID
+        registry.rename_field("parent", "kind", "id").unwrap();
+        assert_eq!(registry.get_protocol("parent").unwrap().fields[0].id, "id");
+    }
+
+    #[test]
+    fn test_rename_field_skips_shadowing_subprotocols() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("parent", None)
+            .with_proto("child", Some("parent".to_string()))
+            .with_proto("grandchild", Some("child".to_string()));
+        registry
+            .protocols
+            .get_mut("parent")
+            .unwrap()
+            .with_f("kind", 8);
+        let echo = |id: &str| {
+            FieldRule::new(
+                id,
+                FieldType::Expr("kind + 1".to_string()),
+                FieldLength::Fixed(8),
+            )
+        };
+        // the child declares a field of the same ID, which its scripts and those of its
+        // subprotocols see instead
+        let child = registry.protocols.get_mut("child").unwrap();
+        child.set_parent_constraint("kind", 1);
+        child.with_f("kind", 8).add_field(echo("echo")).unwrap();
+        let grandchild = registry.protocols.get_mut("grandchild").unwrap();
+        grandchild.set_parent_constraint("kind", 2);
+        grandchild.add_field(echo("echo_2")).unwrap();
+
+        registry.rename_field("parent", "kind", "msg_type").unwrap();
+
+        let child = registry.get_protocol("child").unwrap();
+        assert_eq!(child.parent_constraints.get("msg_type"), Some(&1));
+        assert_eq!(
+            child.fields[1].field_type,
+            FieldType::Expr("kind + 1".to_string())
+        );
+        let grandchild = registry.get_protocol("grandchild").unwrap();
+        assert_eq!(grandchild.parent_constraints.get("kind"), Some(&2));
+        assert_eq!(
+            grandchild.fields[0].field_type,
+            FieldType::Expr("kind + 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_length_budget_applies_to_subprotocols() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("base", None)
+            .with_proto("frame", Some("base".to_string()));
+        registry
+            .edit_protocol("base", |p| {
+                p.with_f("header", 16);
+                p.max_length = Some(32);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(registry.length_budget_issue("frame"), None);
+
+        registry
+            .edit_protocol("frame", |p| {
+                p.with_f("id", 16).with_f("crc", 8);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            registry.length_budget_issue("frame").as_deref(),
+            Some("40 bits exceed the budget of 32 bits set on 'base' by 8 bits")
+        );
+        assert_eq!(registry.length_budget_issue("base"), None);
+    }
+
+    #[test]
+    fn test_field_offsets_include_parent_fields() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("base", None)
+            .with_proto("frame", Some("base".to_string()));
+        registry
+            .edit_protocol("base", |p| {
+                p.with_f("version", 4).with_f("flags", 12);
+                Ok(())
+            })
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "sum",
+                    FieldType::Derived("flags + 1".to_string()),
+                    FieldLength::Fixed(8),
+                ))?;
+                p.with_f("id", 8);
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))
+            })
+            .unwrap();
+
+        let offsets = registry.field_offsets("frame").unwrap();
+        assert_eq!(offsets.get("version"), Some(&0));
+        assert_eq!(offsets.get("flags"), Some(&4));
+        assert_eq!(offsets.get("id"), Some(&16));
+        assert_eq!(offsets.get("payload"), Some(&24));
+        assert_eq!(offsets.get("sum"), None);
+        assert!(registry.field_offsets("missing").is_err());
+
+        let owner = |field| registry.field_owner("frame", field).map(|p| p.id.as_str());
+        assert_eq!(owner("flags"), Some("base"));
+        assert_eq!(owner("id"), Some("frame"));
+        assert_eq!(owner("missing"), None);
+    }
+
+    #[test]
+    fn test_layout_cache_invalidation() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("base", None)
+            .with_proto("frame", Some("base".to_string()));
+        registry
+            .edit_protocol("base", |p| {
+                p.with_f("version", 4);
+                Ok(())
+            })
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.with_f("id", 8);
+                Ok(())
+            })
+            .unwrap();
+
+        let layout = registry.layout("frame").unwrap();
+        assert!(Arc::ptr_eq(&layout, &registry.layout("frame").unwrap()));
+        assert_eq!(layout.offsets.get("id"), Some(&4));
+
+        // editing an ancestor changes the layout of its subprotocols
+        registry
+            .edit_protocol("base", |p| {
+                p.with_f("flags", 4);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            registry.layout("frame").unwrap().offsets.get("id"),
+            Some(&8)
+        );
+
+        registry.rename_field("base", "flags", "bits").unwrap();
+        let ids: Vec<String> = registry
+            .resolve_fields("frame")
+            .unwrap()
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+        assert_eq!(ids, vec!["version", "bits", "id"]);
+
+        let removed = registry.remove_protocol("frame").unwrap();
+        assert!(registry.layout("frame").is_err());
+        registry.add_protocols(removed).unwrap();
+        assert_eq!(registry.layout("frame").unwrap().fields.len(), 3);
+
+        // clones keep their own cache
+        let copy = registry.clone();
+        registry
+            .edit_protocol("frame", |p| p.remove_field("id"))
+            .unwrap();
+        assert_eq!(copy.layout("frame").unwrap().fields.len(), 3);
+        assert_eq!(registry.layout("frame").unwrap().fields.len(), 2);
+    }
+
+    #[test]
+    fn test_paste_fields_deduplicates_ids() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .with_proto("base", None)
+            .with_proto("frame", Some("base".to_string()));
+        registry
+            .edit_protocol("base", |p| {
+                p.add_field(FieldRule::new(
+                    "len",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+
+        let fields = vec![
+            FieldRule::new("len", FieldType::Input, FieldLength::Fixed(8)),
+            FieldRule::new("len_2", FieldType::Input, FieldLength::Fixed(8)),
+            FieldRule::new(
+                "crc",
+                FieldType::Expr("len * 2".to_string()),
+                FieldLength::Fixed(8),
+            ),
+        ];
+        let ids = registry.paste_fields("frame", fields.clone()).unwrap();
+        assert_eq!(ids, vec!["len_3", "len_2", "crc"]);
+        let frame = registry.get_protocol("frame").unwrap();
+        assert_eq!(
+            frame.fields[2].field_type,
+            FieldType::Expr("len_3 * 2".to_string())
+        );
+
+        // pasting again renames everything, and fails as a whole after a variable length field
+        let payload = FieldRule::new("payload", FieldType::Input, FieldLength::Variable);
+        let ids = registry
+            .paste_fields("frame", vec![fields[2].clone(), payload])
+            .unwrap();
+        assert_eq!(ids, vec!["crc_2", "payload"]);
+        assert!(registry.paste_fields("frame", fields).is_err());
+        assert_eq!(registry.get_protocol("frame").unwrap().fields.len(), 5);
+    }
+
     #[test]
     fn test_get_total_length() {
         let mut registry = ProtocolRegistry::new();
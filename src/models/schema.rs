@@ -0,0 +1,492 @@
+//! JSON Schema of the `.bitloom` project format, i.e. [`BitLoomProject`] as serialized by serde.
+//!
+//! [`BitLoomProject`]: super::project::BitLoomProject
+
+use serde_json::{Value, json};
+
+/// The JSON Schema (draft 2020-12) a `.bitloom` project file conforms to
+pub fn project_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "BitLoom project",
+        "type": "object",
+        "required": ["project_version", "protocols"],
+        "properties": {
+            "project_version": { "type": "integer", "minimum": 0 },
+            "protocols": { "type": "array", "items": { "$ref": "#/$defs/Protocol" } },
+            "history": {
+                "description": "Committed revisions of all protocols, oldest first",
+                "type": "object",
+                "required": ["revisions"],
+                "properties": {
+                    "revisions": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/ProtocolRevision" }
+                    }
+                }
+            },
+            "script_library": {
+                "description": "rhai functions and constants shared by all field expressions",
+                "type": "string"
+            },
+            "presets": {
+                "description": "Named packets for the packet builder",
+                "type": "array",
+                "items": { "$ref": "#/$defs/PacketPreset" }
+            },
+            "bookmarks": {
+                "description": "Packets with bookmarked ranges",
+                "type": "array",
+                "items": { "$ref": "#/$defs/AnnotatedPacket" }
+            },
+            "packet_columns": {
+                "description": "Fields shown as columns of the capture packet list, by protocol ID",
+                "type": "object",
+                "additionalProperties": { "type": "array", "items": { "type": "string" } }
+            }
+        },
+        "$defs": {
+            "Protocol": {
+                "type": "object",
+                "required": [
+                    "id",
+                    "endianness",
+                    "fields",
+                    "length",
+                    "metadata",
+                    "parent_constraints"
+                ],
+                "properties": {
+                    "id": { "type": "string", "minLength": 1 },
+                    "name": { "type": ["string", "null"] },
+                    "endianness": { "enum": ["Big", "Little"] },
+                    "fields": { "type": "array", "items": { "$ref": "#/$defs/FieldRule" } },
+                    "length": {
+                        "description": "Length in bits; for variable length protocols the fixed prefix",
+                        "oneOf": [
+                            tagged("Fixed", bits()),
+                            tagged("Variable", bits())
+                        ]
+                    },
+                    "description": {
+                        "description": "Markdown",
+                        "type": ["string", "null"]
+                    },
+                    "metadata": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" }
+                    },
+                    "parent_id": { "type": ["string", "null"] },
+                    "parent_constraints": {
+                        "description": "Values parent fields must have for this subprotocol to apply, by field ID",
+                        "type": "object",
+                        "additionalProperties": { "type": "integer" }
+                    },
+                    "validators": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/PacketValidator" }
+                    },
+                    "max_length": {
+                        "description": "Most bits a packet of the protocol and its subprotocols may take",
+                        "type": ["integer", "null"],
+                        "minimum": 1
+                    },
+                    "frame_length": {
+                        "description": "Exact bits of a packet of the protocol and its subprotocols, padded with zero bits after the last field",
+                        "type": ["integer", "null"],
+                        "minimum": 1
+                    },
+                    "length_field": {
+                        "description": "Field giving the packet length in bytes, for finding messages in a byte stream",
+                        "type": ["object", "null"],
+                        "required": ["field_id", "adjustment"],
+                        "properties": {
+                            "field_id": { "type": "string", "minLength": 1 },
+                            "adjustment": {
+                                "description": "Added to the field value to get the packet length",
+                                "type": "integer"
+                            }
+                        }
+                    },
+                    "sequence_field": {
+                        "description": "Field numbering the packets in sequence, for finding dropped and repeated packets",
+                        "type": ["string", "null"],
+                        "minLength": 1
+                    },
+                    "notes": {
+                        "description": "Free-form observations about the protocol",
+                        "type": "string"
+                    }
+                }
+            },
+            "PacketPreset": {
+                "type": "object",
+                "required": ["name", "protocol_id", "values"],
+                "properties": {
+                    "name": { "type": "string", "minLength": 1 },
+                    "folder": {
+                        "description": "Folder path with '/' between nested folders",
+                        "type": "string"
+                    },
+                    "protocol_id": { "type": "string", "minLength": 1 },
+                    "values": {
+                        "description": "Value literal of each field by field ID",
+                        "type": "object",
+                        "additionalProperties": { "type": "string" }
+                    },
+                    "notes": {
+                        "description": "Free-form observations about the packet",
+                        "type": "string"
+                    }
+                }
+            },
+            "FieldRule": {
+                "type": "object",
+                "required": ["id", "field_type", "length"],
+                "properties": {
+                    "id": { "type": "string", "minLength": 1 },
+                    "name": { "type": ["string", "null"] },
+                    "field_type": { "$ref": "#/$defs/FieldType" },
+                    "length": {
+                        "oneOf": [
+                            tagged("Fixed", bits()),
+                            { "const": "Variable" }
+                        ]
+                    },
+                    "description": {
+                        "description": "Markdown",
+                        "type": ["string", "null"]
+                    },
+                    "color": {
+                        "description": "RGB color the field is shown in",
+                        "type": ["array", "null"],
+                        "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                        "minItems": 3,
+                        "maxItems": 3
+                    },
+                    "display": {
+                        "description": "How values are shown",
+                        "enum": ["Decimal", "Hex", "Binary", "Octal", "Ascii", null]
+                    },
+                    "endianness": {
+                        "description": "Byte order overriding the protocol's",
+                        "enum": ["Big", "Little", null]
+                    },
+                    "subfields": {
+                        "description": "Named bit ranges within the value of the field",
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/SubField" }
+                    },
+                    "mapping": value_mapping(),
+                    "sign_encoding": {
+                        "description": "How negative values of signed range fields are written",
+                        "enum": ["TwosComplement", "SignMagnitude"]
+                    },
+                    "severity": {
+                        "description": "Whether a value breaking the field's constraints fails decoding and encoding or is a warning",
+                        "enum": ["Error", "Warning"]
+                    }
+                }
+            },
+            "SubField": {
+                "type": "object",
+                "required": ["id", "lsb", "bits"],
+                "properties": {
+                    "id": { "type": "string", "minLength": 1 },
+                    "name": { "type": ["string", "null"] },
+                    "description": { "type": ["string", "null"] },
+                    "lsb": {
+                        "description": "Lowest bit of the range, counted from the least significant bit",
+                        "type": "integer",
+                        "minimum": 0
+                    },
+                    "bits": { "type": "integer", "minimum": 1, "maximum": 64 }
+                }
+            },
+            "FieldType": {
+                "oneOf": [
+                    tagged("Fixed", json!({ "type": "integer" })),
+                    tagged("Enum", json!({
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/EnumVariant" }
+                    })),
+                    tagged("Range", json!({
+                        "type": "object",
+                        "required": ["min", "max", "is_signed"],
+                        "properties": {
+                            "min": { "type": "integer" },
+                            "max": { "type": "integer" },
+                            "is_signed": { "type": "boolean" }
+                        }
+                    })),
+                    tagged("Expr", json!({
+                        "description": "rhai script computing the value on encode",
+                        "type": "string"
+                    })),
+                    tagged("Derived", json!({
+                        "description": "rhai script evaluated on decode, not on the wire",
+                        "type": "string"
+                    })),
+                    { "const": "Input" }
+                ]
+            },
+            "EnumVariant": {
+                "type": "object",
+                "required": ["value"],
+                "properties": {
+                    "value": { "type": "integer" },
+                    "name": { "type": ["string", "null"] },
+                    "description": { "type": ["string", "null"] }
+                }
+            },
+            "PacketValidator": {
+                "type": "object",
+                "required": ["name", "severity", "script"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "severity": { "enum": ["Error", "Warning"] },
+                    "script": { "type": "string" }
+                }
+            },
+            "AnnotatedPacket": {
+                "type": "object",
+                "required": ["data", "bookmarks"],
+                "properties": {
+                    "data": {
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0, "maximum": 255 }
+                    },
+                    "bookmarks": { "type": "array", "items": { "$ref": "#/$defs/Bookmark" } }
+                }
+            },
+            "Bookmark": {
+                "type": "object",
+                "required": ["name", "bit_offset", "bit_len"],
+                "properties": {
+                    "name": { "type": "string", "minLength": 1 },
+                    "comment": { "type": "string" },
+                    "bit_offset": bits(),
+                    "bit_len": bits()
+                }
+            },
+            "ProtocolRevision": {
+                "type": "object",
+                "required": ["message", "timestamp", "snapshot"],
+                "properties": {
+                    "message": { "type": "string" },
+                    "timestamp": {
+                        "description": "Seconds since the Unix epoch",
+                        "type": "integer",
+                        "minimum": 0
+                    },
+                    "snapshot": { "$ref": "#/$defs/Protocol" }
+                }
+            }
+        }
+    })
+}
+
+/// An enum variant with data, which serde writes as `{ "Variant": data }`
+fn tagged(variant: &str, data: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": [variant],
+        "properties": { variant: data },
+        "additionalProperties": false
+    })
+}
+
+fn bits() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn value_mapping() -> Value {
+    let entry = json!({
+        "type": "object",
+        "required": ["raw", "shown"],
+        "properties": {
+            "raw": { "type": "integer" },
+            "shown": { "type": "string" }
+        }
+    });
+    let point = json!({
+        "type": "object",
+        "required": ["raw", "value"],
+        "properties": {
+            "raw": { "type": "number" },
+            "value": { "type": "number" }
+        }
+    });
+    json!({
+        "description": "How values are translated for display",
+        "oneOf": [
+            { "type": "null" },
+            tagged("Table", json!({ "type": "array", "items": entry })),
+            tagged("Curve", json!({
+                "type": "object",
+                "required": ["points"],
+                "properties": {
+                    "points": { "type": "array", "items": point },
+                    "unit": { "type": ["string", "null"] }
+                }
+            }))
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::bookmark::{AnnotatedPacket, Bookmark};
+    use crate::models::field::{
+        CurvePoint, DisplayFormat, EnumVariant, FieldLength, FieldRule, FieldType, MappingEntry,
+        SubField, ValueMapping,
+    };
+    use crate::models::history::RevisionHistory;
+    use crate::models::preset::PacketPreset;
+    use crate::models::project::{BitLoomProject, PROJECT_VERSION};
+    use crate::models::protocol::{Endianness, LengthField, PacketValidator, Protocol, Severity};
+
+    /// Check that every object in `value` only has properties the schema knows, and all the
+    /// required ones. Only covers the parts of JSON Schema used above.
+    fn check(schema: &Value, root: &Value, value: &Value, path: &str) {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            return check(&root["$defs"][name], root, value, path);
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            let matching = options
+                .iter()
+                .filter(|option| match (value, &option["const"]) {
+                    (Value::Null, _) => option["type"] == "null",
+                    (_, Value::Null) => option["required"].as_array().is_some_and(|r| {
+                        r.iter().all(|k| value.get(k.as_str().unwrap()).is_some())
+                    }),
+                    (value, constant) => value == constant,
+                });
+            let matching: Vec<_> = matching.collect();
+            assert_eq!(matching.len(), 1, "{} matches no variant: {}", path, value);
+            return check(matching[0], root, value, path);
+        }
+        match value {
+            Value::Object(map) => {
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    let key = required.as_str().unwrap();
+                    assert!(map.contains_key(key), "{} is missing '{}'", path, key);
+                }
+                for (key, item) in map {
+                    let property = schema["properties"]
+                        .get(key)
+                        .or_else(|| schema.get("additionalProperties").filter(|a| a.is_object()))
+                        .unwrap_or_else(|| panic!("{} has unknown property '{}'", path, key));
+                    check(property, root, item, &format!("{}.{}", path, key));
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    check(&schema["items"], root, item, &format!("{}[{}]", path, i));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_project_matches_schema() {
+        let mut protocol = Protocol::new("frame", None, Endianness::Little, None);
+        protocol.update_metadata("tags", "serial");
+        protocol.notes = "device rejects frames over 200 bytes".to_string();
+        protocol.max_length = Some(512);
+        protocol.frame_length = Some(256);
+        protocol.length_field = Some(LengthField {
+            field_id: "length".to_string(),
+            adjustment: 4,
+        });
+        protocol.validators.push(PacketValidator {
+            name: "check".to_string(),
+            severity: Severity::Warning,
+            script: "true".to_string(),
+        });
+        let fields = [
+            FieldType::Fixed(1),
+            FieldType::Enum(vec![EnumVariant {
+                value: 1,
+                name: Some("One".to_string()),
+                description: None,
+            }]),
+            FieldType::Range {
+                min: 0,
+                max: 9,
+                is_signed: false,
+            },
+            FieldType::Expr("1".to_string()),
+            FieldType::Derived("2".to_string()),
+            FieldType::Input,
+        ];
+        for (i, field_type) in fields.into_iter().enumerate() {
+            let length = if i == 5 {
+                FieldLength::Variable
+            } else {
+                FieldLength::Fixed(8)
+            };
+            let mut field = FieldRule::new(&format!("f{}", i), field_type, length);
+            field.color = (i == 0).then_some([0xe6, 0x9f, 0x00]);
+            field.display = (i == 0).then_some(DisplayFormat::Hex);
+            field.endianness = (i == 1).then_some(Endianness::Big);
+            if i == 2 {
+                field.subfields.push(SubField {
+                    id: "mode".to_string(),
+                    name: None,
+                    description: None,
+                    lsb: 4,
+                    bits: 3,
+                });
+                field.mapping = Some(ValueMapping::Table(vec![MappingEntry {
+                    raw: 1,
+                    shown: "one".to_string(),
+                }]));
+            }
+            if i == 4 {
+                field.mapping = Some(ValueMapping::Curve {
+                    points: vec![CurvePoint {
+                        raw: 0.0,
+                        value: -40.0,
+                    }],
+                    unit: Some("°C".to_string()),
+                });
+            }
+            protocol.add_field(field).unwrap();
+        }
+        let mut child = Protocol::new("child", None, Endianness::Big, Some("frame".to_string()));
+        child.set_parent_constraint("f1", 1);
+        let mut history = RevisionHistory::new();
+        history.commit(&protocol, "initial").unwrap();
+
+        let project = BitLoomProject {
+            project_version: PROJECT_VERSION,
+            protocols: vec![protocol, child],
+            history,
+            script_library: "fn f() { 1 }".to_string(),
+            presets: vec![PacketPreset {
+                name: "heartbeat".to_string(),
+                folder: "telemetry".to_string(),
+                protocol_id: "frame".to_string(),
+                values: [("f5".to_string(), "[01]".to_string())].into(),
+                notes: "device rejects this unless bit 3 is set".to_string(),
+            }],
+            bookmarks: vec![AnnotatedPacket {
+                data: vec![0x01, 0x02],
+                bookmarks: vec![Bookmark {
+                    name: "counter".to_string(),
+                    comment: "increments per packet".to_string(),
+                    bit_offset: 8,
+                    bit_len: 8,
+                }],
+            }],
+            packet_columns: [("frame".to_string(), vec!["f1".to_string()])].into(),
+        };
+        let schema = project_schema();
+        let value = serde_json::to_value(&project).unwrap();
+        check(&schema, &schema, &value, "project");
+    }
+}
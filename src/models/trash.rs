@@ -0,0 +1,217 @@
+use super::history::now_timestamp;
+use super::preset::PacketPreset;
+use super::protocol::{Protocol, ProtocolRegistry};
+
+/// A protocol removed from the project with its subprotocols and their presets, kept so that
+/// the deletion can be undone
+#[derive(Clone, PartialEq, Debug)]
+pub struct Deletion {
+    /// the removed protocols, each after its parent
+    pub protocols: Vec<Protocol>,
+    pub presets: Vec<PacketPreset>,
+    /// seconds since the Unix epoch at the deletion; 0 for a preview
+    pub timestamp: u64,
+}
+
+impl Deletion {
+    /// ID of the protocol that was deleted, as opposed to its subprotocols
+    pub fn protocol_id(&self) -> &str {
+        self.protocols.first().map_or("", |p| p.id.as_str())
+    }
+}
+
+/// What deleting a protocol would remove, without removing anything
+pub fn preview_deletion(
+    registry: &ProtocolRegistry,
+    presets: &[PacketPreset],
+    protocol_id: &str,
+) -> Result<Deletion, String> {
+    let protocol = registry
+        .get_protocol(protocol_id)
+        .ok_or_else(|| format!("Protocol with ID '{}' does not exist", protocol_id))?;
+    let protocols: Vec<Protocol> = std::iter::once(protocol)
+        .chain(
+            registry
+                .get_descendant_ids(protocol_id)
+                .iter()
+                .filter_map(|id| registry.get_protocol(id)),
+        )
+        .cloned()
+        .collect();
+    let presets = presets
+        .iter()
+        .filter(|preset| protocols.iter().any(|p| p.id == preset.protocol_id))
+        .cloned()
+        .collect();
+    Ok(Deletion {
+        protocols,
+        presets,
+        timestamp: 0,
+    })
+}
+
+/// Remove a protocol with its subprotocols and the presets of any of them
+pub fn delete_protocol(
+    registry: &mut ProtocolRegistry,
+    presets: &mut Vec<PacketPreset>,
+    protocol_id: &str,
+) -> Result<Deletion, String> {
+    let protocols = registry.remove_protocol(protocol_id)?;
+    let (removed, kept) = std::mem::take(presets)
+        .into_iter()
+        .partition(|preset: &PacketPreset| protocols.iter().any(|p| p.id == preset.protocol_id));
+    *presets = kept;
+    Ok(Deletion {
+        protocols,
+        presets: removed,
+        timestamp: now_timestamp(),
+    })
+}
+
+/// Put deleted protocols and their presets back. Fails, changing nothing, if a protocol with
+/// one of their IDs was created since or the parent of the deleted protocol is gone.
+pub fn restore_deletion(
+    registry: &mut ProtocolRegistry,
+    presets: &mut Vec<PacketPreset>,
+    deletion: Deletion,
+) -> Result<(), String> {
+    registry.add_protocols(deletion.protocols)?;
+    presets.extend(deletion.presets);
+    Ok(())
+}
+
+/// Protocols deleted during the session, oldest first, until they are restored or the trash is
+/// emptied
+#[derive(Default)]
+pub struct Trash {
+    deletions: Vec<Deletion>,
+}
+
+impl Trash {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, deletion: Deletion) {
+        self.deletions.push(deletion);
+    }
+
+    pub fn deletions(&self) -> &[Deletion] {
+        &self.deletions
+    }
+
+    pub fn last(&self) -> Option<&Deletion> {
+        self.deletions.last()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deletions.is_empty()
+    }
+
+    /// Put a deletion back into the project and take it out of the trash.
+    /// Returns the ID of the restored protocol.
+    pub fn restore(
+        &mut self,
+        index: usize,
+        registry: &mut ProtocolRegistry,
+        presets: &mut Vec<PacketPreset>,
+    ) -> Result<String, String> {
+        let deletion = self
+            .deletions
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("Deletion {} is not in the trash", index))?;
+        let protocol_id = deletion.protocol_id().to_string();
+        restore_deletion(registry, presets, deletion)?;
+        self.deletions.remove(index);
+        Ok(protocol_id)
+    }
+
+    /// Forget a deletion for good
+    pub fn discard(&mut self, index: usize) {
+        if index < self.deletions.len() {
+            self.deletions.remove(index);
+        }
+    }
+
+    pub fn empty(&mut self) {
+        self.deletions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::protocol::Endianness;
+
+    #[test]
+    fn test_delete_and_restore() {
+        let mut registry = ProtocolRegistry::new();
+        for (id, parent) in [("root", None), ("frame", Some("root")), ("other", None)] {
+            registry
+                .create_protocol(id, None, Endianness::Big, parent.map(str::to_string))
+                .unwrap();
+        }
+        let mut presets: Vec<PacketPreset> = ["frame", "other"]
+            .into_iter()
+            .map(|id| PacketPreset {
+                name: id.to_string(),
+                folder: String::new(),
+                protocol_id: id.to_string(),
+                values: Default::default(),
+                notes: String::new(),
+            })
+            .collect();
+
+        let preview = preview_deletion(&registry, &presets, "root").unwrap();
+        let deletion = delete_protocol(&mut registry, &mut presets, "root").unwrap();
+        assert_eq!(deletion.protocols, preview.protocols);
+        assert_eq!(deletion.presets, preview.presets);
+        assert_eq!(deletion.protocol_id(), "root");
+        assert_eq!(deletion.protocols.len(), 2);
+        assert_eq!(deletion.presets.len(), 1);
+        assert!(registry.get_protocol("frame").is_none());
+        assert_eq!(presets.len(), 1);
+
+        registry
+            .create_protocol("root", None, Endianness::Big, None)
+            .unwrap();
+        assert!(restore_deletion(&mut registry, &mut presets, deletion.clone()).is_err());
+        assert_eq!(presets.len(), 1);
+
+        registry.remove_protocol("root").unwrap();
+        restore_deletion(&mut registry, &mut presets, deletion).unwrap();
+        assert!(registry.get_protocol("frame").is_some());
+        assert_eq!(presets.len(), 2);
+    }
+
+    #[test]
+    fn test_trash_restores_any_deletion() {
+        let mut registry = ProtocolRegistry::new();
+        for id in ["a", "b"] {
+            registry
+                .create_protocol(id, None, Endianness::Big, None)
+                .unwrap();
+        }
+        let mut presets = Vec::new();
+        let mut trash = Trash::new();
+        for id in ["a", "b"] {
+            trash.push(delete_protocol(&mut registry, &mut presets, id).unwrap());
+        }
+
+        assert_eq!(
+            trash.restore(0, &mut registry, &mut presets),
+            Ok("a".to_string())
+        );
+        assert_eq!(trash.last().map(|d| d.protocol_id()), Some("b"));
+        assert!(trash.restore(1, &mut registry, &mut presets).is_err());
+
+        registry
+            .create_protocol("b", None, Endianness::Big, None)
+            .unwrap();
+        assert!(trash.restore(0, &mut registry, &mut presets).is_err());
+        assert_eq!(trash.deletions().len(), 1);
+        trash.discard(0);
+        assert!(trash.is_empty());
+    }
+}
@@ -0,0 +1,258 @@
+//! Sending recorded packets again with the gaps between them as captured, optionally changing
+//! fields such as sequence numbers on the way out with a rhai script.
+
+use crate::capture::CapturedPacket;
+use crate::codec::compiled::CodecCache;
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+use crate::transport::Transport;
+use rhai::{AST, Dynamic, Map};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Name of the script function called for every packet before it is sent
+pub const REWRITE_FUNCTION: &str = "rewrite";
+
+/// Something that happened while replaying, for the log
+#[derive(Clone, PartialEq, Debug)]
+pub enum ReplayEvent {
+    /// the packet at an index of the recording was sent as these bytes
+    Sent(usize, Vec<u8>),
+    /// the packet at an index was not sent
+    Error(usize, String),
+}
+
+/// Packets are decoded as a protocol and passed to a script with a `rewrite(packet)` function,
+/// which returns a map of the field values to change, or `()` to send the packet as recorded.
+/// `this` is a map kept between packets, as in the simulator.
+struct Rewrite {
+    protocol_id: String,
+    ast: AST,
+    state: Dynamic,
+    codecs: CodecCache,
+}
+
+/// A recording being sent packet by packet as its time comes
+pub struct Replay {
+    packets: Vec<CapturedPacket>,
+    /// how many times faster than recorded
+    speed: f64,
+    rewrite: Option<Rewrite>,
+    /// index of the next packet to send
+    next: usize,
+}
+
+impl Replay {
+    pub fn new(packets: Vec<CapturedPacket>, speed: f64) -> Result<Self, String> {
+        if packets.is_empty() {
+            return Err("There are no packets to replay".to_string());
+        }
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(format!("Replay speed must be above 0, not {}", speed));
+        }
+        Ok(Self {
+            packets,
+            speed,
+            rewrite: None,
+            next: 0,
+        })
+    }
+
+    /// Change packets with a script before they are sent
+    pub fn with_rewrite(
+        mut self,
+        engine: &ScriptEngine,
+        protocol_id: &str,
+        script: &str,
+    ) -> Result<Self, String> {
+        let ast = engine.compile(script).map_err(|e| e.to_string())?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == REWRITE_FUNCTION && f.params.len() == 1)
+        {
+            return Err(format!(
+                "Script must define a function {}(packet)",
+                REWRITE_FUNCTION
+            ));
+        }
+        self.rewrite = Some(Rewrite {
+            protocol_id: protocol_id.to_string(),
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            codecs: CodecCache::new(),
+        });
+        Ok(self)
+    }
+
+    /// Number of packets sent or skipped so far, and in total
+    pub fn progress(&self) -> (usize, usize) {
+        (self.next, self.packets.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.packets.len()
+    }
+
+    /// When a packet is due, counted from the start of the replay
+    pub fn due_time(&self, index: usize) -> Duration {
+        let first = self.packets[0].timestamp;
+        let gap = self.packets[index].timestamp.saturating_sub(first);
+        gap.div_f64(self.speed)
+    }
+
+    /// Send every packet that is due `elapsed` after the start of the replay
+    pub fn poll(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        transport: &mut dyn Transport,
+        elapsed: Duration,
+    ) -> Vec<ReplayEvent> {
+        let mut events = Vec::new();
+        while !self.is_finished() && self.due_time(self.next) <= elapsed {
+            let index = self.next;
+            self.next += 1;
+            let result = self
+                .rewritten(registry, engine, index)
+                .and_then(|data| transport.send(&data).map(|_| data));
+            events.push(match result {
+                Ok(data) => ReplayEvent::Sent(index, data),
+                Err(e) => ReplayEvent::Error(index, e),
+            });
+        }
+        events
+    }
+
+    /// The bytes to send for a packet, after the script has changed its fields
+    fn rewritten(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        index: usize,
+    ) -> Result<Vec<u8>, String> {
+        let data = &self.packets[index].data;
+        let Some(rewrite) = &mut self.rewrite else {
+            return Ok(data.clone());
+        };
+        let packet = rewrite
+            .codecs
+            .decode(registry, engine, &rewrite.protocol_id, data)?;
+        let fields: Vec<_> = packet
+            .fields
+            .iter()
+            .map(|f| (f.rule_id.as_str(), &f.value))
+            .collect();
+        let Some(changes) =
+            engine.call_handler(&rewrite.ast, REWRITE_FUNCTION, &mut rewrite.state, &fields)?
+        else {
+            return Ok(data.clone());
+        };
+        let mut values: HashMap<_, _> = packet
+            .fields
+            .iter()
+            .filter(|f| !f.is_virtual)
+            .map(|f| (f.rule_id.clone(), f.value.clone()))
+            .collect();
+        values.extend(changes);
+        // expression fields such as checksums are computed again for the new values
+        rewrite
+            .codecs
+            .encode(registry, engine, &packet.protocol_id, &values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+
+    #[derive(Default)]
+    struct Recorder {
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl Transport for Recorder {
+        fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+            Ok(None)
+        }
+
+        fn send(&mut self, data: &[u8]) -> Result<(), String> {
+            self.sent.push(data.to_vec());
+            Ok(())
+        }
+    }
+
+    fn packet(millis: u64, data: Vec<u8>) -> CapturedPacket {
+        CapturedPacket {
+            timestamp: Duration::from_millis(millis),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_replay_timing_and_rewrite() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("msg", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("msg", |p| {
+                p.add_field(FieldRule::new(
+                    "seq",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "data",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        let engine = ScriptEngine::new();
+        let packets = vec![
+            packet(1000, vec![7, 0xaa]),
+            packet(1100, vec![7, 0xbb]),
+            packet(1400, vec![7, 0xcc]),
+        ];
+        let mut replay = Replay::new(packets, 2.0)
+            .unwrap()
+            .with_rewrite(
+                &engine,
+                "msg",
+                r#"
+                fn rewrite(packet) {
+                    this.seq = (this.seq ?? 0) + 1;
+                    #{ seq: this.seq }
+                }
+                "#,
+            )
+            .unwrap();
+        assert_eq!(replay.due_time(2), Duration::from_millis(200));
+
+        let mut transport = Recorder::default();
+        let events = replay.poll(
+            &registry,
+            &engine,
+            &mut transport,
+            Duration::from_millis(60),
+        );
+        assert_eq!(events.len(), 2);
+        assert_eq!(transport.sent, vec![vec![1, 0xaa], vec![2, 0xbb]]);
+        assert!(!replay.is_finished());
+
+        replay.poll(
+            &registry,
+            &engine,
+            &mut transport,
+            Duration::from_millis(200),
+        );
+        assert_eq!(transport.sent[2], vec![3, 0xcc]);
+        assert_eq!(replay.progress(), (3, 3));
+        assert!(replay.is_finished());
+
+        assert!(Replay::new(vec![], 1.0).is_err());
+        assert!(Replay::new(vec![packet(0, vec![])], 0.0).is_err());
+    }
+}
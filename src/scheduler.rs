@@ -0,0 +1,158 @@
+//! Sending packets over and over at fixed intervals, e.g. a heartbeat every 100 ms alongside a
+//! status request every second, all over one transport.
+
+use crate::codec::Value;
+use crate::codec::encode::encode;
+use crate::models::preset::PacketPreset;
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+use crate::transport::Transport;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Something that happened while transmitting, for the log
+#[derive(Clone, PartialEq, Debug)]
+pub enum TransmitEvent {
+    /// a packet was sent, with its name
+    Sent(String, Vec<u8>),
+    Error(String, String),
+}
+
+/// A packet sent every `interval`
+#[derive(Clone, PartialEq, Debug)]
+pub struct PeriodicPacket {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub interval: Duration,
+    /// how many times it was sent
+    pub sent: u64,
+    /// when it is sent next, counted from the start of the schedule
+    next_due: Duration,
+}
+
+/// Packets sent at their own intervals, the first time all at once at the start
+#[derive(Default)]
+pub struct Scheduler {
+    packets: Vec<PeriodicPacket>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &str, data: Vec<u8>, interval: Duration) -> Result<(), String> {
+        if interval.is_zero() {
+            return Err(format!("Interval of '{}' must be above 0", name));
+        }
+        self.packets.push(PeriodicPacket {
+            name: name.to_string(),
+            data,
+            interval,
+            sent: 0,
+            next_due: Duration::ZERO,
+        });
+        Ok(())
+    }
+
+    pub fn packets(&self) -> &[PeriodicPacket] {
+        &self.packets
+    }
+
+    /// Send the packets that are due `elapsed` after the start. A packet sends once however
+    /// many of its times were missed, e.g. while the UI was busy, and then keeps its interval
+    /// from now on.
+    pub fn poll(&mut self, transport: &mut dyn Transport, elapsed: Duration) -> Vec<TransmitEvent> {
+        let mut due: Vec<&mut PeriodicPacket> = self
+            .packets
+            .iter_mut()
+            .filter(|p| p.next_due <= elapsed)
+            .collect();
+        due.sort_by_key(|p| p.next_due);
+
+        let mut events = Vec::new();
+        for packet in due {
+            packet.next_due += packet.interval;
+            if packet.next_due <= elapsed {
+                packet.next_due = elapsed + packet.interval;
+            }
+            events.push(match transport.send(&packet.data) {
+                Ok(()) => {
+                    packet.sent += 1;
+                    TransmitEvent::Sent(packet.name.clone(), packet.data.clone())
+                }
+                Err(e) => TransmitEvent::Error(packet.name.clone(), e),
+            });
+        }
+        events
+    }
+}
+
+/// Encode the values saved in a preset as a packet of its protocol
+pub fn encode_preset(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    preset: &PacketPreset,
+) -> Result<Vec<u8>, String> {
+    let values = preset
+        .values
+        .iter()
+        .map(|(id, text)| {
+            let value = Value::parse_literal(text)
+                .map_err(|e| format!("Invalid value for field '{}': {}", id, e))?;
+            Ok((id.clone(), value))
+        })
+        .collect::<Result<HashMap<_, _>, String>>()?;
+    encode(registry, engine, &preset.protocol_id, &values)
+        .map_err(|e| format!("Preset '{}': {}", preset.path(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl Transport for Recorder {
+        fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+            Ok(None)
+        }
+
+        fn send(&mut self, data: &[u8]) -> Result<(), String> {
+            self.sent.push(data.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_packets_at_their_intervals() {
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .add("heartbeat", vec![1], Duration::from_millis(100))
+            .unwrap();
+        scheduler
+            .add("status", vec![2], Duration::from_millis(250))
+            .unwrap();
+        assert!(scheduler.add("never", vec![3], Duration::ZERO).is_err());
+
+        let mut transport = Recorder::default();
+        let ms = Duration::from_millis;
+        for elapsed in [0, 50, 100, 200, 250, 300] {
+            scheduler.poll(&mut transport, ms(elapsed));
+        }
+        let sent: Vec<u8> = transport.sent.iter().map(|p| p[0]).collect();
+        assert_eq!(sent, vec![1, 2, 1, 1, 2, 1]);
+
+        // a long stall sends each packet once, then keeps the interval from then on
+        transport.sent.clear();
+        scheduler.poll(&mut transport, ms(1000));
+        scheduler.poll(&mut transport, ms(1050));
+        assert_eq!(transport.sent, vec![vec![1], vec![2]]);
+        scheduler.poll(&mut transport, ms(1100));
+        assert_eq!(transport.sent.len(), 3);
+        assert_eq!(scheduler.packets()[0].sent, 6);
+    }
+}
@@ -0,0 +1,398 @@
+//! Interactive rhai console with access to the protocol registry, the current packet
+//! and the codec, for bulk edits and ad-hoc analyses.
+
+use super::{ScriptEngine, from_dynamic, to_dynamic};
+use crate::codec::Value;
+use crate::codec::decode::{DecodedPacket, decode};
+use crate::codec::encode::encode;
+use crate::models::field::{FieldLength, FieldRule, FieldType};
+use crate::models::protocol::ProtocolRegistry;
+use rhai::{AST, Array, Blob, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Host functions available in the console, shown as help, each with the ID of the message
+/// describing it
+pub const CONSOLE_API: &[(&str, &str)] = &[
+    ("protocols()", "console-api-protocols"),
+    ("protocol(id)", "console-api-protocol"),
+    ("set_metadata(id, key, value)", "console-api-set-metadata"),
+    ("add_field(id, field, bits)", "console-api-add-field"),
+    (
+        "add_field(id, field, bits, script)",
+        "console-api-add-expression-field",
+    ),
+    ("remove_field(id, field)", "console-api-remove-field"),
+    ("rename_field(id, old, new)", "console-api-rename-field"),
+    ("packet()", "console-api-packet"),
+    ("decode(id, blob)", "console-api-decode"),
+    ("encode(id, map)", "console-api-encode"),
+];
+
+/// What running one console command produced
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConsoleOutput {
+    /// lines passed to `print`
+    pub printed: Vec<String>,
+    /// the value of the command, or the error it failed with
+    pub result: Result<String, String>,
+}
+
+/// The application state a command works on, lent to the console while it runs
+#[derive(Default)]
+struct ConsoleState {
+    registry: ProtocolRegistry,
+    engine: ScriptEngine,
+    packet: Option<DecodedPacket>,
+    printed: Vec<String>,
+}
+
+type HostResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// A rhai session whose variables persist between commands.
+///
+/// Unlike field expressions, console commands are typed by the user, so the engine is not
+/// sandboxed beyond an operation limit that keeps a runaway loop from hanging the app.
+pub struct Console {
+    engine: Engine,
+    scope: Scope<'static>,
+    state: Rc<RefCell<ConsoleState>>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let state = Rc::new(RefCell::new(ConsoleState::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(10_000_000);
+
+        let s = state.clone();
+        engine.on_print(move |text| s.borrow_mut().printed.push(text.to_string()));
+        let s = state.clone();
+        engine.on_debug(move |text, _, _| s.borrow_mut().printed.push(text.to_string()));
+
+        let s = state.clone();
+        engine.register_fn("protocols", move || -> Array {
+            s.borrow()
+                .registry
+                .list_protocols()
+                .into_iter()
+                .map(|p| Dynamic::from(p.id.clone()))
+                .collect()
+        });
+
+        let s = state.clone();
+        engine.register_fn("protocol", move |id: &str| -> HostResult<Map> {
+            let state = s.borrow();
+            let proto = state
+                .registry
+                .get_protocol(id)
+                .ok_or_else(|| format!("Protocol with ID '{}' does not exist", id))?;
+
+            let fields: Array = proto
+                .fields
+                .iter()
+                .map(|f| {
+                    let mut field = Map::new();
+                    field.insert("id".into(), f.id.clone().into());
+                    field.insert("type".into(), f.field_type.kind_name().into());
+                    field.insert(
+                        "bits".into(),
+                        match f.length {
+                            FieldLength::Fixed(bits) => Dynamic::from_int(bits as i64),
+                            FieldLength::Variable => Dynamic::UNIT,
+                        },
+                    );
+                    Dynamic::from_map(field)
+                })
+                .collect();
+            let metadata: Map = proto
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.into(), v.clone().into()))
+                .collect();
+
+            let mut map = Map::new();
+            map.insert("id".into(), proto.id.clone().into());
+            map.insert("name".into(), optional(&proto.name));
+            map.insert("parent".into(), optional(&proto.parent_id));
+            map.insert(
+                "endianness".into(),
+                format!("{:?}", proto.endianness).into(),
+            );
+            map.insert("metadata".into(), Dynamic::from_map(metadata));
+            map.insert("fields".into(), Dynamic::from_array(fields));
+            Ok(map)
+        });
+
+        let s = state.clone();
+        engine.register_fn(
+            "set_metadata",
+            move |id: &str, key: &str, value: &str| -> HostResult<()> {
+                s.borrow_mut()
+                    .registry
+                    .edit_protocol(id, |p| {
+                        p.update_metadata(key, value);
+                        Ok(())
+                    })
+                    .map_err(|e| e.to_string().into())
+            },
+        );
+
+        let s = state.clone();
+        engine.register_fn(
+            "add_field",
+            move |id: &str, field: &str, bits: i64| -> HostResult<()> {
+                let rule = FieldRule::new(field, FieldType::Input, fixed_length(bits)?);
+                add_field(&s, id, rule)
+            },
+        );
+
+        let s = state.clone();
+        engine.register_fn(
+            "add_field",
+            move |id: &str, field: &str, bits: i64, script: &str| -> HostResult<()> {
+                let field_type = FieldType::Expr(script.to_string());
+                let rule = FieldRule::new(field, field_type, fixed_length(bits)?);
+                add_field(&s, id, rule)
+            },
+        );
+
+        let s = state.clone();
+        engine.register_fn(
+            "remove_field",
+            move |id: &str, field: &str| -> HostResult<()> {
+                s.borrow_mut()
+                    .registry
+                    .edit_protocol(id, |p| p.remove_field(field))
+                    .map_err(|e| e.to_string().into())
+            },
+        );
+
+        let s = state.clone();
+        engine.register_fn(
+            "rename_field",
+            move |id: &str, old: &str, new: &str| -> HostResult<()> {
+                s.borrow_mut()
+                    .registry
+                    .rename_field(id, old, new)
+                    .map_err(|e| e.to_string().into())
+            },
+        );
+
+        let s = state.clone();
+        engine.register_fn("packet", move || -> Dynamic {
+            match &s.borrow().packet {
+                Some(packet) => Dynamic::from_map(packet_map(packet)),
+                None => Dynamic::UNIT,
+            }
+        });
+
+        let s = state.clone();
+        engine.register_fn("decode", move |id: &str, data: Blob| -> HostResult<Map> {
+            let state = s.borrow();
+            let packet = decode(&state.registry, &state.engine, id, &data)?;
+            Ok(packet_map(&packet))
+        });
+
+        let s = state.clone();
+        engine.register_fn("encode", move |id: &str, values: Map| -> HostResult<Blob> {
+            let values = values
+                .into_iter()
+                .map(|(k, v)| Ok((k.to_string(), from_dynamic(v)?)))
+                .collect::<Result<HashMap<String, Value>, String>>()?;
+            let state = s.borrow();
+            Ok(encode(&state.registry, &state.engine, id, &values)?)
+        });
+
+        Self {
+            engine,
+            scope: Scope::new(),
+            state,
+        }
+    }
+
+    /// Run `command` against the given application state, which it may modify.
+    pub fn run(
+        &mut self,
+        registry: &mut ProtocolRegistry,
+        engine: &mut ScriptEngine,
+        packet: Option<&DecodedPacket>,
+        command: &str,
+    ) -> ConsoleOutput {
+        self.with_state(registry, engine, packet, |console| {
+            console
+                .engine
+                .eval_with_scope::<Dynamic>(&mut console.scope, command)
+        })
+    }
+
+    /// Call a function defined in `ast`, e.g. by a plugin, with the host API available.
+    /// The top-level statements of `ast` are not run.
+    pub fn call(
+        &mut self,
+        registry: &mut ProtocolRegistry,
+        engine: &mut ScriptEngine,
+        packet: Option<&DecodedPacket>,
+        ast: &AST,
+        function: &str,
+        args: Vec<Dynamic>,
+    ) -> ConsoleOutput {
+        self.with_state(registry, engine, packet, |console| {
+            console.engine.call_fn_with_options::<Dynamic>(
+                CallFnOptions::new().eval_ast(false),
+                &mut Scope::new(),
+                ast,
+                function,
+                args,
+            )
+        })
+    }
+
+    /// Lend the application state to the host functions while `f` runs
+    fn with_state<F>(
+        &mut self,
+        registry: &mut ProtocolRegistry,
+        engine: &mut ScriptEngine,
+        packet: Option<&DecodedPacket>,
+        f: F,
+    ) -> ConsoleOutput
+    where
+        F: FnOnce(&mut Self) -> HostResult<Dynamic>,
+    {
+        {
+            let mut state = self.state.borrow_mut();
+            state.registry = std::mem::take(registry);
+            state.engine = std::mem::take(engine);
+            state.packet = packet.cloned();
+        }
+
+        let result = f(self)
+            .map(|value| {
+                if value.is_unit() {
+                    String::new()
+                } else {
+                    value.to_string()
+                }
+            })
+            .map_err(|e| e.to_string());
+
+        let mut state = self.state.borrow_mut();
+        *registry = std::mem::take(&mut state.registry);
+        *engine = std::mem::take(&mut state.engine);
+        state.packet = None;
+        ConsoleOutput {
+            printed: std::mem::take(&mut state.printed),
+            result,
+        }
+    }
+
+    /// Forget all variables defined by earlier commands
+    pub fn reset(&mut self) {
+        self.scope.clear();
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn optional(value: &Option<String>) -> Dynamic {
+    value.clone().map_or(Dynamic::UNIT, Dynamic::from)
+}
+
+fn fixed_length(bits: i64) -> HostResult<FieldLength> {
+    match u32::try_from(bits) {
+        Ok(bits) if bits > 0 => Ok(FieldLength::Fixed(bits)),
+        _ => Err(format!("Invalid field length {}", bits).into()),
+    }
+}
+
+fn add_field(state: &RefCell<ConsoleState>, id: &str, rule: FieldRule) -> HostResult<()> {
+    state
+        .borrow_mut()
+        .registry
+        .edit_protocol(id, |p| p.add_field(rule))
+        .map_err(|e| e.to_string().into())
+}
+
+fn packet_map(packet: &DecodedPacket) -> Map {
+    packet
+        .fields
+        .iter()
+        .map(|f| (f.rule_id.as_str().into(), to_dynamic(&f.value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::protocol::Endianness;
+
+    fn run(console: &mut Console, registry: &mut ProtocolRegistry, command: &str) -> ConsoleOutput {
+        console.run(registry, &mut ScriptEngine::new(), None, command)
+    }
+
+    #[test]
+    fn test_bulk_edit_by_metadata() {
+        let mut registry = ProtocolRegistry::new();
+        for id in ["a", "b"] {
+            registry
+                .create_protocol(id, None, Endianness::Big, None)
+                .unwrap();
+        }
+        registry
+            .edit_protocol("a", |p| {
+                p.update_metadata("tags", "serial");
+                Ok(())
+            })
+            .unwrap();
+
+        let mut console = Console::new();
+        let output = run(
+            &mut console,
+            &mut registry,
+            r#"
+            for id in protocols() {
+                let tags = protocol(id).metadata.tags;
+                if tags != () && tags.contains("serial") {
+                    add_field(id, "crc", 16);
+                    print(`added crc to ${id}`);
+                }
+            }
+            "#,
+        );
+        assert_eq!(output.result, Ok(String::new()));
+        assert_eq!(output.printed, vec!["added crc to a"]);
+        assert_eq!(registry.get_protocol("a").unwrap().fields.len(), 1);
+        assert!(registry.get_protocol("b").unwrap().fields.is_empty());
+    }
+
+    #[test]
+    fn test_encode_decode_and_persistent_variables() {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("p", None, Endianness::Big, None)
+            .unwrap();
+
+        let mut console = Console::new();
+        run(&mut console, &mut registry, r#"add_field("p", "value", 8)"#);
+        let output = run(
+            &mut console,
+            &mut registry,
+            r#"let data = encode("p", #{ value: 42 });"#,
+        );
+        assert_eq!(output.result, Ok(String::new()));
+        let output = run(&mut console, &mut registry, r#"decode("p", data).value"#);
+        assert_eq!(output.result, Ok("42".to_string()));
+
+        let output = run(&mut console, &mut registry, r#"protocol("missing")"#);
+        assert!(output.result.unwrap_err().contains("does not exist"));
+        assert_eq!(
+            run(&mut console, &mut registry, "packet()").result,
+            Ok(String::new())
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Finding and renaming variable references in rhai scripts, used to keep
+//! field expressions in sync with field ids.
+
+use super::lexer::{TokenKind, tokenize};
+use std::ops::Range;
+
+/// Byte ranges of all identifiers in `script` that may refer to a variable.
+/// Property and method names (preceded by `.`) are skipped.
+pub fn identifier_spans(script: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    // whether the previous significant token was a `.`
+    let mut after_dot = false;
+
+    for token in tokenize(script) {
+        match token.kind {
+            TokenKind::Whitespace | TokenKind::Comment => continue,
+            TokenKind::Ident if !after_dot => spans.push(token.span.clone()),
+            _ => {}
+        }
+        after_dot = token.kind == TokenKind::Punct && &script[token.span] == ".";
+    }
+
+    spans
+}
+
+/// Whether `script` refers to the variable `ident`
+pub fn references_identifier(script: &str, ident: &str) -> bool {
+    identifier_spans(script)
+        .into_iter()
+        .any(|span| &script[span] == ident)
+}
+
+/// Replace every variable reference to `old` in `script` with `new`
+pub fn rename_identifier(script: &str, old: &str, new: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    let mut last = 0;
+    for span in identifier_spans(script) {
+        if &script[span.clone()] == old {
+            out.push_str(&script[last..span.start]);
+            out.push_str(new);
+            last = span.end;
+        }
+    }
+    out.push_str(&script[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_skips_strings_comments_and_properties() {
+        let script = r#"len + 1 // len of payload
+            payload.len + "len" + len_hi"#;
+        let renamed = rename_identifier(script, "len", "length");
+        assert_eq!(
+            renamed,
+            r#"length + 1 // len of payload
+            payload.len + "len" + len_hi"#
+        );
+    }
+
+    #[test]
+    fn test_references_identifier() {
+        assert!(references_identifier("crc == crc16(payload)", "payload"));
+        assert!(!references_identifier("0x1F + payload_len", "payload"));
+        assert!(!references_identifier("/* payload */ 42", "payload"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Tokenizer for rhai scripts, used for highlighting and finding variable references.
+//!
+//! This is not a full parser: it only needs to tell identifiers apart from keywords,
+//! literals, comments and punctuation.
+
+use std::ops::Range;
+
+const KEYWORDS: &[&str] = &[
+    "let", "const", "if", "else", "switch", "while", "loop", "for", "in", "do", "until", "break",
+    "continue", "return", "throw", "try", "catch", "fn", "private", "true", "false", "this",
+    "import", "export", "as",
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TokenKind {
+    Keyword,
+    Ident,
+    Number,
+    Str,
+    Comment,
+    Punct,
+    Whitespace,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// byte range in the script
+    pub span: Range<usize>,
+}
+
+/// Split a script into tokens covering every byte of the input
+pub fn tokenize(script: &str) -> Vec<Token> {
+    let bytes = script.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let kind = match c {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                TokenKind::Comment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                TokenKind::Comment
+            }
+            b'"' | b'\'' | b'`' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != c {
+                    if bytes[i] == b'\\' {
+                        i += 1; // skip escaped character
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::Str
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                if KEYWORDS.contains(&&script[start..i]) {
+                    TokenKind::Keyword
+                } else {
+                    TokenKind::Ident
+                }
+            }
+            c if c.is_ascii_digit() => {
+                // numbers may contain letters (0x1F, 1e3, 10_000), consume them as one token
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric()
+                        || bytes[i] == b'_'
+                        || (bytes[i] == b'.'
+                            && bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit())))
+                {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            c if c.is_ascii_whitespace() => {
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                TokenKind::Whitespace
+            }
+            _ => {
+                // advance by a whole character to keep spans on char boundaries
+                i += script[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punct
+            }
+        };
+        tokens.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(script: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(script)
+            .into_iter()
+            .filter(|t| t.kind != TokenKind::Whitespace)
+            .map(|t| (t.kind, &script[t.span]))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds(r#"if raw > 0x1F { raw / 10.5 } // "hot""#),
+            vec![
+                (TokenKind::Keyword, "if"),
+                (TokenKind::Ident, "raw"),
+                (TokenKind::Punct, ">"),
+                (TokenKind::Number, "0x1F"),
+                (TokenKind::Punct, "{"),
+                (TokenKind::Ident, "raw"),
+                (TokenKind::Punct, "/"),
+                (TokenKind::Number, "10.5"),
+                (TokenKind::Punct, "}"),
+                (TokenKind::Comment, r#"// "hot""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokens_cover_input() {
+        let script = "let s = \"unterminated";
+        let tokens = tokenize(script);
+        assert_eq!(tokens.last().unwrap().span.end, script.len());
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Str);
+    }
+}
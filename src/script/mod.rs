@@ -0,0 +1,558 @@
+pub mod console;
+pub mod idents;
+pub mod lexer;
+pub mod plugins;
+
+use crate::codec::Value;
+use crate::codec::checksum::checksum_by_name;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, Array, Blob, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Functions from the rhai standard library, and the ones BitLoom adds, offered for
+/// autocompletion
+pub const BUILTIN_FUNCTIONS: &[&str] = &[
+    "abs",
+    "sign",
+    "min",
+    "max",
+    "sqrt",
+    "exp",
+    "ln",
+    "log",
+    "floor",
+    "ceiling",
+    "round",
+    "to_int",
+    "to_float",
+    "to_string",
+    "to_hex",
+    "to_binary",
+    "parse_int",
+    "len",
+    "is_empty",
+    "contains",
+    "sub_string",
+    "checksum",
+];
+
+/// A script that failed to parse, with the location of the problem if known
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptError {
+    pub message: String,
+    /// 1-based line and column
+    pub position: Option<(usize, usize)>,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => {
+                write!(f, "{} (line {}, column {})", self.message, line, column)
+            }
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Resource limits for running scripts from untrusted project files
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub max_duration: Duration,
+    pub max_call_levels: usize,
+    pub max_expr_depth: usize,
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            max_duration: Duration::from_millis(100),
+            max_call_levels: 32,
+            max_expr_depth: 64,
+            max_string_size: 64 * 1024,
+            max_array_size: 64 * 1024,
+        }
+    }
+}
+
+/// The limits and library of a [`ScriptEngine`], to set up the same engine elsewhere
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct EngineSettings {
+    pub limits: ScriptLimits,
+    pub library: String,
+}
+
+/// A rhai engine that cannot import modules or use `eval`, discards print output and aborts
+/// scripts once they exceed `limits`, their time counted from the instant returned with it
+pub(crate) fn sandboxed_engine(limits: ScriptLimits) -> (Engine, Arc<Mutex<Instant>>) {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_operations(limits.max_operations)
+        .set_max_call_levels(limits.max_call_levels)
+        .set_max_expr_depths(limits.max_expr_depth, limits.max_expr_depth)
+        .set_max_string_size(limits.max_string_size)
+        .set_max_array_size(limits.max_array_size)
+        .set_max_map_size(limits.max_array_size)
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {});
+    engine.disable_symbol("eval");
+
+    let started = Arc::new(Mutex::new(Instant::now()));
+    let deadline_start = started.clone();
+    engine.on_progress(move |_| {
+        let elapsed = deadline_start
+            .lock()
+            .map(|s| s.elapsed())
+            .unwrap_or_default();
+        (elapsed > limits.max_duration).then(|| "Script exceeded its time limit".into())
+    });
+    (engine, started)
+}
+
+/// Evaluates rhai field expressions against a set of named field values.
+///
+/// Scripts run sandboxed: they cannot import modules or use `eval`, print output is discarded,
+/// and evaluation is aborted once it exceeds the configured [`ScriptLimits`].
+pub struct ScriptEngine {
+    engine: Engine,
+    limits: ScriptLimits,
+    /// project-level functions and constants available to every script
+    library: AST,
+    /// source of `library`, to set up the same engine on another thread
+    library_source: String,
+    /// start of the evaluation in progress, checked against the time limit
+    started: Arc<Mutex<Instant>>,
+}
+
+impl ScriptEngine {
+    pub fn new() -> Self {
+        Self::with_limits(ScriptLimits::default())
+    }
+
+    pub fn with_limits(limits: ScriptLimits) -> Self {
+        let (mut engine, started) = sandboxed_engine(limits);
+        // `checksum("CRC-16/MODBUS", payload)`; 64-bit checksums wrap to negative integers
+        engine.register_fn(
+            "checksum",
+            |name: &str, data: Blob| -> Result<i64, Box<EvalAltResult>> {
+                let checksum =
+                    checksum_by_name(name).ok_or_else(|| format!("Unknown checksum '{}'", name))?;
+                Ok(checksum.compute(&data) as i64)
+            },
+        );
+
+        Self {
+            engine,
+            limits,
+            library: AST::empty(),
+            library_source: String::new(),
+            started,
+        }
+    }
+
+    /// What the engine was set up with. Engines are not `Send`; a worker thread builds its own
+    /// from these with [`ScriptEngine::from_settings`].
+    pub fn settings(&self) -> EngineSettings {
+        EngineSettings {
+            limits: self.limits,
+            library: self.library_source.clone(),
+        }
+    }
+
+    /// An engine with the limits and library of another. The library compiled there, so it
+    /// compiles here too.
+    pub fn from_settings(settings: &EngineSettings) -> Self {
+        let mut engine = Self::with_limits(settings.limits);
+        let _ = engine.set_library(&settings.library);
+        engine
+    }
+
+    /// Replace the project script library. On error the previous library stays installed.
+    pub fn set_library(&mut self, source: &str) -> Result<(), ScriptError> {
+        self.library = self.compile(source)?;
+        self.library_source = source.to_string();
+        Ok(())
+    }
+
+    /// Names of the functions defined in the project script library
+    pub fn library_functions(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .library
+            .iter_functions()
+            .map(|f| f.name.to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Check that `script` parses, without evaluating it
+    pub fn check(&self, script: &str) -> Result<(), ScriptError> {
+        self.compile(script).map(|_| ())
+    }
+
+    /// Parse `script` for repeated calls of the functions it defines
+    pub fn compile(&self, script: &str) -> Result<AST, ScriptError> {
+        self.engine.compile(script).map_err(|e| {
+            let position = e.1;
+            ScriptError {
+                message: e.0.to_string(),
+                position: position
+                    .line()
+                    .map(|line| (line, position.position().unwrap_or(1))),
+            }
+        })
+    }
+
+    /// Evaluate `script` with each `(field_id, value)` pair available as a variable.
+    pub fn eval(&self, script: &str, vars: &[(&str, &Value)]) -> Result<Value, String> {
+        let ast = self.compile(script).map_err(|e| e.to_string())?;
+        self.eval_ast(&ast, vars)
+    }
+
+    /// Like [`Self::eval`], with a script compiled beforehand, e.g. once for many packets
+    pub fn eval_ast(&self, ast: &AST, vars: &[(&str, &Value)]) -> Result<Value, String> {
+        let mut scope = Scope::new();
+        for (id, value) in vars {
+            scope.push_dynamic(id.to_string(), to_dynamic(value));
+        }
+        from_dynamic(self.eval_in_scope(ast, &mut scope)?)
+    }
+
+    /// Run a packet validator script. Fields are available as variables and in a `fields` map.
+    /// Returns the problems the script reported: it may return `true` or `()` for none,
+    /// `false`, a message, or an array of messages.
+    pub fn validate(&self, script: &str, vars: &[(&str, &Value)]) -> Result<Vec<String>, String> {
+        let ast = self.compile(script).map_err(|e| e.to_string())?;
+        self.validate_ast(&ast, vars)
+    }
+
+    /// Like [`Self::validate`], with a script compiled beforehand
+    pub fn validate_ast(&self, ast: &AST, vars: &[(&str, &Value)]) -> Result<Vec<String>, String> {
+        let mut scope = Scope::new();
+        let mut fields = Map::new();
+        for (id, value) in vars {
+            scope.push_dynamic(id.to_string(), to_dynamic(value));
+            fields.insert((*id).into(), to_dynamic(value));
+        }
+        scope.push_constant("fields", fields);
+
+        let result = self.eval_in_scope(ast, &mut scope)?;
+        if result.is_unit() {
+            return Ok(Vec::new());
+        }
+        if let Ok(valid) = result.as_bool() {
+            return Ok(if valid {
+                Vec::new()
+            } else {
+                vec!["Validation failed".to_string()]
+            });
+        }
+        if result.is_string() {
+            let message = result.into_string()?;
+            return Ok(if message.is_empty() {
+                Vec::new()
+            } else {
+                vec![message]
+            });
+        }
+        if result.is_array() {
+            return Ok(result
+                .cast::<Array>()
+                .into_iter()
+                .map(|item| item.to_string())
+                .collect());
+        }
+        Err(format!(
+            "Validator returned unsupported type '{}'",
+            result.type_name()
+        ))
+    }
+
+    /// Call a function defined in `ast`, e.g. a plugin value formatter, with the given arguments.
+    /// The top-level statements of `ast` are not run.
+    pub fn call(&self, ast: &AST, function: &str, args: &[&Value]) -> Result<Value, String> {
+        self.start_timer();
+        let args: Vec<Dynamic> = args.iter().map(|v| to_dynamic(v)).collect();
+        let result = self
+            .engine
+            .call_fn_with_options::<Dynamic>(
+                CallFnOptions::new().eval_ast(false),
+                &mut Scope::new(),
+                ast,
+                function,
+                args,
+            )
+            .map_err(|e| error_message(*e))?;
+        from_dynamic(result)
+    }
+
+    /// Call a function defined in `ast` with the fields of a packet as a map. Returns `None`
+    /// if the function returns `()`.
+    pub fn call_with_packet(
+        &self,
+        ast: &AST,
+        function: &str,
+        fields: &[(&str, &Value)],
+    ) -> Result<Option<Value>, String> {
+        self.start_timer();
+        let packet: Map = fields
+            .iter()
+            .map(|(id, value)| ((*id).into(), to_dynamic(value)))
+            .collect();
+        let result = self
+            .engine
+            .call_fn_with_options::<Dynamic>(
+                CallFnOptions::new().eval_ast(false),
+                &mut Scope::new(),
+                ast,
+                function,
+                (packet,),
+            )
+            .map_err(|e| error_message(*e))?;
+        if result.is_unit() {
+            return Ok(None);
+        }
+        from_dynamic(result).map(Some)
+    }
+
+    /// Call a packet handler defined in `ast` with the fields of a packet as a map, and `this`
+    /// bound to `state` so that it persists between calls. The handler returns a map of field
+    /// values to reply with, or `()` for no reply.
+    pub fn call_handler(
+        &self,
+        ast: &AST,
+        function: &str,
+        state: &mut Dynamic,
+        fields: &[(&str, &Value)],
+    ) -> Result<Option<HashMap<String, Value>>, String> {
+        self.start_timer();
+        let packet: Map = fields
+            .iter()
+            .map(|(id, value)| ((*id).into(), to_dynamic(value)))
+            .collect();
+        let result = self
+            .engine
+            .call_fn_with_options::<Dynamic>(
+                CallFnOptions::new().eval_ast(false).bind_this_ptr(state),
+                &mut Scope::new(),
+                ast,
+                function,
+                (packet,),
+            )
+            .map_err(|e| error_message(*e))?;
+
+        if result.is_unit() {
+            return Ok(None);
+        }
+        let type_name = result.type_name();
+        let reply = result
+            .try_cast::<Map>()
+            .ok_or_else(|| format!("Handler returned '{}' instead of a map", type_name))?;
+        reply
+            .into_iter()
+            .map(|(id, value)| Ok((id.to_string(), from_dynamic(value)?)))
+            .collect::<Result<_, String>>()
+            .map(Some)
+    }
+
+    fn start_timer(&self) {
+        if let Ok(mut started) = self.started.lock() {
+            *started = Instant::now();
+        }
+    }
+
+    fn eval_in_scope(&self, ast: &AST, scope: &mut Scope) -> Result<Dynamic, String> {
+        self.start_timer();
+
+        // library statements (e.g. constants) run first, then the script itself
+        let ast = self.library.merge(ast);
+        self.engine
+            .eval_ast_with_scope::<Dynamic>(scope, &ast)
+            .map_err(|e| error_message(*e))
+    }
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn error_message(e: EvalAltResult) -> String {
+    match e {
+        EvalAltResult::ErrorTerminated(reason, _) => reason.to_string(),
+        e => e.to_string(),
+    }
+}
+
+fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Int(v) => match i64::try_from(*v) {
+            Ok(v) => Dynamic::from_int(v),
+            // rhai integers are 64-bit; expose wider values as raw big-endian bytes
+            Err(_) => Dynamic::from_blob(v.to_be_bytes().to_vec()),
+        },
+        Value::Float(v) => Dynamic::from_float(*v),
+        Value::Bool(v) => Dynamic::from_bool(*v),
+        Value::Str(v) => Dynamic::from(v.clone()),
+        Value::Bytes(v) => Dynamic::from_blob(v.clone()),
+    }
+}
+
+fn from_dynamic(value: Dynamic) -> Result<Value, String> {
+    if value.is_int() {
+        Ok(Value::Int(value.as_int()? as i128))
+    } else if value.is_float() {
+        Ok(Value::Float(value.as_float()?))
+    } else if value.is_bool() {
+        Ok(Value::Bool(value.as_bool()?))
+    } else if value.is_string() {
+        Ok(Value::Str(value.into_string()?))
+    } else if value.is_blob() {
+        Ok(Value::Bytes(value.cast::<Blob>()))
+    } else {
+        Err(format!(
+            "Expression returned unsupported type '{}'",
+            value.type_name()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_with_field_values() {
+        let engine = ScriptEngine::new();
+        let raw = Value::Int(235);
+
+        let result = engine.eval("raw_temp / 10.0", &[("raw_temp", &raw)]);
+        assert_eq!(result, Ok(Value::Float(23.5)));
+    }
+
+    #[test]
+    fn test_eval_bool_result() {
+        let engine = ScriptEngine::new();
+        let crc = Value::Int(0x1234);
+        let expected = Value::Int(0x1234);
+
+        let result = engine.eval("crc == expected", &[("crc", &crc), ("expected", &expected)]);
+        assert_eq!(result, Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_checksum_function() {
+        let engine = ScriptEngine::new();
+        let data = Value::Bytes(b"123456789".to_vec());
+        let result = engine.eval("checksum(\"CRC-16/MODBUS\", data)", &[("data", &data)]);
+        assert_eq!(result, Ok(Value::Int(0x4b37)));
+        assert!(
+            engine
+                .eval("checksum(\"CRC-7\", data)", &[("data", &data)])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_check_syntax() {
+        let engine = ScriptEngine::new();
+        assert!(engine.check("a + b * 2").is_ok());
+
+        let error = engine.check("let x = 1;\nx + * 2").unwrap_err();
+        assert_eq!(error.position.map(|(line, _)| line), Some(2));
+    }
+
+    #[test]
+    fn test_operation_limit() {
+        let engine = ScriptEngine::new();
+        let result = engine.eval("let x = 0; loop { x += 1; }", &[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let engine = ScriptEngine::with_limits(ScriptLimits {
+            max_operations: 0, // unlimited, so only the time limit applies
+            max_duration: Duration::from_millis(20),
+            ..ScriptLimits::default()
+        });
+        let result = engine.eval("loop {}", &[]).unwrap_err();
+        assert!(result.contains("time limit"));
+    }
+
+    #[test]
+    fn test_no_imports_or_eval() {
+        let engine = ScriptEngine::new();
+        assert!(engine.eval(r#"import "/etc/passwd" as m; 1"#, &[]).is_err());
+        assert!(engine.eval(r#"eval("1 + 1")"#, &[]).is_err());
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let engine = ScriptEngine::new();
+        let result = engine.eval("fn f(x) { f(x + 1) } f(0)", &[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_library_functions_and_constants() {
+        let mut engine = ScriptEngine::new();
+        engine
+            .set_library("const OFFSET = 273; fn kelvin_to_raw(t) { (t - global::OFFSET) * 10 }")
+            .unwrap();
+        assert_eq!(engine.library_functions(), vec!["kelvin_to_raw"]);
+
+        let t = Value::Int(300);
+        assert_eq!(
+            engine.eval("kelvin_to_raw(t)", &[("t", &t)]),
+            Ok(Value::Int(270))
+        );
+        assert_eq!(engine.eval("t - OFFSET", &[("t", &t)]), Ok(Value::Int(27)));
+
+        // a broken library keeps the previous one installed
+        assert!(engine.set_library("fn broken(").is_err());
+        assert_eq!(engine.library_functions(), vec!["kelvin_to_raw"]);
+    }
+
+    #[test]
+    fn test_validate_results() {
+        let engine = ScriptEngine::new();
+        let length = Value::Int(3);
+        let payload = Value::Bytes(vec![1, 2]);
+        let vars = [("length", &length), ("payload", &payload)];
+
+        assert_eq!(engine.validate("length > 0", &vars), Ok(vec![]));
+        assert_eq!(
+            engine.validate(
+                r#"if length != payload.len() { "length field inconsistent with payload" }"#,
+                &vars
+            ),
+            Ok(vec!["length field inconsistent with payload".to_string()])
+        );
+        assert_eq!(
+            engine.validate(r#"["a", "b"]"#, &vars),
+            Ok(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            engine.validate(r#"fields["length"] == 3"#, &vars),
+            Ok(vec![])
+        );
+        assert!(engine.validate("1.5", &vars).is_err());
+    }
+
+    #[test]
+    fn test_eval_unknown_variable() {
+        let engine = ScriptEngine::new();
+        assert!(engine.eval("missing + 1", &[]).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! rhai plugins loaded from a directory at startup. A plugin's top-level code registers
+//! its functions as menu actions, exporters or field value formatters.
+
+use super::{ScriptLimits, sandboxed_engine};
+use rhai::AST;
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Directory scanned for `*.rhai` plugins, relative to the working directory
+pub const PLUGIN_DIR: &str = "plugins";
+
+/// Registration functions available to the top-level code of a plugin
+pub const PLUGIN_API: &[(&str, &str)] = &[
+    (
+        "register_action(label, fn_name)",
+        "menu action; fn_name() runs with the console API",
+    ),
+    (
+        "register_exporter(label, extension, fn_name)",
+        "exporter; fn_name(protocol_id) returns the file content",
+    ),
+    (
+        "register_formatter(label, fn_name)",
+        "field value formatter; fn_name(value) returns a string",
+    ),
+];
+
+/// A function of a plugin registered under a label shown to the user
+#[derive(Clone, PartialEq, Debug)]
+pub struct PluginHook {
+    pub label: String,
+    pub function: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PluginExporter {
+    pub hook: PluginHook,
+    /// file extension of the exported content, without the dot
+    pub extension: String,
+}
+
+pub struct Plugin {
+    /// file name of the script without extension
+    pub name: String,
+    pub ast: AST,
+    pub actions: Vec<PluginHook>,
+    pub exporters: Vec<PluginExporter>,
+    pub formatters: Vec<PluginHook>,
+}
+
+#[derive(Default)]
+struct Registrations {
+    actions: Vec<PluginHook>,
+    exporters: Vec<PluginExporter>,
+    formatters: Vec<PluginHook>,
+}
+
+impl Plugin {
+    /// Compile a plugin and run its top-level code to collect what it registers
+    pub fn load(name: &str, source: &str) -> Result<Self, String> {
+        let registrations = Rc::new(RefCell::new(Registrations::default()));
+        let (mut engine, started) = sandboxed_engine(ScriptLimits::default());
+
+        let r = registrations.clone();
+        engine.register_fn("register_action", move |label: &str, function: &str| {
+            r.borrow_mut().actions.push(hook(label, function));
+        });
+        let r = registrations.clone();
+        engine.register_fn(
+            "register_exporter",
+            move |label: &str, extension: &str, function: &str| {
+                r.borrow_mut().exporters.push(PluginExporter {
+                    hook: hook(label, function),
+                    extension: extension.trim_start_matches('.').to_string(),
+                });
+            },
+        );
+        let r = registrations.clone();
+        engine.register_fn("register_formatter", move |label: &str, function: &str| {
+            r.borrow_mut().formatters.push(hook(label, function));
+        });
+
+        let ast = engine
+            .compile(source)
+            .map_err(|e| format!("Plugin '{}': {}", name, e))?;
+        if let Ok(mut started) = started.lock() {
+            *started = Instant::now();
+        }
+        engine
+            .run_ast(&ast)
+            .map_err(|e| format!("Plugin '{}': {}", name, e))?;
+
+        let registrations = registrations.take();
+        let plugin = Self {
+            name: name.to_string(),
+            ast,
+            actions: registrations.actions,
+            exporters: registrations.exporters,
+            formatters: registrations.formatters,
+        };
+        plugin.check_functions()?;
+        Ok(plugin)
+    }
+
+    /// Check that every registered function is defined with the expected number of parameters
+    fn check_functions(&self) -> Result<(), String> {
+        let hooks = self
+            .actions
+            .iter()
+            .map(|h| (h, 0))
+            .chain(self.exporters.iter().map(|e| (&e.hook, 1)))
+            .chain(self.formatters.iter().map(|h| (h, 1)));
+        for (hook, params) in hooks {
+            let defined = self
+                .ast
+                .iter_functions()
+                .any(|f| f.name == hook.function && f.params.len() == params);
+            if !defined {
+                return Err(format!(
+                    "Plugin '{}': '{}' is registered but no function {}() with {} parameter(s) is defined",
+                    self.name, hook.label, hook.function, params
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn hook(label: &str, function: &str) -> PluginHook {
+    PluginHook {
+        label: label.to_string(),
+        function: function.to_string(),
+    }
+}
+
+/// Load every `*.rhai` file in `dir`, sorted by name. A missing directory means no plugins.
+/// Plugins that fail to load are skipped and their errors returned.
+pub fn load_plugins(dir: &Path) -> (Vec<Plugin>, Vec<String>) {
+    let mut plugins = Vec::new();
+    let mut errors = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (plugins, errors);
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))
+            .and_then(|source| Plugin::load(&name, &source));
+        match result {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => errors.push(e),
+        }
+    }
+    (plugins, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Value;
+    use crate::script::ScriptEngine;
+
+    #[test]
+    fn test_load_registrations() {
+        let plugin = Plugin::load(
+            "example",
+            r#"
+            fn as_celsius(value) { `${value / 10.0} °C` }
+            fn export_ids(id) { "" }
+            fn hello() { print("hello") }
+
+            register_formatter("Celsius", "as_celsius");
+            register_exporter("Field list", ".txt", "export_ids");
+            register_action("Say hello", "hello");
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(plugin.formatters, vec![hook("Celsius", "as_celsius")]);
+        assert_eq!(plugin.exporters[0].extension, "txt");
+        assert_eq!(plugin.actions[0].function, "hello");
+
+        let engine = ScriptEngine::new();
+        assert_eq!(
+            engine.call(&plugin.ast, "as_celsius", &[&Value::Int(235)]),
+            Ok(Value::Str("23.5 °C".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_call_exporter_with_console_api() {
+        use crate::models::field::{FieldLength, FieldRule, FieldType};
+        use crate::models::protocol::{Endianness, ProtocolRegistry};
+        use crate::script::console::Console;
+
+        let plugin = Plugin::load(
+            "ids",
+            r#"
+            fn export_ids(id) { protocol(id).fields.map(|f| f.id).reduce(|s, id| s + id + ";", "") }
+            register_exporter("Field list", "txt", "export_ids");
+            "#,
+        )
+        .unwrap();
+
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("p", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("p", |p| {
+                p.add_field(FieldRule::new("a", FieldType::Input, FieldLength::Fixed(8)))?;
+                p.add_field(FieldRule::new("b", FieldType::Input, FieldLength::Fixed(8)))
+            })
+            .unwrap();
+
+        let output = Console::new().call(
+            &mut registry,
+            &mut ScriptEngine::new(),
+            None,
+            &plugin.ast,
+            "export_ids",
+            vec!["p".into()],
+        );
+        assert_eq!(output.result, Ok("a;b;".to_string()));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert!(Plugin::load("broken", "fn f(").is_err());
+        let error = Plugin::load("missing", r#"register_action("Go", "go");"#)
+            .err()
+            .unwrap();
+        assert!(error.contains("go()"));
+
+        // the top-level code runs sandboxed like any other script
+        assert!(Plugin::load("endless", "loop {}").is_err());
+        assert!(Plugin::load("imports", r#"import "other" as other;"#).is_err());
+        assert!(Plugin::load("evals", r#"eval("1")"#).is_err());
+    }
+
+    #[test]
+    fn test_missing_directory() {
+        let (plugins, errors) = load_plugins(Path::new("does/not/exist"));
+        assert!(plugins.is_empty());
+        assert!(errors.is_empty());
+    }
+}
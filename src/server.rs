@@ -0,0 +1,332 @@
+//! Local HTTP API exposing the codec of the loaded project, so other tools and CI jobs on
+//! the same machine can decode, encode and validate packets without reimplementing it.
+//!
+//! Endpoints:
+//! - `GET /protocols`: IDs of all protocols
+//! - `POST /protocols/{id}/decode`: hex body → `{"fields": {...}, "issues": [...]}`
+//! - `POST /protocols/{id}/encode`: JSON object of field values → hex
+//! - `POST /protocols/{id}/validate`: hex body → `{"valid": bool, "issues": [...]}`
+//!
+//! Byte values are arrays of numbers. Decoded integers that do not fit in 64 bits are
+//! returned as decimal strings.
+
+use crate::codec::compiled::CodecCache;
+use crate::codec::decode::ValidationIssue;
+use crate::codec::json::{value_to_json, values_from_json};
+use crate::codec::parse_hex;
+use crate::models::protocol::{ProtocolRegistry, Severity};
+use crate::script::ScriptEngine;
+use serde_json::{Map, json};
+use std::io::Read;
+use std::net::SocketAddr;
+
+/// Largest request body accepted, in bytes
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+/// Status code and body of a response
+#[derive(Clone, PartialEq, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(value: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }).to_string(),
+        }
+    }
+}
+
+/// Answer a single request against the given project, with the codecs compiled for earlier
+/// requests
+pub fn handle_request(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    codecs: &mut CodecCache,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Response {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["protocols"]) => {
+            let ids: Vec<&str> = registry
+                .list_protocols()
+                .into_iter()
+                .map(|p| p.id.as_str())
+                .collect();
+            Response::json(json!(ids))
+        }
+        ("POST", ["protocols", id, action]) => {
+            if registry.get_protocol(id).is_none() {
+                return Response::error(404, format!("Protocol with ID '{}' does not exist", id));
+            }
+            match *action {
+                "decode" => decode_request(registry, engine, codecs, id, body),
+                "encode" => encode_request(registry, engine, codecs, id, body),
+                "validate" => validate_request(registry, engine, codecs, id, body),
+                _ => Response::error(404, format!("Unknown action '{}'", action)),
+            }
+        }
+        (_, ["protocols"]) | (_, ["protocols", _, _]) => {
+            Response::error(405, format!("Method {} not allowed", method))
+        }
+        _ => Response::error(404, format!("Unknown path '{}'", path)),
+    }
+}
+
+fn decode_request(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    codecs: &mut CodecCache,
+    id: &str,
+    body: &str,
+) -> Response {
+    let result = parse_hex(body).and_then(|data| codecs.decode(registry, engine, id, &data));
+    match result {
+        Ok(packet) => {
+            let fields: Map<String, serde_json::Value> = packet
+                .fields
+                .iter()
+                .map(|f| (f.rule_id.clone(), value_to_json(&f.value)))
+                .collect();
+            Response::json(json!({
+                "fields": fields,
+                "issues": issues_to_json(&packet.issues),
+            }))
+        }
+        Err(e) => Response::error(400, e),
+    }
+}
+
+fn encode_request(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    codecs: &mut CodecCache,
+    id: &str,
+    body: &str,
+) -> Response {
+    let result =
+        values_from_json(body).and_then(|values| codecs.encode(registry, engine, id, &values));
+    match result {
+        Ok(data) => Response {
+            status: 200,
+            body: data.iter().map(|b| format!("{:02X}", b)).collect(),
+        },
+        Err(e) => Response::error(400, e),
+    }
+}
+
+fn validate_request(
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    codecs: &mut CodecCache,
+    id: &str,
+    body: &str,
+) -> Response {
+    let result = parse_hex(body).and_then(|data| codecs.decode(registry, engine, id, &data));
+    match result {
+        Ok(packet) => Response::json(json!({
+            "valid": !packet.issues.iter().any(|i| i.severity == Severity::Error),
+            "issues": issues_to_json(&packet.issues),
+        })),
+        Err(e) => Response::json(json!({
+            "valid": false,
+            "issues": [{ "severity": "error", "message": e }],
+        })),
+    }
+}
+
+fn issues_to_json(issues: &[ValidationIssue]) -> serde_json::Value {
+    issues
+        .iter()
+        .map(|issue| {
+            json!({
+                "protocol": issue.protocol_id,
+                "validator": issue.validator,
+                "severity": match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": issue.message,
+            })
+        })
+        .collect()
+}
+
+/// A request that was answered, for the log
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoggedRequest {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+}
+
+/// HTTP server polled from the UI loop, so requests are answered against the current project
+pub struct ApiServer {
+    server: tiny_http::Server,
+    codecs: CodecCache,
+}
+
+impl ApiServer {
+    pub fn start(address: &str) -> Result<Self, String> {
+        let server = tiny_http::Server::http(address)
+            .map_err(|e| format!("Failed to listen on '{}': {}", address, e))?;
+        Ok(Self {
+            server,
+            codecs: CodecCache::new(),
+        })
+    }
+
+    /// Address the server listens on
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Answer every request that has arrived, without blocking
+    pub fn poll(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+    ) -> Result<Vec<LoggedRequest>, String> {
+        let mut answered = Vec::new();
+        while let Some(mut request) = self
+            .server
+            .try_recv()
+            .map_err(|e| format!("HTTP server failed: {}", e))?
+        {
+            let method = request.method().to_string();
+            let path = request.url().to_string();
+            let mut body = String::new();
+            let response = match request.as_reader().take(MAX_BODY).read_to_string(&mut body) {
+                Ok(_) => handle_request(registry, engine, &mut self.codecs, &method, &path, &body),
+                Err(e) => Response::error(400, format!("Failed to read request body: {}", e)),
+            };
+
+            let content_type = if response.body.starts_with('{') || response.body.starts_with('[') {
+                &b"application/json"[..]
+            } else {
+                &b"text/plain"[..]
+            };
+            let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], content_type)
+                .expect("header is valid");
+            let status = response.status;
+            // a client that hung up is not an error of the server
+            let _ = request.respond(
+                tiny_http::Response::from_string(response.body)
+                    .with_status_code(status)
+                    .with_header(header),
+            );
+            answered.push(LoggedRequest {
+                method,
+                path,
+                status,
+            });
+        }
+        Ok(answered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::{Endianness, PacketValidator};
+
+    fn registry() -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        registry
+            .create_protocol("frame", None, Endianness::Big, None)
+            .unwrap();
+        registry
+            .edit_protocol("frame", |p| {
+                p.add_field(FieldRule::new(
+                    "length",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "payload",
+                    FieldType::Input,
+                    FieldLength::Variable,
+                ))?;
+                p.validators.push(PacketValidator {
+                    name: "length".to_string(),
+                    severity: Severity::Error,
+                    script: "length == payload.len()".to_string(),
+                });
+                Ok(())
+            })
+            .unwrap();
+        registry
+    }
+
+    fn request(method: &str, path: &str, body: &str) -> Response {
+        handle_request(
+            &registry(),
+            &ScriptEngine::new(),
+            &mut CodecCache::new(),
+            method,
+            path,
+            body,
+        )
+    }
+
+    #[test]
+    fn test_decode() {
+        let response = request("POST", "/protocols/frame/decode", "02 AB CD");
+        assert_eq!(response.status, 200);
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["fields"]["length"], 2);
+        assert_eq!(body["fields"]["payload"], json!([0xAB, 0xCD]));
+        assert_eq!(body["issues"], json!([]));
+
+        assert_eq!(request("POST", "/protocols/frame/decode", "zz").status, 400);
+        assert_eq!(request("POST", "/protocols/missing/decode", "").status, 404);
+    }
+
+    #[test]
+    fn test_encode() {
+        let response = request(
+            "POST",
+            "/protocols/frame/encode",
+            r#"{"length": 1, "payload": [255]}"#,
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "01FF");
+
+        let response = request("POST", "/protocols/frame/encode", r#"{"length": 1}"#);
+        assert_eq!(response.status, 400);
+        assert!(response.body.contains("payload"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let body = request("POST", "/protocols/frame/validate", "03 AB").body;
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["valid"], false);
+        assert_eq!(body["issues"][0]["validator"], "length");
+
+        let body = request("POST", "/protocols/frame/validate", "01 AB").body;
+        assert!(body.contains(r#""valid":true"#));
+    }
+
+    #[test]
+    fn test_routing() {
+        assert_eq!(request("GET", "/protocols", "").body, r#"["frame"]"#);
+        assert_eq!(request("GET", "/protocols/frame/decode", "").status, 405);
+        assert_eq!(request("POST", "/protocols/frame/other", "").status, 404);
+        assert_eq!(request("GET", "/", "").status, 404);
+    }
+}
@@ -0,0 +1,264 @@
+//! Stand-in for a device: incoming packets are decoded and handed to a rhai script,
+//! whose reply is encoded and sent back.
+
+use crate::codec::compiled::CodecCache;
+use crate::codec::decode::DecodedPacket;
+use crate::codec::stream::StreamDecoder;
+use crate::models::protocol::ProtocolRegistry;
+use crate::script::ScriptEngine;
+use crate::transport::Transport;
+use rhai::{AST, Dynamic, Map};
+
+/// Name of the script function called for every received packet
+pub const HANDLER_FUNCTION: &str = "on_packet";
+
+/// Something that happened while simulating, for the log
+#[derive(Clone, PartialEq, Debug)]
+pub enum SimulatorEvent {
+    Received(DecodedPacket),
+    Sent(Vec<u8>),
+    Error(String),
+}
+
+/// Request→response behavior defined by a script with an `on_packet(packet)` function.
+/// `packet` is a map of the decoded field values; the function returns a map of field values
+/// for the reply, or `()` to stay silent. `this` is a map kept between packets for state
+/// such as counters.
+pub struct Simulator {
+    pub request_protocol: String,
+    pub response_protocol: String,
+    /// split what arrives into requests by the length of the request protocol, for transports
+    /// that carry a byte stream rather than packets
+    pub stream: Option<StreamDecoder>,
+    ast: AST,
+    state: Dynamic,
+    codecs: CodecCache,
+}
+
+impl Simulator {
+    pub fn new(
+        engine: &ScriptEngine,
+        request_protocol: &str,
+        response_protocol: &str,
+        script: &str,
+    ) -> Result<Self, String> {
+        let ast = engine.compile(script).map_err(|e| e.to_string())?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == HANDLER_FUNCTION && f.params.len() == 1)
+        {
+            return Err(format!(
+                "Script must define a function {}(packet)",
+                HANDLER_FUNCTION
+            ));
+        }
+
+        Ok(Self {
+            request_protocol: request_protocol.to_string(),
+            response_protocol: response_protocol.to_string(),
+            stream: None,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            codecs: CodecCache::new(),
+        })
+    }
+
+    /// Run the script on a decoded request and encode its reply, if any
+    pub fn handle(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        packet: &DecodedPacket,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let fields: Vec<_> = packet
+            .fields
+            .iter()
+            .map(|f| (f.rule_id.as_str(), &f.value))
+            .collect();
+        let reply = engine.call_handler(&self.ast, HANDLER_FUNCTION, &mut self.state, &fields)?;
+        reply
+            .map(|values| {
+                self.codecs
+                    .encode(registry, engine, &self.response_protocol, &values)
+            })
+            .transpose()
+    }
+
+    /// Answer every packet that has arrived on `transport`
+    pub fn poll(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        transport: &mut dyn Transport,
+    ) -> Vec<SimulatorEvent> {
+        let mut events = Vec::new();
+        loop {
+            let data = match transport.try_recv() {
+                Ok(Some(data)) => data,
+                Ok(None) => break,
+                Err(e) => {
+                    events.push(SimulatorEvent::Error(e));
+                    break;
+                }
+            };
+
+            let requests = match &mut self.stream {
+                Some(stream) => {
+                    match stream.feed(registry, engine, &self.request_protocol, &data) {
+                        Ok(framed) => framed.into_iter().map(|f| f.decoded).collect(),
+                        Err(e) => vec![Err(e)],
+                    }
+                }
+                None => vec![
+                    self.codecs
+                        .decode(registry, engine, &self.request_protocol, &data),
+                ],
+            };
+            for request in requests {
+                match request {
+                    Ok(packet) => self.answer(registry, engine, transport, packet, &mut events),
+                    Err(e) => events.push(SimulatorEvent::Error(e)),
+                }
+            }
+        }
+        events
+    }
+
+    /// Log a request, and send and log the reply of the script to it
+    fn answer(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        transport: &mut dyn Transport,
+        packet: DecodedPacket,
+        events: &mut Vec<SimulatorEvent>,
+    ) {
+        let reply = self.handle(registry, engine, &packet);
+        events.push(SimulatorEvent::Received(packet));
+        match reply.and_then(|reply| match reply {
+            Some(data) => transport.send(&data).map(|_| Some(data)),
+            None => Ok(None),
+        }) {
+            Ok(Some(data)) => events.push(SimulatorEvent::Sent(data)),
+            Ok(None) => {}
+            Err(e) => events.push(SimulatorEvent::Error(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::field::{FieldLength, FieldRule, FieldType};
+    use crate::models::protocol::Endianness;
+    use std::collections::VecDeque;
+
+    /// Transport replaying queued packets and recording what is sent
+    #[derive(Default)]
+    struct Loopback {
+        incoming: VecDeque<Vec<u8>>,
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl Transport for Loopback {
+        fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.incoming.pop_front())
+        }
+
+        fn send(&mut self, data: &[u8]) -> Result<(), String> {
+            self.sent.push(data.to_vec());
+            Ok(())
+        }
+    }
+
+    fn registry() -> ProtocolRegistry {
+        let mut registry = ProtocolRegistry::new();
+        for id in ["request", "response"] {
+            registry
+                .create_protocol(id, None, Endianness::Big, None)
+                .unwrap();
+        }
+        registry
+            .edit_protocol("request", |p| {
+                p.add_field(FieldRule::new(
+                    "command",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        registry
+            .edit_protocol("response", |p| {
+                p.add_field(FieldRule::new(
+                    "echo",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))?;
+                p.add_field(FieldRule::new(
+                    "count",
+                    FieldType::Input,
+                    FieldLength::Fixed(8),
+                ))
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_stateful_replies() {
+        let registry = registry();
+        let engine = ScriptEngine::new();
+        let mut simulator = Simulator::new(
+            &engine,
+            "request",
+            "response",
+            r#"
+            fn on_packet(packet) {
+                if packet.command == 0 { return; }
+                this.count = (this.count ?? 0) + 1;
+                #{ echo: packet.command, count: this.count }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let mut transport = Loopback {
+            incoming: VecDeque::from([vec![0x05], vec![0x00], vec![0x07], vec![]]),
+            ..Default::default()
+        };
+        let events = simulator.poll(&registry, &engine, &mut transport);
+
+        assert_eq!(transport.sent, vec![vec![0x05, 0x01], vec![0x07, 0x02]]);
+        // the empty packet fails to decode
+        assert!(matches!(events.last(), Some(SimulatorEvent::Error(_))));
+    }
+
+    #[test]
+    fn test_split_stream() {
+        let registry = registry();
+        let engine = ScriptEngine::new();
+        let mut simulator = Simulator::new(
+            &engine,
+            "request",
+            "response",
+            "fn on_packet(packet) { #{ echo: packet.command, count: 0 } }",
+        )
+        .unwrap();
+        simulator.stream = Some(StreamDecoder::new());
+
+        // two requests arriving in one read
+        let mut transport = Loopback {
+            incoming: VecDeque::from([vec![0x01, 0x02]]),
+            ..Default::default()
+        };
+        simulator.poll(&registry, &engine, &mut transport);
+        assert_eq!(transport.sent, vec![vec![0x01, 0x00], vec![0x02, 0x00]]);
+    }
+
+    #[test]
+    fn test_missing_handler() {
+        let engine = ScriptEngine::new();
+        assert!(Simulator::new(&engine, "request", "response", "fn other(p) {}").is_err());
+        assert!(Simulator::new(&engine, "request", "response", "fn on_packet(").is_err());
+    }
+}
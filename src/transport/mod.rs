@@ -0,0 +1,75 @@
+//! Byte channels packets are exchanged over, polled from the UI loop without blocking
+
+pub mod serial;
+pub mod socketcan;
+pub mod tcp;
+pub mod udp;
+
+/// A channel to a device that packets can be sent to and received from
+pub trait Transport {
+    /// Receive the next packet if one has arrived, without blocking
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String>;
+
+    /// Send a packet. Where the transport is addressed, replies go to the sender of the
+    /// last received packet unless a fixed remote address is configured.
+    fn send(&mut self, data: &[u8]) -> Result<(), String>;
+}
+
+/// Settings to open a [`Transport`] with
+#[derive(Clone, PartialEq, Debug)]
+pub enum TransportConfig {
+    Udp {
+        /// local address to listen on, e.g. `0.0.0.0:5000`
+        bind: String,
+        /// address to send to; if empty, replies go to the last sender
+        remote: String,
+    },
+    Serial {
+        port: String,
+        baud_rate: u32,
+    },
+    Tcp {
+        /// address of the device to connect to, e.g. `192.168.1.10:502`
+        remote: String,
+    },
+    /// CAN frames on a SocketCAN interface, see [`socketcan`]
+    SocketCan {
+        /// network interface, e.g. `can0` or `vcan0`
+        interface: String,
+    },
+}
+
+impl TransportConfig {
+    pub fn open(&self) -> Result<Box<dyn Transport>, String> {
+        match self {
+            TransportConfig::Udp { bind, remote } => {
+                let remote = (!remote.trim().is_empty()).then(|| remote.trim());
+                Ok(Box::new(udp::UdpTransport::open(bind.trim(), remote)?))
+            }
+            TransportConfig::Serial { port, baud_rate } => Ok(Box::new(
+                serial::SerialTransport::open(port.trim(), *baud_rate)?,
+            )),
+            TransportConfig::Tcp { remote } => {
+                Ok(Box::new(tcp::TcpTransport::connect(remote.trim())?))
+            }
+            #[cfg(target_os = "linux")]
+            TransportConfig::SocketCan { interface } => Ok(Box::new(
+                socketcan::SocketCanTransport::open(interface.trim())?,
+            )),
+            #[cfg(not(target_os = "linux"))]
+            TransportConfig::SocketCan { .. } => {
+                Err("SocketCAN is only available on Linux".to_string())
+            }
+        }
+    }
+
+    /// Short name of the transport kind for display
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            TransportConfig::Udp { .. } => "UDP",
+            TransportConfig::Serial { .. } => "Serial",
+            TransportConfig::Tcp { .. } => "TCP",
+            TransportConfig::SocketCan { .. } => "SocketCAN",
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use super::Transport;
+use serialport::SerialPort;
+use std::io::{ErrorKind, Read, Write};
+use std::time::Duration;
+
+/// A serial port. Serial links have no framing of their own, so whatever bytes have
+/// arrived since the last poll are returned as one packet.
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+}
+
+impl SerialTransport {
+    pub fn open(port: &str, baud_rate: u32) -> Result<Self, String> {
+        let port = serialport::new(port, baud_rate)
+            .timeout(Duration::from_millis(1))
+            .open()
+            .map_err(|e| format!("Failed to open serial port '{}': {}", port, e))?;
+        Ok(Self { port })
+    }
+}
+
+/// Names of the serial ports present on the system
+pub fn available_ports() -> Vec<String> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| p.port_name)
+        .collect()
+}
+
+impl Transport for SerialTransport {
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let available = self
+            .port
+            .bytes_to_read()
+            .map_err(|e| format!("Failed to read serial port: {}", e))?;
+        if available == 0 {
+            return Ok(None);
+        }
+
+        let mut buf = vec![0u8; available as usize];
+        match self.port.read(&mut buf) {
+            Ok(len) => {
+                buf.truncate(len);
+                Ok((len > 0).then_some(buf))
+            }
+            Err(e) if e.kind() == ErrorKind::TimedOut => Ok(None),
+            Err(e) => Err(format!("Failed to read serial port: {}", e)),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), String> {
+        self.port
+            .write_all(data)
+            .map_err(|e| format!("Failed to write serial port: {}", e))
+    }
+}
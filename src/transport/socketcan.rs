@@ -0,0 +1,188 @@
+//! Classic CAN frames on a Linux SocketCAN interface, real (`can0`) or virtual (`vcan0`).
+//!
+//! A CAN frame is exchanged as a packet of its CAN ID as a big-endian 32-bit word followed by
+//! its 0 to 8 data bytes. Bit 31 of the word marks a 29-bit extended ID and bit 30 a remote
+//! request, as in the kernel's `can_frame`. A protocol for CAN frames thus designates its first
+//! field, 32 bits long, as the CAN ID, and its subprotocols can tell messages apart by it.
+
+use super::Transport;
+
+/// Flag of the CAN ID word for a 29-bit extended ID
+pub const CAN_EFF_FLAG: u32 = 0x8000_0000;
+/// Flag of the CAN ID word for a remote transmission request
+pub const CAN_RTR_FLAG: u32 = 0x4000_0000;
+/// Largest standard 11-bit CAN ID
+pub const CAN_SFF_MASK: u32 = 0x7ff;
+/// Largest extended 29-bit CAN ID
+pub const CAN_EFF_MASK: u32 = 0x1fff_ffff;
+/// Most data bytes of a classic CAN frame
+pub const CAN_MAX_DLEN: usize = 8;
+
+/// The packet for a CAN frame
+pub fn frame_to_packet(can_id: u32, data: &[u8]) -> Vec<u8> {
+    let mut packet = can_id.to_be_bytes().to_vec();
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// The CAN ID word and data of the frame for a packet. An ID too large for a standard frame is
+/// sent as an extended one.
+pub fn packet_to_frame(packet: &[u8]) -> Result<(u32, &[u8]), String> {
+    let (word, data) = packet
+        .split_first_chunk::<4>()
+        .ok_or("A CAN packet starts with the 4 byte CAN ID")?;
+    if data.len() > CAN_MAX_DLEN {
+        return Err(format!(
+            "A CAN frame carries at most {} data bytes, not {}",
+            CAN_MAX_DLEN,
+            data.len()
+        ));
+    }
+    let mut can_id = u32::from_be_bytes(*word);
+    let id = can_id & CAN_EFF_MASK;
+    if can_id & !(CAN_EFF_FLAG | CAN_RTR_FLAG | CAN_EFF_MASK) != 0 {
+        return Err(format!("CAN ID word {:#010x} has unknown flags", can_id));
+    }
+    if id > CAN_SFF_MASK {
+        can_id |= CAN_EFF_FLAG;
+    }
+    Ok((can_id, data))
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::SocketCanTransport;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    /// A raw CAN socket bound to one interface
+    pub struct SocketCanTransport {
+        socket: OwnedFd,
+    }
+
+    impl SocketCanTransport {
+        pub fn open(interface: &str) -> Result<Self, String> {
+            let name = CString::new(interface)
+                .map_err(|_| format!("'{}' is not a valid interface name", interface))?;
+            // SAFETY: `name` is a NUL-terminated string that outlives the call
+            let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+            if index == 0 {
+                return Err(format!("There is no network interface '{}'", interface));
+            }
+
+            // SAFETY: plain system call; the descriptor is owned right after
+            let fd = unsafe {
+                libc::socket(
+                    libc::AF_CAN,
+                    libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                    libc::CAN_RAW,
+                )
+            };
+            if fd < 0 {
+                return Err(format!(
+                    "Failed to open CAN socket: {}",
+                    io::Error::last_os_error()
+                ));
+            }
+            // SAFETY: `fd` is a new descriptor nothing else owns
+            let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+            // SAFETY: all zeros is a valid `sockaddr_can`
+            let mut addr: libc::sockaddr_can = unsafe { std::mem::zeroed() };
+            addr.can_family = libc::AF_CAN as libc::sa_family_t;
+            addr.can_ifindex = index as libc::c_int;
+            // SAFETY: `addr` is a `sockaddr_can` of the size given
+            let result = unsafe {
+                libc::bind(
+                    socket.as_raw_fd(),
+                    &addr as *const libc::sockaddr_can as *const libc::sockaddr,
+                    size_of::<libc::sockaddr_can>() as libc::socklen_t,
+                )
+            };
+            if result < 0 {
+                return Err(format!(
+                    "Failed to bind to CAN interface '{}': {}",
+                    interface,
+                    io::Error::last_os_error()
+                ));
+            }
+            Ok(Self { socket })
+        }
+    }
+
+    impl Transport for SocketCanTransport {
+        fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+            // SAFETY: all zeros is a valid `can_frame`
+            let mut frame: libc::can_frame = unsafe { std::mem::zeroed() };
+            // SAFETY: reads at most the size of `frame` into it
+            let read = unsafe {
+                libc::read(
+                    self.socket.as_raw_fd(),
+                    &mut frame as *mut libc::can_frame as *mut libc::c_void,
+                    size_of::<libc::can_frame>(),
+                )
+            };
+            if read < 0 {
+                let error = io::Error::last_os_error();
+                return match error.kind() {
+                    io::ErrorKind::WouldBlock => Ok(None),
+                    _ => Err(format!("Failed to receive: {}", error)),
+                };
+            }
+            let len = (frame.can_dlc as usize).min(CAN_MAX_DLEN);
+            Ok(Some(frame_to_packet(frame.can_id, &frame.data[..len])))
+        }
+
+        fn send(&mut self, data: &[u8]) -> Result<(), String> {
+            let (can_id, payload) = packet_to_frame(data)?;
+            // SAFETY: all zeros is a valid `can_frame`
+            let mut frame: libc::can_frame = unsafe { std::mem::zeroed() };
+            frame.can_id = can_id;
+            frame.can_dlc = payload.len() as u8;
+            frame.data[..payload.len()].copy_from_slice(payload);
+            // SAFETY: writes the whole of `frame` from it
+            let written = unsafe {
+                libc::write(
+                    self.socket.as_raw_fd(),
+                    &frame as *const libc::can_frame as *const libc::c_void,
+                    size_of::<libc::can_frame>(),
+                )
+            };
+            if written < 0 {
+                return Err(format!("Failed to send: {}", io::Error::last_os_error()));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_packets() {
+        let packet = frame_to_packet(0x123, &[1, 2]);
+        assert_eq!(packet, vec![0, 0, 0x01, 0x23, 1, 2]);
+        assert_eq!(packet_to_frame(&packet), Ok((0x123, &[1, 2][..])));
+
+        // too large for a standard ID
+        let (can_id, _) = packet_to_frame(&[0, 0, 0x08, 0x00]).unwrap();
+        assert_eq!(can_id, CAN_EFF_FLAG | 0x800);
+
+        assert!(packet_to_frame(&[0, 0, 1]).is_err());
+        assert!(packet_to_frame(&[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+        assert!(packet_to_frame(&[0x20, 0, 0, 1]).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_open_missing_interface() {
+        let error = SocketCanTransport::open("nocan9").err().unwrap();
+        assert!(error.contains("nocan9"));
+    }
+}
@@ -0,0 +1,97 @@
+use super::Transport;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Most bytes read in one poll
+const READ_BUFFER: usize = 65_536;
+
+/// How long connecting may take before giving up
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A TCP connection to a device. TCP is a byte stream, so like a serial port whatever bytes
+/// have arrived since the last poll are returned as one packet; a message may be split across
+/// polls or share one with others.
+pub struct TcpTransport {
+    stream: TcpStream,
+}
+
+impl TcpTransport {
+    pub fn connect(remote: &str) -> Result<Self, String> {
+        let addr = remote
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| format!("'{}' is not a valid address", remote))?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+            .map_err(|e| format!("Failed to connect to '{}': {}", remote, e))?;
+        stream
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure socket: {}", e))?;
+        Ok(Self { stream })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let mut buf = vec![0u8; READ_BUFFER];
+        match self.stream.read(&mut buf) {
+            Ok(0) => Err("Connection closed by the remote end".to_string()),
+            Ok(len) => {
+                buf.truncate(len);
+                Ok(Some(buf))
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(format!("Failed to receive: {}", e)),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), String> {
+        // block until the whole packet is handed to the socket
+        let result = self
+            .stream
+            .set_nonblocking(false)
+            .and_then(|_| self.stream.write_all(data))
+            .and_then(|_| self.stream.set_nonblocking(true));
+        result.map_err(|e| format!("Failed to send: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    #[test]
+    fn test_stream_both_ways() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let mut client = TcpTransport::connect(&addr).unwrap();
+        let (mut device, _) = listener.accept().unwrap();
+
+        assert_eq!(client.try_recv(), Ok(None));
+        device.write_all(&[1, 2, 3]).unwrap();
+        let start = Instant::now();
+        let mut received = Vec::new();
+        while received.len() < 3 && start.elapsed() < Duration::from_secs(1) {
+            received.extend(client.try_recv().unwrap().unwrap_or_default());
+        }
+        assert_eq!(received, vec![1, 2, 3]);
+
+        client.send(&[4, 5]).unwrap();
+        let mut reply = [0; 2];
+        device.read_exact(&mut reply).unwrap();
+        assert_eq!(reply, [4, 5]);
+
+        drop(device);
+        let start = Instant::now();
+        let closed = loop {
+            match client.try_recv() {
+                Ok(None) if start.elapsed() < Duration::from_secs(1) => continue,
+                other => break other,
+            }
+        };
+        assert!(closed.is_err());
+    }
+}
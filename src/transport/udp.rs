@@ -0,0 +1,105 @@
+use super::Transport;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// Largest datagram that can be received
+const MAX_DATAGRAM: usize = 65_535;
+
+/// One packet per datagram
+pub struct UdpTransport {
+    socket: UdpSocket,
+    /// fixed destination for sent packets
+    remote: Option<SocketAddr>,
+    /// sender of the last received packet
+    last_peer: Option<SocketAddr>,
+}
+
+impl UdpTransport {
+    pub fn open(bind: &str, remote: Option<&str>) -> Result<Self, String> {
+        let socket =
+            UdpSocket::bind(bind).map_err(|e| format!("Failed to bind to '{}': {}", bind, e))?;
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure socket: {}", e))?;
+        let remote = remote
+            .map(|addr| {
+                addr.to_socket_addrs()
+                    .ok()
+                    .and_then(|mut addrs| addrs.next())
+                    .ok_or_else(|| format!("'{}' is not a valid address", addr))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            socket,
+            remote,
+            last_peer: None,
+        })
+    }
+
+    /// The address the socket is bound to
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.socket.local_addr().ok()
+    }
+}
+
+impl Transport for UdpTransport {
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        match self.socket.recv_from(&mut buf) {
+            Ok((len, peer)) => {
+                buf.truncate(len);
+                self.last_peer = Some(peer);
+                Ok(Some(buf))
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(format!("Failed to receive: {}", e)),
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), String> {
+        let target = self
+            .remote
+            .or(self.last_peer)
+            .ok_or("No remote address to send to")?;
+        self.socket
+            .send_to(data, target)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to send to {}: {}", target, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn recv_within(transport: &mut UdpTransport, timeout: Duration) -> Option<Vec<u8>> {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if let Some(data) = transport.try_recv().unwrap() {
+                return Some(data);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        None
+    }
+
+    #[test]
+    fn test_reply_to_last_sender() {
+        let mut device = UdpTransport::open("127.0.0.1:0", None).unwrap();
+        let device_addr = device.local_addr().unwrap().to_string();
+        let mut client = UdpTransport::open("127.0.0.1:0", Some(&device_addr)).unwrap();
+
+        assert!(device.send(&[1]).is_err()); // nobody to reply to yet
+        assert_eq!(device.try_recv(), Ok(None));
+
+        client.send(&[0x01, 0x02]).unwrap();
+        let request = recv_within(&mut device, Duration::from_secs(1));
+        assert_eq!(request, Some(vec![0x01, 0x02]));
+
+        device.send(&[0x03]).unwrap();
+        let reply = recv_within(&mut client, Duration::from_secs(1));
+        assert_eq!(reply, Some(vec![0x03]));
+    }
+}
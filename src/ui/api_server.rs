@@ -0,0 +1,103 @@
+use crate::app::BitLoomApp;
+use bitloom::server::{ApiServer, LoggedRequest};
+use bitloom::tr;
+use eframe::egui;
+use std::time::Duration;
+
+/// Most recent requests kept for the log
+const MAX_LOG: usize = 200;
+
+/// Answer API requests that arrived since the last frame
+pub fn poll(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(server) = &mut app.api_server else {
+        return;
+    };
+    match server.poll(&app.registry, &app.script_engine) {
+        Ok(requests) => app.api_server_log.extend(requests),
+        Err(e) => {
+            app.api_server = None;
+            app.error = Some(e);
+            return;
+        }
+    }
+    let excess = app.api_server_log.len().saturating_sub(MAX_LOG);
+    app.api_server_log.drain(..excess);
+    ctx.request_repaint_after(Duration::from_millis(50));
+}
+
+/// Address, start/stop controls and request log of the local HTTP API
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_api_server;
+    egui::Window::new(tr!("api-server-title"))
+        .id(egui::Id::new("api_server"))
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("transport-listen-on"));
+                ui.add_enabled(
+                    app.api_server.is_none(),
+                    egui::TextEdit::singleline(&mut app.api_server_address),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                if let Some(server) = &app.api_server {
+                    let address = server
+                        .local_addr()
+                        .map_or_else(|| app.api_server_address.clone(), |a| a.to_string());
+                    ui.label(tr!("api-server-running", address = address));
+                    if ui.button(tr!("dialog-stop")).clicked() {
+                        app.api_server = None;
+                    }
+                } else if ui.button(tr!("dialog-start")).clicked() {
+                    let result = ApiServer::start(&app.api_server_address);
+                    app.api_server = app.report(result);
+                }
+                if ui.button(tr!("dialog-clear-log")).clicked() {
+                    app.api_server_log.clear();
+                }
+            });
+
+            ui.collapsing(tr!("api-server-endpoints"), |ui| {
+                for (endpoint, description) in ENDPOINTS {
+                    ui.horizontal(|ui| {
+                        ui.monospace(*endpoint);
+                        ui.label(tr!(description));
+                    });
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for LoggedRequest {
+                        method,
+                        path,
+                        status,
+                    } in &app.api_server_log
+                    {
+                        let text = format!("{} {} {}", status, method, path);
+                        if *status < 400 {
+                            ui.monospace(text);
+                        } else {
+                            ui.colored_label(
+                                ui.visuals().error_fg_color,
+                                egui::RichText::new(text).monospace(),
+                            );
+                        }
+                    }
+                });
+        });
+    app.show_api_server = open;
+}
+
+/// Each endpoint with the ID of the message describing it
+const ENDPOINTS: &[(&str, &str)] = &[
+    ("GET /protocols", "api-server-list"),
+    ("POST /protocols/{id}/decode", "api-server-decode"),
+    ("POST /protocols/{id}/encode", "api-server-encode"),
+    ("POST /protocols/{id}/validate", "api-server-validate"),
+];
@@ -0,0 +1,124 @@
+use crate::app::BitLoomApp;
+use crate::ui::export_dialog::PendingExport;
+use bitloom::models::accounting::{BitAccounting, BitUse, bit_accounting};
+use bitloom::tr;
+use eframe::egui;
+
+/// Where every bit of the selected protocol goes: used, reserved or unassigned, with the gaps
+/// and the overruns of the frame length
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_bit_accounting;
+    egui::Window::new(tr!("bit-accounting-title"))
+        .id(egui::Id::new("bit_accounting"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let Some(protocol_id) = app.selected_protocol.clone() else {
+                ui.label(tr!("bit-accounting-select-protocol"));
+                return;
+            };
+            let accounting = match bit_accounting(&app.registry, &protocol_id) {
+                Ok(accounting) => accounting,
+                Err(e) => {
+                    ui.label(e);
+                    return;
+                }
+            };
+
+            ui.horizontal(|ui| {
+                ui.label(summary(&accounting));
+                if ui.button(tr!("bit-accounting-export")).clicked() {
+                    app.pending_export = Some(PendingExport::new(
+                        &tr!("bit-accounting-export-title"),
+                        &format!("{}.bits.csv", protocol_id),
+                        csv(&accounting),
+                    ));
+                }
+            });
+            if let Some((bits, id)) = &accounting.frame_length {
+                ui.weak(tr!(
+                    "bit-accounting-frame-length",
+                    bits = *bits,
+                    protocol = id.as_str()
+                ));
+            }
+            for issue in &accounting.issues {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", issue));
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("bit_accounting")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong(tr!("bit-accounting-bits"));
+                        ui.strong(tr!("bit-accounting-length"));
+                        ui.strong(tr!("bit-accounting-use"));
+                        ui.strong(tr!("bit-accounting-field"));
+                        ui.end_row();
+                        for span in &accounting.spans {
+                            ui.monospace(format!("{}-{}", span.start, span.start + span.bits - 1));
+                            ui.label(span.bits.to_string());
+                            let color = match span.usage {
+                                BitUse::Used => ui.visuals().text_color(),
+                                BitUse::Reserved => ui.visuals().weak_text_color(),
+                                BitUse::Unassigned => ui.visuals().warn_fg_color,
+                            };
+                            ui.colored_label(color, use_label(span.usage));
+                            ui.label(
+                                span.owner
+                                    .clone()
+                                    .unwrap_or_else(|| tr!("bit-accounting-padding")),
+                            );
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    app.show_bit_accounting = open;
+}
+
+/// How bits are used, for display; the CSV export keeps the untranslated labels
+fn use_label(usage: BitUse) -> String {
+    match usage {
+        BitUse::Used => tr!("bit-accounting-used"),
+        BitUse::Reserved => tr!("bit-accounting-reserved"),
+        BitUse::Unassigned => tr!("bit-accounting-unassigned"),
+    }
+}
+
+fn summary(accounting: &BitAccounting) -> String {
+    let total = accounting.total_bits.max(1) as f32;
+    let part = |usage| {
+        let bits = accounting.count(usage);
+        tr!(
+            "bit-accounting-part",
+            bits = bits,
+            kind = use_label(usage),
+            percent = format!("{:.0}", bits as f32 / total * 100.0)
+        )
+    };
+    tr!(
+        "bit-accounting-summary",
+        bits = accounting.total_bits,
+        used = part(BitUse::Used),
+        reserved = part(BitUse::Reserved),
+        unassigned = part(BitUse::Unassigned)
+    )
+}
+
+fn csv(accounting: &BitAccounting) -> String {
+    let mut out = String::from("start,end,bits,use,field\n");
+    for span in &accounting.spans {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            span.start,
+            span.start + span.bits - 1,
+            span.bits,
+            span.usage.label(),
+            span.owner.as_deref().unwrap_or("")
+        ));
+    }
+    out
+}
@@ -0,0 +1,230 @@
+use crate::app::BitLoomApp;
+use crate::ui::widgets::int_input;
+use bitloom::models::field::SignEncoding;
+use bitloom::tr;
+use eframe::egui;
+
+/// Largest width whose bits are shown as toggles
+const MAX_TOGGLE_BITS: u32 = 64;
+
+/// An operation applied to the value of the bit calculator
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operation {
+    And,
+    Or,
+    Xor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl Operation {
+    const ALL: [Operation; 5] = [
+        Operation::And,
+        Operation::Or,
+        Operation::Xor,
+        Operation::ShiftLeft,
+        Operation::ShiftRight,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Xor => "XOR",
+            Operation::ShiftLeft => "<<",
+            Operation::ShiftRight => ">>",
+        }
+    }
+
+    /// The result of the operation on raw values of `bits` bits, truncated to `bits` bits
+    fn apply(self, value: u128, operand: i128, bits: u32) -> u128 {
+        let operand = operand as u128 & mask(bits);
+        let shift = operand.min(128) as u32;
+        let result = match self {
+            Operation::And => value & operand,
+            Operation::Or => value | operand,
+            Operation::Xor => value ^ operand,
+            Operation::ShiftLeft => value.checked_shl(shift).unwrap_or(0),
+            Operation::ShiftRight => value.checked_shr(shift).unwrap_or(0),
+        };
+        result & mask(bits)
+    }
+}
+
+/// Value, width and operation entered in the bit calculator
+pub struct BitCalculator {
+    pub value: i128,
+    /// width in bits the value is read at
+    pub bits: u32,
+    pub operation: Operation,
+    pub operand: i128,
+}
+
+impl Default for BitCalculator {
+    fn default() -> Self {
+        Self {
+            value: 0,
+            bits: 16,
+            operation: Operation::And,
+            operand: 0xff,
+        }
+    }
+}
+
+/// The low `bits` bits set
+fn mask(bits: u32) -> u128 {
+    u128::MAX.checked_shr(128 - bits).unwrap_or(0)
+}
+
+/// Conversions of a value, masking and shifting it, and reading it as a signed value of any
+/// width
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_bit_calculator;
+    egui::Window::new(tr!("bit-calculator-title"))
+        .id(egui::Id::new("bit_calculator"))
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let calculator = &mut app.bit_calculator;
+            egui::Grid::new("bit_calculator_input")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(tr!("bit-calculator-value"));
+                    int_input(ui, "bit_calculator_value", &mut calculator.value);
+                    ui.end_row();
+                    ui.label(tr!("bit-calculator-width"));
+                    ui.add(
+                        egui::DragValue::new(&mut calculator.bits)
+                            .range(1..=128)
+                            .suffix(format!(" {}", tr!("bit-calculator-bits-unit"))),
+                    );
+                    ui.end_row();
+                });
+
+            let bits = calculator.bits;
+            let raw = calculator.value as u128 & mask(bits);
+            let fits = calculator.value >= 0 && calculator.value as u128 == raw
+                || SignEncoding::TwosComplement.fits(calculator.value, bits);
+            if !fits {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    tr!("bit-calculator-truncated", bits = bits),
+                );
+            }
+            ui.separator();
+            conversions(ui, "bit_calculator_value", raw, bits);
+
+            if bits <= MAX_TOGGLE_BITS {
+                ui.separator();
+                if let Some(toggled) = bit_toggles(ui, raw, bits) {
+                    calculator.value = (raw ^ (1 << toggled)) as i128;
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("bit_calculator_operation")
+                    .selected_text(calculator.operation.label())
+                    .width(60.0)
+                    .show_ui(ui, |ui| {
+                        for operation in Operation::ALL {
+                            ui.selectable_value(
+                                &mut calculator.operation,
+                                operation,
+                                operation.label(),
+                            );
+                        }
+                    });
+                int_input(ui, "bit_calculator_operand", &mut calculator.operand);
+            });
+            let result = calculator.operation.apply(raw, calculator.operand, bits);
+            conversions(ui, "bit_calculator_result", result, bits);
+            if ui
+                .button(tr!("bit-calculator-use-result"))
+                .on_hover_text(tr!("bit-calculator-use-result-hint"))
+                .clicked()
+            {
+                calculator.value = result as i128;
+            }
+        });
+    app.show_bit_calculator = open;
+}
+
+/// A raw value of `bits` bits in every notation, each with a button to copy it
+fn conversions(ui: &mut egui::Ui, id_salt: &str, raw: u128, bits: u32) {
+    let digits = bits.div_ceil(4) as usize;
+    let binary = format!("{:0width$b}", raw, width = bits as usize);
+    let binary = binary
+        .as_bytes()
+        .rchunks(4)
+        .rev()
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("_");
+    let bytes = raw.to_be_bytes();
+    let ascii: String = bytes[16 - bits.div_ceil(8) as usize..]
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    let rows = [
+        (tr!("bit-calculator-unsigned"), raw.to_string()),
+        (tr!("bit-calculator-hex"), format!("0x{:0digits$X}", raw)),
+        (tr!("bit-calculator-binary"), format!("0b{}", binary)),
+        (
+            tr!("bit-calculator-twos-complement"),
+            SignEncoding::TwosComplement.decode(raw, bits).to_string(),
+        ),
+        (
+            tr!("bit-calculator-sign-magnitude"),
+            SignEncoding::SignMagnitude.decode(raw, bits).to_string(),
+        ),
+        (tr!("bit-calculator-ascii"), ascii),
+    ];
+    egui::Grid::new(id_salt).num_columns(3).show(ui, |ui| {
+        for (label, text) in rows {
+            ui.label(label);
+            ui.monospace(&text);
+            if ui
+                .small_button("📋")
+                .on_hover_text(tr!("bit-calculator-copy"))
+                .clicked()
+            {
+                ui.ctx().copy_text(text);
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// The bits of a value as buttons, most significant first. Returns the bit that was clicked.
+fn bit_toggles(ui: &mut egui::Ui, raw: u128, bits: u32) -> Option<u32> {
+    let mut toggled = None;
+    for row in (0..bits.div_ceil(16)).rev() {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 2.0;
+            let low = row * 16;
+            for bit in (low..(low + 16).min(bits)).rev() {
+                let set = raw >> bit & 1 == 1;
+                let response = ui
+                    .selectable_label(
+                        set,
+                        egui::RichText::new(if set { "1" } else { "0" }).monospace(),
+                    )
+                    .on_hover_text(tr!("bit-calculator-bit", bit = bit));
+                if response.clicked() {
+                    toggled = Some(bit);
+                }
+                if bit % 4 == 0 && bit != low {
+                    ui.add_space(4.0);
+                }
+            }
+        });
+    }
+    toggled
+}
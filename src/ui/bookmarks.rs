@@ -0,0 +1,128 @@
+use crate::app::BitLoomApp;
+use bitloom::models::bookmark::{add_bookmark, remove_bookmark};
+use bitloom::tr;
+use eframe::egui;
+
+/// The bookmarks of each annotated packet, each showing its range in the hex view when clicked
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_bookmarks;
+    let mut jump = None;
+    let mut remove = None;
+    egui::Window::new(tr!("bookmarks-title"))
+        .id(egui::Id::new("bookmarks"))
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            if app.bookmarks.is_empty() {
+                ui.label(tr!("bookmarks-none"));
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (p, packet) in app.bookmarks.iter().enumerate() {
+                    let shown = packet.data == app.packet_data;
+                    let mut title = tr!("bookmarks-packet", bytes = packet.data.len());
+                    if shown {
+                        title = format!("{} ({})", title, tr!("bookmarks-shown"));
+                    }
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("bookmarked_packet", p))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (b, bookmark) in packet.bookmarks.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .link(&bookmark.name)
+                                        .on_hover_text(tr!("bookmarks-jump"))
+                                        .clicked()
+                                    {
+                                        jump = Some((p, b));
+                                    }
+                                    ui.weak(bookmark.range_label());
+                                    ui.with_layout(
+                                        egui::Layout::right_to_left(egui::Align::Center),
+                                        |ui| {
+                                            if ui
+                                                .small_button("✖")
+                                                .on_hover_text(tr!("bookmarks-remove"))
+                                                .clicked()
+                                            {
+                                                remove = Some((p, b));
+                                            }
+                                        },
+                                    );
+                                });
+                                if !bookmark.comment.is_empty() {
+                                    ui.label(&bookmark.comment);
+                                }
+                            }
+                        });
+                }
+            });
+        });
+    app.show_bookmarks = open;
+
+    if let Some((p, b)) = jump {
+        let packet = &app.bookmarks[p];
+        let bytes = packet.bookmarks[b].byte_range();
+        if packet.data != app.packet_data {
+            app.packet_data = packet.data.clone();
+            app.decode_packet();
+        }
+        app.hex_selection = Some((bytes.start, bytes.end - 1));
+        app.hex_scroll_to = Some(bytes.start);
+    }
+    if let Some((p, b)) = remove {
+        remove_bookmark(&mut app.bookmarks, p, b);
+    }
+}
+
+/// Name and comment of a new bookmark on the packet in the hex view, with its range
+pub fn show_new(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(bookmark) = &mut app.pending_bookmark else {
+        return;
+    };
+
+    let mut open = true;
+    let mut add = false;
+    let packet_bits = app.packet_data.len() * 8;
+    egui::Window::new(tr!("bookmark-new-title"))
+        .id(egui::Id::new("new_bookmark"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("new_bookmark_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(tr!("bookmark-name"));
+                    let name = ui.text_edit_singleline(&mut bookmark.name);
+                    if name.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        add = true;
+                    }
+                    ui.end_row();
+                    ui.label(tr!("bookmark-comment"));
+                    ui.text_edit_multiline(&mut bookmark.comment);
+                    ui.end_row();
+                    ui.label(tr!("bookmark-bit-offset"));
+                    ui.add(
+                        egui::DragValue::new(&mut bookmark.bit_offset)
+                            .range(0..=packet_bits.saturating_sub(1)),
+                    );
+                    ui.end_row();
+                    ui.label(tr!("bookmark-bit-length"));
+                    ui.add(egui::DragValue::new(&mut bookmark.bit_len).range(1..=packet_bits));
+                    ui.end_row();
+                });
+            ui.label(bookmark.range_label());
+            add |= ui.button(tr!("bookmark-add")).clicked();
+        });
+
+    if add {
+        let result = add_bookmark(&mut app.bookmarks, &app.packet_data, bookmark.clone());
+        if app.report(result).is_some() {
+            open = false;
+        }
+    }
+    if !open {
+        app.pending_bookmark = None;
+    }
+}
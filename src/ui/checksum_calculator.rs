@@ -0,0 +1,221 @@
+use crate::app::BitLoomApp;
+use crate::ui::hex_view::selected_bytes;
+use crate::ui::widgets::int_input;
+use bitloom::codec::checksum::{CHECKSUMS, Checksum, CrcParams, checksum_by_name};
+use bitloom::codec::parse_hex;
+use bitloom::tr;
+use eframe::egui;
+
+/// Bytes and algorithm entered in the checksum calculator
+pub struct ChecksumCalculator {
+    pub input: String,
+    /// whether `input` is text whose UTF-8 bytes are checked, rather than hex digits
+    pub as_text: bool,
+    /// name of the checksum in `CHECKSUMS`, or `None` for the custom CRC
+    pub algorithm: Option<&'static str>,
+    pub custom: CrcParams,
+}
+
+impl Default for ChecksumCalculator {
+    fn default() -> Self {
+        Self {
+            input: String::new(),
+            as_text: false,
+            algorithm: Some(CHECKSUMS[0].0),
+            custom: CrcParams {
+                width: 16,
+                poly: 0x1021,
+                init: 0xffff,
+                refin: false,
+                refout: false,
+                xorout: 0,
+            },
+        }
+    }
+}
+
+/// Checksum of pasted bytes with a common algorithm or custom CRC parameters
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_checksum_calculator;
+    egui::Window::new(tr!("checksum-calculator-title"))
+        .id(egui::Id::new("checksum_calculator"))
+        .open(&mut open)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            let selected = selected_bytes(app);
+            ui.horizontal(|ui| {
+                let calculator = &mut app.checksum_calculator;
+                ui.radio_value(
+                    &mut calculator.as_text,
+                    false,
+                    tr!("checksum-calculator-hex"),
+                );
+                ui.radio_value(
+                    &mut calculator.as_text,
+                    true,
+                    tr!("checksum-calculator-text"),
+                );
+                if ui
+                    .add_enabled(
+                        !app.packet_data.is_empty(),
+                        egui::Button::new(tr!("checksum-calculator-use-packet")),
+                    )
+                    .on_hover_text(tr!("checksum-calculator-use-packet-hint"))
+                    .clicked()
+                {
+                    let bytes = match selected {
+                        Some(range) => &app.packet_data[range],
+                        None => &app.packet_data[..],
+                    };
+                    calculator.input = bytes
+                        .iter()
+                        .map(|b| format!("{:02X}", b))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    calculator.as_text = false;
+                }
+            });
+            let calculator = &mut app.checksum_calculator;
+            ui.add(
+                egui::TextEdit::multiline(&mut calculator.input)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+                    .hint_text(if calculator.as_text {
+                        "123456789"
+                    } else {
+                        "31 32 33 34 35 36 37 38 39"
+                    }),
+            );
+
+            egui::ComboBox::from_id_salt("checksum_algorithm")
+                .selected_text(
+                    calculator
+                        .algorithm
+                        .map_or_else(|| tr!("checksum-calculator-custom"), str::to_string),
+                )
+                .show_ui(ui, |ui| {
+                    for (name, _) in CHECKSUMS {
+                        ui.selectable_value(&mut calculator.algorithm, Some(*name), *name);
+                    }
+                    ui.selectable_value(
+                        &mut calculator.algorithm,
+                        None,
+                        tr!("checksum-calculator-custom"),
+                    );
+                });
+            let checksum = match calculator.algorithm.and_then(checksum_by_name) {
+                Some(checksum) => {
+                    if let Checksum::Crc(params) = checksum {
+                        crc_summary(ui, &params);
+                        if ui
+                            .small_button(tr!("checksum-calculator-customize"))
+                            .on_hover_text(tr!("checksum-calculator-customize-hint"))
+                            .clicked()
+                        {
+                            calculator.custom = params;
+                            calculator.algorithm = None;
+                        }
+                    }
+                    Ok(checksum)
+                }
+                None => {
+                    crc_inputs(ui, &mut calculator.custom);
+                    calculator
+                        .custom
+                        .validate()
+                        .map(|()| Checksum::Crc(calculator.custom))
+                }
+            };
+            ui.separator();
+
+            let data = if calculator.as_text {
+                Ok(calculator.input.as_bytes().to_vec())
+            } else {
+                parse_hex(&calculator.input)
+            };
+            match (data, checksum) {
+                (Ok(data), Ok(checksum)) => result(ui, calculator.algorithm, &checksum, &data),
+                (Err(e), _) | (_, Err(e)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                }
+            }
+        });
+    app.show_checksum_calculator = open;
+}
+
+/// The parameters of a catalogue CRC
+fn crc_summary(ui: &mut egui::Ui, params: &CrcParams) {
+    let digits = params.width.div_ceil(4) as usize;
+    ui.weak(format!(
+        "width {}  poly 0x{:0digits$X}  init 0x{:0digits$X}  refin {}  refout {}  xorout 0x{:0digits$X}",
+        params.width, params.poly, params.init, params.refin, params.refout, params.xorout
+    ));
+}
+
+fn crc_inputs(ui: &mut egui::Ui, params: &mut CrcParams) {
+    egui::Grid::new("custom_crc").num_columns(2).show(ui, |ui| {
+        ui.label(tr!("checksum-calculator-width"));
+        ui.add(
+            egui::DragValue::new(&mut params.width)
+                .range(1..=64)
+                .suffix(format!(" {}", tr!("checksum-calculator-bits-unit"))),
+        );
+        ui.end_row();
+        for (id, label, value) in [
+            ("poly", "checksum-calculator-polynomial", &mut params.poly),
+            ("init", "checksum-calculator-init", &mut params.init),
+            ("xorout", "checksum-calculator-xorout", &mut params.xorout),
+        ] {
+            ui.label(tr!(label));
+            let mut wide = *value as i128;
+            if int_input(ui, ("custom_crc", id), &mut wide) {
+                *value = wide as u64;
+            }
+            ui.end_row();
+        }
+        ui.label(tr!("checksum-calculator-reflect"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut params.refin, tr!("checksum-calculator-reflect-input"));
+            ui.checkbox(
+                &mut params.refout,
+                tr!("checksum-calculator-reflect-output"),
+            );
+        });
+        ui.end_row();
+    });
+}
+
+/// The checksum in hex and decimal, and how to compute it in a field expression
+fn result(ui: &mut egui::Ui, algorithm: Option<&str>, checksum: &Checksum, data: &[u8]) {
+    let value = checksum.compute(data);
+    let digits = checksum.width().div_ceil(4) as usize;
+    let hex = format!("0x{:0digits$X}", value);
+    egui::Grid::new("checksum_result")
+        .num_columns(3)
+        .show(ui, |ui| {
+            ui.label(tr!("checksum-calculator-bytes", bytes = data.len()));
+            ui.end_row();
+            for (label, text) in [
+                (tr!("checksum-calculator-hex"), hex),
+                (tr!("checksum-calculator-decimal"), value.to_string()),
+            ] {
+                ui.label(label);
+                ui.strong(egui::RichText::new(&text).monospace());
+                if ui
+                    .small_button("📋")
+                    .on_hover_text(tr!("checksum-calculator-copy"))
+                    .clicked()
+                {
+                    ui.ctx().copy_text(text);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(name) = algorithm {
+        ui.horizontal(|ui| {
+            ui.weak(tr!("checksum-calculator-expression"));
+            ui.monospace(format!("checksum(\"{}\", payload)", name));
+        });
+    }
+}
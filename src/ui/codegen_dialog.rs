@@ -0,0 +1,80 @@
+use crate::app::BitLoomApp;
+use crate::ui::export_dialog::PendingExport;
+use bitloom::codegen::Target;
+use bitloom::tr;
+use eframe::egui;
+
+/// Code generation the user is choosing a target and options for
+pub struct CodegenDialog {
+    /// every target, keeping its options while the user switches between them
+    pub targets: Vec<Target>,
+    pub selected: usize,
+}
+
+impl CodegenDialog {
+    pub fn new() -> Self {
+        Self {
+            targets: Target::all(),
+            selected: 0,
+        }
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(dialog) = &mut app.codegen_dialog else {
+        return;
+    };
+
+    let mut open = true;
+    let mut clicked = false;
+    egui::Window::new(tr!("codegen-title"))
+        .id(egui::Id::new("codegen_dialog"))
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::ComboBox::from_label(tr!("codegen-target"))
+                .selected_text(dialog.targets[dialog.selected].label())
+                .show_ui(ui, |ui| {
+                    for (i, target) in dialog.targets.iter().enumerate() {
+                        ui.selectable_value(&mut dialog.selected, i, target.label());
+                    }
+                });
+            ui.separator();
+            match &mut dialog.targets[dialog.selected] {
+                Target::Go(options) => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("codegen-package"));
+                        ui.text_edit_singleline(&mut options.package);
+                    });
+                    ui.checkbox(&mut options.encoder, tr!("codegen-encode-methods"));
+                }
+                Target::Rust(options) => {
+                    ui.checkbox(&mut options.encoder, tr!("codegen-encode-methods"));
+                    ui.checkbox(&mut options.proptest, tr!("codegen-proptest"))
+                        .on_hover_text(tr!("codegen-proptest-hint"));
+                }
+                Target::TypeScript(options) => {
+                    ui.checkbox(&mut options.bigint, tr!("codegen-bigint"));
+                    ui.checkbox(&mut options.encoder, tr!("codegen-encode-functions"));
+                }
+            }
+            ui.separator();
+            clicked = ui.button(tr!("codegen-generate")).clicked();
+        });
+
+    if clicked && let Some(protocol_id) = app.selected_protocol.clone() {
+        let target = dialog.targets[dialog.selected].clone();
+        let result = target.generate(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("codegen-export-title", language = target.label()),
+                &target.file_name(&protocol_id),
+                content,
+            ));
+            app.codegen_dialog = None;
+        }
+    }
+    if !open {
+        app.codegen_dialog = None;
+    }
+}
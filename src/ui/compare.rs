@@ -0,0 +1,60 @@
+use crate::app::BitLoomApp;
+use bitloom::models::diff::diff_protocols;
+use bitloom::models::protocol::ProtocolRegistry;
+use bitloom::tr;
+use eframe::egui;
+
+/// Diffs two protocols and shows the change report
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_compare;
+    egui::Window::new(tr!("compare-title"))
+        .id(egui::Id::new("compare"))
+        .open(&mut open)
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                protocol_picker(ui, "compare_old", &app.registry, &mut app.compare_ids.0);
+                ui.label("→");
+                protocol_picker(ui, "compare_new", &app.registry, &mut app.compare_ids.1);
+            });
+            ui.separator();
+
+            let (Some(old), Some(new)) = (
+                app.compare_ids
+                    .0
+                    .as_deref()
+                    .and_then(|id| app.registry.get_protocol(id)),
+                app.compare_ids
+                    .1
+                    .as_deref()
+                    .and_then(|id| app.registry.get_protocol(id)),
+            ) else {
+                ui.label(tr!("compare-select-protocols"));
+                return;
+            };
+
+            let report = diff_protocols(old, new).to_report();
+            if ui.button(tr!("compare-copy-report")).clicked() {
+                ui.ctx().copy_text(report.clone());
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.monospace(report);
+            });
+        });
+    app.show_compare = open;
+}
+
+fn protocol_picker(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    registry: &ProtocolRegistry,
+    selected: &mut Option<String>,
+) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected.clone().unwrap_or_else(|| tr!("compare-none")))
+        .show_ui(ui, |ui| {
+            for proto in registry.list_protocols() {
+                ui.selectable_value(selected, Some(proto.id.clone()), &proto.id);
+            }
+        });
+}
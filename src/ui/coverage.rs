@@ -0,0 +1,131 @@
+use crate::app::BitLoomApp;
+use bitloom::coverage::{CoverageReport, Sample, coverage};
+use bitloom::tr;
+use eframe::egui;
+
+/// Which packets the coverage report counts, and the report last made
+#[derive(Default)]
+pub struct CoverageState {
+    /// count the saved packets rather than the decoded packets of the capture
+    pub from_presets: bool,
+    pub only_missing: bool,
+    /// the protocol the report is of, with the report
+    pub report: Option<(String, CoverageReport)>,
+}
+
+/// The subprotocols and enum variants of the selected protocol that the captured or saved
+/// packets never exercise
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_coverage;
+    let mut analyze = None;
+    egui::Window::new(tr!("coverage-title"))
+        .id(egui::Id::new("coverage"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let state = &mut app.coverage;
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut state.from_presets, false, tr!("coverage-capture"));
+                ui.selectable_value(&mut state.from_presets, true, tr!("coverage-presets"));
+                ui.separator();
+                ui.checkbox(&mut state.only_missing, tr!("coverage-only-missing"));
+            });
+            let Some(protocol_id) = &app.selected_protocol else {
+                ui.label(tr!("coverage-select-protocol"));
+                return;
+            };
+            if ui
+                .button(tr!("coverage-analyze", protocol = protocol_id.as_str()))
+                .clicked()
+            {
+                analyze = Some(protocol_id.clone());
+            }
+            if let Some((protocol_id, report)) = &state.report {
+                ui.separator();
+                report_view(ui, protocol_id, report, state.only_missing);
+            }
+        });
+    app.show_coverage = open;
+
+    if let Some(protocol_id) = analyze {
+        let samples: Vec<Sample> = if app.coverage.from_presets {
+            app.presets.iter().map(Sample::from).collect()
+        } else {
+            app.capture
+                .rows
+                .iter()
+                .filter_map(|row| row.decoded.as_ref()?.as_ref().ok())
+                .map(Sample::from)
+                .collect()
+        };
+        let result = coverage(&app.registry, &protocol_id, &samples);
+        if let Some(report) = app.report(result) {
+            app.coverage.report = Some((protocol_id, report));
+        }
+    }
+}
+
+fn report_view(ui: &mut egui::Ui, protocol_id: &str, report: &CoverageReport, only_missing: bool) {
+    let protocols = report.protocols.len();
+    let variants = report.variants.len();
+    ui.label(tr!(
+        "coverage-summary",
+        protocol = protocol_id,
+        protocols = protocols - report.missing_protocols().count(),
+        protocols_total = protocols,
+        variants = variants - report.missing_variants().count(),
+        variants_total = variants
+    ));
+    if report.ignored > 0 {
+        ui.weak(tr!("coverage-ignored", count = report.ignored));
+    }
+
+    let warn = ui.visuals().warn_fg_color;
+    let count_label = |ui: &mut egui::Ui, count: usize| {
+        if count == 0 {
+            ui.colored_label(warn, "0");
+        } else {
+            ui.label(count.to_string());
+        }
+    };
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::CollapsingHeader::new(tr!("coverage-protocols"))
+            .id_salt("coverage_protocols")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("coverage_protocol_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for protocol in &report.protocols {
+                            if only_missing && protocol.count > 0 {
+                                continue;
+                            }
+                            ui.label(&protocol.protocol_id);
+                            count_label(ui, protocol.count);
+                            ui.end_row();
+                        }
+                    });
+            });
+        egui::CollapsingHeader::new(tr!("coverage-variants"))
+            .id_salt("coverage_variants")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("coverage_variant_grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for variant in &report.variants {
+                            if only_missing && variant.count > 0 {
+                                continue;
+                            }
+                            ui.label(format!("{}.{}", variant.protocol_id, variant.field_id));
+                            ui.monospace(variant.value.to_string());
+                            ui.label(variant.name.as_deref().unwrap_or(""));
+                            count_label(ui, variant.count);
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+}
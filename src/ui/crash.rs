@@ -0,0 +1,150 @@
+//! Keeping the project when the app panics: a panic on the UI thread writes the project to an
+//! emergency file with a crash report, and the next launch shows the report and offers to
+//! restore the project.
+
+use crate::app::BitLoomApp;
+use crate::ui::open_dialog;
+use bitloom::tr;
+use eframe::egui;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+const REPORT_FILE: &str = "crash.txt";
+const PROJECT_FILE: &str = "emergency.bitloom";
+/// First line of a report whose project was written, followed by the path of the project
+const PROJECT_LINE: &str = "project: ";
+
+thread_local! {
+    /// Message and backtrace of the last panic on this thread
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// The crash of the last run
+pub struct CrashReport {
+    pub report: String,
+    /// the project as it was when the app crashed, if it could be written
+    pub project_path: Option<PathBuf>,
+}
+
+/// Where the crash report and emergency project are written
+fn crash_dir() -> PathBuf {
+    eframe::storage_dir("BitLoom").unwrap_or_else(std::env::temp_dir)
+}
+
+/// Record the message and backtrace of each panic, in addition to printing them as before
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = format!("{}\n\n{}", info, Backtrace::force_capture());
+        let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some(report));
+        default_hook(info);
+    }));
+}
+
+/// Run a frame of the app. If it panics, the project and the crash report are written before
+/// the panic continues and ends the app.
+pub fn guard(app: &mut BitLoomApp, frame: impl FnOnce(&mut BitLoomApp)) {
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| frame(app))) {
+        let report = LAST_PANIC
+            .with(|last| last.borrow_mut().take())
+            .unwrap_or_else(|| "Unknown panic".to_string());
+        dump(app, &report);
+        panic::resume_unwind(payload);
+    }
+}
+
+fn dump(app: &BitLoomApp, report: &str) {
+    let dir = crash_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    let project_path = dir.join(PROJECT_FILE);
+    // an edit interrupted by the panic can leave the project inconsistent, which the integrity
+    // check finds when it is restored
+    let written = serde_json::to_string_pretty(&app.project())
+        .is_ok_and(|json| std::fs::write(&project_path, json).is_ok());
+    let mut text = String::new();
+    if written {
+        text = format!("{}{}\n", PROJECT_LINE, project_path.display());
+    }
+    text.push_str(report);
+    match std::fs::write(dir.join(REPORT_FILE), text) {
+        Ok(()) if written => eprintln!("Project saved to {}", project_path.display()),
+        Ok(()) => {}
+        Err(e) => eprintln!("Failed to write the crash report: {}", e),
+    }
+}
+
+/// The crash of the last run, if it crashed. The report is removed, so that it is shown once.
+pub fn take_report() -> Option<CrashReport> {
+    let path = crash_dir().join(REPORT_FILE);
+    let text = std::fs::read_to_string(&path).ok()?;
+    let _ = std::fs::remove_file(&path);
+    let (project_path, report) = match text
+        .strip_prefix(PROJECT_LINE)
+        .and_then(|rest| rest.split_once('\n'))
+    {
+        Some((project_path, report)) => (Some(PathBuf::from(project_path)), report.to_string()),
+        None => (None, text),
+    };
+    Some(CrashReport {
+        report,
+        project_path,
+    })
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(crash) = &app.crash else {
+        return;
+    };
+
+    let mut open = true;
+    let mut restore = None;
+    egui::Window::new(tr!("crash-title"))
+        .id(egui::Id::new("crash"))
+        .open(&mut open)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            ui.label(tr!("crash-message"));
+            match &crash.project_path {
+                Some(path) => {
+                    ui.label(tr!(
+                        "crash-project-saved",
+                        path = path.display().to_string()
+                    ));
+                }
+                None => {
+                    ui.colored_label(ui.visuals().error_fg_color, tr!("crash-project-lost"));
+                }
+            }
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(&crash.report).monospace(),
+                    ));
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if let Some(path) = &crash.project_path
+                    && ui.button(tr!("crash-restore")).clicked()
+                {
+                    restore = Some(path.display().to_string());
+                }
+                if ui.button(tr!("crash-copy")).clicked() {
+                    ui.ctx().copy_text(crash.report.clone());
+                }
+            });
+        });
+
+    if let Some(path) = restore {
+        let result = open_dialog::open_project(app, &path);
+        if app.report(result).is_some() {
+            open = false;
+        }
+    }
+    if !open {
+        app.crash = None;
+    }
+}
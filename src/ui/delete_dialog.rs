@@ -0,0 +1,103 @@
+use crate::app::BitLoomApp;
+use bitloom::models::trash::{Deletion, delete_protocol, preview_deletion};
+use bitloom::tr;
+use eframe::egui;
+
+/// Undo the last deletion
+pub const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+/// Ask to confirm deleting a protocol, listing everything that goes with it
+pub fn confirm(app: &mut BitLoomApp, protocol_id: &str) {
+    let result = preview_deletion(&app.registry, &app.presets, protocol_id);
+    app.pending_delete = app.report(result);
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(pending) = &app.pending_delete else {
+        return;
+    };
+
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new(tr!("delete-title"))
+        .id(egui::Id::new("delete_protocol"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(tr!("delete-confirm", protocol = pending.protocol_id()));
+            impact(ui, pending);
+            ui.weak(tr!(
+                "delete-restore-hint",
+                menu = tr!("menu-edit"),
+                item = tr!("menu-recently-deleted")
+            ));
+            ui.separator();
+            ui.horizontal(|ui| {
+                let delete = egui::Button::new(
+                    egui::RichText::new(tr!("delete-button")).color(ui.visuals().error_fg_color),
+                );
+                confirmed = ui.add(delete).clicked();
+                cancelled = ui.button(tr!("dialog-cancel")).clicked();
+            });
+        });
+
+    if confirmed {
+        let protocol_id = pending.protocol_id().to_string();
+        app.pending_delete = None;
+        let result = delete_protocol(&mut app.registry, &mut app.presets, &protocol_id);
+        if let Some(deletion) = app.report(result) {
+            let selected_removed = app
+                .selected_protocol
+                .as_ref()
+                .is_some_and(|id| deletion.protocols.iter().any(|p| &p.id == id));
+            if selected_removed {
+                app.selected_protocol = None;
+                app.selected_field = None;
+                app.selected_fields.clear();
+            }
+            app.trash.push(deletion);
+        }
+    } else if cancelled {
+        app.pending_delete = None;
+    }
+}
+
+/// The subprotocols and presets removed along with a protocol
+fn impact(ui: &mut egui::Ui, deletion: &Deletion) {
+    let subprotocols = deletion.protocols.get(1..).unwrap_or_default();
+    if subprotocols.is_empty() {
+        ui.label(tr!("delete-no-subprotocols"));
+    } else {
+        ui.label(tr!("delete-subprotocols", count = subprotocols.len()));
+        for proto in subprotocols {
+            ui.label(format!("  • {}", proto.id));
+        }
+    }
+    if !deletion.presets.is_empty() {
+        ui.label(tr!("delete-presets", count = deletion.presets.len()));
+        for preset in &deletion.presets {
+            ui.label(format!("  • {} ({})", preset.path(), preset.protocol_id));
+        }
+    }
+}
+
+/// Put back the protocols deleted last
+pub fn undo(app: &mut BitLoomApp) {
+    if let Some(index) = app.trash.deletions().len().checked_sub(1) {
+        restore(app, index);
+    }
+}
+
+/// Put back a deletion from the trash and select the restored protocol
+pub fn restore(app: &mut BitLoomApp, index: usize) {
+    let result = app
+        .trash
+        .restore(index, &mut app.registry, &mut app.presets);
+    if let Some(protocol_id) = app.report(result) {
+        app.selected_protocol = Some(protocol_id);
+        app.selected_field = None;
+        app.selected_fields.clear();
+    }
+}
@@ -0,0 +1,48 @@
+use bitloom::tr;
+use eframe::egui;
+
+/// Show a view in its own OS window, for putting it on another monitor. Where the backend
+/// supports only one window, the view floats in an egui window instead.
+/// Returns false once the user closes the window.
+pub fn show(
+    ctx: &egui::Context,
+    id: &str,
+    title: &str,
+    size: [f32; 2],
+    mut contents: impl FnMut(&mut egui::Ui),
+) -> bool {
+    let mut open = true;
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of(id),
+        egui::ViewportBuilder::default()
+            .with_title(title)
+            .with_inner_size(size),
+        |ctx, class| {
+            if class == egui::ViewportClass::Embedded {
+                egui::Window::new(title)
+                    .id(egui::Id::new(id))
+                    .open(&mut open)
+                    .default_size(size)
+                    .show(ctx, |ui| contents(ui));
+            } else {
+                egui::CentralPanel::default().show(ctx, |ui| contents(ui));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open = false;
+                }
+            }
+        },
+    );
+    open
+}
+
+/// Button moving a view between the main window and a window of its own
+pub fn toggle(ui: &mut egui::Ui, detached: &mut bool) {
+    let (label, hint) = if *detached {
+        (tr!("detached-dock"), tr!("detached-dock-hint"))
+    } else {
+        (tr!("detached-pop-out"), tr!("detached-pop-out-hint"))
+    };
+    if ui.small_button(label).on_hover_text(hint).clicked() {
+        *detached = !*detached;
+    }
+}
@@ -0,0 +1,84 @@
+use crate::app::BitLoomApp;
+use bitloom::tr;
+use eframe::egui;
+
+/// Generated export content waiting to be saved or copied by the user
+pub struct PendingExport {
+    pub title: String,
+    pub path: String,
+    /// the text to save, or a description of `binary`
+    pub content: String,
+    /// the bytes to save, for exports that are not text
+    pub binary: Option<Vec<u8>>,
+}
+
+impl PendingExport {
+    pub fn new(title: &str, file_name: &str, content: String) -> Self {
+        Self {
+            title: title.to_string(),
+            path: file_name.to_string(),
+            content,
+            binary: None,
+        }
+    }
+
+    /// An export of bytes, which can be saved but not copied
+    pub fn binary(title: &str, file_name: &str, description: String, data: Vec<u8>) -> Self {
+        Self {
+            binary: Some(data),
+            ..Self::new(title, file_name, description)
+        }
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(export) = &mut app.pending_export else {
+        return;
+    };
+
+    let mut open = true;
+    let mut result = None;
+    egui::Window::new(tr!("export-dialog-title", title = export.title.as_str()))
+        .id(egui::Id::new("export_dialog"))
+        .open(&mut open)
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("dialog-path"));
+                ui.text_edit_singleline(&mut export.path);
+                if ui.button(tr!("export-dialog-save")).clicked() {
+                    let data = match &export.binary {
+                        Some(data) => data.as_slice(),
+                        None => export.content.as_bytes(),
+                    };
+                    result = Some(std::fs::write(&export.path, data).map_err(|e| {
+                        tr!(
+                            "export-dialog-write-failed",
+                            path = export.path.as_str(),
+                            error = e.to_string()
+                        )
+                    }));
+                }
+                if ui
+                    .add_enabled(
+                        export.binary.is_none(),
+                        egui::Button::new(tr!("export-dialog-copy")),
+                    )
+                    .clicked()
+                {
+                    ui.ctx().copy_text(export.content.clone());
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::both().show(ui, |ui| {
+                ui.monospace(&export.content);
+            });
+        });
+
+    match result {
+        Some(Ok(())) => app.pending_export = None,
+        Some(Err(e)) => app.error = Some(e),
+        None if !open => app.pending_export = None,
+        None => {}
+    }
+}
@@ -0,0 +1,244 @@
+use crate::ui::external_editor;
+use bitloom::codec::Value;
+use bitloom::script::idents::identifier_spans;
+use bitloom::script::lexer::{TokenKind, tokenize};
+use bitloom::script::{BUILTIN_FUNCTIONS, ScriptEngine};
+use bitloom::tr;
+use eframe::egui::{self, Color32, FontId, TextFormat, text::LayoutJob};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Code editor for a rhai field script, with syntax highlighting, the parse error
+/// underlined in place, and completion of field ids and built-in functions (Tab or click).
+pub fn show(
+    ui: &mut egui::Ui,
+    id_salt: impl Hash,
+    script: &mut String,
+    engine: &ScriptEngine,
+    variables: &[String],
+) {
+    let id = ui.make_persistent_id(id_salt);
+    let error = if script.trim().is_empty() {
+        None
+    } else {
+        engine.check(script).err()
+    };
+    let error_offset = error
+        .as_ref()
+        .and_then(|e| e.position)
+        .map(|(line, column)| byte_offset(script, line, column));
+
+    // completions for the identifier in front of the cursor
+    let cursor = egui::TextEdit::load_state(ui.ctx(), id)
+        .and_then(|state| state.cursor.char_range())
+        .map(|range| range.primary.index);
+    let focused = ui.memory(|m| m.has_focus(id));
+    let (prefix_start, suggestions) = match cursor {
+        Some(cursor) if focused => completions(script, cursor, variables),
+        _ => (0, Vec::new()),
+    };
+
+    let mut accepted = None;
+    if !suggestions.is_empty()
+        && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab))
+    {
+        accepted = Some(suggestions[0].clone());
+    }
+
+    let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+        let mut job = highlight(ui, text.as_str(), error_offset);
+        job.wrap.max_width = wrap_width;
+        ui.fonts_mut(|f| f.layout_job(job))
+    };
+    ui.add(
+        egui::TextEdit::multiline(script)
+            .id(id)
+            .code_editor()
+            .desired_rows(4)
+            .desired_width(f32::INFINITY)
+            .layouter(&mut layouter),
+    );
+
+    ui.horizontal_wrapped(|ui| {
+        for suggestion in &suggestions {
+            if ui.small_button(suggestion).clicked() {
+                accepted = Some(suggestion.clone());
+            }
+        }
+        let name = format!("expression-{:x}", id.value());
+        external_editor::button(ui, id, &name, script);
+    });
+
+    if let (Some(completion), Some(cursor)) = (accepted, cursor) {
+        let start = char_to_byte(script, prefix_start);
+        let end = char_to_byte(script, cursor);
+        script.replace_range(start..end, &completion);
+
+        if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+            let new_cursor = egui::text::CCursor::new(prefix_start + completion.chars().count());
+            state
+                .cursor
+                .set_char_range(Some(egui::text::CCursorRange::one(new_cursor)));
+            state.store(ui.ctx(), id);
+        }
+        ui.memory_mut(|m| m.request_focus(id));
+    }
+
+    if let Some(error) = &error {
+        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+    }
+
+    egui::CollapsingHeader::new(tr!("expr-preview"))
+        .id_salt(id.with("preview"))
+        .show(ui, |ui| {
+            if error.is_none() {
+                preview(ui, id, script, engine, variables);
+            } else {
+                ui.label(tr!("expr-fix-syntax"));
+            }
+        });
+}
+
+/// Evaluate the script with sample values entered for the fields it refers to
+fn preview(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    script: &str,
+    engine: &ScriptEngine,
+    variables: &[String],
+) {
+    let mut referenced: Vec<&str> = identifier_spans(script)
+        .into_iter()
+        .map(|span| &script[span])
+        .filter(|ident| variables.iter().any(|v| v == ident))
+        .collect();
+    referenced.sort();
+    referenced.dedup();
+
+    let samples_id = id.with("preview_samples");
+    let mut samples: HashMap<String, String> =
+        ui.data_mut(|d| d.get_temp(samples_id).unwrap_or_default());
+
+    let mut values = Vec::new();
+    let mut invalid = false;
+    egui::Grid::new(id.with("preview_grid"))
+        .num_columns(2)
+        .show(ui, |ui| {
+            for name in &referenced {
+                ui.label(*name);
+                let text = samples.entry(name.to_string()).or_insert("0".to_string());
+                let parsed = Value::parse_literal(text);
+                let mut edit = egui::TextEdit::singleline(text).desired_width(120.0);
+                if parsed.is_err() {
+                    edit = edit.text_color(ui.visuals().error_fg_color);
+                }
+                ui.add(edit).on_hover_text(tr!("expr-sample-hint"));
+                match parsed {
+                    Ok(value) => values.push((*name, value)),
+                    Err(_) => invalid = true,
+                }
+                ui.end_row();
+            }
+        });
+    ui.data_mut(|d| d.insert_temp(samples_id, samples));
+
+    if invalid {
+        ui.label(tr!("expr-invalid-samples"));
+        return;
+    }
+
+    let vars: Vec<(&str, &Value)> = values.iter().map(|(name, v)| (*name, v)).collect();
+    match engine.eval(script, &vars) {
+        Ok(result) => {
+            ui.horizontal(|ui| {
+                ui.label(tr!("expr-result"));
+                ui.monospace(result.to_string());
+            });
+        }
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+    }
+}
+
+/// Candidates completing the identifier that ends at char index `cursor`,
+/// along with the char index where that identifier starts.
+fn completions(script: &str, cursor: usize, variables: &[String]) -> (usize, Vec<String>) {
+    let before: Vec<char> = script.chars().take(cursor).collect();
+    let start = before
+        .iter()
+        .rposition(|c| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map_or(0, |i| i + 1);
+    let prefix: String = before[start..].iter().collect();
+    if prefix.is_empty() || prefix.starts_with(|c: char| c.is_ascii_digit()) {
+        return (start, Vec::new());
+    }
+
+    let suggestions = variables
+        .iter()
+        .map(String::as_str)
+        .chain(BUILTIN_FUNCTIONS.iter().copied())
+        .filter(|candidate| candidate.starts_with(&prefix) && *candidate != prefix)
+        .take(MAX_SUGGESTIONS)
+        .map(str::to_string)
+        .collect();
+    (start, suggestions)
+}
+
+/// Syntax highlighted layout of a rhai script, underlining the token at `error_offset`
+pub fn highlight(ui: &egui::Ui, script: &str, error_offset: Option<usize>) -> LayoutJob {
+    let font_id = FontId::monospace(egui::TextStyle::Monospace.resolve(ui.style()).size);
+    let dark = ui.visuals().dark_mode;
+    let mut job = LayoutJob::default();
+
+    let tokens = tokenize(script);
+    // the token containing the error, or the last token if the error is at the end
+    let error_token = error_offset.map(|offset| {
+        tokens
+            .iter()
+            .position(|t| t.span.contains(&offset) && t.kind != TokenKind::Whitespace)
+            .unwrap_or(tokens.len().saturating_sub(1))
+    });
+
+    for (i, token) in tokens.iter().enumerate() {
+        let color = match (token.kind, dark) {
+            (TokenKind::Keyword, true) => Color32::from_rgb(198, 120, 221),
+            (TokenKind::Keyword, false) => Color32::from_rgb(152, 0, 152),
+            (TokenKind::Number, true) => Color32::from_rgb(209, 154, 102),
+            (TokenKind::Number, false) => Color32::from_rgb(152, 80, 0),
+            (TokenKind::Str, true) => Color32::from_rgb(152, 195, 121),
+            (TokenKind::Str, false) => Color32::from_rgb(40, 120, 40),
+            (TokenKind::Comment, _) => Color32::GRAY,
+            _ => ui.visuals().text_color(),
+        };
+        let mut format = TextFormat::simple(font_id.clone(), color);
+        if error_token == Some(i) {
+            format.underline = egui::Stroke::new(2.0, ui.visuals().error_fg_color);
+        }
+        job.append(&script[token.span.clone()], 0.0, format);
+    }
+    job
+}
+
+/// Byte offset of a 1-based line and column
+pub fn byte_offset(script: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = script
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line_text = &script[line_start.min(script.len())..];
+    line_start
+        + line_text
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(line_text.len(), |(i, _)| i)
+}
+
+fn char_to_byte(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(i, _)| i)
+}
@@ -0,0 +1,192 @@
+//! Editing a script in the user's own editor: the script is written to a temporary file that is
+//! opened in the editor, and read back whenever the editor saves it.
+
+use bitloom::tr;
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// How often the file is checked for changes while it is open
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A script open in an external editor
+#[derive(Clone)]
+struct ExternalEdit {
+    path: PathBuf,
+    /// when the file was last written, by the editor or by us
+    modified: Option<SystemTime>,
+    /// the script as last written to or read from the file
+    text: String,
+    /// the pass of the UI the script was last shown in
+    shown: u64,
+}
+
+impl ExternalEdit {
+    fn open(name: &str, script: &str) -> Result<Self, String> {
+        let dir = std::env::temp_dir().join(format!("bitloom-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            tr!(
+                "external-editor-create-failed",
+                path = dir.display().to_string(),
+                error = e.to_string()
+            )
+        })?;
+        let mut edit = Self {
+            path: dir.join(format!("{}.rhai", name)),
+            modified: None,
+            text: String::new(),
+            shown: 0,
+        };
+        edit.write(script)?;
+        launch(&edit.path)?;
+        Ok(edit)
+    }
+
+    fn write(&mut self, script: &str) -> Result<(), String> {
+        std::fs::write(&self.path, script).map_err(|e| {
+            tr!(
+                "external-editor-write-failed",
+                path = self.path.display().to_string(),
+                error = e.to_string()
+            )
+        })?;
+        self.modified = modified(&self.path);
+        self.text = script.to_string();
+        Ok(())
+    }
+
+    /// The script in the file, if the editor changed it since it was last read or written
+    fn poll(&mut self) -> Result<Option<String>, String> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return Ok(None);
+        }
+        self.modified = modified;
+        let text = std::fs::read_to_string(&self.path).map_err(|e| {
+            tr!(
+                "external-editor-read-failed",
+                path = self.path.display().to_string(),
+                error = e.to_string()
+            )
+        })?;
+        if text == self.text {
+            return Ok(None);
+        }
+        self.text = text.clone();
+        Ok(Some(text))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Open a file in the editor named by `$VISUAL` or `$EDITOR`, which may have arguments, or
+/// else in the default application for it
+fn launch(path: &Path) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty());
+    let mut command = match editor {
+        Some(editor) => {
+            let mut words = editor.split_whitespace();
+            let mut command = Command::new(words.next().unwrap_or_default());
+            command.args(words);
+            command
+        }
+        None if cfg!(target_os = "windows") => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        None if cfg!(target_os = "macos") => Command::new("open"),
+        None => Command::new("xdg-open"),
+    };
+    let mut child = command
+        .arg(path)
+        .spawn()
+        .map_err(|e| tr!("external-editor-start-failed", error = e.to_string()))?;
+    // wait for the editor in the background so it does not linger once it exits
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Button opening the script in an external editor, as a file named after `name`. While the
+/// script is open, it is reloaded every time the editor saves the file, and changes made to it
+/// here are written to the file. Editing stops when Stop is clicked or the script is no longer
+/// shown. Returns whether the script was reloaded from the file.
+pub fn button(ui: &mut egui::Ui, id: egui::Id, name: &str, script: &mut String) -> bool {
+    let id = id.with("external_edit");
+    let error_id = id.with("error");
+    let pass = ui.ctx().cumulative_pass_nr();
+    let mut edit: Option<ExternalEdit> = ui
+        .data_mut(|d| d.get_temp::<ExternalEdit>(id))
+        .filter(|edit| edit.shown + 1 >= pass);
+    let mut error: Option<String> = ui.data_mut(|d| d.get_temp(error_id));
+    let mut reloaded = false;
+
+    match &mut edit {
+        None => {
+            if ui
+                .button(tr!("external-editor-open"))
+                .on_hover_text(tr!("external-editor-open-hint"))
+                .clicked()
+            {
+                match ExternalEdit::open(name, script) {
+                    Ok(opened) => {
+                        edit = Some(opened);
+                        error = None;
+                    }
+                    Err(e) => error = Some(e),
+                }
+            }
+        }
+        Some(open) => {
+            let result = if *script != open.text {
+                open.write(script).map(|_| None)
+            } else {
+                open.poll()
+            };
+            match result {
+                Ok(Some(text)) => {
+                    *script = text;
+                    reloaded = true;
+                    error = None;
+                }
+                Ok(None) => {}
+                Err(e) => error = Some(e),
+            }
+            let stop = ui
+                .button(tr!("external-editor-stop"))
+                .on_hover_text(tr!("external-editor-stop-hint"))
+                .clicked();
+            ui.weak(tr!("external-editor-editing"))
+                .on_hover_text(open.path.display().to_string());
+            if stop {
+                edit = None;
+            } else {
+                ui.ctx().request_repaint_after(POLL_INTERVAL);
+            }
+        }
+    }
+    if let Some(error) = &error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    ui.data_mut(|d| {
+        match edit {
+            Some(mut edit) => {
+                edit.shown = pass;
+                d.insert_temp(id, edit);
+            }
+            None => d.remove::<ExternalEdit>(id),
+        }
+        match error {
+            Some(error) => d.insert_temp(error_id, error),
+            None => d.remove::<String>(error_id),
+        }
+    });
+    reloaded
+}
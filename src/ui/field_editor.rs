@@ -0,0 +1,700 @@
+use crate::app::BitLoomApp;
+use crate::ui::expr_editor;
+use crate::ui::widgets::{
+    display_format_label, endianness_label, field_type_label, int_input, optional_color,
+    optional_text, severity_label, sign_encoding_label,
+};
+use bitloom::models::field::{
+    CurvePoint, DisplayFormat, EnumVariant, FieldLength, FieldRule, FieldType, MappingEntry,
+    SignEncoding, SubField, ValueMapping, merge_enum_variants,
+};
+use bitloom::models::ident::slugify;
+use bitloom::models::protocol::{Endianness, Severity};
+use bitloom::script::ScriptEngine;
+use bitloom::tr;
+use eframe::egui;
+
+/// A field being edited, applied to the protocol only once it validates
+pub struct FieldEditor {
+    pub protocol_id: String,
+    /// ID of the field being edited, or `None` when adding a new field
+    pub original_id: Option<String>,
+    /// position a new field is inserted at; the end of the protocol when not set
+    pub index: Option<usize>,
+    pub draft: FieldRule,
+    /// numeric inputs that currently hold text which is not a number
+    invalid_inputs: usize,
+}
+
+impl FieldEditor {
+    pub fn edit(protocol_id: &str, field: &FieldRule) -> Self {
+        Self {
+            protocol_id: protocol_id.to_string(),
+            original_id: Some(field.id.clone()),
+            index: None,
+            draft: field.clone(),
+            invalid_inputs: 0,
+        }
+    }
+
+    pub fn add(protocol_id: &str) -> Self {
+        Self {
+            protocol_id: protocol_id.to_string(),
+            original_id: None,
+            index: None,
+            draft: FieldRule::default(),
+            invalid_inputs: 0,
+        }
+    }
+
+    /// Add a new field before the one at `index`
+    pub fn insert(protocol_id: &str, index: usize) -> Self {
+        Self {
+            index: Some(index),
+            ..Self::add(protocol_id)
+        }
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(editor) = &mut app.field_editor else {
+        return;
+    };
+    if app.registry.get_protocol(&editor.protocol_id).is_none() {
+        app.field_editor = None;
+        return;
+    }
+
+    // collect problems before drawing so the Apply button reflects them
+    let mut errors = editor.draft.validate();
+    // the ID must be free in the ancestors and subprotocols too, whose fields share the packet
+    if editor.original_id.as_deref() != Some(editor.draft.id.as_str())
+        && let Some(owner) = app
+            .registry
+            .related_field_owner(&editor.protocol_id, &editor.draft.id)
+    {
+        errors.push(tr!(
+            "field-editor-id-exists",
+            field = editor.draft.id.as_str(),
+            protocol = owner.id.as_str()
+        ));
+    }
+    if let Some(script) = editor.draft.field_type.script()
+        && !script.trim().is_empty()
+        && app.script_engine.check(script).is_err()
+    {
+        // the details are shown under the script editor
+        errors.push(tr!("field-editor-script-error"));
+    }
+    let resolved = app
+        .registry
+        .resolve_fields(&editor.protocol_id)
+        .unwrap_or_default();
+    // fields an expression can refer to
+    let variables: Vec<String> = resolved
+        .iter()
+        .map(|f| f.id.clone())
+        .filter(|id| *id != editor.draft.id)
+        .chain(app.script_engine.library_functions())
+        .collect();
+    // the palette color the field has while it has none of its own
+    let position = resolved
+        .iter()
+        .position(|f| Some(&f.id) == editor.original_id.as_ref())
+        .unwrap_or(resolved.len());
+    let auto_color = app.appearance.palette.color(position);
+    if editor.invalid_inputs > 0 {
+        errors.push(tr!("field-editor-invalid-numbers"));
+    }
+
+    let title = match &editor.original_id {
+        Some(id) => tr!("field-editor-edit-title", field = id.as_str()),
+        None => tr!("field-editor-add-title"),
+    };
+    let mut open = true;
+    let mut apply = false;
+    let mut cancel = false;
+    egui::Window::new(title)
+        .id(egui::Id::new("field_editor"))
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            editor.invalid_inputs = 0;
+            egui::Grid::new("field_editor_common")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(tr!("field-editor-id"));
+                    ui.text_edit_singleline(&mut editor.draft.id);
+                    ui.end_row();
+
+                    ui.label(tr!("field-editor-name"));
+                    optional_text(ui, &mut editor.draft.name, false);
+                    ui.end_row();
+
+                    // an ID to go with a name that cannot be one as it is
+                    if let Some(name) = &editor.draft.name {
+                        let slug = slugify(name);
+                        if slug != *name && slug != editor.draft.id {
+                            ui.label("");
+                            if ui
+                                .small_button(tr!("field-editor-use-id", id = slug.as_str()))
+                                .on_hover_text(tr!("field-editor-use-id-hint"))
+                                .clicked()
+                            {
+                                editor.draft.id = slug;
+                            }
+                            ui.end_row();
+                        }
+                    }
+
+                    ui.label(tr!("field-editor-type"));
+                    type_picker(ui, &mut editor.draft.field_type);
+                    ui.end_row();
+
+                    if !editor.draft.is_virtual() {
+                        ui.label(tr!("field-editor-length"));
+                        length_input(ui, &mut editor.draft.length);
+                        ui.end_row();
+
+                        ui.label(tr!("field-editor-byte-order"));
+                        let endianness = &mut editor.draft.endianness;
+                        egui::ComboBox::from_id_salt("byte_order")
+                            .selected_text(
+                                endianness
+                                    .map_or(tr!("field-editor-protocol-default"), endianness_label),
+                            )
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    endianness,
+                                    None,
+                                    tr!("field-editor-protocol-default"),
+                                );
+                                for option in [Endianness::Big, Endianness::Little] {
+                                    ui.selectable_value(
+                                        endianness,
+                                        Some(option),
+                                        endianness_label(option),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    }
+
+                    if matches!(
+                        editor.draft.field_type,
+                        FieldType::Fixed(_) | FieldType::Enum(_) | FieldType::Range { .. }
+                    ) {
+                        ui.label(tr!("field-editor-out-of-spec"));
+                        let severity = &mut editor.draft.severity;
+                        egui::ComboBox::from_id_salt("field_severity")
+                            .selected_text(severity_label(*severity))
+                            .show_ui(ui, |ui| {
+                                for option in [Severity::Error, Severity::Warning] {
+                                    ui.selectable_value(severity, option, severity_label(option));
+                                }
+                            })
+                            .response
+                            .on_hover_text(tr!("field-editor-out-of-spec-hint"));
+                        ui.end_row();
+                    }
+
+                    if editor.draft.is_signed() && !editor.draft.is_virtual() {
+                        ui.label(tr!("field-editor-negative-values"));
+                        let encoding = &mut editor.draft.sign_encoding;
+                        egui::ComboBox::from_id_salt("sign_encoding")
+                            .selected_text(sign_encoding_label(*encoding))
+                            .show_ui(ui, |ui| {
+                                for option in SignEncoding::ALL {
+                                    ui.selectable_value(
+                                        encoding,
+                                        option,
+                                        sign_encoding_label(option),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    }
+
+                    ui.label(tr!("field-editor-display"));
+                    egui::ComboBox::from_id_salt("display_format")
+                        .selected_text(
+                            editor
+                                .draft
+                                .display
+                                .map_or(tr!("field-editor-display-default"), display_format_label),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut editor.draft.display,
+                                None,
+                                tr!("field-editor-display-default"),
+                            );
+                            for format in DisplayFormat::ALL {
+                                ui.selectable_value(
+                                    &mut editor.draft.display,
+                                    Some(format),
+                                    display_format_label(format),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(tr!("field-editor-color"));
+                    optional_color(ui, &mut editor.draft.color, auto_color);
+                    ui.end_row();
+
+                    ui.label(tr!("field-editor-description"));
+                    optional_text(ui, &mut editor.draft.description, true);
+                    ui.end_row();
+                });
+
+            ui.separator();
+            editor.invalid_inputs += type_specific_inputs(
+                ui,
+                &mut editor.draft.field_type,
+                &app.script_engine,
+                &variables,
+            );
+
+            if let FieldLength::Fixed(bits) = editor.draft.length
+                && !editor.draft.is_virtual()
+            {
+                ui.separator();
+                egui::CollapsingHeader::new(tr!(
+                    "field-editor-subfields",
+                    count = editor.draft.subfields.len()
+                ))
+                .id_salt("field_subfields")
+                .default_open(!editor.draft.subfields.is_empty())
+                .show(ui, |ui| {
+                    subfield_table(ui, &mut editor.draft.subfields, bits)
+                });
+            }
+
+            ui.separator();
+            egui::CollapsingHeader::new(tr!("field-editor-mapping"))
+                .id_salt("field_mapping")
+                .default_open(editor.draft.mapping.is_some())
+                .show(ui, |ui| {
+                    editor.invalid_inputs += mapping_inputs(ui, &mut editor.draft.mapping);
+                });
+
+            ui.separator();
+            for error in &errors {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(errors.is_empty(), egui::Button::new(tr!("dialog-apply")))
+                    .clicked()
+                {
+                    apply = true;
+                }
+                if ui.button(tr!("dialog-cancel")).clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if apply {
+        apply_edit(app);
+    } else if !open || cancel {
+        app.field_editor = None;
+    }
+}
+
+fn apply_edit(app: &mut BitLoomApp) {
+    let Some(editor) = app.field_editor.take() else {
+        return;
+    };
+    let draft = editor.draft.clone();
+
+    let result = match &editor.original_id {
+        Some(original_id) => {
+            // the rename reaches into subprotocols, so undo it as a whole if the edit fails
+            let backup = app.registry.clone();
+            let result = app
+                .registry
+                .rename_field(&editor.protocol_id, original_id, &draft.id)
+                .and_then(|_| {
+                    app.registry.edit_protocol(&editor.protocol_id, |p| {
+                        p.edit_field(&draft.id, |f| {
+                            *f = draft.clone();
+                            Ok(())
+                        })
+                    })
+                });
+            if result.is_err() {
+                app.registry = backup;
+            }
+            result
+        }
+        None => app
+            .registry
+            .edit_protocol(&editor.protocol_id, |p| match editor.index {
+                Some(index) => p.insert_field(index, draft.clone()),
+                None => p.add_field(draft.clone()),
+            }),
+    };
+
+    if app.report(result).is_some() {
+        app.selected_field = Some(draft.id);
+    } else {
+        app.field_editor = Some(editor); // keep the draft so the user can fix it
+    }
+}
+
+fn type_picker(ui: &mut egui::Ui, field_type: &mut FieldType) {
+    let options = [
+        FieldType::Fixed(0),
+        FieldType::Enum(vec![]),
+        FieldType::Range {
+            min: 0,
+            max: 0,
+            is_signed: false,
+        },
+        FieldType::Expr(String::new()),
+        FieldType::Derived(String::new()),
+        FieldType::Input,
+    ];
+
+    egui::ComboBox::from_id_salt("field_type")
+        .selected_text(field_type_label(field_type))
+        .show_ui(ui, |ui| {
+            for option in options {
+                let selected = option.kind_name() == field_type.kind_name();
+                if ui
+                    .selectable_label(selected, field_type_label(&option))
+                    .clicked()
+                    && !selected
+                {
+                    *field_type = option;
+                }
+            }
+        });
+}
+
+fn length_input(ui: &mut egui::Ui, length: &mut FieldLength) {
+    ui.horizontal(|ui| {
+        let mut variable = *length == FieldLength::Variable;
+        if ui
+            .checkbox(&mut variable, tr!("field-editor-variable"))
+            .changed()
+        {
+            *length = if variable {
+                FieldLength::Variable
+            } else {
+                FieldLength::Fixed(8)
+            };
+        }
+        if let FieldLength::Fixed(bits) = length {
+            ui.add(
+                egui::DragValue::new(bits)
+                    .range(1..=u16::MAX as u32)
+                    .suffix(format!(" {}", tr!("field-editor-bits-unit"))),
+            );
+        }
+    });
+}
+
+/// Inputs for the settings of the selected field type.
+/// Returns the number of numeric inputs that do not currently parse.
+fn type_specific_inputs(
+    ui: &mut egui::Ui,
+    field_type: &mut FieldType,
+    engine: &ScriptEngine,
+    variables: &[String],
+) -> usize {
+    let mut invalid = 0;
+    match field_type {
+        FieldType::Fixed(value) => {
+            ui.horizontal(|ui| {
+                ui.label(tr!("field-editor-value"));
+                invalid += !int_input(ui, "fixed_value", value) as usize;
+            });
+        }
+        FieldType::Range {
+            min,
+            max,
+            is_signed,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label(tr!("field-editor-min"));
+                invalid += !int_input(ui, "range_min", min) as usize;
+                ui.label(tr!("field-editor-max"));
+                invalid += !int_input(ui, "range_max", max) as usize;
+                ui.checkbox(is_signed, tr!("field-editor-signed"));
+            });
+        }
+        FieldType::Enum(variants) => {
+            invalid += variant_table(ui, variants);
+        }
+        FieldType::Expr(script) | FieldType::Derived(script) => {
+            ui.label(tr!("field-editor-script"));
+            expr_editor::show(ui, "field_script", script, engine, variables);
+        }
+        FieldType::Input => {
+            ui.label(tr!("field-editor-input-hint"));
+        }
+    }
+    invalid
+}
+
+fn variant_table(ui: &mut egui::Ui, variants: &mut Vec<EnumVariant>) -> usize {
+    let mut invalid = 0;
+    let mut remove = None;
+    let mut swap = None;
+    let count = variants.len();
+    let values: Vec<i128> = variants.iter().map(|v| v.value).collect();
+
+    egui::Grid::new("enum_variants")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong(tr!("field-editor-value"));
+            ui.strong(tr!("field-editor-name"));
+            ui.strong(tr!("field-editor-description"));
+            ui.end_row();
+
+            for (i, variant) in variants.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    invalid += !int_input(ui, ("variant_value", i), &mut variant.value) as usize;
+                    if values.iter().filter(|v| **v == variant.value).count() > 1 {
+                        ui.colored_label(ui.visuals().error_fg_color, "⚠")
+                            .on_hover_text(tr!("field-editor-duplicate-value"));
+                    }
+                });
+                optional_text(ui, &mut variant.name, false);
+                optional_text(ui, &mut variant.description, false);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(i > 0, egui::Button::new("⏶").small())
+                        .clicked()
+                    {
+                        swap = Some((i - 1, i));
+                    }
+                    if ui
+                        .add_enabled(i + 1 < count, egui::Button::new("⏷").small())
+                        .clicked()
+                    {
+                        swap = Some((i, i + 1));
+                    }
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text(tr!("field-editor-remove-variant"))
+                        .clicked()
+                    {
+                        remove = Some(i);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    if let Some((a, b)) = swap {
+        variants.swap(a, b);
+    }
+    if let Some(i) = remove {
+        variants.remove(i);
+    }
+    if ui.button(tr!("field-editor-add-variant")).clicked() {
+        let next = variants.iter().map(|v| v.value + 1).max().unwrap_or(0);
+        variants.push(EnumVariant {
+            value: next,
+            name: None,
+            description: None,
+        });
+    }
+
+    egui::CollapsingHeader::new(tr!("field-editor-import-clipboard")).show(ui, |ui| {
+        import_variants(ui, variants);
+    });
+    invalid
+}
+
+/// Named bit ranges within the value of a field of `bits` bits
+fn subfield_table(ui: &mut egui::Ui, subfields: &mut Vec<SubField>, bits: u32) {
+    let mut remove = None;
+    if !subfields.is_empty() {
+        egui::Grid::new("subfields")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong(tr!("field-editor-id"));
+                ui.strong(tr!("field-editor-name"));
+                ui.strong(tr!("field-editor-lowest-bit"));
+                ui.strong(tr!("field-editor-bits"));
+                ui.end_row();
+
+                for (i, sub) in subfields.iter_mut().enumerate() {
+                    ui.add(egui::TextEdit::singleline(&mut sub.id).desired_width(90.0));
+                    optional_text(ui, &mut sub.name, false);
+                    ui.add(egui::DragValue::new(&mut sub.lsb).range(0..=bits.saturating_sub(1)));
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut sub.bits).range(1..=bits.clamp(1, 64)));
+                        ui.weak(sub.range_label());
+                    });
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text(tr!("field-editor-remove-subfield"))
+                        .clicked()
+                    {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+    if let Some(i) = remove {
+        subfields.remove(i);
+    }
+    ui.weak(tr!("field-editor-subfield-hint"));
+    if ui.button(tr!("field-editor-add-subfield")).clicked() {
+        // above the highest sub-field so far
+        let lsb = subfields.iter().map(|s| s.lsb + s.bits).max().unwrap_or(0);
+        subfields.push(SubField {
+            id: format!("sub_{}", subfields.len() + 1),
+            name: None,
+            description: None,
+            lsb: lsb.min(bits.saturating_sub(1)),
+            bits: 1,
+        });
+    }
+}
+
+/// A table or curve translating raw values for display.
+/// Returns the number of numeric inputs that do not currently parse.
+fn mapping_inputs(ui: &mut egui::Ui, mapping: &mut Option<ValueMapping>) -> usize {
+    let mut invalid = 0;
+    let kind = match mapping {
+        None => "field-editor-mapping-none",
+        Some(ValueMapping::Table(_)) => "field-editor-mapping-table",
+        Some(ValueMapping::Curve { .. }) => "field-editor-mapping-curve",
+    };
+    let (table, curve) = ("field-editor-mapping-table", "field-editor-mapping-curve");
+    egui::ComboBox::from_id_salt("mapping_kind")
+        .selected_text(tr!(kind))
+        .show_ui(ui, |ui| {
+            if ui
+                .selectable_label(mapping.is_none(), tr!("field-editor-mapping-none"))
+                .clicked()
+            {
+                *mapping = None;
+            }
+            if ui.selectable_label(kind == table, tr!(table)).clicked() && kind != table {
+                *mapping = Some(ValueMapping::Table(Vec::new()));
+            }
+            if ui.selectable_label(kind == curve, tr!(curve)).clicked() && kind != curve {
+                *mapping = Some(ValueMapping::Curve {
+                    points: Vec::new(),
+                    unit: None,
+                });
+            }
+        })
+        .response
+        .on_hover_text(tr!("field-editor-mapping-hint"));
+
+    let mut remove = None;
+    match mapping {
+        None => {}
+        Some(ValueMapping::Table(entries)) => {
+            egui::Grid::new("mapping_table")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(tr!("field-editor-raw"));
+                    ui.strong(tr!("field-editor-shown-as"));
+                    ui.end_row();
+                    for (i, entry) in entries.iter_mut().enumerate() {
+                        invalid += !int_input(ui, ("mapping_raw", i), &mut entry.raw) as usize;
+                        ui.text_edit_singleline(&mut entry.shown);
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text(tr!("field-editor-remove-entry"))
+                            .clicked()
+                        {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                entries.remove(i);
+            }
+            if ui.button(tr!("field-editor-add-entry")).clicked() {
+                let raw = entries.iter().map(|e| e.raw + 1).max().unwrap_or(0);
+                entries.push(MappingEntry {
+                    raw,
+                    shown: String::new(),
+                });
+            }
+        }
+        Some(ValueMapping::Curve { points, unit }) => {
+            ui.horizontal(|ui| {
+                ui.label(tr!("field-editor-unit"));
+                optional_text(ui, unit, false);
+            });
+            egui::Grid::new("mapping_curve")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(tr!("field-editor-raw"));
+                    ui.strong(tr!("field-editor-value"));
+                    ui.end_row();
+                    for (i, point) in points.iter_mut().enumerate() {
+                        ui.add(egui::DragValue::new(&mut point.raw));
+                        ui.add(egui::DragValue::new(&mut point.value).speed(0.1));
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text(tr!("field-editor-remove-point"))
+                            .clicked()
+                        {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                points.remove(i);
+            }
+            ui.weak(tr!("field-editor-curve-hint"));
+            if ui.button(tr!("field-editor-add-point")).clicked() {
+                let last = points.last().copied();
+                points.push(CurvePoint {
+                    raw: last.map_or(0.0, |p| p.raw + 1.0),
+                    value: last.map_or(0.0, |p| p.value),
+                });
+            }
+        }
+    }
+    invalid
+}
+
+/// Paste `value name [description]` rows, e.g. copied from a spreadsheet or spec table
+fn import_variants(ui: &mut egui::Ui, variants: &mut Vec<EnumVariant>) {
+    let text_id = ui.make_persistent_id("variant_import_text");
+    let mut text: String = ui.data_mut(|d| d.get_temp(text_id).unwrap_or_default());
+
+    ui.label(tr!("field-editor-import-hint"));
+    ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .code_editor()
+            .desired_rows(4)
+            .hint_text("0x01\tPing\n0x02\tPong"),
+    );
+
+    match EnumVariant::parse_list(&text) {
+        Ok(imported) if !imported.is_empty() => {
+            let label = tr!("field-editor-import-variants", count = imported.len());
+            if ui.button(label).clicked() {
+                merge_enum_variants(variants, imported);
+                text.clear();
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+    }
+    ui.data_mut(|d| d.insert_temp(text_id, text));
+}
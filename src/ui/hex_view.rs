@@ -1,13 +1,772 @@
-use crate::app::BitLoomApp;
-use eframe::egui;
+use crate::app::{BitLoomApp, ViewPage};
+use crate::ui::detached;
+use crate::ui::export_dialog::PendingExport;
+use crate::ui::packet_builder::FLASH_SECONDS;
+use crate::ui::theme::text_color_on;
+use bitloom::analysis::entropy::{OffsetStats, offset_stats};
+use bitloom::codec::compiled::CompiledCodec;
+use bitloom::codec::decode::{DecodeFailure, DecodedPacket};
+use bitloom::codec::hexdump::{BYTES_PER_LINE, format_hex_dump, parse_hex_dump};
+use bitloom::models::bookmark::{Bookmark, bookmarks_of};
+use bitloom::script::ScriptEngine;
+use bitloom::tr;
+use eframe::egui::{self, Color32, TextFormat, text::LayoutJob};
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+/// Decoding packets as if the protocol had the other byte or bit order, to diagnose a
+/// mismatch with a device without editing the protocol
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct OrderPreview {
+    /// read multi-byte fields in the opposite byte order
+    pub swap_bytes: bool,
+    /// read the bits of each byte least significant first
+    pub reverse_bits: bool,
+}
+
+impl OrderPreview {
+    pub fn is_active(self) -> bool {
+        self.swap_bytes || self.reverse_bits
+    }
+
+    /// Decode a packet with a codec of its protocol, in the previewed order
+    pub fn decode(
+        self,
+        mut codec: CompiledCodec,
+        engine: &ScriptEngine,
+        data: &[u8],
+    ) -> Result<DecodedPacket, Box<DecodeFailure>> {
+        if self.swap_bytes {
+            codec.swap_byte_order();
+        }
+        if self.reverse_bits {
+            let reversed: Vec<u8> = data.iter().map(|b| b.reverse_bits()).collect();
+            codec.decode_partial(engine, &reversed)
+        } else {
+            codec.decode_partial(engine, data)
+        }
+    }
+
+    fn description(self) -> String {
+        let mut changes = Vec::new();
+        if self.swap_bytes {
+            changes.push(tr!("hex-preview-swapped"));
+        }
+        if self.reverse_bits {
+            changes.push(tr!("hex-preview-reversed"));
+        }
+        let and = format!(" {} ", tr!("hex-preview-and"));
+        tr!("hex-preview", changes = changes.join(&and))
+    }
+}
+
+/// A packet open in a tab of the hex view
+pub struct PacketTab {
+    pub title: String,
+    /// the bytes of the packet while another tab is active; the active tab's are in
+    /// `BitLoomApp::packet_data`
+    pub data: Vec<u8>,
+}
+
+/// The packets open in the hex view, so a reference packet stays at hand while another one is
+/// built or decoded
+pub struct PacketTabs {
+    pub tabs: Vec<PacketTab>,
+    pub active: usize,
+    /// a packet pinned to compare the viewed ones with: it is shown read-only above them, and
+    /// the bytes they differ from it in are tinted
+    pub reference: Option<PacketTab>,
+    /// whether the bytes differing from the reference are tinted
+    pub tint_changes: bool,
+    /// number of the next new tab's title
+    next: usize,
+}
+
+impl Default for PacketTabs {
+    fn default() -> Self {
+        Self {
+            tabs: vec![PacketTab {
+                title: tr!("hex-packet-tab", number = 1),
+                data: Vec::new(),
+            }],
+            active: 0,
+            reference: None,
+            tint_changes: true,
+            next: 2,
+        }
+    }
+}
+
+impl PacketTabs {
+    fn add(&mut self, data: Vec<u8>) -> usize {
+        self.tabs.push(PacketTab {
+            title: tr!("hex-packet-tab", number = self.next),
+            data,
+        });
+        self.next += 1;
+        self.tabs.len() - 1
+    }
+
+    /// The bytes of a tab, given the bytes of the active one
+    fn data<'a>(&'a self, index: usize, active: &'a [u8]) -> &'a [u8] {
+        if index == self.active {
+            active
+        } else {
+            &self.tabs[index].data
+        }
+    }
+
+    /// Pin a copy of the bytes of a tab as the reference
+    fn pin(&mut self, index: usize, active: &[u8]) {
+        self.reference = Some(PacketTab {
+            title: self.tabs[index].title.clone(),
+            data: self.data(index, active).to_vec(),
+        });
+    }
+
+    /// Which bytes of a packet differ from the reference, including those past its end
+    pub fn changes(&self, data: &[u8]) -> Option<Vec<bool>> {
+        let reference = &self.reference.as_ref()?.data;
+        Some(
+            data.iter()
+                .enumerate()
+                .map(|(i, byte)| reference.get(i) != Some(byte))
+                .collect(),
+        )
+    }
+}
+
+/// Make another tab the active one, keeping the packet of the active one in its tab
+fn switch_tab(app: &mut BitLoomApp, index: usize) {
+    let tabs = &mut app.packet_tabs;
+    if index == tabs.active || index >= tabs.tabs.len() {
+        return;
+    }
+    tabs.tabs[tabs.active].data = std::mem::take(&mut app.packet_data);
+    app.packet_data = std::mem::take(&mut tabs.tabs[index].data);
+    tabs.active = index;
+    app.hex_selection = None;
+    app.hex_cursor = None;
+    app.decoded = None;
+    app.decode_error = None;
+    if !app.packet_data.is_empty() {
+        app.decode_packet();
+    }
+}
+
+fn close_tab(app: &mut BitLoomApp, index: usize) {
+    if app.packet_tabs.tabs.len() < 2 {
+        return;
+    }
+    if index == app.packet_tabs.active {
+        switch_tab(app, if index == 0 { 1 } else { index - 1 });
+    }
+    let tabs = &mut app.packet_tabs;
+    tabs.tabs.remove(index);
+    if tabs.active > index {
+        tabs.active -= 1;
+    }
+}
+
+/// The tabs of the packets open in the hex view, with buttons to open new ones
+fn tab_bar(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let mut switch = None;
+    let mut close = None;
+    let mut new = None;
+    ui.horizontal_wrapped(|ui| {
+        let tabs = &mut app.packet_tabs;
+        let count = tabs.tabs.len();
+        for index in 0..count {
+            let len = tabs.data(index, &app.packet_data).len();
+            let title = tabs.tabs[index].title.clone();
+            let response = ui
+                .selectable_label(index == tabs.active, title)
+                .on_hover_text(tr!("hex-tab-hint", bytes = len));
+            if response.clicked() {
+                switch = Some(index);
+            }
+            response.context_menu(|ui| {
+                ui.label(tr!("hex-tab-title"));
+                ui.text_edit_singleline(&mut tabs.tabs[index].title);
+                ui.separator();
+                if ui
+                    .button(tr!("hex-pin"))
+                    .on_hover_text(tr!("hex-pin-hint"))
+                    .clicked()
+                {
+                    tabs.pin(index, &app.packet_data);
+                    ui.close();
+                }
+                if ui.button(tr!("hex-duplicate")).clicked() {
+                    new = Some(tabs.data(index, &app.packet_data).to_vec());
+                    ui.close();
+                }
+                if ui
+                    .add_enabled(count > 1, egui::Button::new(tr!("hex-close")))
+                    .clicked()
+                {
+                    close = Some(index);
+                    ui.close();
+                }
+            });
+            if count > 1
+                && ui
+                    .small_button("×")
+                    .on_hover_text(tr!("hex-close"))
+                    .clicked()
+            {
+                close = Some(index);
+            }
+        }
+        if ui
+            .small_button("+")
+            .on_hover_text(tr!("hex-new-tab-hint"))
+            .clicked()
+        {
+            new = Some(Vec::new());
+        }
+    });
+    if let Some(data) = new {
+        let index = app.packet_tabs.add(data);
+        switch_tab(app, index);
+    } else if let Some(index) = close {
+        close_tab(app, index);
+    } else if let Some(index) = switch {
+        switch_tab(app, index);
+    }
+}
+
+/// Width of the bar of each offset in the entropy strip
+const STRIP_CELL_WIDTH: f32 = 6.0;
+const STRIP_HEIGHT: f32 = 36.0;
+
+/// Statistics of the bytes at each offset of the captured packets
+fn capture_offset_stats(app: &BitLoomApp) -> Vec<OffsetStats> {
+    let packets: Vec<&[u8]> = app
+        .capture
+        .rows
+        .iter()
+        .map(|row| row.packet.data.as_slice())
+        .collect();
+    offset_stats(&packets)
+}
+
+/// Bars of how much the bytes at each offset of the captured packets vary, from constant to
+/// as random as the number of packets allows, with the byte under the pointer in the hex view
+/// marked. Clicking a bar selects the byte at its offset.
+fn entropy_strip(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let Some(stats) = &app.offset_stats else {
+        return;
+    };
+    let mut refresh = false;
+    let mut close = false;
+    ui.horizontal(|ui| {
+        ui.label(tr!(
+            "hex-entropy-title",
+            count = stats.first().map_or(0, |s| s.packets)
+        ));
+        ui.weak(tr!("hex-entropy-legend"));
+        refresh = ui
+            .small_button("⟳")
+            .on_hover_text(tr!("hex-entropy-refresh"))
+            .clicked();
+        close = ui
+            .small_button("✖")
+            .on_hover_text(tr!("hex-close"))
+            .clicked();
+    });
+
+    let mut clicked = None;
+    egui::ScrollArea::horizontal()
+        .id_salt("entropy_strip")
+        .show(ui, |ui| {
+            let size = egui::vec2(stats.len() as f32 * STRIP_CELL_WIDTH, STRIP_HEIGHT);
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals();
+            painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+            let cell = |offset: usize, height: f32| {
+                let x = rect.left() + offset as f32 * STRIP_CELL_WIDTH;
+                egui::Rect::from_min_max(
+                    egui::pos2(x, rect.bottom() - height),
+                    egui::pos2(x + STRIP_CELL_WIDTH - 1.0, rect.bottom()),
+                )
+            };
+            for (offset, s) in stats.iter().enumerate() {
+                let spread = s.spread() as f32;
+                let color = visuals
+                    .weak_text_color()
+                    .lerp_to_gamma(visuals.warn_fg_color, spread);
+                painter.rect_filled(cell(offset, (spread * STRIP_HEIGHT).max(1.0)), 0.0, color);
+            }
+            if let Some(byte) = app.hex_cursor.filter(|b| *b < stats.len()) {
+                painter.rect_stroke(
+                    cell(byte, STRIP_HEIGHT),
+                    0.0,
+                    visuals.selection.stroke,
+                    egui::StrokeKind::Inside,
+                );
+            }
+
+            let offset_at = |pos: egui::Pos2| ((pos.x - rect.left()) / STRIP_CELL_WIDTH) as usize;
+            if let Some(pos) = response.hover_pos()
+                && let Some(s) = stats.get(offset_at(pos))
+            {
+                let (byte, count) = s.most_common;
+                let lines = [
+                    tr!(
+                        "hex-strip-entropy",
+                        byte = offset_at(pos),
+                        entropy = format!("{:.2}", s.entropy),
+                        distinct = s.distinct
+                    ),
+                    tr!(
+                        "hex-strip-most-common",
+                        value = format!("0x{:02x}", byte),
+                        count = count,
+                        packets = s.packets
+                    ),
+                    tr!(
+                        "hex-strip-mean",
+                        mean = format!("{:.1}", s.mean),
+                        deviation = format!("{:.1}", s.variance.sqrt())
+                    ),
+                ];
+                response.clone().on_hover_text_at_pointer(lines.join("\n"));
+            }
+            if response.clicked() {
+                clicked = response.interact_pointer_pos().map(offset_at);
+            }
+        });
+
+    if let Some(offset) = clicked.filter(|o| *o < app.packet_data.len()) {
+        app.hex_selection = Some((offset, offset));
+        app.hex_scroll_to = Some(offset);
+    }
+    if refresh {
+        app.offset_stats = Some(capture_offset_stats(app));
+    }
+    if close {
+        app.offset_stats = None;
+    }
+}
+
+/// The pinned reference packet, read-only, with how the viewed packet differs from it
+fn reference(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let tabs = &mut app.packet_tabs;
+    let Some(reference) = &tabs.reference else {
+        return;
+    };
+    let changed = tabs
+        .changes(&app.packet_data)
+        .map_or(0, |c| c.iter().filter(|c| **c).count());
+    let summary = match (changed, app.packet_data.len().cmp(&reference.data.len())) {
+        (0, Ordering::Equal) => tr!("hex-reference-equal"),
+        (_, Ordering::Less) => tr!(
+            "hex-reference-shorter",
+            changed = changed,
+            shorter = reference.data.len() - app.packet_data.len()
+        ),
+        _ => tr!("hex-reference-differ", changed = changed),
+    };
+    let mut unpin = false;
+    egui::CollapsingHeader::new(tr!(
+        "hex-reference",
+        title = reference.title.as_str(),
+        bytes = reference.data.len()
+    ))
+    .id_salt("hex_reference")
+    .default_open(true)
+    .show(ui, |ui| {
+        if reference.data.is_empty() {
+            ui.weak(tr!("hex-empty-packet"));
+        } else {
+            ui.label(
+                egui::RichText::new(format_hex_dump(std::slice::from_ref(&reference.data)))
+                    .monospace()
+                    .weak(),
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.label(summary);
+            ui.checkbox(&mut tabs.tint_changes, tr!("hex-tint-changes"));
+            if ui.small_button(tr!("hex-unpin")).clicked() {
+                unpin = true;
+            }
+        });
+    });
+    if unpin {
+        tabs.reference = None;
+    }
+    ui.separator();
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    if app.hex_view_detached {
+        let open = detached::show(ctx, "hex_view", &tr!("hex-title"), [640.0, 320.0], |ui| {
+            contents(app, ui)
+        });
+        if !open {
+            app.hex_view_detached = false;
+        }
+        return;
+    }
 
-pub fn show(_app: &mut BitLoomApp, ctx: &egui::Context) {
     egui::TopBottomPanel::bottom("hex_view")
         .resizable(true)
         .default_height(200.0)
         .show(ctx, |ui| {
             ui.take_available_height();
+            contents(app, ui);
+        });
+}
+
+fn contents(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.label(tr!("hex-title"));
+        detached::toggle(ui, &mut app.hex_view_detached);
+        ui.separator();
+        let can_decode = app.selected_protocol.is_some() && !app.packet_data.is_empty();
+        if ui
+            .add_enabled(can_decode, egui::Button::new(tr!("hex-decode")))
+            .on_hover_text(tr!("hex-decode-hint"))
+            .clicked()
+        {
+            app.decode_packet();
+        }
+        let preview = app.order_preview;
+        ui.menu_button(tr!("hex-preview-order"), |ui| {
+            ui.checkbox(&mut app.order_preview.swap_bytes, tr!("hex-swap-bytes"))
+                .on_hover_text(tr!("hex-swap-bytes-hint"));
+            ui.checkbox(&mut app.order_preview.reverse_bits, tr!("hex-reverse-bits"))
+                .on_hover_text(tr!("hex-reverse-bits-hint"));
+        });
+        if app.order_preview != preview && can_decode {
+            app.decode_packet();
+        }
+        ui.add_enabled_ui(!app.packet_data.is_empty(), |ui| {
+            if ui.button(tr!("hex-copy-dump")).clicked() {
+                ui.ctx()
+                    .copy_text(format_hex_dump(std::slice::from_ref(&app.packet_data)));
+            }
+            if ui.button(tr!("hex-export-dump")).clicked() {
+                app.pending_export = Some(PendingExport::new(
+                    &tr!("hex-export-dump-title"),
+                    "packet.txt",
+                    format_hex_dump(std::slice::from_ref(&app.packet_data)),
+                ));
+            }
+        });
+        if ui
+            .add_enabled(
+                !app.packet_data.is_empty(),
+                egui::Button::new(tr!("hex-pin")),
+            )
+            .on_hover_text(tr!("hex-pin-hint"))
+            .clicked()
+        {
+            let tabs = &mut app.packet_tabs;
+            tabs.pin(tabs.active, &app.packet_data);
+        }
+        let entropy = app.offset_stats.is_some();
+        if ui
+            .add_enabled(
+                app.capture.rows.len() >= 2,
+                egui::Button::selectable(entropy, tr!("hex-entropy")),
+            )
+            .on_hover_text(tr!("hex-entropy-hint"))
+            .clicked()
+        {
+            app.offset_stats = (!entropy).then(|| capture_offset_stats(app));
+        }
+        let selected = selected_bytes(app);
+        if ui
+            .add_enabled(selected.is_some(), egui::Button::new(tr!("hex-bookmark")))
+            .on_hover_text(tr!("hex-bookmark-hint"))
+            .clicked()
+            && let Some(selected) = selected
+        {
+            app.pending_bookmark = Some(Bookmark {
+                name: String::new(),
+                comment: String::new(),
+                bit_offset: selected.start() * 8,
+                bit_len: selected.count() * 8,
+            });
+        }
+    });
+    if app.order_preview.is_active() {
+        ui.horizontal(|ui| {
+            ui.colored_label(ui.visuals().warn_fg_color, app.order_preview.description());
+            if ui.small_button(tr!("hex-stop-preview")).clicked() {
+                app.order_preview = OrderPreview::default();
+                app.decode_packet();
+            }
+        });
+    }
+    if let Some(failure) = &app.decode_error {
+        ui.colored_label(ui.visuals().error_fg_color, &failure.message);
+        let decoded = (failure.bit_offset + failure.bit_len).div_ceil(8);
+        if decoded < app.packet_data.len() {
+            ui.weak(tr!(
+                "hex-not-decoded",
+                bytes = app.packet_data.len() - decoded,
+                offset = decoded
+            ));
+        }
+    }
+    ui.separator();
+    tab_bar(app, ui);
 
-            ui.label("Hex View");
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        reference(app, ui);
+        entropy_strip(app, ui);
+        egui::CollapsingHeader::new(tr!("hex-import")).show(ui, |ui| {
+            import_hex_dump(app, ui);
         });
+        if app.packet_data.is_empty() {
+            ui.label(tr!("hex-no-packet"));
+        } else {
+            dump(app, ui);
+        }
+    });
+}
+
+/// Bytes selected in the hex view, in order and within the packet
+pub fn selected_bytes(app: &BitLoomApp) -> Option<RangeInclusive<usize>> {
+    let (anchor, end) = app.hex_selection?;
+    let last = app.packet_data.len().checked_sub(1)?;
+    Some(anchor.min(end).min(last)..=anchor.max(end).min(last))
+}
+
+/// The highlighted hex dump, tracking the byte under the pointer and the bytes dragged over
+fn dump(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let (job, bytes) = highlighted_dump(app, ui);
+    let galley = ui.fonts_mut(|f| f.layout_job(job));
+    let (rect, response) = ui.allocate_exact_size(galley.size(), egui::Sense::click_and_drag());
+    ui.painter()
+        .galley(rect.min, galley.clone(), ui.visuals().text_color());
+
+    let byte_at = |pos: egui::Pos2| {
+        let index = galley.cursor_from_pos(pos - rect.min).index;
+        bytes.get(index).copied().flatten()
+    };
+    app.hex_cursor = response.hover_pos().and_then(byte_at);
+    if let Some(byte) = app.hex_cursor {
+        let bookmarks: Vec<&Bookmark> = bookmarks_of(&app.bookmarks, &app.packet_data)
+            .iter()
+            .filter(|b| b.byte_range().contains(&byte))
+            .collect();
+        if !bookmarks.is_empty() {
+            response.clone().on_hover_ui_at_pointer(|ui| {
+                for bookmark in bookmarks {
+                    ui.strong(&bookmark.name);
+                    if !bookmark.comment.is_empty() {
+                        ui.label(&bookmark.comment);
+                    }
+                }
+            });
+        }
+    }
+    if let Some(byte) = app.hex_scroll_to.take()
+        && let Some(index) = bytes.iter().position(|b| *b == Some(byte))
+    {
+        let cursor = galley.pos_from_cursor(egui::text::CCursor::new(index));
+        ui.scroll_to_rect(
+            cursor.translate(rect.min.to_vec2()),
+            Some(egui::Align::Center),
+        );
+    }
+    let pointer = response.interact_pointer_pos().and_then(byte_at);
+    if response.drag_started() || response.clicked() {
+        app.hex_selection = pointer.map(|byte| (byte, byte));
+    } else if response.dragged()
+        && let (Some((_, end)), Some(byte)) = (&mut app.hex_selection, pointer)
+    {
+        *end = byte;
+    }
+}
+
+/// Hex dump of the packet with the bytes of each decoded field on the field's color, and the
+/// byte each character of it shows. When decoding failed, the bytes decoded before the failure
+/// are colored the same way, followed by the failed field and the undecoded rest.
+fn highlighted_dump(app: &BitLoomApp, ui: &egui::Ui) -> (LayoutJob, Vec<Option<usize>>) {
+    let mut backgrounds = vec![Color32::TRANSPARENT; app.packet_data.len()];
+    let failure = app.decode_error.as_ref();
+    if let Some(packet) = app.decoded.as_ref().or(failure.map(|f| &f.packet)) {
+        let colors = app
+            .appearance
+            .field_colors(&app.registry, &packet.protocol_id);
+        for field in packet.fields.iter().filter(|f| !f.is_virtual) {
+            let Some(&color) = colors.get(&field.rule_id) else {
+                continue;
+            };
+            let start = field.bit_offset / 8;
+            let end = (field.bit_offset + field.bit_len).div_ceil(8);
+            // a byte shared by several fields takes the color of the first
+            for background in backgrounds.iter_mut().take(end).skip(start) {
+                if *background == Color32::TRANSPARENT {
+                    *background = color;
+                }
+            }
+        }
+    }
+
+    // the bytes of fields whose validation is overridden in the packet builder stand out
+    if app.current_page == ViewPage::PacketBuilder
+        && let Some(packet) = &app.decoded
+    {
+        let warn = ui.visuals().warn_fg_color;
+        for field in packet
+            .fields
+            .iter()
+            .filter(|f| !f.is_virtual && app.builder.ignore_rules.contains(&f.rule_id))
+        {
+            let start = field.bit_offset / 8;
+            let end = (field.bit_offset + field.bit_len).div_ceil(8);
+            for background in backgrounds.iter_mut().take(end).skip(start) {
+                *background = warn;
+            }
+        }
+    }
+
+    // bytes differing from the pinned reference are tinted
+    if app.packet_tabs.tint_changes
+        && let Some(changes) = app.packet_tabs.changes(&app.packet_data)
+    {
+        let tint = ui.visuals().warn_fg_color;
+        for (background, _) in backgrounds.iter_mut().zip(changes).filter(|(_, c)| *c) {
+            *background = if *background == Color32::TRANSPARENT {
+                tint
+            } else {
+                background.lerp_to_gamma(tint, 0.6)
+            };
+        }
+    }
+
+    if let Some(failure) = failure {
+        let start = failure.bit_offset / 8;
+        let end = (failure.bit_offset + failure.bit_len).div_ceil(8);
+        let failed = ui.visuals().error_fg_color;
+        let undecoded = ui.visuals().widgets.inactive.bg_fill;
+        for (i, background) in backgrounds.iter_mut().enumerate().skip(start) {
+            *background = if i < end { failed } else { undecoded };
+        }
+    }
+
+    // the bytes of the field just edited in the packet builder fade from the accent color
+    if let (Some(packet), Some((field_id, time))) = (&app.decoded, &app.builder.flash) {
+        let elapsed = ui.input(|i| i.time) - time;
+        if elapsed < FLASH_SECONDS {
+            let accent = ui.visuals().selection.bg_fill;
+            let t = (elapsed / FLASH_SECONDS) as f32;
+            for field in packet.fields.iter().filter(|f| f.rule_id == *field_id) {
+                let start = field.bit_offset / 8;
+                let end = (field.bit_offset + field.bit_len).div_ceil(8);
+                for background in backgrounds.iter_mut().take(end).skip(start) {
+                    *background = accent.lerp_to_gamma(*background, t);
+                }
+            }
+            ui.ctx().request_repaint();
+        }
+    }
+
+    if let Some(selected) = selected_bytes(app) {
+        let accent = ui.visuals().selection.bg_fill;
+        for background in &mut backgrounds[selected] {
+            *background = accent;
+        }
+    }
+
+    // bookmarked bytes are underlined
+    let mut bookmarked = vec![false; app.packet_data.len()];
+    for bookmark in bookmarks_of(&app.bookmarks, &app.packet_data) {
+        let bytes = bookmark.byte_range();
+        for byte in bookmarked.iter_mut().take(bytes.end).skip(bytes.start) {
+            *byte = true;
+        }
+    }
+
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let mut job = LayoutJob::default();
+    let mut bytes = Vec::new();
+    let mut append = |text: &str, background: Color32, byte: Option<usize>| {
+        let color = if background == Color32::TRANSPARENT {
+            text_color
+        } else {
+            text_color_on(background)
+        };
+        let underline = match byte {
+            Some(byte) if bookmarked[byte] && text != " " => egui::Stroke::new(1.0, color),
+            _ => egui::Stroke::NONE,
+        };
+        bytes.extend(std::iter::repeat_n(byte, text.chars().count()));
+        job.append(
+            text,
+            0.0,
+            TextFormat {
+                font_id: font_id.clone(),
+                color,
+                background,
+                underline,
+                ..Default::default()
+            },
+        );
+    };
+    for (line, chunk) in app.packet_data.chunks(BYTES_PER_LINE).enumerate() {
+        let offset = line * BYTES_PER_LINE;
+        append(&format!("{:04x}  ", offset), Color32::TRANSPARENT, None);
+        for (i, byte) in chunk.iter().enumerate() {
+            let index = offset + i;
+            append(&format!("{:02x}", byte), backgrounds[index], Some(index));
+            // the gap is colored too where it lies within a field
+            let next = backgrounds.get(index + 1);
+            let gap = match next {
+                Some(&next) if i + 1 < chunk.len() && next == backgrounds[index] => next,
+                _ => Color32::TRANSPARENT,
+            };
+            append(" ", gap, Some(index));
+        }
+        let padding = (BYTES_PER_LINE - chunk.len()) * 3;
+        append(&format!("{:padding$}  ", ""), Color32::TRANSPARENT, None);
+        for (i, &byte) in chunk.iter().enumerate() {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            append(&c.to_string(), backgrounds[offset + i], Some(offset + i));
+        }
+        append("\n", Color32::TRANSPARENT, None);
+    }
+    (job, bytes)
+}
+
+/// Load a packet from pasted Wireshark or text2pcap hex dump text
+fn import_hex_dump(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    ui.add(
+        egui::TextEdit::multiline(&mut app.hex_dump_input)
+            .font(egui::TextStyle::Monospace)
+            .desired_rows(4)
+            .hint_text("0000   45 00 00 3c 1c 46 40 00   E..<.F@."),
+    );
+
+    match parse_hex_dump(&app.hex_dump_input) {
+        Ok(packets) if !packets.is_empty() => {
+            let label = if packets.len() == 1 {
+                tr!("hex-load-bytes", bytes = packets[0].len())
+            } else {
+                tr!("hex-load-first", count = packets.len())
+            };
+            if ui.button(label).clicked() {
+                app.packet_data = packets.into_iter().next().unwrap_or_default();
+                app.hex_dump_input.clear();
+                app.decode_packet();
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+    }
 }
@@ -0,0 +1,72 @@
+use crate::app::BitLoomApp;
+use bitloom::models::diff::diff_protocols;
+use bitloom::tr;
+use eframe::egui;
+
+/// Commit, browse and restore revisions of the selected protocol
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_history;
+    egui::Window::new(tr!("history-title"))
+        .id(egui::Id::new("history"))
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let Some(current) = app
+                .selected_protocol
+                .as_deref()
+                .and_then(|id| app.registry.get_protocol(id))
+                .cloned()
+            else {
+                ui.label(tr!("history-select-protocol"));
+                return;
+            };
+
+            let message_id = ui.id().with("commit_message");
+            let mut message: String = ui.data_mut(|d| d.get_temp(message_id).unwrap_or_default());
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut message)
+                    .on_hover_text(tr!("history-message"));
+                if ui.button(tr!("history-commit")).clicked() {
+                    let result = app.history.commit(&current, &message);
+                    if app.report(result).is_some() {
+                        message.clear();
+                    }
+                }
+            });
+            ui.data_mut(|d| d.insert_temp(message_id, message));
+            ui.separator();
+
+            let revisions = app.history.revisions_of(&current.id);
+            if revisions.is_empty() {
+                ui.label(tr!("history-none"));
+                return;
+            }
+
+            let mut restore = None;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (i, revision) in revisions.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(revision.formatted_time());
+                        ui.strong(&revision.message);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button(tr!("history-restore")).clicked() {
+                                restore = Some(revision.snapshot.clone());
+                            }
+                        });
+                    });
+                    egui::CollapsingHeader::new(tr!("history-diff"))
+                        .id_salt(("revision_diff", i))
+                        .show(ui, |ui| {
+                            ui.monospace(diff_protocols(&revision.snapshot, &current).to_report());
+                        });
+                    ui.separator();
+                }
+            });
+
+            if let Some(snapshot) = restore {
+                let result = app.registry.restore_protocol(&snapshot);
+                app.report(result);
+            }
+        });
+    app.show_history = open;
+}
@@ -0,0 +1,113 @@
+use crate::app::BitLoomApp;
+use bitloom::import::c_header::import_c_header;
+use bitloom::import::dbc::import_dbc;
+use bitloom::models::protocol::Protocol;
+use bitloom::tr;
+use eframe::egui;
+
+/// File formats protocols can be imported from
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImportFormat {
+    Dbc,
+    CHeader,
+}
+
+impl ImportFormat {
+    pub fn label(&self) -> String {
+        match self {
+            ImportFormat::Dbc => tr!("import-dbc"),
+            ImportFormat::CHeader => tr!("import-c-header"),
+        }
+    }
+
+    /// Parse a file into protocols, with warnings about what was left out
+    fn parse(&self, text: &str) -> Result<(Vec<Protocol>, Vec<String>), String> {
+        match self {
+            ImportFormat::Dbc => import_dbc(text).map(|import| (import.protocols, import.warnings)),
+            ImportFormat::CHeader => {
+                import_c_header(text).map(|import| (import.protocols, import.warnings))
+            }
+        }
+    }
+}
+
+/// An import the user is choosing a file for
+pub struct PendingImport {
+    pub format: ImportFormat,
+    pub path: String,
+    /// what the last import did
+    pub report: Vec<String>,
+}
+
+impl PendingImport {
+    pub fn new(format: ImportFormat) -> Self {
+        Self {
+            format,
+            path: String::new(),
+            report: Vec::new(),
+        }
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(import) = &mut app.pending_import else {
+        return;
+    };
+
+    let mut open = true;
+    let mut clicked = false;
+    egui::Window::new(tr!("import-title", format = import.format.label()))
+        .id(egui::Id::new("import_dialog"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("dialog-path"));
+                ui.text_edit_singleline(&mut import.path);
+                clicked = ui.button(tr!("import-button")).clicked();
+            });
+            for line in &import.report {
+                ui.label(line);
+            }
+        });
+
+    if clicked {
+        let result = run_import(app);
+        if let Some(report) = app.report(result)
+            && let Some(import) = &mut app.pending_import
+        {
+            import.report = report;
+        }
+    }
+    if !open {
+        app.pending_import = None;
+    }
+}
+
+/// Read the chosen file and add its protocols to the registry
+fn run_import(app: &mut BitLoomApp) -> Result<Vec<String>, String> {
+    let Some(import) = &app.pending_import else {
+        return Ok(Vec::new());
+    };
+    let text = std::fs::read_to_string(&import.path).map_err(|e| {
+        tr!(
+            "import-read-failed",
+            path = import.path.as_str(),
+            error = e.to_string()
+        )
+    })?;
+    let (protocols, warnings) = import.format.parse(&text)?;
+
+    let first = protocols.first().map(|p| p.id.clone());
+    let count = protocols.len();
+    app.registry.add_protocols(protocols)?;
+    if first.is_some() {
+        app.selected_protocol = first;
+        app.selected_field = None;
+        app.selected_fields.clear();
+    }
+
+    let mut report = vec![tr!("import-done", count = count)];
+    report.extend(warnings);
+    Ok(report)
+}
@@ -1,21 +1,411 @@
 use crate::app::BitLoomApp;
+use crate::ui::detached;
+use crate::ui::widgets::{color_swatch, display_format_label, display_format_picker};
+use bitloom::codec::Value;
+use bitloom::codec::decode::ValidationIssue;
+use bitloom::codec::dispatch::{DispatchStep, explain_dispatch};
+use bitloom::codec::encode::patch_field;
+use bitloom::models::field::{DisplayFormat, FieldType};
+use bitloom::models::protocol::Severity;
+use bitloom::script::plugins::Plugin;
+use bitloom::tr;
 use eframe::egui;
+use egui_commonmark::CommonMarkViewer;
+use std::collections::HashMap;
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    if app.inspector_detached {
+        let open = detached::show(
+            ctx,
+            "inspector",
+            &tr!("inspector-title"),
+            [360.0, 560.0],
+            |ui| contents(app, ui),
+        );
+        if !open {
+            app.inspector_detached = false;
+        }
+        return;
+    }
 
-pub fn show(_app: &mut BitLoomApp, ctx: &egui::Context) {
     egui::SidePanel::right("inspector")
         .resizable(true)
         .default_width(200.0)
         .show(ctx, |ui| {
             ui.take_available_width();
+            contents(app, ui);
+        });
+}
+
+fn contents(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.add_space(4.0); // left margin
+        ui.strong(tr!("inspector-title"));
+        detached::toggle(ui, &mut app.inspector_detached);
+        display_format_picker(ui, "inspector_display", &mut app.appearance.display);
+    });
+
+    ui.separator();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        show_selection(app, ui);
+        ui.separator();
+        show_packet(app, ui);
+    });
+}
+
+/// Descriptions of the selected protocol and field, rendered as Markdown
+fn show_selection(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let Some(proto) = app
+        .selected_protocol
+        .as_deref()
+        .and_then(|id| app.registry.get_protocol(id))
+    else {
+        ui.label(tr!("status-no-protocol"));
+        return;
+    };
+
+    ui.strong(proto.name.as_deref().unwrap_or(&proto.id));
+    if let Some(description) = &proto.description {
+        CommonMarkViewer::new().show(ui, &mut app.markdown_cache, description);
+    }
+
+    let Some(field) = app
+        .selected_field
+        .as_deref()
+        .and_then(|id| proto.fields.iter().find(|f| f.id == id))
+    else {
+        return;
+    };
+
+    ui.separator();
+    ui.strong(field.name.as_deref().unwrap_or(&field.id));
+    if let Some(description) = &field.description {
+        CommonMarkViewer::new().show(ui, &mut app.markdown_cache, description);
+    }
+
+    if let FieldType::Enum(variants) = &field.field_type {
+        for variant in variants {
+            ui.label(format!(
+                "{} = {}",
+                variant.value,
+                variant.name.as_deref().unwrap_or("")
+            ));
+            if let Some(description) = &variant.description {
+                ui.indent(("variant_description", variant.value), |ui| {
+                    CommonMarkViewer::new().show(ui, &mut app.markdown_cache, description);
+                });
+            }
+        }
+    }
+}
 
-            ui.horizontal(|ui| {
-                ui.add_space(4.0); // left margin
-                ui.strong("Inspector");
-            });
+fn show_packet(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    // a packet that failed to decode shows the fields before the failure
+    let failure = app.decode_error.as_ref();
+    let Some(packet) = app.decoded.as_ref().or(failure.map(|f| &f.packet)) else {
+        ui.label(tr!("inspector-no-packet"));
+        return;
+    };
 
-            ui.separator();
+    for issue in &packet.issues {
+        issue_badge(ui, issue);
+    }
 
-            // TODO: protocol list
-            ui.label("Field 1: Value");
+    let colors = app
+        .appearance
+        .field_colors(&app.registry, &packet.protocol_id);
+    let formats = app.display_formats(&packet.protocol_id);
+    // a sub-field set to a new value: the rule, byte order and offset of its field, and the
+    // new value of the field
+    let mut patch = None;
+    // fields come in the order of the inheritance chain, root first
+    for group in packet
+        .fields
+        .chunk_by(|a, b| a.protocol_id == b.protocol_id)
+    {
+        let protocol_id = &group[0].protocol_id;
+        egui::CollapsingHeader::new(tr!(
+            "inspector-fields",
+            protocol = protocol_id.as_str(),
+            count = group.len()
+        ))
+        .id_salt(("inspector_group", protocol_id))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new(("inspector_fields", protocol_id))
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for field in group {
+                        let rule = app.registry.get_protocol(&field.protocol_id).and_then(|p| {
+                            Some((p, p.fields.iter().find(|f| f.id == field.rule_id)?))
+                        });
+                        if field.is_virtual {
+                            // derived values are not part of the wire format
+                            ui.label(egui::RichText::new(&field.rule_id).italics())
+                                .on_hover_text(tr!("inspector-derived-hint"));
+                        } else {
+                            ui.horizontal(|ui| {
+                                // the color the field's bytes have in the hex view
+                                let color = colors.get(&field.rule_id).copied();
+                                color_swatch(ui, color.unwrap_or(egui::Color32::TRANSPARENT));
+                                ui.label(&field.rule_id);
+                            });
+                        }
+                        let formatter = app.field_formatters.get(&field.rule_id).copied();
+                        let response = match formatter {
+                            Some((p, f)) => {
+                                let plugin = &app.plugins[p];
+                                let function = &plugin.formatters[f].function;
+                                match app
+                                    .script_engine
+                                    .call(&plugin.ast, function, &[&field.value])
+                                {
+                                    Ok(Value::Str(text)) => ui.label(text),
+                                    Ok(value) => ui.label(value.to_string()),
+                                    Err(e) => ui.colored_label(ui.visuals().error_fg_color, e),
+                                }
+                            }
+                            None => {
+                                let format = formats.get(&field.rule_id).copied();
+                                let format = format.unwrap_or(app.appearance.display);
+                                let text = field.value.format(format, field.bit_len);
+                                let mapping = rule.and_then(|(_, r)| r.mapping.as_ref());
+                                match (mapping, &field.value) {
+                                    (Some(mapping), Value::Int(raw)) => match mapping.map(*raw) {
+                                        Some(mapped) => ui.label(mapped).on_hover_text(tr!(
+                                            "inspector-raw-value",
+                                            value = text
+                                        )),
+                                        None => ui.label(text),
+                                    },
+                                    _ => ui.label(text),
+                                }
+                            }
+                        };
+                        response.context_menu(|ui| {
+                            formatter_menu(
+                                ui,
+                                &app.plugins,
+                                &mut app.field_formatters,
+                                &mut app.display_overrides,
+                                &field.rule_id,
+                            )
+                        });
+                        ui.end_row();
+
+                        let (Value::Int(value), Some((protocol, rule))) = (&field.value, rule)
+                        else {
+                            continue;
+                        };
+                        let value = *value;
+                        let format = formats.get(&field.rule_id).copied();
+                        for sub in &rule.subfields {
+                            let mut hover = tr!(
+                                "inspector-subfield-hint",
+                                range = sub.range_label(),
+                                field = rule.id.as_str()
+                            );
+                            for text in [&sub.name, &sub.description].into_iter().flatten() {
+                                hover = format!("{}\n{}", hover, text);
+                            }
+                            ui.label(format!("  ↳ {}", sub.id)).on_hover_text(hover);
+                            let mut sub_value = sub.extract(value) as u64;
+                            let max = u64::MAX >> (64 - sub.bits.clamp(1, 64));
+                            let input = egui::DragValue::new(&mut sub_value).range(0..=max);
+                            let input = match format.unwrap_or(app.appearance.display) {
+                                DisplayFormat::Hex => input.hexadecimal(1, false, true),
+                                DisplayFormat::Binary => input.binary(sub.bits as usize, false),
+                                _ => input,
+                            };
+                            if ui.add(input).changed() {
+                                patch = Some((
+                                    rule.clone(),
+                                    rule.byte_order(protocol.endianness),
+                                    field.bit_offset,
+                                    sub.insert(value, sub_value as i128),
+                                ));
+                            }
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    }
+
+    if let Some((rule, endianness, bit_offset, value)) = patch {
+        let result = patch_field(
+            &mut app.packet_data,
+            &rule,
+            endianness,
+            bit_offset,
+            &Value::Int(value),
+        );
+        if app.report(result).is_some() {
+            app.decode_packet();
+        }
+        return;
+    }
+
+    if let Some(failure) = failure {
+        let error = ui.visuals().error_fg_color;
+        match &failure.field_id {
+            Some(field_id) => ui.colored_label(error, format!("⛔ {}", field_id)),
+            None => ui.colored_label(error, tr!("status-decode-failed")),
+        };
+        ui.indent("decode_failure", |ui| {
+            ui.label(&failure.message);
+            if failure.bit_len > 0 {
+                ui.weak(tr!(
+                    "inspector-failure-bits",
+                    bits = failure.bit_len,
+                    offset = failure.bit_offset
+                ));
+            }
         });
+    }
+
+    let steps = explain_dispatch(&app.registry, packet);
+    if !steps.is_empty() {
+        egui::CollapsingHeader::new(tr!("inspector-dispatch"))
+            .id_salt("inspector_dispatch")
+            .show(ui, |ui| dispatch(ui, &steps));
+    }
+}
+
+/// Which subprotocols the parent field values of the packet select, at each level of its
+/// inheritance chain
+fn dispatch(ui: &mut egui::Ui, steps: &[DispatchStep]) {
+    for step in steps {
+        ui.label(tr!(
+            "inspector-subprotocols-of",
+            protocol = step.parent_id.as_str()
+        ));
+        ui.indent(("dispatch", &step.parent_id), |ui| {
+            for candidate in &step.candidates {
+                let (icon, color) = match (candidate.chosen, candidate.matches()) {
+                    (true, true) => ("✔", ui.visuals().text_color()),
+                    (true, false) => ("⚠", ui.visuals().warn_fg_color),
+                    // another subprotocol would have applied as well
+                    (false, true) => ("⚠", ui.visuals().warn_fg_color),
+                    (false, false) => ("✖", ui.visuals().weak_text_color()),
+                };
+                let mut text =
+                    egui::RichText::new(format!("{} {}", icon, candidate.protocol_id)).color(color);
+                if candidate.chosen {
+                    text = text.strong();
+                }
+                ui.label(text);
+                ui.indent(("candidate", &candidate.protocol_id), |ui| {
+                    if candidate.checks.is_empty() {
+                        ui.weak(tr!("inspector-no-constraints"));
+                    }
+                    for check in &candidate.checks {
+                        let text = match &check.actual {
+                            _ if check.is_met() => {
+                                format!("{} = {}", check.field_id, check.expected)
+                            }
+                            Some(actual) => tr!(
+                                "inspector-check-actual",
+                                field = check.field_id.as_str(),
+                                actual = actual.to_string(),
+                                expected = check.expected.to_string()
+                            ),
+                            None => tr!(
+                                "inspector-check-missing",
+                                field = check.field_id.as_str(),
+                                expected = check.expected.to_string()
+                            ),
+                        };
+                        if check.is_met() {
+                            ui.label(text);
+                        } else {
+                            ui.weak(text);
+                        }
+                    }
+                    match (candidate.chosen, candidate.matches()) {
+                        (true, false) => {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                tr!("inspector-chosen-mismatch"),
+                            );
+                        }
+                        (false, true) => {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                tr!("inspector-also-matches"),
+                            );
+                        }
+                        _ => {}
+                    }
+                });
+            }
+        });
+    }
+}
+
+/// Choose a display format or plugin formatter for the values of a field
+fn formatter_menu(
+    ui: &mut egui::Ui,
+    plugins: &[Plugin],
+    formatters: &mut HashMap<String, (usize, usize)>,
+    overrides: &mut HashMap<String, DisplayFormat>,
+    field_id: &str,
+) {
+    ui.label(tr!("inspector-show-as"));
+    let current = overrides.get(field_id).copied();
+    if ui
+        .selectable_label(current.is_none(), tr!("inspector-field-default"))
+        .clicked()
+    {
+        overrides.remove(field_id);
+        ui.close();
+    }
+    for format in DisplayFormat::ALL {
+        if ui
+            .selectable_label(current == Some(format), display_format_label(format))
+            .clicked()
+        {
+            overrides.insert(field_id.to_string(), format);
+            ui.close();
+        }
+    }
+    ui.separator();
+
+    ui.label(tr!("inspector-format-with"));
+    let current = formatters.get(field_id).copied();
+    if ui
+        .selectable_label(current.is_none(), tr!("inspector-default"))
+        .clicked()
+    {
+        formatters.remove(field_id);
+        ui.close();
+    }
+    for (p, plugin) in plugins.iter().enumerate() {
+        for (f, formatter) in plugin.formatters.iter().enumerate() {
+            let label = format!("{} ({})", formatter.label, plugin.name);
+            if ui
+                .selectable_label(current == Some((p, f)), label)
+                .clicked()
+            {
+                formatters.insert(field_id.to_string(), (p, f));
+                ui.close();
+            }
+        }
+    }
+}
+
+/// A problem reported by a packet validator, colored by severity
+pub fn issue_badge(ui: &mut egui::Ui, issue: &ValidationIssue) {
+    let (icon, color) = match issue.severity {
+        Severity::Error => ("⛔", ui.visuals().error_fg_color),
+        Severity::Warning => ("⚠", ui.visuals().warn_fg_color),
+    };
+    ui.colored_label(color, format!("{} {}", icon, issue.message))
+        .on_hover_text(tr!(
+            "inspector-reported-by",
+            validator = issue.validator.as_str(),
+            protocol = issue.protocol_id.as_str()
+        ));
 }
@@ -0,0 +1,62 @@
+use crate::app::BitLoomApp;
+use bitloom::models::integrity::IntegrityIssue;
+use bitloom::tr;
+use eframe::egui;
+
+/// The broken references between protocols, each with the repair for it
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(issues) = &app.integrity_issues else {
+        return;
+    };
+
+    let mut open = true;
+    let mut repair: Vec<IntegrityIssue> = Vec::new();
+    egui::Window::new(tr!("integrity-title"))
+        .id(egui::Id::new("integrity"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if issues.is_empty() {
+                ui.label(tr!("integrity-ok"));
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for issue in issues {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(ui.visuals().warn_fg_color, "⚠");
+                            ui.label(issue.to_string());
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if ui
+                                        .small_button(tr!("integrity-repair"))
+                                        .on_hover_text(issue.repair_description())
+                                        .clicked()
+                                    {
+                                        repair.push(issue.clone());
+                                    }
+                                },
+                            );
+                        });
+                    }
+                });
+            ui.separator();
+            if ui.button(tr!("integrity-repair-all")).clicked() {
+                repair = issues.clone();
+            }
+        });
+
+    if !repair.is_empty() {
+        for issue in &repair {
+            let result = app.registry.repair(issue);
+            app.report(result);
+        }
+        // a repair can resolve or reveal others, e.g. the constraints of a reattached protocol
+        app.integrity_issues = Some(app.registry.check_integrity());
+    }
+    if !open {
+        app.integrity_issues = None;
+    }
+}
@@ -1,7 +1,36 @@
+pub mod api_server;
+pub mod bit_accounting;
+pub mod bit_calculator;
+pub mod bookmarks;
+pub mod checksum_calculator;
+pub mod codegen_dialog;
+pub mod compare;
+pub mod coverage;
+pub mod crash;
+pub mod delete_dialog;
+pub mod detached;
+pub mod export_dialog;
+pub mod expr_editor;
+pub mod external_editor;
+pub mod field_editor;
 pub mod hex_view;
+pub mod history;
+pub mod import_dialog;
 pub mod inspector;
+pub mod integrity;
+pub mod open_dialog;
 pub mod pages;
+pub mod replay;
+pub mod scheduler;
+pub mod scrub_dialog;
 pub mod sidebar;
+pub mod simulator;
+pub mod skeleton_dialog;
+pub mod status_bar;
+pub mod theme;
 pub mod top_panel;
+pub mod trash;
+pub mod where_used;
+pub mod widgets;
 
-pub use pages::protocol_designer;
+pub use pages::{capture, console, packet_builder, protocol_designer, script_library};
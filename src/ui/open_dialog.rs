@@ -0,0 +1,71 @@
+use crate::app::BitLoomApp;
+use bitloom::models::project::{BitLoomProject, PROJECT_VERSION};
+use bitloom::models::protocol::ProtocolRegistry;
+use bitloom::models::trash::Trash;
+use bitloom::tr;
+use eframe::egui;
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(path) = &mut app.pending_open else {
+        return;
+    };
+
+    let mut open = true;
+    let mut clicked = false;
+    egui::Window::new(tr!("open-title"))
+        .id(egui::Id::new("open_project"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("dialog-path"));
+                ui.add(egui::TextEdit::singleline(path).hint_text("project.bitloom"));
+                clicked = ui.button(tr!("open-button")).clicked();
+            });
+        });
+
+    if clicked {
+        let path = path.trim().to_string();
+        let result = open_project(app, &path);
+        if app.report(result).is_some() {
+            open = false;
+        }
+    }
+    if !open {
+        app.pending_open = None;
+    }
+}
+
+/// Replace the protocols, history, script library, saved packets and bookmarks with those of a
+/// project file, then check the references between the protocols
+pub fn open_project(app: &mut BitLoomApp, path: &str) -> Result<(), String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| tr!("open-read-failed", path = path, error = e.to_string()))?;
+    let project: BitLoomProject = serde_json::from_str(&text)
+        .map_err(|e| tr!("open-invalid", path = path, error = e.to_string()))?;
+    if project.project_version > PROJECT_VERSION {
+        return Err(tr!(
+            "open-too-new",
+            path = path,
+            version = project.project_version,
+            supported = PROJECT_VERSION
+        ));
+    }
+    let registry = ProtocolRegistry::from_protocols(project.protocols)?;
+
+    app.script_library_error = app.script_engine.set_library(&project.script_library).err();
+    app.script_library = project.script_library;
+    app.registry = registry;
+    app.history = project.history;
+    app.presets = project.presets;
+    app.bookmarks = project.bookmarks;
+    app.capture.columns = project.packet_columns;
+    app.trash = Trash::new();
+    app.selected_protocol = app.registry.iter().next().map(|p| p.id.clone());
+    app.selected_field = None;
+    app.selected_fields.clear();
+
+    let issues = app.registry.check_integrity();
+    app.integrity_issues = (!issues.is_empty()).then_some(issues);
+    Ok(())
+}
@@ -0,0 +1,1827 @@
+use crate::app::BitLoomApp;
+use crate::ui::scrub_dialog::ScrubDialog;
+use crate::ui::skeleton_dialog::SkeletonDialog;
+use crate::ui::{expr_editor, widgets};
+use bitloom::analysis::framing::{FramingAnalysis, analyze_framing};
+use bitloom::analysis::sequence::{SequenceEvent, SequenceReport, check_sequences};
+use bitloom::analysis::timing::{TimingStats, inter_arrival};
+use bitloom::capture::{
+    BinaryLogFormat, CapturedPacket, Framing, TcpMessageSplitter, TimeUnit, TimestampHeader,
+    read_binary_log, read_pcap, tcp_messages, udp_payload,
+};
+use bitloom::codec::batch::{Batch, decode_batch};
+use bitloom::codec::compiled::CodecCache;
+use bitloom::codec::decode::DecodedPacket;
+use bitloom::codec::stream::StreamDecoder;
+use bitloom::codec::{Value, parse_hex};
+use bitloom::conversation::{
+    Exchange, PAIR_KEY_FUNCTION, PairBy, pair_packets, suggest_key_fields,
+};
+use bitloom::live_capture::{Interface, LiveCapture};
+use bitloom::models::field::DisplayFormat;
+use bitloom::models::protocol::{Endianness, ProtocolRegistry};
+use bitloom::script::ScriptEngine;
+use bitloom::tr;
+use bitloom::transport::{Transport, TransportConfig};
+use eframe::egui;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime};
+
+/// Most recent packets kept in the list
+const MAX_PACKETS: usize = 10_000;
+
+/// Fewer packets than this are decoded right away rather than on worker threads
+const BATCH_DECODE_MIN: usize = 200;
+
+/// Bars of the histogram of the times between packets
+const TIMING_BINS: usize = 40;
+const HISTOGRAM_HEIGHT: f32 = 48.0;
+
+/// Width in characters of the field columns of the packet list; longer values are cut short
+const FIELD_COLUMN_WIDTH: usize = 14;
+
+/// A column of the packet list
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PacketColumn {
+    Number,
+    Time,
+    Length,
+    /// a field of the protocol the packets are decoded as, by ID
+    Field(String),
+}
+
+/// What the capture page lists
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaptureView {
+    Packets,
+    /// requests paired with their responses
+    Conversations,
+    /// gaps and repeats in the sequence numbers of the packets
+    Sequence,
+    /// times between the packets of each protocol
+    Timing,
+}
+
+/// Packets received live or loaded from a pcap file or binary log, with how they are decoded
+pub struct CaptureState {
+    pub transport: TransportConfig,
+    pub running: Option<Box<dyn Transport>>,
+    /// capture live from a network interface with libpcap rather than from `transport`
+    pub from_interface: bool,
+    pub interface: String,
+    /// BPF filter of the frames captured from `interface`
+    pub filter: String,
+    /// interfaces found when the list was last refreshed
+    pub interfaces: Vec<Interface>,
+    pub sniffer: Option<LiveCapture>,
+    /// TCP streams of the frames captured from `interface`
+    pub tcp_splitter: TcpMessageSplitter,
+    pub file_path: String,
+    /// how to split the file into packets if it is a raw binary log rather than a pcap file
+    pub log_format: Option<BinaryLogFormat>,
+    /// the delimiter of `log_format` as typed, in hex
+    pub delimiter_text: String,
+    /// framings suggested for the binary log at `file_path`
+    pub framing_analysis: Option<FramingAnalysis>,
+    /// what of the frames in pcap files and from interfaces to load
+    pub pcap_payload: PcapPayload,
+    /// split what is received live into messages by the length of the protocol, for
+    /// transports that carry a byte stream rather than packets
+    pub split_stream: bool,
+    /// messages of the byte stream received live, with the bytes of the one in progress
+    pub stream: StreamDecoder,
+    /// protocol the packets are decoded as
+    pub protocol: Option<String>,
+    /// decode each packet further as the subprotocol whose parent constraints it meets
+    pub dispatch: bool,
+    pub rows: Vec<CaptureRow>,
+    pub selected: Option<usize>,
+    /// fields shown as columns of the packet list, in order, by protocol ID; saved in the
+    /// project
+    pub columns: BTreeMap<String, Vec<String>>,
+    /// column the packet list is sorted by, and whether ascending
+    pub sort: Option<(PacketColumn, bool)>,
+    /// pcap link type of the rows if they are all whole frames of the same link
+    pub link_type: Option<u32>,
+    pub view: CaptureView,
+    /// pair packets by `pair_script` rather than by `pair_fields`
+    pub pair_with_script: bool,
+    pub pair_fields: Vec<String>,
+    pub pair_script: String,
+    /// the exchanges found when packets were last paired; cleared when rows are removed or
+    /// decoded again
+    pub exchanges: Option<Vec<Exchange>>,
+    /// the sequences found when sequence numbers were last checked; cleared with `exchanges`
+    pub sequences: Option<Vec<SequenceReport>>,
+    /// the times between packets when last measured; cleared with `exchanges`
+    pub timing: Option<Vec<TimingStats>>,
+    /// period in milliseconds the packets of a protocol are meant to come at, by protocol ID
+    pub expected_periods: BTreeMap<String, f64>,
+    /// how far in percent of the expected period the time between packets may be off
+    pub period_tolerance: f64,
+    /// rows being decoded on worker threads, from `decoding_first` on
+    pub decoding: Option<Batch<Result<DecodedPacket, String>>>,
+    pub decoding_first: usize,
+    /// rows removed from the front since `decoding` started
+    pub decoding_dropped: usize,
+    /// codecs of the protocols packets received live are decoded as
+    codecs: CodecCache,
+}
+
+impl Default for CaptureState {
+    fn default() -> Self {
+        Self {
+            transport: TransportConfig::Udp {
+                bind: "0.0.0.0:5000".to_string(),
+                remote: String::new(),
+            },
+            running: None,
+            from_interface: false,
+            interface: String::new(),
+            filter: String::new(),
+            interfaces: Vec::new(),
+            sniffer: None,
+            tcp_splitter: TcpMessageSplitter::new(),
+            file_path: String::new(),
+            log_format: None,
+            delimiter_text: String::new(),
+            framing_analysis: None,
+            pcap_payload: PcapPayload::Udp,
+            split_stream: false,
+            stream: StreamDecoder::new(),
+            protocol: None,
+            dispatch: false,
+            rows: Vec::new(),
+            selected: None,
+            columns: BTreeMap::new(),
+            sort: None,
+            link_type: None,
+            view: CaptureView::Packets,
+            pair_with_script: false,
+            pair_fields: Vec::new(),
+            pair_script: format!(
+                "fn {}(packet) {{\n    // return what a request and its response share, or () to leave the packet out\n    packet.seq\n}}",
+                PAIR_KEY_FUNCTION
+            ),
+            exchanges: None,
+            sequences: None,
+            timing: None,
+            expected_periods: BTreeMap::new(),
+            period_tolerance: 10.0,
+            decoding: None,
+            decoding_first: 0,
+            decoding_dropped: 0,
+            codecs: CodecCache::new(),
+        }
+    }
+}
+
+/// The part of each frame of a pcap file that is loaded as a packet
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PcapPayload {
+    Frame,
+    /// the payload of UDP datagrams; other frames are skipped
+    Udp,
+    /// the messages in the reassembled TCP streams, found by the length of the protocol
+    Tcp,
+}
+
+impl PcapPayload {
+    pub const ALL: [PcapPayload; 3] = [PcapPayload::Frame, PcapPayload::Udp, PcapPayload::Tcp];
+
+    pub fn label(self) -> String {
+        match self {
+            PcapPayload::Frame => tr!("capture-payload-frame"),
+            PcapPayload::Udp => tr!("capture-payload-udp"),
+            PcapPayload::Tcp => tr!("capture-payload-tcp"),
+        }
+    }
+
+    fn hover_text(self) -> String {
+        match self {
+            PcapPayload::Frame => tr!("capture-payload-frame-hint"),
+            PcapPayload::Udp => tr!("capture-payload-udp-hint"),
+            PcapPayload::Tcp => tr!("capture-payload-tcp-hint"),
+        }
+    }
+}
+
+pub struct CaptureRow {
+    pub packet: CapturedPacket,
+    /// the packet decoded as the chosen protocol, or why that failed
+    pub decoded: Option<Result<DecodedPacket, String>>,
+}
+
+impl CaptureState {
+    /// Whether packets are being received live
+    pub fn is_live(&self) -> bool {
+        self.running.is_some() || self.sniffer.is_some()
+    }
+
+    /// Note the link type of packets about to be added, `None` if they are not whole frames
+    fn note_source(&mut self, link_type: Option<u32>) {
+        if self.rows.is_empty() {
+            self.link_type = link_type;
+        } else if self.link_type != link_type {
+            self.link_type = None;
+        }
+    }
+
+    fn push(&mut self, registry: &ProtocolRegistry, engine: &ScriptEngine, packet: CapturedPacket) {
+        let decoded = self.decode(registry, engine, &packet.data);
+        self.push_decoded(packet, decoded);
+    }
+
+    fn push_decoded(
+        &mut self,
+        packet: CapturedPacket,
+        decoded: Option<Result<DecodedPacket, String>>,
+    ) {
+        self.rows.push(CaptureRow { packet, decoded });
+        self.trim();
+    }
+
+    /// Add many packets at once, e.g. from a file, decoding them on worker threads
+    fn push_all(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        packets: Vec<CapturedPacket>,
+    ) {
+        let first = self.rows.len();
+        self.rows
+            .extend(packets.into_iter().map(|packet| CaptureRow {
+                packet,
+                decoded: None,
+            }));
+        let excess = self.trim();
+        // rows still being decoded are decoded again along with the new ones
+        let first = match self.decoding {
+            Some(_) => 0,
+            None => first.saturating_sub(excess),
+        };
+        self.decode_rows(registry, engine, first);
+    }
+
+    /// Remove the oldest rows beyond `MAX_PACKETS`, returning how many
+    fn trim(&mut self) -> usize {
+        let excess = self.rows.len().saturating_sub(MAX_PACKETS);
+        self.rows.drain(..excess);
+        self.selected = self.selected.and_then(|i| i.checked_sub(excess));
+        if excess > 0 {
+            self.exchanges = None;
+            self.sequences = None;
+            self.timing = None;
+            self.decoding_dropped += excess;
+        }
+        excess
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.selected = None;
+        self.exchanges = None;
+        self.sequences = None;
+        self.timing = None;
+        self.decoding = None;
+    }
+
+    pub fn redecode(&mut self, registry: &ProtocolRegistry, engine: &ScriptEngine) {
+        self.exchanges = None;
+        self.sequences = None;
+        self.timing = None;
+        self.decode_rows(registry, engine, 0);
+    }
+
+    /// Decode the rows from `first` on, on worker threads if there are many
+    fn decode_rows(&mut self, registry: &ProtocolRegistry, engine: &ScriptEngine, first: usize) {
+        self.decoding = None;
+        let rows = &mut self.rows[first..];
+        let Some(protocol_id) = self.protocol.as_deref() else {
+            rows.iter_mut().for_each(|row| row.decoded = None);
+            return;
+        };
+        if rows.len() < BATCH_DECODE_MIN {
+            for row in rows {
+                row.decoded = Some(decode_as(
+                    &mut self.codecs,
+                    registry,
+                    engine,
+                    protocol_id,
+                    self.dispatch,
+                    &row.packet.data,
+                ));
+            }
+            return;
+        }
+        let packets = rows
+            .iter_mut()
+            .map(|row| {
+                row.decoded = None;
+                row.packet.data.clone()
+            })
+            .collect();
+        self.decoding = Some(decode_batch(
+            registry,
+            engine,
+            protocol_id,
+            self.dispatch,
+            packets,
+        ));
+        self.decoding_first = first;
+        self.decoding_dropped = 0;
+    }
+
+    /// Put the packets decoded on worker threads since the last frame in their rows
+    fn apply_decoded(&mut self) {
+        let Some(batch) = &mut self.decoding else {
+            return;
+        };
+        for (index, result) in batch.try_recv() {
+            let row = (self.decoding_first + index).checked_sub(self.decoding_dropped);
+            if let Some(row) = row.and_then(|i| self.rows.get_mut(i)) {
+                row.decoded = Some(result);
+            }
+        }
+        if batch.is_finished() {
+            self.decoding = None;
+        }
+    }
+
+    fn decode(
+        &mut self,
+        registry: &ProtocolRegistry,
+        engine: &ScriptEngine,
+        data: &[u8],
+    ) -> Option<Result<DecodedPacket, String>> {
+        let protocol_id = self.protocol.as_deref()?;
+        Some(decode_as(
+            &mut self.codecs,
+            registry,
+            engine,
+            protocol_id,
+            self.dispatch,
+            data,
+        ))
+    }
+}
+
+fn decode_as(
+    codecs: &mut CodecCache,
+    registry: &ProtocolRegistry,
+    engine: &ScriptEngine,
+    protocol_id: &str,
+    dispatch: bool,
+    data: &[u8],
+) -> Result<DecodedPacket, String> {
+    if dispatch {
+        codecs.decode_dispatched(registry, engine, protocol_id, data)
+    } else {
+        codecs.decode(registry, engine, protocol_id, data)
+    }
+}
+
+/// The decoded fields of a packet in one line, in their display formats
+fn summarize(
+    packet: &DecodedPacket,
+    formats: &HashMap<String, DisplayFormat>,
+    default: DisplayFormat,
+) -> String {
+    let fields: Vec<String> = packet
+        .fields
+        .iter()
+        .map(|f| {
+            let format = formats.get(&f.rule_id).copied().unwrap_or(default);
+            format!("{}={}", f.rule_id, f.value.format(format, f.bit_len))
+        })
+        .collect();
+    fields.join(" ")
+}
+
+/// Add packets received since the last frame
+pub fn poll(app: &mut BitLoomApp, ctx: &egui::Context) {
+    poll_transport(app, ctx);
+    poll_interface(app, ctx);
+    if app.capture.decoding.is_some() {
+        app.capture.apply_decoded();
+        ctx.request_repaint_after(Duration::from_millis(16));
+    }
+}
+
+fn poll_transport(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(transport) = &mut app.capture.running else {
+        return;
+    };
+    let mut received = Vec::new();
+    let mut error = None;
+    loop {
+        match transport.try_recv() {
+            Ok(Some(data)) => received.push(data),
+            Ok(None) => break,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let capture = &mut app.capture;
+    if capture.split_stream
+        && let Some(protocol_id) = &capture.protocol
+    {
+        capture.stream.dispatch = capture.dispatch;
+        let mut messages = Vec::new();
+        for data in received {
+            match capture
+                .stream
+                .feed(&app.registry, &app.script_engine, protocol_id, &data)
+            {
+                Ok(framed) => messages.extend(framed),
+                Err(e) => error = error.or(Some(e)),
+            }
+        }
+        if !messages.is_empty() {
+            capture.note_source(None);
+        }
+        for message in messages {
+            let packet = CapturedPacket {
+                timestamp,
+                data: message.data,
+            };
+            capture.push_decoded(packet, Some(message.decoded));
+        }
+    } else {
+        if !received.is_empty() {
+            capture.note_source(None);
+        }
+        for data in received {
+            capture.push(
+                &app.registry,
+                &app.script_engine,
+                CapturedPacket { timestamp, data },
+            );
+        }
+    }
+    if let Some(e) = error {
+        app.capture.running = None;
+        app.error = Some(e);
+    }
+    ctx.request_repaint_after(Duration::from_millis(10));
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        sources(app, ui);
+        ui.separator();
+        ui.horizontal(|ui| {
+            let capture = &mut app.capture;
+            ui.selectable_value(
+                &mut capture.view,
+                CaptureView::Packets,
+                tr!("capture-view-packets"),
+            );
+            ui.selectable_value(
+                &mut capture.view,
+                CaptureView::Conversations,
+                tr!("capture-view-conversations"),
+            )
+            .on_hover_text(tr!("capture-view-conversations-hint"));
+            ui.selectable_value(
+                &mut capture.view,
+                CaptureView::Sequence,
+                tr!("capture-view-sequence"),
+            )
+            .on_hover_text(tr!("capture-view-sequence-hint"));
+            ui.selectable_value(
+                &mut capture.view,
+                CaptureView::Timing,
+                tr!("capture-view-timing"),
+            )
+            .on_hover_text(tr!("capture-view-timing-hint"));
+        });
+        match app.capture.view {
+            CaptureView::Packets => packet_list(app, ui),
+            CaptureView::Conversations => conversations(app, ui),
+            CaptureView::Sequence => sequences(app, ui),
+            CaptureView::Timing => timing(app, ui),
+        }
+    });
+}
+
+/// Frames captured from the interface since the last frame, taking what `pcap_payload` says
+/// of each
+fn poll_interface(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(sniffer) = &mut app.capture.sniffer else {
+        return;
+    };
+    let mut frames = Vec::new();
+    let mut error = None;
+    loop {
+        match sniffer.try_recv() {
+            Ok(Some(frame)) => frames.push(frame),
+            Ok(None) => break,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let link_type = sniffer.link_type();
+    let capture = &mut app.capture;
+    let mut packets = Vec::new();
+    for mut frame in frames {
+        match capture.pcap_payload {
+            PcapPayload::Frame => packets.push(frame),
+            PcapPayload::Udp => {
+                if let Some(payload) = udp_payload(link_type, &frame.data) {
+                    frame.data = payload.to_vec();
+                    packets.push(frame);
+                }
+            }
+            PcapPayload::Tcp => {
+                let result = match &capture.protocol {
+                    Some(protocol_id) => {
+                        capture
+                            .tcp_splitter
+                            .push(link_type, &frame, &app.registry, protocol_id)
+                    }
+                    None => Err(tr!("capture-tcp-needs-protocol")),
+                };
+                match result {
+                    Ok(messages) => packets.extend(messages),
+                    Err(e) => error = error.or(Some(e)),
+                }
+            }
+        }
+    }
+    if !packets.is_empty() {
+        let frames = capture.pcap_payload == PcapPayload::Frame;
+        capture.note_source(frames.then_some(link_type));
+    }
+    for packet in packets {
+        capture.push(&app.registry, &app.script_engine, packet);
+    }
+    if let Some(e) = error {
+        capture.sniffer = None;
+        app.error = Some(e);
+    }
+    ctx.request_repaint_after(Duration::from_millis(10));
+}
+
+/// Live capture and file import controls, and the protocol to decode as
+fn sources(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let protocol_ids: Vec<String> = app
+        .registry
+        .list_protocols()
+        .into_iter()
+        .map(|p| p.id.clone())
+        .collect();
+
+    ui.horizontal(|ui| {
+        ui.strong(tr!("capture-title"));
+        ui.separator();
+        ui.label(tr!("capture-decode-as"));
+        let before = app.capture.protocol.clone();
+        egui::ComboBox::from_id_salt("capture_protocol")
+            .selected_text(
+                app.capture
+                    .protocol
+                    .clone()
+                    .unwrap_or_else(|| tr!("capture-select")),
+            )
+            .show_ui(ui, |ui| {
+                for id in &protocol_ids {
+                    ui.selectable_value(&mut app.capture.protocol, Some(id.clone()), id);
+                }
+            });
+        let dispatch_changed = ui
+            .checkbox(&mut app.capture.dispatch, tr!("capture-subprotocols"))
+            .on_hover_text(tr!("capture-subprotocols-hint"))
+            .changed();
+        if app.capture.protocol != before || dispatch_changed {
+            app.capture.redecode(&app.registry, &app.script_engine);
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(
+                    !app.capture.rows.is_empty(),
+                    egui::Button::new(tr!("capture-scrub")),
+                )
+                .on_hover_text(tr!("capture-scrub-hint"))
+                .clicked()
+            {
+                app.scrub_dialog = Some(ScrubDialog::default());
+            }
+            if ui
+                .add_enabled(
+                    !app.capture.rows.is_empty(),
+                    egui::Button::new(tr!("capture-suggest-fields")),
+                )
+                .on_hover_text(tr!("capture-suggest-fields-hint"))
+                .clicked()
+            {
+                let result = SkeletonDialog::from_capture(app);
+                app.skeleton_dialog = app.report(result);
+            }
+            if ui.button(tr!("capture-clear")).clicked() {
+                app.capture.clear();
+            }
+            if let Some(batch) = &app.capture.decoding {
+                let (done, total) = batch.progress();
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .desired_width(160.0)
+                        .text(tr!("capture-decoding", done = done, total = total)),
+                );
+            }
+        });
+    });
+
+    ui.columns(2, |columns| {
+        let ui = &mut columns[0];
+        ui.horizontal(|ui| {
+            ui.label(tr!("capture-live"));
+            ui.add_enabled_ui(!app.capture.is_live(), |ui| {
+                let capture = &mut app.capture;
+                ui.selectable_value(&mut capture.from_interface, false, tr!("capture-transport"));
+                ui.selectable_value(&mut capture.from_interface, true, tr!("capture-interface"))
+                    .on_hover_text(tr!("capture-interface-hint"));
+            });
+        });
+        if app.capture.from_interface {
+            interface_capture(app, ui);
+        } else {
+            transport_capture(app, ui);
+        }
+
+        let ui = &mut columns[1];
+        ui.horizontal(|ui| {
+            ui.label(tr!("capture-file"));
+            let capture = &mut app.capture;
+            ui.selectable_value(&mut capture.log_format, None, "pcap");
+            if ui
+                .selectable_label(capture.log_format.is_some(), tr!("capture-binary-log"))
+                .on_hover_text(tr!("capture-binary-log-hint"))
+                .clicked()
+                && capture.log_format.is_none()
+            {
+                capture.log_format = Some(BinaryLogFormat::default());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("dialog-path"));
+            ui.text_edit_singleline(&mut app.capture.file_path);
+        });
+        let capture = &mut app.capture;
+        match &mut capture.log_format {
+            Some(format) => log_format_settings(ui, format, &mut capture.delimiter_text),
+            None => payload_picker(ui, &mut capture.pcap_payload),
+        }
+        ui.horizontal(|ui| {
+            if ui.button(tr!("capture-load")).clicked() {
+                let result = load_file(app);
+                app.report(result);
+            }
+            if app.capture.log_format.is_some()
+                && ui
+                    .button(tr!("capture-detect-framing"))
+                    .on_hover_text(tr!("capture-detect-framing-hint"))
+                    .clicked()
+            {
+                let path = app.capture.file_path.trim();
+                let result = std::fs::read(path)
+                    .map(|bytes| analyze_framing(&bytes))
+                    .map_err(|e| tr!("capture-read-failed", path = path, error = e.to_string()));
+                app.capture.framing_analysis = app.report(result);
+            }
+        });
+        if app.capture.log_format.is_some() {
+            framing_suggestions(ui, &mut app.capture);
+        }
+    });
+}
+
+/// The framings suggested for the binary log, each of which can be used to load it
+fn framing_suggestions(ui: &mut egui::Ui, capture: &mut CaptureState) {
+    let Some(analysis) = &capture.framing_analysis else {
+        return;
+    };
+    let mut chosen = None;
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.strong(tr!("capture-suggested-framing"));
+            if ui
+                .small_button("✖")
+                .on_hover_text(tr!("capture-close"))
+                .clicked()
+            {
+                chosen = Some(None);
+            }
+        });
+        let frequent: Vec<String> = analysis
+            .frequent
+            .iter()
+            .map(|(b, share)| format!("{:02x} {:.0}%", b, share * 100.0))
+            .collect();
+        ui.weak(tr!(
+            "capture-frequent-bytes",
+            count = analysis.analyzed,
+            bytes = frequent.join(", ")
+        ));
+        if analysis.suggestions.is_empty() {
+            ui.label(tr!("capture-no-framing"));
+            return;
+        }
+        egui::Grid::new("framing_suggestions")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for suggestion in &analysis.suggestions {
+                    ui.label(describe_framing(&suggestion.framing));
+                    ui.label(tr!(
+                        "capture-framing-records",
+                        records = suggestion.records,
+                        shortest = suggestion.shortest,
+                        longest = suggestion.longest
+                    ));
+                    ui.add(
+                        egui::ProgressBar::new(suggestion.confidence as f32)
+                            .desired_width(60.0)
+                            .show_percentage(),
+                    )
+                    .on_hover_text(tr!("capture-framing-confidence-hint"));
+                    let current = capture
+                        .log_format
+                        .as_ref()
+                        .is_some_and(|f| f.framing == suggestion.framing);
+                    if ui
+                        .add_enabled(!current, egui::Button::new(tr!("capture-use")))
+                        .clicked()
+                    {
+                        chosen = Some(Some(suggestion.framing.clone()));
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+    match chosen {
+        Some(Some(framing)) => {
+            if let Framing::Delimiter(delimiter) = &framing {
+                capture.delimiter_text = format_hex(delimiter);
+            }
+            capture.log_format = Some(BinaryLogFormat {
+                framing,
+                timestamp: None,
+            });
+        }
+        Some(None) => capture.framing_analysis = None,
+        None => {}
+    }
+}
+
+/// Bytes as hex digits separated by spaces, as the delimiter is typed
+fn format_hex(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    hex.join(" ")
+}
+
+fn describe_framing(framing: &Framing) -> String {
+    match framing {
+        Framing::Fixed { size } => tr!("capture-framing-fixed", size = *size),
+        Framing::LengthPrefix {
+            offset,
+            width,
+            byte_order,
+            adjustment,
+        } => format!(
+            "{}{}",
+            tr!(
+                if *width == 1 {
+                    "capture-framing-length-one"
+                } else {
+                    "capture-framing-length"
+                },
+                width = *width,
+                offset = *offset,
+                order = match byte_order {
+                    Endianness::Big => tr!("capture-big-endian"),
+                    Endianness::Little => tr!("capture-little-endian"),
+                }
+            ),
+            match adjustment {
+                0 => String::new(),
+                a => format!(", {:+}", a),
+            }
+        ),
+        Framing::Delimiter(delimiter) => {
+            tr!(
+                "capture-framing-delimiter",
+                delimiter = format_hex(delimiter)
+            )
+        }
+    }
+}
+
+fn transport_capture(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let running = app.capture.running.is_some();
+    ui.add_enabled_ui(!running, |ui| {
+        egui::Grid::new("capture_transport")
+            .num_columns(2)
+            .show(ui, |ui| {
+                widgets::transport_settings(
+                    ui,
+                    "capture_transport_kind",
+                    &mut app.capture.transport,
+                    None,
+                );
+            });
+    });
+    ui.add_enabled_ui(!running, |ui| {
+        ui.checkbox(&mut app.capture.split_stream, tr!("capture-split"))
+            .on_hover_text(tr!("capture-split-hint"));
+    });
+    if running {
+        if ui.button(tr!("capture-stop")).clicked() {
+            app.capture.running = None;
+        }
+    } else if ui.button(tr!("capture-start")).clicked() {
+        let result = app.capture.transport.open();
+        app.capture.running = app.report(result);
+        app.capture.stream = StreamDecoder::new();
+    }
+}
+
+fn interface_capture(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let running = app.capture.sniffer.is_some();
+    ui.add_enabled_ui(!running, |ui| {
+        egui::Grid::new("capture_interface")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr!("capture-interface"));
+                ui.horizontal(|ui| {
+                    let capture = &mut app.capture;
+                    egui::ComboBox::from_id_salt("capture_interface_name")
+                        .selected_text(&capture.interface)
+                        .show_ui(ui, |ui| {
+                            for interface in &capture.interfaces {
+                                let response = ui.selectable_value(
+                                    &mut capture.interface,
+                                    interface.name.clone(),
+                                    &interface.name,
+                                );
+                                if let Some(description) = &interface.description {
+                                    response.on_hover_text(description);
+                                }
+                            }
+                        });
+                    if ui.button(tr!("capture-refresh")).clicked() {
+                        let result = LiveCapture::interfaces();
+                        if let Some(interfaces) = app.report(result) {
+                            app.capture.interfaces = interfaces;
+                        }
+                    }
+                });
+                ui.end_row();
+                ui.label(tr!("capture-filter"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut app.capture.filter)
+                        .hint_text(tr!("capture-filter-example")),
+                )
+                .on_hover_text(tr!("capture-filter-hint"));
+                ui.end_row();
+            });
+        payload_picker(ui, &mut app.capture.pcap_payload);
+    });
+    if running {
+        if ui.button(tr!("capture-stop")).clicked() {
+            app.capture.sniffer = None;
+        }
+    } else if ui.button(tr!("capture-start")).clicked() {
+        let result = LiveCapture::start(app.capture.interface.trim(), &app.capture.filter);
+        app.capture.sniffer = app.report(result);
+        app.capture.tcp_splitter = TcpMessageSplitter::new();
+    }
+}
+
+fn payload_picker(ui: &mut egui::Ui, pcap_payload: &mut PcapPayload) {
+    ui.horizontal(|ui| {
+        for payload in PcapPayload::ALL {
+            ui.selectable_value(pcap_payload, payload, payload.label())
+                .on_hover_text(payload.hover_text());
+        }
+    });
+}
+
+/// Inputs for how a binary log is split into records
+/// Name of a way of splitting a log into records, for choosing it
+fn framing_label(framing: &Framing) -> String {
+    match framing {
+        Framing::Fixed { .. } => tr!("capture-framing-kind-fixed"),
+        Framing::LengthPrefix { .. } => tr!("capture-framing-kind-length-prefix"),
+        Framing::Delimiter(_) => tr!("capture-framing-kind-delimiter"),
+    }
+}
+
+fn log_format_settings(
+    ui: &mut egui::Ui,
+    format: &mut BinaryLogFormat,
+    delimiter_text: &mut String,
+) {
+    egui::Grid::new("log_format").num_columns(2).show(ui, |ui| {
+        ui.label(tr!("capture-framing"));
+        egui::ComboBox::from_id_salt("log_framing")
+            .selected_text(framing_label(&format.framing))
+            .show_ui(ui, |ui| {
+                let options = [
+                    Framing::Fixed { size: 8 },
+                    Framing::LengthPrefix {
+                        offset: 0,
+                        width: 2,
+                        byte_order: Endianness::Big,
+                        adjustment: 0,
+                    },
+                    Framing::Delimiter(parse_hex(delimiter_text).unwrap_or_default()),
+                ];
+                for option in options {
+                    let selected = option.kind_name() == format.framing.kind_name();
+                    if ui
+                        .selectable_label(selected, framing_label(&option))
+                        .clicked()
+                        && !selected
+                    {
+                        format.framing = option;
+                    }
+                }
+            });
+        ui.end_row();
+
+        match &mut format.framing {
+            Framing::Fixed { size } => {
+                ui.label(tr!("capture-record-size"));
+                ui.add(
+                    egui::DragValue::new(size)
+                        .range(1..=65_535)
+                        .suffix(format!(" {}", tr!("capture-bytes-unit"))),
+                );
+                ui.end_row();
+            }
+            Framing::LengthPrefix {
+                offset,
+                width,
+                byte_order,
+                adjustment,
+            } => {
+                ui.label(tr!("capture-length-at"));
+                ui.add(
+                    egui::DragValue::new(offset)
+                        .range(0..=1024)
+                        .prefix(format!("{} ", tr!("capture-byte-prefix"))),
+                );
+                ui.end_row();
+                ui.label(tr!("capture-length-size"));
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(width)
+                            .range(1..=8)
+                            .suffix(format!(" {}", tr!("capture-bytes-unit"))),
+                    );
+                    byte_order_picker(ui, "length_byte_order", byte_order);
+                });
+                ui.end_row();
+                ui.label(tr!("capture-adjustment"))
+                    .on_hover_text(tr!("capture-adjustment-hint"));
+                ui.add(egui::DragValue::new(adjustment).range(-1024..=1024));
+                ui.end_row();
+            }
+            Framing::Delimiter(delimiter) => {
+                ui.label(tr!("capture-delimiter"));
+                let valid = parse_hex(delimiter_text).ok().filter(|d| !d.is_empty());
+                let mut edit = egui::TextEdit::singleline(delimiter_text)
+                    .hint_text(tr!("capture-delimiter-example"));
+                if valid.is_none() {
+                    edit = edit.text_color(ui.visuals().error_fg_color);
+                }
+                ui.add(edit);
+                if let Some(bytes) = valid {
+                    *delimiter = bytes;
+                }
+                ui.end_row();
+            }
+        }
+
+        ui.label(tr!("capture-timestamp"));
+        let mut has_timestamp = format.timestamp.is_some();
+        ui.checkbox(&mut has_timestamp, tr!("capture-timestamp-before"))
+            .on_hover_text(tr!("capture-timestamp-hint"));
+        ui.end_row();
+        match (&mut format.timestamp, has_timestamp) {
+            (None, true) => {
+                format.timestamp = Some(TimestampHeader {
+                    width: 8,
+                    byte_order: Endianness::Big,
+                    unit: TimeUnit::Microseconds,
+                })
+            }
+            (Some(_), false) => format.timestamp = None,
+            (Some(header), true) => {
+                ui.label("");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut header.width)
+                            .range(1..=8)
+                            .suffix(format!(" {}", tr!("capture-bytes-unit"))),
+                    );
+                    byte_order_picker(ui, "timestamp_byte_order", &mut header.byte_order);
+                    egui::ComboBox::from_id_salt("timestamp_unit")
+                        .selected_text(format!("{:?}", header.unit))
+                        .show_ui(ui, |ui| {
+                            for unit in TimeUnit::ALL {
+                                ui.selectable_value(&mut header.unit, unit, format!("{:?}", unit));
+                            }
+                        });
+                });
+                ui.end_row();
+            }
+            (None, false) => {}
+        }
+    });
+}
+
+fn byte_order_picker(ui: &mut egui::Ui, id_salt: &str, byte_order: &mut Endianness) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(widgets::endianness_label(*byte_order))
+        .show_ui(ui, |ui| {
+            for option in [Endianness::Big, Endianness::Little] {
+                ui.selectable_value(byte_order, option, widgets::endianness_label(option));
+            }
+        });
+}
+
+fn load_file(app: &mut BitLoomApp) -> Result<(), String> {
+    let path = app.capture.file_path.trim();
+    let bytes = std::fs::read(path)
+        .map_err(|e| tr!("capture-read-failed", path = path, error = e.to_string()))?;
+    if let Some(format) = &app.capture.log_format {
+        let packets = read_binary_log(&bytes, format)?;
+        app.capture.note_source(None);
+        app.capture
+            .push_all(&app.registry, &app.script_engine, packets);
+        return Ok(());
+    }
+    let file = read_pcap(&bytes)?;
+    let link_type = file.link_type;
+    let packets = match app.capture.pcap_payload {
+        PcapPayload::Frame => file.packets,
+        PcapPayload::Udp => file
+            .packets
+            .into_iter()
+            .filter_map(|mut packet| {
+                packet.data = udp_payload(file.link_type, &packet.data)?.to_vec();
+                Some(packet)
+            })
+            .collect(),
+        PcapPayload::Tcp => {
+            let protocol_id = app
+                .capture
+                .protocol
+                .as_deref()
+                .ok_or_else(|| tr!("capture-tcp-needs-protocol"))?;
+            tcp_messages(&file, &app.registry, protocol_id)?
+        }
+    };
+    let frames = app.capture.pcap_payload == PcapPayload::Frame;
+    app.capture.note_source(frames.then_some(link_type));
+    app.capture
+        .push_all(&app.registry, &app.script_engine, packets);
+    Ok(())
+}
+
+/// One line per packet, with the chosen fields as columns; selecting one shows it in the hex
+/// view and inspector
+fn packet_list(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    column_chooser(app, ui);
+    let capture = &app.capture;
+    let fields: Vec<String> = capture
+        .protocol
+        .as_ref()
+        .and_then(|id| capture.columns.get(id))
+        .cloned()
+        .unwrap_or_default();
+    let start = capture.rows.first().map(|r| r.packet.timestamp);
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+    let mut sort = capture.sort.clone();
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        ui.add_space(ui.spacing().button_padding.x);
+        sort_header(
+            ui,
+            &mut sort,
+            PacketColumn::Number,
+            &format!("{:>6}", tr!("capture-column-number")),
+        );
+        sort_header(
+            ui,
+            &mut sort,
+            PacketColumn::Time,
+            &format!("{:>12}", tr!("capture-column-time")),
+        );
+        sort_header(
+            ui,
+            &mut sort,
+            PacketColumn::Length,
+            &format!("{:>6}", tr!("capture-column-length")),
+        );
+        for field in &fields {
+            let title = format!("{:<FIELD_COLUMN_WIDTH$}", cut(field));
+            sort_header(ui, &mut sort, PacketColumn::Field(field.clone()), &title);
+        }
+        if fields.is_empty() {
+            ui.monospace(tr!("capture-column-decoded"));
+        }
+    });
+
+    // by protocol, as packets may decode as different subprotocols
+    let mut formats: HashMap<String, HashMap<String, DisplayFormat>> = HashMap::new();
+    let order = sorted_rows(capture);
+    let mut clicked = None;
+    egui::ScrollArea::vertical()
+        .auto_shrink(false)
+        .stick_to_bottom(capture.is_live() && capture.sort.is_none())
+        .show_rows(ui, row_height, order.len(), |ui, range| {
+            for &i in &order[range] {
+                let row = &capture.rows[i];
+                let time = row
+                    .packet
+                    .timestamp
+                    .saturating_sub(start.unwrap_or_default());
+                let mut text = format!(
+                    "{:>6}  {:>12.6}  {:>6}  ",
+                    i + 1,
+                    time.as_secs_f64(),
+                    row.packet.data.len()
+                );
+                let summary = match &row.decoded {
+                    Some(Ok(packet)) => {
+                        let formats = formats
+                            .entry(packet.protocol_id.clone())
+                            .or_insert_with(|| app.display_formats(&packet.protocol_id));
+                        if fields.is_empty() {
+                            summarize(packet, formats, app.appearance.display)
+                        } else {
+                            for id in &fields {
+                                let value =
+                                    packet.fields.iter().find(|f| f.rule_id == *id).map(|f| {
+                                        let format = formats
+                                            .get(id)
+                                            .copied()
+                                            .unwrap_or(app.appearance.display);
+                                        f.value.format(format, f.bit_len)
+                                    });
+                                let value = cut(value.as_deref().unwrap_or("-"));
+                                text.push_str(&format!("{:<FIELD_COLUMN_WIDTH$}  ", value));
+                            }
+                            String::new()
+                        }
+                    }
+                    Some(Err(e)) => format!("⚠ {}", e),
+                    None if capture.decoding.is_some() => "…".to_string(),
+                    None => String::new(),
+                };
+                text.push_str(&summary);
+                let mut text = egui::RichText::new(text).monospace();
+                if matches!(row.decoded, Some(Err(_))) {
+                    text = text.color(ui.visuals().warn_fg_color);
+                }
+                if ui
+                    .selectable_label(capture.selected == Some(i), text)
+                    .clicked()
+                {
+                    clicked = Some(i);
+                }
+            }
+        });
+
+    app.capture.sort = sort;
+    if let Some(i) = clicked {
+        select_row(app, i);
+    }
+}
+
+/// A value cut short to the width of a field column
+fn cut(text: &str) -> String {
+    if text.chars().count() <= FIELD_COLUMN_WIDTH {
+        text.to_string()
+    } else {
+        let mut text: String = text.chars().take(FIELD_COLUMN_WIDTH - 1).collect();
+        text.push('…');
+        text
+    }
+}
+
+/// The title of a column of the packet list, sorting by it when clicked and the other way
+/// round when clicked again
+fn sort_header(
+    ui: &mut egui::Ui,
+    sort: &mut Option<(PacketColumn, bool)>,
+    column: PacketColumn,
+    title: &str,
+) {
+    let arrow = match sort {
+        Some((sorted, ascending)) if *sorted == column => {
+            if *ascending {
+                "⏶ "
+            } else {
+                "⏷ "
+            }
+        }
+        _ => "  ",
+    };
+    let label = egui::Label::new(egui::RichText::new(format!("{}{}", title, arrow)).monospace())
+        .sense(egui::Sense::click());
+    if ui
+        .add(label)
+        .on_hover_text(tr!("capture-sort-hint"))
+        .clicked()
+    {
+        *sort = match sort.take() {
+            Some((sorted, ascending)) if sorted == column => Some((sorted, !ascending)),
+            _ => Some((column, true)),
+        };
+    }
+}
+
+/// Indices of the rows in the order they are listed in
+fn sorted_rows(capture: &CaptureState) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..capture.rows.len()).collect();
+    let Some((column, ascending)) = &capture.sort else {
+        return order;
+    };
+    let rows = &capture.rows;
+    match column {
+        PacketColumn::Number => {}
+        PacketColumn::Time => order.sort_by_key(|&i| rows[i].packet.timestamp),
+        PacketColumn::Length => order.sort_by_key(|&i| rows[i].packet.data.len()),
+        PacketColumn::Field(id) => {
+            let values: Vec<Option<&Value>> = rows
+                .iter()
+                .map(|row| match &row.decoded {
+                    Some(Ok(packet)) => packet
+                        .fields
+                        .iter()
+                        .find(|f| f.rule_id == *id)
+                        .map(|f| &f.value),
+                    _ => None,
+                })
+                .collect();
+            // packets without the field go last
+            order.sort_by(|&a, &b| match (values[a], values[b]) {
+                (Some(a), Some(b)) => a.sort_cmp(b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            });
+        }
+    }
+    if !ascending {
+        order.reverse();
+    }
+    order
+}
+
+/// Choose the fields of the protocol the packets are decoded as that the packet list shows as
+/// columns, and their order
+fn column_chooser(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let Some(protocol_id) = app.capture.protocol.clone() else {
+        return;
+    };
+    let layout = app.registry.layout(&protocol_id).unwrap_or_default();
+    let mut columns = app
+        .capture
+        .columns
+        .get(&protocol_id)
+        .cloned()
+        .unwrap_or_default();
+    let before = columns.clone();
+    ui.horizontal(|ui| {
+        ui.menu_button(tr!("capture-columns", count = columns.len()), |ui| {
+            let mut moved = None;
+            let mut removed = None;
+            for (i, id) in columns.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let mut shown = true;
+                    if ui.checkbox(&mut shown, id).changed() {
+                        removed = Some(i);
+                    }
+                    if ui
+                        .add_enabled(i > 0, egui::Button::new("⏶").small())
+                        .clicked()
+                    {
+                        moved = Some((i, i - 1));
+                    }
+                    if ui
+                        .add_enabled(i + 1 < columns.len(), egui::Button::new("⏷").small())
+                        .clicked()
+                    {
+                        moved = Some((i, i + 1));
+                    }
+                });
+            }
+            if let Some((a, b)) = moved {
+                columns.swap(a, b);
+            }
+            if let Some(i) = removed {
+                columns.remove(i);
+            }
+            if !columns.is_empty() {
+                ui.separator();
+            }
+            for field in &layout.fields {
+                if !columns.contains(&field.id) {
+                    let mut shown = false;
+                    if ui.checkbox(&mut shown, &field.id).changed() {
+                        columns.push(field.id.clone());
+                    }
+                }
+            }
+            ui.separator();
+            if ui
+                .add_enabled(
+                    !columns.is_empty(),
+                    egui::Button::new(tr!("capture-show-summary")),
+                )
+                .on_hover_text(tr!("capture-show-summary-hint"))
+                .clicked()
+            {
+                columns.clear();
+                ui.close();
+            }
+        })
+        .response
+        .on_hover_text(tr!("capture-columns-hint", protocol = protocol_id.as_str()));
+        if app.capture.sort.is_some() && ui.small_button(tr!("capture-unsort")).clicked() {
+            app.capture.sort = None;
+        }
+    });
+    if columns != before {
+        if let Some((PacketColumn::Field(id), _)) = &app.capture.sort
+            && !columns.contains(id)
+        {
+            app.capture.sort = None;
+        }
+        if columns.is_empty() {
+            app.capture.columns.remove(&protocol_id);
+        } else {
+            app.capture.columns.insert(protocol_id, columns);
+        }
+    }
+}
+
+/// Show a captured packet in the hex view and inspector
+fn select_row(app: &mut BitLoomApp, i: usize) {
+    app.capture.selected = Some(i);
+    app.packet_data = app.capture.rows[i].packet.data.clone();
+    if app.capture.protocol.is_some() {
+        app.selected_protocol = app.capture.protocol.clone();
+    }
+    app.decode_packet();
+}
+
+/// How packets are paired, and the exchanges found with their round-trip times
+fn conversations(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let decoded: Vec<&DecodedPacket> = app
+        .capture
+        .rows
+        .iter()
+        .filter_map(|r| r.decoded.as_ref()?.as_ref().ok())
+        .collect();
+    let mut field_ids: Vec<String> = Vec::new();
+    for packet in decoded.iter().take(100) {
+        for field in &packet.fields {
+            if !field_ids.contains(&field.rule_id) {
+                field_ids.push(field.rule_id.clone());
+            }
+        }
+    }
+    let suggested = suggest_key_fields(decoded.iter().copied().take(100));
+
+    let capture = &mut app.capture;
+    ui.horizontal(|ui| {
+        ui.label(tr!("capture-pair-by"));
+        ui.selectable_value(
+            &mut capture.pair_with_script,
+            false,
+            tr!("capture-pair-fields"),
+        );
+        ui.selectable_value(
+            &mut capture.pair_with_script,
+            true,
+            tr!("capture-pair-script"),
+        );
+        if !capture.pair_with_script {
+            let label = match capture.pair_fields.len() {
+                0 => tr!("capture-choose"),
+                _ => capture.pair_fields.join(", "),
+            };
+            ui.menu_button(label, |ui| {
+                for id in &field_ids {
+                    let mut checked = capture.pair_fields.contains(id);
+                    if ui.checkbox(&mut checked, id).changed() {
+                        if checked {
+                            capture.pair_fields.push(id.clone());
+                        } else {
+                            capture.pair_fields.retain(|f| f != id);
+                        }
+                    }
+                }
+                if field_ids.is_empty() {
+                    ui.weak(tr!("capture-decode-first"));
+                }
+            });
+            if ui
+                .add_enabled(
+                    !suggested.is_empty(),
+                    egui::Button::new(tr!("capture-suggest")),
+                )
+                .on_hover_text(tr!("capture-suggest-hint"))
+                .clicked()
+            {
+                capture.pair_fields = suggested.clone();
+            }
+        }
+    });
+    if capture.pair_with_script {
+        let variables: Vec<String> = app.script_engine.library_functions();
+        expr_editor::show(
+            ui,
+            "pair_script",
+            &mut capture.pair_script,
+            &app.script_engine,
+            &variables,
+        );
+    }
+    if ui
+        .add_enabled(
+            capture.decoding.is_none(),
+            egui::Button::new(tr!("capture-pair")),
+        )
+        .on_disabled_hover_text(tr!("capture-wait-decoding"))
+        .clicked()
+    {
+        let packets: Vec<_> = capture
+            .rows
+            .iter()
+            .map(|r| {
+                let decoded = r.decoded.as_ref().and_then(|d| d.as_ref().ok());
+                (r.packet.timestamp, decoded)
+            })
+            .collect();
+        let by = if capture.pair_with_script {
+            PairBy::Script(capture.pair_script.clone())
+        } else {
+            PairBy::Fields(capture.pair_fields.clone())
+        };
+        let result = pair_packets(&app.script_engine, &packets, &by);
+        app.capture.exchanges = app.report(result);
+    }
+    ui.separator();
+
+    let Some(exchanges) = &app.capture.exchanges else {
+        ui.weak(tr!("capture-pair-prompt"));
+        return;
+    };
+    let round_trips: Vec<f64> = exchanges
+        .iter()
+        .filter_map(|e| Some(e.round_trip?.as_secs_f64() * 1000.0))
+        .collect();
+    let unanswered = exchanges.len() - round_trips.len();
+    let summary = if round_trips.is_empty() {
+        tr!(
+            "capture-exchanges",
+            count = exchanges.len(),
+            unanswered = unanswered
+        )
+    } else {
+        let min = round_trips.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = round_trips.iter().copied().fold(0.0, f64::max);
+        let mean = round_trips.iter().sum::<f64>() / round_trips.len() as f64;
+        tr!(
+            "capture-exchanges-round-trip",
+            count = exchanges.len(),
+            unanswered = unanswered,
+            min = format!("{:.3}", min),
+            mean = format!("{:.3}", mean),
+            max = format!("{:.3}", max)
+        )
+    };
+    ui.label(summary);
+
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    ui.monospace(format!(
+        "{:>6}  {:>6}  {:>10}  {:>7}  {}",
+        tr!("capture-column-request"),
+        tr!("capture-column-response"),
+        tr!("capture-column-rtt"),
+        tr!("capture-column-retries"),
+        tr!("capture-column-key")
+    ));
+    let selected = app.capture.selected;
+    let mut clicked = None;
+    egui::ScrollArea::vertical()
+        .id_salt("conversations")
+        .auto_shrink(false)
+        .show_rows(ui, row_height, exchanges.len(), |ui, range| {
+            for exchange in &exchanges[range] {
+                ui.horizontal(|ui| {
+                    let request = format!("{:>6}", exchange.request + 1);
+                    let response = match exchange.response {
+                        Some(i) => format!("{:>6}", i + 1),
+                        None => format!("{:>6}", "—"),
+                    };
+                    if ui
+                        .selectable_label(
+                            selected == Some(exchange.request),
+                            egui::RichText::new(request).monospace(),
+                        )
+                        .clicked()
+                    {
+                        clicked = Some(exchange.request);
+                    }
+                    let response_label = ui.selectable_label(
+                        exchange.response.is_some() && selected == exchange.response,
+                        egui::RichText::new(response).monospace(),
+                    );
+                    if response_label.clicked() {
+                        clicked = exchange.response.or(clicked);
+                    }
+                    let round_trip = match exchange.round_trip {
+                        Some(rtt) => format!("{:>10.3}", rtt.as_secs_f64() * 1000.0),
+                        None => format!("{:>10}", ""),
+                    };
+                    let text = format!(
+                        "{}  {:>7}  {}",
+                        round_trip, exchange.retransmissions, exchange.key
+                    );
+                    let mut text = egui::RichText::new(text).monospace();
+                    if exchange.response.is_none() {
+                        text = text.color(ui.visuals().warn_fg_color);
+                    }
+                    ui.label(text);
+                });
+            }
+        });
+
+    if let Some(i) = clicked {
+        select_row(app, i);
+    }
+}
+
+/// The sequence number checks of the packets, listing the gaps, duplicates, wrap-arounds and
+/// steps back found with their times
+fn sequences(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let decoded = app.capture.decoding.is_none();
+    let check = ui
+        .add_enabled(decoded, egui::Button::new(tr!("capture-check")))
+        .on_hover_text(tr!("capture-check-hint"))
+        .on_disabled_hover_text(tr!("capture-wait-decoding"))
+        .clicked();
+    if check {
+        let packets: Vec<_> = app
+            .capture
+            .rows
+            .iter()
+            .map(|r| {
+                let decoded = r.decoded.as_ref().and_then(|d| d.as_ref().ok());
+                (r.packet.timestamp, decoded)
+            })
+            .collect();
+        let reports = check_sequences(&app.registry, &packets);
+        app.capture.sequences = Some(reports);
+    }
+    ui.separator();
+
+    let capture = &app.capture;
+    let Some(reports) = &capture.sequences else {
+        ui.weak(tr!("capture-check-prompt"));
+        return;
+    };
+    if reports.is_empty() {
+        ui.weak(tr!("capture-no-sequence"));
+        return;
+    }
+    let start = capture.rows.first().map(|r| r.packet.timestamp);
+    for report in reports {
+        let count = |kind: fn(&SequenceEvent) -> bool| report.count(kind);
+        ui.label(tr!(
+            "capture-sequence-summary",
+            field = format!("{}.{}", report.protocol_id, report.field_id),
+            packets = report.packets,
+            missing = report.missing.to_string(),
+            gaps = count(|e| matches!(e, SequenceEvent::Gap { .. })),
+            duplicates = count(|e| matches!(e, SequenceEvent::Duplicate { .. })),
+            wraps = count(|e| matches!(e, SequenceEvent::WrapAround { .. })),
+            back = count(|e| matches!(e, SequenceEvent::Backwards { .. })),
+        ));
+    }
+
+    let issues: Vec<(&SequenceReport, usize)> = reports
+        .iter()
+        .flat_map(|r| (0..r.issues.len()).map(move |i| (r, i)))
+        .collect();
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    ui.monospace(format!(
+        "{:>6}  {:>12}  {:>10}  {}",
+        tr!("capture-column-number"),
+        tr!("capture-column-time"),
+        tr!("capture-column-after"),
+        tr!("capture-column-event")
+    ));
+    let selected = capture.selected;
+    let mut clicked = None;
+    egui::ScrollArea::vertical()
+        .id_salt("sequence_issues")
+        .auto_shrink(false)
+        .show_rows(ui, row_height, issues.len(), |ui, range| {
+            for &(report, i) in &issues[range] {
+                let issue = &report.issues[i];
+                let time = issue.timestamp.saturating_sub(start.unwrap_or_default());
+                let mut text = format!(
+                    "{:>6}  {:>12.6}  {:>10.3}  ",
+                    issue.packet + 1,
+                    time.as_secs_f64(),
+                    issue.since_previous.as_secs_f64() * 1000.0
+                );
+                if reports.len() > 1 {
+                    text += &format!("{}: ", report.protocol_id);
+                }
+                text += &issue.event.description();
+                let mut text = egui::RichText::new(text).monospace();
+                if !matches!(issue.event, SequenceEvent::WrapAround { .. }) {
+                    text = text.color(ui.visuals().warn_fg_color);
+                }
+                if ui
+                    .selectable_label(selected == Some(issue.packet), text)
+                    .clicked()
+                {
+                    clicked = Some(issue.packet);
+                }
+            }
+        });
+
+    if let Some(i) = clicked {
+        select_row(app, i);
+    }
+}
+
+/// The times between the packets of each protocol they decoded as, with a histogram of them
+/// and how many are off the period the protocol is meant to come at
+fn timing(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let decoded = app.capture.decoding.is_none();
+    let mut measure = false;
+    ui.horizontal(|ui| {
+        measure = ui
+            .add_enabled(decoded, egui::Button::new(tr!("capture-measure")))
+            .on_hover_text(tr!("capture-measure-hint"))
+            .on_disabled_hover_text(tr!("capture-wait-decoding"))
+            .clicked();
+        ui.label(tr!("capture-tolerance"));
+        ui.add(
+            egui::DragValue::new(&mut app.capture.period_tolerance)
+                .range(0.0..=100.0)
+                .speed(0.5)
+                .suffix(" %"),
+        )
+        .on_hover_text(tr!("capture-tolerance-hint"));
+    });
+    if measure {
+        let packets: Vec<_> = app
+            .capture
+            .rows
+            .iter()
+            .map(|r| {
+                let decoded = r.decoded.as_ref().and_then(|d| d.as_ref().ok());
+                (r.packet.timestamp, decoded)
+            })
+            .collect();
+        app.capture.timing = Some(inter_arrival(&packets));
+    }
+    ui.separator();
+
+    let capture = &mut app.capture;
+    let Some(stats) = &capture.timing else {
+        ui.weak(tr!("capture-measure-prompt"));
+        return;
+    };
+    if stats.is_empty() {
+        ui.weak(tr!("capture-nothing-to-time"));
+        return;
+    }
+    egui::ScrollArea::vertical()
+        .id_salt("timing")
+        .auto_shrink(false)
+        .show(ui, |ui| {
+            for s in stats {
+                ui.strong(&s.protocol_id);
+                ui.label(tr!(
+                    "capture-timing-summary",
+                    packets = s.packets,
+                    min = format!("{:.3}", s.min * 1000.0),
+                    mean = format!("{:.3}", s.mean * 1000.0),
+                    max = format!("{:.3}", s.max * 1000.0),
+                    jitter = format!("{:.3}", s.jitter * 1000.0),
+                    rate = format!("{:.2}", if s.mean > 0.0 { 1.0 / s.mean } else { 0.0 })
+                ));
+                let expected = capture.expected_periods.get(&s.protocol_id).copied();
+                ui.horizontal(|ui| {
+                    let mut enabled = expected.is_some();
+                    let mut period = expected.unwrap_or((s.mean * 1000.0).max(0.001));
+                    let checkbox = ui.checkbox(&mut enabled, tr!("capture-expected-every"));
+                    let drag = ui.add_enabled(
+                        enabled,
+                        egui::DragValue::new(&mut period)
+                            .range(0.001..=f64::MAX)
+                            .speed(0.1)
+                            .suffix(" ms"),
+                    );
+                    if (checkbox.changed() || drag.changed()) && enabled {
+                        capture
+                            .expected_periods
+                            .insert(s.protocol_id.clone(), period);
+                    } else if checkbox.changed() {
+                        capture.expected_periods.remove(&s.protocol_id);
+                    }
+                    if let Some(period) = expected {
+                        let tolerance = period * capture.period_tolerance / 100.0;
+                        let outside = s.outside(period / 1000.0, tolerance / 1000.0);
+                        let text = tr!(
+                            "capture-off-period",
+                            outside = outside,
+                            total = s.intervals.len(),
+                            tolerance = format!("{:.3}", tolerance)
+                        );
+                        if outside > 0 {
+                            ui.colored_label(ui.visuals().warn_fg_color, text);
+                        } else {
+                            ui.label(text);
+                        }
+                    }
+                });
+                histogram(ui, s, expected.map(|p| p / 1000.0));
+                ui.add_space(8.0);
+            }
+        });
+}
+
+/// Bars of how many intervals fall in each range from the shortest to the longest, with a line
+/// at the expected period in seconds if it is in that range
+fn histogram(ui: &mut egui::Ui, stats: &TimingStats, expected: Option<f64>) {
+    let counts = stats.histogram(TIMING_BINS);
+    let size = egui::vec2(ui.available_width().min(600.0), HISTOGRAM_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+    let most = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let bar_width = rect.width() / counts.len() as f32;
+    for (bin, &count) in counts.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let x = rect.left() + bin as f32 * bar_width;
+        let height = (count as f32 / most * rect.height()).max(1.0);
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(x, rect.bottom() - height),
+                egui::pos2(x + (bar_width - 1.0).max(1.0), rect.bottom()),
+            ),
+            0.0,
+            visuals.selection.bg_fill,
+        );
+    }
+    let range = stats.max - stats.min;
+    if let Some(period) = expected
+        && range > 0.0
+        && (stats.min..=stats.max).contains(&period)
+    {
+        let x = rect.left() + ((period - stats.min) / range) as f32 * rect.width();
+        painter.vline(
+            x,
+            rect.y_range(),
+            egui::Stroke::new(1.0, visuals.warn_fg_color),
+        );
+    }
+
+    if let Some(pos) = response.hover_pos() {
+        let bin = (((pos.x - rect.left()) / bar_width) as usize).min(counts.len() - 1);
+        let width = range / counts.len() as f64;
+        let from = stats.min + bin as f64 * width;
+        response.on_hover_text_at_pointer(tr!(
+            "capture-histogram-hint",
+            from = format!("{:.3}", from * 1000.0),
+            to = format!("{:.3}", (from + width) * 1000.0),
+            count = counts[bin]
+        ));
+    }
+}
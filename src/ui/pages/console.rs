@@ -0,0 +1,96 @@
+use crate::app::BitLoomApp;
+use crate::ui::expr_editor::highlight;
+use bitloom::script::console::CONSOLE_API;
+use bitloom::tr;
+use eframe::egui;
+
+/// Interactive rhai console working on the loaded project and the current packet
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let run_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Enter);
+        let mut run = ui.input_mut(|i| i.consume_shortcut(&run_shortcut));
+
+        ui.horizontal(|ui| {
+            ui.strong(tr!("console-title"));
+            ui.label(tr!("console-intro"));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button(tr!("console-clear")).clicked() {
+                    app.console_log.clear();
+                }
+                if ui
+                    .button(tr!("console-reset"))
+                    .on_hover_text(tr!("console-reset-hint"))
+                    .clicked()
+                {
+                    app.console.reset();
+                }
+                ui.menu_button(tr!("console-api"), |ui| {
+                    egui::Grid::new("console_api")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            for (signature, description) in CONSOLE_API {
+                                ui.monospace(*signature);
+                                ui.label(tr!(description));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        });
+        ui.separator();
+
+        let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+            let mut job = highlight(ui, text.as_str(), None);
+            job.wrap.max_width = wrap_width;
+            ui.fonts_mut(|f| f.layout_job(job))
+        };
+        egui::TopBottomPanel::bottom("console_input")
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut app.console_input)
+                        .code_editor()
+                        .desired_rows(4)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("for id in protocols() { print(id) }")
+                        .layouter(&mut layouter),
+                );
+                run |= ui
+                    .button(tr!("console-run"))
+                    .on_hover_text(ctx.format_shortcut(&run_shortcut))
+                    .clicked();
+            });
+
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (command, output) in &app.console_log {
+                    ui.monospace(format!("> {}", command.trim()));
+                    for line in &output.printed {
+                        ui.monospace(line);
+                    }
+                    match &output.result {
+                        Ok(value) if value.is_empty() => {}
+                        Ok(value) => {
+                            ui.monospace(value);
+                        }
+                        Err(e) => {
+                            ui.colored_label(ui.visuals().error_fg_color, e);
+                        }
+                    }
+                }
+            });
+
+        if run && !app.console_input.trim().is_empty() {
+            let command = std::mem::take(&mut app.console_input);
+            let output = app.console.run(
+                &mut app.registry,
+                &mut app.script_engine,
+                app.decoded.as_ref(),
+                &command,
+            );
+            app.console_log.push((command, output));
+        }
+    });
+}
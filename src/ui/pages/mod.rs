@@ -1 +1,5 @@
+pub mod capture;
+pub mod console;
+pub mod packet_builder;
 pub mod protocol_designer;
+pub mod script_library;
@@ -0,0 +1,664 @@
+use crate::app::BitLoomApp;
+use crate::ui::export_dialog::PendingExport;
+use crate::ui::widgets;
+use bitloom::codec::Value;
+use bitloom::codec::compiled::CompiledCodec;
+use bitloom::codec::json::{values_from_json, values_to_json};
+use bitloom::models::field::{FieldLength, FieldRule, FieldType};
+use bitloom::models::preset::{
+    PacketPreset, duplicate_preset, move_preset, parse_pasted_values, presets_by_folder,
+    save_preset,
+};
+use bitloom::tr;
+use bitloom::transport::{Transport, TransportConfig};
+use eframe::egui;
+use std::collections::{HashMap, HashSet};
+
+/// How long the bytes of an edited field stay highlighted in the hex view, in seconds
+pub const FLASH_SECONDS: f64 = 1.0;
+
+/// Field values being entered to build a packet of the selected protocol
+pub struct BuilderState {
+    /// literal text of each field value by field ID, as `Value::parse_literal` reads it
+    pub inputs: HashMap<String, String>,
+    /// why the values could not be encoded
+    pub error: Option<String>,
+    /// IDs of the fields whose rules are overridden, so values breaking them are encoded
+    pub ignore_rules: HashSet<String>,
+    /// whether the user confirmed sending packets with overridden fields
+    pub send_overridden: bool,
+    /// the field edited last, with the time of the edit
+    pub flash: Option<(String, f64)>,
+    /// name and folder the current values are saved as a preset under
+    pub preset_name: String,
+    pub preset_folder: String,
+    /// preset being moved, with the folder being typed for it
+    pub moving: Option<(usize, String)>,
+    /// JSON file to load field values from
+    pub values_path: String,
+    /// field values pasted from a spreadsheet, not applied yet
+    pub pasted: String,
+    /// where built packets are sent
+    pub transport: TransportConfig,
+    pub connection: Option<Box<dyn Transport>>,
+}
+
+impl Default for BuilderState {
+    fn default() -> Self {
+        Self {
+            inputs: HashMap::new(),
+            error: None,
+            ignore_rules: HashSet::new(),
+            send_overridden: false,
+            flash: None,
+            preset_name: String::new(),
+            preset_folder: String::new(),
+            moving: None,
+            values_path: String::new(),
+            pasted: String::new(),
+            transport: TransportConfig::Udp {
+                bind: "0.0.0.0:0".to_string(),
+                remote: "127.0.0.1:5000".to_string(),
+            },
+            connection: None,
+        }
+    }
+}
+
+/// Value a new field input starts with
+fn initial_input(rule: &FieldRule) -> String {
+    match (&rule.field_type, &rule.length) {
+        (_, FieldLength::Variable) => "[]".to_string(),
+        (FieldType::Enum(variants), _) => variants
+            .first()
+            .map_or("0".to_string(), |v| v.value.to_string()),
+        (FieldType::Range { min, .. }, _) => min.to_string(),
+        _ => "0".to_string(),
+    }
+}
+
+/// Whether a value is entered for the field, rather than fixed or computed
+fn takes_value(field: &FieldRule) -> bool {
+    !matches!(field.field_type, FieldType::Fixed(_) | FieldType::Expr(_)) && !field.is_virtual()
+}
+
+impl BuilderState {
+    /// The values entered for the fields that take one, keyed by field ID
+    fn values(&self, fields: &[FieldRule]) -> Result<HashMap<String, Value>, String> {
+        let mut values = HashMap::new();
+        for field in fields {
+            let Some(text) = self.inputs.get(&field.id) else {
+                continue;
+            };
+            if !takes_value(field) {
+                continue;
+            }
+            let value = Value::parse_literal(text).map_err(|e| {
+                tr!(
+                    "builder-invalid-value",
+                    field = field.id.as_str(),
+                    error = e
+                )
+            })?;
+            values.insert(field.id.clone(), value);
+        }
+        Ok(values)
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    egui::SidePanel::left("presets")
+        .resizable(true)
+        .default_width(180.0)
+        .show(ctx, |ui| presets(app, ui));
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let Some(protocol_id) = app.selected_protocol.clone() else {
+            ui.label(tr!("builder-select-protocol"));
+            return;
+        };
+        let fields = app
+            .registry
+            .resolve_fields(&protocol_id)
+            .unwrap_or_default();
+        for field in &fields {
+            app.builder
+                .inputs
+                .entry(field.id.clone())
+                .or_insert_with(|| initial_input(field));
+        }
+
+        values_file(app, ui, &protocol_id, &fields);
+        paste_values(app, ui, &fields);
+        send_controls(app, ui, &fields);
+        override_banner(app, ui, &fields);
+        ui.separator();
+
+        let mut edited = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                egui::Grid::new("builder_fields")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong(tr!("builder-id"));
+                        ui.strong(tr!("builder-type"));
+                        ui.strong(tr!("builder-value"));
+                        ui.strong(tr!("builder-override"));
+                        ui.end_row();
+
+                        for field in fields.iter().filter(|f| !f.is_virtual()) {
+                            let overridden = app.builder.ignore_rules.contains(&field.id);
+                            let label = if overridden {
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    format!("⚠ {}", field.id),
+                                )
+                                .on_hover_text(tr!("builder-overridden-hint"))
+                            } else {
+                                ui.label(&field.id)
+                            };
+                            ui.label(widgets::field_type_label(&field.field_type));
+                            let input = app.builder.inputs.entry(field.id.clone()).or_default();
+                            if value_input(ui, field, input, label.id) {
+                                edited = Some(field.id.clone());
+                            }
+                            override_toggle(app, ui, field, overridden);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some(id) = edited {
+            app.builder.flash = Some((id, ui.input(|i| i.time)));
+        }
+        // also picks up changes made to the protocol since the last frame
+        encode_preview(app, &protocol_id, &fields);
+
+        if let Some(error) = &app.builder.error {
+            ui.separator();
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    });
+}
+
+/// Load the field values from a JSON file, or export them as one
+fn values_file(app: &mut BitLoomApp, ui: &mut egui::Ui, protocol_id: &str, fields: &[FieldRule]) {
+    ui.horizontal(|ui| {
+        ui.label(tr!("builder-values-json"));
+        ui.text_edit_singleline(&mut app.builder.values_path);
+        if ui
+            .button(tr!("builder-load"))
+            .on_hover_text(tr!("builder-load-hint"))
+            .clicked()
+        {
+            let result = load_values(app);
+            app.report(result);
+        }
+        if ui.button(tr!("builder-export")).clicked() {
+            let result = app.builder.values(fields);
+            if let Some(values) = app.report(result) {
+                app.pending_export = Some(PendingExport::new(
+                    &tr!("builder-export-title"),
+                    &format!("{}.values.json", protocol_id),
+                    values_to_json(&values),
+                ));
+            }
+        }
+    });
+}
+
+/// Fill many fields at once with values pasted from a spreadsheet, previewing the changes
+fn paste_values(app: &mut BitLoomApp, ui: &mut egui::Ui, fields: &[FieldRule]) {
+    egui::CollapsingHeader::new(tr!("builder-paste-values"))
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.add(
+                egui::TextEdit::multiline(&mut app.builder.pasted)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_rows(4)
+                    .hint_text(tr!("builder-paste-hint")),
+            );
+            if app.builder.pasted.trim().is_empty() {
+                return;
+            }
+            let field_ids: Vec<&str> = fields
+                .iter()
+                .filter(|f| takes_value(f))
+                .map(|f| f.id.as_str())
+                .collect();
+            let pasted = match parse_pasted_values(&app.builder.pasted, &field_ids) {
+                Ok(pasted) => pasted,
+                Err(e) => {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                    return;
+                }
+            };
+
+            let mut valid = true;
+            egui::Grid::new("paste_preview")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(tr!("builder-id"));
+                    ui.strong(tr!("builder-current"));
+                    ui.strong(tr!("builder-pasted"));
+                    ui.end_row();
+                    for (id, value) in &pasted {
+                        ui.label(id);
+                        ui.monospace(app.builder.inputs.get(id).map_or("", |v| v.as_str()));
+                        match Value::parse_literal(value) {
+                            Ok(_) => {
+                                ui.monospace(value);
+                            }
+                            Err(e) => {
+                                valid = false;
+                                ui.colored_label(ui.visuals().error_fg_color, value)
+                                    .on_hover_text(e);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(valid, egui::Button::new(tr!("dialog-apply")))
+                    .on_disabled_hover_text(tr!("builder-fix-invalid"))
+                    .clicked()
+                {
+                    app.builder.inputs.extend(pasted);
+                    app.builder.pasted.clear();
+                }
+                if ui.button(tr!("builder-discard")).clicked() {
+                    app.builder.pasted.clear();
+                }
+            });
+        });
+}
+
+fn load_values(app: &mut BitLoomApp) -> Result<(), String> {
+    let path = app.builder.values_path.trim();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| tr!("builder-read-failed", path = path, error = e.to_string()))?;
+    let values = values_from_json(&text)?;
+    app.builder
+        .inputs
+        .extend(values.into_iter().map(|(id, value)| (id, value.literal())));
+    Ok(())
+}
+
+/// Checkbox overriding the validation of a field, for fields that have rules to break
+fn override_toggle(app: &mut BitLoomApp, ui: &mut egui::Ui, field: &FieldRule, overridden: bool) {
+    if matches!(field.field_type, FieldType::Fixed(_) | FieldType::Expr(_)) {
+        ui.label("");
+        return;
+    }
+    let mut checked = overridden;
+    let response = ui
+        .checkbox(&mut checked, "")
+        .on_hover_text(tr!("builder-override-hint"));
+    response.widget_info(|| {
+        let name = tr!("builder-override-name", field = field.id.as_str());
+        egui::WidgetInfo::selected(egui::WidgetType::Checkbox, true, checked, name)
+    });
+    if checked != overridden {
+        if checked {
+            app.builder.ignore_rules.insert(field.id.clone());
+        } else {
+            app.builder.ignore_rules.remove(&field.id);
+        }
+        // every change to the overridden fields needs a new confirmation before sending
+        app.builder.send_overridden = false;
+    }
+}
+
+/// IDs of the fields of the protocol whose validation is overridden, in field order
+fn overridden_fields<'a>(app: &BitLoomApp, fields: &'a [FieldRule]) -> Vec<&'a str> {
+    fields
+        .iter()
+        .filter(|f| !f.is_virtual() && app.builder.ignore_rules.contains(&f.id))
+        .map(|f| f.id.as_str())
+        .collect()
+}
+
+/// Warning listing the fields whose validation is overridden
+fn override_banner(app: &mut BitLoomApp, ui: &mut egui::Ui, fields: &[FieldRule]) {
+    let overridden = overridden_fields(app, fields);
+    if overridden.is_empty() {
+        return;
+    }
+    let warn = ui.visuals().warn_fg_color;
+    egui::Frame::group(ui.style())
+        .stroke(egui::Stroke::new(1.0, warn))
+        .show(ui, |ui| {
+            ui.colored_label(
+                warn,
+                tr!("builder-overridden-banner", fields = overridden.join(", ")),
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut app.builder.send_overridden,
+                    tr!("builder-allow-sending"),
+                );
+                if ui.button(tr!("builder-clear-overrides")).clicked() {
+                    app.builder.ignore_rules.clear();
+                    app.builder.send_overridden = false;
+                }
+            });
+        });
+}
+
+/// Transport settings, connecting and sending the built packet. A SocketCAN packet starts with
+/// the CAN ID, so a CAN protocol is sent as is with its first field as the ID.
+fn send_controls(app: &mut BitLoomApp, ui: &mut egui::Ui, fields: &[FieldRule]) {
+    egui::CollapsingHeader::new(tr!("builder-send-section"))
+        .default_open(false)
+        .show(ui, |ui| {
+            let connected = app.builder.connection.is_some();
+            ui.add_enabled_ui(!connected, |ui| {
+                egui::Grid::new("builder_transport")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        widgets::transport_settings(
+                            ui,
+                            "builder_transport_kind",
+                            &mut app.builder.transport,
+                            Some(&tr!("builder-send-to")),
+                        );
+                    });
+            });
+            ui.horizontal(|ui| {
+                if connected {
+                    if ui.button(tr!("builder-disconnect")).clicked() {
+                        app.builder.connection = None;
+                    }
+                } else if ui.button(tr!("builder-connect")).clicked() {
+                    let result = app.builder.transport.open();
+                    app.builder.connection = app.report(result);
+                }
+                let confirmed =
+                    app.builder.send_overridden || overridden_fields(app, fields).is_empty();
+                let can_send = connected && app.builder.error.is_none() && confirmed;
+                if ui
+                    .add_enabled(can_send, egui::Button::new(tr!("builder-send")))
+                    .on_disabled_hover_text(if confirmed {
+                        tr!("builder-send-disabled")
+                    } else {
+                        tr!("builder-send-unconfirmed")
+                    })
+                    .clicked()
+                    && let Some(connection) = &mut app.builder.connection
+                {
+                    let result = connection.send(&app.packet_data);
+                    app.report(result);
+                }
+            });
+        });
+}
+
+/// Input for the value of a field, named for screen readers by the label with ID `label_id`.
+/// Returns whether it was changed.
+fn value_input(
+    ui: &mut egui::Ui,
+    field: &FieldRule,
+    input: &mut String,
+    label_id: egui::Id,
+) -> bool {
+    match &field.field_type {
+        FieldType::Fixed(value) => {
+            ui.label(value.to_string());
+            false
+        }
+        FieldType::Expr(_) => {
+            ui.weak(tr!("builder-computed"));
+            false
+        }
+        field_type => {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                let parsed = Value::parse_literal(input);
+                let mut edit = egui::TextEdit::singleline(input)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(160.0);
+                if let Err(e) = &parsed {
+                    edit = edit.text_color(ui.visuals().error_fg_color);
+                    changed |= ui
+                        .add(edit)
+                        .labelled_by(label_id)
+                        .on_hover_text(e)
+                        .changed();
+                } else {
+                    let mut response = ui.add(edit).labelled_by(label_id);
+                    if let Ok(Value::Int(v)) = &parsed
+                        && input.trim() != v.to_string()
+                    {
+                        response = response.on_hover_text(widgets::int_notations(*v));
+                    }
+                    changed |= response.changed();
+                }
+
+                if let FieldType::Enum(variants) = field_type {
+                    let current = parsed.ok();
+                    let name = variants
+                        .iter()
+                        .find(|v| current == Some(Value::Int(v.value)))
+                        .and_then(|v| v.name.as_deref())
+                        .unwrap_or("");
+                    let variant_picker = egui::ComboBox::from_id_salt(("variant", &field.id))
+                        .selected_text(name)
+                        .show_ui(ui, |ui| {
+                            for variant in variants {
+                                let label = format!(
+                                    "{} = {}",
+                                    variant.value,
+                                    variant.name.as_deref().unwrap_or("")
+                                );
+                                let selected = current == Some(Value::Int(variant.value));
+                                if ui.selectable_label(selected, label).clicked() {
+                                    *input = variant.value.to_string();
+                                    changed = true;
+                                }
+                            }
+                        });
+                    variant_picker.response.widget_info(|| {
+                        let name = tr!("builder-variant-name", field = field.id.as_str());
+                        egui::WidgetInfo::labeled(egui::WidgetType::ComboBox, true, name)
+                    });
+                }
+            });
+            changed
+        }
+    }
+}
+
+/// Encode the entered values and show the packet in the hex view and inspector. The rules of
+/// overridden fields only give warnings, both when encoding and when decoding the packet.
+fn encode_preview(app: &mut BitLoomApp, protocol_id: &str, fields: &[FieldRule]) {
+    let result = CompiledCodec::compile(&app.registry, &app.script_engine, protocol_id).and_then(
+        |mut codec| {
+            codec.ignore_rules(&app.builder.ignore_rules);
+            let values = app.builder.values(fields)?;
+            let bytes = codec.encode(&app.script_engine, &values)?;
+            Ok((codec, bytes))
+        },
+    );
+    match result {
+        Ok((codec, bytes)) => {
+            app.builder.error = None;
+            match app.order_preview.decode(codec, &app.script_engine, &bytes) {
+                Ok(packet) => {
+                    app.decoded = Some(packet);
+                    app.decode_error = None;
+                }
+                Err(e) => {
+                    app.decoded = None;
+                    app.decode_error = Some(*e);
+                }
+            }
+            app.packet_data = bytes;
+        }
+        Err(e) => app.builder.error = Some(e),
+    }
+}
+
+/// Presets of the project by folder, and saving the current values as one
+fn presets(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    ui.strong(tr!("builder-presets"));
+    ui.separator();
+
+    ui.add_enabled_ui(app.selected_protocol.is_some(), |ui| {
+        egui::Grid::new("save_preset")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr!("builder-preset-name"));
+                ui.text_edit_singleline(&mut app.builder.preset_name);
+                ui.end_row();
+                ui.label(tr!("builder-folder"));
+                ui.text_edit_singleline(&mut app.builder.preset_folder)
+                    .on_hover_text(tr!("builder-folder-hint"));
+                ui.end_row();
+            });
+        if ui
+            .button(tr!("builder-save"))
+            .on_hover_text(tr!("builder-save-hint"))
+            .clicked()
+        {
+            let result = save_current(app);
+            app.report(result);
+        }
+    });
+    // notes of the preset the values were loaded from or saved as
+    if let Some(preset) = app
+        .presets
+        .iter_mut()
+        .find(|p| p.name == app.builder.preset_name && p.folder == app.builder.preset_folder)
+    {
+        egui::CollapsingHeader::new(tr!("builder-notes"))
+            .id_salt("preset_notes")
+            .default_open(!preset.notes.is_empty())
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut preset.notes)
+                        .desired_rows(3)
+                        .hint_text(tr!("builder-notes-hint")),
+                );
+            });
+    }
+
+    ui.separator();
+
+    let mut load = None;
+    let mut duplicate = None;
+    let mut remove = None;
+    let mut moved = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (folder, indices) in presets_by_folder(&app.presets) {
+            let list = |ui: &mut egui::Ui| {
+                for i in indices {
+                    let preset = &app.presets[i];
+                    let selected = app.builder.preset_name == preset.name
+                        && app.builder.preset_folder == preset.folder;
+                    let mut hover = tr!(
+                        "builder-preset-hint",
+                        protocol = preset.protocol_id.as_str()
+                    );
+                    if !preset.notes.is_empty() {
+                        hover = format!("{}\n\n{}", hover, preset.notes);
+                    }
+                    let response = ui
+                        .selectable_label(selected, &preset.name)
+                        .on_hover_text(hover);
+                    if response.clicked() {
+                        load = Some(i);
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button(tr!("builder-duplicate")).clicked() {
+                            duplicate = Some(i);
+                            ui.close();
+                        }
+                        if ui.button(tr!("builder-move-to-folder")).clicked() {
+                            app.builder.moving = Some((i, preset.folder.clone()));
+                            ui.close();
+                        }
+                        if ui.button(tr!("builder-delete")).clicked() {
+                            remove = Some(i);
+                            ui.close();
+                        }
+                    });
+                    if let Some((index, target)) = &mut app.builder.moving
+                        && *index == i
+                    {
+                        ui.horizontal(|ui| {
+                            let response = ui.text_edit_singleline(target);
+                            if ui.small_button(tr!("builder-move")).clicked()
+                                || response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                            {
+                                moved = Some((i, target.clone()));
+                            }
+                        });
+                    }
+                }
+            };
+            if folder.is_empty() {
+                list(ui);
+            } else {
+                egui::CollapsingHeader::new(folder)
+                    .default_open(true)
+                    .show(ui, list);
+            }
+        }
+    });
+
+    if let Some(i) = load {
+        let preset = &app.presets[i];
+        app.selected_protocol = Some(preset.protocol_id.clone());
+        app.builder.preset_name = preset.name.clone();
+        app.builder.preset_folder = preset.folder.clone();
+        app.builder
+            .inputs
+            .extend(preset.values.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    if let Some(i) = duplicate {
+        let result = duplicate_preset(&mut app.presets, i);
+        app.report(result);
+    }
+    if let Some((i, folder)) = moved {
+        let result = move_preset(&mut app.presets, i, &folder);
+        if app.report(result).is_some() {
+            app.builder.moving = None;
+        }
+    }
+    if let Some(i) = remove {
+        app.presets.remove(i);
+        app.builder.moving = None;
+    }
+}
+
+/// Save the values entered for the selected protocol as a preset
+fn save_current(app: &mut BitLoomApp) -> Result<(), String> {
+    let protocol_id = app
+        .selected_protocol
+        .clone()
+        .ok_or_else(|| tr!("builder-select-protocol-first"))?;
+    let fields = app.registry.resolve_fields(&protocol_id)?;
+    let values = fields
+        .iter()
+        .filter_map(|f| Some((f.id.clone(), app.builder.inputs.get(&f.id)?.clone())))
+        .collect();
+    let preset = PacketPreset {
+        name: app.builder.preset_name.trim().to_string(),
+        folder: app
+            .builder
+            .preset_folder
+            .trim()
+            .trim_matches('/')
+            .to_string(),
+        protocol_id,
+        values,
+        notes: String::new(),
+    };
+    save_preset(&mut app.presets, preset)?;
+    Ok(())
+}
@@ -1,6 +1,1322 @@
 use crate::app::BitLoomApp;
-use eframe::egui;
+use crate::ui::expr_editor;
+use crate::ui::field_editor::FieldEditor;
+use crate::ui::theme::text_color_on;
+use crate::ui::widgets::{
+    RowKey, color_swatch, endianness_label, field_type_label, name_row, row_key, severity_label,
+};
+use bitloom::models::constraints::{ConstraintMatrix, constraint_matrix};
+use bitloom::models::field::{FieldLength, FieldRule};
+use bitloom::models::protocol::{
+    Endianness, FieldReference, LengthField, PacketValidator, Protocol, ProtocolLength,
+    ProtocolRegistry, Severity,
+};
+use bitloom::script::ScriptEngine;
+use bitloom::tr;
+use eframe::egui::{self, Color32};
+use std::collections::{HashMap, HashSet};
 
-pub fn show(_app: &mut BitLoomApp, ctx: &egui::Context) {
-    egui::CentralPanel::default().show(ctx, |ui| ui.label("Filed table here"));
+/// Bits per row of the layout diagram, as in RFC packet diagrams
+const DIAGRAM_ROW_BITS: usize = 32;
+/// Height of the layout diagram before it scrolls
+const DIAGRAM_HEIGHT: f32 = 240.0;
+const MINIMAP_WIDTH: f32 = 48.0;
+
+/// Zoom and position of the layout diagram
+pub struct LayoutView {
+    /// scale of the diagram, 1 fitting a row of 32 bits in the width of the page
+    pub zoom: f32,
+    /// text typed to jump to a field
+    pub jump: String,
+    /// field the diagram scrolls to on the next frame
+    pub reveal: Option<String>,
+    /// vertical scroll offset the minimap moves the diagram to on the next frame
+    pub scroll_to: Option<f32>,
+}
+
+impl Default for LayoutView {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            jump: String::new(),
+            reveal: None,
+            scroll_to: None,
+        }
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let Some(proto) = app
+            .selected_protocol
+            .as_deref()
+            .and_then(|id| app.registry.get_protocol(id))
+        else {
+            ui.label(tr!("designer-select-protocol"));
+            return;
+        };
+
+        // the selected fields of this protocol, in field order
+        let selection: Vec<String> = proto
+            .fields
+            .iter()
+            .filter(|f| {
+                app.selected_fields.contains(&f.id)
+                    || app.selected_field.as_deref() == Some(f.id.as_str())
+            })
+            .map(|f| f.id.clone())
+            .collect();
+
+        let mut open_editor = None;
+        let mut bulk_edit = None;
+        let mut clicked = None;
+        let mut copy = false;
+        let mut paste = None;
+        ui.horizontal(|ui| {
+            if ui.button(tr!("designer-add-field")).clicked() {
+                open_editor = Some(FieldEditor::add(&proto.id));
+            }
+            if ui
+                .add_enabled(
+                    !selection.is_empty(),
+                    egui::Button::new(tr!("designer-copy")),
+                )
+                .on_hover_text(tr!("designer-copy-hint"))
+                .clicked()
+            {
+                copy = true;
+            }
+            if ui
+                .add_enabled(
+                    !app.field_clipboard.is_empty(),
+                    egui::Button::new(tr!("designer-paste")),
+                )
+                .on_hover_text(tr!(
+                    "designer-paste-hint",
+                    count = app.field_clipboard.len()
+                ))
+                .clicked()
+            {
+                paste = Some(app.field_clipboard.clone());
+            }
+            if !selection.is_empty() {
+                ui.separator();
+                bulk_edit = bulk_toolbar(ui, selection.len());
+            }
+        });
+        ui.separator();
+
+        // the shortcuts, unless they are meant for a text input
+        if ui.memory(|m| m.focused().is_none()) {
+            ui.input(|i| {
+                for event in &i.events {
+                    match event {
+                        egui::Event::Copy => copy |= !selection.is_empty(),
+                        // fields copied in another instance of the app come as JSON
+                        egui::Event::Paste(text) => {
+                            if let Ok(fields) = serde_json::from_str::<Vec<FieldRule>>(text) {
+                                paste = Some(fields);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            });
+        }
+
+        let mut lengths = (proto.max_length, proto.frame_length);
+        let lengths_changed = length_summary(ui, &app.registry, proto, &mut lengths);
+        let mut length_field = proto.length_field.clone();
+        let length_field_changed = length_field_input(ui, &app.registry, proto, &mut length_field);
+        let mut sequence_field = proto.sequence_field.clone();
+        let sequence_field_changed =
+            sequence_field_input(ui, &app.registry, proto, &mut sequence_field);
+        if let Some((protocol, field)) = parent_constraints(ui, &app.registry, proto) {
+            go_to_field(app, protocol, field);
+            return;
+        }
+        ui.separator();
+
+        let colors = app.appearance.field_colors(&app.registry, &proto.id);
+        let layout = app.registry.layout(&proto.id).unwrap_or_default();
+        let jumped = layout_toolbar(ui, &mut app.layout_view, &layout.fields);
+        let diagram = layout_diagram(
+            ui,
+            &mut app.layout_view,
+            &layout.fields,
+            &colors,
+            app.selected_field.as_deref(),
+        );
+        if let Some(id) = jumped.or(diagram) {
+            app.selected_fields = HashSet::from([id.clone()]);
+            app.selected_field = Some(id);
+        }
+        ui.separator();
+
+        let offsets = &layout.offsets;
+        if let Some((protocol, field)) =
+            inherited_fields(ui, &app.registry, &proto.id, &colors, offsets)
+        {
+            go_to_field(app, protocol, field);
+            return;
+        }
+        let mut child = None;
+        let mut row_ids = Vec::new();
+        let mut stepped = None;
+        egui::Grid::new("field_table")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong(tr!("designer-id"));
+                ui.strong(tr!("designer-type"));
+                ui.strong(tr!("designer-length"));
+                ui.strong(tr!("designer-bit-offset"));
+                ui.strong(tr!("designer-byte-offset"));
+                ui.end_row();
+
+                for (i, field) in proto.fields.iter().enumerate() {
+                    let selected = selection.contains(&field.id);
+                    let length = length_str(field);
+                    let response = ui
+                        .horizontal(|ui| {
+                            let color = colors.get(&field.id).copied();
+                            color_swatch(ui, color.unwrap_or(Color32::TRANSPARENT));
+                            let label = ui.selectable_label(selected, &field.id);
+                            if let Some(id) =
+                                constrained_by_children(ui, &app.registry, &proto.id, &field.id)
+                            {
+                                child = Some(id);
+                            }
+                            label
+                        })
+                        .inner
+                        .on_hover_text(tr!("designer-row-hint"));
+                    let (id, kind) = (field.id.as_str(), field_type_label(&field.field_type));
+                    let name = match offsets.get(&field.id) {
+                        Some(offset) => tr!(
+                            "designer-row-name-offset",
+                            field = id,
+                            kind = kind,
+                            length = length.as_str(),
+                            offset = *offset
+                        ),
+                        None => tr!(
+                            "designer-row-name",
+                            field = id,
+                            kind = kind,
+                            length = length.as_str()
+                        ),
+                    };
+                    name_row(&response, selected, &name);
+                    row_ids.push(response.id);
+                    if response.clicked() {
+                        clicked = Some((i, ui.input(|i| i.modifiers)));
+                    }
+                    if response.double_clicked() {
+                        open_editor = Some(FieldEditor::edit(&proto.id, field));
+                    }
+                    // the arrows move the selection, with Shift extending it
+                    match row_key(ui, &response) {
+                        Some((RowKey::Open, _)) => {
+                            open_editor = Some(FieldEditor::edit(&proto.id, field));
+                        }
+                        Some((RowKey::Previous, modifiers)) if i > 0 => {
+                            stepped = Some((i - 1, modifiers));
+                        }
+                        Some((RowKey::Next, modifiers)) if i + 1 < proto.fields.len() => {
+                            stepped = Some((i + 1, modifiers));
+                        }
+                        _ => {}
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button(tr!("designer-edit")).clicked() {
+                            open_editor = Some(FieldEditor::edit(&proto.id, field));
+                            ui.close();
+                        }
+                        if ui.button(tr!("designer-insert-above")).clicked() {
+                            open_editor = Some(FieldEditor::insert(&proto.id, i));
+                            ui.close();
+                        }
+                        if ui.button(tr!("designer-insert-below")).clicked() {
+                            open_editor = Some(FieldEditor::insert(&proto.id, i + 1));
+                            ui.close();
+                        }
+                    });
+                    ui.label(field_type_label(&field.field_type));
+                    ui.label(length);
+                    offset_cells(ui, offsets.get(&field.id).copied());
+                    ui.end_row();
+                }
+            });
+
+        if let Some(id) = child {
+            app.selected_protocol = Some(id);
+            app.selected_field = None;
+            app.selected_fields.clear();
+            return;
+        }
+        if open_editor.is_some() {
+            app.field_editor = open_editor;
+        }
+        if let Some((i, modifiers)) = stepped {
+            ui.memory_mut(|m| m.request_focus(row_ids[i]));
+            clicked = Some((
+                i,
+                egui::Modifiers {
+                    shift: modifiers.shift,
+                    ..Default::default()
+                },
+            ));
+        }
+        let protocol_id = proto.id.clone();
+        if let Some((i, modifiers)) = clicked {
+            let fields = proto.fields.clone();
+            select_field(app, &fields, i, modifiers);
+        }
+        if lengths_changed || length_field_changed || sequence_field_changed {
+            let result = app.registry.edit_protocol(&protocol_id, |p| {
+                (p.max_length, p.frame_length) = lengths;
+                p.length_field = length_field;
+                p.sequence_field = sequence_field;
+                Ok(())
+            });
+            app.report(result);
+        }
+        if copy {
+            copy_fields(app, ui.ctx(), &protocol_id, &selection);
+        }
+        if let Some(fields) = paste {
+            let result = app.registry.paste_fields(&protocol_id, fields);
+            if let Some(ids) = app.report(result) {
+                app.selected_field = ids.first().cloned();
+                app.selected_fields = ids.into_iter().collect();
+            }
+            return;
+        }
+        if let Some(edit) = bulk_edit {
+            apply_bulk_edit(app, &protocol_id, &selection, edit);
+            return;
+        }
+        let Some(proto) = app.registry.get_protocol(&protocol_id) else {
+            return;
+        };
+
+        // everything a validator script can refer to
+        let variables: Vec<String> = app
+            .registry
+            .layout(&protocol_id)
+            .unwrap_or_default()
+            .fields
+            .iter()
+            .map(|f| f.id.clone())
+            .chain(["fields".to_string()])
+            .chain(app.script_engine.library_functions())
+            .collect();
+        let mut validators = proto.validators.clone();
+        let mut notes = proto.notes.clone();
+
+        if let Ok(matrix) = constraint_matrix(&app.registry, &protocol_id)
+            && !matrix.rows.is_empty()
+        {
+            ui.separator();
+            let mut title = tr!("designer-constraints", count = matrix.rows.len());
+            if !matrix.overlaps.is_empty() || !matrix.gaps.is_empty() {
+                title.push_str(" ⚠");
+            }
+            egui::CollapsingHeader::new(title)
+                .id_salt("subprotocol_constraints")
+                .show(ui, |ui| match constraint_table(ui, &matrix) {
+                    Some(ConstraintLink::Subprotocol(id)) => {
+                        app.selected_protocol = Some(id);
+                        app.selected_field = None;
+                        app.selected_fields.clear();
+                    }
+                    Some(ConstraintLink::Field(field)) => {
+                        if let Some(owner) = app.registry.field_owner(&protocol_id, &field) {
+                            let owner = owner.id.clone();
+                            go_to_field(app, owner, field);
+                        }
+                    }
+                    None => {}
+                });
+        }
+
+        ui.separator();
+        egui::CollapsingHeader::new(tr!("designer-validators", count = validators.len()))
+            .id_salt("packet_validators")
+            .show(ui, |ui| {
+                if validator_list(ui, &mut validators, &app.script_engine, &variables) {
+                    let result = app.registry.edit_protocol(&protocol_id, |p| {
+                        p.validators = validators;
+                        Ok(())
+                    });
+                    app.report(result);
+                }
+            });
+
+        ui.separator();
+        egui::CollapsingHeader::new(tr!("designer-notes"))
+            .id_salt("protocol_notes")
+            .default_open(!notes.is_empty())
+            .show(ui, |ui| {
+                let edit = egui::TextEdit::multiline(&mut notes)
+                    .desired_width(f32::INFINITY)
+                    .hint_text(tr!("designer-notes-hint"));
+                if ui.add(edit).changed() {
+                    let result = app.registry.edit_protocol(&protocol_id, |p| {
+                        p.notes = notes;
+                        Ok(())
+                    });
+                    app.report(result);
+                }
+            });
+    });
+}
+
+fn length_str(field: &FieldRule) -> String {
+    match field.length {
+        _ if field.is_virtual() => "-".to_string(),
+        FieldLength::Fixed(bits) => tr!("designer-bits", bits = bits),
+        FieldLength::Variable => tr!("designer-variable"),
+    }
+}
+
+/// The bit and byte offset columns of the field table
+fn offset_cells(ui: &mut egui::Ui, offset: Option<u32>) {
+    match offset {
+        Some(offset) => {
+            ui.label(offset.to_string());
+            if offset % 8 == 0 {
+                ui.label((offset / 8).to_string());
+            } else {
+                ui.label(format!("{}.{}", offset / 8, offset % 8))
+                    .on_hover_text(tr!(
+                        "designer-bit-of-byte",
+                        bit = offset % 8,
+                        byte = offset / 8
+                    ));
+            }
+        }
+        None => {
+            ui.label("-");
+            ui.label("-");
+        }
+    }
+}
+
+/// The fields of the ancestors of a protocol, read-only and collapsed by default, so the whole
+/// wire layout shows above the fields of the protocol. Returns the protocol and field
+/// double-clicked to edit it there.
+fn inherited_fields(
+    ui: &mut egui::Ui,
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    colors: &HashMap<String, Color32>,
+    offsets: &HashMap<String, u32>,
+) -> Option<(String, String)> {
+    let chain = registry.get_inheritance_chain(protocol_id);
+    let ancestors = &chain[..chain.len().saturating_sub(1)];
+    let count: usize = ancestors.iter().map(|p| p.fields.len()).sum();
+    if count == 0 {
+        return None;
+    }
+    let mut open = None;
+    egui::CollapsingHeader::new(tr!("designer-inherited-fields", count = count))
+        .id_salt(("inherited_fields", protocol_id))
+        .show(ui, |ui| {
+            egui::Grid::new("inherited_field_table")
+                .num_columns(6)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(tr!("designer-id"));
+                    ui.strong(tr!("designer-type"));
+                    ui.strong(tr!("designer-length"));
+                    ui.strong(tr!("designer-bit-offset"));
+                    ui.strong(tr!("designer-byte-offset"));
+                    ui.strong(tr!("designer-from"));
+                    ui.end_row();
+                    for proto in ancestors {
+                        for field in &proto.fields {
+                            let response = ui
+                                .horizontal(|ui| {
+                                    let color = colors.get(&field.id).copied();
+                                    color_swatch(ui, color.unwrap_or(Color32::TRANSPARENT));
+                                    ui.add(
+                                        egui::Label::new(egui::RichText::new(&field.id).weak())
+                                            .sense(egui::Sense::click()),
+                                    )
+                                })
+                                .inner
+                                .on_hover_text(tr!(
+                                    "designer-inherited-hint",
+                                    protocol = proto.id.as_str()
+                                ));
+                            if response.double_clicked() {
+                                open = Some((proto.id.clone(), field.id.clone()));
+                            }
+                            ui.weak(field_type_label(&field.field_type));
+                            ui.weak(length_str(field));
+                            offset_cells(ui, offsets.get(&field.id).copied());
+                            ui.weak(&proto.id);
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    open
+}
+
+/// Total length of the protocol including inherited fields, with inputs for its length budget
+/// and frame length and a warning when the fields exceed either. Returns whether the budget or
+/// frame length, given as `(max_length, frame_length)`, was changed.
+fn length_summary(
+    ui: &mut egui::Ui,
+    registry: &ProtocolRegistry,
+    proto: &Protocol,
+    (max_length, frame_length): &mut (Option<u32>, Option<u32>),
+) -> bool {
+    let mut changed = false;
+    let total = registry.get_total_length(&proto.id);
+    ui.horizontal(|ui| {
+        ui.label(tr!("designer-total-length"));
+        ui.strong(match total {
+            ProtocolLength::Fixed(bits) => {
+                tr!(
+                    "designer-length-fixed",
+                    bits = bits,
+                    bytes = bytes_str(bits)
+                )
+            }
+            ProtocolLength::Variable(bits) => {
+                tr!(
+                    "designer-length-variable",
+                    bits = bits,
+                    bytes = bytes_str(bits)
+                )
+            }
+        });
+        ui.separator();
+        changed |= optional_bits(
+            ui,
+            &tr!("designer-budget"),
+            &tr!("designer-budget-hint"),
+            max_length,
+        );
+        changed |= optional_bits(
+            ui,
+            &tr!("designer-frame"),
+            &tr!("designer-frame-hint"),
+            frame_length,
+        );
+    });
+
+    if let Some(issue) = registry.length_budget_issue(&proto.id) {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", issue));
+    }
+    if let Some((frame_bits, frame_id)) = registry.frame_length(&proto.id) {
+        let bits = match total {
+            ProtocolLength::Fixed(bits) | ProtocolLength::Variable(bits) => bits,
+        };
+        let inherited = frame_id != proto.id;
+        let protocol = frame_id;
+        match frame_bits.checked_sub(bits) {
+            Some(padding) => ui.label(if inherited {
+                tr!(
+                    "designer-frame-padding-inherited",
+                    padding = padding,
+                    frame = frame_bits,
+                    protocol = protocol
+                )
+            } else {
+                tr!(
+                    "designer-frame-padding",
+                    padding = padding,
+                    frame = frame_bits
+                )
+            }),
+            None => ui.colored_label(
+                ui.visuals().warn_fg_color,
+                if inherited {
+                    tr!(
+                        "designer-frame-exceeded-inherited",
+                        frame = frame_bits,
+                        protocol = protocol,
+                        bits = bits - frame_bits
+                    )
+                } else {
+                    tr!(
+                        "designer-frame-exceeded",
+                        frame = frame_bits,
+                        bits = bits - frame_bits
+                    )
+                },
+            ),
+        };
+    }
+    changed
+}
+
+/// Checkbox enabling a number of bits, with an input for it. Returns whether it was changed.
+/// Picker for the field giving the packet length, which tells where messages end in a byte
+/// stream. Returns whether it was changed.
+fn length_field_input(
+    ui: &mut egui::Ui,
+    registry: &ProtocolRegistry,
+    proto: &Protocol,
+    length_field: &mut Option<LengthField>,
+) -> bool {
+    let candidates: Vec<String> = registry
+        .layout(&proto.id)
+        .unwrap_or_default()
+        .fields
+        .iter()
+        .filter(|f| !f.is_virtual() && matches!(f.length, FieldLength::Fixed(_)))
+        .map(|f| f.id.clone())
+        .collect();
+    let selected = match (&length_field, registry.length_field(&proto.id)) {
+        (Some(length), _) => length.field_id.clone(),
+        (None, Some(inherited)) => {
+            tr!("designer-inherited", field = inherited.field_id.as_str())
+        }
+        (None, None) => tr!("designer-none"),
+    };
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(tr!("designer-length-field"))
+            .on_hover_text(tr!("designer-length-field-hint"));
+        egui::ComboBox::from_id_salt("length_field")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(length_field.is_none(), tr!("designer-none"))
+                    .clicked()
+                    && length_field.is_some()
+                {
+                    *length_field = None;
+                    changed = true;
+                }
+                for id in &candidates {
+                    let selected = length_field.as_ref().is_some_and(|l| &l.field_id == id);
+                    if ui.selectable_label(selected, id).clicked() && !selected {
+                        let adjustment = length_field.as_ref().map_or(0, |l| l.adjustment);
+                        *length_field = Some(LengthField {
+                            field_id: id.clone(),
+                            adjustment,
+                        });
+                        changed = true;
+                    }
+                }
+            });
+        if let Some(length) = length_field {
+            ui.label(tr!("designer-plus"));
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut length.adjustment)
+                        .suffix(format!(" {}", tr!("designer-bytes-unit"))),
+                )
+                .on_hover_text(tr!("designer-adjustment-hint"))
+                .changed();
+        }
+    });
+    changed
+}
+
+/// Picker for the field numbering the packets in sequence, which the sequence analysis of a
+/// capture checks for gaps. Returns whether it was changed.
+fn sequence_field_input(
+    ui: &mut egui::Ui,
+    registry: &ProtocolRegistry,
+    proto: &Protocol,
+    sequence_field: &mut Option<String>,
+) -> bool {
+    let candidates: Vec<String> = registry
+        .layout(&proto.id)
+        .unwrap_or_default()
+        .fields
+        .iter()
+        .filter(|f| matches!(f.length, FieldLength::Fixed(_)))
+        .map(|f| f.id.clone())
+        .collect();
+    let selected = match (&sequence_field, registry.sequence_field(&proto.id)) {
+        (Some(field_id), _) => field_id.clone(),
+        (None, Some((inherited, _))) => tr!("designer-inherited", field = inherited),
+        (None, None) => tr!("designer-none"),
+    };
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(tr!("designer-sequence-field"))
+            .on_hover_text(tr!("designer-sequence-field-hint"));
+        egui::ComboBox::from_id_salt("sequence_field")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(sequence_field.is_none(), tr!("designer-none"))
+                    .clicked()
+                    && sequence_field.is_some()
+                {
+                    *sequence_field = None;
+                    changed = true;
+                }
+                for id in &candidates {
+                    let selected = sequence_field.as_ref() == Some(id);
+                    if ui.selectable_label(selected, id).clicked() && !selected {
+                        *sequence_field = Some(id.clone());
+                        changed = true;
+                    }
+                }
+            });
+    });
+    changed
+}
+
+fn optional_bits(ui: &mut egui::Ui, label: &str, hover: &str, value: &mut Option<u32>) -> bool {
+    let mut enabled = value.is_some();
+    let mut bits = value.unwrap_or(8 * 64);
+    let checkbox = ui.checkbox(&mut enabled, label).on_hover_text(hover);
+    let drag = ui.add_enabled(
+        enabled,
+        egui::DragValue::new(&mut bits)
+            .range(1..=u32::MAX)
+            .suffix(format!(" {}", tr!("designer-bits-unit"))),
+    );
+    *value = enabled.then_some(bits);
+    checkbox.changed() || drag.changed()
+}
+
+/// Bits as a number of bytes, e.g. `2 bytes` or `2.5 bytes`
+fn bytes_str(bits: u32) -> String {
+    if bits.is_multiple_of(8) {
+        tr!("designer-bytes", bytes = bits / 8)
+    } else {
+        tr!("designer-bytes", bytes = bits as f64 / 8.0)
+    }
+}
+
+/// A change made to all the selected fields at once
+enum BulkEdit {
+    Delete,
+    Move { up: bool },
+    SetLength(u32),
+    SetByteOrder(Option<Endianness>),
+}
+
+/// Select the field at `index` of the field table: only it on a plain click, adding or removing
+/// it with Ctrl, or every field from the last clicked one to it with Shift.
+fn select_field(
+    app: &mut BitLoomApp,
+    fields: &[FieldRule],
+    index: usize,
+    modifiers: egui::Modifiers,
+) {
+    let id = fields[index].id.clone();
+    let anchor = app
+        .selected_field
+        .as_deref()
+        .and_then(|anchor| fields.iter().position(|f| f.id == anchor));
+    match anchor {
+        Some(anchor) if modifiers.shift => {
+            let range = anchor.min(index)..=anchor.max(index);
+            app.selected_fields = fields[range].iter().map(|f| f.id.clone()).collect();
+            // the anchor stays, so that the range can be extended from it again
+            return;
+        }
+        _ if modifiers.command => {
+            if let Some(anchor) = app.selected_field.take() {
+                app.selected_fields.insert(anchor);
+            }
+            if app.selected_fields.remove(&id) {
+                return;
+            }
+            app.selected_fields.insert(id.clone());
+        }
+        _ => app.selected_fields = HashSet::from([id.clone()]),
+    }
+    app.selected_field = Some(id);
+}
+
+/// Buttons for the operations on the selected fields. Returns the one that was chosen.
+fn bulk_toolbar(ui: &mut egui::Ui, count: usize) -> Option<BulkEdit> {
+    let mut edit = None;
+    ui.label(tr!("designer-selected", count = count));
+    if ui.button(tr!("designer-delete")).clicked() {
+        edit = Some(BulkEdit::Delete);
+    }
+    if ui
+        .button("⏶")
+        .on_hover_text(tr!("designer-move-up"))
+        .clicked()
+    {
+        edit = Some(BulkEdit::Move { up: true });
+    }
+    if ui
+        .button("⏷")
+        .on_hover_text(tr!("designer-move-down"))
+        .clicked()
+    {
+        edit = Some(BulkEdit::Move { up: false });
+    }
+
+    let id = ui.id().with("bulk_length");
+    let mut bits = ui.data_mut(|d| *d.get_temp_mut_or(id, 8u32));
+    ui.add(
+        egui::DragValue::new(&mut bits)
+            .range(1..=u16::MAX as u32)
+            .suffix(format!(" {}", tr!("designer-bits-unit"))),
+    );
+    ui.data_mut(|d| d.insert_temp(id, bits));
+    if ui
+        .button(tr!("designer-set-length"))
+        .on_hover_text(tr!("designer-set-length-hint"))
+        .clicked()
+    {
+        edit = Some(BulkEdit::SetLength(bits));
+    }
+
+    ui.menu_button(tr!("designer-byte-order"), |ui| {
+        let options = [
+            (None, tr!("designer-protocol-default")),
+            (Some(Endianness::Big), endianness_label(Endianness::Big)),
+            (
+                Some(Endianness::Little),
+                endianness_label(Endianness::Little),
+            ),
+        ];
+        for (endianness, label) in options {
+            if ui.button(label).clicked() {
+                edit = Some(BulkEdit::SetByteOrder(endianness));
+                ui.close();
+            }
+        }
+    });
+    edit
+}
+
+/// Copy the selected fields to the clipboard of the app, and as JSON to that of the system
+fn copy_fields(app: &mut BitLoomApp, ctx: &egui::Context, protocol_id: &str, selection: &[String]) {
+    let Some(proto) = app.registry.get_protocol(protocol_id) else {
+        return;
+    };
+    app.field_clipboard = proto
+        .fields
+        .iter()
+        .filter(|f| selection.contains(&f.id))
+        .cloned()
+        .collect();
+    if let Ok(json) = serde_json::to_string_pretty(&app.field_clipboard) {
+        ctx.copy_text(json);
+    }
+}
+
+/// Apply a bulk edit to the selected fields, changing none of them if it fails for any
+fn apply_bulk_edit(app: &mut BitLoomApp, protocol_id: &str, selection: &[String], edit: BulkEdit) {
+    let result = app.registry.edit_protocol(protocol_id, |p| match &edit {
+        BulkEdit::Delete => selection.iter().try_for_each(|id| p.remove_field(id)),
+        BulkEdit::Move { up } => p.move_fields(selection, *up),
+        BulkEdit::SetLength(bits) => selection.iter().try_for_each(|id| {
+            p.edit_field(id, |f| {
+                if !f.is_virtual() {
+                    f.length = FieldLength::Fixed(*bits);
+                }
+                Ok(())
+            })
+        }),
+        BulkEdit::SetByteOrder(endianness) => selection.iter().try_for_each(|id| {
+            p.edit_field(id, |f| {
+                f.endianness = *endianness;
+                Ok(())
+            })
+        }),
+    });
+    if app.report(result).is_some() && matches!(edit, BulkEdit::Delete) {
+        app.selected_fields.clear();
+        app.selected_field = None;
+    }
+}
+
+/// Zoom buttons of the layout diagram, and jumping to a field by typing part of its ID.
+/// Returns the field jumped to.
+fn layout_toolbar(
+    ui: &mut egui::Ui,
+    view: &mut LayoutView,
+    fields: &[FieldRule],
+) -> Option<String> {
+    let mut jumped = None;
+    ui.horizontal(|ui| {
+        if ui
+            .small_button("−")
+            .on_hover_text(tr!("designer-zoom-out"))
+            .clicked()
+        {
+            view.zoom = (view.zoom / 1.25).max(0.25);
+        }
+        if ui
+            .small_button(format!("{:.0}%", view.zoom * 100.0))
+            .on_hover_text(tr!("designer-zoom-reset"))
+            .clicked()
+        {
+            view.zoom = 1.0;
+        }
+        if ui
+            .small_button("+")
+            .on_hover_text(tr!("designer-zoom-in"))
+            .clicked()
+        {
+            view.zoom = (view.zoom * 1.25).min(4.0);
+        }
+        ui.separator();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut view.jump)
+                .hint_text(tr!("designer-jump"))
+                .desired_width(160.0),
+        );
+        let query = view.jump.trim().to_lowercase();
+        let target = (!query.is_empty())
+            .then(|| {
+                let wire = || fields.iter().filter(|f| !f.is_virtual());
+                wire()
+                    .find(|f| f.id.to_lowercase() == query)
+                    .or_else(|| wire().find(|f| f.id.to_lowercase().contains(&query)))
+            })
+            .flatten();
+        match target {
+            Some(field) => {
+                let entered =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui
+                    .button(tr!("designer-go-to", field = field.id.as_str()))
+                    .clicked()
+                    || entered
+                {
+                    view.reveal = Some(field.id.clone());
+                    jumped = Some(field.id.clone());
+                }
+            }
+            None if !query.is_empty() => {
+                ui.weak(tr!("designer-no-such-field"));
+            }
+            None => {}
+        }
+    });
+    jumped
+}
+
+/// Open the designer of a protocol with one of its fields selected
+fn go_to_field(app: &mut BitLoomApp, protocol_id: String, field_id: String) {
+    app.selected_protocol = Some(protocol_id);
+    app.selected_fields = HashSet::from([field_id.clone()]);
+    app.selected_field = Some(field_id);
+}
+
+/// The values of parent fields a subprotocol applies to, each field a link. Returns the
+/// protocol defining the clicked field, and the field.
+fn parent_constraints(
+    ui: &mut egui::Ui,
+    registry: &ProtocolRegistry,
+    proto: &Protocol,
+) -> Option<(String, String)> {
+    let parent_id = proto.parent_id.as_deref()?;
+    if proto.parent_constraints.is_empty() {
+        return None;
+    }
+    let mut constraints: Vec<_> = proto.parent_constraints.iter().collect();
+    constraints.sort();
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(tr!("designer-applies-when"));
+        for (i, (field_id, value)) in constraints.into_iter().enumerate() {
+            if i > 0 {
+                ui.label(tr!("designer-and"));
+            }
+            match registry.field_owner(parent_id, field_id) {
+                Some(owner) => {
+                    if ui
+                        .link(field_id)
+                        .on_hover_text(tr!("designer-go-to-field-in", protocol = owner.id.as_str()))
+                        .clicked()
+                    {
+                        clicked = Some((owner.id.clone(), field_id.clone()));
+                    }
+                }
+                None => {
+                    ui.colored_label(ui.visuals().warn_fg_color, field_id)
+                        .on_hover_text(tr!("designer-no-field", protocol = parent_id));
+                }
+            }
+            ui.label(format!("= {}", value));
+        }
+    });
+    clicked
+}
+
+/// A marker on a field that subprotocols select on, listing them in a menu. Returns the
+/// subprotocol picked from the menu.
+fn constrained_by_children(
+    ui: &mut egui::Ui,
+    registry: &ProtocolRegistry,
+    protocol_id: &str,
+    field_id: &str,
+) -> Option<String> {
+    let children: Vec<(String, i128)> = registry
+        .get_field_references(protocol_id, field_id)
+        .into_iter()
+        .filter_map(|r| match r {
+            FieldReference::ParentConstraint { protocol_id, value } => Some((protocol_id, value)),
+            _ => None,
+        })
+        .collect();
+    if children.is_empty() {
+        return None;
+    }
+    let mut clicked = None;
+    ui.menu_button(format!("⑂ {}", children.len()), |ui| {
+        ui.weak(tr!("designer-constrained-by"));
+        for (child, value) in &children {
+            if ui
+                .button(format!("{} ({} = {})", child, field_id, value))
+                .clicked()
+            {
+                clicked = Some(child.clone());
+                ui.close();
+            }
+        }
+    })
+    .response
+    .on_hover_text(tr!("designer-constrained-hint", count = children.len()));
+    clicked
+}
+
+/// What a link in the subprotocol constraints table leads to
+enum ConstraintLink {
+    Subprotocol(String),
+    /// a constrained field of the protocol or its ancestors
+    Field(String),
+}
+
+/// The wire fields of a protocol, including inherited ones, as rows of 32 bits with each field
+/// in its color, zoomed and scrolled as `view` says, with a minimap of the whole packet next to
+/// it when it does not fit. A variable length field fills the rest of its row. Returns the
+/// clicked field.
+fn layout_diagram(
+    ui: &mut egui::Ui,
+    view: &mut LayoutView,
+    fields: &[FieldRule],
+    colors: &HashMap<String, Color32>,
+    selected: Option<&str>,
+) -> Option<String> {
+    // (field, first bit, bits) of each field on the wire
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for field in fields.iter().filter(|f| !f.is_virtual()) {
+        let bits = match field.length {
+            FieldLength::Fixed(bits) => bits as usize,
+            FieldLength::Variable => DIAGRAM_ROW_BITS - offset % DIAGRAM_ROW_BITS,
+        };
+        spans.push((field, offset, bits));
+        offset += bits;
+    }
+    if spans.is_empty() {
+        return None;
+    }
+
+    let rows = offset.div_ceil(DIAGRAM_ROW_BITS);
+    let row_height = (ui.text_style_height(&egui::TextStyle::Body) + 8.0) * view.zoom;
+    let fits = row_height * rows as f32 <= DIAGRAM_HEIGHT;
+    let available = ui.available_width() - if fits { 0.0 } else { MINIMAP_WIDTH + 8.0 };
+    let width = available.min(640.0) * view.zoom;
+    let mut clicked = None;
+    ui.horizontal_top(|ui| {
+        let mut scroll = egui::ScrollArea::both()
+            .id_salt("layout_diagram")
+            .max_height(DIAGRAM_HEIGHT)
+            .max_width(available)
+            .auto_shrink([true, true]);
+        if let Some(offset) = view.scroll_to.take() {
+            scroll = scroll.vertical_scroll_offset(offset);
+        }
+        let output = scroll.show(ui, |ui| {
+            let (area, response) = ui.allocate_exact_size(
+                egui::vec2(width, row_height * rows as f32),
+                egui::Sense::hover(),
+            );
+            if response.hovered() {
+                let zoom = ui.input(|i| i.zoom_delta());
+                if zoom != 1.0 {
+                    view.zoom = (view.zoom * zoom).clamp(0.25, 4.0);
+                }
+            }
+            clicked = paint_diagram(ui, view, area, &spans, colors, selected, row_height);
+        });
+        if !fits {
+            minimap(ui, view, &spans, colors, rows, &output);
+        }
+    });
+    clicked
+}
+
+/// The fields of the layout diagram in `area`, one row of `row_height` per 32 bits
+fn paint_diagram(
+    ui: &mut egui::Ui,
+    view: &mut LayoutView,
+    area: egui::Rect,
+    spans: &[(&FieldRule, usize, usize)],
+    colors: &HashMap<String, Color32>,
+    selected: Option<&str>,
+    row_height: f32,
+) -> Option<String> {
+    let bit_width = area.width() / DIAGRAM_ROW_BITS as f32;
+
+    let font_id = egui::TextStyle::Small.resolve(ui.style());
+    let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+    let selection = ui.visuals().selection.stroke;
+    let mut clicked = None;
+    for &(field, start, bits) in spans {
+        // a field longer than the rest of its row continues on the next ones
+        let mut bit = start;
+        while bit < start + bits {
+            let row = bit / DIAGRAM_ROW_BITS;
+            let column = bit % DIAGRAM_ROW_BITS;
+            let end = (start + bits).min((row + 1) * DIAGRAM_ROW_BITS);
+            let rect = egui::Rect::from_min_size(
+                area.min + egui::vec2(column as f32 * bit_width, row as f32 * row_height),
+                egui::vec2((end - bit) as f32 * bit_width, row_height),
+            );
+            let color = colors.get(&field.id).copied().unwrap_or(Color32::GRAY);
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect.shrink(1.0), 2.0, color);
+            let outline = if selected == Some(field.id.as_str()) {
+                egui::Stroke::new(selection.width.max(2.0), selection.color)
+            } else {
+                stroke
+            };
+            painter.rect_stroke(rect.shrink(1.0), 2.0, outline, egui::StrokeKind::Inside);
+            if bit == start && view.reveal.as_ref() == Some(&field.id) {
+                ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                view.reveal = None;
+            }
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                &field.id,
+                font_id.clone(),
+                text_color_on(color),
+            );
+
+            let length = match field.length {
+                FieldLength::Variable => tr!("designer-variable-length"),
+                FieldLength::Fixed(bits) => tr!("designer-bits", bits = bits),
+            };
+            let response = ui
+                .interact(
+                    rect,
+                    ui.id().with(("layout", &field.id, bit)),
+                    egui::Sense::click(),
+                )
+                .on_hover_text(tr!(
+                    "designer-layout-hint",
+                    field = field.id.as_str(),
+                    length = length,
+                    bit = start
+                ));
+            if response.clicked() {
+                clicked = Some(field.id.clone());
+            }
+            bit = end;
+        }
+    }
+    clicked
+}
+
+/// The whole layout diagram shrunk to the height of its scroll area, with the part in view
+/// outlined. Clicking or dragging on it scrolls the diagram there.
+fn minimap(
+    ui: &mut egui::Ui,
+    view: &mut LayoutView,
+    spans: &[(&FieldRule, usize, usize)],
+    colors: &HashMap<String, Color32>,
+    rows: usize,
+    diagram: &egui::scroll_area::ScrollAreaOutput<()>,
+) {
+    let height = diagram.inner_rect.height();
+    let (area, response) = ui.allocate_exact_size(
+        egui::vec2(MINIMAP_WIDTH, height),
+        egui::Sense::click_and_drag(),
+    );
+    let painter = ui.painter_at(area);
+    painter.rect_filled(area, 0.0, ui.visuals().extreme_bg_color);
+    let row_height = height / rows as f32;
+    let bit_width = MINIMAP_WIDTH / DIAGRAM_ROW_BITS as f32;
+    for &(field, start, bits) in spans {
+        let color = colors.get(&field.id).copied().unwrap_or(Color32::GRAY);
+        let mut bit = start;
+        while bit < start + bits {
+            let row = bit / DIAGRAM_ROW_BITS;
+            let column = bit % DIAGRAM_ROW_BITS;
+            let end = (start + bits).min((row + 1) * DIAGRAM_ROW_BITS);
+            let rect = egui::Rect::from_min_size(
+                area.min + egui::vec2(column as f32 * bit_width, row as f32 * row_height),
+                egui::vec2((end - bit) as f32 * bit_width, row_height.max(1.0)),
+            );
+            painter.rect_filled(rect, 0.0, color);
+            bit = end;
+        }
+    }
+
+    let content = diagram.content_size.y.max(1.0);
+    let top = diagram.state.offset.y / content * height;
+    let visible = egui::Rect::from_min_size(
+        area.min + egui::vec2(0.0, top),
+        egui::vec2(MINIMAP_WIDTH, height * height / content),
+    );
+    painter.rect_stroke(
+        visible,
+        1.0,
+        ui.visuals().selection.stroke,
+        egui::StrokeKind::Inside,
+    );
+
+    let response = response.on_hover_text(tr!("designer-minimap-hint"));
+    if (response.clicked() || response.dragged())
+        && let Some(pointer) = response.interact_pointer_pos()
+    {
+        let fraction = ((pointer.y - area.top()) / height).clamp(0.0, 1.0);
+        view.scroll_to = Some((fraction * content - height / 2.0).max(0.0));
+        ui.ctx().request_repaint();
+    }
+}
+
+/// The value each subprotocol requires of each discriminator field, with the subprotocols that
+/// apply to the same packets and the values none applies to. Returns the subprotocol clicked.
+fn constraint_table(ui: &mut egui::Ui, matrix: &ConstraintMatrix) -> Option<ConstraintLink> {
+    let warn = ui.visuals().warn_fg_color;
+    let mut clicked = None;
+    egui::Grid::new("constraint_matrix")
+        .num_columns(matrix.fields.len() + 1)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong(tr!("designer-subprotocol"));
+            for field in &matrix.fields {
+                if ui
+                    .link(egui::RichText::new(field).strong())
+                    .on_hover_text(tr!("designer-go-to-field"))
+                    .clicked()
+                {
+                    clicked = Some(ConstraintLink::Field(field.clone()));
+                }
+            }
+            ui.end_row();
+
+            for (protocol_id, values) in &matrix.rows {
+                let mut text = egui::RichText::new(protocol_id);
+                if matrix.overlaps(protocol_id) {
+                    text = text.color(warn);
+                }
+                if ui
+                    .link(text)
+                    .on_hover_text(tr!("designer-open-subprotocol"))
+                    .clicked()
+                {
+                    clicked = Some(ConstraintLink::Subprotocol(protocol_id.clone()));
+                }
+                for value in values {
+                    match value {
+                        Some(value) => ui.label(value.to_string()),
+                        None => ui
+                            .weak(tr!("designer-any"))
+                            .on_hover_text(tr!("designer-not-constrained")),
+                    };
+                }
+                ui.end_row();
+            }
+        });
+
+    for (a, b) in &matrix.overlaps {
+        ui.colored_label(
+            warn,
+            tr!("designer-overlap", a = a.as_str(), b = b.as_str()),
+        );
+    }
+    for (field, ranges) in &matrix.gaps {
+        let values: Vec<String> = ranges
+            .iter()
+            .map(|r| {
+                if r.start() == r.end() {
+                    r.start().to_string()
+                } else {
+                    format!("{}..={}", r.start(), r.end())
+                }
+            })
+            .collect();
+        ui.colored_label(
+            warn,
+            tr!(
+                "designer-gap",
+                field = field.as_str(),
+                values = values.join(", ")
+            ),
+        );
+    }
+    if matrix.overlaps.is_empty() && matrix.gaps.is_empty() {
+        ui.weak(tr!("designer-no-conflicts"));
+    }
+    clicked
+}
+
+/// Editors for the packet validators of a protocol. Returns whether any were changed.
+fn validator_list(
+    ui: &mut egui::Ui,
+    validators: &mut Vec<PacketValidator>,
+    engine: &ScriptEngine,
+    variables: &[String],
+) -> bool {
+    ui.label(tr!("designer-validators-hint"));
+
+    let mut changed = false;
+    let mut remove = None;
+    for (i, validator) in validators.iter_mut().enumerate() {
+        ui.push_id(("validator", i), |ui| {
+            ui.horizontal(|ui| {
+                changed |= ui.text_edit_singleline(&mut validator.name).changed();
+                egui::ComboBox::from_id_salt("severity")
+                    .selected_text(severity_label(validator.severity))
+                    .show_ui(ui, |ui| {
+                        for severity in [Severity::Error, Severity::Warning] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut validator.severity,
+                                    severity,
+                                    severity_label(severity),
+                                )
+                                .changed();
+                        }
+                    });
+                if ui
+                    .small_button("✖")
+                    .on_hover_text(tr!("designer-remove-validator"))
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+            });
+            let before = validator.script.clone();
+            expr_editor::show(ui, "script", &mut validator.script, engine, variables);
+            changed |= validator.script != before;
+        });
+        ui.separator();
+    }
+
+    if let Some(i) = remove {
+        validators.remove(i);
+        changed = true;
+    }
+    if ui.button(tr!("designer-add-validator")).clicked() {
+        validators.push(PacketValidator {
+            name: format!("validator_{}", validators.len() + 1),
+            severity: Severity::Error,
+            script: "true".to_string(),
+        });
+        changed = true;
+    }
+    changed
 }
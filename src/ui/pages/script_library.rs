@@ -0,0 +1,65 @@
+use crate::app::BitLoomApp;
+use crate::ui::expr_editor::{byte_offset, highlight};
+use crate::ui::external_editor;
+use bitloom::tr;
+use eframe::egui;
+
+/// Editor for the project script library, installed into the script engine on save
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let save_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::S);
+        let mut save = ui.input_mut(|i| i.consume_shortcut(&save_shortcut));
+
+        ui.horizontal(|ui| {
+            ui.strong(tr!("library-title"));
+            ui.label(tr!("library-intro"))
+                .on_hover_text(tr!("library-intro-hint"));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                save |= ui
+                    .button(tr!("library-save"))
+                    .on_hover_text(ctx.format_shortcut(&save_shortcut))
+                    .clicked();
+                // saving the file in the external editor installs the library as Save does
+                save |= external_editor::button(
+                    ui,
+                    egui::Id::new("script_library"),
+                    "library",
+                    &mut app.script_library,
+                );
+                let functions = app.script_engine.library_functions();
+                if !functions.is_empty() {
+                    ui.label(tr!("library-loaded", functions = functions.join(", ")));
+                }
+            });
+        });
+
+        if let Some(error) = &app.script_library_error {
+            ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+        }
+        ui.separator();
+
+        let error_offset = app
+            .script_library_error
+            .as_ref()
+            .and_then(|e| e.position)
+            .map(|(line, column)| byte_offset(&app.script_library, line, column));
+        let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+            let mut job = highlight(ui, text.as_str(), error_offset);
+            job.wrap.max_width = wrap_width;
+            ui.fonts_mut(|f| f.layout_job(job))
+        };
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.add_sized(
+                ui.available_size(),
+                egui::TextEdit::multiline(&mut app.script_library)
+                    .code_editor()
+                    .hint_text("fn kelvin_to_raw(t) { (t - 273) * 10 }")
+                    .layouter(&mut layouter),
+            );
+        });
+
+        if save {
+            app.script_library_error = app.script_engine.set_library(&app.script_library).err();
+        }
+    });
+}
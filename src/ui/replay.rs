@@ -0,0 +1,176 @@
+use crate::app::BitLoomApp;
+use crate::ui::{expr_editor, widgets};
+use bitloom::replay::{REWRITE_FUNCTION, Replay, ReplayEvent};
+use bitloom::tr;
+use bitloom::transport::{Transport, TransportConfig};
+use eframe::egui;
+use std::time::{Duration, Instant};
+
+/// Most recent replay events kept for the log
+const MAX_LOG: usize = 200;
+
+/// How the captured packets are replayed, kept while no replay is running
+pub struct ReplaySettings {
+    pub transport: TransportConfig,
+    /// how many times faster than recorded
+    pub speed: f64,
+    /// change fields with `script` before sending, decoding as the capture protocol
+    pub rewrite: bool,
+    pub script: String,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            transport: TransportConfig::Udp {
+                bind: "0.0.0.0:0".to_string(),
+                remote: "127.0.0.1:5000".to_string(),
+            },
+            speed: 1.0,
+            rewrite: false,
+            script: format!(
+                "fn {}(packet) {{\n    // return #{{ field: value }} to change fields, or () to send as recorded\n    ()\n}}",
+                REWRITE_FUNCTION
+            ),
+        }
+    }
+}
+
+pub struct RunningReplay {
+    pub replay: Replay,
+    pub transport: Box<dyn Transport>,
+    pub started: Instant,
+}
+
+/// Send the packets that have come due since the last frame
+pub fn poll(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(running) = &mut app.running_replay else {
+        return;
+    };
+    let events = running.replay.poll(
+        &app.registry,
+        &app.script_engine,
+        running.transport.as_mut(),
+        running.started.elapsed(),
+    );
+    app.replay_log.extend(events);
+    let excess = app.replay_log.len().saturating_sub(MAX_LOG);
+    app.replay_log.drain(..excess);
+    if running.replay.is_finished() {
+        app.running_replay = None;
+    } else {
+        ctx.request_repaint_after(Duration::from_millis(1));
+    }
+}
+
+/// Settings, start/stop controls and log of replaying the packets of the capture page
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_replay;
+    egui::Window::new(tr!("replay-title"))
+        .id(egui::Id::new("replay"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let running = app.running_replay.is_some();
+            ui.add_enabled_ui(!running, |ui| settings(app, ui));
+
+            ui.horizontal(|ui| {
+                if let Some(running) = &app.running_replay {
+                    let (sent, total) = running.replay.progress();
+                    ui.label(tr!("replay-progress", sent = sent, total = total));
+                    if ui.button(tr!("dialog-stop")).clicked() {
+                        app.running_replay = None;
+                    }
+                } else if ui
+                    .button(tr!("dialog-start"))
+                    .on_hover_text(tr!("replay-start-hint"))
+                    .clicked()
+                {
+                    let result = start(app);
+                    app.running_replay = app.report(result);
+                }
+                if ui.button(tr!("dialog-clear-log")).clicked() {
+                    app.replay_log.clear();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for event in &app.replay_log {
+                        match event {
+                            ReplayEvent::Sent(index, data) => {
+                                let hex: Vec<String> =
+                                    data.iter().map(|b| format!("{:02X}", b)).collect();
+                                ui.monospace(format!("#{} → {}", index + 1, hex.join(" ")));
+                            }
+                            ReplayEvent::Error(index, e) => {
+                                ui.colored_label(
+                                    ui.visuals().error_fg_color,
+                                    format!("#{}: {}", index + 1, e),
+                                );
+                            }
+                        }
+                    }
+                });
+        });
+    app.show_replay = open;
+}
+
+fn start(app: &BitLoomApp) -> Result<RunningReplay, String> {
+    let settings = &app.replay;
+    let packets = app.capture.rows.iter().map(|r| r.packet.clone()).collect();
+    let mut replay = Replay::new(packets, settings.speed)?;
+    if settings.rewrite {
+        let protocol_id = app
+            .capture
+            .protocol
+            .as_deref()
+            .ok_or_else(|| tr!("replay-select-protocol"))?;
+        replay = replay.with_rewrite(&app.script_engine, protocol_id, &settings.script)?;
+    }
+    let transport = settings.transport.open()?;
+    Ok(RunningReplay {
+        replay,
+        transport,
+        started: Instant::now(),
+    })
+}
+
+fn settings(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    ui.label(tr!("replay-packets", count = app.capture.rows.len()));
+    let settings = &mut app.replay;
+    egui::Grid::new("replay_settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            widgets::transport_settings(
+                ui,
+                "replay_transport",
+                &mut settings.transport,
+                Some(&tr!("replay-send-to")),
+            );
+            ui.label(tr!("replay-speed"));
+            ui.add(
+                egui::DragValue::new(&mut settings.speed)
+                    .range(0.01..=1000.0)
+                    .speed(0.1)
+                    .suffix("×"),
+            );
+            ui.end_row();
+        });
+
+    ui.checkbox(&mut settings.rewrite, tr!("replay-rewrite"))
+        .on_hover_text(tr!("replay-rewrite-hint"));
+    if settings.rewrite {
+        let variables: Vec<String> = app.script_engine.library_functions();
+        expr_editor::show(
+            ui,
+            "replay_script",
+            &mut settings.script,
+            &app.script_engine,
+            &variables,
+        );
+    }
+}
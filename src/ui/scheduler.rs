@@ -0,0 +1,170 @@
+use crate::app::BitLoomApp;
+use crate::ui::widgets::transport_label;
+use bitloom::scheduler::{Scheduler, TransmitEvent, encode_preset};
+use bitloom::tr;
+use eframe::egui;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Most recent transmit events kept for the log
+const MAX_LOG: usize = 200;
+
+/// Whether a preset is sent periodically, and how often
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScheduleEntry {
+    pub enabled: bool,
+    pub interval_ms: u64,
+}
+
+impl Default for ScheduleEntry {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 1000,
+        }
+    }
+}
+
+/// The periodic packets of the schedule, keyed by preset path
+pub type Schedule = BTreeMap<String, ScheduleEntry>;
+
+pub struct RunningSchedule {
+    pub scheduler: Scheduler,
+    pub started: Instant,
+}
+
+/// Send the packets that have come due since the last frame over the builder's connection
+pub fn poll(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(running) = &mut app.running_schedule else {
+        return;
+    };
+    let Some(connection) = &mut app.builder.connection else {
+        app.running_schedule = None;
+        return;
+    };
+    let events = running
+        .scheduler
+        .poll(connection.as_mut(), running.started.elapsed());
+    app.transmit_log.extend(events);
+    let excess = app.transmit_log.len().saturating_sub(MAX_LOG);
+    app.transmit_log.drain(..excess);
+    ctx.request_repaint_after(Duration::from_millis(1));
+}
+
+/// Presets to send periodically with their intervals, start/stop controls and transmit log
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_scheduler;
+    egui::Window::new(tr!("scheduler-title"))
+        .id(egui::Id::new("scheduler"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let running = app.running_schedule.is_some();
+            match &app.builder.connection {
+                Some(_) => ui.label(tr!(
+                    "scheduler-connection",
+                    transport = transport_label(&app.builder.transport)
+                )),
+                None => ui.weak(tr!(
+                    "scheduler-not-connected",
+                    section = tr!("builder-send-section")
+                )),
+            };
+            ui.add_enabled_ui(!running, |ui| entries(app, ui));
+
+            ui.horizontal(|ui| {
+                if let Some(running) = &app.running_schedule {
+                    let sent: u64 = running.scheduler.packets().iter().map(|p| p.sent).sum();
+                    ui.label(tr!("scheduler-running", sent = sent));
+                    if ui.button(tr!("dialog-stop")).clicked() {
+                        app.running_schedule = None;
+                    }
+                } else if ui
+                    .add_enabled(
+                        app.builder.connection.is_some(),
+                        egui::Button::new(tr!("dialog-start")),
+                    )
+                    .clicked()
+                {
+                    let result = start(app);
+                    app.running_schedule = app.report(result);
+                }
+                if ui.button(tr!("dialog-clear-log")).clicked() {
+                    app.transmit_log.clear();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_salt("transmit_log")
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for event in &app.transmit_log {
+                        match event {
+                            TransmitEvent::Sent(name, data) => {
+                                let hex: Vec<String> =
+                                    data.iter().map(|b| format!("{:02X}", b)).collect();
+                                ui.monospace(format!("{} → {}", name, hex.join(" ")));
+                            }
+                            TransmitEvent::Error(name, e) => {
+                                ui.colored_label(
+                                    ui.visuals().error_fg_color,
+                                    format!("{}: {}", name, e),
+                                );
+                            }
+                        }
+                    }
+                });
+        });
+    app.show_scheduler = open;
+}
+
+/// A row per preset with whether it is sent and its interval
+fn entries(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    if app.presets.is_empty() {
+        ui.weak(tr!("scheduler-no-presets"));
+        return;
+    }
+    egui::ScrollArea::vertical()
+        .id_salt("schedule_entries")
+        .max_height(240.0)
+        .show(ui, |ui| {
+            egui::Grid::new("schedule_entries")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for preset in &app.presets {
+                        let entry = app.schedule.entry(preset.path()).or_default();
+                        ui.checkbox(&mut entry.enabled, preset.path());
+                        ui.add_enabled(
+                            entry.enabled,
+                            egui::DragValue::new(&mut entry.interval_ms)
+                                .range(1..=3_600_000)
+                                .prefix(format!("{} ", tr!("scheduler-every")))
+                                .suffix(" ms"),
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn start(app: &BitLoomApp) -> Result<RunningSchedule, String> {
+    let mut scheduler = Scheduler::new();
+    for preset in &app.presets {
+        let path = preset.path();
+        let Some(entry) = app.schedule.get(&path).filter(|e| e.enabled) else {
+            continue;
+        };
+        let data = encode_preset(&app.registry, &app.script_engine, preset)?;
+        scheduler.add(&path, data, Duration::from_millis(entry.interval_ms))?;
+    }
+    if scheduler.packets().is_empty() {
+        return Err(tr!("scheduler-none-checked"));
+    }
+    Ok(RunningSchedule {
+        scheduler,
+        started: Instant::now(),
+    })
+}
@@ -0,0 +1,219 @@
+use crate::app::BitLoomApp;
+use bitloom::capture::{LINKTYPE_USER0, PcapFile, write_pcap};
+use bitloom::codec::Value;
+use bitloom::models::field::FieldType;
+use bitloom::scrub::{ScrubAction, Scrubber};
+use bitloom::tr;
+use eframe::egui;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// What to do with a field when scrubbing
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ScrubChoice {
+    #[default]
+    Keep,
+    Fixed,
+    Randomize,
+}
+
+impl ScrubChoice {
+    pub const ALL: [ScrubChoice; 3] = [
+        ScrubChoice::Keep,
+        ScrubChoice::Fixed,
+        ScrubChoice::Randomize,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            ScrubChoice::Keep => tr!("scrub-keep"),
+            ScrubChoice::Fixed => tr!("scrub-fixed"),
+            ScrubChoice::Randomize => tr!("scrub-randomize"),
+        }
+    }
+}
+
+/// Fields of the captured packets being chosen for scrubbing, and where to save the result
+#[derive(Default)]
+pub struct ScrubDialog {
+    /// choice and fixed value as typed, by field ID
+    pub choices: BTreeMap<String, (ScrubChoice, String)>,
+    pub path: String,
+    /// what the last scrub did
+    pub status: Option<String>,
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    if app.scrub_dialog.is_none() {
+        return;
+    }
+    let fields = scrubbable_fields(app);
+    let Some(dialog) = &mut app.scrub_dialog else {
+        return;
+    };
+
+    let mut open = true;
+    let mut apply = false;
+    let mut save = false;
+    egui::Window::new(tr!("scrub-title"))
+        .id(egui::Id::new("scrub_capture"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if fields.is_empty() {
+                ui.weak(tr!("scrub-no-fields"));
+            }
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("scrub_fields")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for id in &fields {
+                                let (choice, fixed) = dialog.choices.entry(id.clone()).or_default();
+                                ui.label(id);
+                                ui.horizontal(|ui| {
+                                    for option in ScrubChoice::ALL {
+                                        ui.selectable_value(choice, option, option.label());
+                                    }
+                                });
+                                if *choice == ScrubChoice::Fixed {
+                                    let valid = Value::parse_literal(fixed).is_ok();
+                                    let mut edit = egui::TextEdit::singleline(fixed)
+                                        .font(egui::TextStyle::Monospace)
+                                        .desired_width(120.0)
+                                        .hint_text("0");
+                                    if !valid {
+                                        edit = edit.text_color(ui.visuals().error_fg_color);
+                                    }
+                                    ui.add(edit);
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            apply = ui
+                .button(tr!("scrub-apply"))
+                .on_hover_text(tr!("scrub-apply-hint"))
+                .clicked();
+            if let Some(status) = &dialog.status {
+                ui.label(status);
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(tr!("dialog-path"));
+                ui.add(egui::TextEdit::singleline(&mut dialog.path).hint_text("scrubbed.pcap"));
+                save = ui
+                    .button(tr!("scrub-save"))
+                    .on_hover_text(tr!("scrub-save-hint"))
+                    .clicked();
+            });
+        });
+
+    if apply {
+        let result = scrub(app);
+        if let Some(status) = app.report(result)
+            && let Some(dialog) = &mut app.scrub_dialog
+        {
+            dialog.status = Some(status);
+        }
+    }
+    if save {
+        let result = save_pcap(app);
+        app.report(result);
+    }
+    if !open {
+        app.scrub_dialog = None;
+    }
+}
+
+/// IDs of the wire fields that take a value in the protocols the captured packets decoded as
+fn scrubbable_fields(app: &BitLoomApp) -> Vec<String> {
+    let protocols: BTreeSet<&str> = app
+        .capture
+        .rows
+        .iter()
+        .filter_map(|r| Some(r.decoded.as_ref()?.as_ref().ok()?.protocol_id.as_str()))
+        .collect();
+    let mut fields = Vec::new();
+    for protocol_id in protocols {
+        for rule in app.registry.resolve_fields(protocol_id).unwrap_or_default() {
+            let takes_value = !matches!(rule.field_type, FieldType::Fixed(_) | FieldType::Expr(_));
+            if takes_value && !rule.is_virtual() && !fields.contains(&rule.id) {
+                fields.push(rule.id);
+            }
+        }
+    }
+    fields
+}
+
+/// Replace the chosen fields in every captured packet. Returns a summary.
+fn scrub(app: &mut BitLoomApp) -> Result<String, String> {
+    let dialog = app
+        .scrub_dialog
+        .as_ref()
+        .ok_or_else(|| tr!("scrub-closed"))?;
+    let protocol_id = app
+        .capture
+        .protocol
+        .clone()
+        .ok_or_else(|| tr!("scrub-select-protocol"))?;
+    let mut actions = HashMap::new();
+    for (id, (choice, fixed)) in &dialog.choices {
+        let action = match choice {
+            ScrubChoice::Keep => continue,
+            ScrubChoice::Fixed => ScrubAction::Fixed(
+                Value::parse_literal(fixed)
+                    .map_err(|e| tr!("scrub-invalid-value", field = id.as_str(), error = e))?,
+            ),
+            ScrubChoice::Randomize => ScrubAction::Randomize,
+        };
+        actions.insert(id.clone(), action);
+    }
+    if actions.is_empty() {
+        return Err(tr!("scrub-no-choice"));
+    }
+
+    let mut scrubber = Scrubber::new(actions);
+    let capture = &mut app.capture;
+    let total = capture.rows.len();
+    capture.rows.retain_mut(|row| {
+        match scrubber.scrub(
+            &app.registry,
+            &app.script_engine,
+            &protocol_id,
+            &row.packet.data,
+        ) {
+            Ok(data) => {
+                row.packet.data = data;
+                true
+            }
+            Err(_) => false,
+        }
+    });
+    let dropped = total - capture.rows.len();
+    capture.selected = None;
+    capture.redecode(&app.registry, &app.script_engine);
+    Ok(tr!(
+        "scrub-done",
+        scrubbed = capture.rows.len(),
+        dropped = dropped
+    ))
+}
+
+fn save_pcap(app: &BitLoomApp) -> Result<(), String> {
+    let path = app
+        .scrub_dialog
+        .as_ref()
+        .map(|d| d.path.trim())
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| tr!("scrub-no-path"))?;
+    let file = PcapFile {
+        link_type: app.capture.link_type.unwrap_or(LINKTYPE_USER0),
+        packets: app.capture.rows.iter().map(|r| r.packet.clone()).collect(),
+    };
+    std::fs::write(path, write_pcap(&file))
+        .map_err(|e| tr!("scrub-write-failed", path = path, error = e.to_string()))
+}
@@ -1,7 +1,11 @@
-use crate::app::BitLoomApp;
+use crate::app::{BitLoomApp, ViewPage};
+use crate::ui::delete_dialog;
+use crate::ui::widgets::{RowKey, name_button, name_row, row_key};
+use bitloom::models::protocol::{Endianness, Protocol, ProtocolRegistry};
+use bitloom::tr;
 use eframe::egui;
 
-pub fn show(_app: &mut BitLoomApp, ctx: &egui::Context) {
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
     egui::SidePanel::left("sidebar")
         .resizable(true)
         .default_width(200.0)
@@ -10,20 +14,142 @@ pub fn show(_app: &mut BitLoomApp, ctx: &egui::Context) {
 
             ui.horizontal(|ui| {
                 ui.add_space(4.0); // left margin
-                ui.strong("Protocols");
+                ui.strong(tr!("sidebar-protocols"));
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.add_space(4.0); // right margin
                     // new protocol button
-                    if ui.small_button("+").clicked() {
-                        todo!();
+                    let new = ui.small_button("+");
+                    name_button(&new, &tr!("sidebar-new"));
+                    if new.clicked() {
+                        new_protocol(app);
+                    }
+                    let sort = ui.small_button("⇅").on_hover_text(tr!("sidebar-sort"));
+                    name_button(&sort, &tr!("sidebar-sort"));
+                    if sort.clicked() {
+                        app.registry.sort_protocols();
                     }
                 });
             });
 
             ui.separator();
 
-            // TODO: protocol list
-            ui.label("Protocol 1");
+            let mut delete = None;
+            let mut moved = None;
+            let mut select = None;
+            let mut step = None;
+            let rows = tree(&app.registry);
+            let mut row_ids = Vec::new();
+            for (row, &(depth, proto)) in rows.iter().enumerate() {
+                let selected = app.selected_protocol.as_deref() == Some(proto.id.as_str());
+                let label = proto.name.as_deref().unwrap_or(&proto.id);
+                // keyed by protocol, so that the keyboard focus stays with a protocol as it moves
+                let response = ui
+                    .push_id(&proto.id, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add_space(depth as f32 * 12.0);
+                            ui.selectable_label(selected, label)
+                        })
+                        .inner
+                    })
+                    .inner;
+                let name = match &proto.parent_id {
+                    Some(parent) => tr!(
+                        "sidebar-subprotocol",
+                        name = label,
+                        parent = parent.as_str()
+                    ),
+                    None => label.to_string(),
+                };
+                name_row(&response, selected, &name);
+                row_ids.push(response.id);
+                if response.clicked() && !selected {
+                    select = Some(row);
+                }
+                // Alt with the arrows reorders, like Move Up and Move Down
+                match row_key(ui, &response) {
+                    Some((RowKey::Previous, m)) if m.alt => moved = Some((proto.id.clone(), true)),
+                    Some((RowKey::Next, m)) if m.alt => moved = Some((proto.id.clone(), false)),
+                    Some((RowKey::Previous, _)) if row > 0 => step = Some(row - 1),
+                    Some((RowKey::Next, _)) if row + 1 < rows.len() => step = Some(row + 1),
+                    _ => {}
+                }
+                if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Delete)) {
+                    delete = Some(proto.id.clone());
+                }
+                response.context_menu(|ui| {
+                    if ui
+                        .add(egui::Button::new(tr!("sidebar-move-up")).shortcut_text("Alt+↑"))
+                        .clicked()
+                    {
+                        moved = Some((proto.id.clone(), true));
+                        ui.close();
+                    }
+                    if ui
+                        .add(egui::Button::new(tr!("sidebar-move-down")).shortcut_text("Alt+↓"))
+                        .clicked()
+                    {
+                        moved = Some((proto.id.clone(), false));
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui
+                        .add(egui::Button::new(tr!("sidebar-delete")).shortcut_text("Del"))
+                        .clicked()
+                    {
+                        delete = Some(proto.id.clone());
+                        ui.close();
+                    }
+                });
+            }
+            if let Some(row) = step {
+                ui.memory_mut(|m| m.request_focus(row_ids[row]));
+                select = Some(row);
+            }
+            if let Some(row) = select {
+                app.selected_protocol = Some(rows[row].1.id.clone());
+                app.selected_field = None;
+                app.selected_fields.clear();
+            }
+            if let Some((id, up)) = moved {
+                let result = app.registry.move_protocol(&id, up);
+                app.report(result);
+            }
+            if let Some(id) = delete {
+                delete_dialog::confirm(app, &id);
+            }
         });
 }
+
+/// Add an empty protocol with an unused ID and open it in the designer
+fn new_protocol(app: &mut BitLoomApp) {
+    let id = (1..)
+        .map(|n| match n {
+            1 => "new_protocol".to_string(),
+            n => format!("new_protocol_{}", n),
+        })
+        .find(|id| app.registry.get_protocol(id).is_none())
+        .expect("an unused ID");
+    let result = app
+        .registry
+        .create_protocol(&id, None, Endianness::Big, None);
+    if app.report(result).is_some() {
+        app.selected_protocol = Some(id);
+        app.selected_field = None;
+        app.selected_fields.clear();
+        app.current_page = ViewPage::ProtocolDesigner;
+    }
+}
+
+/// The protocols with their depth in the inheritance tree, each followed by its subprotocols
+fn tree(registry: &ProtocolRegistry) -> Vec<(usize, &Protocol)> {
+    let mut protocols = Vec::new();
+    let mut stack: Vec<(usize, &Protocol)> = registry.iter_roots().map(|p| (0, p)).collect();
+    stack.reverse();
+    while let Some((depth, proto)) = stack.pop() {
+        protocols.push((depth, proto));
+        let children: Vec<_> = registry.children_of(&proto.id).collect();
+        stack.extend(children.into_iter().rev().map(|p| (depth + 1, p)));
+    }
+    protocols
+}
@@ -0,0 +1,197 @@
+use crate::app::BitLoomApp;
+use crate::ui::{expr_editor, widgets};
+use bitloom::codec::stream::StreamDecoder;
+use bitloom::simulator::{HANDLER_FUNCTION, Simulator, SimulatorEvent};
+use bitloom::tr;
+use bitloom::transport::{Transport, TransportConfig};
+use eframe::egui;
+use std::time::Duration;
+
+/// Most recent simulator events kept for the log
+const MAX_LOG: usize = 200;
+
+/// How the simulator is set up, kept while it is stopped
+pub struct SimulatorSettings {
+    pub transport: TransportConfig,
+    pub request_protocol: Option<String>,
+    pub response_protocol: Option<String>,
+    /// split what arrives into requests by the length of the request protocol
+    pub split_stream: bool,
+    pub script: String,
+}
+
+impl Default for SimulatorSettings {
+    fn default() -> Self {
+        Self {
+            transport: TransportConfig::Udp {
+                bind: "127.0.0.1:5000".to_string(),
+                remote: String::new(),
+            },
+            request_protocol: None,
+            response_protocol: None,
+            split_stream: false,
+            script: format!(
+                "fn {}(packet) {{\n    // return #{{ field: value }} to reply, or () to stay silent\n    #{{}}\n}}",
+                HANDLER_FUNCTION
+            ),
+        }
+    }
+}
+
+pub struct RunningSimulator {
+    pub simulator: Simulator,
+    pub transport: Box<dyn Transport>,
+}
+
+/// Answer packets that arrived since the last frame
+pub fn poll(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(running) = &mut app.running_simulator else {
+        return;
+    };
+    let events = running.simulator.poll(
+        &app.registry,
+        &app.script_engine,
+        running.transport.as_mut(),
+    );
+    app.simulator_log.extend(events);
+    let excess = app.simulator_log.len().saturating_sub(MAX_LOG);
+    app.simulator_log.drain(..excess);
+    ctx.request_repaint_after(Duration::from_millis(10));
+}
+
+/// Settings, start/stop controls and event log of the device simulator
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_simulator;
+    egui::Window::new(tr!("simulator-title"))
+        .id(egui::Id::new("simulator"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let running = app.running_simulator.is_some();
+            ui.add_enabled_ui(!running, |ui| settings(app, ui));
+
+            ui.horizontal(|ui| {
+                if running {
+                    ui.label(tr!("simulator-running"));
+                    if ui.button(tr!("dialog-stop")).clicked() {
+                        app.running_simulator = None;
+                    }
+                } else if ui.button(tr!("dialog-start")).clicked() {
+                    let result = start(app);
+                    app.running_simulator = app.report(result);
+                }
+                if ui.button(tr!("dialog-clear-log")).clicked() {
+                    app.simulator_log.clear();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for event in &app.simulator_log {
+                        match event {
+                            SimulatorEvent::Received(packet) => {
+                                let fields: Vec<String> = packet
+                                    .fields
+                                    .iter()
+                                    .map(|f| format!("{}={}", f.rule_id, f.value))
+                                    .collect();
+                                ui.monospace(format!("← {}", fields.join(" ")));
+                            }
+                            SimulatorEvent::Sent(data) => {
+                                let hex: Vec<String> =
+                                    data.iter().map(|b| format!("{:02X}", b)).collect();
+                                ui.monospace(format!("→ {}", hex.join(" ")));
+                            }
+                            SimulatorEvent::Error(e) => {
+                                ui.colored_label(ui.visuals().error_fg_color, e);
+                            }
+                        }
+                    }
+                });
+        });
+    app.show_simulator = open;
+}
+
+fn start(app: &BitLoomApp) -> Result<RunningSimulator, String> {
+    let settings = &app.simulator;
+    let (Some(request), Some(response)) = (&settings.request_protocol, &settings.response_protocol)
+    else {
+        return Err(tr!("simulator-select-protocols"));
+    };
+    let mut simulator = Simulator::new(&app.script_engine, request, response, &settings.script)?;
+    if settings.split_stream {
+        simulator.stream = Some(StreamDecoder::new());
+    }
+    let transport = settings.transport.open()?;
+    Ok(RunningSimulator {
+        simulator,
+        transport,
+    })
+}
+
+fn settings(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let protocol_ids: Vec<String> = app
+        .registry
+        .list_protocols()
+        .into_iter()
+        .map(|p| p.id.clone())
+        .collect();
+    let settings = &mut app.simulator;
+
+    egui::Grid::new("simulator_settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            widgets::transport_settings(
+                ui,
+                "simulator_transport",
+                &mut settings.transport,
+                Some(&tr!("simulator-reply-to")),
+            );
+
+            ui.label(tr!("simulator-request"));
+            protocol_picker(
+                ui,
+                "simulator_request",
+                &protocol_ids,
+                &mut settings.request_protocol,
+            );
+            ui.end_row();
+            ui.label(tr!("simulator-response"));
+            protocol_picker(
+                ui,
+                "simulator_response",
+                &protocol_ids,
+                &mut settings.response_protocol,
+            );
+            ui.end_row();
+        });
+    ui.checkbox(&mut settings.split_stream, tr!("simulator-split"))
+        .on_hover_text(tr!("simulator-split-hint"));
+
+    let variables: Vec<String> = app.script_engine.library_functions();
+    expr_editor::show(
+        ui,
+        "simulator_script",
+        &mut settings.script,
+        &app.script_engine,
+        &variables,
+    );
+}
+
+fn protocol_picker(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    protocol_ids: &[String],
+    selected: &mut Option<String>,
+) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected.clone().unwrap_or_else(|| tr!("simulator-select")))
+        .show_ui(ui, |ui| {
+            for id in protocol_ids {
+                ui.selectable_value(selected, Some(id.clone()), id);
+            }
+        });
+}
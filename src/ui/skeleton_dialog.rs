@@ -0,0 +1,108 @@
+use crate::app::{BitLoomApp, ViewPage};
+use bitloom::analysis::fields::{MAX_PACKETS, SuggestedField, skeleton_fields, suggest_fields};
+use bitloom::models::protocol::{Endianness, Protocol};
+use bitloom::tr;
+use eframe::egui;
+
+/// Fields guessed from the captured packets, to be made into a new protocol
+pub struct SkeletonDialog {
+    pub fields: Vec<SuggestedField>,
+    /// number of packets the fields were guessed from
+    pub packets: usize,
+    /// ID of the protocol to create
+    pub protocol_id: String,
+}
+
+impl SkeletonDialog {
+    /// Guess the fields of the captured packets, which should all be of one type
+    pub fn from_capture(app: &BitLoomApp) -> Result<Self, String> {
+        let packets: Vec<&[u8]> = app
+            .capture
+            .rows
+            .iter()
+            .map(|row| row.packet.data.as_slice())
+            .collect();
+        Ok(Self {
+            fields: suggest_fields(&packets)?,
+            packets: packets.len().min(MAX_PACKETS),
+            protocol_id: "suggested".to_string(),
+        })
+    }
+}
+
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let Some(dialog) = &mut app.skeleton_dialog else {
+        return;
+    };
+
+    let mut open = true;
+    let mut create = false;
+    egui::Window::new(tr!("skeleton-title"))
+        .id(egui::Id::new("suggested_fields"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.weak(tr!("skeleton-guessed", count = dialog.packets));
+            egui::Grid::new("suggested_fields")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(tr!("skeleton-bytes"));
+                    ui.strong(tr!("skeleton-length"));
+                    ui.strong(tr!("skeleton-looks-like"));
+                    ui.end_row();
+                    for field in &dialog.fields {
+                        ui.monospace(match field.len {
+                            Some(len) => format!("{}-{}", field.offset, field.offset + len - 1),
+                            None => format!("{}-", field.offset),
+                        });
+                        ui.label(match field.len {
+                            Some(len) if len > 1 && field.byte_order == Endianness::Little => {
+                                tr!("skeleton-length-little", bytes = len)
+                            }
+                            Some(len) => tr!("skeleton-length-bytes", bytes = len),
+                            None => tr!("skeleton-variable"),
+                        });
+                        ui.label(field.label());
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(tr!("skeleton-protocol-id"));
+                ui.text_edit_singleline(&mut dialog.protocol_id);
+                create = ui
+                    .button(tr!("skeleton-create"))
+                    .on_hover_text(tr!("skeleton-create-hint"))
+                    .clicked();
+            });
+        });
+
+    if create {
+        let result = create_protocol(app);
+        if app.report(result).is_some() {
+            open = false;
+        }
+    }
+    if !open {
+        app.skeleton_dialog = None;
+    }
+}
+
+/// Add a protocol of the suggested fields and select it
+fn create_protocol(app: &mut BitLoomApp) -> Result<(), String> {
+    let Some(dialog) = &app.skeleton_dialog else {
+        return Ok(());
+    };
+    let id = dialog.protocol_id.trim().to_string();
+    let mut protocol = Protocol::new(&id, None, Endianness::Big, None);
+    for field in skeleton_fields(&dialog.fields) {
+        protocol.add_field(field)?;
+    }
+    app.registry.add_protocols(vec![protocol])?;
+    app.selected_protocol = Some(id);
+    app.selected_field = None;
+    app.selected_fields.clear();
+    app.current_page = ViewPage::ProtocolDesigner;
+    Ok(())
+}
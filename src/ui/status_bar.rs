@@ -0,0 +1,111 @@
+use crate::app::BitLoomApp;
+use crate::ui::hex_view::selected_bytes;
+use crate::ui::widgets::transport_label;
+use bitloom::tr;
+use eframe::egui;
+
+/// Bar along the bottom of the window with the selected protocol, the position of the pointer
+/// and the selection in the hex view, how the packet decoded, and what is running in the
+/// background
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            let protocol = app
+                .selected_protocol
+                .as_deref()
+                .and_then(|id| app.registry.get_protocol(id));
+            match protocol {
+                Some(proto) => ui.label(proto.name.as_deref().unwrap_or(&proto.id)),
+                None => ui.weak(tr!("status-no-protocol")),
+            };
+            ui.separator();
+
+            match app.hex_cursor {
+                Some(byte) => ui.label(tr!(
+                    "status-offset",
+                    hex = format!("{:04x}", byte),
+                    offset = byte,
+                    bit = byte * 8
+                )),
+                None => ui.weak(tr!("status-no-offset")),
+            };
+            if let Some(selected) = selected_bytes(app) {
+                ui.separator();
+                let len = selected.end() - selected.start() + 1;
+                ui.label(tr!(
+                    "status-selected",
+                    bytes = len,
+                    bits = len * 8,
+                    hex = format!("{:04x}", selected.start())
+                ));
+            }
+            ui.separator();
+
+            decode_status(app, ui);
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                background_tasks(app, ui);
+            });
+        });
+    });
+}
+
+/// Whether the packet in the hex view was decoded, and the problems found in it
+fn decode_status(app: &BitLoomApp, ui: &mut egui::Ui) {
+    if let Some(error) = &app.decode_error {
+        ui.colored_label(ui.visuals().error_fg_color, tr!("status-decode-failed"))
+            .on_hover_text(&error.message);
+    } else if let Some(packet) = &app.decoded {
+        ui.label(tr!(
+            "status-decoded",
+            protocol = packet.protocol_id.as_str()
+        ));
+        if !packet.issues.is_empty() {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                tr!("status-issues", count = packet.issues.len()),
+            );
+        }
+    } else {
+        ui.weak(tr!("status-not-decoded"));
+    }
+}
+
+/// Capture, replay, scheduler, simulator, API server and builder connection, when they are
+/// running
+fn background_tasks(app: &BitLoomApp, ui: &mut egui::Ui) {
+    let task = |ui: &mut egui::Ui, text: String| {
+        ui.label(text);
+        ui.spinner();
+        ui.separator();
+    };
+    if let Some(server) = &app.api_server {
+        let address = server
+            .local_addr()
+            .map_or_else(|| app.api_server_address.clone(), |a| a.to_string());
+        task(ui, tr!("status-api-server", address = address));
+    }
+    if app.running_simulator.is_some() {
+        task(ui, tr!("status-simulator"));
+    }
+    if let Some(running) = &app.running_replay {
+        let (sent, total) = running.replay.progress();
+        task(ui, tr!("status-replaying", sent = sent, total = total));
+    }
+    if let Some(running) = &app.running_schedule {
+        let count = running.scheduler.packets().len();
+        task(ui, tr!("status-scheduling", count = count));
+    }
+    if app.builder.connection.is_some() {
+        task(
+            ui,
+            tr!(
+                "status-builder",
+                transport = transport_label(&app.builder.transport)
+            ),
+        );
+    }
+    if app.capture.is_live() {
+        task(ui, tr!("status-capturing", count = app.capture.rows.len()));
+    }
+}
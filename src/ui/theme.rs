@@ -0,0 +1,295 @@
+use crate::app::BitLoomApp;
+use crate::ui::widgets::{color_swatch, display_format_picker, optional_color};
+use bitloom::i18n::{self, Language};
+use bitloom::models::field::DisplayFormat;
+use bitloom::models::protocol::ProtocolRegistry;
+use bitloom::tr;
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Key the appearance settings are persisted under
+pub const STORAGE_KEY: &str = "appearance";
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    /// follow the light or dark setting of the operating system
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Dark, Theme::Light];
+
+    fn preference(self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Dark => egui::ThemePreference::Dark,
+            Theme::Light => egui::ThemePreference::Light,
+        }
+    }
+}
+
+/// Colors fields are highlighted with, in the order they are assigned
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Palette {
+    /// Okabe & Ito, distinguishable with all common forms of color blindness
+    OkabeIto,
+    /// Paul Tol's bright qualitative scheme
+    TolBright,
+    /// Paul Tol's light qualitative scheme, readable behind text in either theme
+    TolLight,
+    Custom(Vec<[u8; 3]>),
+}
+
+const OKABE_ITO: &[[u8; 3]] = &[
+    [0xe6, 0x9f, 0x00],
+    [0x56, 0xb4, 0xe9],
+    [0x00, 0x9e, 0x73],
+    [0xf0, 0xe4, 0x42],
+    [0x00, 0x72, 0xb2],
+    [0xd5, 0x5e, 0x00],
+    [0xcc, 0x79, 0xa7],
+    [0x99, 0x99, 0x99],
+];
+
+const TOL_BRIGHT: &[[u8; 3]] = &[
+    [0x44, 0x77, 0xaa],
+    [0xee, 0x66, 0x77],
+    [0x22, 0x88, 0x33],
+    [0xcc, 0xbb, 0x44],
+    [0x66, 0xcc, 0xee],
+    [0xaa, 0x33, 0x77],
+    [0xbb, 0xbb, 0xbb],
+];
+
+const TOL_LIGHT: &[[u8; 3]] = &[
+    [0x77, 0xaa, 0xdd],
+    [0xee, 0x88, 0x66],
+    [0xee, 0xdd, 0x88],
+    [0xff, 0xaa, 0xbb],
+    [0x99, 0xdd, 0xff],
+    [0x44, 0xbb, 0x99],
+    [0xbb, 0xcc, 0x33],
+    [0xaa, 0xaa, 0x00],
+    [0xdd, 0xdd, 0xdd],
+];
+
+impl Palette {
+    pub const PRESETS: [Palette; 3] = [Palette::OkabeIto, Palette::TolBright, Palette::TolLight];
+
+    pub fn label(&self) -> String {
+        match self {
+            Palette::OkabeIto => "Okabe-Ito".to_string(),
+            Palette::TolBright => "Tol Bright".to_string(),
+            Palette::TolLight => "Tol Light".to_string(),
+            Palette::Custom(_) => tr!("appearance-custom"),
+        }
+    }
+
+    pub fn colors(&self) -> &[[u8; 3]] {
+        match self {
+            Palette::OkabeIto => OKABE_ITO,
+            Palette::TolBright => TOL_BRIGHT,
+            Palette::TolLight => TOL_LIGHT,
+            Palette::Custom(colors) => colors,
+        }
+    }
+
+    /// Color of the `index`th field, repeating the palette once it runs out
+    pub fn color(&self, index: usize) -> Color32 {
+        let colors = self.colors();
+        if colors.is_empty() {
+            return Color32::GRAY;
+        }
+        let [r, g, b] = colors[index % colors.len()];
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// Look of the application, persisted between sessions
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub theme: Theme,
+    /// color of selections and links, instead of the egui default
+    pub accent: Option<[u8; 3]>,
+    pub palette: Palette,
+    /// how values of fields without a format of their own are shown
+    pub display: DisplayFormat,
+    pub language: Language,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            theme: Theme::System,
+            accent: None,
+            palette: Palette::OkabeIto,
+            display: DisplayFormat::Decimal,
+            language: Language::English,
+        }
+    }
+}
+
+impl Appearance {
+    /// Set the theme and accent color of every egui style
+    pub fn apply(&self, ctx: &egui::Context) {
+        i18n::set_language(self.language);
+        ctx.set_theme(self.theme.preference());
+        for theme in [egui::Theme::Dark, egui::Theme::Light] {
+            ctx.style_mut_of(theme, |style| {
+                let default = theme.default_visuals();
+                let visuals = &mut style.visuals;
+                match self.accent {
+                    Some([r, g, b]) => {
+                        let accent = Color32::from_rgb(r, g, b);
+                        visuals.selection.bg_fill = accent;
+                        visuals.selection.stroke.color = text_color_on(accent);
+                        visuals.hyperlink_color = accent;
+                    }
+                    None => {
+                        visuals.selection = default.selection;
+                        visuals.hyperlink_color = default.hyperlink_color;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Color of each field of a protocol and the protocols it inherits from, by field ID.
+    /// Fields without a color of their own take the next palette color in wire order.
+    pub fn field_colors(
+        &self,
+        registry: &ProtocolRegistry,
+        protocol_id: &str,
+    ) -> HashMap<String, Color32> {
+        let Ok(layout) = registry.layout(protocol_id) else {
+            return HashMap::new();
+        };
+        layout
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let color = match field.color {
+                    Some([r, g, b]) => Color32::from_rgb(r, g, b),
+                    None => self.palette.color(i),
+                };
+                (field.id.clone(), color)
+            })
+            .collect()
+    }
+}
+
+/// Black or white, whichever is easier to read on `background`
+pub fn text_color_on(background: Color32) -> Color32 {
+    let [r, g, b, _] = background.to_array();
+    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luma > 140.0 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
+
+/// Window choosing the theme, accent color and field palette
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let before = app.appearance.clone();
+    let appearance = &mut app.appearance;
+    egui::Window::new(tr!("appearance-title"))
+        .id(egui::Id::new("appearance"))
+        .open(&mut app.show_appearance)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("appearance").num_columns(2).show(ui, |ui| {
+                ui.label(tr!("appearance-language"));
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(appearance.language.label())
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(
+                                &mut appearance.language,
+                                language,
+                                language.label(),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(tr!("appearance-theme"));
+                ui.horizontal(|ui| {
+                    for theme in Theme::ALL {
+                        ui.selectable_value(&mut appearance.theme, theme, format!("{:?}", theme));
+                    }
+                });
+                ui.end_row();
+
+                ui.label(tr!("appearance-accent"));
+                let default = ui.visuals().selection.bg_fill;
+                optional_color(ui, &mut appearance.accent, default);
+                ui.end_row();
+
+                ui.label(tr!("appearance-field-colors"));
+                egui::ComboBox::from_id_salt("palette")
+                    .selected_text(appearance.palette.label())
+                    .show_ui(ui, |ui| {
+                        for preset in Palette::PRESETS {
+                            let label = preset.label();
+                            ui.selectable_value(&mut appearance.palette, preset, label);
+                        }
+                        let custom = Palette::Custom(appearance.palette.colors().to_vec());
+                        if ui
+                            .selectable_label(
+                                matches!(appearance.palette, Palette::Custom(_)),
+                                tr!("appearance-custom"),
+                            )
+                            .on_hover_text(tr!("appearance-custom-hint"))
+                            .clicked()
+                        {
+                            appearance.palette = custom;
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(tr!("appearance-values"));
+                display_format_picker(ui, "display_format", &mut appearance.display);
+                ui.end_row();
+            });
+
+            ui.horizontal_wrapped(|ui| match &mut appearance.palette {
+                Palette::Custom(colors) => {
+                    let mut remove = None;
+                    for (i, color) in colors.iter_mut().enumerate() {
+                        ui.color_edit_button_srgb(color)
+                            .on_hover_text(tr!("appearance-remove-hint"))
+                            .context_menu(|ui| {
+                                if ui.button(tr!("appearance-remove")).clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                    }
+                    if let Some(i) = remove {
+                        colors.remove(i);
+                    }
+                    if ui
+                        .small_button("+")
+                        .on_hover_text(tr!("appearance-add-color"))
+                        .clicked()
+                    {
+                        colors.push([0x80, 0x80, 0x80]);
+                    }
+                }
+                palette => {
+                    for i in 0..palette.colors().len() {
+                        color_swatch(ui, palette.color(i));
+                    }
+                }
+            });
+        });
+
+    if app.appearance != before {
+        app.appearance.apply(ctx);
+    }
+}
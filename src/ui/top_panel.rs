@@ -1,14 +1,102 @@
 use crate::app::{BitLoomApp, ViewPage};
+use crate::ui::codegen_dialog::CodegenDialog;
+use crate::ui::delete_dialog;
+use crate::ui::export_dialog::PendingExport;
+use crate::ui::import_dialog::{ImportFormat, PendingImport};
+use bitloom::codec::Value;
+use bitloom::export::binary_template::binary_template;
+use bitloom::export::bytefield::bytefield;
+use bitloom::export::dbc::dbc_database;
+use bitloom::export::definitions::protocol_definitions;
+use bitloom::export::diagram::{ROW_BITS, ascii_diagram};
+use bitloom::export::fuzz::fuzz_target;
+use bitloom::export::golden::{bundle_file_name, golden_bundle, golden_harness};
+use bitloom::export::markdown::protocol_documentation;
+use bitloom::export::negative::{default_values, negative_corpus};
+use bitloom::export::parquet::decoded_parquet;
+use bitloom::export::report::{ReportPacket, html_report, summarize_capture};
+use bitloom::export::scapy::scapy_module;
+use bitloom::models::schema::project_schema;
+use bitloom::script::plugins::PLUGIN_DIR;
+use bitloom::tr;
 use eframe::egui;
 
 pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    // text inputs have an undo of their own
+    if ctx.memory(|m| m.focused().is_none())
+        && ctx.input_mut(|i| i.consume_shortcut(&delete_dialog::UNDO_SHORTCUT))
+    {
+        delete_dialog::undo(app);
+    }
+
     egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
         egui::MenuBar::new().ui(ui, |ui| {
-            ui.menu_button("File", |ui| {
-                if ui.button("New").clicked() {}
-                if ui.button("Open").clicked() {}
+            ui.menu_button(tr!("menu-file"), |ui| {
+                if ui.button(tr!("menu-new")).clicked() {
+                    // TODO: create a new project
+                }
+                if ui.button(tr!("menu-open")).clicked() {
+                    app.pending_open = Some(String::new());
+                }
+                ui.separator();
+                ui.menu_button(tr!("menu-import"), |ui| {
+                    for format in [ImportFormat::Dbc, ImportFormat::CHeader] {
+                        if ui.button(format.label()).clicked() {
+                            app.pending_import = Some(PendingImport::new(format));
+                        }
+                    }
+                });
+                ui.add_enabled_ui(app.selected_protocol.is_some(), |ui| {
+                    ui.menu_button(tr!("menu-export"), |ui| export_menu(app, ui));
+                    if ui.button(tr!("menu-generate-code")).clicked() {
+                        app.codegen_dialog = Some(CodegenDialog::new());
+                    }
+                });
+            });
+            ui.menu_button(tr!("menu-edit"), |ui| {
+                let label = match app.trash.last() {
+                    Some(deletion) => tr!(
+                        "menu-undo-delete-protocol",
+                        protocol = deletion.protocol_id()
+                    ),
+                    None => tr!("menu-undo-delete"),
+                };
+                let undo = egui::Button::new(label)
+                    .shortcut_text(ui.ctx().format_shortcut(&delete_dialog::UNDO_SHORTCUT));
+                if ui.add_enabled(!app.trash.is_empty(), undo).clicked() {
+                    delete_dialog::undo(app);
+                }
+                ui.separator();
+                ui.checkbox(&mut app.show_trash, tr!("menu-recently-deleted"));
+                if ui.button(tr!("menu-check-integrity")).clicked() {
+                    app.integrity_issues = Some(app.registry.check_integrity());
+                }
+            });
+            ui.menu_button(tr!("menu-view"), |ui| {
+                ui.checkbox(&mut app.show_where_used, tr!("menu-where-used"));
+                ui.checkbox(&mut app.show_compare, tr!("menu-compare"));
+                ui.checkbox(&mut app.show_coverage, tr!("menu-coverage"));
+                ui.checkbox(&mut app.show_bit_accounting, tr!("menu-bit-accounting"));
+                ui.checkbox(&mut app.show_history, tr!("menu-history"));
+                ui.checkbox(&mut app.show_bookmarks, tr!("menu-bookmarks"));
+                ui.checkbox(&mut app.show_simulator, tr!("menu-simulator"));
+                ui.checkbox(&mut app.show_replay, tr!("menu-replay"));
+                ui.checkbox(&mut app.show_scheduler, tr!("menu-scheduler"));
+                ui.checkbox(&mut app.show_api_server, tr!("menu-api-server"));
+                ui.checkbox(&mut app.show_bit_calculator, tr!("menu-bit-calculator"));
+                ui.checkbox(
+                    &mut app.show_checksum_calculator,
+                    tr!("menu-checksum-calculator"),
+                );
+                ui.separator();
+                ui.checkbox(&mut app.show_appearance, tr!("menu-appearance"));
+            });
+            ui.menu_button(tr!("menu-plugins"), |ui| plugins_menu(app, ui));
+            ui.menu_button(tr!("menu-help"), |ui| {
+                if ui.button(tr!("menu-about")).clicked() {
+                    // TODO: about dialog
+                }
             });
-            ui.menu_button("Help", |ui| if ui.button("About").clicked() {});
         });
     });
 
@@ -17,13 +105,306 @@ pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
             ui.selectable_value(
                 &mut app.current_page,
                 ViewPage::ProtocolDesigner,
-                "Protocol Designer",
+                tr!("page-protocol-designer"),
             );
             ui.selectable_value(
                 &mut app.current_page,
                 ViewPage::PacketBuilder,
-                "Packet Builder",
+                tr!("page-packet-builder"),
+            );
+            ui.selectable_value(
+                &mut app.current_page,
+                ViewPage::Capture,
+                tr!("page-capture"),
+            );
+            ui.selectable_value(
+                &mut app.current_page,
+                ViewPage::Scripts,
+                tr!("page-scripts"),
+            );
+            ui.selectable_value(
+                &mut app.current_page,
+                ViewPage::Console,
+                tr!("page-console"),
             );
         });
     });
 }
+
+/// Exports of the selected protocol
+fn export_menu(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    let Some(protocol_id) = app.selected_protocol.clone() else {
+        return;
+    };
+
+    if ui.button(tr!("export-documentation")).clicked() {
+        let result = protocol_documentation(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-documentation-title"),
+                &format!("{}.md", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui
+        .button(tr!("export-ascii-diagram"))
+        .on_hover_text(tr!("export-ascii-diagram-hint"))
+        .clicked()
+    {
+        let result = ascii_diagram(&app.registry, &protocol_id, ROW_BITS);
+        if let Some(diagram) = app.report(result) {
+            ui.ctx().copy_text(diagram);
+        }
+    }
+    if ui.button(tr!("export-bytefield")).clicked() {
+        let result = bytefield(&app.registry, &protocol_id, ROW_BITS);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-bytefield-title"),
+                &format!("{}.tex", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-scapy")).clicked() {
+        let result = scapy_module(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-scapy-title"),
+                &format!("{}.py", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-binary-template")).clicked() {
+        let result = binary_template(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-binary-template-title"),
+                &format!("{}.bt", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-dbc")).clicked() {
+        let result = dbc_database(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-dbc-title"),
+                &format!("{}.dbc", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-fuzz")).clicked() {
+        let result = fuzz_target(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-fuzz-title"),
+                &format!("{}.rs", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-golden-bundle")).clicked() {
+        let result = golden_bundle(
+            &app.registry,
+            &app.script_engine,
+            &protocol_id,
+            &app.presets,
+        )
+        .and_then(|bundle| serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string()));
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-golden-bundle-title"),
+                &bundle_file_name(&protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-golden-harness")).clicked() {
+        let result = golden_harness(&app.registry, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-golden-harness-title"),
+                &format!("{}_golden.rs", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui
+        .button(tr!("export-negative-corpus"))
+        .on_hover_text(tr!("export-negative-corpus-hint"))
+        .clicked()
+    {
+        let result = malformed_packets(app, &protocol_id);
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-negative-corpus-title"),
+                &format!("{}.malformed.json", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui
+        .add_enabled(
+            !app.capture.rows.is_empty(),
+            egui::Button::new(tr!("export-capture-report")),
+        )
+        .on_hover_text(tr!("export-capture-report-hint"))
+        .clicked()
+    {
+        let content = capture_report(app);
+        app.pending_export = Some(PendingExport::new(
+            &tr!("export-capture-report-title"),
+            "capture-report.html",
+            content,
+        ));
+    }
+    if ui
+        .add_enabled(
+            !app.capture.rows.is_empty(),
+            egui::Button::new(tr!("export-capture-parquet")),
+        )
+        .on_hover_text(tr!("export-capture-parquet-hint"))
+        .clicked()
+    {
+        let result = decoded_parquet(&capture_packets(app));
+        if let Some(data) = app.report(result) {
+            let description = tr!(
+                "export-capture-parquet-description",
+                bytes = data.len(),
+                packets = app.capture.rows.len()
+            );
+            app.pending_export = Some(PendingExport::binary(
+                &tr!("export-capture-parquet-title"),
+                "capture.parquet",
+                description,
+                data,
+            ));
+        }
+    }
+    if ui.button(tr!("export-definitions")).clicked() {
+        let result = protocol_definitions(
+            &app.registry,
+            &protocol_id,
+            &app.script_library,
+            &app.presets,
+        );
+        if let Some(content) = app.report(result) {
+            app.pending_export = Some(PendingExport::new(
+                &tr!("export-definitions-title"),
+                &format!("{}.bitloom", protocol_id),
+                content,
+            ));
+        }
+    }
+    if ui.button(tr!("export-schema")).clicked() {
+        let content = serde_json::to_string_pretty(&project_schema()).unwrap_or_default();
+        app.pending_export = Some(PendingExport::new(
+            &tr!("export-schema-title"),
+            "bitloom.schema.json",
+            content,
+        ));
+    }
+
+    let mut clicked = None;
+    for (p, plugin) in app.plugins.iter().enumerate() {
+        for (e, exporter) in plugin.exporters.iter().enumerate() {
+            if ui.button(&exporter.hook.label).clicked() {
+                clicked = Some((p, e));
+            }
+        }
+    }
+    if let Some((p, e)) = clicked {
+        let plugin = &app.plugins[p];
+        let exporter = &plugin.exporters[e];
+        let output = app.console.call(
+            &mut app.registry,
+            &mut app.script_engine,
+            app.decoded.as_ref(),
+            &plugin.ast,
+            &exporter.hook.function,
+            vec![protocol_id.clone().into()],
+        );
+        let result = output.result.map_err(|e| {
+            tr!(
+                "export-plugin-failed",
+                exporter = exporter.hook.label.as_str(),
+                error = e
+            )
+        });
+        if let Some(content) = app.report(result) {
+            let exporter = &app.plugins[p].exporters[e];
+            app.pending_export = Some(PendingExport::new(
+                &exporter.hook.label,
+                &format!("{}.{}", protocol_id, exporter.extension),
+                content,
+            ));
+        }
+    }
+}
+
+/// The packets of the capture page, with their time since the first one
+fn capture_packets(app: &BitLoomApp) -> Vec<ReportPacket<'_>> {
+    let rows = &app.capture.rows;
+    let start = rows.first().map(|r| r.packet.timestamp).unwrap_or_default();
+    rows.iter()
+        .map(|row| ReportPacket {
+            time: row.packet.timestamp.saturating_sub(start).as_secs_f64(),
+            data: &row.packet.data,
+            decoded: row.decoded.as_ref(),
+        })
+        .collect()
+}
+
+/// The packets of the capture page summarized as an HTML page
+fn capture_report(app: &BitLoomApp) -> String {
+    let packets = capture_packets(app);
+    let title = match &app.capture.protocol {
+        Some(protocol_id) => tr!(
+            "export-capture-report-decoded",
+            protocol = protocol_id.as_str()
+        ),
+        None => tr!("export-capture-report-capture"),
+    };
+    html_report(&title, &summarize_capture(&app.registry, &packets))
+}
+
+/// Malformed packets made from the values entered in the packet builder, with defaults for the
+/// fields that have none
+fn malformed_packets(app: &BitLoomApp, protocol_id: &str) -> Result<String, String> {
+    let mut values = default_values(&app.registry, protocol_id)?;
+    for (id, value) in values.iter_mut() {
+        if let Some(Ok(entered)) = app.builder.inputs.get(id).map(|t| Value::parse_literal(t)) {
+            *value = entered;
+        }
+    }
+    let corpus = negative_corpus(&app.registry, &app.script_engine, protocol_id, &values)?;
+    serde_json::to_string_pretty(&corpus).map_err(|e| e.to_string())
+}
+
+/// Menu actions registered by plugins
+fn plugins_menu(app: &mut BitLoomApp, ui: &mut egui::Ui) {
+    if app.plugins.is_empty() {
+        ui.label(tr!("menu-no-plugins", dir = PLUGIN_DIR));
+    }
+
+    let mut clicked = None;
+    for (p, plugin) in app.plugins.iter().enumerate() {
+        ui.menu_button(&plugin.name, |ui| {
+            if plugin.actions.is_empty() {
+                ui.label(tr!("menu-no-actions"));
+            }
+            for (a, action) in plugin.actions.iter().enumerate() {
+                if ui.button(&action.label).clicked() {
+                    clicked = Some((p, a));
+                }
+            }
+        });
+    }
+    if let Some((p, a)) = clicked {
+        app.run_plugin_action(p, a);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::app::BitLoomApp;
+use crate::ui::delete_dialog;
+use bitloom::models::history::format_timestamp;
+use bitloom::tr;
+use eframe::egui;
+
+/// Protocols deleted this session, newest first, with a button to restore each
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_trash;
+    egui::Window::new(tr!("trash-title"))
+        .id(egui::Id::new("trash"))
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            if app.trash.is_empty() {
+                ui.label(tr!("trash-none"));
+                return;
+            }
+
+            let mut restore = None;
+            let mut discard = None;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (i, deletion) in app.trash.deletions().iter().enumerate().rev() {
+                    ui.horizontal(|ui| {
+                        ui.strong(deletion.protocol_id());
+                        ui.weak(format_timestamp(deletion.timestamp));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui
+                                .small_button("✖")
+                                .on_hover_text(tr!("trash-discard"))
+                                .clicked()
+                            {
+                                discard = Some(i);
+                            }
+                            if ui.small_button(tr!("trash-restore")).clicked() {
+                                restore = Some(i);
+                            }
+                        });
+                    });
+                    let subprotocols = deletion.protocols.len().saturating_sub(1);
+                    if subprotocols > 0 || !deletion.presets.is_empty() {
+                        ui.label(tr!(
+                            "trash-with",
+                            subprotocols = subprotocols,
+                            presets = deletion.presets.len()
+                        ));
+                    }
+                    ui.separator();
+                }
+            });
+
+            if ui.button(tr!("trash-empty-button")).clicked() {
+                app.trash.empty();
+            }
+            if let Some(i) = restore {
+                delete_dialog::restore(app, i);
+            }
+            if let Some(i) = discard {
+                app.trash.discard(i);
+            }
+        });
+    app.show_trash = open;
+}
@@ -0,0 +1,68 @@
+use crate::app::BitLoomApp;
+use bitloom::models::protocol::FieldReference;
+use bitloom::tr;
+use eframe::egui;
+
+/// Lists everything that refers to the selected field
+pub fn show(app: &mut BitLoomApp, ctx: &egui::Context) {
+    let mut open = app.show_where_used;
+    egui::Window::new(tr!("where-used-title"))
+        .id(egui::Id::new("where_used"))
+        .open(&mut open)
+        .default_width(280.0)
+        .show(ctx, |ui| {
+            let (Some(protocol_id), Some(field_id)) = (&app.selected_protocol, &app.selected_field)
+            else {
+                ui.label(tr!("where-used-select-field"));
+                return;
+            };
+
+            ui.label(tr!(
+                "where-used-references",
+                protocol = protocol_id.as_str(),
+                field = field_id.as_str()
+            ));
+            ui.separator();
+
+            let references = app.registry.get_field_references(protocol_id, field_id);
+            if references.is_empty() {
+                ui.label(tr!("where-used-none"));
+            }
+
+            for reference in references {
+                match reference {
+                    FieldReference::ParentConstraint { protocol_id, value } => {
+                        ui.label(tr!(
+                            "where-used-constraint",
+                            protocol = protocol_id,
+                            value = value.to_string()
+                        ));
+                    }
+                    FieldReference::Expression {
+                        protocol_id,
+                        field_id,
+                    } => {
+                        ui.label(tr!(
+                            "where-used-expression",
+                            protocol = protocol_id,
+                            field = field_id
+                        ));
+                    }
+                    FieldReference::Validator { protocol_id, name } => {
+                        ui.label(tr!(
+                            "where-used-validator",
+                            validator = name,
+                            protocol = protocol_id
+                        ));
+                    }
+                    FieldReference::LengthField { protocol_id } => {
+                        ui.label(tr!("where-used-length-field", protocol = protocol_id));
+                    }
+                    FieldReference::SequenceField { protocol_id } => {
+                        ui.label(tr!("where-used-sequence-field", protocol = protocol_id));
+                    }
+                }
+            }
+        });
+    app.show_where_used = open;
+}
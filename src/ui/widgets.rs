@@ -0,0 +1,287 @@
+use bitloom::models::field::{DisplayFormat, FieldType, SignEncoding, parse_int};
+use bitloom::models::protocol::{Endianness, Severity};
+use bitloom::tr;
+use bitloom::transport::TransportConfig;
+use bitloom::transport::serial::available_ports;
+use eframe::egui;
+use std::hash::Hash;
+
+/// Single line text input for an integer value in any notation `parse_int` reads.
+/// The raw text is kept while it is being edited or does not parse, so that
+/// intermediate input like "-" is not lost. Returns whether the text is valid.
+pub fn int_input(ui: &mut egui::Ui, id_salt: impl Hash, value: &mut i128) -> bool {
+    let id = ui.make_persistent_id(id_salt);
+    let mut text = ui
+        .data_mut(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| value.to_string());
+
+    let parsed = parse_int(&text);
+    let mut edit = egui::TextEdit::singleline(&mut text).desired_width(80.0);
+    if parsed.is_err() {
+        edit = edit.text_color(ui.visuals().error_fg_color);
+    }
+    let response = ui.add(edit);
+
+    let parsed = parse_int(&text);
+    let has_focus = response.has_focus();
+    match &parsed {
+        Ok(v) => {
+            *value = *v;
+            if text.trim() != v.to_string() {
+                response.on_hover_text(int_notations(*v));
+            }
+        }
+        Err(e) => {
+            response.on_hover_text(e);
+        }
+    }
+
+    if has_focus || parsed.is_err() {
+        ui.data_mut(|d| d.insert_temp(id, text));
+    } else {
+        ui.data_mut(|d| d.remove::<String>(id));
+    }
+    parsed.is_ok()
+}
+
+/// An integer in decimal, hex and binary, for showing what a typed value was read as
+pub fn int_notations(value: i128) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let abs = value.unsigned_abs();
+    let mut text = format!("= {} = {}0x{:X} = {}0b{:b}", value, sign, abs, sign, abs);
+    if let Some(c) = u8::try_from(value)
+        .ok()
+        .filter(|b| b.is_ascii_graphic() || *b == b' ')
+    {
+        text.push_str(&format!(" = '{}'", c as char));
+    }
+    text
+}
+
+/// Text input for an optional string, where empty text means `None`
+pub fn optional_text(ui: &mut egui::Ui, value: &mut Option<String>, multiline: bool) {
+    let mut text = value.clone().unwrap_or_default();
+    let changed = if multiline {
+        ui.text_edit_multiline(&mut text).changed()
+    } else {
+        ui.text_edit_singleline(&mut text).changed()
+    };
+    if changed {
+        *value = (!text.is_empty()).then_some(text);
+    }
+}
+
+/// Color picker for an optional color, where `None` means `default` is used
+pub fn optional_color(ui: &mut egui::Ui, value: &mut Option<[u8; 3]>, default: egui::Color32) {
+    ui.horizontal(|ui| {
+        let mut custom = value.is_some();
+        ui.checkbox(&mut custom, tr!("widgets-custom"));
+        match (custom, value) {
+            (true, Some(color)) => {
+                ui.color_edit_button_srgb(color);
+            }
+            (true, value @ None) => {
+                let [r, g, b, _] = default.to_array();
+                *value = Some([r, g, b]);
+            }
+            (false, value) => {
+                *value = None;
+                color_swatch(ui, default);
+            }
+        }
+    });
+}
+
+/// Combo box choosing how values are displayed
+pub fn display_format_picker(ui: &mut egui::Ui, id_salt: impl Hash, format: &mut DisplayFormat) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(display_format_label(*format))
+        .show_ui(ui, |ui| {
+            for option in DisplayFormat::ALL {
+                ui.selectable_value(format, option, display_format_label(option));
+            }
+        });
+}
+
+/// Name of a kind of field, for display
+pub fn field_type_label(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Fixed(_) => tr!("field-type-fixed"),
+        FieldType::Enum(_) => tr!("field-type-enum"),
+        FieldType::Range { .. } => tr!("field-type-range"),
+        FieldType::Expr(_) => tr!("field-type-expr"),
+        FieldType::Derived(_) => tr!("field-type-derived"),
+        FieldType::Input => tr!("field-type-input"),
+    }
+}
+
+/// Name of a display format, for choosing it
+pub fn display_format_label(format: DisplayFormat) -> String {
+    match format {
+        DisplayFormat::Decimal => tr!("display-decimal"),
+        DisplayFormat::Hex => tr!("display-hex"),
+        DisplayFormat::Binary => tr!("display-binary"),
+        DisplayFormat::Octal => tr!("display-octal"),
+        DisplayFormat::Ascii => tr!("display-ascii"),
+    }
+}
+
+/// Name of a sign encoding, for choosing it
+pub fn sign_encoding_label(encoding: SignEncoding) -> String {
+    match encoding {
+        SignEncoding::TwosComplement => tr!("sign-twos-complement"),
+        SignEncoding::SignMagnitude => tr!("sign-magnitude"),
+    }
+}
+
+/// Name of a kind of transport, for choosing it
+pub fn transport_label(transport: &TransportConfig) -> String {
+    match transport {
+        TransportConfig::Udp { .. } => tr!("transport-udp"),
+        TransportConfig::Serial { .. } => tr!("transport-serial"),
+        TransportConfig::Tcp { .. } => tr!("transport-tcp"),
+        TransportConfig::SocketCan { .. } => tr!("transport-socketcan"),
+    }
+}
+
+/// Name of a byte order, for choosing it
+pub fn endianness_label(endianness: Endianness) -> String {
+    match endianness {
+        Endianness::Big => tr!("endianness-big"),
+        Endianness::Little => tr!("endianness-little"),
+    }
+}
+
+/// Name of a severity, for choosing it
+pub fn severity_label(severity: Severity) -> String {
+    match severity {
+        Severity::Error => tr!("severity-error"),
+        Severity::Warning => tr!("severity-warning"),
+    }
+}
+
+/// Small square filled with a color
+pub fn color_swatch(ui: &mut egui::Ui, color: egui::Color32) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, color);
+    response
+}
+
+/// A key pressed on the focused row of a list
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RowKey {
+    Previous,
+    Next,
+    Open,
+}
+
+/// The arrow keys and Enter on a row of a list, e.g. a selectable label, while it has keyboard
+/// focus, with the modifiers held. The up and down arrows are kept from moving the focus, so
+/// that the list can move it along with its selection.
+pub fn row_key(ui: &egui::Ui, response: &egui::Response) -> Option<(RowKey, egui::Modifiers)> {
+    if !response.has_focus() {
+        return None;
+    }
+    let filter = egui::EventFilter {
+        vertical_arrows: true,
+        ..Default::default()
+    };
+    ui.memory_mut(|m| m.set_focus_lock_filter(response.id, filter));
+    ui.input(|i| {
+        let key = if i.key_pressed(egui::Key::ArrowUp) {
+            RowKey::Previous
+        } else if i.key_pressed(egui::Key::ArrowDown) {
+            RowKey::Next
+        } else if i.key_pressed(egui::Key::Enter) {
+            RowKey::Open
+        } else {
+            return None;
+        };
+        Some((key, i.modifiers))
+    })
+}
+
+/// Give a selectable row the name a screen reader reads for it, instead of its visible text
+pub fn name_row(response: &egui::Response, selected: bool, name: &str) {
+    response.widget_info(|| {
+        egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, true, selected, name)
+    });
+}
+
+/// Give a button showing a symbol the name a screen reader reads for it
+pub fn name_button(response: &egui::Response, name: &str) {
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, true, name));
+}
+
+/// Grid rows choosing a transport and its settings. The UDP remote address is only shown
+/// with a label for where packets are sent, for uses that send.
+pub fn transport_settings(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    transport: &mut TransportConfig,
+    remote_label: Option<&str>,
+) {
+    ui.label(tr!("transport"));
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(transport_label(transport))
+        .show_ui(ui, |ui| {
+            let options = [
+                TransportConfig::Udp {
+                    bind: "127.0.0.1:5000".to_string(),
+                    remote: String::new(),
+                },
+                TransportConfig::Serial {
+                    port: available_ports().into_iter().next().unwrap_or_default(),
+                    baud_rate: 115_200,
+                },
+                TransportConfig::Tcp {
+                    remote: "127.0.0.1:5000".to_string(),
+                },
+                TransportConfig::SocketCan {
+                    interface: "vcan0".to_string(),
+                },
+            ];
+            for option in options {
+                let selected = option.kind_name() == transport.kind_name();
+                if ui
+                    .selectable_label(selected, transport_label(&option))
+                    .clicked()
+                    && !selected
+                {
+                    *transport = option;
+                }
+            }
+        });
+    ui.end_row();
+
+    match transport {
+        TransportConfig::Udp { bind, remote } => {
+            ui.label(tr!("transport-listen-on"));
+            ui.text_edit_singleline(bind);
+            ui.end_row();
+            if let Some(label) = remote_label {
+                ui.label(label);
+                ui.add(egui::TextEdit::singleline(remote).hint_text(tr!("transport-remote-hint")));
+                ui.end_row();
+            }
+        }
+        TransportConfig::Serial { port, baud_rate } => {
+            ui.label(tr!("transport-port"));
+            ui.text_edit_singleline(port);
+            ui.end_row();
+            ui.label(tr!("transport-baud-rate"));
+            ui.add(egui::DragValue::new(baud_rate).range(1..=10_000_000));
+            ui.end_row();
+        }
+        TransportConfig::Tcp { remote } => {
+            ui.label(tr!("transport-connect-to"));
+            ui.text_edit_singleline(remote);
+            ui.end_row();
+        }
+        TransportConfig::SocketCan { interface } => {
+            ui.label(tr!("transport-interface"));
+            ui.text_edit_singleline(interface);
+            ui.end_row();
+        }
+    }
+}